@@ -44,6 +44,33 @@ impl Value {
             Self::Null => "<null>".to_string(),
         }
     }
+
+    /// Converts self into a JSON value, replacing any invalid UTF-8 sequences
+    /// in `Bytes` with the Unicode replacement character.
+    ///
+    /// Unlike the fallible `TryInto` conversion, this never fails, which makes
+    /// it useful to compare the output of a program against a JSON fixture.
+    pub fn into_json_lossy(self) -> serde_json::Value {
+        match self {
+            Value::Bytes(v) => String::from_utf8_lossy(&v).into_owned().into(),
+            Value::Regex(regex) => regex.as_str().into(),
+            Value::Timestamp(v) => timestamp_to_string(&v).into(),
+            Value::Integer(v) => v.into(),
+            Value::Float(v) => v.into_inner().into(),
+            Value::Boolean(v) => v.into(),
+            Value::Object(v) => v
+                .into_iter()
+                .map(|(k, v)| (k, v.into_json_lossy()))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+            Value::Array(v) => v
+                .into_iter()
+                .map(Value::into_json_lossy)
+                .collect::<Vec<_>>()
+                .into(),
+            Self::Null => serde_json::Value::Null,
+        }
+    }
 }
 
 impl Serialize for Value {
@@ -210,10 +237,14 @@ impl TryInto<serde_json::Value> for Value {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
     use std::fs;
     use std::io::Read;
     use std::path::Path;
 
+    use bytes::Bytes;
+    use ordered_float::NotNan;
+
     use crate::value::Value;
 
     pub fn parse_artifact(path: impl AsRef<Path>) -> std::io::Result<Vec<u8>> {
@@ -282,4 +313,28 @@ mod test {
                 _ => panic!("This test should never read Err'ing type folders."),
             });
     }
+
+    #[test]
+    fn into_json_lossy() {
+        let value = Value::Object(BTreeMap::from([
+            (
+                "bytes".to_owned(),
+                Value::Bytes(Bytes::from_static(b"foo\xffbar")),
+            ),
+            (
+                "array".to_owned(),
+                Value::Array(vec![Value::Integer(1), Value::Null]),
+            ),
+            ("float".to_owned(), Value::Float(NotNan::new(1.5).unwrap())),
+        ]));
+
+        assert_eq!(
+            value.into_json_lossy(),
+            serde_json::json!({
+                "bytes": "foo\u{fffd}bar",
+                "array": [1, null],
+                "float": 1.5,
+            })
+        );
+    }
 }
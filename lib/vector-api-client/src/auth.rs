@@ -0,0 +1,20 @@
+/// Credentials to authenticate with a Vector API server that requires them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Auth {
+    /// Basic authentication, with a user and password.
+    Basic { user: String, password: String },
+    /// Bearer authentication, with a token.
+    Bearer { token: String },
+}
+
+impl Auth {
+    /// Returns the value of the `Authorization` header that carries these credentials.
+    pub fn header_value(&self) -> String {
+        match self {
+            Self::Basic { user, password } => {
+                format!("Basic {}", base64::encode(format!("{}:{}", user, password)))
+            }
+            Self::Bearer { token } => format!("Bearer {}", token),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use anyhow::Context;
 use graphql_client::GraphQLQuery;
 use indoc::indoc;
+use reqwest::{header::AUTHORIZATION, StatusCode};
 use url::Url;
 
+use crate::{Auth, CaCertificate};
+
 /// Wrapped `Result` type, that returns deserialized GraphQL response data.
 pub type QueryResult<T> =
     anyhow::Result<graphql_client::Response<<T as GraphQLQuery>::ResponseData>>;
@@ -11,25 +14,65 @@ pub type QueryResult<T> =
 #[derive(Debug)]
 pub struct Client {
     url: Url,
+    auth: Option<Auth>,
+    ca_certificate: Option<CaCertificate>,
 }
 
 impl Client {
     /// Returns a new GraphQL query client, bound to the provided URL.
     pub fn new(url: Url) -> Self {
-        Self { url }
+        Self {
+            url,
+            auth: None,
+            ca_certificate: None,
+        }
+    }
+
+    /// Sets the credentials sent with each query, for API servers that require them.
+    pub fn with_auth(mut self, auth: Option<Auth>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Sets an additional CA to trust, for API servers with a certificate issued by a private CA.
+    pub fn with_ca_certificate(mut self, ca_certificate: Option<CaCertificate>) -> Self {
+        self.ca_certificate = ca_certificate;
+        self
     }
 
-    pub async fn new_with_healthcheck(url: Url) -> Option<Self> {
+    pub async fn new_with_healthcheck(
+        url: Url,
+        auth: Option<Auth>,
+        ca_certificate: Option<CaCertificate>,
+    ) -> Option<Self> {
         #![allow(clippy::print_stderr)]
 
         use crate::gql::HealthQueryExt;
 
         // Create a new API client for connecting to the local/remote Vector instance.
-        let client = Self::new(url.clone());
+        let client = Self::new(url.clone())
+            .with_auth(auth)
+            .with_ca_certificate(ca_certificate);
 
         // Check that the GraphQL server is reachable
         match client.health_query().await {
             Ok(_) => Some(client),
+            Err(error)
+                if error
+                    .downcast_ref::<reqwest::Error>()
+                    .and_then(reqwest::Error::status)
+                    == Some(StatusCode::UNAUTHORIZED) =>
+            {
+                eprintln!(
+                    indoc! {"
+                    Vector API server ({}) rejected the credentials.
+
+                    Pass the credentials that the `auth` option of the `api` config requires,
+                    with either `--token`, or `--user` and `--password`."},
+                    url
+                );
+                None
+            }
             _ => {
                 eprintln!(
                     indoc! {"
@@ -54,11 +97,18 @@ impl Client {
         &self,
         request_body: &graphql_client::QueryBody<T::Variables>,
     ) -> QueryResult<T> {
-        let client = reqwest::Client::new();
+        let mut client = reqwest::Client::builder();
+        if let Some(ca_certificate) = &self.ca_certificate {
+            client = client.add_root_certificate(ca_certificate.reqwest_certificate());
+        }
+        let client = client.build().context("Couldn't build the HTTP client")?;
 
-        client
-            .post(self.url.clone())
-            .json(request_body)
+        let mut request = client.post(self.url.clone()).json(request_body);
+        if let Some(auth) = &self.auth {
+            request = request.header(AUTHORIZATION, auth.header_value());
+        }
+
+        request
             .send()
             .await
             .with_context(|| {
@@ -68,6 +118,14 @@ impl Client {
                     &self.url.as_str()
                 )
             })?
+            .error_for_status()
+            .with_context(|| {
+                format!(
+                    "'{}' query was rejected by {}",
+                    request_body.operation_name,
+                    &self.url.as_str()
+                )
+            })?
             .json()
             .await
             .with_context(|| {
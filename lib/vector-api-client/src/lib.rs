@@ -12,11 +12,15 @@
 #![deny(warnings)]
 #![deny(missing_debug_implementations, missing_copy_implementations)]
 
+mod auth;
 mod client;
 /// GraphQL queries
 pub mod gql;
 mod subscription;
 pub mod test;
+mod tls;
 
+pub use auth::*;
 pub use client::*;
 pub use subscription::*;
+pub use tls::*;
@@ -13,10 +13,20 @@ use tokio::sync::{
     mpsc, oneshot,
 };
 use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::{
+    connect_async_tls_with_config,
+    tungstenite::{
+        client::IntoClientRequest,
+        http::{header::AUTHORIZATION, HeaderValue},
+        Message,
+    },
+    Connector,
+};
 use url::Url;
 use uuid::Uuid;
 
+use crate::{Auth, CaCertificate};
+
 /// Subscription GraphQL response, returned from an active stream.
 pub type BoxedSubscription<T> = Pin<
     Box<
@@ -172,11 +182,41 @@ impl SubscriptionClient {
 
 /// Connect to a new WebSocket GraphQL server endpoint, and return a `SubscriptionClient`.
 /// This method will a) connect to a ws(s):// endpoint, and perform the initial handshake, and b)
-/// set up channel forwarding to expose just the returned `Payload`s to the client.
+/// set up channel forwarding to expose just the returned `Payload`s to the client. The given
+/// credentials, if any, are sent with the handshake, and the given CA, if any, is trusted when
+/// connecting to a wss:// endpoint.
 pub async fn connect_subscription_client(
     url: Url,
+    auth: Option<&Auth>,
+    ca_certificate: Option<&CaCertificate>,
 ) -> Result<SubscriptionClient, tokio_tungstenite::tungstenite::Error> {
-    let (ws, _) = connect_async(url).await?;
+    let mut request = url.into_client_request()?;
+    if let Some(auth) = auth {
+        let value = HeaderValue::from_str(&auth.header_value())
+            .map_err(tokio_tungstenite::tungstenite::http::Error::from)?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+
+    let connector = match ca_certificate {
+        Some(ca_certificate) => {
+            let connector = ca_certificate
+                .native_tls_certificate()
+                .and_then(|certificate| {
+                    native_tls::TlsConnector::builder()
+                        .add_root_certificate(certificate)
+                        .build()
+                })
+                .map_err(|error| {
+                    tokio_tungstenite::tungstenite::Error::Tls(
+                        tokio_tungstenite::tungstenite::error::TlsError::Native(error),
+                    )
+                })?;
+            Some(Connector::NativeTls(connector))
+        }
+        None => None,
+    };
+
+    let (ws, _) = connect_async_tls_with_config(request, None, connector).await?;
     let (mut ws_tx, mut ws_rx) = futures::StreamExt::split(ws);
 
     let (send_tx, mut send_rx) = mpsc::unbounded_channel::<Payload>();
@@ -0,0 +1,36 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+
+/// A certificate authority to trust when connecting to a Vector API server over TLS, in addition
+/// to the ones the system trusts, such as a private CA that issued the server's certificate.
+#[derive(Clone, Debug)]
+pub struct CaCertificate {
+    pem: Vec<u8>,
+}
+
+impl CaCertificate {
+    /// Reads a PEM-encoded CA certificate from the file at the given path.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let pem = fs::read(path).with_context(|| {
+            format!(
+                "Couldn't read CA certificate file {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        // Check that the certificate is valid up front, rather than on each connection.
+        reqwest::Certificate::from_pem(&pem)
+            .with_context(|| format!("Invalid CA certificate file {}", path.to_string_lossy()))?;
+
+        Ok(Self { pem })
+    }
+
+    pub(crate) fn reqwest_certificate(&self) -> reqwest::Certificate {
+        reqwest::Certificate::from_pem(&self.pem).expect("checked when loaded")
+    }
+
+    pub(crate) fn native_tls_certificate(&self) -> native_tls::Result<native_tls::Certificate> {
+        native_tls::Certificate::from_pem(&self.pem)
+    }
+}
@@ -10,7 +10,7 @@ use criterion::{
     Criterion, SamplingMode, Throughput,
 };
 use tokio::runtime::{Handle, Runtime};
-use vector_buffers::{BufferType, WhenFull};
+use vector_buffers::{BufferType, DiskBufferCompression, WhenFull};
 
 use crate::common::{init_instrumentation, war_measurement, wtr_measurement};
 
@@ -83,12 +83,17 @@ fn create_disk_v2_variant(_max_events: usize, max_size: u64) -> BufferType {
     BufferType::DiskV2 {
         max_size: NonZeroU64::new(max_size).unwrap(),
         when_full: WhenFull::DropNewest,
+        compression: DiskBufferCompression::None,
+        encryption: None,
+        retention: None,
+        shared: None,
     }
 }
 
 fn create_in_memory_variant(max_events: usize, _max_size: u64) -> BufferType {
     BufferType::Memory {
-        max_events: NonZeroUsize::new(max_events).unwrap(),
+        max_events: Some(NonZeroUsize::new(max_events).unwrap()),
+        max_size: None,
         when_full: WhenFull::DropNewest,
     }
 }
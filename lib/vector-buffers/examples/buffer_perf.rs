@@ -21,7 +21,7 @@ use vector_buffers::{
         builder::TopologyBuilder,
         channel::{BufferReceiver, BufferSender},
     },
-    Acker, BufferType, Bufferable, EventCount, WhenFull,
+    Acker, BufferType, Bufferable, DiskBufferCompression, EventCount, WhenFull,
 };
 use vector_common::byte_size_of::ByteSizeOf;
 
@@ -241,7 +241,8 @@ where
                 max_size_events
             );
             BufferType::Memory {
-                max_events: max_size_events,
+                max_events: Some(max_size_events),
+                max_size: None,
                 when_full,
             }
         }
@@ -263,6 +264,10 @@ where
             BufferType::DiskV2 {
                 max_size: max_size_bytes,
                 when_full,
+                compression: DiskBufferCompression::None,
+                encryption: None,
+                retention: None,
+                shared: None,
             }
         }
         s => panic!(
@@ -11,7 +11,10 @@ use tracing::{Instrument, Span};
 use vector_common::internal_event::emit;
 
 use crate::{
-    internal_events::{BufferCreated, BufferEventsDropped, BufferEventsReceived, BufferEventsSent},
+    internal_events::{
+        BufferCreated, BufferEventsDropped, BufferEventsReceived, BufferEventsSent,
+        BufferLogicalBytes,
+    },
     spawn_named,
 };
 
@@ -127,6 +130,25 @@ impl BufferUsageHandle {
         self.state.sent.increment(count, byte_size);
     }
 
+    /// Increments the logical size of the events received by this buffer component.
+    ///
+    /// For buffers that store events in a compressed form, the byte size tracked alongside the received events is the
+    /// size of the events as stored, while the logical size is the size of the events before compression.
+    pub fn increment_received_logical_byte_size(&self, byte_size: u64) {
+        self.state
+            .received_logical_byte_size
+            .fetch_add(byte_size, Ordering::Relaxed);
+    }
+
+    /// Increments the logical size of the events sent by this buffer component.
+    ///
+    /// See [`increment_received_logical_byte_size`] for the difference between the byte size and the logical size.
+    pub fn increment_sent_logical_byte_size(&self, byte_size: u64) {
+        self.state
+            .sent_logical_byte_size
+            .fetch_add(byte_size, Ordering::Relaxed);
+    }
+
     /// Increment the number of dropped events (and their total size) for this buffer component.
     pub fn increment_dropped_event_count_and_byte_size(
         &self,
@@ -150,6 +172,8 @@ struct BufferUsageData {
     dropped: CategoryMetrics,
     dropped_intentional: CategoryMetrics,
     max_size: CategoryMetrics,
+    received_logical_byte_size: AtomicU64,
+    sent_logical_byte_size: AtomicU64,
 }
 
 impl BufferUsageData {
@@ -181,6 +205,8 @@ impl BufferUsageData {
                 .event_count
                 .try_into()
                 .expect("should never be bigger than `usize`"),
+            received_logical_byte_size: self.received_logical_byte_size.load(Ordering::Acquire),
+            sent_logical_byte_size: self.sent_logical_byte_size.load(Ordering::Acquire),
         }
     }
 }
@@ -198,6 +224,8 @@ pub struct BufferUsageSnapshot {
     pub dropped_event_byte_size_intentional: u64,
     pub max_size_bytes: u64,
     pub max_size_events: usize,
+    pub received_logical_byte_size: u64,
+    pub sent_logical_byte_size: u64,
 }
 
 /// Builder for tracking buffer usage metrics.
@@ -279,6 +307,18 @@ impl BufferUsage {
                         });
                     }
 
+                    let received_logical_byte_size =
+                        stage.received_logical_byte_size.swap(0, Ordering::AcqRel);
+                    let sent_logical_byte_size =
+                        stage.sent_logical_byte_size.swap(0, Ordering::AcqRel);
+                    if received_logical_byte_size > 0 || sent_logical_byte_size > 0 {
+                        emit(BufferLogicalBytes {
+                            idx: stage.idx,
+                            received_byte_size: received_logical_byte_size,
+                            sent_byte_size: sent_logical_byte_size,
+                        });
+                    }
+
                     let dropped = stage.dropped.consume();
                     if dropped.has_updates() {
                         emit(BufferEventsDropped {
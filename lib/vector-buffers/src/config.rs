@@ -1,11 +1,12 @@
 use std::{
-    fmt,
+    fmt, fs,
     num::{NonZeroU64, NonZeroUsize},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+use ring::digest;
 use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
-use snafu::{ResultExt, Snafu};
+use snafu::Snafu;
 use tracing::Span;
 
 use crate::{
@@ -13,7 +14,10 @@ use crate::{
         builder::{TopologyBuilder, TopologyError},
         channel::{BufferReceiver, BufferSender},
     },
-    variants::{DiskV1Buffer, DiskV2Buffer, MemoryBuffer},
+    variants::{
+        disk_v2, DiskBufferCompression, DiskBufferEncryptionKey, DiskBufferInspector,
+        DiskBufferRetention, DiskV1Buffer, DiskV2Buffer, InspectError, MemoryBuffer,
+    },
     Acker, Bufferable, WhenFull,
 };
 
@@ -25,6 +29,85 @@ pub enum BufferBuildError {
     FailedToBuildTopology { source: TopologyError },
     #[snafu(display("`max_events` must be greater than zero"))]
     InvalidMaxEvents,
+    #[snafu(display("invalid disk buffer encryption key: {}", reason))]
+    InvalidEncryptionKey { reason: String },
+    #[snafu(display("failed to open encrypted disk buffer: {}", source))]
+    FailedToDecrypt { source: TopologyError },
+    #[snafu(display("failed to open disk buffer: {}", source))]
+    FailedToInspect { source: InspectError },
+}
+
+/// Encryption of a disk buffer.
+///
+/// The key is a 256-bit key, encoded as base64, and is given either directly, which allows it to be
+/// loaded from a secret backend, or as the path of a file that contains it.
+///
+/// A key given directly is never serialized as is, but only as a digest of it.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiskBufferEncryption {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_redacted_key"
+    )]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+}
+
+impl DiskBufferEncryption {
+    /// Loads the configured encryption key.
+    ///
+    /// # Errors
+    ///
+    /// If neither or both of `key` and `key_file` are set, if the key file cannot be read, or if the
+    /// key is not a valid base64-encoded 256-bit key, an error variant will be returned describing
+    /// the error.
+    pub fn load_key(&self) -> Result<DiskBufferEncryptionKey, BufferBuildError> {
+        let key = match (&self.key, &self.key_file) {
+            (Some(key), None) => DiskBufferEncryptionKey::from_base64(key),
+            (None, Some(key_file)) => fs::read_to_string(key_file)
+                .map_err(|e| {
+                    format!(
+                        "failed to read key file '{}': {}",
+                        key_file.to_string_lossy(),
+                        e
+                    )
+                })
+                .and_then(|key| DiskBufferEncryptionKey::from_base64(&key)),
+            (None, None) => Err("one of `key` or `key_file` must be set".to_string()),
+            (Some(_), Some(_)) => Err("only one of `key` or `key_file` can be set".to_string()),
+        };
+
+        key.map_err(|reason| BufferBuildError::InvalidEncryptionKey { reason })
+    }
+}
+
+/// Serializes the encryption key as a digest of it.
+///
+/// Configurations are compared by their serialized form when reloading, so the key can't be left out
+/// entirely, or changing the key alone wouldn't rebuild the buffer.
+fn serialize_redacted_key<S>(key: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let digest = key
+        .as_ref()
+        .map(|key| digest::digest(&digest::SHA256, key.as_bytes()));
+    let redacted = digest.map(|digest| format!("**REDACTED** (sha256:{})", base64::encode(digest)));
+
+    redacted.serialize(serializer)
+}
+
+impl fmt::Debug for DiskBufferEncryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the key itself.
+        f.debug_struct("DiskBufferEncryption")
+            .field("key", &self.key.as_ref().map(|_| "**REDACTED**"))
+            .field("key_file", &self.key_file)
+            .finish()
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -37,7 +120,27 @@ enum BufferTypeKind {
     DiskV2,
 }
 
-const ALL_FIELDS: [&str; 4] = ["type", "max_events", "max_size", "when_full"];
+const ALL_FIELDS: [&str; 8] = [
+    "type",
+    "max_events",
+    "max_size",
+    "when_full",
+    "compression",
+    "encryption",
+    "retention",
+    "shared",
+];
+const MEMORY_FIELDS: [&str; 4] = ["type", "max_events", "max_size", "when_full"];
+const DISK_V1_FIELDS: [&str; 3] = ["type", "max_size", "when_full"];
+const DISK_V2_FIELDS: [&str; 7] = [
+    "type",
+    "max_size",
+    "when_full",
+    "compression",
+    "encryption",
+    "retention",
+    "shared",
+];
 
 struct BufferTypeVisitor;
 
@@ -50,6 +153,10 @@ impl BufferTypeVisitor {
         let mut max_events: Option<NonZeroUsize> = None;
         let mut max_size: Option<NonZeroU64> = None;
         let mut when_full: Option<WhenFull> = None;
+        let mut compression: Option<DiskBufferCompression> = None;
+        let mut encryption: Option<DiskBufferEncryption> = None;
+        let mut retention: Option<DiskBufferRetention> = None;
+        let mut shared: Option<String> = None;
         while let Some(key) = map.next_key::<String>()? {
             match key.as_str() {
                 "type" => {
@@ -76,6 +183,30 @@ impl BufferTypeVisitor {
                     }
                     when_full = Some(map.next_value()?);
                 }
+                "compression" => {
+                    if compression.is_some() {
+                        return Err(de::Error::duplicate_field("compression"));
+                    }
+                    compression = Some(map.next_value()?);
+                }
+                "encryption" => {
+                    if encryption.is_some() {
+                        return Err(de::Error::duplicate_field("encryption"));
+                    }
+                    encryption = Some(map.next_value()?);
+                }
+                "retention" => {
+                    if retention.is_some() {
+                        return Err(de::Error::duplicate_field("retention"));
+                    }
+                    retention = Some(map.next_value()?);
+                }
+                "shared" => {
+                    if shared.is_some() {
+                        return Err(de::Error::duplicate_field("shared"));
+                    }
+                    shared = Some(map.next_value()?);
+                }
                 other => {
                     return Err(de::Error::unknown_field(other, &ALL_FIELDS));
                 }
@@ -85,23 +216,44 @@ impl BufferTypeVisitor {
         let when_full = when_full.unwrap_or_default();
         match kind {
             BufferTypeKind::Memory => {
-                if max_size.is_some() {
-                    return Err(de::Error::unknown_field(
-                        "max_size",
-                        &["type", "max_events", "when_full"],
-                    ));
+                if compression.is_some() {
+                    return Err(de::Error::unknown_field("compression", &MEMORY_FIELDS));
                 }
+                if encryption.is_some() {
+                    return Err(de::Error::unknown_field("encryption", &MEMORY_FIELDS));
+                }
+                if retention.is_some() {
+                    return Err(de::Error::unknown_field("retention", &MEMORY_FIELDS));
+                }
+                if shared.is_some() {
+                    return Err(de::Error::unknown_field("shared", &MEMORY_FIELDS));
+                }
+                // Without a byte limit, the buffer has to be bound by the number of events.
+                let max_events = match (max_events, max_size) {
+                    (None, None) => Some(memory_buffer_default_max_events()),
+                    (max_events, _) => max_events,
+                };
                 Ok(BufferType::Memory {
-                    max_events: max_events.unwrap_or_else(memory_buffer_default_max_events),
+                    max_events,
+                    max_size,
                     when_full,
                 })
             }
             BufferTypeKind::DiskV1 => {
                 if max_events.is_some() {
-                    return Err(de::Error::unknown_field(
-                        "max_events",
-                        &["type", "max_size", "when_full"],
-                    ));
+                    return Err(de::Error::unknown_field("max_events", &DISK_V1_FIELDS));
+                }
+                if compression.is_some() {
+                    return Err(de::Error::unknown_field("compression", &DISK_V1_FIELDS));
+                }
+                if encryption.is_some() {
+                    return Err(de::Error::unknown_field("encryption", &DISK_V1_FIELDS));
+                }
+                if retention.is_some() {
+                    return Err(de::Error::unknown_field("retention", &DISK_V1_FIELDS));
+                }
+                if shared.is_some() {
+                    return Err(de::Error::unknown_field("shared", &DISK_V1_FIELDS));
                 }
                 Ok(BufferType::DiskV1 {
                     max_size: max_size.ok_or_else(|| de::Error::missing_field("max_size"))?,
@@ -110,14 +262,22 @@ impl BufferTypeVisitor {
             }
             BufferTypeKind::DiskV2 => {
                 if max_events.is_some() {
-                    return Err(de::Error::unknown_field(
-                        "max_events",
-                        &["type", "max_size", "when_full"],
-                    ));
+                    return Err(de::Error::unknown_field("max_events", &DISK_V2_FIELDS));
+                }
+                if let Some(retention) = &retention {
+                    if retention.max_age_secs.is_none() && retention.max_size.is_none() {
+                        return Err(de::Error::custom(
+                            "`retention` must set at least one of `max_age_secs` or `max_size`",
+                        ));
+                    }
                 }
                 Ok(BufferType::DiskV2 {
                     max_size: max_size.ok_or_else(|| de::Error::missing_field("max_size"))?,
                     when_full,
+                    compression: compression.unwrap_or_default(),
+                    encryption,
+                    retention,
+                    shared,
                 })
             }
         }
@@ -208,15 +368,20 @@ pub const fn memory_buffer_default_max_events() -> NonZeroUsize {
 }
 
 /// A specific type of buffer stage.
-#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
 pub enum BufferType {
     /// A buffer stage backed by an in-memory channel provided by `tokio`.
+    ///
+    /// The channel is bound by the number of events, the total size of the events in bytes, or
+    /// both. If neither limit is configured, it holds up to 500 events.
     #[serde(rename = "memory")]
     Memory {
-        #[serde(default = "memory_buffer_default_max_events")]
-        max_events: NonZeroUsize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_events: Option<NonZeroUsize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_size: Option<NonZeroU64>,
         #[serde(default)]
         when_full: WhenFull,
     },
@@ -228,11 +393,27 @@ pub enum BufferType {
         when_full: WhenFull,
     },
     /// A buffer stage backed by disk.
+    ///
+    /// Records can optionally be compressed, and then encrypted, before being written to disk.
+    ///
+    /// When `retention` is set, data files are kept for a while after all of their records have
+    /// been acknowledged, so that their records can be replayed.
+    ///
+    /// When `shared` is set, the buffer is shared by every sink configured with the same name: each
+    /// record is written once, and each sink reads and acknowledges it on its own.
     #[serde(rename = "disk")]
     DiskV2 {
         max_size: NonZeroU64,
         #[serde(default)]
         when_full: WhenFull,
+        #[serde(default)]
+        compression: DiskBufferCompression,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encryption: Option<DiskBufferEncryption>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retention: Option<DiskBufferRetention>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        shared: Option<String>,
     },
 }
 
@@ -241,8 +422,9 @@ impl BufferType {
     ///
     /// # Errors
     ///
-    /// If a required parameter is missing, or if there is an error building the topology itself, an
-    /// error variant will be returned desribing the error
+    /// If a required parameter is missing, if the encryption key of a disk buffer cannot be loaded,
+    /// or if there is an error building the topology itself, an error variant will be returned
+    /// desribing the error
     pub fn add_to_builder<T>(
         &self,
         builder: &mut TopologyBuilder<T>,
@@ -252,26 +434,59 @@ impl BufferType {
     where
         T: Bufferable + Clone,
     {
-        match *self {
+        let consumers = vec![id.clone()];
+        self.add_to_builder_with_consumers(builder, data_dir, id, &consumers)
+    }
+
+    fn add_to_builder_with_consumers<T>(
+        &self,
+        builder: &mut TopologyBuilder<T>,
+        data_dir: Option<PathBuf>,
+        id: String,
+        shared_consumers: &[String],
+    ) -> Result<(), BufferBuildError>
+    where
+        T: Bufferable + Clone,
+    {
+        match self {
             BufferType::Memory {
                 when_full,
                 max_events,
+                max_size,
             } => {
-                builder.stage(MemoryBuffer::new(max_events), when_full);
+                // A limit beyond what fits in memory is no limit at all.
+                let max_size = max_size.and_then(|max_size| {
+                    NonZeroUsize::new(usize::try_from(max_size.get()).unwrap_or(usize::MAX))
+                });
+                builder.stage(MemoryBuffer::with_limits(*max_events, max_size), *when_full);
             }
             BufferType::DiskV1 {
                 when_full,
                 max_size,
             } => {
                 let data_dir = data_dir.ok_or(BufferBuildError::RequiresDataDir)?;
-                builder.stage(DiskV1Buffer::new(id, data_dir, max_size), when_full);
+                builder.stage(DiskV1Buffer::new(id, data_dir, *max_size), *when_full);
             }
             BufferType::DiskV2 {
                 when_full,
                 max_size,
+                compression,
+                encryption,
+                retention,
+                shared,
             } => {
                 let data_dir = data_dir.ok_or(BufferBuildError::RequiresDataDir)?;
-                builder.stage(DiskV2Buffer::new(id, data_dir, max_size), when_full);
+                let encryption_key = encryption
+                    .as_ref()
+                    .map(DiskBufferEncryption::load_key)
+                    .transpose()?;
+                let mut buffer =
+                    DiskV2Buffer::new(id, data_dir, *max_size, *compression, encryption_key)
+                        .retention(*retention);
+                if let Some(name) = shared {
+                    buffer = buffer.shared(name.clone(), shared_consumers.to_vec());
+                }
+                builder.stage(buffer, *when_full);
             }
         };
 
@@ -306,7 +521,8 @@ impl Default for BufferConfig {
     fn default() -> Self {
         Self {
             stages: vec![BufferType::Memory {
-                max_events: memory_buffer_default_max_events(),
+                max_events: Some(memory_buffer_default_max_events()),
+                max_size: None,
                 when_full: WhenFull::default(),
             }],
         }
@@ -319,6 +535,58 @@ impl BufferConfig {
         &self.stages
     }
 
+    /// Gets the name of the shared disk buffer this buffer uses, if any.
+    pub fn shared_disk_buffer(&self) -> Option<&str> {
+        self.stages.iter().find_map(|stage| match stage {
+            BufferType::DiskV2 {
+                shared: Some(name), ..
+            } => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Opens the disk buffer this buffer uses, if it has one of its own, to read it while it's in
+    /// use, such as to replay its retained data files.
+    ///
+    /// A shared disk buffer isn't opened, as it's not this buffer's alone, so `None` is returned for
+    /// it, just as for a buffer without a disk buffer stage.
+    ///
+    /// # Errors
+    ///
+    /// If the data directory provided is `None`, if the encryption key of the disk buffer cannot be
+    /// loaded, or if the disk buffer cannot be opened, an error variant will be returned.
+    pub async fn open_disk_buffer(
+        &self,
+        data_dir: Option<&Path>,
+        buffer_id: &str,
+    ) -> Result<Option<DiskBufferInspector>, BufferBuildError> {
+        let encryption = self.stages.iter().find_map(|stage| match stage {
+            BufferType::DiskV2 {
+                encryption,
+                shared: None,
+                ..
+            } => Some(encryption),
+            _ => None,
+        });
+        let encryption = match encryption {
+            Some(encryption) => encryption,
+            None => return Ok(None),
+        };
+
+        let data_dir = data_dir.ok_or(BufferBuildError::RequiresDataDir)?;
+        let encryption_key = encryption
+            .as_ref()
+            .map(DiskBufferEncryption::load_key)
+            .transpose()?;
+        DiskBufferInspector::open(
+            &disk_v2::get_disk_v2_data_dir_path(data_dir, buffer_id),
+            encryption_key,
+        )
+        .await
+        .map(Some)
+        .map_err(|source| BufferBuildError::FailedToInspect { source })
+    }
+
     /// Builds the buffer components represented by this configuration.
     ///
     /// The caller gets back a `Sink` and `Stream` implementation that represent a way to push items
@@ -333,11 +601,20 @@ impl BufferConfig {
     ///
     /// If a disk buffer stage is configured and the data directory provided is `None`, an error
     /// variant will be thrown.
+    ///
+    /// If an encrypted disk buffer stage cannot be opened because of its encryption, such as when
+    /// the configured key is not the key the buffer was encrypted with, an error variant will be
+    /// thrown.
+    ///
+    /// For a buffer with a shared disk buffer stage, `shared_consumers` are the IDs of every buffer
+    /// sharing it, including this one.  Consumers of the shared disk buffer that aren't among them
+    /// are removed.  If empty, this buffer is assumed to be the only one sharing it.
     #[allow(clippy::needless_pass_by_value)]
     pub async fn build<T>(
         &self,
         data_dir: Option<PathBuf>,
         buffer_id: String,
+        shared_consumers: &[String],
         span: Span,
     ) -> Result<(BufferSender<T>, BufferReceiver<T>, Acker), BufferBuildError>
     where
@@ -345,14 +622,37 @@ impl BufferConfig {
     {
         let mut builder = TopologyBuilder::default();
 
+        let own_consumer = [buffer_id.clone()];
+        let shared_consumers = if shared_consumers.is_empty() {
+            &own_consumer[..]
+        } else {
+            shared_consumers
+        };
         for stage in &self.stages {
-            stage.add_to_builder(&mut builder, data_dir.clone(), buffer_id.clone())?;
+            stage.add_to_builder_with_consumers(
+                &mut builder,
+                data_dir.clone(),
+                buffer_id.clone(),
+                shared_consumers,
+            )?;
         }
 
-        builder
-            .build(buffer_id, span)
-            .await
-            .context(FailedToBuildTopologySnafu)
+        builder.build(buffer_id, span).await.map_err(|source| {
+            // Surface disk buffers that can't be opened because of their encryption on their own,
+            // as it's usually a configuration mistake rather than a problem with the buffer.
+            let is_encryption_error = match &source {
+                TopologyError::FailedToBuildStage { source, .. } => source
+                    .downcast_ref::<disk_v2::BufferError<T>>()
+                    .map_or(false, disk_v2::BufferError::<T>::is_encryption_error),
+                _ => false,
+            };
+
+            if is_encryption_error {
+                BufferBuildError::FailedToDecrypt { source }
+            } else {
+                BufferBuildError::FailedToBuildTopology { source }
+            }
+        })
     }
 }
 
@@ -360,7 +660,11 @@ impl BufferConfig {
 mod test {
     use std::num::{NonZeroU64, NonZeroUsize};
 
-    use crate::{BufferConfig, BufferType, WhenFull};
+    use super::BufferBuildError;
+    use crate::{
+        BufferConfig, BufferType, DiskBufferCompression, DiskBufferEncryption, DiskBufferRetention,
+        WhenFull,
+    };
 
     fn check_single_stage(source: &str, expected: BufferType) {
         let config: BufferConfig = serde_yaml::from_str(source).unwrap();
@@ -390,20 +694,189 @@ mod test {
         let error = serde_yaml::from_str::<BufferConfig>(source).unwrap_err();
         assert_eq!(
             error.to_string(),
-            "unknown field `foo`, expected one of `type`, `max_events`, `max_size`, `when_full` at line 1 column 4"
+            "unknown field `foo`, expected one of `type`, `max_events`, `max_size`, `when_full`, `compression`, `encryption`, `retention`, `shared` at line 1 column 4"
         );
     }
 
     #[test]
     fn parse_partial_invalid_keys() {
-        let source = r#"max_size: 100
+        let source = r#"type: disk
+max_size: 100
 max_events: 42
 "#;
         let error = serde_yaml::from_str::<BufferConfig>(source).unwrap_err();
-        assert_eq!(
-            error.to_string(),
-            "unknown field `max_size`, expected one of `type`, `max_events`, `when_full` at line 1 column 9"
+        assert!(error.to_string().starts_with(
+            "unknown field `max_events`, expected one of `type`, `max_size`, `when_full`, `compression`, `encryption`, `retention`, `shared`"
+        ));
+    }
+
+    #[test]
+    fn parse_compression_only_for_disk() {
+        let source = r#"type: memory
+compression: zstd
+"#;
+        let error = serde_yaml::from_str::<BufferConfig>(source).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "unknown field `compression`, expected one of `type`, `max_events`, `max_size`, `when_full`"
+        ));
+
+        check_single_stage(
+            r#"
+          type: disk
+          max_size: 1024
+          compression: zstd
+          "#,
+            BufferType::DiskV2 {
+                max_size: NonZeroU64::new(1024).unwrap(),
+                when_full: WhenFull::Block,
+                compression: DiskBufferCompression::Zstd,
+                encryption: None,
+                retention: None,
+                shared: None,
+            },
+        );
+    }
+
+    #[test]
+    fn parse_encryption_only_for_disk() {
+        let source = r#"type: disk_v1
+max_size: 1024
+encryption:
+  key: foo
+"#;
+        let error = serde_yaml::from_str::<BufferConfig>(source).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "unknown field `encryption`, expected one of `type`, `max_size`, `when_full`"
+        ));
+
+        check_single_stage(
+            r#"
+          type: disk
+          max_size: 1024
+          encryption:
+            key_file: /etc/vector/buffer.key
+          "#,
+            BufferType::DiskV2 {
+                max_size: NonZeroU64::new(1024).unwrap(),
+                when_full: WhenFull::Block,
+                compression: DiskBufferCompression::None,
+                encryption: Some(DiskBufferEncryption {
+                    key: None,
+                    key_file: Some("/etc/vector/buffer.key".into()),
+                }),
+                retention: None,
+                shared: None,
+            },
+        );
+    }
+
+    #[test]
+    fn parse_shared_only_for_disk() {
+        let source = r#"type: memory
+shared: logs
+"#;
+        let error = serde_yaml::from_str::<BufferConfig>(source).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "unknown field `shared`, expected one of `type`, `max_events`, `max_size`, `when_full`"
+        ));
+
+        let source = r#"
+          type: disk
+          max_size: 1024
+          shared: logs
+          "#;
+        check_single_stage(
+            source,
+            BufferType::DiskV2 {
+                max_size: NonZeroU64::new(1024).unwrap(),
+                when_full: WhenFull::Block,
+                compression: DiskBufferCompression::None,
+                encryption: None,
+                retention: None,
+                shared: Some("logs".to_string()),
+            },
+        );
+
+        let config: BufferConfig = serde_yaml::from_str(source).unwrap();
+        assert_eq!(config.shared_disk_buffer(), Some("logs"));
+        assert_eq!(BufferConfig::default().shared_disk_buffer(), None);
+    }
+
+    #[test]
+    fn parse_retention() {
+        let source = r#"
+          type: disk
+          max_size: 1024
+          retention:
+            max_age_secs: 86400
+          "#;
+        check_single_stage(
+            source,
+            BufferType::DiskV2 {
+                max_size: NonZeroU64::new(1024).unwrap(),
+                when_full: WhenFull::Block,
+                compression: DiskBufferCompression::None,
+                encryption: None,
+                retention: Some(DiskBufferRetention {
+                    max_age_secs: NonZeroU64::new(86400),
+                    max_size: None,
+                }),
+                shared: None,
+            },
         );
+
+        let source = r#"type: disk
+max_size: 1024
+retention: {}
+"#;
+        let error = serde_yaml::from_str::<BufferConfig>(source).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("`retention` must set at least one of `max_age_secs` or `max_size`"));
+    }
+
+    #[test]
+    fn load_encryption_key() {
+        let encryption = |key: Option<&str>, key_file: Option<&str>| DiskBufferEncryption {
+            key: key.map(Into::into),
+            key_file: key_file.map(Into::into),
+        };
+
+        let key = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+        assert!(encryption(Some(key), None).load_key().is_ok());
+        for (key, key_file) in [
+            (None, None),
+            (Some(key), Some("/etc/vector/buffer.key")),
+            (Some("AAECAwQFBgcICQoLDA0ODw=="), None),
+            (Some("not base64!"), None),
+            (None, Some("/nonexistent/buffer.key")),
+        ] {
+            assert!(matches!(
+                encryption(key, key_file).load_key(),
+                Err(BufferBuildError::InvalidEncryptionKey { .. })
+            ));
+        }
+
+        let debug = format!("{:?}", encryption(Some(key), None));
+        assert!(!debug.contains(key));
+    }
+
+    #[test]
+    fn serialize_encryption_key_redacted() {
+        let serialize = |key: &str| {
+            serde_yaml::to_string(&DiskBufferEncryption {
+                key: Some(key.into()),
+                key_file: None,
+            })
+            .unwrap()
+        };
+
+        let key = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+        let other_key = "HxseHRwbGhkYFxYVFBMSERAPDg0MCwoJCAcGBQQDAgE=";
+        assert!(!serialize(key).contains(key));
+        assert!(serialize(key).contains("**REDACTED**"));
+        assert_eq!(serialize(key), serialize(key));
+        assert_ne!(serialize(key), serialize(other_key));
     }
 
     #[test]
@@ -413,7 +886,8 @@ max_events: 42
           max_events: 100
           "#,
             BufferType::Memory {
-                max_events: NonZeroUsize::new(100).unwrap(),
+                max_events: Some(NonZeroUsize::new(100).unwrap()),
+                max_size: None,
                 when_full: WhenFull::Block,
             },
         );
@@ -429,11 +903,13 @@ max_events: 42
           "#,
             &[
                 BufferType::Memory {
-                    max_events: NonZeroUsize::new(42).unwrap(),
+                    max_events: Some(NonZeroUsize::new(42).unwrap()),
+                    max_size: None,
                     when_full: WhenFull::Block,
                 },
                 BufferType::Memory {
-                    max_events: NonZeroUsize::new(100).unwrap(),
+                    max_events: Some(NonZeroUsize::new(100).unwrap()),
+                    max_size: None,
                     when_full: WhenFull::DropNewest,
                 },
             ],
@@ -458,7 +934,8 @@ max_events: 42
           type: memory
           "#,
             BufferType::Memory {
-                max_events: NonZeroUsize::new(500).unwrap(),
+                max_events: Some(NonZeroUsize::new(500).unwrap()),
+                max_size: None,
                 when_full: WhenFull::Block,
             },
         );
@@ -469,7 +946,33 @@ max_events: 42
           max_events: 100
           "#,
             BufferType::Memory {
-                max_events: NonZeroUsize::new(100).unwrap(),
+                max_events: Some(NonZeroUsize::new(100).unwrap()),
+                max_size: None,
+                when_full: WhenFull::Block,
+            },
+        );
+
+        check_single_stage(
+            r#"
+          type: memory
+          max_size: 1024
+          "#,
+            BufferType::Memory {
+                max_events: None,
+                max_size: Some(NonZeroU64::new(1024).unwrap()),
+                when_full: WhenFull::Block,
+            },
+        );
+
+        check_single_stage(
+            r#"
+          type: memory
+          max_events: 100
+          max_size: 1024
+          "#,
+            BufferType::Memory {
+                max_events: Some(NonZeroUsize::new(100).unwrap()),
+                max_size: Some(NonZeroU64::new(1024).unwrap()),
                 when_full: WhenFull::Block,
             },
         );
@@ -480,7 +983,8 @@ max_events: 42
           when_full: drop_newest
           "#,
             BufferType::Memory {
-                max_events: NonZeroUsize::new(500).unwrap(),
+                max_events: Some(NonZeroUsize::new(500).unwrap()),
+                max_size: None,
                 when_full: WhenFull::DropNewest,
             },
         );
@@ -491,7 +995,8 @@ max_events: 42
           when_full: overflow
           "#,
             BufferType::Memory {
-                max_events: NonZeroUsize::new(500).unwrap(),
+                max_events: Some(NonZeroUsize::new(500).unwrap()),
+                max_size: None,
                 when_full: WhenFull::Overflow,
             },
         );
@@ -504,6 +1009,10 @@ max_events: 42
             BufferType::DiskV2 {
                 max_size: NonZeroU64::new(1024).unwrap(),
                 when_full: WhenFull::Block,
+                compression: DiskBufferCompression::None,
+                encryption: None,
+                retention: None,
+                shared: None,
             },
         );
     }
@@ -51,6 +51,23 @@ impl InternalEvent for BufferEventsSent {
     }
 }
 
+/// Logical size of the events received by, and sent from, a buffer that may store events compressed.
+pub struct BufferLogicalBytes {
+    pub idx: usize,
+    pub received_byte_size: u64,
+    pub sent_byte_size: u64,
+}
+
+impl InternalEvent for BufferLogicalBytes {
+    #[allow(clippy::cast_precision_loss)]
+    fn emit(self) {
+        counter!("buffer_received_logical_bytes_total", self.received_byte_size, "stage" => self.idx.to_string());
+        counter!("buffer_sent_logical_bytes_total", self.sent_byte_size, "stage" => self.idx.to_string());
+        increment_gauge!("buffer_logical_byte_size", self.received_byte_size as f64, "stage" => self.idx.to_string());
+        decrement_gauge!("buffer_logical_byte_size", self.sent_byte_size as f64, "stage" => self.idx.to_string());
+    }
+}
+
 pub struct BufferEventsDropped {
     pub idx: usize,
     pub count: u64,
@@ -19,7 +19,7 @@ pub use acknowledgements::{Ackable, Acker};
 mod buffer_usage_data;
 
 pub mod config;
-pub use config::{BufferConfig, BufferType};
+pub use config::{BufferConfig, BufferType, DiskBufferEncryption};
 use encoding::Encodable;
 
 pub mod encoding;
@@ -31,6 +31,11 @@ pub mod test;
 pub mod topology;
 
 pub(crate) mod variants;
+pub use variants::{
+    DataFileSummary, DiskBufferCompression, DiskBufferEncryptionKey, DiskBufferInspector,
+    DiskBufferRetention, InspectError, LedgerSnapshot, ReplaySummary, RetainedDataFile,
+    RetainedDataFileFormat,
+};
 
 use std::fmt::Debug;
 
@@ -56,9 +61,9 @@ impl Default for WhenFull {
 #[cfg(test)]
 impl Arbitrary for WhenFull {
     fn arbitrary(g: &mut Gen) -> Self {
-        // TODO: We explicitly avoid generating "overflow" as a possible value because nothing yet
-        // supports handling it, and will be defaulted to to using "block" if they encounter
-        // "overflow".  Thus, there's no reason to emit it here... yet.
+        // We explicitly avoid generating "overflow" as a possible value because it's only valid for
+        // a stage that has another stage after it, which a single "when full" value can't express.
+        // Overflow topologies are generated as a whole instead, via `Variant::Overflow`.
         if bool::arbitrary(g) {
             WhenFull::Block
         } else {
@@ -12,7 +12,7 @@ use crate::{
         builder::TopologyBuilder,
         channel::{BufferReceiver, BufferSender},
     },
-    variants::{DiskV1Buffer, DiskV2Buffer, MemoryBuffer},
+    variants::{DiskBufferCompression, DiskV1Buffer, DiskV2Buffer, MemoryBuffer},
     Bufferable, WhenFull,
 };
 
@@ -45,6 +45,11 @@ pub enum Variant {
         data_dir: PathBuf,
         id: String,
     },
+    /// An in-memory stage, in overflow mode, in front of another variant.
+    Overflow {
+        max_events: NonZeroUsize,
+        overflow: Box<Variant>,
+    },
 }
 
 impl Variant {
@@ -53,6 +58,20 @@ impl Variant {
         T: Bufferable + Clone,
     {
         let mut builder = TopologyBuilder::default();
+        self.add_stages(&mut builder);
+
+        let (sender, receiver, _acker) = builder
+            .build(String::from("benches"), Span::none())
+            .await
+            .expect("topology build should not fail");
+
+        (sender, receiver)
+    }
+
+    fn add_stages<T>(&self, builder: &mut TopologyBuilder<T>)
+    where
+        T: Bufferable + Clone,
+    {
         match self {
             Variant::Memory {
                 max_events,
@@ -79,18 +98,24 @@ impl Variant {
                 id,
             } => {
                 builder.stage(
-                    DiskV2Buffer::new(id.clone(), data_dir.clone(), *max_size),
+                    DiskV2Buffer::new(
+                        id.clone(),
+                        data_dir.clone(),
+                        *max_size,
+                        DiskBufferCompression::None,
+                        None,
+                    ),
                     *when_full,
                 );
             }
+            Variant::Overflow {
+                max_events,
+                overflow,
+            } => {
+                builder.stage(MemoryBuffer::new(*max_events), WhenFull::Overflow);
+                overflow.add_stages(builder);
+            }
         };
-
-        let (sender, receiver, _acker) = builder
-            .build(String::from("benches"), Span::none())
-            .await
-            .expect("topology build should not fail");
-
-        (sender, receiver)
     }
 }
 
@@ -116,7 +141,7 @@ impl Arbitrary for Id {
 #[cfg(test)]
 impl Arbitrary for Variant {
     fn arbitrary(g: &mut Gen) -> Self {
-        let idx = usize::arbitrary(g) % 3;
+        let idx = usize::arbitrary(g) % 4;
 
         // Using a u16 ensures we avoid any allocation errors for our holding buffers, etc.
         let max_events = NonZeroU16::arbitrary(g)
@@ -145,7 +170,22 @@ impl Arbitrary for Variant {
                 id: Id::arbitrary(g).inner,
                 data_dir: PathBuf::arbitrary(g),
             },
-            _ => unreachable!("idx divisor should be 3"),
+            3 => {
+                // Overflow topologies are generated with a single overflow stage, so that the
+                // topology doesn't grow without bound.
+                let overflow = loop {
+                    let variant = Variant::arbitrary(g);
+                    if !matches!(variant, Variant::Overflow { .. }) {
+                        break variant;
+                    }
+                };
+
+                Variant::Overflow {
+                    max_events,
+                    overflow: Box::new(overflow),
+                }
+            }
+            _ => unreachable!("idx divisor should be 4"),
         }
     }
 
@@ -198,6 +238,24 @@ impl Arbitrary for Variant {
                     data_dir: data_dir.clone(),
                 }))
             }
+            Variant::Overflow {
+                max_events,
+                overflow,
+            } => {
+                let max_events = *max_events;
+                let shrunk_base = {
+                    let overflow = overflow.clone();
+                    max_events.shrink().map(move |me| Variant::Overflow {
+                        max_events: me,
+                        overflow: overflow.clone(),
+                    })
+                };
+                let shrunk_overflow = overflow.as_ref().shrink().map(move |o| Variant::Overflow {
+                    max_events,
+                    overflow: Box::new(o),
+                });
+                Box::new(shrunk_base.chain(shrunk_overflow))
+            }
         }
     }
 }
@@ -40,10 +40,11 @@ impl Model for InMemory {
                 if self.inner.len() >= self.capacity {
                     // DropNewest never blocks, instead it silently drops the
                     // item pushed in when the buffer is too full.
+                    Progress::Dropped
                 } else {
                     self.inner.push_back(item);
+                    Progress::Advanced
                 }
-                Progress::Advanced
             }
             WhenFull::Block | WhenFull::Overflow => {
                 if self.inner.len() >= self.capacity {
@@ -0,0 +1,184 @@
+use std::{num::NonZeroUsize, task::Poll};
+
+use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+use tokio::runtime::Builder;
+use tokio_test::task::spawn;
+
+pub(crate) use crate::test::common::Message;
+use crate::{
+    test::common::{Action, Variant},
+    WhenFull,
+};
+
+mod in_memory;
+use self::in_memory::InMemory;
+
+mod on_disk_v1;
+use self::on_disk_v1::OnDiskV1;
+
+mod on_disk_v2;
+use self::on_disk_v2::OnDiskV2;
+
+mod overflow;
+use self::overflow::Overflow;
+
+/// Maximum number of stages in a generated topology.
+const MAX_STAGES: usize = 3;
+
+/// Maximum number of events a generated in-memory stage can hold.
+///
+/// This is kept small so that the stages are regularly full, and overflowing, during a test run.
+const MAX_STAGE_EVENTS: usize = 8;
+
+/// The result of sending an item to a `Model`.
+#[derive(Debug)]
+pub(crate) enum Progress {
+    /// The item was accepted into the buffer.
+    Advanced,
+    /// The buffer was full, and the item was dropped.
+    Dropped,
+    /// The buffer was full, and sending the item would wait until there was capacity for it.
+    Blocked(Message),
+}
+
+/// A model of a buffer topology.
+///
+/// Models are a simplified, synchronous, implementation of the behavior callers should be able to
+/// observe when sending items to, and receiving items from, a buffer topology.
+pub(crate) trait Model {
+    /// Sends an item to the buffer.
+    fn send(&mut self, item: Message) -> Progress;
+
+    /// Receives the next item from the buffer, if one is available.
+    fn recv(&mut self) -> Option<Message>;
+
+    /// Gets whether or not the buffer is full.
+    fn is_full(&self) -> bool;
+
+    /// Gets whether or not the buffer is empty.
+    fn is_empty(&self) -> bool;
+}
+
+/// Creates the model for the given variant.
+pub(crate) fn model_for(variant: &Variant) -> Box<dyn Model> {
+    match variant {
+        Variant::Memory { .. } => Box::new(InMemory::new(variant)),
+        Variant::DiskV1 { .. } => Box::new(OnDiskV1::new(variant)),
+        Variant::DiskV2 { .. } => Box::new(OnDiskV2::new(variant)),
+        Variant::Overflow { .. } => Box::new(Overflow::new(variant)),
+    }
+}
+
+/// A topology made up entirely of in-memory stages.
+///
+/// The disk buffers do I/O in the background, so whether or not a record is available to read at
+/// any given moment can't be precisely modeled.  In-memory stages, on the other hand, let us drive
+/// the topology in lockstep with its model.
+#[derive(Clone, Debug)]
+struct MemoryTopology(Variant);
+
+impl Arbitrary for MemoryTopology {
+    fn arbitrary(g: &mut Gen) -> Self {
+        fn max_events(g: &mut Gen) -> NonZeroUsize {
+            NonZeroUsize::new(usize::arbitrary(g) % MAX_STAGE_EVENTS + 1)
+                .expect("max events must be nonzero")
+        }
+
+        let overflow_stages = usize::arbitrary(g) % MAX_STAGES;
+        let mut variant = Variant::Memory {
+            max_events: max_events(g),
+            when_full: WhenFull::arbitrary(g),
+        };
+        for _ in 0..overflow_stages {
+            variant = Variant::Overflow {
+                max_events: max_events(g),
+                overflow: Box::new(variant),
+            };
+        }
+
+        MemoryTopology(variant)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.0.shrink().map(MemoryTopology))
+    }
+}
+
+fn check_topology(topology: MemoryTopology, actions: Vec<Action>) -> TestResult {
+    let rt = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should not fail to build runtime");
+
+    let variant = topology.0;
+    let mut model = model_for(&variant);
+
+    rt.block_on(async move {
+        let (mut sender, mut receiver) = variant.create_sender_receiver::<Message>().await;
+
+        // We poll each send and receive exactly once, which is enough to make progress with
+        // in-memory stages.  Anything that can't make progress right away is dropped, which is
+        // also what the model does with an item that is blocked.
+        for action in actions {
+            match action {
+                Action::Send(item) => {
+                    let mut send = spawn(sender.send(item.clone()));
+                    match (model.send(item), send.poll()) {
+                        (Progress::Advanced | Progress::Dropped, Poll::Ready(Ok(())))
+                        | (Progress::Blocked(_), Poll::Pending) => {}
+                        (progress, result) => {
+                            return TestResult::error(format!(
+                                "model send was {:?}, but actual send was {:?}",
+                                progress, result
+                            ))
+                        }
+                    }
+                }
+                Action::Recv => {
+                    let mut recv = spawn(receiver.next());
+                    match (model.recv(), recv.poll()) {
+                        (Some(expected), Poll::Ready(Some(actual))) if expected == actual => {}
+                        (None, Poll::Pending) => {}
+                        (expected, result) => {
+                            return TestResult::error(format!(
+                                "model recv was {:?}, but actual recv was {:?}",
+                                expected, result
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+
+        // Once the sender is gone, everything left in the buffer should come out in the same order
+        // as the model, followed by the end of the stream.
+        drop(sender);
+        loop {
+            let mut recv = spawn(receiver.next());
+            match (model.recv(), recv.poll()) {
+                (Some(expected), Poll::Ready(Some(actual))) if expected == actual => {}
+                (None, Poll::Ready(None)) => break,
+                (expected, result) => {
+                    return TestResult::error(format!(
+                        "model recv was {:?}, but actual recv was {:?} while draining",
+                        expected, result
+                    ))
+                }
+            }
+        }
+
+        if model.is_empty() {
+            TestResult::passed()
+        } else {
+            TestResult::error("model should be empty after draining")
+        }
+    })
+}
+
+#[test]
+fn model_check_memory_topologies() {
+    QuickCheck::new()
+        .tests(1_000)
+        .max_tests(10_000)
+        .quickcheck(check_topology as fn(MemoryTopology, Vec<Action>) -> TestResult);
+}
@@ -44,11 +44,12 @@ impl Model for OnDiskV2 {
                 if self.is_full() {
                     // DropNewest never blocks, instead it silently drops the
                     // item pushed in when the buffer is too full.
+                    Progress::Dropped
                 } else {
                     self.current_bytes += byte_size;
                     self.inner.push_back(item);
+                    Progress::Advanced
                 }
-                Progress::Advanced
             }
             WhenFull::Block | WhenFull::Overflow => {
                 if self.is_full() {
@@ -0,0 +1,73 @@
+use super::{in_memory::InMemory, model_for, Progress};
+use crate::{
+    test::{
+        common::Variant,
+        model::{Message, Model},
+    },
+    WhenFull,
+};
+
+/// `Overflow` is the `Model` for an in-memory stage which overflows into another stage.
+///
+/// Once an item has been sent to the overflow stage, subsequent items also go to the overflow
+/// stage until every item sent to it has been received again, and the base stage is always
+/// drained before the overflow stage.  Taken together, items are received in the order they were
+/// sent, no matter which stage they went through.
+pub(crate) struct Overflow {
+    base: InMemory,
+    overflow: Box<dyn Model>,
+    overflow_pending: usize,
+}
+
+impl Overflow {
+    pub(crate) fn new(variant: &Variant) -> Self {
+        match variant {
+            Variant::Overflow {
+                max_events,
+                overflow,
+            } => Overflow {
+                base: InMemory::new(&Variant::Memory {
+                    max_events: *max_events,
+                    when_full: WhenFull::Overflow,
+                }),
+                overflow: model_for(overflow),
+                overflow_pending: 0,
+            },
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Model for Overflow {
+    fn send(&mut self, item: Message) -> Progress {
+        if self.overflow_pending == 0 && !self.base.is_full() {
+            return self.base.send(item);
+        }
+
+        match self.overflow.send(item) {
+            Progress::Advanced => {
+                self.overflow_pending += 1;
+                Progress::Advanced
+            }
+            progress => progress,
+        }
+    }
+
+    fn recv(&mut self) -> Option<Message> {
+        self.base.recv().or_else(|| {
+            let item = self.overflow.recv();
+            if item.is_some() {
+                self.overflow_pending = self.overflow_pending.saturating_sub(1);
+            }
+            item
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.base.is_full() && self.overflow.is_full()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.base.is_empty() && self.overflow.is_empty()
+    }
+}
@@ -7,7 +7,7 @@ use tracing::Span;
 use super::channel::{ReceiverAdapter, SenderAdapter};
 use crate::{
     buffer_usage_data::{BufferUsage, BufferUsageHandle},
-    topology::channel::{BufferReceiver, BufferSender},
+    topology::channel::{BufferReceiver, BufferSender, OverflowPending},
     variants::MemoryBuffer,
     Acker, Bufferable, WhenFull,
 };
@@ -113,6 +113,7 @@ impl<T: Bufferable> TopologyBuilder<T> {
         let mut buffer_usage = BufferUsage::from_span(span);
         let mut current_acker = None;
         let mut current_stage = None;
+        let mut current_stage_events = 0;
 
         for (stage_idx, stage) in self.stages.into_iter().enumerate().rev() {
             // Make sure the stage is valid for our current builder state.
@@ -171,12 +172,23 @@ impl<T: Bufferable> TopologyBuilder<T> {
                     BufferSender::new(sender, stage.when_full),
                     BufferReceiver::new(receiver),
                 ),
-                Some((current_sender, current_receiver)) => (
-                    BufferSender::with_overflow(sender, current_sender),
-                    BufferReceiver::with_overflow(receiver, current_receiver),
-                ),
+                Some((current_sender, current_receiver)) => {
+                    let overflow_pending = OverflowPending::new(current_stage_events);
+                    (
+                        BufferSender::with_overflow(
+                            sender,
+                            current_sender,
+                            overflow_pending.clone(),
+                        ),
+                        BufferReceiver::with_overflow(receiver, current_receiver, overflow_pending),
+                    )
+                }
             };
 
+            // Disk stages account for the events they already hold when they're created, which
+            // are the events any outer stage overflowing into this one finds waiting in it.
+            current_stage_events += usage_handle.snapshot().received_event_count;
+
             if !provides_instrumentation {
                 sender.with_instrumentation(usage_handle.clone());
                 receiver.with_instrumentation(usage_handle);
@@ -8,12 +8,15 @@ use std::{
 };
 
 use async_stream::stream;
-use crossbeam_queue::ArrayQueue;
+use crossbeam_queue::SegQueue;
 use futures::Stream;
 use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore, TryAcquireError};
 
 use crate::Bufferable;
 
+/// The maximum number of permits a `Semaphore` can hold.
+const MAX_PERMITS: usize = usize::MAX >> 3;
+
 /// Error returned by `LimitedSender::send` when the receiver has disconnected.
 #[derive(Debug, PartialEq)]
 pub struct SendError<T>(pub T);
@@ -54,11 +57,50 @@ impl<T> fmt::Display for TrySendError<T> {
 
 impl<T: fmt::Debug> std::error::Error for TrySendError<T> {}
 
+/// A limit on the capacity of the channel, enforced by handing out permits.
+#[derive(Clone, Debug)]
+struct Limiter {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+impl Limiter {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit)),
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn required_permits(&self, amount: usize) -> u32 {
+        // We have to limit the number of permits we ask for to the overall limit since we're always
+        // willing to store more items than the limit if the queue is entirely empty, because
+        // otherwise we might deadlock ourselves by not being able to send a single item.
+        cmp::min(cmp::min(self.limit, amount), u32::MAX as usize) as u32
+    }
+}
+
+/// The permits held by an item while it's in the channel.
+///
+/// Dropping them, once the item is received, returns the capacity to the channel.
+#[derive(Debug)]
+struct Permits {
+    _events: OwnedSemaphorePermit,
+    _bytes: Option<OwnedSemaphorePermit>,
+}
+
 #[derive(Debug)]
 struct Inner<T> {
-    data: Arc<ArrayQueue<(OwnedSemaphorePermit, T)>>,
-    limit: usize,
-    limiter: Arc<Semaphore>,
+    data: Arc<SegQueue<(Permits, T)>>,
+
+    /// Limits the number of events in the channel. Without an event limit, this limiter has the
+    /// maximum number of permits, and is only used to signal that the channel is closed.
+    events: Limiter,
+
+    /// Limits the total size of the items in the channel, as measured by `ByteSizeOf`.
+    bytes: Option<Limiter>,
+
     read_waker: Arc<Notify>,
 }
 
@@ -66,13 +108,26 @@ impl<T> Clone for Inner<T> {
     fn clone(&self) -> Self {
         Self {
             data: self.data.clone(),
-            limit: self.limit,
-            limiter: self.limiter.clone(),
+            events: self.events.clone(),
+            bytes: self.bytes.clone(),
             read_waker: self.read_waker.clone(),
         }
     }
 }
 
+impl<T> Inner<T> {
+    fn close(&self) {
+        self.events.semaphore.close();
+        if let Some(bytes) = self.bytes.as_ref() {
+            bytes.semaphore.close();
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.events.semaphore.is_closed()
+    }
+}
+
 #[derive(Debug)]
 pub struct LimitedSender<T> {
     inner: Inner<T>,
@@ -80,17 +135,30 @@ pub struct LimitedSender<T> {
 }
 
 impl<T: Bufferable> LimitedSender<T> {
-    #[allow(clippy::cast_possible_truncation)]
-    fn get_required_permits_for_item(&self, item: &T) -> u32 {
-        // We have to limit the number of permits we ask for to the overall limit since we're always
-        // willing to store more items than the limit if the queue is entirely empty, because
-        // otherwise we might deadlock ourselves by not being able to send a single item.
-        cmp::min(self.inner.limit, item.event_count()) as u32
+    /// Gets the number of permits required to send the item, for the event limit and the byte
+    /// limit, if any.
+    fn get_required_permits_for_item(&self, item: &T) -> (u32, Option<u32>) {
+        let events = self.inner.events.required_permits(item.event_count());
+        let bytes = self
+            .inner
+            .bytes
+            .as_ref()
+            .map(|bytes| bytes.required_permits(item.size_of()));
+
+        (events, bytes)
     }
 
     /// Gets the number of items that this channel could accept.
     pub fn available_capacity(&self) -> usize {
-        self.inner.limiter.available_permits()
+        self.inner.events.semaphore.available_permits()
+    }
+
+    /// Gets the number of bytes that this channel could accept, if it is limited by size.
+    pub fn available_byte_capacity(&self) -> Option<usize> {
+        self.inner
+            .bytes
+            .as_ref()
+            .map(|bytes| bytes.semaphore.available_permits())
     }
 
     /// Sends an item into the channel.
@@ -101,23 +169,35 @@ impl<T: Bufferable> LimitedSender<T> {
     /// with the given `item`.
     pub async fn send(&mut self, item: T) -> Result<(), SendError<T>> {
         // Calculate how many permits we need, and wait until we can acquire all of them.
-        let permits_required = self.get_required_permits_for_item(&item);
-        let permits = match self
+        let (event_permits, byte_permits) = self.get_required_permits_for_item(&item);
+        let events = match self
             .inner
-            .limiter
+            .events
+            .semaphore
             .clone()
-            .acquire_many_owned(permits_required)
+            .acquire_many_owned(event_permits)
             .await
         {
             Ok(permits) => permits,
             Err(_) => return Err(SendError(item)),
         };
 
-        self.inner
-            .data
-            .push((permits, item))
-            .expect("acquired permits but channel reported being full");
-        self.inner.read_waker.notify_one();
+        let bytes = match (self.inner.bytes.as_ref(), byte_permits) {
+            (Some(bytes), Some(byte_permits)) => {
+                match bytes
+                    .semaphore
+                    .clone()
+                    .acquire_many_owned(byte_permits)
+                    .await
+                {
+                    Ok(permits) => Some(permits),
+                    Err(_) => return Err(SendError(item)),
+                }
+            }
+            _ => None,
+        };
+
+        self.push(item, events, bytes);
 
         trace!("Sent item.");
 
@@ -134,32 +214,53 @@ impl<T: Bufferable> LimitedSender<T> {
     /// returned with the given `item`.
     pub fn try_send(&mut self, item: T) -> Result<(), TrySendError<T>> {
         // Calculate how many permits we need, and try to acquire them all without waiting.
-        let permits_required = self.get_required_permits_for_item(&item);
-        let permits = match self
+        let (event_permits, byte_permits) = self.get_required_permits_for_item(&item);
+        let events = match self
             .inner
-            .limiter
+            .events
+            .semaphore
             .clone()
-            .try_acquire_many_owned(permits_required)
+            .try_acquire_many_owned(event_permits)
         {
             Ok(permits) => permits,
-            Err(ae) => {
-                return match ae {
-                    TryAcquireError::NoPermits => Err(TrySendError::InsufficientCapacity(item)),
-                    TryAcquireError::Closed => Err(TrySendError::Disconnected(item)),
+            Err(ae) => return Err(try_send_error(ae, item)),
+        };
+
+        // If the byte limit is what's lacking, the event permits we already acquired are
+        // returned to the channel when dropped.
+        let bytes = match (self.inner.bytes.as_ref(), byte_permits) {
+            (Some(bytes), Some(byte_permits)) => {
+                match bytes.semaphore.clone().try_acquire_many_owned(byte_permits) {
+                    Ok(permits) => Some(permits),
+                    Err(ae) => return Err(try_send_error(ae, item)),
                 }
             }
+            _ => None,
         };
 
-        self.inner
-            .data
-            .push((permits, item))
-            .expect("acquired permits but channel reported being full");
-        self.inner.read_waker.notify_one();
+        self.push(item, events, bytes);
 
         trace!("Attempt to send item succeeded.");
 
         Ok(())
     }
+
+    fn push(&self, item: T, events: OwnedSemaphorePermit, bytes: Option<OwnedSemaphorePermit>) {
+        let permits = Permits {
+            _events: events,
+            _bytes: bytes,
+        };
+
+        self.inner.data.push((permits, item));
+        self.inner.read_waker.notify_one();
+    }
+}
+
+fn try_send_error<T>(error: TryAcquireError, item: T) -> TrySendError<T> {
+    match error {
+        TryAcquireError::NoPermits => TrySendError::InsufficientCapacity(item),
+        TryAcquireError::Closed => TrySendError::Disconnected(item),
+    }
 }
 
 impl<T> Clone for LimitedSender<T> {
@@ -177,7 +278,7 @@ impl<T> Drop for LimitedSender<T> {
     fn drop(&mut self) {
         // If we're the last sender to drop, close the semaphore on our way out the door.
         if self.sender_count.fetch_sub(1, Ordering::SeqCst) == 1 {
-            self.inner.limiter.close();
+            self.inner.close();
             self.inner.read_waker.notify_one();
         }
     }
@@ -191,18 +292,18 @@ pub struct LimitedReceiver<T> {
 impl<T: Send + 'static> LimitedReceiver<T> {
     /// Gets the number of items that this channel could accept.
     pub fn available_capacity(&self) -> usize {
-        self.inner.limiter.available_permits()
+        self.inner.events.semaphore.available_permits()
     }
 
     pub async fn next(&mut self) -> Option<T> {
         loop {
-            if let Some((_permit, item)) = self.inner.data.pop() {
+            if let Some((_permits, item)) = self.inner.data.pop() {
                 return Some(item);
             }
 
             // There wasn't an item for us to pop, so see if the channel is actually closed.  If so,
             // then it's time for us to close up shop as well.
-            if self.inner.limiter.is_closed() {
+            if self.inner.is_closed() {
                 return None;
             }
 
@@ -228,15 +329,28 @@ impl<T> Drop for LimitedReceiver<T> {
         // Notify senders that the channel is now closed by closing the semaphore.  Any pending
         // acquisitions will be awoken and notified that the semaphore is closed, and further new
         // sends will immediately see the semaphore is closed.
-        self.inner.limiter.close();
+        self.inner.close();
     }
 }
 
+/// Creates a channel that holds up to `limit` events.
 pub fn limited<T>(limit: usize) -> (LimitedSender<T>, LimitedReceiver<T>) {
+    limited_by_size(Some(limit), None)
+}
+
+/// Creates a channel that holds up to `max_events` events, and items with a total size of up to
+/// `max_size` bytes, as measured by `ByteSizeOf`.
+///
+/// Either limit can be omitted, in which case the channel is only bound by the other one. A
+/// channel without any limit is unbounded.
+pub fn limited_by_size<T>(
+    max_events: Option<usize>,
+    max_size: Option<usize>,
+) -> (LimitedSender<T>, LimitedReceiver<T>) {
     let inner = Inner {
-        data: Arc::new(ArrayQueue::new(limit)),
-        limit,
-        limiter: Arc::new(Semaphore::new(limit)),
+        data: Arc::new(SegQueue::new()),
+        events: Limiter::new(max_events.unwrap_or(MAX_PERMITS)),
+        bytes: max_size.map(|max_size| Limiter::new(cmp::min(max_size, MAX_PERMITS))),
         read_waker: Arc::new(Notify::new()),
     };
 
@@ -253,8 +367,11 @@ pub fn limited<T>(limit: usize) -> (LimitedSender<T>, LimitedReceiver<T>) {
 mod tests {
     use tokio_test::{assert_pending, assert_ready, task::spawn};
 
-    use super::limited;
-    use crate::{test::common::MultiEventRecord, topology::channel::limited_queue::SendError};
+    use super::{limited, limited_by_size};
+    use crate::{
+        test::common::{MultiEventRecord, SizedRecord},
+        topology::channel::limited_queue::{SendError, TrySendError},
+    };
 
     #[tokio::test]
     async fn send_receive() {
@@ -589,4 +706,88 @@ mod tests {
 
         assert_eq!(2, tx.available_capacity());
     }
+
+    #[test]
+    fn sender_waits_for_more_byte_capacity() {
+        let (mut tx, mut rx) = limited_by_size(None, Some(100));
+
+        assert_eq!(Some(100), tx.available_byte_capacity());
+
+        // `SizedRecord` is four bytes on top of the bytes it allocates.
+        let msg1 = SizedRecord(60);
+        let msg2 = SizedRecord(60);
+
+        let mut send1 = spawn(async { tx.send(msg1.clone()).await });
+        assert_eq!(Ok(()), assert_ready!(send1.poll()));
+        drop(send1);
+
+        assert_eq!(Some(36), tx.available_byte_capacity());
+
+        // The second send doesn't fit in the remaining bytes, even though there's no limit on the
+        // number of events, so it has to wait for the first item to be received.
+        let mut send2 = spawn(async { tx.send(msg2.clone()).await });
+        assert_pending!(send2.poll());
+
+        let mut recv1 = spawn(async { rx.next().await });
+        assert_eq!(Some(msg1), assert_ready!(recv1.poll()));
+        drop(recv1);
+
+        assert!(send2.is_woken());
+        assert_eq!(Ok(()), assert_ready!(send2.poll()));
+        drop(send2);
+
+        let mut recv2 = spawn(async { rx.next().await });
+        assert_eq!(Some(msg2), assert_ready!(recv2.poll()));
+        drop(recv2);
+
+        assert_eq!(Some(100), tx.available_byte_capacity());
+    }
+
+    #[test]
+    fn try_send_respects_both_limits() {
+        let (mut tx, mut rx) = limited_by_size(Some(2), Some(100));
+
+        // The byte limit is reached before the event limit...
+        assert_eq!(Ok(()), tx.try_send(SizedRecord(80)));
+        assert_eq!(
+            Err(TrySendError::InsufficientCapacity(SizedRecord(20))),
+            tx.try_send(SizedRecord(20))
+        );
+
+        // ...and an item refused for lack of bytes doesn't hold on to its event permits.
+        assert_eq!(1, tx.available_capacity());
+        assert_eq!(Ok(()), tx.try_send(SizedRecord(4)));
+
+        // Now the event limit is reached, even though there are bytes to spare.
+        assert_eq!(0, tx.available_capacity());
+        assert_eq!(Some(8), tx.available_byte_capacity());
+        assert_eq!(
+            Err(TrySendError::InsufficientCapacity(SizedRecord(0))),
+            tx.try_send(SizedRecord(0))
+        );
+
+        let mut recv = spawn(async { rx.next().await });
+        assert_eq!(Some(SizedRecord(80)), assert_ready!(recv.poll()));
+        drop(recv);
+
+        assert_eq!(Ok(()), tx.try_send(SizedRecord(20)));
+    }
+
+    #[test]
+    fn oversized_byte_send_allowed_when_empty() {
+        let (mut tx, mut rx) = limited_by_size(None, Some(10));
+
+        let msg = SizedRecord(100);
+
+        // An item bigger than the whole byte limit still goes through when the channel is empty,
+        // consuming all of the available bytes.
+        assert_eq!(Ok(()), tx.try_send(msg.clone()));
+        assert_eq!(Some(0), tx.available_byte_capacity());
+
+        let mut recv = spawn(async { rx.next().await });
+        assert_eq!(Some(msg), assert_ready!(recv.poll()));
+        drop(recv);
+
+        assert_eq!(Some(10), tx.available_byte_capacity());
+    }
 }
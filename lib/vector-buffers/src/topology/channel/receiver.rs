@@ -10,7 +10,7 @@ use tokio::select;
 use tokio_util::sync::ReusableBoxFuture;
 use vector_common::internal_event::emit;
 
-use super::limited_queue::LimitedReceiver;
+use super::{limited_queue::LimitedReceiver, sender::OverflowPending};
 use crate::{
     buffer_usage_data::BufferUsageHandle,
     variants::{
@@ -82,13 +82,15 @@ where
 /// The receiver handles retrieving events from the buffer, regardless of the overall buffer configuration.
 ///
 /// If a buffer was configured to operate in "overflow" mode, then the receiver will be responsible
-/// for querying the overflow buffer as well.  The ordering of events when operating in "overflow"
-/// is undefined, as the receiver will try to manage polling both its own buffer, as well as the
-/// overflow buffer, in order to fairly balance throughput.
+/// for querying the overflow buffer as well.  The base buffer is always drained first, which
+/// preserves the order in which events were sent, as described in more detail by [`BufferSender`].
+///
+/// [`BufferSender`]: super::BufferSender
 #[derive(Debug)]
 pub struct BufferReceiver<T: Bufferable> {
     base: ReceiverAdapter<T>,
     overflow: Option<Box<BufferReceiver<T>>>,
+    overflow_pending: OverflowPending,
     instrumentation: Option<BufferUsageHandle>,
 }
 
@@ -98,15 +100,25 @@ impl<T: Bufferable> BufferReceiver<T> {
         Self {
             base,
             overflow: None,
+            overflow_pending: OverflowPending::default(),
             instrumentation: None,
         }
     }
 
     /// Creates a new [`BufferReceiver`] wrapping the given channel receiver and overflow receiver.
-    pub fn with_overflow(base: ReceiverAdapter<T>, overflow: BufferReceiver<T>) -> Self {
+    ///
+    /// `overflow_pending` must be shared with the [`BufferSender`] created for the same stage.
+    ///
+    /// [`BufferSender`]: super::BufferSender
+    pub(crate) fn with_overflow(
+        base: ReceiverAdapter<T>,
+        overflow: BufferReceiver<T>,
+        overflow_pending: OverflowPending,
+    ) -> Self {
         Self {
             base,
             overflow: Some(Box::new(overflow)),
+            overflow_pending,
             instrumentation: None,
         }
     }
@@ -116,8 +128,13 @@ impl<T: Bufferable> BufferReceiver<T> {
     /// Note: this resets the internal state of this sender, and so this should not be called except
     /// when initially constructing `BufferSender<T>`.
     #[cfg(test)]
-    pub fn switch_to_overflow(&mut self, overflow: BufferReceiver<T>) {
+    pub(crate) fn switch_to_overflow(
+        &mut self,
+        overflow: BufferReceiver<T>,
+        overflow_pending: OverflowPending,
+    ) {
         self.overflow = Some(Box::new(overflow));
+        self.overflow_pending = overflow_pending;
     }
 
     /// Configures this receiver to instrument the items passing through it.
@@ -127,14 +144,10 @@ impl<T: Bufferable> BufferReceiver<T> {
 
     #[async_recursion]
     pub async fn next(&mut self) -> Option<T> {
-        // We want to poll both our base and overflow receivers without waiting for one or the
-        // other to entirely drain before checking the other.  This ensures that we're fairly
-        // servicing both receivers, and avoiding stalls in one or the other.
-        //
-        // This is primarily important in situations where an overflow-triggering event has
-        // occurred, and is over, and items are flowing through the base receiver.  If we waited to
-        // entirely drain the overflow receiver, we might cause another small stall of the pipeline
-        // attached to the base receiver.
+        // The sender only sends events to the overflow receiver once the base receiver is full,
+        // and keeps doing so until we've received all of them, so everything in the base receiver
+        // is always older than what's in the overflow receiver.  Draining the base receiver first
+        // keeps events in the order they were sent, no matter when either of them became ready.
         let overflow = self.overflow.as_mut().map(Pin::new);
 
         let (item, from_base) = match overflow {
@@ -142,15 +155,19 @@ impl<T: Bufferable> BufferReceiver<T> {
                 Some(item) => (item, true),
                 None => return None,
             },
-            Some(mut overflow) => {
-                select! {
-                    Some(item) = overflow.next() => (item, false),
-                    Some(item) = self.base.next() => (item, true),
-                    else => return None,
-                }
-            }
+            Some(mut overflow) => select! {
+                biased;
+
+                Some(item) = self.base.next() => (item, true),
+                Some(item) = overflow.next() => (item, false),
+                else => return None,
+            },
         };
 
+        if !from_base {
+            self.overflow_pending.received(item.event_count() as u64);
+        }
+
         // If instrumentation is enabled, and we got the item from the base receiver, then and only
         // then do we track sending the event out.
         if let Some(handle) = self.instrumentation.as_ref() {
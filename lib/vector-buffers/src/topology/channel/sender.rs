@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
 use async_recursion::async_recursion;
 use tokio::sync::Mutex;
@@ -24,6 +27,9 @@ pub enum SenderAdapter<T: Bufferable> {
 
     /// The disk v2 buffer.
     DiskV2(Arc<Mutex<disk_v2::Writer<T, ProductionFilesystem>>>),
+
+    /// A consumer of a shared disk v2 buffer.
+    DiskV2Shared(Arc<disk_v2::SharedSender<T>>),
 }
 
 impl<T: Bufferable> From<LimitedSender<T>> for SenderAdapter<T> {
@@ -44,6 +50,12 @@ impl<T: Bufferable> From<disk_v2::Writer<T, ProductionFilesystem>> for SenderAda
     }
 }
 
+impl<T: Bufferable> From<disk_v2::SharedSender<T>> for SenderAdapter<T> {
+    fn from(v: disk_v2::SharedSender<T>) -> Self {
+        Self::DiskV2Shared(Arc::new(v))
+    }
+}
+
 impl<T> SenderAdapter<T>
 where
     T: Bufferable,
@@ -63,6 +75,17 @@ where
                     panic!("writer hit unrecoverable error during write: {}", e);
                 }
 
+                Ok(())
+            }
+            Self::DiskV2Shared(sender) => {
+                // Only one consumer of a shared buffer writes the records sent to all of them.
+                if let Some(mut writer) = sender.writer().await {
+                    if let Err(e) = writer.write_record(item).await {
+                        // Can't really do much except panic here. :sweat:
+                        panic!("writer hit unrecoverable error during write: {}", e);
+                    }
+                }
+
                 Ok(())
             }
         }
@@ -95,6 +118,27 @@ where
                     }
                 }
             }
+            Self::DiskV2Shared(sender) => {
+                let mut writer = match sender.writer().await {
+                    Some(writer) => writer,
+                    None => return Ok(None),
+                };
+
+                match writer.try_write_record(item).await {
+                    Ok(None) => {
+                        if let Err(e) = writer.flush().await {
+                            // Can't really do much except panic here. :sweat:
+                            panic!("writer hit unrecoverable error during flush: {}", e);
+                        }
+                        Ok(None)
+                    }
+                    Ok(Some(item)) => Ok(Some(item)),
+                    Err(e) => {
+                        // Can't really do much except panic here. :sweat:
+                        panic!("writer hit unrecoverable error during write: {}", e);
+                    }
+                }
+            }
         }
     }
 
@@ -113,6 +157,16 @@ where
                     panic!("writer hit unrecoverable error during flush: {}", e);
                 }
 
+                Ok(())
+            }
+            Self::DiskV2Shared(sender) => {
+                if let Some(mut writer) = sender.writer().await {
+                    if let Err(e) = writer.flush().await {
+                        // Can't really do much except panic here. :sweat:
+                        panic!("writer hit unrecoverable error during flush: {}", e);
+                    }
+                }
+
                 Ok(())
             }
         }
@@ -121,7 +175,102 @@ where
     pub fn capacity(&self) -> Option<usize> {
         match self {
             Self::InMemory(tx) => Some(tx.available_capacity()),
-            Self::DiskV1(_) | Self::DiskV2(_) => None,
+            Self::DiskV1(_) | Self::DiskV2(_) | Self::DiskV2Shared(_) => None,
+        }
+    }
+}
+
+/// Number of events sent to an overflow buffer that have not yet been received from it.
+///
+/// This is shared between a [`BufferSender`] and the [`BufferReceiver`] of the same topology
+/// stage, and is what allows them to uphold the ordering guarantees of "overflow" mode.
+///
+/// Events left in a disk-based overflow buffer by a previous process are tracked separately, as
+/// they were never sent by this sender.  They're ahead of any event this sender overflows, so they
+/// are the first ones to be received from the overflow buffer.
+///
+/// [`BufferReceiver`]: super::BufferReceiver
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OverflowPending {
+    pending: Arc<AtomicU64>,
+    preexisting: Arc<AtomicU64>,
+}
+
+impl OverflowPending {
+    /// Creates a new [`OverflowPending`] for an overflow buffer that already holds the given
+    /// number of events.
+    pub(crate) fn new(preexisting_events: u64) -> Self {
+        Self {
+            pending: Arc::default(),
+            preexisting: Arc::new(AtomicU64::new(preexisting_events)),
+        }
+    }
+
+    /// Gets whether or not any events sent to the overflow buffer are still waiting to be received.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending.load(Ordering::Acquire) > 0
+    }
+
+    fn increment(&self, events: u64) {
+        self.pending.fetch_add(events, Ordering::AcqRel);
+    }
+
+    fn decrement(&self, events: u64) {
+        let _ = self
+            .pending
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                Some(n.saturating_sub(events))
+            });
+    }
+
+    /// Marks the given number of events as having been received from the overflow buffer.
+    ///
+    /// Events left in the overflow buffer by a previous process are received first, so they're
+    /// accounted for before the events sent to it by this process.
+    pub(crate) fn received(&self, events: u64) {
+        let mut remaining = events;
+        let _ = self
+            .preexisting
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                remaining = events.saturating_sub(n);
+                Some(n.saturating_sub(events))
+            });
+        if remaining > 0 {
+            self.decrement(remaining);
+        }
+    }
+}
+
+/// Undoes the accounting for an event sent to the overflow buffer unless the send is known to have
+/// succeeded.
+///
+/// As sending to the overflow buffer may wait, the send can be cancelled part-way, and without this,
+/// the event would be counted as pending forever.
+struct OverflowSendGuard<'a> {
+    pending: &'a OverflowPending,
+    events: u64,
+    accepted: bool,
+}
+
+impl<'a> OverflowSendGuard<'a> {
+    fn new(pending: &'a OverflowPending, events: u64) -> Self {
+        pending.increment(events);
+        Self {
+            pending,
+            events,
+            accepted: false,
+        }
+    }
+
+    fn accept(mut self) {
+        self.accepted = true;
+    }
+}
+
+impl<'a> Drop for OverflowSendGuard<'a> {
+    fn drop(&mut self) {
+        if !self.accepted {
+            self.pending.decrement(self.events);
         }
     }
 }
@@ -143,6 +292,26 @@ where
 /// dropped and proceed no further. In "overflow" mode, events will be sent to another buffer
 /// sender.  Callers can specify the overflow sender to use when constructing their buffers initially.
 ///
+/// ## Ordering in "overflow" mode
+///
+/// Events are delivered in the order they were sent, even when some of them have been sent to the
+/// overflow buffer.  Once an event has been sent to the overflow buffer, all subsequent events are
+/// also sent to the overflow buffer, even if the base channel has capacity again, until the
+/// receiver has taken every overflowed event back out.  Likewise, [`BufferReceiver`] drains the
+/// base channel -- which only holds events older than those in the overflow buffer -- before
+/// reading from the overflow buffer.  Only then does the sender go back to the base channel.
+///
+/// This means that the overflow buffer is only used while the base channel is applying
+/// backpressure, and that the base channel is used again as soon as that backpressure has been
+/// fully relieved.
+///
+/// This guarantee applies to events sent by a single sender, and within the lifetime of the
+/// buffer: events sent concurrently by clones of the same sender have no defined order relative to
+/// each other, and events left in a disk-based overflow buffer by a previous process are delivered
+/// whenever the base channel is empty, ahead of any event overflowed by this process.
+///
+/// [`BufferReceiver`]: super::BufferReceiver
+///
 /// TODO: We should eventually rework `BufferSender`/`BufferReceiver` so that they contain a vector
 /// of the fields we already have here, but instead of cascading via calling into `overflow`, we'd
 /// linearize the nesting instead, so that `BufferSender` would only ever be calling the underlying
@@ -152,6 +321,7 @@ where
 pub struct BufferSender<T: Bufferable> {
     base: SenderAdapter<T>,
     overflow: Option<Box<BufferSender<T>>>,
+    overflow_pending: OverflowPending,
     when_full: WhenFull,
     instrumentation: Option<BufferUsageHandle>,
 }
@@ -162,16 +332,26 @@ impl<T: Bufferable> BufferSender<T> {
         Self {
             base,
             overflow: None,
+            overflow_pending: OverflowPending::default(),
             when_full,
             instrumentation: None,
         }
     }
 
     /// Creates a new [`BufferSender`] wrapping the given channel sender and overflow sender.
-    pub fn with_overflow(base: SenderAdapter<T>, overflow: BufferSender<T>) -> Self {
+    ///
+    /// `overflow_pending` must be shared with the [`BufferReceiver`] created for the same stage.
+    ///
+    /// [`BufferReceiver`]: super::BufferReceiver
+    pub(crate) fn with_overflow(
+        base: SenderAdapter<T>,
+        overflow: BufferSender<T>,
+        overflow_pending: OverflowPending,
+    ) -> Self {
         Self {
             base,
             overflow: Some(Box::new(overflow)),
+            overflow_pending,
             when_full: WhenFull::Overflow,
             instrumentation: None,
         }
@@ -182,8 +362,13 @@ impl<T: Bufferable> BufferSender<T> {
     /// Note: this resets the internal state of this sender, and so this should not be called except
     /// when initially constructing `BufferSender<T>`.
     #[cfg(test)]
-    pub fn switch_to_overflow(&mut self, overflow: BufferSender<T>) {
+    pub(crate) fn switch_to_overflow(
+        &mut self,
+        overflow: BufferSender<T>,
+        overflow_pending: OverflowPending,
+    ) {
         self.overflow = Some(Box::new(overflow));
+        self.overflow_pending = overflow_pending;
         self.when_full = WhenFull::Overflow;
     }
 
@@ -204,8 +389,15 @@ impl<T: Bufferable> BufferSender<T> {
         self.overflow.as_ref().map(AsRef::as_ref)
     }
 
-    #[async_recursion]
     pub async fn send(&mut self, item: T) -> Result<(), ()> {
+        self.send_item(item).await.map(|_| ())
+    }
+
+    /// Sends an item into the buffer, returning whether or not it was accepted.
+    ///
+    /// An item is not accepted if it was dropped, either by this stage or by an overflow stage.
+    #[async_recursion]
+    async fn send_item(&mut self, item: T) -> Result<bool, ()> {
         let item_sizing = self
             .instrumentation
             .as_ref()
@@ -213,6 +405,7 @@ impl<T: Bufferable> BufferSender<T> {
 
         let mut sent_to_base = true;
         let mut was_dropped = false;
+        let mut accepted = true;
         match self.when_full {
             WhenFull::Block => self.base.send(item).await?,
             WhenFull::DropNewest => {
@@ -221,13 +414,28 @@ impl<T: Bufferable> BufferSender<T> {
                 }
             }
             WhenFull::Overflow => {
-                if let Some(item) = self.base.try_send(item).await? {
+                // If events we previously overflowed haven't all been received yet, this event has
+                // to follow them into the overflow buffer, even if the base channel has capacity
+                // again, otherwise it could be received before them.
+                let item = if self.overflow_pending.is_pending() {
+                    Some(item)
+                } else {
+                    self.base.try_send(item).await?
+                };
+
+                if let Some(item) = item {
                     sent_to_base = false;
-                    self.overflow
+                    let guard =
+                        OverflowSendGuard::new(&self.overflow_pending, item.event_count() as u64);
+                    accepted = self
+                        .overflow
                         .as_mut()
                         .expect("overflow must exist")
-                        .send(item)
+                        .send_item(item)
                         .await?;
+                    if accepted {
+                        guard.accept();
+                    }
                 }
             }
         };
@@ -251,7 +459,7 @@ impl<T: Bufferable> BufferSender<T> {
             }
         }
 
-        Ok(())
+        Ok(accepted && !was_dropped)
     }
 
     #[async_recursion]
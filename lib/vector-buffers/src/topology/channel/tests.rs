@@ -1,16 +1,22 @@
 use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use tokio::{pin, sync::Barrier, time::sleep};
+use tracing::Span;
 
 use crate::{
+    test::common::with_temp_dir,
     topology::{
+        builder::TopologyBuilder,
         channel::{BufferReceiver, BufferSender},
         test_util::{assert_current_send_capacity, build_buffer},
     },
-    Bufferable, WhenFull,
+    variants::{DiskBufferCompression, DiskV2Buffer, MemoryBuffer},
+    Acker, Bufferable, WhenFull,
 };
 
 async fn assert_send_ok_with_capacities<T>(
@@ -25,6 +31,33 @@ async fn assert_send_ok_with_capacities<T>(
     assert_current_send_capacity(sender, base_expected, overflow_expected);
 }
 
+/// Builds a topology with an in-memory stage, with the given capacity, which overflows to a disk
+/// stage in the given data directory.
+async fn build_memory_to_disk_topology(
+    data_dir: PathBuf,
+    capacity: usize,
+) -> (BufferSender<u64>, BufferReceiver<u64>, Acker) {
+    let mut builder = TopologyBuilder::<u64>::default();
+    builder.stage(
+        MemoryBuffer::new(NonZeroUsize::new(capacity).unwrap()),
+        WhenFull::Overflow,
+    );
+    builder.stage(
+        DiskV2Buffer::new(
+            "test".to_string(),
+            data_dir,
+            NonZeroU64::new(268_435_488).unwrap(),
+            DiskBufferCompression::None,
+            None,
+        ),
+        WhenFull::Block,
+    );
+    builder
+        .build(String::from("test"), Span::none())
+        .await
+        .expect("topology build should not fail")
+}
+
 async fn blocking_send_and_drain_receiver<T>(
     mut sender: BufferSender<T>,
     receiver: BufferReceiver<T>,
@@ -185,6 +218,109 @@ async fn test_sender_overflow_drop_newest() {
     assert_eq!(results, vec![1, 2, 7, 8]);
 }
 
+#[tokio::test]
+async fn test_sender_overflow_preserves_order() {
+    // Get an overflow buffer, where the overflow buffer is in blocking mode, and both the base
+    // and overflow buffers have a capacity of 2.
+    let (mut tx, mut rx, _) = build_buffer(2, WhenFull::Overflow, Some(WhenFull::Block)).await;
+
+    // Fill up the base buffer, and then overflow a single message.
+    assert_send_ok_with_capacities(&mut tx, 1, Some(1), Some(2)).await;
+    assert_send_ok_with_capacities(&mut tx, 2, Some(0), Some(2)).await;
+    assert_send_ok_with_capacities(&mut tx, 3, Some(0), Some(1)).await;
+
+    // Receiving a message frees up capacity in the base buffer, but since the overflowed message
+    // hasn't been received yet, the next message has to follow it into the overflow buffer, or it
+    // could otherwise be received before it.
+    assert_eq!(rx.next().await, Some(1));
+    assert_send_ok_with_capacities(&mut tx, 4, Some(1), Some(0)).await;
+
+    // The base buffer is drained before the overflow buffer, so everything comes out in order.
+    assert_eq!(rx.next().await, Some(2));
+    assert_eq!(rx.next().await, Some(3));
+    assert_eq!(rx.next().await, Some(4));
+
+    // Now that all overflowed messages have been received, we go back to using the base buffer.
+    assert_send_ok_with_capacities(&mut tx, 5, Some(1), Some(2)).await;
+    assert_eq!(rx.next().await, Some(5));
+}
+
+#[tokio::test]
+async fn test_sender_overflow_to_disk_preserves_order() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            // Get a topology with an in-memory stage, with a capacity of 4, which overflows to a
+            // disk stage.
+            let (mut tx, mut rx, _acker) = build_memory_to_disk_topology(data_dir, 4).await;
+
+            // Send enough messages that most of them spill to disk, and interleave receiving some
+            // of them, which frees up capacity in the in-memory stage as we go.  Like the fanout
+            // does, we flush after every send so that the disk stage makes records readable.
+            let mut received = Vec::new();
+            for i in 0..32 {
+                assert!(tx.send(i).await.is_ok());
+                assert!(tx.flush().await.is_ok());
+                if i % 4 == 0 {
+                    received.push(rx.next().await.expect("should receive message"));
+                }
+            }
+
+            while received.len() < 32 {
+                received.push(rx.next().await.expect("should receive message"));
+            }
+            assert_eq!(received, (0..32).collect::<Vec<_>>());
+
+            // Once everything has been received, messages go to the in-memory stage again.
+            assert_current_send_capacity(&mut tx, Some(4), None);
+            assert!(tx.send(32).await.is_ok());
+            assert_current_send_capacity(&mut tx, Some(3), None);
+            assert_eq!(rx.next().await, Some(32));
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_sender_overflow_after_restart_preserves_order() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            // Leave four messages in the disk stage, which is all that survives the topology being
+            // dropped, as the in-memory stage only holds the first two.
+            let (mut tx, rx, acker) = build_memory_to_disk_topology(data_dir.clone(), 2).await;
+            for i in 0..6 {
+                assert!(tx.send(i).await.is_ok());
+                assert!(tx.flush().await.is_ok());
+            }
+            drop((tx, rx, acker));
+
+            // Fill up the in-memory stage, and overflow two messages after the ones left over.
+            let (mut tx, mut rx, _acker) = build_memory_to_disk_topology(data_dir, 2).await;
+            for i in 10..14 {
+                assert!(tx.send(i).await.is_ok());
+                assert!(tx.flush().await.is_ok());
+            }
+
+            // Receiving the left over messages doesn't count as receiving the ones we overflowed,
+            // so the next message still has to follow them into the disk stage.
+            for expected in [10, 11, 2, 3] {
+                assert_eq!(rx.next().await, Some(expected));
+            }
+            assert!(tx.send(14).await.is_ok());
+            assert!(tx.flush().await.is_ok());
+            assert_current_send_capacity(&mut tx, Some(2), None);
+
+            for expected in [4, 5, 12, 13, 14] {
+                assert_eq!(rx.next().await, Some(expected));
+            }
+        }
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn test_buffer_metrics_normal() {
     // Get a regular blocking buffer.
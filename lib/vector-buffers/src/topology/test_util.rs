@@ -6,7 +6,7 @@ use super::builder::TopologyBuilder;
 use crate::{
     buffer_usage_data::BufferUsageHandle,
     encoding::FixedEncodable,
-    topology::channel::{BufferReceiver, BufferSender},
+    topology::channel::{BufferReceiver, BufferSender, OverflowPending},
     Bufferable, EventCount, WhenFull,
 };
 
@@ -79,8 +79,9 @@ pub async fn build_buffer(
                 handle.clone(),
             )
             .await;
-            base_sender.switch_to_overflow(overflow_sender);
-            base_receiver.switch_to_overflow(overflow_receiver);
+            let overflow_pending = OverflowPending::default();
+            base_sender.switch_to_overflow(overflow_sender, overflow_pending.clone());
+            base_receiver.switch_to_overflow(overflow_receiver, overflow_pending);
 
             (base_sender, base_receiver)
         }
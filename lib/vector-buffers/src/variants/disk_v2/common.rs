@@ -1,13 +1,18 @@
 use std::{
-    cmp,
+    cmp, io,
+    num::NonZeroU64,
     path::{Path, PathBuf},
     time::Duration,
 };
 
 use crc32fast::Hasher;
+use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 
-use super::io::{Filesystem, ProductionFilesystem};
+use super::{
+    encryption::DiskBufferEncryptionKey,
+    io::{Filesystem, ProductionFilesystem},
+};
 
 // We don't want data files to be bigger than 128MB, but we might end up overshooting slightly.
 pub const DEFAULT_MAX_DATA_FILE_SIZE: usize = 128 * 1024 * 1024;
@@ -43,6 +48,106 @@ pub(crate) fn create_crc32c_hasher() -> Hasher {
     crc32fast::Hasher::new()
 }
 
+/// Compression applied to the payload of each record written to the buffer.
+///
+/// The compression of a buffer is stored in its ledger when the buffer is created, and all records
+/// in the buffer use it.  Changing the compression of an existing buffer requires migrating its
+/// records, which happens automatically when the buffer is loaded.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskBufferCompression {
+    /// Records are stored as-is.
+    None,
+
+    /// Records are compressed with [Zstandard][zstd], at its default compression level.
+    ///
+    /// [zstd]: https://facebook.github.io/zstd/
+    Zstd,
+}
+
+impl Default for DiskBufferCompression {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Retention of data files once every record in them has been acknowledged.
+///
+/// Instead of being deleted, acknowledged data files are moved aside, and kept until they're older
+/// than `max_age_secs`, or until the retained data files take up more than `max_size` bytes, at
+/// which point the oldest ones are deleted.  The records in retained data files can be replayed
+/// into the buffer, to send them again.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiskBufferRetention {
+    /// Maximum age, in seconds, of a retained data file, from when it was last written to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<NonZeroU64>,
+
+    /// Maximum size, in bytes, of all retained data files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<NonZeroU64>,
+}
+
+impl DiskBufferRetention {
+    /// Gets the maximum age of a retained data file, if any.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age_secs
+            .map(|max_age_secs| Duration::from_secs(max_age_secs.get()))
+    }
+}
+
+impl DiskBufferCompression {
+    /// Gets the identifier of this compression, as stored in the ledger.
+    pub(crate) fn as_u32(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Zstd => 1,
+        }
+    }
+
+    /// Gets the compression for the given identifier, as stored in the ledger.
+    pub(crate) fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Compresses `src`, appending the compressed bytes to `dst`.
+    ///
+    /// # Errors
+    ///
+    /// If the compressor encounters an error, an error variant will be returned describing the
+    /// error.
+    pub(crate) fn compress(self, src: &[u8], dst: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Self::None => {
+                dst.extend_from_slice(src);
+                Ok(())
+            }
+            Self::Zstd => zstd::stream::copy_encode(src, dst, zstd::DEFAULT_COMPRESSION_LEVEL),
+        }
+    }
+
+    /// Decompresses `src`, appending the decompressed bytes to `dst`.
+    ///
+    /// # Errors
+    ///
+    /// If `src` is not valid for this compression, an error variant will be returned describing
+    /// the error.
+    pub(crate) fn decompress(self, src: &[u8], dst: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Self::None => {
+                dst.extend_from_slice(src);
+                Ok(())
+            }
+            Self::Zstd => zstd::stream::copy_decode(src, dst),
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum BuildError {
     #[snafu(display("parameter '{}' was invalid: {}", param_name, reason))]
@@ -101,6 +206,30 @@ pub struct DiskBufferConfig<FS> {
     /// amount of data written since the last flush would be lost.
     pub(crate) flush_interval: Duration,
 
+    /// Compression applied to the payload of each record.
+    ///
+    /// This must match the compression stored in the ledger of an existing buffer.
+    pub(crate) compression: DiskBufferCompression,
+
+    /// Key used to encrypt the payload of each record, if any.
+    ///
+    /// Whether or not the buffer is encrypted, and the fingerprint of the key, must match what is
+    /// stored in the ledger of an existing buffer.
+    pub(crate) encryption_key: Option<DiskBufferEncryptionKey>,
+
+    /// IDs of the consumers of a shared buffer.
+    ///
+    /// Each consumer reads the records in the buffer independently, and data files are only
+    /// deleted once every consumer has acknowledged all of their records.  Empty if the buffer is
+    /// not shared, in which case it has a single reader.
+    pub(crate) consumers: Vec<String>,
+
+    /// Retention of acknowledged data files, if any.
+    ///
+    /// When set, data files are retained, rather than deleted, once every record in them has been
+    /// acknowledged.
+    pub(crate) retention: Option<DiskBufferRetention>,
+
     /// Filesystem implementation for opening data files.
     ///
     /// We allow parameterizing the filesystem implementation for ease of testing.  The "filesystem"
@@ -121,6 +250,10 @@ where
     pub(crate) max_record_size: Option<usize>,
     pub(crate) write_buffer_size: Option<usize>,
     pub(crate) flush_interval: Option<Duration>,
+    pub(crate) compression: Option<DiskBufferCompression>,
+    pub(crate) encryption_key: Option<DiskBufferEncryptionKey>,
+    pub(crate) consumers: Vec<String>,
+    pub(crate) retention: Option<DiskBufferRetention>,
     pub(crate) filesystem: FS,
 }
 
@@ -136,6 +269,10 @@ impl DiskBufferConfigBuilder {
             max_record_size: None,
             write_buffer_size: None,
             flush_interval: None,
+            compression: None,
+            encryption_key: None,
+            consumers: Vec::new(),
+            retention: None,
             filesystem: ProductionFilesystem,
         }
     }
@@ -216,6 +353,56 @@ where
         self
     }
 
+    /// Sets the compression applied to the payload of each record.
+    ///
+    /// Records are compressed after being encoded, and the maximum record size applies to the
+    /// encoded record before compression.
+    ///
+    /// Defaults to no compression.
+    #[allow(dead_code)]
+    pub fn compression(mut self, compression: DiskBufferCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sets the key used to encrypt the payload of each record.
+    ///
+    /// Records are encrypted after being compressed, so the checksum of a record covers its
+    /// encrypted payload.
+    ///
+    /// Defaults to no encryption.
+    #[allow(dead_code)]
+    pub fn encryption_key(mut self, key: Option<DiskBufferEncryptionKey>) -> Self {
+        self.encryption_key = key;
+        self
+    }
+
+    /// Sets the IDs of the consumers of a shared buffer.
+    ///
+    /// Each consumer reads the records in the buffer independently, with its own acknowledgements,
+    /// and data files are only deleted once every consumer has acknowledged all of their records.
+    /// Consumers that were previously configured, but no longer are, are removed when the buffer is
+    /// loaded.
+    ///
+    /// Defaults to no consumers, where the buffer is not shared and has a single reader.
+    #[allow(dead_code)]
+    pub fn consumers(mut self, consumers: Vec<String>) -> Self {
+        self.consumers = consumers;
+        self
+    }
+
+    /// Sets the retention of data files once every record in them has been acknowledged.
+    ///
+    /// Retained data files are moved aside, where they no longer count towards the size of the
+    /// buffer, and are deleted once they exceed the limits of the retention.
+    ///
+    /// Defaults to no retention, where acknowledged data files are deleted right away.
+    #[allow(dead_code)]
+    pub fn retention(mut self, retention: Option<DiskBufferRetention>) -> Self {
+        self.retention = retention;
+        self
+    }
+
     /// Filesystem implementation for opening data files.
     ///
     /// We allow parameterizing the filesystem implementation for ease of testing.  The "filesystem"
@@ -235,6 +422,10 @@ where
             max_record_size: self.max_record_size,
             write_buffer_size: self.write_buffer_size,
             flush_interval: self.flush_interval,
+            compression: self.compression,
+            encryption_key: self.encryption_key,
+            consumers: self.consumers,
+            retention: self.retention,
             filesystem,
         }
     }
@@ -249,6 +440,10 @@ where
         let max_record_size = self.max_record_size.unwrap_or(DEFAULT_MAX_RECORD_SIZE);
         let write_buffer_size = self.write_buffer_size.unwrap_or(DEFAULT_WRITE_BUFFER_SIZE);
         let flush_interval = self.flush_interval.unwrap_or(DEFAULT_FLUSH_INTERVAL);
+        let compression = self.compression.unwrap_or_default();
+        let encryption_key = self.encryption_key;
+        let consumers = self.consumers;
+        let retention = self.retention;
         let filesystem = self.filesystem;
 
         // Validate the input parameters.
@@ -283,6 +478,15 @@ where
             });
         }
 
+        if let Some(retention) = retention {
+            if retention.max_age_secs.is_none() && retention.max_size.is_none() {
+                return Err(BuildError::InvalidParameter {
+                    param_name: "retention",
+                    reason: "must set a maximum age, a maximum size, or both".to_string(),
+                });
+            }
+        }
+
         // We calculate our current buffer size based on the number of unacknowledged records. However, we only delete
         // data files once they've been entirely acknowledged. This means that we may report a current buffer size that
         // is smaller than the sum of the size of the data files currently on disk.
@@ -308,6 +512,10 @@ where
             max_record_size,
             write_buffer_size,
             flush_interval,
+            compression,
+            encryption_key,
+            consumers,
+            retention,
             filesystem,
         })
     }
@@ -0,0 +1,253 @@
+use std::{io, path::Path};
+
+use super::{
+    common::DiskBufferCompression,
+    encryption::DiskBufferEncryptionKey,
+    get_disk_v2_data_dir_path,
+    ledger::read_ledger_record_format,
+    retention::{carry_over_retained_data_files, RetainedDataFileFormat},
+    Buffer, DiskBufferConfigBuilder,
+};
+use crate::{buffer_usage_data::BufferUsageHandle, Bufferable};
+
+/// Migrates an existing `disk_v2` buffer to the given compression and encryption key, if need be.
+///
+/// The compression and encryption of a buffer are fixed when it is created, so when the configured
+/// compression changes, or encryption is enabled for an unencrypted buffer, we copy every unread
+/// record into a new buffer that uses the configured compression and encryption key, move the old
+/// buffer aside, move the new buffer into place, carry over the data files the old buffer retained,
+/// and only then delete the old buffer.  If the
+/// migration is interrupted, it picks back up where it left off the next time the buffer is loaded.
+///
+/// Records of an encrypted buffer can only be read with the key they were encrypted with, so an
+/// encrypted buffer is never migrated to a different key, or to no encryption at all: the mismatch
+/// is reported when the buffer is loaded instead.
+pub async fn try_disk_v2_compression_migration<T>(
+    base_data_dir: &Path,
+    id: &str,
+    compression: DiskBufferCompression,
+    encryption_key: Option<&DiskBufferEncryptionKey>,
+) -> Result<(), String>
+where
+    T: Bufferable + Clone,
+{
+    let src_buffer_dir = get_disk_v2_data_dir_path(base_data_dir, id);
+    let dst_buffer_dir = src_buffer_dir.with_file_name(format!("{}.migrating", id));
+    let old_buffer_dir = src_buffer_dir.with_file_name(format!("{}.old", id));
+
+    let src_format = read_ledger_record_format(&src_buffer_dir)
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to read disk buffer ledger at '{}': {}",
+                src_buffer_dir.to_string_lossy(),
+                e
+            )
+        })?;
+
+    let src_format = match src_format {
+        Some(src_format)
+            if src_format.compression != compression
+                || (!src_format.encrypted && encryption_key.is_some()) =>
+        {
+            src_format
+        }
+        // The buffer already uses the configured compression and encryption, but we may have been
+        // interrupted after moving the new buffer into place but before deleting the old buffer.
+        Some(_) => return remove_old_buffer(&src_buffer_dir, &old_buffer_dir).await,
+        // There's no buffer at all, but we may have been interrupted after moving the old buffer
+        // aside but before moving the new buffer into place, so finish that up now.
+        None => return finalize_migration(&src_buffer_dir, &dst_buffer_dir, &old_buffer_dir).await,
+    };
+
+    info!(
+        "Detected disk buffer for the `{}` sink with {:?} compression and encryption {}, but {:?} compression and encryption {} is configured. Automatically migrating.",
+        id,
+        src_format.compression,
+        describe_encryption(src_format.encrypted),
+        compression,
+        describe_encryption(encryption_key.is_some()),
+    );
+
+    // An encrypted source buffer can only have been encrypted with the configured key, if any, as
+    // we never migrate away from encryption.  If it was encrypted with another key, loading it fails.
+    let src_encryption_key = if src_format.encrypted {
+        encryption_key.cloned()
+    } else {
+        None
+    };
+
+    // Set both buffers to an essentially unlimited size so we can ensure that whatever is in the
+    // source buffer can be written to the destination buffer.
+    let src_config = DiskBufferConfigBuilder::from_path(src_buffer_dir.clone())
+        .max_buffer_size(u64::MAX)
+        .compression(src_format.compression)
+        .encryption_key(src_encryption_key)
+        .build()
+        .map_err(|e| format!("Failed to configure source disk buffer: {}", e))?;
+    let dst_config = DiskBufferConfigBuilder::from_path(dst_buffer_dir.clone())
+        .max_buffer_size(u64::MAX)
+        .compression(compression)
+        .encryption_key(encryption_key.cloned())
+        .build()
+        .map_err(|e| format!("Failed to configure destination disk buffer: {}", e))?;
+
+    let (mut src_writer, mut src_reader, src_acker) =
+        Buffer::<T>::from_config(src_config, BufferUsageHandle::noop())
+            .await
+            .map_err(|e| format!("Failed to load source disk buffer: {}", e))?;
+    let (mut dst_writer, _, _) = Buffer::<T>::from_config(dst_config, BufferUsageHandle::noop())
+        .await
+        .map_err(|e| format!("Failed to build destination disk buffer: {}", e))?;
+
+    // Nothing will be written to the source buffer, so close the writer to let the reader know it
+    // can stop once it has read every record.
+    src_writer.close();
+
+    // Read each record from the source and write it to the destination. If the write succeeds, we
+    // acknowledge it in the source so that it can't be copied again if the migration is interrupted
+    // and must be restarted.
+    let mut migrated_records = 0;
+    loop {
+        let record = match src_reader.next().await {
+            Ok(Some(record)) => record,
+            Ok(None) => break,
+            Err(e) => {
+                return Err(format!(
+                    "failed reading record {} from the source disk buffer: {}",
+                    migrated_records, e,
+                ))
+            }
+        };
+        let record_event_count = record.event_count();
+
+        dst_writer.write_record(record).await.map_err(|e| {
+            format!(
+                "failed writing record {} to the destination disk buffer: {}",
+                migrated_records, e,
+            )
+        })?;
+
+        dst_writer.flush().await.map_err(|e| {
+            format!(
+                "failed flushing record {} to the destination disk buffer: {}",
+                migrated_records, e,
+            )
+        })?;
+
+        src_acker.ack(record_event_count);
+        migrated_records += record_event_count;
+    }
+
+    // Close both buffers, which releases their locks, before swapping them around.
+    drop(src_writer);
+    drop(src_reader);
+    drop(src_acker);
+    drop(dst_writer);
+
+    // Move the old buffer aside rather than deleting it outright, so that there's always a complete
+    // buffer on disk, whichever step we might be interrupted at.
+    tokio::fs::rename(&src_buffer_dir, &old_buffer_dir)
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to move old disk buffer from '{}' to '{}': {}",
+                src_buffer_dir.to_string_lossy(),
+                old_buffer_dir.to_string_lossy(),
+                e
+            )
+        })?;
+    finalize_migration(&src_buffer_dir, &dst_buffer_dir, &old_buffer_dir).await?;
+
+    info!(
+        "Migrated {} records in disk buffer for `{}` sink to {:?} compression and encryption {}.",
+        migrated_records,
+        id,
+        compression,
+        describe_encryption(encryption_key.is_some()),
+    );
+
+    Ok(())
+}
+
+/// Moves a fully migrated buffer into place, if one exists, and then deletes the old buffer.
+///
+/// If there's no migrated buffer, the old buffer is moved back into place instead, if it was moved
+/// aside, so that it's never deleted without a replacement.
+async fn finalize_migration(
+    src_buffer_dir: &Path,
+    dst_buffer_dir: &Path,
+    old_buffer_dir: &Path,
+) -> Result<(), String> {
+    match tokio::fs::rename(dst_buffer_dir, src_buffer_dir).await {
+        Ok(()) => remove_old_buffer(src_buffer_dir, old_buffer_dir).await,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            match tokio::fs::rename(old_buffer_dir, src_buffer_dir).await {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(format!(
+                    "Failed to move old disk buffer from '{}' back to '{}': {}",
+                    old_buffer_dir.to_string_lossy(),
+                    src_buffer_dir.to_string_lossy(),
+                    e
+                )),
+            }
+        }
+        Err(e) => Err(format!(
+            "Failed to move migrated disk buffer from '{}' to '{}': {}",
+            dst_buffer_dir.to_string_lossy(),
+            src_buffer_dir.to_string_lossy(),
+            e
+        )),
+    }
+}
+
+/// Deletes the old buffer left behind by a migration, if one exists, once its retained data files
+/// have been moved into the migrated buffer.
+///
+/// Retained data files are kept in the format they were written in, as they can only be replayed
+/// by reading them as such.
+async fn remove_old_buffer(src_buffer_dir: &Path, old_buffer_dir: &Path) -> Result<(), String> {
+    let old_format = read_ledger_record_format(old_buffer_dir)
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to read old disk buffer ledger at '{}': {}",
+                old_buffer_dir.to_string_lossy(),
+                e
+            )
+        })?;
+    if let Some(old_format) = old_format {
+        let old_format = RetainedDataFileFormat {
+            compression: old_format.compression,
+            encrypted: old_format.encrypted,
+        };
+        carry_over_retained_data_files(old_buffer_dir, src_buffer_dir, old_format)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Failed to move retained data files from the old disk buffer at '{}': {}",
+                    old_buffer_dir.to_string_lossy(),
+                    e
+                )
+            })?;
+    }
+
+    match tokio::fs::remove_dir_all(old_buffer_dir).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!(
+            "Failed to delete the old disk buffer data directory at '{}': {}",
+            old_buffer_dir.to_string_lossy(),
+            e
+        )),
+    }
+}
+
+fn describe_encryption(encrypted: bool) -> &'static str {
+    if encrypted {
+        "enabled"
+    } else {
+        "disabled"
+    }
+}
@@ -0,0 +1,207 @@
+use std::{fmt, sync::Arc};
+
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    digest,
+    error::Unspecified,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// Length, in bytes, of a disk buffer encryption key.
+pub const ENCRYPTION_KEY_LEN: usize = 32;
+
+// Domain separation for the key fingerprint stored in the ledger, so that the fingerprint can't be
+// confused with a hash of the key computed for any other purpose.
+const KEY_FINGERPRINT_CONTEXT: &[u8] = b"vector disk buffer encryption key fingerprint\0";
+
+/// Key used to encrypt the payload of each record written to the buffer.
+///
+/// Records are encrypted with AES-256-GCM, using a random nonce per record which is stored
+/// alongside the ciphertext.  The ID of the record is used as additional authenticated data, so a
+/// record can't be moved to another position in the buffer without failing to decrypt.
+///
+/// A fingerprint of the key is stored in the ledger when the buffer is created, so that loading a
+/// buffer with the wrong key fails right away rather than when the first record is read.
+#[derive(Clone)]
+pub struct DiskBufferEncryptionKey {
+    key: Arc<LessSafeKey>,
+    fingerprint: [u8; 32],
+    rng: SystemRandom,
+}
+
+impl DiskBufferEncryptionKey {
+    /// Creates an encryption key from its raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// If `key` is not exactly [`ENCRYPTION_KEY_LEN`] bytes long, an error describing the problem
+    /// is returned.
+    pub fn from_bytes(key: &[u8]) -> Result<Self, String> {
+        if key.len() != ENCRYPTION_KEY_LEN {
+            return Err(format!(
+                "key must be {} bytes long, but is {} bytes long",
+                ENCRYPTION_KEY_LEN,
+                key.len()
+            ));
+        }
+
+        let unbound_key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| "key is not a valid AES-256 key".to_string())?;
+
+        let mut context = digest::Context::new(&digest::SHA256);
+        context.update(KEY_FINGERPRINT_CONTEXT);
+        context.update(key);
+        let mut fingerprint = [0; 32];
+        fingerprint.copy_from_slice(context.finish().as_ref());
+
+        Ok(Self {
+            key: Arc::new(LessSafeKey::new(unbound_key)),
+            fingerprint,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Creates an encryption key from its base64 encoding.
+    ///
+    /// Leading and trailing whitespace is ignored.
+    ///
+    /// # Errors
+    ///
+    /// If `encoded` is not valid base64, or does not decode to a valid key, an error describing
+    /// the problem is returned.
+    pub fn from_base64(encoded: &str) -> Result<Self, String> {
+        let key = base64::decode(encoded.trim())
+            .map_err(|e| format!("key is not valid base64: {}", e))?;
+        Self::from_bytes(&key)
+    }
+
+    /// Gets the fingerprint of this key, as stored in the ledger.
+    pub(crate) fn fingerprint(&self) -> &[u8; 32] {
+        &self.fingerprint
+    }
+
+    /// Encrypts `src` as the payload of the record with the given ID, appending the nonce,
+    /// ciphertext, and authentication tag to `dst`.
+    ///
+    /// # Errors
+    ///
+    /// If a nonce cannot be generated, or the encryption itself fails, an error is returned.
+    pub(crate) fn encrypt(
+        &self,
+        record_id: u64,
+        src: &[u8],
+        dst: &mut Vec<u8>,
+    ) -> Result<(), Unspecified> {
+        let mut nonce = [0; NONCE_LEN];
+        self.rng.fill(&mut nonce)?;
+        dst.extend_from_slice(&nonce);
+
+        let ciphertext_start = dst.len();
+        dst.extend_from_slice(src);
+        let tag = self.key.seal_in_place_separate_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(record_id.to_be_bytes()),
+            &mut dst[ciphertext_start..],
+        )?;
+        dst.extend_from_slice(tag.as_ref());
+
+        Ok(())
+    }
+
+    /// Decrypts `src`, the payload of the record with the given ID, appending the plaintext to
+    /// `dst`.
+    ///
+    /// # Errors
+    ///
+    /// If `src` was not encrypted with this key for the given record ID, or was altered after
+    /// being encrypted, an error is returned.
+    pub(crate) fn decrypt(
+        &self,
+        record_id: u64,
+        src: &[u8],
+        dst: &mut Vec<u8>,
+    ) -> Result<(), Unspecified> {
+        if src.len() < NONCE_LEN {
+            return Err(Unspecified);
+        }
+
+        let (nonce, ciphertext) = src.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)?;
+
+        let plaintext_start = dst.len();
+        dst.extend_from_slice(ciphertext);
+        let plaintext_len = self
+            .key
+            .open_in_place(
+                nonce,
+                Aad::from(record_id.to_be_bytes()),
+                &mut dst[plaintext_start..],
+            )?
+            .len();
+        dst.truncate(plaintext_start + plaintext_len);
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for DiskBufferEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the key itself.
+        f.debug_struct("DiskBufferEncryptionKey")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskBufferEncryptionKey;
+
+    fn key(byte: u8) -> DiskBufferEncryptionKey {
+        DiskBufferEncryptionKey::from_bytes(&[byte; 32]).expect("key should be valid")
+    }
+
+    #[test]
+    fn roundtrip() {
+        let key = key(1);
+        let mut encrypted = Vec::new();
+        key.encrypt(42, b"hello world", &mut encrypted)
+            .expect("encryption should not fail");
+        assert_ne!(&encrypted[..], b"hello world");
+
+        let mut decrypted = Vec::new();
+        key.decrypt(42, &encrypted, &mut decrypted)
+            .expect("decryption should not fail");
+        assert_eq!(&decrypted[..], b"hello world");
+    }
+
+    #[test]
+    fn wrong_key_or_record_id_fails() {
+        let mut encrypted = Vec::new();
+        key(1)
+            .encrypt(42, b"hello world", &mut encrypted)
+            .expect("encryption should not fail");
+
+        let mut decrypted = Vec::new();
+        assert!(key(2).decrypt(42, &encrypted, &mut decrypted).is_err());
+        assert!(key(1).decrypt(43, &encrypted, &mut decrypted).is_err());
+
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(key(1).decrypt(42, &tampered, &mut decrypted).is_err());
+        assert!(key(1).decrypt(42, &encrypted[..4], &mut decrypted).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(DiskBufferEncryptionKey::from_bytes(&[0; 16]).is_err());
+        assert!(DiskBufferEncryptionKey::from_base64("not base64!").is_err());
+        assert!(DiskBufferEncryptionKey::from_base64(&base64::encode([7; 32])).is_ok());
+        assert_eq!(
+            DiskBufferEncryptionKey::from_base64(&format!("{}\n", base64::encode([7; 32])))
+                .expect("key should be valid")
+                .fingerprint(),
+            key(7).fingerprint()
+        );
+    }
+}
@@ -0,0 +1,523 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use fslock::LockFile;
+use snafu::{ResultExt, Snafu};
+use tokio::fs;
+
+use super::{
+    common::{DiskBufferCompression, MAX_FILE_ID},
+    encryption::DiskBufferEncryptionKey,
+    ledger::{read_ledger_snapshot, LedgerLoadCreateError, LedgerSnapshot, LEDGER_VERSION},
+    reader::{ReaderError, RecordReader},
+    retention::{list_retained_data_files, RetainedDataFile},
+    Buffer, BufferError, DiskBufferConfigBuilder,
+};
+use crate::{buffer_usage_data::BufferUsageHandle, Bufferable, EventCount};
+
+/// Error that occurred while inspecting or repairing a disk buffer.
+#[derive(Debug, Snafu)]
+pub enum InspectError {
+    /// There is no disk buffer in the given directory.
+    #[snafu(display("no disk buffer found in '{}'", path.to_string_lossy()))]
+    NotFound { path: PathBuf },
+
+    /// The ledger could not be read, or is not compatible with this version of Vector.
+    #[snafu(display("failed to read ledger: {}", source))]
+    Ledger { source: LedgerLoadCreateError },
+
+    /// A general I/O error occurred.
+    #[snafu(display("I/O error: {}", source))]
+    Io { source: io::Error },
+
+    /// The records of an encrypted buffer were needed, but no encryption key was given.
+    #[snafu(display(
+        "the buffer is encrypted; its encryption key is required to read its records"
+    ))]
+    EncryptionKeyRequired,
+
+    /// The buffer could not be loaded to replay records into it.
+    #[snafu(display("failed to load buffer: {}", reason))]
+    Load { reason: String },
+
+    /// A replayed record could not be written to the buffer.
+    #[snafu(display("failed to write replayed record: {}", reason))]
+    Replay { reason: String },
+}
+
+/// Summary of the records in a single data file.
+#[derive(Clone, Debug)]
+pub struct DataFileSummary {
+    /// ID of the data file.
+    pub file_id: u16,
+    /// Path to the data file.
+    pub path: PathBuf,
+    /// Size of the data file, in bytes.
+    pub size: u64,
+    /// Number of valid records in the data file.
+    pub records: u64,
+    /// Number of valid records in the data file that have yet to be read and acknowledged.
+    pub unread_records: u64,
+    /// Number of events in the valid records of the data file.
+    ///
+    /// This is only known if the records could be decoded, which requires the encryption key for
+    /// encrypted buffers.
+    pub events: Option<u64>,
+    /// Number of events in the valid records of the data file that have yet to be read and
+    /// acknowledged.
+    pub unread_events: Option<u64>,
+    /// Number of valid records that could not be decoded.
+    pub undecodable_records: u64,
+    /// Number of bytes, from the start of the data file, taken up by valid records.
+    pub valid_len: u64,
+    /// The error that stopped the data file from being read past `valid_len`, if any.
+    ///
+    /// The reader skips the rest of a data file once it encounters a corrupted or partially-written
+    /// record, so any records past this point are lost.
+    pub error: Option<String>,
+}
+
+impl DataFileSummary {
+    /// Whether or not the data file has a corrupted tail that would be truncated by a repair.
+    pub fn is_corrupted(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// Summary of the retained records replayed into a buffer.
+#[derive(Clone, Debug, Default)]
+pub struct ReplaySummary {
+    /// Number of retained data files that were replayed.
+    pub data_files: u64,
+    /// Number of records that were replayed.
+    pub records: u64,
+    /// Number of events in the records that were replayed.
+    pub events: u64,
+}
+
+/// Offline inspector for a disk buffer.
+///
+/// The buffer is opened read-only: the ledger and data files are read directly, without loading
+/// the buffer, so it can be inspected even while a Vector process is using it.  The only exception
+/// is [`DiskBufferInspector::repair`], which requires that no Vector process is using the buffer.
+///
+/// Records are validated with the same logic as the reader of the buffer uses, so the inspector
+/// sees exactly the records that Vector would read.
+#[derive(Debug)]
+pub struct DiskBufferInspector {
+    data_dir: PathBuf,
+    ledger: LedgerSnapshot,
+    compression: DiskBufferCompression,
+    encryption_key: Option<DiskBufferEncryptionKey>,
+}
+
+impl DiskBufferInspector {
+    /// Opens the disk buffer in the given data directory.
+    ///
+    /// The encryption key is only needed to decode the records of an encrypted buffer.
+    ///
+    /// # Errors
+    ///
+    /// If there is no buffer in `data_dir`, if its ledger cannot be read or was written by a newer
+    /// version of Vector, or if the encryption key does not match the one the buffer was encrypted
+    /// with, an error variant will be returned describing the error.
+    pub async fn open(
+        data_dir: &Path,
+        encryption_key: Option<DiskBufferEncryptionKey>,
+    ) -> Result<Self, InspectError> {
+        let ledger = read_ledger_snapshot(data_dir)
+            .await
+            .context(LedgerSnafu)?
+            .ok_or_else(|| InspectError::NotFound {
+                path: data_dir.to_path_buf(),
+            })?;
+
+        if ledger.version > LEDGER_VERSION {
+            return Err(InspectError::Ledger {
+                source: LedgerLoadCreateError::Incompatible {
+                    reason: format!(
+                        "buffer format version {} is not supported (expected at most {})",
+                        ledger.version, LEDGER_VERSION
+                    ),
+                },
+            });
+        }
+
+        let compression = ledger.record_format().context(LedgerSnafu)?.compression;
+        if let Some(encryption_key) = encryption_key.as_ref() {
+            ledger
+                .check_encryption_key(encryption_key)
+                .context(LedgerSnafu)?;
+        }
+
+        Ok(Self {
+            data_dir: data_dir.to_path_buf(),
+            ledger,
+            compression,
+            encryption_key,
+        })
+    }
+
+    /// Gets the ledger state of the buffer, as of when it was opened.
+    pub fn ledger(&self) -> &LedgerSnapshot {
+        &self.ledger
+    }
+
+    /// Gets the compression applied to record payloads.
+    pub fn compression(&self) -> DiskBufferCompression {
+        self.compression
+    }
+
+    /// Whether or not records can be decoded.
+    ///
+    /// Records of an encrypted buffer can only be decoded when the encryption key was given.
+    pub fn can_decode(&self) -> bool {
+        !self.ledger.encrypted || self.encryption_key.is_some()
+    }
+
+    /// Gets the ID and path of each data file in the buffer.
+    ///
+    /// Data files are ordered from the oldest, which is the one the reader is on, to the newest,
+    /// which is the one the writer is on.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while listing the data directory, an error variant will be returned.
+    pub async fn data_files(&self) -> Result<Vec<(u16, PathBuf)>, InspectError> {
+        let mut data_files = Vec::new();
+        let mut entries = fs::read_dir(&self.data_dir).await.context(IoSnafu)?;
+        while let Some(entry) = entries.next_entry().await.context(IoSnafu)? {
+            let file_id = entry.file_name().to_str().and_then(|file_name| {
+                file_name
+                    .strip_prefix("buffer-data-")
+                    .and_then(|file_name| file_name.strip_suffix(".dat"))
+                    .and_then(|file_id| file_id.parse::<u16>().ok())
+            });
+            if let Some(file_id) = file_id {
+                data_files.push((file_id, entry.path()));
+            }
+        }
+
+        // File IDs wrap around, so order them by how far past the reader's current file they are.
+        let reader_file_id = u32::from(self.ledger.reader_current_data_file_id);
+        let max_file_id = u32::from(MAX_FILE_ID);
+        data_files.sort_by_key(|(file_id, _)| {
+            (u32::from(*file_id) + max_file_id - reader_file_id) % max_file_id
+        });
+
+        Ok(data_files)
+    }
+
+    /// Reads every record in the given data file, calling `visit` with the ID of each record that
+    /// could be decoded, along with the decoded record.
+    ///
+    /// Reading stops at the first corrupted or partially-written record, as it would for the reader
+    /// of the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while reading the data file, an error variant will be returned.
+    pub async fn scan_data_file<T, F>(
+        &self,
+        file_id: u16,
+        path: &Path,
+        visit: F,
+    ) -> Result<DataFileSummary, InspectError>
+    where
+        T: Bufferable,
+        F: FnMut(u64, T),
+    {
+        self.scan_data_file_inner(file_id, path, self.can_decode(), visit)
+            .await
+    }
+
+    async fn scan_data_file_inner<T, F>(
+        &self,
+        file_id: u16,
+        path: &Path,
+        decode: bool,
+        mut visit: F,
+    ) -> Result<DataFileSummary, InspectError>
+    where
+        T: Bufferable,
+        F: FnMut(u64, T),
+    {
+        let file = fs::File::open(path).await.context(IoSnafu)?;
+        let size = file.metadata().await.context(IoSnafu)?.len();
+        let mut reader =
+            RecordReader::<_, T>::new(file, self.compression, self.encryption_key.clone());
+
+        let mut summary = DataFileSummary {
+            file_id,
+            path: path.to_path_buf(),
+            size,
+            records: 0,
+            unread_records: 0,
+            events: decode.then(|| 0),
+            unread_events: decode.then(|| 0),
+            undecodable_records: 0,
+            valid_len: 0,
+            error: None,
+        };
+
+        loop {
+            // Data files are never appended to by an offline buffer, so we treat them as finalized,
+            // which lets the reader flag a partially-written record at the end of the file.
+            let token = match reader.try_next_record(true).await {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(ReaderError::Io { source }) => return Err(InspectError::Io { source }),
+                Err(e) => {
+                    summary.error = Some(e.to_string());
+                    break;
+                }
+            };
+
+            let record_id = token.record_id();
+            let is_unread = self.ledger.is_unread(record_id);
+            summary.records += 1;
+            if is_unread {
+                summary.unread_records += 1;
+            }
+            summary.valid_len +=
+                u64::try_from(token.record_bytes()).expect("record length should never exceed u64");
+
+            if !decode {
+                continue;
+            }
+
+            match reader.read_record(token) {
+                Ok(item) => {
+                    let events = u64::try_from(item.event_count())
+                        .expect("event count should never exceed u64");
+                    summary.events = summary.events.map(|n| n + events);
+                    if is_unread {
+                        summary.unread_events = summary.unread_events.map(|n| n + events);
+                    }
+                    visit(record_id, item);
+                }
+                // Like the reader, we skip over records that are valid but can't be decoded.
+                Err(_) => summary.undecodable_records += 1,
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Gets the data files retained after all of their records were acknowledged, from oldest to
+    /// newest.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while listing the retained data files, an error variant will be
+    /// returned.
+    pub async fn retained_data_files(&self) -> Result<Vec<RetainedDataFile>, InspectError> {
+        list_retained_data_files(&self.data_dir)
+            .await
+            .context(IoSnafu)
+    }
+
+    /// Reads the records in a retained data file.
+    ///
+    /// Like the reader of the buffer, records that are valid but can't be decoded are skipped, and
+    /// reading stops at the first corrupted record.  Retained data files aren't written to, so they
+    /// can be read while a Vector process is using the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If the buffer is encrypted and no encryption key was given, or if an I/O error occurs while
+    /// reading the retained data file, an error variant will be returned describing the error.
+    pub async fn read_retained_data_file<T>(
+        &self,
+        file: &RetainedDataFile,
+    ) -> Result<Vec<T>, InspectError>
+    where
+        T: Bufferable,
+    {
+        if !self.can_decode() {
+            return Err(InspectError::EncryptionKeyRequired);
+        }
+
+        // Data files retained before the buffer was migrated to another format are read in the
+        // format they were written in.  Migrations never change the encryption key, so any
+        // encrypted ones were encrypted with the key of the buffer.
+        let (compression, encryption_key) = match file.format {
+            Some(format) => (
+                format.compression,
+                self.encryption_key.clone().filter(|_| format.encrypted),
+            ),
+            None => (self.compression, self.encryption_key.clone()),
+        };
+        let data_file = fs::File::open(&file.path).await.context(IoSnafu)?;
+        let mut reader = RecordReader::<_, T>::new(data_file, compression, encryption_key);
+
+        let mut records = Vec::new();
+        loop {
+            let token = match reader.try_next_record(true).await {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(ReaderError::Io { source }) => return Err(InspectError::Io { source }),
+                Err(_) => break,
+            };
+            if let Ok(record) = reader.read_record(token) {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Deletes a retained data file, such as once it's been replayed.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while deleting the retained data file, other than it having already
+    /// been deleted, an error variant will be returned.
+    pub async fn remove_retained_data_file(
+        &self,
+        file: &RetainedDataFile,
+    ) -> Result<(), InspectError> {
+        match fs::remove_file(&file.path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(InspectError::Io { source }),
+        }
+    }
+
+    /// Replays the records in the retained data files written to since the given time, by writing
+    /// them into the buffer again, after any records that have yet to be read.
+    ///
+    /// Retained data files are replayed whole, so records written shortly before `since` may be
+    /// replayed as well.  Each replayed data file is deleted once its records have been written to
+    /// the buffer, as they're retained again once they've been acknowledged.  As with a repair, no
+    /// Vector process can be using the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If a Vector process is using the buffer, if the buffer is encrypted and no encryption key was
+    /// given, or if an I/O error occurs while reading a retained data file or writing its records,
+    /// an error variant will be returned describing the error.
+    pub async fn replay_retained<T>(&self, since: SystemTime) -> Result<ReplaySummary, InspectError>
+    where
+        T: Bufferable + Clone,
+    {
+        if !self.can_decode() {
+            return Err(InspectError::EncryptionKeyRequired);
+        }
+
+        let mut summary = ReplaySummary::default();
+        let retained = self
+            .retained_data_files()
+            .await?
+            .into_iter()
+            .filter(|file| file.modified >= since)
+            .collect::<Vec<_>>();
+        if retained.is_empty() {
+            return Ok(summary);
+        }
+
+        // Loading the buffer takes the same lock a Vector process holds while using it, so we can't
+        // write to the buffer out from under it.
+        let config = DiskBufferConfigBuilder::from_path(self.data_dir.clone())
+            .max_buffer_size(u64::MAX)
+            .compression(self.compression)
+            .encryption_key(self.encryption_key.clone())
+            .build()
+            .map_err(|e| InspectError::Load {
+                reason: e.to_string(),
+            })?;
+        let (mut writer, _reader, _acker, _ledger) =
+            Buffer::<T>::from_config_inner(config, BufferUsageHandle::noop())
+                .await
+                .map_err(|e| match e {
+                    BufferError::LedgerError { source } => InspectError::Ledger { source },
+                    e => InspectError::Load {
+                        reason: e.to_string(),
+                    },
+                })?;
+
+        for file in retained {
+            for record in self.read_retained_data_file::<T>(&file).await? {
+                let events = u64::try_from(record.event_count())
+                    .expect("event count should never exceed u64");
+                writer
+                    .write_record(record)
+                    .await
+                    .map_err(|e| InspectError::Replay {
+                        reason: e.to_string(),
+                    })?;
+                summary.records += 1;
+                summary.events += events;
+            }
+
+            writer.flush().await.context(IoSnafu)?;
+            self.remove_retained_data_file(&file).await?;
+            summary.data_files += 1;
+
+            info!(
+                retained_path = file.path.to_string_lossy().as_ref(),
+                "Replayed retained data file."
+            );
+        }
+
+        Ok(summary)
+    }
+
+    /// Repairs the buffer by truncating each data file with a corrupted tail to its valid records.
+    ///
+    /// The records in a corrupted tail are lost either way, as the reader skips the rest of a data
+    /// file once it encounters a corrupted record, but truncating them lets the buffer reclaim the
+    /// space they take up, and keeps the writer from having to skip the data file it was on.  The
+    /// ledger is left as-is, as the buffer resynchronizes it with the data files when it's loaded.
+    ///
+    /// Returns the summary of each data file that was truncated, as it was before the repair.
+    ///
+    /// # Errors
+    ///
+    /// If a Vector process is using the buffer, or if an I/O error occurs while reading or
+    /// truncating a data file, an error variant will be returned describing the error.
+    pub async fn repair<T>(&self) -> Result<Vec<DataFileSummary>, InspectError>
+    where
+        T: Bufferable,
+    {
+        // Hold the same lock a Vector process holds while using the buffer, so that we can't
+        // truncate a data file out from under it.
+        let mut lock = LockFile::open(&self.data_dir.join("buffer.lock")).context(IoSnafu)?;
+        if !lock.try_lock().context(IoSnafu)? {
+            return Err(InspectError::Ledger {
+                source: LedgerLoadCreateError::LedgerLockAlreadyHeld,
+            });
+        }
+
+        let mut repaired = Vec::new();
+        for (file_id, path) in self.data_files().await? {
+            let summary = self
+                .scan_data_file_inner::<T, _>(file_id, &path, false, |_, _| {})
+                .await?;
+            if !summary.is_corrupted() {
+                continue;
+            }
+
+            warn!(
+                data_file = path.to_string_lossy().as_ref(),
+                valid_len = summary.valid_len,
+                size = summary.size,
+                "Truncating corrupted data file."
+            );
+            let file = fs::OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .await
+                .context(IoSnafu)?;
+            file.set_len(summary.valid_len).await.context(IoSnafu)?;
+            file.sync_all().await.context(IoSnafu)?;
+
+            repaired.push(summary);
+        }
+
+        Ok(repaired)
+    }
+}
@@ -81,6 +81,14 @@ pub trait Filesystem: Send + Sync {
     /// If an I/O error occurred when attempting to delete the file, an error variant will be
     /// returned describing the underlying error.
     async fn delete_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Renames a file, replacing the destination file if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurred when attempting to rename the file, an error variant will be
+    /// returned describing the underlying error.
+    async fn rename_file(&self, from: &Path, to: &Path) -> io::Result<()>;
 }
 
 #[async_trait]
@@ -169,6 +177,10 @@ impl Filesystem for ProductionFilesystem {
     async fn delete_file(&self, path: &Path) -> io::Result<()> {
         tokio::fs::remove_file(path).await
     }
+
+    async fn rename_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        tokio::fs::rename(from, to).await
+    }
 }
 
 #[async_trait]
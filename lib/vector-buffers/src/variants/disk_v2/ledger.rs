@@ -1,7 +1,11 @@
 use std::{
+    collections::HashMap,
     fmt, io,
-    path::PathBuf,
-    sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering},
+        Arc, Weak,
+    },
     time::Instant,
 };
 
@@ -9,15 +13,18 @@ use bytecheck::CheckBytes;
 use bytes::BytesMut;
 use crossbeam_utils::atomic::AtomicCell;
 use fslock::LockFile;
-use rkyv::{with::Atomic, Archive, Serialize};
+use parking_lot::Mutex;
+use rkyv::{with::Atomic, AlignedVec, Archive, Serialize};
 use snafu::{ResultExt, Snafu};
 use tokio::{fs, io::AsyncWriteExt, sync::Notify};
 
 use super::{
     backed_archive::BackedArchive,
-    common::{DiskBufferConfig, MAX_FILE_ID},
+    common::{DiskBufferCompression, DiskBufferConfig, MAX_FILE_ID},
+    encryption::DiskBufferEncryptionKey,
     io::{AsyncFile, WritableMemoryMap},
-    ser::SerializeError,
+    retention::{prune_retained_data_files, retain_data_file},
+    ser::{try_as_archive, SerializeError},
     Filesystem,
 };
 use crate::buffer_usage_data::BufferUsageHandle;
@@ -65,8 +72,58 @@ pub enum LedgerLoadCreateError {
     /// buffers required for the serialization step.
     #[snafu(display("failed to serialize ledger to buffer: {}", reason))]
     FailedToSerialize { reason: String },
+
+    /// The ledger was written by an incompatible version of Vector.
+    ///
+    /// This occurs when the buffer was created by a newer version of Vector, with a newer version
+    /// of the buffer format, or if the compression stored in the ledger is unknown.
+    #[snafu(display("ledger not compatible: {}", reason))]
+    Incompatible { reason: String },
+
+    /// The buffer was created with a different compression than the one configured.
+    ///
+    /// Existing records must be migrated to the configured compression before the buffer can be
+    /// loaded, which is handled automatically when building the buffer from its configuration.
+    #[snafu(display(
+        "buffer uses {:?} compression, but {:?} compression is configured",
+        actual,
+        configured
+    ))]
+    CompressionMismatch {
+        actual: DiskBufferCompression,
+        configured: DiskBufferCompression,
+    },
+
+    /// The buffer is encrypted but no encryption key is configured, or vice versa.
+    #[snafu(display("{}", reason))]
+    EncryptionMismatch { reason: String },
+
+    /// The configured encryption key is not the key the buffer was created with.
+    #[snafu(display(
+        "configured encryption key does not match the key the buffer was encrypted with"
+    ))]
+    WrongEncryptionKey,
+
+    /// A reader is already attached to the consumer of a shared buffer.
+    ///
+    /// Each consumer has a single read position, so only one reader can use it at a time.
+    #[snafu(display(
+        "consumer '{}' of the shared buffer already has a reader attached",
+        consumer_id
+    ))]
+    ConsumerAlreadyAttached { consumer_id: String },
 }
 
+/// Version of the buffer format.
+///
+/// This is stored in the ledger, and must be bumped whenever the layout of the ledger, or of the
+/// records in data files, changes.
+///
+/// - version 1: original format, without any version stored in the ledger
+/// - version 2: adds record payload compression, and the tracking of the logical size of records
+/// - version 3: adds record payload encryption
+pub const LEDGER_VERSION: u32 = 3;
+
 /// Ledger state.
 ///
 /// Stores the relevant information related to both the reader and writer.  Gets serailized and
@@ -81,7 +138,9 @@ pub enum LedgerLoadCreateError {
 ///
 /// Doing so will change the serialized representation.  This will break things.
 ///
-/// Do not do any of the listed things unless you _absolutely_ know what you're doing. :)
+/// Do not do any of the listed things unless you _absolutely_ know what you're doing. :)  If you
+/// do, bump [`LEDGER_VERSION`] and teach `try_upgrade_ledger` how to upgrade from the previous
+/// layout, as was done when going from version 2 to version 3.
 #[derive(Archive, Serialize, Debug)]
 #[archive_attr(derive(CheckBytes, Debug))]
 pub struct LedgerState {
@@ -97,10 +156,30 @@ pub struct LedgerState {
     /// The last record ID read by the reader.
     #[with(Atomic)]
     reader_last_record_id: AtomicU64,
+    /// The version of the buffer format.
+    version: u32,
+    /// The compression applied to record payloads, as given by `DiskBufferCompression::as_u32`.
+    compression: u32,
+    /// The total logical size of all records written, before compression.
+    ///
+    /// This is a running total that wraps around, which, together with `reader_logical_bytes`,
+    /// tracks the logical size of the unread records in the buffer across restarts.
+    #[with(Atomic)]
+    writer_logical_bytes: AtomicU64,
+    /// The total logical size of all records read and acknowledged, before compression.
+    #[with(Atomic)]
+    reader_logical_bytes: AtomicU64,
+    /// Whether or not record payloads are encrypted: 0 for plaintext, 1 for AES-256-GCM.
+    encryption: u32,
+    /// Fingerprint of the encryption key, or all zeroes if record payloads are not encrypted.
+    key_fingerprint: [u8; 32],
 }
 
-impl Default for LedgerState {
-    fn default() -> Self {
+impl LedgerState {
+    fn new(
+        compression: DiskBufferCompression,
+        encryption_key: Option<&DiskBufferEncryptionKey>,
+    ) -> Self {
         Self {
             // First record written is always 1, so that our default of 0 for
             // `reader_last_record_id` ensures we start up in a state of "alright, waiting to read
@@ -109,11 +188,157 @@ impl Default for LedgerState {
             writer_current_data_file_id: AtomicU16::new(0),
             reader_current_data_file_id: AtomicU16::new(0),
             reader_last_record_id: AtomicU64::new(0),
+            version: LEDGER_VERSION,
+            compression: compression.as_u32(),
+            writer_logical_bytes: AtomicU64::new(0),
+            reader_logical_bytes: AtomicU64::new(0),
+            encryption: u32::from(encryption_key.is_some()),
+            key_fingerprint: encryption_key.map_or([0; 32], |key| *key.fingerprint()),
+        }
+    }
+
+    /// Creates the ledger state for a buffer created before the buffer format was versioned.
+    ///
+    /// Records in those buffers were never compressed, and their logical size is unknown, so it's
+    /// only tracked from here on.
+    fn from_v1(legacy: &ArchivedLegacyLedgerState) -> Self {
+        Self {
+            writer_next_record_id: AtomicU64::new(
+                legacy.writer_next_record_id.load(Ordering::Acquire),
+            ),
+            writer_current_data_file_id: AtomicU16::new(
+                legacy.writer_current_data_file_id.load(Ordering::Acquire),
+            ),
+            reader_current_data_file_id: AtomicU16::new(
+                legacy.reader_current_data_file_id.load(Ordering::Acquire),
+            ),
+            reader_last_record_id: AtomicU64::new(
+                legacy.reader_last_record_id.load(Ordering::Acquire),
+            ),
+            ..Self::new(DiskBufferCompression::None, None)
+        }
+    }
+
+    /// Creates the ledger state for a buffer created with version 2 of the buffer format.
+    ///
+    /// Records in those buffers were never encrypted.
+    fn from_v2(legacy: &ArchivedLedgerStateV2) -> Self {
+        Self {
+            writer_next_record_id: AtomicU64::new(
+                legacy.writer_next_record_id.load(Ordering::Acquire),
+            ),
+            writer_current_data_file_id: AtomicU16::new(
+                legacy.writer_current_data_file_id.load(Ordering::Acquire),
+            ),
+            reader_current_data_file_id: AtomicU16::new(
+                legacy.reader_current_data_file_id.load(Ordering::Acquire),
+            ),
+            reader_last_record_id: AtomicU64::new(
+                legacy.reader_last_record_id.load(Ordering::Acquire),
+            ),
+            compression: legacy.compression,
+            writer_logical_bytes: AtomicU64::new(
+                legacy.writer_logical_bytes.load(Ordering::Acquire),
+            ),
+            reader_logical_bytes: AtomicU64::new(
+                legacy.reader_logical_bytes.load(Ordering::Acquire),
+            ),
+            ..Self::new(DiskBufferCompression::None, None)
         }
     }
 }
 
+/// Ledger state of version 1 of the buffer format.
+///
+/// Only used to upgrade the ledger of existing buffers to the current version.
+///
+/// # Warning
+///
+/// This must stay identical to the layout of `LedgerState` as of version 1.
+#[derive(Archive, Serialize, Debug, Default)]
+#[archive_attr(derive(CheckBytes, Debug))]
+pub(super) struct LegacyLedgerState {
+    #[with(Atomic)]
+    pub(super) writer_next_record_id: AtomicU64,
+    #[with(Atomic)]
+    pub(super) writer_current_data_file_id: AtomicU16,
+    #[with(Atomic)]
+    pub(super) reader_current_data_file_id: AtomicU16,
+    #[with(Atomic)]
+    pub(super) reader_last_record_id: AtomicU64,
+}
+
+/// Ledger state of version 2 of the buffer format.
+///
+/// Only used to upgrade the ledger of existing buffers to the current version.
+///
+/// # Warning
+///
+/// This must stay identical to the layout of `LedgerState` as of version 2.
+#[derive(Archive, Serialize, Debug, Default)]
+#[archive_attr(derive(CheckBytes, Debug))]
+pub(super) struct LedgerStateV2 {
+    #[with(Atomic)]
+    pub(super) writer_next_record_id: AtomicU64,
+    #[with(Atomic)]
+    pub(super) writer_current_data_file_id: AtomicU16,
+    #[with(Atomic)]
+    pub(super) reader_current_data_file_id: AtomicU16,
+    #[with(Atomic)]
+    pub(super) reader_last_record_id: AtomicU64,
+    pub(super) version: u32,
+    pub(super) compression: u32,
+    #[with(Atomic)]
+    pub(super) writer_logical_bytes: AtomicU64,
+    #[with(Atomic)]
+    pub(super) reader_logical_bytes: AtomicU64,
+}
+
 impl ArchivedLedgerState {
+    /// Gets the version of the buffer format.
+    pub(super) fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Gets the compression applied to record payloads, if it's a known compression.
+    pub(super) fn compression(&self) -> Option<DiskBufferCompression> {
+        DiskBufferCompression::from_u32(self.compression)
+    }
+
+    /// Whether or not record payloads are encrypted.
+    pub(super) fn is_encrypted(&self) -> bool {
+        self.encryption != 0
+    }
+
+    fn get_outstanding_logical_bytes(&self) -> u64 {
+        self.writer_logical_bytes
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.reader_logical_bytes.load(Ordering::Acquire))
+    }
+
+    fn increment_writer_logical_bytes(&self, amount: u64) {
+        self.writer_logical_bytes
+            .fetch_add(amount, Ordering::AcqRel);
+    }
+
+    fn increment_reader_logical_bytes(&self, amount: u64) {
+        self.reader_logical_bytes
+            .fetch_add(amount, Ordering::AcqRel);
+    }
+
+    fn get_writer_logical_bytes(&self) -> u64 {
+        self.writer_logical_bytes.load(Ordering::Acquire)
+    }
+
+    fn get_reader_logical_bytes(&self) -> u64 {
+        self.reader_logical_bytes.load(Ordering::Acquire)
+    }
+
+    fn reset_logical_bytes(&self) {
+        let written = self.writer_logical_bytes.load(Ordering::Acquire);
+        self.reader_logical_bytes.store(written, Ordering::Release);
+    }
+
     fn get_current_writer_file_id(&self) -> u16 {
         self.writer_current_data_file_id.load(Ordering::Acquire)
     }
@@ -166,6 +391,16 @@ impl ArchivedLedgerState {
             .fetch_add(amount, Ordering::AcqRel);
     }
 
+    /// Sets the last record ID read, and the logical size of all records read, by the reader.
+    ///
+    /// For a shared buffer, this is how the read position of the slowest consumer is tracked.
+    fn set_reader_position(&self, last_record_id: u64, logical_bytes: u64) {
+        self.reader_last_record_id
+            .store(last_record_id, Ordering::Release);
+        self.reader_logical_bytes
+            .store(logical_bytes, Ordering::Release);
+    }
+
     #[cfg(test)]
     pub unsafe fn unsafe_set_writer_next_record_id(&self, id: u64) {
         // UNSAFETY:
@@ -199,8 +434,113 @@ impl ArchivedLedgerState {
     }
 }
 
-/// Tracks the internal state of the buffer.
-pub struct Ledger<FS>
+/// Read position of a consumer of a shared buffer.
+///
+/// Each consumer of a shared buffer reads, and acknowledges, records independently of the other
+/// consumers, so each one has its own read position, stored in its own file next to the ledger.
+/// The read position in the ledger state then tracks the slowest consumer instead, which is to say
+/// which data files are still needed by at least one consumer.
+///
+/// # Warning
+///
+/// The same warnings apply as for [`LedgerState`]: changing this struct changes its serialized
+/// representation.
+#[derive(Archive, Serialize, Debug)]
+#[archive_attr(derive(CheckBytes, Debug))]
+pub struct ConsumerState {
+    /// The current data file ID being read from.
+    #[with(Atomic)]
+    reader_current_data_file_id: AtomicU16,
+    /// The last record ID read by the consumer.
+    #[with(Atomic)]
+    reader_last_record_id: AtomicU64,
+    /// The total logical size of all records read and acknowledged by the consumer, before
+    /// compression.
+    #[with(Atomic)]
+    reader_logical_bytes: AtomicU64,
+}
+
+impl ConsumerState {
+    /// Creates the read position of a new consumer, starting at the oldest record in the buffer.
+    fn starting_at(state: &ArchivedLedgerState) -> Self {
+        Self {
+            reader_current_data_file_id: AtomicU16::new(state.get_current_reader_file_id()),
+            reader_last_record_id: AtomicU64::new(state.get_last_reader_record_id()),
+            reader_logical_bytes: AtomicU64::new(state.get_reader_logical_bytes()),
+        }
+    }
+}
+
+impl ArchivedConsumerState {
+    fn get_current_reader_file_id(&self) -> u16 {
+        self.reader_current_data_file_id.load(Ordering::Acquire)
+    }
+
+    fn get_offset_reader_file_id(&self, offset: u16) -> u16 {
+        self.get_current_reader_file_id().wrapping_add(offset) % MAX_FILE_ID
+    }
+
+    fn increment_reader_file_id(&self) -> u16 {
+        let value = (self.get_current_reader_file_id() + 1) % MAX_FILE_ID;
+        self.reader_current_data_file_id
+            .store(value, Ordering::Release);
+        value
+    }
+
+    fn get_last_reader_record_id(&self) -> u64 {
+        self.reader_last_record_id.load(Ordering::Acquire)
+    }
+
+    fn increment_last_reader_record_id(&self, amount: u64) {
+        self.reader_last_record_id
+            .fetch_add(amount, Ordering::AcqRel);
+    }
+
+    fn get_reader_logical_bytes(&self) -> u64 {
+        self.reader_logical_bytes.load(Ordering::Acquire)
+    }
+
+    fn increment_reader_logical_bytes(&self, amount: u64) {
+        self.reader_logical_bytes
+            .fetch_add(amount, Ordering::AcqRel);
+    }
+
+    fn reset_logical_bytes(&self, written: u64) {
+        self.reader_logical_bytes.store(written, Ordering::Release);
+    }
+}
+
+/// A consumer of a shared buffer.
+struct Consumer<FS>
+where
+    FS: Filesystem,
+{
+    // Read position of the consumer.
+    state: BackedArchive<FS::MutableMemoryMap, ConsumerState>,
+    // Whether or not a reader is currently attached to the consumer.
+    attached: AtomicBool,
+}
+
+impl<FS> Consumer<FS>
+where
+    FS: Filesystem,
+{
+    fn state(&self) -> &ArchivedConsumerState {
+        self.state.get_archive_ref()
+    }
+}
+
+/// Progress of a single reader of the buffer.
+#[derive(Debug, Default)]
+struct ReaderProgress {
+    // Notifier for writer-related progress.
+    writer_notify: Notify,
+    // The total size, in bytes, of the records yet to be read by a consumer of a shared buffer.
+    unread_bytes: AtomicU64,
+}
+
+/// State of the buffer shared by the writer and every reader.
+struct LedgerInner<FS>
 where
     FS: Filesystem,
 {
@@ -211,19 +551,80 @@ where
     // Ledger state.
     state: BackedArchive<FS::MutableMemoryMap, LedgerState>,
     // The total size, in bytes, of all unread records in the buffer.
+    //
+    // For a shared buffer, this is instead the total size of all data files, as records are only
+    // unread by every consumer once the data file they're in can be deleted.
     total_buffer_size: AtomicU64,
     // Notifier for reader-related progress.
     reader_notify: Notify,
-    // Notifier for writer-related progress.
-    writer_notify: Notify,
     // Tracks when writer has fully shutdown.
     writer_done: AtomicBool,
+    // Last flush of all unflushed files: ledger, data file, etc.
+    last_flush: AtomicCell<Instant>,
+    // Consumers of a shared buffer, by ID.
+    consumers: tokio::sync::Mutex<HashMap<String, Arc<Consumer<FS>>>>,
+    // Progress of every reader, which the writer must wake up when it makes progress.
+    readers: Mutex<Vec<Weak<ReaderProgress>>>,
+}
+
+impl<FS> LedgerInner<FS>
+where
+    FS: Filesystem,
+{
+    fn is_shared(&self) -> bool {
+        !self.config.consumers.is_empty()
+    }
+
+    fn state(&self) -> &ArchivedLedgerState {
+        self.state.get_archive_ref()
+    }
+
+    /// Gets the progress of every reader that is still around.
+    fn readers(&self) -> Vec<Arc<ReaderProgress>> {
+        let mut readers = self.readers.lock();
+        readers.retain(|reader| reader.strong_count() > 0);
+        readers.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    fn increment_total_buffer_size(&self, amount: u64) {
+        let last_total_buffer_size = self.total_buffer_size.fetch_add(amount, Ordering::AcqRel);
+        trace!(
+            previous_buffer_size = last_total_buffer_size,
+            new_buffer_size = last_total_buffer_size + amount,
+            "Updated buffer size.",
+        );
+    }
+
+    fn decrement_total_buffer_size(&self, amount: u64) {
+        let last_total_buffer_size = self.total_buffer_size.fetch_sub(amount, Ordering::AcqRel);
+        trace!(
+            previous_buffer_size = last_total_buffer_size,
+            new_buffer_size = last_total_buffer_size - amount,
+            "Updated buffer size.",
+        );
+    }
+}
+
+/// Tracks the internal state of the buffer.
+///
+/// Each reader of a shared buffer has its own `Ledger`, which tracks the read position of its
+/// consumer, while sharing the rest of the state with the `Ledger` of the writer.
+pub struct Ledger<FS>
+where
+    FS: Filesystem,
+{
+    // State shared by the writer and every reader.
+    inner: Arc<LedgerInner<FS>>,
+    // The consumer whose read position is tracked, if the buffer is shared.
+    consumer: Option<Arc<Consumer<FS>>>,
+    // Progress of the reader.
+    progress: Arc<ReaderProgress>,
+    // Whether or not the consumer no longer gets new records, even if the writer is still open.
+    consumer_closed: AtomicBool,
     // Number of pending record acknowledgements that have yeet to be consumed by the reader.
     pending_acks: AtomicU64,
     // The file ID offset of the reader past the acknowledged reader file ID.
     unacked_reader_file_id_offset: AtomicU16,
-    // Last flush of all unflushed files: ledger, data file, etc.
-    last_flush: AtomicCell<Instant>,
     // Tracks usage data about the buffer.
     usage_handle: BufferUsageHandle,
 }
@@ -232,21 +633,54 @@ impl<FS> Ledger<FS>
 where
     FS: Filesystem,
 {
+    fn with_inner(
+        inner: Arc<LedgerInner<FS>>,
+        consumer: Option<Arc<Consumer<FS>>>,
+        usage_handle: BufferUsageHandle,
+    ) -> Self {
+        let progress = Arc::new(ReaderProgress::default());
+        {
+            // Consumers start out with every record in the buffer left to read, and seek past the
+            // ones they've already read.
+            let mut readers = inner.readers.lock();
+            progress.unread_bytes.store(
+                inner.total_buffer_size.load(Ordering::Acquire),
+                Ordering::Release,
+            );
+            readers.push(Arc::downgrade(&progress));
+        }
+
+        Self {
+            inner,
+            consumer,
+            progress,
+            consumer_closed: AtomicBool::new(false),
+            pending_acks: AtomicU64::new(0),
+            unacked_reader_file_id_offset: AtomicU16::new(0),
+            usage_handle,
+        }
+    }
+
     /// Gets the configuration for the buffer that this ledger represents.
     pub fn config(&self) -> &DiskBufferConfig<FS> {
-        &self.config
+        &self.inner.config
     }
 
     /// Gets the filesystem configured for this buffer.
     pub fn filesystem(&self) -> &FS {
-        &self.config.filesystem
+        &self.inner.config.filesystem
     }
 
     /// Gets the internal ledger state.
     ///
     /// This is the information persisted to disk.
     pub fn state(&self) -> &ArchivedLedgerState {
-        self.state.get_archive_ref()
+        self.inner.state()
+    }
+
+    /// Whether or not this ledger tracks a consumer of a shared buffer.
+    pub(super) fn is_consumer(&self) -> bool {
+        self.consumer.is_some()
     }
 
     /// Gets the total number of unread records in the buffer.
@@ -256,7 +690,7 @@ where
     /// would return `7`.
     pub fn get_total_records(&self) -> u64 {
         let next_writer_id = self.state().get_next_writer_record_id();
-        let last_reader_id = self.state().get_last_reader_record_id();
+        let last_reader_id = self.get_last_reader_record_id();
 
         next_writer_id.wrapping_sub(last_reader_id) - 1
     }
@@ -268,28 +702,63 @@ where
     /// leads to behavior where writes and reads will change this value only by the size of the
     /// records being written and read, while data files on disk will grow incrementally, and be
     /// deleted in full.
+    ///
+    /// For a consumer of a shared buffer, this is the number of bytes for the records it has yet
+    /// to read.
     pub(super) fn get_total_buffer_size(&self) -> u64 {
-        self.total_buffer_size.load(Ordering::Acquire)
+        if self.is_consumer() {
+            return self.progress.unread_bytes.load(Ordering::Acquire);
+        }
+
+        self.inner.total_buffer_size.load(Ordering::Acquire)
     }
 
     /// Increments the total number of bytes for all unread records in the buffer.
     pub(super) fn increment_total_buffer_size(&self, amount: u64) {
-        let last_total_buffer_size = self.total_buffer_size.fetch_add(amount, Ordering::AcqRel);
-        trace!(
-            previous_buffer_size = last_total_buffer_size,
-            new_buffer_size = last_total_buffer_size + amount,
-            "Updated buffer size.",
-        );
+        if !self.inner.is_shared() {
+            self.inner.increment_total_buffer_size(amount);
+            return;
+        }
+
+        // Consumers must see the increment at the same time as the total, so that a consumer that
+        // attaches in the meantime doesn't count the bytes twice.
+        let readers = self.inner.readers.lock();
+        self.inner.increment_total_buffer_size(amount);
+        for reader in readers.iter().filter_map(Weak::upgrade) {
+            reader.unread_bytes.fetch_add(amount, Ordering::AcqRel);
+        }
     }
 
     /// Decrements the total number of bytes for all unread records in the buffer.
     pub(super) fn decrement_total_buffer_size(&self, amount: u64) {
-        let last_total_buffer_size = self.total_buffer_size.fetch_sub(amount, Ordering::AcqRel);
-        trace!(
-            previous_buffer_size = last_total_buffer_size,
-            new_buffer_size = last_total_buffer_size - amount,
-            "Updated buffer size.",
-        );
+        if self.is_consumer() {
+            // The data files of a shared buffer are only removed from the total buffer size once
+            // they're deleted, after every consumer is done with them.
+            let _ = self.progress.unread_bytes.fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |unread_bytes| Some(unread_bytes.saturating_sub(amount)),
+            );
+            return;
+        }
+
+        self.inner.decrement_total_buffer_size(amount);
+    }
+
+    /// Gets the last record ID read, and acknowledged, by the reader.
+    pub(super) fn get_last_reader_record_id(&self) -> u64 {
+        match &self.consumer {
+            Some(consumer) => consumer.state().get_last_reader_record_id(),
+            None => self.state().get_last_reader_record_id(),
+        }
+    }
+
+    /// Increments the last record ID read, and acknowledged, by the reader.
+    pub(super) fn increment_last_reader_record_id(&self, amount: u64) {
+        match &self.consumer {
+            Some(consumer) => consumer.state().increment_last_reader_record_id(amount),
+            None => self.state().increment_last_reader_record_id(amount),
+        }
     }
 
     /// Gets the current reader file ID.
@@ -299,7 +768,10 @@ where
     /// would start reading from if the process crashed or was abruptly stopped.
     pub fn get_current_reader_file_id(&self) -> u16 {
         let unacked_offset = self.unacked_reader_file_id_offset.load(Ordering::Acquire);
-        self.state().get_offset_reader_file_id(unacked_offset)
+        match &self.consumer {
+            Some(consumer) => consumer.state().get_offset_reader_file_id(unacked_offset),
+            None => self.state().get_offset_reader_file_id(unacked_offset),
+        }
     }
 
     /// Gets the current writer file ID.
@@ -344,18 +816,43 @@ where
 
     /// Gets the data file path for an arbitrary file ID.
     pub fn get_data_file_path(&self, file_id: u16) -> PathBuf {
-        self.config
+        self.config()
             .data_dir
             .join(format!("buffer-data-{}.dat", file_id))
     }
 
+    /// Releases a data file whose records have all been acknowledged.
+    ///
+    /// The data file is deleted, unless the buffer retains acknowledged data files, in which case
+    /// it's moved into the retained data files instead.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while deleting or moving the data file, an error variant will be
+    /// returned describing the error.
+    pub(super) async fn release_data_file(&self, data_file_path: &Path) -> io::Result<()> {
+        match &self.config().retention {
+            Some(retention) => {
+                retain_data_file(&self.config().data_dir, data_file_path, retention).await
+            }
+            None => self.filesystem().delete_file(data_file_path).await,
+        }
+    }
+
+    /// Gets the path of the file storing the read position of the given consumer.
+    fn get_consumer_file_path(&self, consumer_id: &str) -> PathBuf {
+        self.config()
+            .data_dir
+            .join(format!("buffer-consumer-{}.db", consumer_id))
+    }
+
     /// Waits for a signal from the reader that progress has been made.
     ///
     /// This will only occur when a record is read, which may allow enough space (below the maximum
     /// configured buffer size) for a write to occur, or similarly, when a data file is deleted.
     #[cfg_attr(test, instrument(skip(self), level = "trace"))]
     pub async fn wait_for_reader(&self) {
-        self.reader_notify.notified().await;
+        self.inner.reader_notify.notified().await;
     }
 
     /// Waits for a signal from the writer that progress has been made.
@@ -363,33 +860,67 @@ where
     /// This will occur when a record is written, or when a new data file is created.
     #[cfg_attr(test, instrument(skip(self), level = "trace"))]
     pub async fn wait_for_writer(&self) {
-        self.writer_notify.notified().await;
+        self.progress.writer_notify.notified().await;
     }
 
     /// Notifies all tasks waiting on progress by the reader.
     #[cfg_attr(test, instrument(skip(self), level = "trace"))]
     pub fn notify_reader_waiters(&self) {
-        self.reader_notify.notify_one();
+        self.inner.reader_notify.notify_one();
     }
 
     /// Notifies all tasks waiting on progress by the writer.
     #[cfg_attr(test, instrument(skip(self), level = "trace"))]
     pub fn notify_writer_waiters(&self) {
-        self.writer_notify.notify_one();
+        if !self.inner.is_shared() {
+            self.progress.writer_notify.notify_one();
+            return;
+        }
+
+        for reader in self.inner.readers() {
+            reader.writer_notify.notify_one();
+        }
     }
 
     /// Tracks the statistics of a successful write.
-    pub fn track_write(&self, event_count: u64, record_size: u64) {
+    ///
+    /// The record size is the size of the record on disk, while the logical size is the size of
+    /// the encoded record before compression.
+    pub fn track_write(&self, event_count: u64, record_size: u64, logical_size: u64) {
         self.increment_total_buffer_size(record_size);
+        self.state().increment_writer_logical_bytes(logical_size);
         self.usage_handle
             .increment_received_event_count_and_byte_size(event_count, record_size);
+        self.usage_handle
+            .increment_received_logical_byte_size(logical_size);
     }
 
     /// Tracks the statistics of multiple successful reads.
-    pub fn track_reads(&self, event_count: u64, total_record_size: u64) {
+    pub fn track_reads(&self, event_count: u64, total_record_size: u64, total_logical_size: u64) {
         self.decrement_total_buffer_size(total_record_size);
+        match &self.consumer {
+            Some(consumer) => consumer
+                .state()
+                .increment_reader_logical_bytes(total_logical_size),
+            None => self
+                .state()
+                .increment_reader_logical_bytes(total_logical_size),
+        }
         self.usage_handle
             .increment_sent_event_count_and_byte_size(event_count, total_record_size);
+        self.usage_handle
+            .increment_sent_logical_byte_size(total_logical_size);
+    }
+
+    /// Gets the logical size of the records yet to be read and acknowledged, before compression.
+    fn get_outstanding_logical_bytes(&self) -> u64 {
+        match &self.consumer {
+            Some(consumer) => self
+                .state()
+                .get_writer_logical_bytes()
+                .wrapping_sub(consumer.state().get_reader_logical_bytes()),
+            None => self.state().get_outstanding_logical_bytes(),
+        }
     }
 
     /// Marks the writer as finished.
@@ -397,14 +928,28 @@ where
     /// If the writer was not yet marked done, `false` is returned.  Otherwise, `true` is returned,
     /// and the caller should handle any necessary logic for closing the writer.
     pub fn mark_writer_done(&self) -> bool {
-        self.writer_done
+        self.inner
+            .writer_done
             .compare_exchange_weak(false, true, Ordering::SeqCst, Ordering::SeqCst)
             .is_ok()
     }
 
     /// Returns `true` if the writer was marked as done.
+    ///
+    /// For a consumer of a shared buffer, this is also the case once the consumer was closed.
     pub fn is_writer_done(&self) -> bool {
-        self.writer_done.load(Ordering::Acquire)
+        self.inner.writer_done.load(Ordering::Acquire)
+            || self.consumer_closed.load(Ordering::Acquire)
+    }
+
+    /// Marks the consumer as no longer getting new records.
+    ///
+    /// The reader of the consumer then finishes once it has read the records already in the buffer,
+    /// as it would once the writer is closed.
+    pub(super) fn close_consumer(&self) {
+        if !self.consumer_closed.swap(true, Ordering::AcqRel) {
+            self.progress.writer_notify.notify_one();
+        }
     }
 
     /// Increments the pending acknowledgement counter by the given amount.
@@ -451,7 +996,10 @@ where
     /// Since the unacked file ID is simply a relative offset to the acked file ID, we decrement it
     /// here to keep the "current" file ID stable.
     pub fn increment_acked_reader_file_id(&self) {
-        let new_reader_file_id = self.state().increment_reader_file_id();
+        let new_reader_file_id = match &self.consumer {
+            Some(consumer) => consumer.state().increment_reader_file_id(),
+            None => self.state().increment_reader_file_id(),
+        };
 
         // We ignore the return value because when the value is already zero, we don't want to do an
         // update, so we return `None`, which causes `fetch_update` to return `Err`.  It's not
@@ -483,9 +1031,10 @@ where
     /// will get a return value of `true`, and the others will receive `false`.  The caller that
     /// receives `true` is responsible for flushing the necessary files.
     pub fn should_flush(&self) -> bool {
-        let last_flush = self.last_flush.load();
-        if last_flush.elapsed() > self.config.flush_interval
+        let last_flush = self.inner.last_flush.load();
+        if last_flush.elapsed() > self.config().flush_interval
             && self
+                .inner
                 .last_flush
                 .compare_exchange(last_flush, Instant::now())
                 .is_ok()
@@ -498,6 +1047,8 @@ where
 
     /// Flushes the memory-mapped file backing the ledger to disk.
     ///
+    /// For a consumer of a shared buffer, the file backing its read position is flushed as well.
+    ///
     /// This operation is synchronous.
     ///
     /// # Errors
@@ -505,7 +1056,11 @@ where
     /// If there is an error while flushing the ledger to disk, an error variant will be returned
     /// describing the error.
     pub(super) fn flush(&self) -> io::Result<()> {
-        self.state.get_backing_ref().flush()
+        self.inner.state.get_backing_ref().flush()?;
+        match &self.consumer {
+            Some(consumer) => consumer.state.get_backing_ref().flush(),
+            None => Ok(()),
+        }
     }
 
     /// Synchronizes the record count and total size of the buffer with buffer usage data.
@@ -521,6 +1076,19 @@ where
                 initial_buffer_events,
                 initial_buffer_size,
             );
+
+        // The logical size of records that were lost, due to corruption or otherwise, is never
+        // read back, so we drop whatever is left over once the buffer is empty.
+        if initial_buffer_events == 0 {
+            match &self.consumer {
+                Some(consumer) => consumer
+                    .state()
+                    .reset_logical_bytes(self.state().get_writer_logical_bytes()),
+                None => self.state().reset_logical_bytes(),
+            }
+        }
+        self.usage_handle
+            .increment_received_logical_byte_size(self.get_outstanding_logical_bytes());
     }
 
     pub fn track_dropped_events(&self, count: u64) {
@@ -547,6 +1115,10 @@ where
     /// If the ledger file does not yet exist, a default ledger state will be created and persisted
     /// to disk.  Otherwise, the ledger file on disk will be loaded and verified.
     ///
+    /// For a shared buffer, the read position of each of its configured consumers is loaded, or
+    /// created, as well.  The returned ledger is then only meant for the writer, as the readers of a
+    /// shared buffer each use the ledger returned by [`Ledger::attach_consumer`].
+    ///
     /// # Errors
     ///
     /// If there is an error during either serialization of the new, default ledger state, or
@@ -591,7 +1163,10 @@ where
             debug!("Ledger file empty.  Initializing with default ledger state.");
             let mut buf = BytesMut::new();
             loop {
-                match BackedArchive::from_value(&mut buf, LedgerState::default()) {
+                match BackedArchive::from_value(
+                    &mut buf,
+                    LedgerState::new(config.compression, config.encryption_key.as_ref()),
+                ) {
                     Ok(archive) => {
                         ledger_handle
                             .write_all(archive.get_backing_ref())
@@ -609,6 +1184,11 @@ where
 
             // Now sync the file to ensure everything is on disk before proceeding.
             ledger_handle.sync_all().await.context(IoSnafu)?;
+        } else {
+            // The buffer already exists, but it may have been created by a version of Vector that
+            // used an older version of the buffer format, so upgrade the ledger if need be.
+            drop(ledger_handle);
+            try_upgrade_ledger(&config.filesystem, &ledger_path).await?;
         }
 
         // Load the ledger state by memory-mapping the ledger file, and zero-copy deserializing our
@@ -629,28 +1209,46 @@ where
             }
         };
 
+        check_ledger_compatibility(
+            ledger_state.get_archive_ref(),
+            config.compression,
+            config.encryption_key.as_ref(),
+        )?;
+
         // Create the ledger object, and synchronize the buffer statistics with the buffer usage
         // handle.  This handles making sure we account for the starting size of the buffer, and
         // what not.
-        let mut ledger = Ledger {
+        let consumers = config.consumers.clone();
+        let inner = LedgerInner {
             config,
             ledger_lock,
             state: ledger_state,
             total_buffer_size: AtomicU64::new(0),
             reader_notify: Notify::new(),
-            writer_notify: Notify::new(),
             writer_done: AtomicBool::new(false),
-            pending_acks: AtomicU64::new(0),
-            unacked_reader_file_id_offset: AtomicU16::new(0),
             last_flush: AtomicCell::new(Instant::now()),
-            usage_handle,
+            consumers: tokio::sync::Mutex::new(HashMap::new()),
+            readers: Mutex::new(Vec::new()),
         };
+        let ledger = Ledger::with_inner(Arc::new(inner), None, usage_handle);
         ledger.update_buffer_size().await?;
 
+        // Retained data files are otherwise only pruned when another data file is retained, so
+        // catch up on any that aged past the retention while the buffer wasn't loaded, or while
+        // nothing was acknowledged.
+        if let Some(retention) = &ledger.config().retention {
+            prune_retained_data_files(&ledger.config().data_dir, retention)
+                .await
+                .context(IoSnafu)?;
+        }
+        if !consumers.is_empty() {
+            ledger.sync_consumers(&consumers).await?;
+        }
+
         Ok(ledger)
     }
 
-    async fn update_buffer_size(&mut self) -> Result<(), LedgerLoadCreateError> {
+    async fn update_buffer_size(&self) -> Result<(), LedgerLoadCreateError> {
         // Under normal operation, the reader and writer maintain a consistent state within the
         // ledger.  However, due to the nature of how we update the ledger, process crashes could
         // lead to missed updates as we execute reads and writes as non-atomic units of execution:
@@ -668,7 +1266,9 @@ where
         // When the reader does any necessary seeking to get to the record it left off on, it will
         // adjust the "total buffer size" downwards for each record it runs through, leaving "total
         // buffer size" at the correct value.
-        let mut dat_reader = fs::read_dir(&self.config.data_dir).await.context(IoSnafu)?;
+        let mut dat_reader = fs::read_dir(&self.config().data_dir)
+            .await
+            .context(IoSnafu)?;
 
         let mut total_buffer_size = 0;
         while let Some(dir_entry) = dat_reader.next_entry().await.context(IoSnafu)? {
@@ -696,6 +1296,554 @@ where
 
         Ok(())
     }
+
+    /// Brings the consumers of a shared buffer in line with the given consumer IDs.
+    ///
+    /// Consumers that don't exist yet are created, starting at the oldest record in the buffer.
+    /// Consumers that aren't in `consumer_ids`, and aren't attached to a reader, are removed, so
+    /// that they no longer hold back the deletion of data files.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while loading, creating, or removing the file storing the read
+    /// position of a consumer, or if that file is not valid, an error variant will be returned
+    /// describing the error.
+    pub(super) async fn sync_consumers(
+        &self,
+        consumer_ids: &[String],
+    ) -> Result<(), LedgerLoadCreateError> {
+        let mut consumers = self.inner.consumers.lock().await;
+
+        let mut entries = fs::read_dir(&self.config().data_dir)
+            .await
+            .context(IoSnafu)?;
+        while let Some(entry) = entries.next_entry().await.context(IoSnafu)? {
+            let consumer_id = entry.file_name().to_str().and_then(|file_name| {
+                file_name
+                    .strip_prefix("buffer-consumer-")
+                    .and_then(|file_name| file_name.strip_suffix(".db"))
+                    .map(ToString::to_string)
+            });
+            let consumer_id = match consumer_id {
+                Some(consumer_id) if !consumer_ids.contains(&consumer_id) => consumer_id,
+                _ => continue,
+            };
+            let is_attached = consumers
+                .get(&consumer_id)
+                .map_or(false, |consumer| consumer.attached.load(Ordering::Acquire));
+            if is_attached {
+                continue;
+            }
+
+            info!(
+                consumer_id = consumer_id.as_str(),
+                "Removing consumer that is no longer configured for shared buffer."
+            );
+            consumers.remove(&consumer_id);
+            self.filesystem()
+                .delete_file(&entry.path())
+                .await
+                .context(IoSnafu)?;
+        }
+
+        for consumer_id in consumer_ids {
+            if !consumers.contains_key(consumer_id) {
+                let consumer = self.load_or_create_consumer(consumer_id).await?;
+                consumers.insert(consumer_id.clone(), Arc::new(consumer));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn load_or_create_consumer(
+        &self,
+        consumer_id: &str,
+    ) -> Result<Consumer<FS>, LedgerLoadCreateError> {
+        let consumer_path = self.get_consumer_file_path(consumer_id);
+        let mut consumer_handle = self
+            .filesystem()
+            .open_file_writable(&consumer_path)
+            .await
+            .context(IoSnafu)?;
+
+        let consumer_metadata = consumer_handle.metadata().await.context(IoSnafu)?;
+        if consumer_metadata.len() == 0 {
+            debug!(
+                consumer_id,
+                "Consumer file empty.  Starting consumer at the oldest record in the buffer."
+            );
+            let mut buf = BytesMut::new();
+            loop {
+                match BackedArchive::from_value(&mut buf, ConsumerState::starting_at(self.state()))
+                {
+                    Ok(archive) => {
+                        consumer_handle
+                            .write_all(archive.get_backing_ref())
+                            .await
+                            .context(IoSnafu)?;
+                        break;
+                    }
+                    Err(SerializeError::FailedToSerialize(reason)) => {
+                        return Err(LedgerLoadCreateError::FailedToSerialize { reason })
+                    }
+                    // Our buffer wasn't big enough, but that's OK!  Resize it and try again.
+                    Err(SerializeError::BackingStoreTooSmall(_, min_len)) => buf.resize(min_len, 0),
+                }
+            }
+
+            consumer_handle.sync_all().await.context(IoSnafu)?;
+        }
+        drop(consumer_handle);
+
+        let consumer_mmap = self
+            .filesystem()
+            .open_mmap_writable(&consumer_path)
+            .await
+            .context(IoSnafu)?;
+        let state = BackedArchive::from_backing(consumer_mmap).map_err(|e| {
+            LedgerLoadCreateError::FailedToDeserialize {
+                reason: e.into_inner(),
+            }
+        })?;
+
+        Ok(Consumer {
+            state,
+            attached: AtomicBool::new(false),
+        })
+    }
+
+    /// Attaches a reader to the given consumer of a shared buffer.
+    ///
+    /// The returned ledger tracks the read position, and acknowledgements, of the consumer, and is
+    /// meant to be used by its reader.  It shares everything else with this ledger, including the
+    /// writer.  If the consumer doesn't exist yet, it's created, starting at the oldest record in the
+    /// buffer.
+    ///
+    /// # Errors
+    ///
+    /// If a reader is already attached to the consumer, or if the consumer needed to be created and
+    /// that failed, an error variant will be returned describing the error.
+    pub(super) async fn attach_consumer(
+        &self,
+        consumer_id: &str,
+        usage_handle: BufferUsageHandle,
+    ) -> Result<Ledger<FS>, LedgerLoadCreateError> {
+        let mut consumers = self.inner.consumers.lock().await;
+        let consumer = match consumers.get(consumer_id) {
+            Some(consumer) => Arc::clone(consumer),
+            None => {
+                let consumer = Arc::new(self.load_or_create_consumer(consumer_id).await?);
+                consumers.insert(consumer_id.to_string(), Arc::clone(&consumer));
+                consumer
+            }
+        };
+
+        if consumer.attached.swap(true, Ordering::AcqRel) {
+            return Err(LedgerLoadCreateError::ConsumerAlreadyAttached {
+                consumer_id: consumer_id.to_string(),
+            });
+        }
+
+        Ok(Ledger::with_inner(
+            Arc::clone(&self.inner),
+            Some(consumer),
+            usage_handle,
+        ))
+    }
+
+    /// Deletes the data files that every consumer of a shared buffer has moved past.
+    ///
+    /// The read position in the ledger state, which tracks the slowest consumer, is moved past
+    /// each deleted data file, and caught up with the record IDs acknowledged by every consumer.
+    ///
+    /// # Errors
+    ///
+    /// If an I/O error occurs while deleting a data file, or flushing the ledger, an error variant
+    /// will be returned describing the error.
+    pub(super) async fn delete_released_data_files(&self) -> io::Result<()> {
+        let consumers = self.inner.consumers.lock().await;
+        let state = self.state();
+
+        loop {
+            let file_id = state.get_current_reader_file_id();
+            let in_use = file_id == state.get_current_writer_file_id()
+                || consumers
+                    .values()
+                    .any(|consumer| consumer.state().get_current_reader_file_id() == file_id);
+            if in_use {
+                break;
+            }
+
+            let data_file_path = self.get_data_file_path(file_id);
+            match self.filesystem().open_file_readable(&data_file_path).await {
+                Ok(data_file) => {
+                    let data_file_size = data_file.metadata().await?.len();
+                    drop(data_file);
+
+                    self.release_data_file(&data_file_path).await?;
+                    self.inner.decrement_total_buffer_size(data_file_size);
+
+                    debug!(
+                        data_file_path = data_file_path.to_string_lossy().as_ref(),
+                        "Deleted data file that every consumer is done with."
+                    );
+                }
+                // Consumers skip over data files that were never created, or went missing.
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            state.increment_reader_file_id();
+        }
+
+        // Consumers only ever move forward from the read position in the ledger state, so the
+        // slowest consumer is the one the least far past it.
+        let last_record_id = state.get_last_reader_record_id();
+        let slowest = consumers
+            .values()
+            .map(|consumer| consumer.state())
+            .min_by_key(|consumer| {
+                consumer
+                    .get_last_reader_record_id()
+                    .wrapping_sub(last_record_id)
+            });
+        if let Some(slowest) = slowest {
+            state.set_reader_position(
+                slowest.get_last_reader_record_id(),
+                slowest.get_reader_logical_bytes(),
+            );
+        }
+        drop(consumers);
+
+        self.inner.state.get_backing_ref().flush()
+    }
+}
+
+impl<FS> Drop for Ledger<FS>
+where
+    FS: Filesystem,
+{
+    fn drop(&mut self) {
+        if let Some(consumer) = &self.consumer {
+            consumer.attached.store(false, Ordering::Release);
+        }
+    }
+}
+
+/// Upgrades the ledger at `ledger_path` to the current version of the buffer format, if it was
+/// written with an older version.
+///
+/// Records written with older versions are identical to unencrypted records of the current version,
+/// so only the ledger itself needs to be rewritten.  The new ledger is written next to the existing
+/// one and then moved over it, so that a crash midway leaves either ledger intact.
+async fn try_upgrade_ledger<FS>(
+    filesystem: &FS,
+    ledger_path: &Path,
+) -> Result<(), LedgerLoadCreateError>
+where
+    FS: Filesystem,
+{
+    let mut state = {
+        let ledger_mmap = filesystem
+            .open_mmap_readable(ledger_path)
+            .await
+            .context(IoSnafu)?;
+        let ledger_buf = ledger_mmap.as_ref();
+
+        // If the ledger is already valid for the current version, or isn't valid for any older
+        // version either, there's nothing to upgrade: any error will surface when the ledger is
+        // loaded.
+        //
+        // Newer layouts only ever grow, so we must check them from newest to oldest, as the tail
+        // of a newer ledger may well be a valid older ledger.
+        if try_as_archive::<LedgerState>(ledger_buf).is_ok() {
+            return Ok(());
+        }
+        if let Ok(legacy) = try_as_archive::<LedgerStateV2>(ledger_buf) {
+            LedgerState::from_v2(legacy)
+        } else if let Ok(legacy) = try_as_archive::<LegacyLedgerState>(ledger_buf) {
+            LedgerState::from_v1(legacy)
+        } else {
+            return Ok(());
+        }
+    };
+
+    info!(
+        ledger_path = ledger_path.to_string_lossy().as_ref(),
+        "Upgrading disk buffer ledger to version {}.", LEDGER_VERSION
+    );
+
+    let mut buf = BytesMut::new();
+    let upgraded_ledger_path = ledger_path.with_extension("db.upgrade");
+    loop {
+        match BackedArchive::from_value(&mut buf, state) {
+            Ok(archive) => {
+                // A previous upgrade may have been interrupted midway, so start from scratch.
+                match filesystem.delete_file(&upgraded_ledger_path).await {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                        return Err(LedgerLoadCreateError::Io { source: e })
+                    }
+                    _ => {}
+                }
+                let mut upgraded_ledger = filesystem
+                    .open_file_writable_atomic(&upgraded_ledger_path)
+                    .await
+                    .context(IoSnafu)?;
+                upgraded_ledger
+                    .write_all(archive.get_backing_ref())
+                    .await
+                    .context(IoSnafu)?;
+                upgraded_ledger.sync_all().await.context(IoSnafu)?;
+                break;
+            }
+            Err(SerializeError::FailedToSerialize(reason)) => {
+                return Err(LedgerLoadCreateError::FailedToSerialize { reason })
+            }
+            // Our buffer wasn't big enough, but that's OK!  Resize it and try again.
+            Err(SerializeError::BackingStoreTooSmall(value, min_len)) => {
+                state = value;
+                buf.resize(min_len, 0);
+            }
+        }
+    }
+
+    filesystem
+        .rename_file(&upgraded_ledger_path, ledger_path)
+        .await
+        .context(IoSnafu)
+}
+
+/// Checks that a loaded ledger can be used with the given compression and encryption key.
+fn check_ledger_compatibility(
+    state: &ArchivedLedgerState,
+    configured: DiskBufferCompression,
+    encryption_key: Option<&DiskBufferEncryptionKey>,
+) -> Result<(), LedgerLoadCreateError> {
+    if state.version() != LEDGER_VERSION {
+        return Err(LedgerLoadCreateError::Incompatible {
+            reason: format!(
+                "buffer format version {} is not supported (expected {})",
+                state.version(),
+                LEDGER_VERSION
+            ),
+        });
+    }
+
+    match state.compression() {
+        None => {
+            return Err(LedgerLoadCreateError::Incompatible {
+                reason: format!("unknown compression identifier {}", state.compression),
+            })
+        }
+        Some(actual) if actual != configured => {
+            return Err(LedgerLoadCreateError::CompressionMismatch { actual, configured })
+        }
+        Some(_) => {}
+    }
+
+    match (state.encryption, encryption_key) {
+        (0, None) => Ok(()),
+        (0, Some(_)) => Err(LedgerLoadCreateError::EncryptionMismatch {
+            reason: "buffer is not encrypted, but an encryption key is configured".to_string(),
+        }),
+        (1, None) => Err(LedgerLoadCreateError::EncryptionMismatch {
+            reason: "buffer is encrypted, but no encryption key is configured".to_string(),
+        }),
+        (1, Some(key)) if state.key_fingerprint != *key.fingerprint() => {
+            Err(LedgerLoadCreateError::WrongEncryptionKey)
+        }
+        (1, Some(_)) => Ok(()),
+        (encryption, _) => Err(LedgerLoadCreateError::Incompatible {
+            reason: format!("unknown encryption identifier {}", encryption),
+        }),
+    }
+}
+
+/// Format of the records stored in an existing buffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct StoredRecordFormat {
+    pub(super) compression: DiskBufferCompression,
+    pub(super) encrypted: bool,
+}
+
+/// Copy of the ledger state of a buffer, read without loading the buffer.
+#[derive(Clone, Debug)]
+pub struct LedgerSnapshot {
+    /// Version of the buffer format.
+    pub version: u32,
+    /// Whether or not record payloads are encrypted.
+    pub encrypted: bool,
+    /// Next record ID to use when writing a record.
+    pub writer_next_record_id: u64,
+    /// The current data file ID being written to.
+    pub writer_current_data_file_id: u16,
+    /// The current data file ID being read from.
+    pub reader_current_data_file_id: u16,
+    /// The last record ID read, and acknowledged, by the reader.
+    pub reader_last_record_id: u64,
+    /// The logical size of the unread records, before compression.
+    ///
+    /// This is not tracked by version 1 of the buffer format.
+    pub unread_logical_bytes: Option<u64>,
+    compression: u32,
+    key_fingerprint: [u8; 32],
+}
+
+impl LedgerSnapshot {
+    fn from_current(state: &ArchivedLedgerState) -> Self {
+        Self {
+            version: state.version(),
+            encrypted: state.is_encrypted(),
+            writer_next_record_id: state.get_next_writer_record_id(),
+            writer_current_data_file_id: state.get_current_writer_file_id(),
+            reader_current_data_file_id: state.get_current_reader_file_id(),
+            reader_last_record_id: state.get_last_reader_record_id(),
+            unread_logical_bytes: Some(state.get_outstanding_logical_bytes()),
+            compression: state.compression,
+            key_fingerprint: state.key_fingerprint,
+        }
+    }
+
+    fn from_v2(state: &ArchivedLedgerStateV2) -> Self {
+        Self {
+            version: state.version,
+            encrypted: false,
+            writer_next_record_id: state.writer_next_record_id.load(Ordering::Acquire),
+            writer_current_data_file_id: state.writer_current_data_file_id.load(Ordering::Acquire),
+            reader_current_data_file_id: state.reader_current_data_file_id.load(Ordering::Acquire),
+            reader_last_record_id: state.reader_last_record_id.load(Ordering::Acquire),
+            unread_logical_bytes: Some(
+                state
+                    .writer_logical_bytes
+                    .load(Ordering::Acquire)
+                    .wrapping_sub(state.reader_logical_bytes.load(Ordering::Acquire)),
+            ),
+            compression: state.compression,
+            key_fingerprint: [0; 32],
+        }
+    }
+
+    fn from_v1(state: &ArchivedLegacyLedgerState) -> Self {
+        Self {
+            version: 1,
+            encrypted: false,
+            writer_next_record_id: state.writer_next_record_id.load(Ordering::Acquire),
+            writer_current_data_file_id: state.writer_current_data_file_id.load(Ordering::Acquire),
+            reader_current_data_file_id: state.reader_current_data_file_id.load(Ordering::Acquire),
+            reader_last_record_id: state.reader_last_record_id.load(Ordering::Acquire),
+            unread_logical_bytes: None,
+            compression: DiskBufferCompression::None.as_u32(),
+            key_fingerprint: [0; 32],
+        }
+    }
+
+    /// Gets the compression applied to record payloads, if it's a known compression.
+    pub fn compression(&self) -> Option<DiskBufferCompression> {
+        DiskBufferCompression::from_u32(self.compression)
+    }
+
+    /// Gets the format of the records in the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If the compression of the records is unknown, an error variant will be returned.
+    pub(super) fn record_format(&self) -> Result<StoredRecordFormat, LedgerLoadCreateError> {
+        match self.compression() {
+            Some(compression) => Ok(StoredRecordFormat {
+                compression,
+                encrypted: self.encrypted,
+            }),
+            None => Err(LedgerLoadCreateError::Incompatible {
+                reason: format!("unknown compression identifier {}", self.compression),
+            }),
+        }
+    }
+
+    /// Checks that records in the buffer can be decrypted with the given encryption key.
+    ///
+    /// # Errors
+    ///
+    /// If the buffer is not encrypted, or was encrypted with a different key, an error variant
+    /// will be returned describing the problem.
+    pub(super) fn check_encryption_key(
+        &self,
+        encryption_key: &DiskBufferEncryptionKey,
+    ) -> Result<(), LedgerLoadCreateError> {
+        if !self.encrypted {
+            Err(LedgerLoadCreateError::EncryptionMismatch {
+                reason: "buffer is not encrypted, but an encryption key was given".to_string(),
+            })
+        } else if self.key_fingerprint != *encryption_key.fingerprint() {
+            Err(LedgerLoadCreateError::WrongEncryptionKey)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether or not the record with the given ID has yet to be read and acknowledged.
+    pub fn is_unread(&self, record_id: u64) -> bool {
+        record_id > self.reader_last_record_id
+    }
+}
+
+/// Reads the ledger state of the buffer stored in `data_dir`, without loading the buffer.
+///
+/// The ledger is only ever read, so this is safe to use while the buffer is in use, although the
+/// state may of course be outdated as soon as it's returned.
+///
+/// Returns `None` if there is no buffer in `data_dir`.
+///
+/// # Errors
+///
+/// If an I/O error occurs while reading the ledger, or the ledger is not valid, an error variant
+/// will be returned describing the error.
+pub(super) async fn read_ledger_snapshot(
+    data_dir: &Path,
+) -> Result<Option<LedgerSnapshot>, LedgerLoadCreateError> {
+    let ledger_buf = match fs::read(data_dir.join("buffer.db")).await {
+        Ok(buf) if !buf.is_empty() => buf,
+        Ok(_) => return Ok(None),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(LedgerLoadCreateError::Io { source }),
+    };
+
+    // Validating the archive requires it to be suitably aligned, which a plain `Vec<u8>` is not.
+    let mut aligned_buf = AlignedVec::with_capacity(ledger_buf.len());
+    aligned_buf.extend_from_slice(&ledger_buf);
+
+    // As when upgrading, check the layouts from newest to oldest.
+    let snapshot = if let Ok(state) = try_as_archive::<LedgerState>(&aligned_buf) {
+        LedgerSnapshot::from_current(state)
+    } else if let Ok(state) = try_as_archive::<LedgerStateV2>(&aligned_buf) {
+        LedgerSnapshot::from_v2(state)
+    } else {
+        let state = try_as_archive::<LegacyLedgerState>(&aligned_buf).map_err(|e| {
+            LedgerLoadCreateError::FailedToDeserialize {
+                reason: e.into_inner(),
+            }
+        })?;
+        LedgerSnapshot::from_v1(state)
+    };
+
+    Ok(Some(snapshot))
+}
+
+/// Reads the format of the records in the buffer stored in `data_dir`, without loading the buffer.
+///
+/// Returns `None` if there is no buffer in `data_dir`.  Buffers using older versions of the buffer
+/// format are reported as unencrypted, and, for version 1, as uncompressed.
+///
+/// # Errors
+///
+/// If an I/O error occurs while reading the ledger, or the ledger is not valid, an error variant
+/// will be returned describing the error.
+pub(super) async fn read_ledger_record_format(
+    data_dir: &Path,
+) -> Result<Option<StoredRecordFormat>, LedgerLoadCreateError> {
+    read_ledger_snapshot(data_dir)
+        .await?
+        .map(|snapshot| snapshot.record_format())
+        .transpose()
 }
 
 impl<FS> fmt::Debug for Ledger<FS>
@@ -704,22 +1852,33 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Ledger")
-            .field("config", &self.config)
-            .field("ledger_lock", &self.ledger_lock)
-            .field("state", &self.state.get_archive_ref())
+            .field("config", &self.inner.config)
+            .field("ledger_lock", &self.inner.ledger_lock)
+            .field("state", &self.inner.state.get_archive_ref())
+            .field(
+                "consumer",
+                &self.consumer.as_ref().map(|consumer| consumer.state()),
+            )
             .field(
                 "total_buffer_size",
-                &self.total_buffer_size.load(Ordering::Acquire),
+                &self.inner.total_buffer_size.load(Ordering::Acquire),
             )
-            .field("reader_notify", &self.reader_notify)
-            .field("writer_notify", &self.writer_notify)
+            .field("reader_notify", &self.inner.reader_notify)
+            .field("progress", &self.progress)
             .field("pending_acks", &self.pending_acks.load(Ordering::Acquire))
             .field(
                 "unacked_reader_file_id_offset",
                 &self.unacked_reader_file_id_offset.load(Ordering::Acquire),
             )
-            .field("writer_done", &self.writer_done.load(Ordering::Acquire))
-            .field("last_flush", &self.last_flush)
+            .field(
+                "writer_done",
+                &self.inner.writer_done.load(Ordering::Acquire),
+            )
+            .field(
+                "consumer_closed",
+                &self.consumer_closed.load(Ordering::Acquire),
+            )
+            .field("last_flush", &self.inner.last_flush)
             .finish()
     }
 }
@@ -35,6 +35,13 @@
 //!     `record_id`:  uint64
 //!     `payload`:    uint8[]
 //!
+//! The payload is the encoded record, optionally compressed and then encrypted.  The compression and
+//! encryption used are fixed for the lifetime of a buffer and stored in the ledger, and the checksum
+//! covers the payload as stored.
+//!
+//! Encrypted payloads consist of a random 96-bit nonce, followed by the AES-256-GCM ciphertext and
+//! its authentication tag.  The record ID is authenticated along with the payload.
+//!
 //! We say pseudo-structure because we serialize these records to disk using `rkyv`, a zero-copy
 //! deserialization library which focuses on the speed of reading values by writing them to storage
 //! in a way that allows them to be "deserialized" without any copies, which means the layout of
@@ -124,6 +131,36 @@
 //!     writer current data file ID: uint16
 //!     reader current data file ID: uint16
 //!     reader last record ID:       uint64
+//!     version:                     uint32
+//!     compression:                 uint32
+//!     writer logical bytes:        uint64
+//!     reader logical bytes:        uint64
+//!     encryption:                  uint32
+//!     key fingerprint:             uint8[32]
+//!
+//! The version identifies the layout of the ledger and data files.  Ledgers written before the
+//! version was introduced only contain the first four fields, and are upgraded in place when loaded.
+//! The logical byte counters track the size of records before compression, so that the buffer can
+//! report both its logical size and its size on disk.  The key fingerprint is a SHA-256 digest of the
+//! encryption key, used to detect when a buffer is loaded with the wrong key.
+//!
+//! ## Shared buffers
+//!
+//! A buffer can be shared by multiple consumers, which all read the same records: records are
+//! written once, and each consumer reads, and acknowledges, them independently. Each consumer has
+//! its own read position, stored next to the ledger, which takes the place of the reader fields of
+//! the ledger for that consumer:
+//!
+//!   buffer-consumer-<consumer ID>.db:
+//!     reader current data file ID: uint16
+//!     reader last record ID:       uint64
+//!     reader logical bytes:        uint64
+//!
+//! The reader fields of the ledger then track the slowest consumer: a data file is only deleted
+//! once every consumer has acknowledged all of its records, at which point the reader current data
+//! file ID of the ledger moves past it. New consumers start at the oldest record in the buffer, and
+//! consumers that are no longer configured are removed when the buffer is loaded, so that they
+//! don't hold back the deletion of data files forever.
 //!
 //! As the disk buffer structure is meant to emulate a ring buffer, most of the bookkeeping resolves
 //! around the writer and reader being able to quickly figure out where they left off. Record and
@@ -149,11 +186,16 @@ use snafu::{ResultExt, Snafu};
 mod acknowledgements;
 mod backed_archive;
 mod common;
+mod compression_migration;
+mod encryption;
+mod inspect;
 mod io;
 mod ledger;
 mod reader;
 mod record;
+mod retention;
 mod ser;
+mod shared;
 mod v1_migration;
 mod writer;
 
@@ -161,13 +203,21 @@ mod writer;
 mod tests;
 
 use self::{
-    acknowledgements::create_disk_v2_acker, ledger::Ledger, v1_migration::try_disk_v1_migration,
+    acknowledgements::create_disk_v2_acker,
+    compression_migration::try_disk_v2_compression_migration, ledger::Ledger,
+    v1_migration::try_disk_v1_migration,
 };
 pub use self::{
-    common::{DiskBufferConfig, DiskBufferConfigBuilder},
+    common::{
+        DiskBufferCompression, DiskBufferConfig, DiskBufferConfigBuilder, DiskBufferRetention,
+    },
+    encryption::DiskBufferEncryptionKey,
+    inspect::{DataFileSummary, DiskBufferInspector, InspectError, ReplaySummary},
     io::{Filesystem, ProductionFilesystem},
-    ledger::LedgerLoadCreateError,
+    ledger::{LedgerLoadCreateError, LedgerSnapshot},
     reader::{Reader, ReaderError},
+    retention::{RetainedDataFile, RetainedDataFileFormat},
+    shared::SharedSender,
     writer::{Writer, WriterError},
 };
 use crate::{
@@ -198,6 +248,29 @@ where
     WriterSeekFailed { source: WriterError<T> },
 }
 
+impl<T> BufferError<T>
+where
+    T: Bufferable,
+{
+    /// Whether or not this error was caused by the encryption of the buffer.
+    ///
+    /// This covers a missing, unexpected, or wrong encryption key, as well as records that fail to
+    /// decrypt when the buffer is loaded.
+    pub fn is_encryption_error(&self) -> bool {
+        matches!(
+            self,
+            BufferError::LedgerError {
+                source: LedgerLoadCreateError::EncryptionMismatch { .. }
+                    | LedgerLoadCreateError::WrongEncryptionKey
+            } | BufferError::ReaderSeekFailed {
+                source: ReaderError::Decryption
+            } | BufferError::WriterSeekFailed {
+                source: WriterError::FailedToDecrypt
+            }
+        )
+    }
+}
+
 /// Helper type for creating a disk buffer.
 pub struct Buffer<T> {
     _t: PhantomData<T>,
@@ -240,6 +313,78 @@ where
         Ok((writer, reader, acker, ledger))
     }
 
+    /// Creates the writer of a shared disk buffer from the given [`DiskBufferConfig`].
+    ///
+    /// The consumers of the buffer are the ones configured with
+    /// [`DiskBufferConfigBuilder::consumers`], and readers are attached to them with
+    /// [`Buffer::attach_consumer`], using the returned ledger.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurred during the creation or loading of the disk buffer, an error variant
+    /// will be returned describing the error.
+    #[cfg_attr(test, instrument(skip(config, usage_handle), level = "trace"))]
+    pub(crate) async fn from_config_shared<FS>(
+        config: DiskBufferConfig<FS>,
+        usage_handle: BufferUsageHandle,
+    ) -> Result<(Writer<T, FS>, Arc<Ledger<FS>>), BufferError<T>>
+    where
+        FS: Filesystem + Clone + 'static,
+        FS::File: Unpin,
+    {
+        let ledger = Ledger::load_or_create(config, usage_handle)
+            .await
+            .context(LedgerSnafu)?;
+        let ledger = Arc::new(ledger);
+
+        let mut writer = Writer::new(Arc::clone(&ledger));
+        writer
+            .validate_last_write()
+            .await
+            .context(WriterSeekFailedSnafu)?;
+
+        Ok((writer, ledger))
+    }
+
+    /// Attaches a reader to the given consumer of a shared disk buffer.
+    ///
+    /// The [`Reader`] reads every record in the buffer that the consumer has yet to acknowledge,
+    /// and the [`Acker`] acknowledges records for that consumer alone.  The returned ledger tracks
+    /// the read position of the consumer.
+    ///
+    /// # Errors
+    ///
+    /// If a reader is already attached to the consumer, or an error occurred while seeking to
+    /// where the consumer left off, an error variant will be returned describing the error.
+    #[cfg_attr(test, instrument(skip(ledger, usage_handle), level = "trace"))]
+    pub(crate) async fn attach_consumer<FS>(
+        ledger: &Ledger<FS>,
+        consumer_id: &str,
+        usage_handle: BufferUsageHandle,
+    ) -> Result<(Reader<T, FS>, Acker, Arc<Ledger<FS>>), BufferError<T>>
+    where
+        FS: Filesystem + Clone + 'static,
+        FS::File: Unpin,
+    {
+        let ledger = ledger
+            .attach_consumer(consumer_id, usage_handle)
+            .await
+            .context(LedgerSnafu)?;
+        let ledger = Arc::new(ledger);
+
+        let mut reader = Reader::new(Arc::clone(&ledger));
+        reader
+            .seek_to_next_record()
+            .await
+            .context(ReaderSeekFailedSnafu)?;
+
+        ledger.synchronize_buffer_usage();
+
+        let acker = create_disk_v2_acker(Arc::clone(&ledger));
+
+        Ok((reader, acker, ledger))
+    }
+
     /// Creates a new disk buffer from the given [`DiskBufferConfig`].
     ///
     /// If successful, a [`Writer`] and [`Reader`] value, representing the write/read sides of the
@@ -270,16 +415,48 @@ pub struct DiskV2Buffer {
     id: String,
     data_dir: PathBuf,
     max_size: NonZeroU64,
+    compression: DiskBufferCompression,
+    encryption_key: Option<DiskBufferEncryptionKey>,
+    retention: Option<DiskBufferRetention>,
+    shared: Option<(String, Vec<String>)>,
 }
 
 impl DiskV2Buffer {
-    pub fn new(id: String, data_dir: PathBuf, max_size: NonZeroU64) -> Self {
+    pub fn new(
+        id: String,
+        data_dir: PathBuf,
+        max_size: NonZeroU64,
+        compression: DiskBufferCompression,
+        encryption_key: Option<DiskBufferEncryptionKey>,
+    ) -> Self {
         Self {
             id,
             data_dir,
             max_size,
+            compression,
+            encryption_key,
+            retention: None,
+            shared: None,
         }
     }
+
+    /// Retains data files once every record in them has been acknowledged, rather than deleting
+    /// them, so that their records can be replayed.
+    #[must_use]
+    pub fn retention(mut self, retention: Option<DiskBufferRetention>) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Makes this buffer a consumer of the shared disk buffer with the given name.
+    ///
+    /// The consumers are the IDs of every buffer sharing the disk buffer, including this one, and
+    /// the shared disk buffer is only opened once for all of them.
+    #[must_use]
+    pub fn shared(mut self, name: String, consumers: Vec<String>) -> Self {
+        self.shared = Some((name, consumers));
+        self
+    }
 }
 
 #[async_trait]
@@ -296,9 +473,44 @@ where
         usage_handle: BufferUsageHandle,
     ) -> Result<(SenderAdapter<T>, ReceiverAdapter<T>, Option<Acker>), Box<dyn Error + Send + Sync>>
     {
+        // Shared buffers can't be migrated, as each of their consumers has its own read position.
+        if let Some((name, consumers)) = self.shared {
+            let (sender, reader, acker) = build_shared_disk_v2_buffer(
+                usage_handle,
+                &self.data_dir,
+                &name,
+                self.id,
+                consumers,
+                self.max_size,
+                self.compression,
+                self.encryption_key,
+                self.retention,
+            )
+            .await?;
+
+            return Ok((sender.into(), reader.into(), Some(acker)));
+        }
+
+        // Attempt to migrate the existing buffer to the configured compression and encryption, if
+        // it uses a different one. If the buffer doesn't exist, or already uses them, then this
+        // does nothing.
+        try_disk_v2_compression_migration::<T>(
+            self.data_dir.as_path(),
+            self.id.as_str(),
+            self.compression,
+            self.encryption_key.as_ref(),
+        )
+        .await?;
+
         // Attempt to migrate a disk v1 buffer based on the same data directory and buffer ID if one
         // exists. If one doesn't exist, then this method does nothing.
-        try_disk_v1_migration::<T>(self.data_dir.as_path(), self.id.as_str()).await?;
+        try_disk_v1_migration::<T>(
+            self.data_dir.as_path(),
+            self.id.as_str(),
+            self.compression,
+            self.encryption_key.as_ref(),
+        )
+        .await?;
 
         // Now that we've handled any necessary migrations, go ahead and build the buffer.
         let (writer, reader, acker) = build_disk_v2_buffer(
@@ -306,6 +518,9 @@ where
             &self.data_dir,
             self.id.as_str(),
             self.max_size,
+            self.compression,
+            self.encryption_key,
+            self.retention,
         )
         .await?;
 
@@ -318,6 +533,9 @@ async fn build_disk_v2_buffer<T>(
     data_dir: &Path,
     id: &str,
     max_size: NonZeroU64,
+    compression: DiskBufferCompression,
+    encryption_key: Option<DiskBufferEncryptionKey>,
+    retention: Option<DiskBufferRetention>,
 ) -> Result<
     (
         Writer<T, ProductionFilesystem>,
@@ -334,12 +552,49 @@ where
     let buffer_path = get_disk_v2_data_dir_path(data_dir, id);
     let config = DiskBufferConfigBuilder::from_path(buffer_path)
         .max_buffer_size(max_size.get())
+        .compression(compression)
+        .encryption_key(encryption_key)
+        .retention(retention)
         .build()?;
     Buffer::from_config(config, usage_handle)
         .await
         .map_err(Into::into)
 }
 
+#[allow(clippy::too_many_arguments)]
+async fn build_shared_disk_v2_buffer<T>(
+    usage_handle: BufferUsageHandle,
+    data_dir: &Path,
+    name: &str,
+    consumer_id: String,
+    consumers: Vec<String>,
+    max_size: NonZeroU64,
+    compression: DiskBufferCompression,
+    encryption_key: Option<DiskBufferEncryptionKey>,
+    retention: Option<DiskBufferRetention>,
+) -> Result<(SharedSender<T>, Reader<T, ProductionFilesystem>, Acker), Box<dyn Error + Send + Sync>>
+where
+    T: Bufferable + Clone,
+{
+    usage_handle.set_buffer_limits(Some(max_size.get()), None);
+
+    let buffer_path = get_shared_disk_v2_data_dir_path(data_dir, name);
+    let config = DiskBufferConfigBuilder::from_path(buffer_path)
+        .max_buffer_size(max_size.get())
+        .compression(compression)
+        .encryption_key(encryption_key)
+        .retention(retention)
+        .consumers(consumers)
+        .build()?;
+    shared::open_shared_buffer(config, consumer_id, usage_handle)
+        .await
+        .map_err(Into::into)
+}
+
 pub(crate) fn get_disk_v2_data_dir_path(base_dir: &Path, buffer_id: &str) -> PathBuf {
     base_dir.join("buffer").join("v2").join(buffer_id)
 }
+
+pub(crate) fn get_shared_disk_v2_data_dir_path(base_dir: &Path, name: &str) -> PathBuf {
+    base_dir.join("buffer").join("v2-shared").join(name)
+}
@@ -13,7 +13,8 @@ use snafu::{ResultExt, Snafu};
 use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 
 use super::{
-    common::create_crc32c_hasher,
+    common::{create_crc32c_hasher, DiskBufferCompression},
+    encryption::DiskBufferEncryptionKey,
     ledger::Ledger,
     record::{validate_record_archive, ArchivedRecord, Record, RecordStatus},
     Filesystem,
@@ -99,6 +100,21 @@ where
         source: <T as Encodable>::DecodeError,
     },
 
+    /// The record payload could not be decompressed.
+    ///
+    /// Like decoding errors, the record can be assumed to have been written and read correctly, as
+    /// the checksum covers the compressed payload.
+    #[snafu(display("failed to decompress record: {}", source))]
+    Decompression { source: io::Error },
+
+    /// The record payload could not be decrypted.
+    ///
+    /// As the checksum covers the encrypted payload, and the fingerprint of the encryption key is
+    /// checked when loading the buffer, this means the record was altered on disk by something
+    /// other than Vector.
+    #[snafu(display("failed to decrypt record: payload failed authentication"))]
+    Decryption,
+
     /// The record is not compatible with this version of Vector.
     ///
     /// This can occur when records written to a buffer in previous versions of Vector are read by
@@ -143,6 +159,8 @@ where
             ReaderError::Deserialization { .. } => "deser_failed",
             ReaderError::Checksum { .. } => "checksum_mismatch",
             ReaderError::Decode { .. } => "decode_failed",
+            ReaderError::Decompression { .. } => "decompress_failed",
+            ReaderError::Decryption => "decrypt_failed",
             ReaderError::Incompatible { .. } => "incompatible_record_version",
             ReaderError::PartialWrite => "partial_write",
             ReaderError::EmptyRecord => "empty_record",
@@ -158,6 +176,8 @@ where
             ReaderError::Deserialization { .. }
             | ReaderError::Checksum { .. }
             | ReaderError::Decode { .. }
+            | ReaderError::Decompression { .. }
+            | ReaderError::Decryption
             | ReaderError::Incompatible { .. }
             | ReaderError::PartialWrite => Some(BufferReadError { error_code, error }),
         }
@@ -185,6 +205,10 @@ impl<T: Bufferable> PartialEq for ReaderError<T> {
                 },
             ) => l_calculated == r_calculated && l_actual == r_actual,
             (Self::Decode { .. }, Self::Decode { .. }) => true,
+            (
+                Self::Decompression { source: l_source },
+                Self::Decompression { source: r_source },
+            ) => l_source.kind() == r_source.kind(),
             (Self::Incompatible { reason: l_reason }, Self::Incompatible { reason: r_reason }) => {
                 l_reason == r_reason
             }
@@ -197,8 +221,13 @@ impl<T: Bufferable> PartialEq for ReaderError<T> {
 pub(super) struct RecordReader<R, T> {
     reader: BufReader<R>,
     aligned_buf: AlignedVec,
+    decrypt_buf: Vec<u8>,
+    decompress_buf: Vec<u8>,
     checksummer: Hasher,
+    compression: DiskBufferCompression,
+    encryption_key: Option<DiskBufferEncryptionKey>,
     current_record_id: u64,
+    last_decoded_len: usize,
     _t: PhantomData<T>,
 }
 
@@ -211,16 +240,33 @@ where
     ///
     /// Internally, the reader is wrapped in a [`BufReader`], so callers should not pass in an
     /// already buffered reader.
-    pub fn new(reader: R) -> Self {
+    ///
+    /// Record payloads are decrypted and decompressed using the given encryption key and
+    /// compression, which must be the ones they were written with.
+    pub fn new(
+        reader: R,
+        compression: DiskBufferCompression,
+        encryption_key: Option<DiskBufferEncryptionKey>,
+    ) -> Self {
         Self {
             reader: BufReader::with_capacity(256 * 1024, reader),
             aligned_buf: AlignedVec::new(),
+            decrypt_buf: Vec::new(),
+            decompress_buf: Vec::new(),
             checksummer: create_crc32c_hasher(),
+            compression,
+            encryption_key,
             current_record_id: 0,
+            last_decoded_len: 0,
             _t: PhantomData,
         }
     }
 
+    /// Gets the encoded size of the last record read, after decompression.
+    pub fn last_decoded_len(&self) -> usize {
+        self.last_decoded_len
+    }
+
     #[cfg_attr(test, instrument(skip(self), level = "trace"))]
     async fn read_length_delimiter(
         &mut self,
@@ -375,7 +421,16 @@ where
         // - `try_next_record` does all the archive checks, checksum validation, etc
         let record = unsafe { archived_root::<Record<'_>>(&self.aligned_buf) };
 
-        decode_record_payload(record)
+        let payload = unwrap_record_payload(
+            record,
+            self.compression,
+            self.encryption_key.as_ref(),
+            &mut self.decrypt_buf,
+            &mut self.decompress_buf,
+        )?;
+        self.last_decoded_len = payload.len();
+
+        decode_payload(record.metadata(), payload)
     }
 }
 
@@ -388,6 +443,8 @@ where
             .field("reader", &self.reader)
             .field("aligned_buf", &self.aligned_buf)
             .field("checksummer", &self.checksummer)
+            .field("compression", &self.compression)
+            .field("encryption_key", &self.encryption_key)
             .field("current_record_id", &self.current_record_id)
             .finish()
     }
@@ -407,7 +464,7 @@ where
     data_file_record_count: u64,
     data_file_marked_record_count: u64,
     ready_to_read: bool,
-    record_acks: OrderedAcknowledgements<u64, u64>,
+    record_acks: OrderedAcknowledgements<u64, (u64, u64)>,
     data_file_acks: OrderedAcknowledgements<u64, (PathBuf, u64)>,
     _t: PhantomData<T>,
 }
@@ -420,7 +477,7 @@ where
 {
     /// Creates a new [`Reader`] attached to the given [`Ledger`].
     pub(crate) fn new(ledger: Arc<Ledger<FS>>) -> Self {
-        let ledger_last_reader_record_id = ledger.state().get_last_reader_record_id();
+        let ledger_last_reader_record_id = ledger.get_last_reader_record_id();
         let next_expected_record_id = ledger_last_reader_record_id.wrapping_add(1);
 
         Self {
@@ -444,7 +501,13 @@ where
         self.data_file_start_record_id = None;
     }
 
-    fn track_read(&mut self, record_id: u64, record_bytes: u64, event_count: NonZeroU64) {
+    fn track_read(
+        &mut self,
+        record_id: u64,
+        record_bytes: u64,
+        logical_bytes: u64,
+        event_count: NonZeroU64,
+    ) {
         // We explicitly reduce the event count by one here in order to correctly calculate the
         // "last" record ID, which you can visualize as follows...
         //
@@ -472,10 +535,11 @@ where
         // We've done a "real" record read, so we need to track it for acknowledgement.  Check our
         // acknowledge state first to see if this is the next record ID we expected.
         self.data_file_record_count += 1;
-        if let Err(me) =
-            self.record_acks
-                .add_marker(record_id, Some(event_count.get()), Some(record_bytes))
-        {
+        if let Err(me) = self.record_acks.add_marker(
+            record_id,
+            Some(event_count.get()),
+            Some((record_bytes, logical_bytes)),
+        ) {
             match me {
                 MarkerError::MonotonicityViolation => {
                     panic!("record ID monotonicity violation detected; this is a serious bug")
@@ -539,13 +603,19 @@ where
 
         drop(data_file);
 
-        // Delete the current data file, and increment our actual reader file ID.
-        self.ledger
-            .filesystem()
-            .delete_file(&data_file_path)
-            .await?;
-        self.ledger.increment_acked_reader_file_id();
-        self.ledger.flush()?;
+        if self.ledger.is_consumer() {
+            // Other consumers of a shared buffer may still need the data file, so we only move our
+            // own reader file ID past it, and leave it to the ledger to delete it once every
+            // consumer has done the same.
+            self.ledger.increment_acked_reader_file_id();
+            self.ledger.flush()?;
+            self.ledger.delete_released_data_files().await?;
+        } else {
+            // Delete, or retain, the current data file, and increment our actual reader file ID.
+            self.ledger.release_data_file(&data_file_path).await?;
+            self.ledger.increment_acked_reader_file_id();
+            self.ledger.flush()?;
+        }
 
         debug!("Flushed after deleting data file, notifying writers and continuing.");
 
@@ -577,6 +647,7 @@ where
         let mut events_acknowledged: u64 = 0;
         let mut events_skipped: u64 = 0;
         let mut bytes_acknowledged: u64 = 0;
+        let mut logical_bytes_acknowledged: u64 = 0;
 
         let consumed_acks = self.ledger.consume_pending_acks();
         if consumed_acks > 0 {
@@ -601,8 +672,10 @@ where
                     }
                     // We got a valid marker representing a known number of events.
                     EligibleMarkerLength::Known(len) => {
-                        // We specifically pass the size of the record, in bytes, as the marker data.
-                        let record_bytes = data.expect("record bytes should always be known");
+                        // We specifically pass the size of the record, in bytes, as the marker
+                        // data, both on disk and before compression.
+                        let (record_bytes, logical_bytes) =
+                            data.expect("record bytes should always be known");
 
                         records_acknowledged = records_acknowledged.checked_add(1).expect(
                             "acknowledging more than 2^64 records at a time is obviously a bug",
@@ -613,20 +686,27 @@ where
                         bytes_acknowledged = bytes_acknowledged.checked_add(record_bytes).expect(
                             "acknowledging more than 2^64 bytes at a time is obviously a bug",
                         );
+                        logical_bytes_acknowledged = logical_bytes_acknowledged
+                            .checked_add(logical_bytes)
+                            .expect(
+                                "acknowledging more than 2^64 bytes at a time is obviously a bug",
+                            );
                     }
                 }
             }
 
             // We successfully processed at least one record, so update our buffer and ledger accounting.
             if had_eligible_records {
-                self.ledger
-                    .track_reads(events_acknowledged, bytes_acknowledged);
+                self.ledger.track_reads(
+                    events_acknowledged,
+                    bytes_acknowledged,
+                    logical_bytes_acknowledged,
+                );
 
                 // We need to account for skipped events, too, so that our "last reader record ID"
                 // value stays correct as we process these gap markers.
                 let last_increment_amount = events_acknowledged + events_skipped;
                 self.ledger
-                    .state()
                     .increment_last_reader_record_id(last_increment_amount);
 
                 self.data_file_acks
@@ -784,7 +864,11 @@ where
                 "Opened data file for reading."
             );
 
-            self.reader = Some(RecordReader::new(data_file));
+            self.reader = Some(RecordReader::new(
+                data_file,
+                self.ledger.config().compression,
+                self.ledger.config().encryption_key.clone(),
+            ));
             return Ok(());
         }
     }
@@ -814,7 +898,7 @@ where
         // also rely on it to reset the data file before trying to read, and we _also_ rely on it to
         // update `self.last_reader_record_id`, so basically... just keep reading records until we
         // get to the one we left off with last time.
-        let ledger_last = self.ledger.state().get_last_reader_record_id();
+        let ledger_last = self.ledger.get_last_reader_record_id();
         debug!(
             last_acknowledged_record_id = ledger_last,
             "Seeking to last acknowledged record for reader."
@@ -849,7 +933,11 @@ where
                 } => {
                     let record = try_as_record_archive(data_file_mmap.as_ref())
                         .expect("record was already validated");
-                    let item = match decode_record_payload::<T>(record) {
+                    let item = match decode_record_payload::<T>(
+                        record,
+                        self.ledger.config().compression,
+                        self.ledger.config().encryption_key.as_ref(),
+                    ) {
                         Ok(item) => item,
                         // If there's an error decoding the item, just fall back to the slow path,
                         // because this file might actually be where we left off, so we don't want
@@ -1071,6 +1159,7 @@ where
             .as_mut()
             .expect("reader should exist after `ensure_ready_for_read`");
         let record = reader.read_record(token)?;
+        let logical_bytes = reader.last_decoded_len() as u64;
 
         let record_events: u64 = record
             .event_count()
@@ -1079,7 +1168,7 @@ where
         let record_events = record_events
             .try_into()
             .map_err(|_| ReaderError::EmptyRecord)?;
-        self.track_read(record_id, record_bytes, record_events);
+        self.track_read(record_id, record_bytes, logical_bytes, record_events);
 
         if self.ready_to_read {
             trace!(
@@ -1095,12 +1184,61 @@ where
     }
 }
 
+/// Decodes the payload of a record that was written with the given compression and encryption key.
 pub(crate) fn decode_record_payload<T: Bufferable>(
     record: &ArchivedRecord<'_>,
+    compression: DiskBufferCompression,
+    encryption_key: Option<&DiskBufferEncryptionKey>,
 ) -> Result<T, ReaderError<T>> {
+    let mut decrypt_buf = Vec::new();
+    let mut decompress_buf = Vec::new();
+    let payload = unwrap_record_payload(
+        record,
+        compression,
+        encryption_key,
+        &mut decrypt_buf,
+        &mut decompress_buf,
+    )?;
+
+    decode_payload(record.metadata(), payload)
+}
+
+/// Decrypts and decompresses the payload of a record, using the given buffers as scratch space if
+/// need be.
+fn unwrap_record_payload<'a, T: Bufferable>(
+    record: &'a ArchivedRecord<'_>,
+    compression: DiskBufferCompression,
+    encryption_key: Option<&DiskBufferEncryptionKey>,
+    decrypt_buf: &'a mut Vec<u8>,
+    decompress_buf: &'a mut Vec<u8>,
+) -> Result<&'a [u8], ReaderError<T>> {
+    let payload = match encryption_key {
+        None => record.payload(),
+        Some(encryption_key) => {
+            decrypt_buf.clear();
+            encryption_key
+                .decrypt(record.id(), record.payload(), decrypt_buf)
+                .map_err(|_| ReaderError::Decryption)?;
+            &decrypt_buf[..]
+        }
+    };
+
+    match compression {
+        DiskBufferCompression::None => Ok(payload),
+        compression => {
+            decompress_buf.clear();
+            compression
+                .decompress(payload, decompress_buf)
+                .context(DecompressionSnafu)?;
+            Ok(&decompress_buf[..])
+        }
+    }
+}
+
+fn decode_payload<T: Bufferable>(raw_metadata: u32, payload: &[u8]) -> Result<T, ReaderError<T>> {
     // Try and convert the raw record metadata into the true metadata type used by `T`, and then
     // also verify that `T` is able to decode records with the metadata used for this record in particular.
-    let metadata = T::Metadata::from_u32(record.metadata()).ok_or(ReaderError::Incompatible {
+    let metadata = T::Metadata::from_u32(raw_metadata).ok_or(ReaderError::Incompatible {
         reason: format!("invalid metadata for {}", std::any::type_name::<T>()),
     })?;
 
@@ -1108,11 +1246,11 @@ pub(crate) fn decode_record_payload<T: Bufferable>(
         return Err(ReaderError::Incompatible {
             reason: format!(
                 "record metadata not supported (metadata: {:#036b})",
-                record.metadata()
+                raw_metadata
             ),
         });
     }
 
     // Now we can finally try decoding.
-    T::decode(metadata, record.payload()).context(DecodeSnafu)
+    T::decode(metadata, payload).context(DecodeSnafu)
 }
@@ -0,0 +1,264 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tokio::fs;
+
+use super::common::{DiskBufferCompression, DiskBufferRetention};
+
+/// Name of the directory, within the data directory of a buffer, where data files are retained.
+const RETAINED_DIR: &str = "retained";
+
+/// Prefix of the directories, within the retained data files, that hold data files retained before
+/// the buffer was migrated to another compression or encryption.
+const FORMAT_DIR_PREFIX: &str = "format-";
+
+/// A data file that was retained after every record in it had been acknowledged.
+#[derive(Clone, Debug)]
+pub struct RetainedDataFile {
+    /// Path to the retained data file.
+    pub path: PathBuf,
+    /// Size of the retained data file, in bytes.
+    pub size: u64,
+    /// When the data file was last written to, which is when its newest record was written.
+    pub modified: SystemTime,
+    /// Format of the records in the retained data file, if it was retained before the buffer was
+    /// migrated to another format.  Otherwise, the records are in the format of the buffer.
+    pub format: Option<RetainedDataFileFormat>,
+}
+
+/// Format of the records in a data file retained before its buffer was migrated to another
+/// compression or encryption.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetainedDataFileFormat {
+    /// Compression of the records.
+    pub compression: DiskBufferCompression,
+    /// Whether or not the records are encrypted.
+    pub encrypted: bool,
+}
+
+impl RetainedDataFileFormat {
+    fn dir_name(self) -> String {
+        format!(
+            "{}{}-{}",
+            FORMAT_DIR_PREFIX,
+            self.compression.as_u32(),
+            u8::from(self.encrypted)
+        )
+    }
+
+    fn from_dir_name(dir_name: &str) -> Option<Self> {
+        let (compression, encrypted) = dir_name.strip_prefix(FORMAT_DIR_PREFIX)?.split_once('-')?;
+        let compression = DiskBufferCompression::from_u32(compression.parse().ok()?)?;
+        let encrypted = match encrypted {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+
+        Some(Self {
+            compression,
+            encrypted,
+        })
+    }
+}
+
+pub(super) fn get_retained_dir_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(RETAINED_DIR)
+}
+
+/// Moves a data file, whose records have all been acknowledged, into the retained data files, and
+/// then deletes the retained data files that exceed the limits of the retention.
+///
+/// # Errors
+///
+/// If an I/O error occurs while moving the data file, or while deleting retained data files, an
+/// error variant will be returned describing the error.
+pub(super) async fn retain_data_file(
+    data_dir: &Path,
+    data_file_path: &Path,
+    retention: &DiskBufferRetention,
+) -> io::Result<()> {
+    let retained_dir = get_retained_dir_path(data_dir);
+    fs::create_dir_all(&retained_dir).await?;
+
+    // Data file IDs are reused, so retained data files are named after when they were retained,
+    // which also orders them from oldest to newest.
+    let retained_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let file_name = data_file_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .unwrap_or("buffer-data.dat");
+    let retained_path = retained_dir.join(format!("{:020}-{}", retained_at, file_name));
+    fs::rename(data_file_path, &retained_path).await?;
+
+    debug!(
+        data_file_path = data_file_path.to_string_lossy().as_ref(),
+        retained_path = retained_path.to_string_lossy().as_ref(),
+        "Retained acknowledged data file."
+    );
+
+    prune_retained_data_files(data_dir, retention).await
+}
+
+/// Gets the retained data files of the buffer in the given data directory, from oldest to newest.
+///
+/// # Errors
+///
+/// If an I/O error occurs while listing the retained data files, an error variant will be
+/// returned.
+pub(super) async fn list_retained_data_files(data_dir: &Path) -> io::Result<Vec<RetainedDataFile>> {
+    let retained_dir = get_retained_dir_path(data_dir);
+    let mut retained = Vec::new();
+    list_data_files_in(&retained_dir, None, &mut retained).await?;
+
+    // Data files retained before the buffer was migrated to another format are kept apart, in a
+    // directory for each format.
+    for (format_dir, format) in list_format_dirs(&retained_dir).await? {
+        list_data_files_in(&format_dir, Some(format), &mut retained).await?;
+    }
+
+    // Retained data files are named after when they were retained, whichever directory they're in.
+    retained.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
+
+    Ok(retained)
+}
+
+async fn list_data_files_in(
+    dir: &Path,
+    format: Option<RetainedDataFileFormat>,
+    retained: &mut Vec<RetainedDataFile>,
+) -> io::Result<()> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let is_data_file = entry
+            .file_name()
+            .to_str()
+            .map_or(false, |file_name| file_name.ends_with(".dat"));
+        if !is_data_file {
+            continue;
+        }
+
+        let metadata = entry.metadata().await?;
+        retained.push(RetainedDataFile {
+            path: entry.path(),
+            size: metadata.len(),
+            modified: metadata.modified()?,
+            format,
+        });
+    }
+
+    Ok(())
+}
+
+async fn list_format_dirs(
+    retained_dir: &Path,
+) -> io::Result<Vec<(PathBuf, RetainedDataFileFormat)>> {
+    let mut entries = match fs::read_dir(retained_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut format_dirs = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let format = entry
+            .file_name()
+            .to_str()
+            .and_then(RetainedDataFileFormat::from_dir_name);
+        if let Some(format) = format {
+            if entry.file_type().await?.is_dir() {
+                format_dirs.push((entry.path(), format));
+            }
+        }
+    }
+
+    Ok(format_dirs)
+}
+
+/// Moves the retained data files of a buffer that was migrated to another format into the buffer
+/// it was migrated to.
+///
+/// The retained data files of the old buffer are in the format it had, given by `old_format`, so
+/// they're moved into the directory for that format, while those that were already in the
+/// directory for another format are moved into the same directory in the new buffer.  Data files
+/// are moved one at a time, so this can be picked back up if interrupted.
+///
+/// # Errors
+///
+/// If an I/O error occurs while listing or moving the retained data files, an error variant will
+/// be returned.
+pub(super) async fn carry_over_retained_data_files(
+    old_data_dir: &Path,
+    new_data_dir: &Path,
+    old_format: RetainedDataFileFormat,
+) -> io::Result<()> {
+    let new_retained_dir = get_retained_dir_path(new_data_dir);
+    for file in list_retained_data_files(old_data_dir).await? {
+        let format = file.format.unwrap_or(old_format);
+        let format_dir = new_retained_dir.join(format.dir_name());
+        fs::create_dir_all(&format_dir).await?;
+
+        let file_name = file
+            .path
+            .file_name()
+            .expect("retained data file should have a file name");
+        fs::rename(&file.path, format_dir.join(file_name)).await?;
+    }
+
+    Ok(())
+}
+
+/// Deletes the retained data files that are older than the maximum age, and then the oldest
+/// retained data files until they fit within the maximum size.
+///
+/// # Errors
+///
+/// If an I/O error occurs while listing or deleting the retained data files, an error variant will
+/// be returned.
+pub(super) async fn prune_retained_data_files(
+    data_dir: &Path,
+    retention: &DiskBufferRetention,
+) -> io::Result<()> {
+    let retained = list_retained_data_files(data_dir).await?;
+    let now = SystemTime::now();
+    let mut total_size = retained.iter().map(|file| file.size).sum::<u64>();
+
+    for file in retained {
+        let expired = retention.max_age().map_or(false, |max_age| {
+            now.duration_since(file.modified)
+                .map_or(false, |age| age > max_age)
+        });
+        let oversized = retention
+            .max_size
+            .map_or(false, |max_size| total_size > max_size.get());
+        if !expired && !oversized {
+            // Retained data files are ordered from oldest to newest, so the rest fit as well.
+            break;
+        }
+
+        match fs::remove_file(&file.path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        total_size -= file.size;
+
+        debug!(
+            retained_path = file.path.to_string_lossy().as_ref(),
+            "Deleted retained data file."
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::{
+    any::Any,
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+};
+
+use parking_lot::{const_mutex, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard};
+
+use super::{
+    common::DiskBufferConfig, io::ProductionFilesystem, ledger::Ledger, Buffer, BufferError,
+    Reader, Writer,
+};
+use crate::{buffer_usage_data::BufferUsageHandle, Acker, Bufferable};
+
+/// Shared buffers that are currently open in this process, by data directory.
+///
+/// Every consumer of a shared buffer must use the same writer and ledger, as the buffer can only be
+/// loaded once at a time.
+static OPEN_BUFFERS: Mutex<Vec<(PathBuf, Weak<dyn Any + Send + Sync>)>> = const_mutex(Vec::new());
+
+/// A shared disk buffer, along with which of its senders is writing to it.
+struct SharedDiskBuffer<T>
+where
+    T: Bufferable,
+{
+    writer: AsyncMutex<Writer<T, ProductionFilesystem>>,
+    ledger: Arc<Ledger<ProductionFilesystem>>,
+    writing_sender: Mutex<Option<usize>>,
+    next_sender_id: AtomicUsize,
+}
+
+/// Opens the shared buffer for the given configuration, and attaches a reader to the given consumer.
+///
+/// If the buffer is already open in this process, for another consumer, the existing buffer is
+/// used, and its consumers are synchronized with the ones in the given configuration.
+///
+/// # Errors
+///
+/// If an error occurred during the creation or loading of the disk buffer, or while attaching the
+/// reader to the consumer, an error variant will be returned describing the error.
+pub(super) async fn open_shared_buffer<T>(
+    config: DiskBufferConfig<ProductionFilesystem>,
+    consumer_id: String,
+    usage_handle: BufferUsageHandle,
+) -> Result<(SharedSender<T>, Reader<T, ProductionFilesystem>, Acker), BufferError<T>>
+where
+    T: Bufferable,
+{
+    let data_dir = config.data_dir.clone();
+    let existing = {
+        let mut open_buffers = OPEN_BUFFERS.lock();
+        open_buffers.retain(|(_, buffer)| buffer.strong_count() > 0);
+        open_buffers
+            .iter()
+            .find(|(path, _)| *path == data_dir)
+            .and_then(|(_, buffer)| buffer.upgrade())
+    };
+
+    let buffer = match existing.map(Arc::downcast::<SharedDiskBuffer<T>>) {
+        Some(Ok(buffer)) => {
+            buffer
+                .ledger
+                .sync_consumers(&config.consumers)
+                .await
+                .map_err(|source| BufferError::LedgerError { source })?;
+            buffer
+        }
+        // The buffer is either not open, or open for another type of record, in which case loading
+        // it again fails, as its ledger is locked.
+        _ => {
+            let (writer, ledger) = Buffer::from_config_shared(config, usage_handle.clone()).await?;
+            let buffer = Arc::new(SharedDiskBuffer {
+                writer: AsyncMutex::new(writer),
+                ledger,
+                writing_sender: Mutex::new(None),
+                next_sender_id: AtomicUsize::new(0),
+            });
+
+            let weak: Weak<dyn Any + Send + Sync> = Arc::downgrade(&buffer) as _;
+            OPEN_BUFFERS.lock().push((data_dir, weak));
+            buffer
+        }
+    };
+
+    let (reader, acker, consumer) =
+        Buffer::attach_consumer(&buffer.ledger, &consumer_id, usage_handle).await?;
+    let sender = SharedSender {
+        id: buffer.next_sender_id.fetch_add(1, Ordering::Relaxed),
+        buffer,
+        consumer,
+    };
+
+    Ok((sender, reader, acker))
+}
+
+/// Sender for a single consumer of a shared disk buffer.
+///
+/// Every consumer of a shared buffer is sent the same records, as they're all fed from the same
+/// inputs, but each record must only be written to the buffer once.  Only one sender at a time is
+/// the one writing to the buffer: the first to send a record, until it's dropped.  All other
+/// senders discard the records sent to them.
+pub struct SharedSender<T>
+where
+    T: Bufferable,
+{
+    id: usize,
+    buffer: Arc<SharedDiskBuffer<T>>,
+    consumer: Arc<Ledger<ProductionFilesystem>>,
+}
+
+impl<T> SharedSender<T>
+where
+    T: Bufferable,
+{
+    /// Gets the writer of the buffer, if this sender is the one writing to it.
+    ///
+    /// If no sender is writing to the buffer, this sender becomes the one writing to it.
+    pub(crate) async fn writer(&self) -> Option<MutexGuard<'_, Writer<T, ProductionFilesystem>>> {
+        let is_writing = {
+            let mut writing_sender = self.buffer.writing_sender.lock();
+            *writing_sender.get_or_insert(self.id) == self.id
+        };
+
+        if is_writing {
+            Some(self.buffer.writer.lock().await)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for SharedSender<T>
+where
+    T: Bufferable,
+{
+    fn drop(&mut self) {
+        {
+            let mut writing_sender = self.buffer.writing_sender.lock();
+            if *writing_sender == Some(self.id) {
+                *writing_sender = None;
+            }
+        }
+
+        // The consumer no longer gets new records, so let its reader finish once it has read the
+        // ones already in the buffer.
+        self.consumer.close_consumer();
+    }
+}
+
+impl<T> fmt::Debug for SharedSender<T>
+where
+    T: Bufferable,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSender")
+            .field("id", &self.id)
+            .field("consumer", &self.consumer)
+            .finish_non_exhaustive()
+    }
+}
@@ -8,7 +8,10 @@ use super::create_default_buffer_v2;
 use crate::{
     assert_buffer_is_empty, assert_buffer_records,
     test::common::{install_tracing_helpers, with_temp_dir, MultiEventRecord, SizedRecord},
-    variants::disk_v2::{tests::create_default_buffer_v2_with_usage, writer::RecordWriter},
+    variants::disk_v2::{
+        common::DiskBufferCompression, tests::create_default_buffer_v2_with_usage,
+        writer::RecordWriter,
+    },
     EventCount,
 };
 
@@ -170,8 +173,15 @@ async fn initial_size_correct_with_multievents() {
             // are identical:
             let expected_bytes = stream::iter(input_items.iter().copied())
                 .filter_map(|record| async move {
-                    let mut record_writer =
-                        RecordWriter::new(Cursor::new(Vec::new()), 0, 16_384, u64::MAX, usize::MAX);
+                    let mut record_writer = RecordWriter::new(
+                        Cursor::new(Vec::new()),
+                        0,
+                        16_384,
+                        u64::MAX,
+                        usize::MAX,
+                        DiskBufferCompression::None,
+                        None,
+                    );
                     let (bytes_written, flush_result) = record_writer
                         .write_record(0, record)
                         .await
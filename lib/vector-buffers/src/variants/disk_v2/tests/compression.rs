@@ -0,0 +1,306 @@
+use std::{
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
+
+use bytes::BytesMut;
+
+use super::FilesystemUnderTest;
+use crate::{
+    assert_buffer_is_empty, assert_buffer_records,
+    buffer_usage_data::BufferUsageHandle,
+    test::common::{with_temp_dir, SizedRecord},
+    variants::disk_v2::{
+        backed_archive::BackedArchive,
+        common::DiskBufferCompression,
+        compression_migration::try_disk_v2_compression_migration,
+        get_disk_v2_data_dir_path,
+        ledger::{LegacyLedgerState, LEDGER_VERSION},
+        ser::SerializeError,
+        Buffer, BufferError, DiskBufferConfigBuilder, Ledger, LedgerLoadCreateError, Reader,
+        Writer,
+    },
+    Acker,
+};
+
+async fn create_buffer_v2_with_compression<P>(
+    data_dir: P,
+    compression: DiskBufferCompression,
+) -> Result<
+    (
+        Writer<SizedRecord, FilesystemUnderTest>,
+        Reader<SizedRecord, FilesystemUnderTest>,
+        Acker,
+        Arc<Ledger<FilesystemUnderTest>>,
+        BufferUsageHandle,
+    ),
+    BufferError<SizedRecord>,
+>
+where
+    P: AsRef<Path>,
+{
+    let config = DiskBufferConfigBuilder::from_path(data_dir)
+        .compression(compression)
+        .build()
+        .expect("creating buffer should not fail");
+    let usage_handle = BufferUsageHandle::noop();
+    let (writer, reader, acker, ledger) =
+        Buffer::from_config_inner(config, usage_handle.clone()).await?;
+    Ok((writer, reader, acker, ledger, usage_handle))
+}
+
+#[tokio::test]
+async fn compressed_read_write_loop() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, mut reader, acker, ledger, usage) =
+                create_buffer_v2_with_compression(data_dir, DiskBufferCompression::Zstd)
+                    .await
+                    .expect("should not fail to create buffer");
+            assert_buffer_is_empty!(ledger);
+
+            let expected_items = (512..768)
+                .into_iter()
+                .cycle()
+                .take(10)
+                .map(SizedRecord)
+                .collect::<Vec<_>>();
+            let logical_bytes = expected_items
+                .iter()
+                .map(|record| u64::from(record.0) + 4)
+                .sum::<u64>();
+
+            for item in expected_items.clone() {
+                writer
+                    .write_record(item)
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("writer flush should not fail");
+            writer.close();
+
+            // Our records are highly compressible, so they should take up less space on disk than
+            // their encoded size, while we still track their encoded size as the logical size.
+            assert_buffer_records!(ledger, expected_items.len());
+            let snapshot = usage.snapshot();
+            assert_eq!(logical_bytes, snapshot.received_logical_byte_size);
+            assert!(snapshot.received_byte_size < snapshot.received_logical_byte_size);
+
+            let mut actual_items = Vec::new();
+            while let Some(record) = reader.next().await.expect("reader should not fail") {
+                actual_items.push(record);
+                acker.ack(1);
+            }
+
+            assert_buffer_is_empty!(ledger);
+            assert_eq!(actual_items, expected_items);
+
+            let snapshot = usage.snapshot();
+            assert_eq!(logical_bytes, snapshot.sent_logical_byte_size);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn reopening_with_different_compression_fails() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (writer, reader, acker, ledger, _) =
+                create_buffer_v2_with_compression(&data_dir, DiskBufferCompression::Zstd)
+                    .await
+                    .expect("should not fail to create buffer");
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            match create_buffer_v2_with_compression(&data_dir, DiskBufferCompression::None).await {
+                Err(BufferError::LedgerError {
+                    source: LedgerLoadCreateError::CompressionMismatch { actual, configured },
+                }) => {
+                    assert_eq!(actual, DiskBufferCompression::Zstd);
+                    assert_eq!(configured, DiskBufferCompression::None);
+                }
+                Err(e) => panic!("unexpected error: {}", e),
+                Ok(_) => panic!("buffer should not load with a different compression"),
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn legacy_ledger_is_upgraded() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            // Write a ledger using the layout from before the buffer format was versioned, as if
+            // the buffer had been written to and fully read.
+            let legacy_state = LegacyLedgerState::default();
+            legacy_state
+                .writer_next_record_id
+                .store(43, Ordering::Release);
+            legacy_state
+                .reader_last_record_id
+                .store(42, Ordering::Release);
+
+            let mut buf = BytesMut::new();
+            let mut state = legacy_state;
+            let archive = loop {
+                match BackedArchive::from_value(&mut buf, state) {
+                    Ok(archive) => break archive,
+                    Err(SerializeError::BackingStoreTooSmall(value, min_len)) => {
+                        state = value;
+                        buf.resize(min_len, 0);
+                    }
+                    Err(_) => panic!("serializing legacy ledger should not fail"),
+                }
+            };
+            tokio::fs::write(data_dir.join("buffer.db"), archive.get_backing_ref())
+                .await
+                .expect("writing legacy ledger should not fail");
+
+            let (_, _, _, ledger, _) =
+                create_buffer_v2_with_compression(&data_dir, DiskBufferCompression::None)
+                    .await
+                    .expect("should not fail to load buffer with legacy ledger");
+            assert_eq!(ledger.state().version(), LEDGER_VERSION);
+            assert_eq!(
+                ledger.state().compression(),
+                Some(DiskBufferCompression::None)
+            );
+            assert_eq!(ledger.state().get_next_writer_record_id(), 43);
+            assert_eq!(ledger.state().get_last_reader_record_id(), 42);
+            assert_buffer_is_empty!(ledger);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn migrates_existing_buffer_to_configured_compression() {
+    with_temp_dir(|dir| {
+        let base_dir = dir.to_path_buf();
+
+        async move {
+            let buffer_dir = get_disk_v2_data_dir_path(&base_dir, "test");
+            let expected_items = (1..32).map(SizedRecord).collect::<Vec<_>>();
+
+            let (mut writer, reader, acker, ledger, _) =
+                create_buffer_v2_with_compression(&buffer_dir, DiskBufferCompression::None)
+                    .await
+                    .expect("should not fail to create buffer");
+            for item in expected_items.clone() {
+                writer
+                    .write_record(item)
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("writer flush should not fail");
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            try_disk_v2_compression_migration::<SizedRecord>(
+                &base_dir,
+                "test",
+                DiskBufferCompression::Zstd,
+                None,
+            )
+            .await
+            .expect("migration should not fail");
+
+            let (mut writer, mut reader, acker, ledger, _) =
+                create_buffer_v2_with_compression(&buffer_dir, DiskBufferCompression::Zstd)
+                    .await
+                    .expect("should not fail to load migrated buffer");
+            assert_buffer_records!(ledger, expected_items.len());
+            writer.close();
+
+            let mut actual_items = Vec::new();
+            while let Some(record) = reader.next().await.expect("reader should not fail") {
+                actual_items.push(record);
+                acker.ack(1);
+            }
+            assert_eq!(actual_items, expected_items);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn finishes_migration_interrupted_after_moving_old_buffer_aside() {
+    with_temp_dir(|dir| {
+        let base_dir = dir.to_path_buf();
+
+        async move {
+            let buffer_dir = get_disk_v2_data_dir_path(&base_dir, "test");
+            let migrated_buffer_dir = buffer_dir.with_file_name("test.migrating");
+            let old_buffer_dir = buffer_dir.with_file_name("test.old");
+            let expected_items = (1..32).map(SizedRecord).collect::<Vec<_>>();
+
+            // Leave things as they'd be if we were interrupted right after moving the old buffer
+            // aside: a fully migrated buffer, and the old buffer, but nothing in place.
+            let (mut writer, reader, acker, ledger, _) = create_buffer_v2_with_compression(
+                &migrated_buffer_dir,
+                DiskBufferCompression::Zstd,
+            )
+            .await
+            .expect("should not fail to create buffer");
+            for item in expected_items.clone() {
+                writer
+                    .write_record(item)
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("writer flush should not fail");
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            let (writer, reader, acker, ledger, _) =
+                create_buffer_v2_with_compression(&old_buffer_dir, DiskBufferCompression::None)
+                    .await
+                    .expect("should not fail to create buffer");
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            try_disk_v2_compression_migration::<SizedRecord>(
+                &base_dir,
+                "test",
+                DiskBufferCompression::Zstd,
+                None,
+            )
+            .await
+            .expect("migration should not fail");
+
+            assert!(!migrated_buffer_dir.exists());
+            assert!(!old_buffer_dir.exists());
+
+            let (mut writer, mut reader, acker, ledger, _) =
+                create_buffer_v2_with_compression(&buffer_dir, DiskBufferCompression::Zstd)
+                    .await
+                    .expect("should not fail to load migrated buffer");
+            assert_buffer_records!(ledger, expected_items.len());
+            writer.close();
+
+            let mut actual_items = Vec::new();
+            while let Some(record) = reader.next().await.expect("reader should not fail") {
+                actual_items.push(record);
+                acker.ack(1);
+            }
+            assert_eq!(actual_items, expected_items);
+        }
+    })
+    .await;
+}
@@ -0,0 +1,318 @@
+use std::{
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
+
+use bytes::BytesMut;
+use tracing::Span;
+
+use super::FilesystemUnderTest;
+use crate::{
+    assert_buffer_is_empty, assert_buffer_records,
+    buffer_usage_data::BufferUsageHandle,
+    config::BufferBuildError,
+    test::common::{with_temp_dir, SizedRecord},
+    variants::disk_v2::{
+        backed_archive::BackedArchive,
+        common::DiskBufferCompression,
+        compression_migration::try_disk_v2_compression_migration,
+        encryption::DiskBufferEncryptionKey,
+        get_disk_v2_data_dir_path,
+        ledger::{LedgerStateV2, LEDGER_VERSION},
+        ser::SerializeError,
+        Buffer, BufferError, DiskBufferConfigBuilder, Ledger, LedgerLoadCreateError, Reader,
+        Writer,
+    },
+    Acker, BufferConfig,
+};
+
+async fn create_buffer_v2_with_encryption_key<P>(
+    data_dir: P,
+    encryption_key: Option<DiskBufferEncryptionKey>,
+) -> Result<
+    (
+        Writer<SizedRecord, FilesystemUnderTest>,
+        Reader<SizedRecord, FilesystemUnderTest>,
+        Acker,
+        Arc<Ledger<FilesystemUnderTest>>,
+    ),
+    BufferError<SizedRecord>,
+>
+where
+    P: AsRef<Path>,
+{
+    let config = DiskBufferConfigBuilder::from_path(data_dir)
+        .encryption_key(encryption_key)
+        .build()
+        .expect("creating buffer should not fail");
+    Buffer::from_config_inner(config, BufferUsageHandle::noop()).await
+}
+
+fn encryption_key(byte: u8) -> DiskBufferEncryptionKey {
+    DiskBufferEncryptionKey::from_bytes(&[byte; 32]).expect("key should be valid")
+}
+
+async fn read_data_files(data_dir: &Path) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut entries = tokio::fs::read_dir(data_dir)
+        .await
+        .expect("reading data directory should not fail");
+    while let Some(entry) = entries.next_entry().await.expect("should not fail") {
+        if entry.path().extension().map_or(false, |ext| ext == "dat") {
+            data.extend(
+                tokio::fs::read(entry.path())
+                    .await
+                    .expect("reading data file should not fail"),
+            );
+        }
+    }
+    data
+}
+
+#[tokio::test]
+async fn encrypted_read_write_loop() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_encryption_key(&data_dir, Some(encryption_key(1)))
+                    .await
+                    .expect("should not fail to create buffer");
+            assert_buffer_is_empty!(ledger);
+
+            let expected_items = (512..768)
+                .into_iter()
+                .cycle()
+                .take(10)
+                .map(SizedRecord)
+                .collect::<Vec<_>>();
+            for item in expected_items.clone() {
+                writer
+                    .write_record(item)
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("writer flush should not fail");
+            writer.close();
+            assert_buffer_records!(ledger, expected_items.len());
+
+            // The payload of our records is a long run of the same byte, which must not show up in
+            // the data files in plaintext.
+            let data = read_data_files(&data_dir).await;
+            assert!(!data.is_empty());
+            assert!(!data.windows(64).any(|window| window == [0x42; 64]));
+
+            let mut actual_items = Vec::new();
+            while let Some(record) = reader.next().await.expect("reader should not fail") {
+                actual_items.push(record);
+                acker.ack(1);
+            }
+
+            assert_buffer_is_empty!(ledger);
+            assert_eq!(actual_items, expected_items);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn reopening_with_wrong_or_missing_key_fails() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, reader, acker, ledger) =
+                create_buffer_v2_with_encryption_key(&data_dir, Some(encryption_key(1)))
+                    .await
+                    .expect("should not fail to create buffer");
+            writer
+                .write_record(SizedRecord(64))
+                .await
+                .expect("write should not fail");
+            writer.flush().await.expect("writer flush should not fail");
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            match create_buffer_v2_with_encryption_key(&data_dir, Some(encryption_key(2))).await {
+                Err(
+                    e @ BufferError::LedgerError {
+                        source: LedgerLoadCreateError::WrongEncryptionKey,
+                    },
+                ) => assert!(e.is_encryption_error()),
+                Err(e) => panic!("unexpected error: {}", e),
+                Ok(_) => panic!("buffer should not load with the wrong key"),
+            }
+
+            match create_buffer_v2_with_encryption_key(&data_dir, None).await {
+                Err(
+                    e @ BufferError::LedgerError {
+                        source: LedgerLoadCreateError::EncryptionMismatch { .. },
+                    },
+                ) => assert!(e.is_encryption_error()),
+                Err(e) => panic!("unexpected error: {}", e),
+                Ok(_) => panic!("buffer should not load without a key"),
+            }
+
+            // The right key still works, and nothing was lost along the way.
+            let (mut writer, mut reader, _, ledger) =
+                create_buffer_v2_with_encryption_key(&data_dir, Some(encryption_key(1)))
+                    .await
+                    .expect("should not fail to load buffer");
+            assert_buffer_records!(ledger, 1);
+            writer.close();
+            assert_eq!(
+                reader.next().await.expect("reader should not fail"),
+                Some(SizedRecord(64))
+            );
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn wrong_key_is_reported_when_building_from_config() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let config = |key: &[u8; 32]| -> BufferConfig {
+                serde_yaml::from_str(&format!(
+                    "type: disk\nmax_size: 268435488\nencryption:\n  key: {}\n",
+                    base64::encode(key)
+                ))
+                .expect("config should be valid")
+            };
+
+            let (sender, receiver, acker) = config(&[1; 32])
+                .build::<SizedRecord>(
+                    Some(data_dir.clone()),
+                    "test".to_string(),
+                    &[],
+                    Span::none(),
+                )
+                .await
+                .expect("should not fail to create buffer");
+            drop(sender);
+            drop(receiver);
+            drop(acker);
+
+            match config(&[2; 32])
+                .build::<SizedRecord>(
+                    Some(data_dir.clone()),
+                    "test".to_string(),
+                    &[],
+                    Span::none(),
+                )
+                .await
+            {
+                Err(BufferBuildError::FailedToDecrypt { .. }) => {}
+                Err(e) => panic!("unexpected error: {}", e),
+                Ok(_) => panic!("buffer should not build with the wrong key"),
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn v2_ledger_is_upgraded() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            // Write a ledger using the layout from before encryption was supported, as if the
+            // buffer had been written to and fully read.
+            let v2_state = LedgerStateV2 {
+                version: 2,
+                compression: DiskBufferCompression::None.as_u32(),
+                ..LedgerStateV2::default()
+            };
+            v2_state.writer_next_record_id.store(43, Ordering::Release);
+            v2_state.reader_last_record_id.store(42, Ordering::Release);
+            v2_state.writer_logical_bytes.store(100, Ordering::Release);
+            v2_state.reader_logical_bytes.store(100, Ordering::Release);
+
+            let mut buf = BytesMut::new();
+            let mut state = v2_state;
+            let archive = loop {
+                match BackedArchive::from_value(&mut buf, state) {
+                    Ok(archive) => break archive,
+                    Err(SerializeError::BackingStoreTooSmall(value, min_len)) => {
+                        state = value;
+                        buf.resize(min_len, 0);
+                    }
+                    Err(_) => panic!("serializing v2 ledger should not fail"),
+                }
+            };
+            tokio::fs::write(data_dir.join("buffer.db"), archive.get_backing_ref())
+                .await
+                .expect("writing v2 ledger should not fail");
+
+            let (_, _, _, ledger) = create_buffer_v2_with_encryption_key(&data_dir, None)
+                .await
+                .expect("should not fail to load buffer with v2 ledger");
+            assert_eq!(ledger.state().version(), LEDGER_VERSION);
+            assert!(!ledger.state().is_encrypted());
+            assert_eq!(ledger.state().get_next_writer_record_id(), 43);
+            assert_eq!(ledger.state().get_last_reader_record_id(), 42);
+            assert_buffer_is_empty!(ledger);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn migrates_existing_buffer_to_encryption() {
+    with_temp_dir(|dir| {
+        let base_dir = dir.to_path_buf();
+
+        async move {
+            let buffer_dir = get_disk_v2_data_dir_path(&base_dir, "test");
+            let expected_items = (1..32).map(SizedRecord).collect::<Vec<_>>();
+
+            let (mut writer, reader, acker, ledger) =
+                create_buffer_v2_with_encryption_key(&buffer_dir, None)
+                    .await
+                    .expect("should not fail to create buffer");
+            for item in expected_items.clone() {
+                writer
+                    .write_record(item)
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("writer flush should not fail");
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            try_disk_v2_compression_migration::<SizedRecord>(
+                &base_dir,
+                "test",
+                DiskBufferCompression::None,
+                Some(&encryption_key(1)),
+            )
+            .await
+            .expect("migration should not fail");
+
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_encryption_key(&buffer_dir, Some(encryption_key(1)))
+                    .await
+                    .expect("should not fail to load migrated buffer");
+            assert!(ledger.state().is_encrypted());
+            assert_buffer_records!(ledger, expected_items.len());
+            writer.close();
+
+            let mut actual_items = Vec::new();
+            while let Some(record) = reader.next().await.expect("reader should not fail") {
+                actual_items.push(record);
+                acker.ack(1);
+            }
+            assert_eq!(actual_items, expected_items);
+        }
+    })
+    .await;
+}
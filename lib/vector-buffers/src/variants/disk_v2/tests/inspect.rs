@@ -0,0 +1,209 @@
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use super::{create_buffer_v2_with_max_data_file_size, create_default_buffer_v2};
+use crate::{
+    assert_reader_writer_v2_file_positions, await_timeout, set_data_file_length,
+    test::common::{with_temp_dir, SizedRecord},
+    variants::disk_v2::{DiskBufferInspector, InspectError},
+};
+
+#[tokio::test]
+async fn inspector_reports_ledger_and_records() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, mut reader, acker, ledger) =
+                create_default_buffer_v2(data_dir.clone()).await;
+
+            for record_size in [32, 33, 34] {
+                writer
+                    .write_record(SizedRecord(record_size))
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("flush should not fail");
+
+            // Read and acknowledge the first record, and then read the second record, which
+            // processes the acknowledgement and updates the ledger.
+            let first_read = await_timeout!(reader.next(), 2).expect("read should not fail");
+            assert_eq!(first_read, Some(SizedRecord(32)));
+            acker.ack(1);
+            let second_read = await_timeout!(reader.next(), 2).expect("read should not fail");
+            assert_eq!(second_read, Some(SizedRecord(33)));
+
+            drop(writer);
+            drop(reader);
+            drop(ledger);
+
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            assert!(inspector.can_decode());
+            assert!(!inspector.ledger().encrypted);
+            assert_eq!(inspector.ledger().writer_current_data_file_id, 0);
+            assert_eq!(inspector.ledger().reader_current_data_file_id, 0);
+
+            let data_files = inspector
+                .data_files()
+                .await
+                .expect("listing data files should not fail");
+            assert_eq!(data_files.len(), 1);
+
+            let (file_id, path) = &data_files[0];
+            let mut records = Vec::new();
+            let summary = inspector
+                .scan_data_file(*file_id, path, |_, record: SizedRecord| {
+                    records.push(record)
+                })
+                .await
+                .expect("scanning data file should not fail");
+
+            assert_eq!(
+                records,
+                vec![SizedRecord(32), SizedRecord(33), SizedRecord(34)]
+            );
+            assert_eq!(summary.records, 3);
+            assert_eq!(summary.unread_records, 2);
+            assert_eq!(summary.events, Some(3));
+            assert_eq!(summary.unread_events, Some(2));
+            assert_eq!(summary.undecodable_records, 0);
+            assert_eq!(summary.valid_len, summary.size);
+            assert!(!summary.is_corrupted());
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn inspector_fails_without_buffer() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let result = DiskBufferInspector::open(&data_dir, None).await;
+            assert!(matches!(result, Err(InspectError::NotFound { .. })));
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn repair_truncates_corrupted_tail() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            // Write two records to the first data file, and one to the second, so that we can
+            // corrupt the first data file after the writer has moved on from it.
+            let (mut writer, _, _, ledger) =
+                create_buffer_v2_with_max_data_file_size(data_dir.clone(), 172).await;
+
+            let first_bytes_written = writer
+                .write_record(SizedRecord(32))
+                .await
+                .expect("write should not fail");
+            let second_bytes_written = writer
+                .write_record(SizedRecord(33))
+                .await
+                .expect("write should not fail");
+            writer.flush().await.expect("flush should not fail");
+            let first_data_file_path = ledger.get_current_writer_data_file_path();
+
+            writer
+                .write_record(SizedRecord(34))
+                .await
+                .expect("write should not fail");
+            writer.flush().await.expect("flush should not fail");
+            assert_reader_writer_v2_file_positions!(ledger, 0, 1);
+
+            drop(writer);
+            drop(ledger);
+
+            // Cut the second record in half.
+            let corrupted_len = first_bytes_written + (second_bytes_written / 2);
+            set_data_file_length!(
+                &first_data_file_path,
+                first_bytes_written + second_bytes_written,
+                corrupted_len
+            );
+
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let data_files = inspector
+                .data_files()
+                .await
+                .expect("listing data files should not fail");
+            assert_eq!(
+                data_files.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+                vec![0, 1]
+            );
+
+            let (file_id, path) = &data_files[0];
+            let summary = inspector
+                .scan_data_file(*file_id, path, |_, _: SizedRecord| {})
+                .await
+                .expect("scanning data file should not fail");
+            assert_eq!(summary.records, 1);
+            assert_eq!(summary.size, corrupted_len as u64);
+            assert_eq!(summary.valid_len, first_bytes_written as u64);
+            assert!(summary.is_corrupted());
+
+            // Repairing should only touch the corrupted data file, and truncate it to its valid
+            // records.
+            let repaired = inspector
+                .repair::<SizedRecord>()
+                .await
+                .expect("repair should not fail");
+            assert_eq!(repaired.len(), 1);
+            assert_eq!(repaired[0].file_id, 0);
+
+            let summary = inspector
+                .scan_data_file(*file_id, path, |_, _: SizedRecord| {})
+                .await
+                .expect("scanning data file should not fail");
+            assert_eq!(summary.records, 1);
+            assert_eq!(summary.size, first_bytes_written as u64);
+            assert!(!summary.is_corrupted());
+
+            // The repaired buffer should still load, and give us the records that survived.
+            let (mut writer, mut reader, acker, _) =
+                create_default_buffer_v2::<_, SizedRecord>(data_dir).await;
+            writer.close();
+
+            let first_read = await_timeout!(reader.next(), 2).expect("read should not fail");
+            assert_eq!(first_read, Some(SizedRecord(32)));
+            acker.ack(1);
+
+            let second_read = await_timeout!(reader.next(), 2).expect("read should not fail");
+            assert_eq!(second_read, Some(SizedRecord(34)));
+            acker.ack(1);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn repair_fails_while_buffer_is_in_use() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, _, _, _ledger) = create_default_buffer_v2(data_dir.clone()).await;
+            writer
+                .write_record(SizedRecord(32))
+                .await
+                .expect("write should not fail");
+            writer.flush().await.expect("flush should not fail");
+
+            // Inspecting is fine while the buffer is loaded, but repairing is not.
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let result = inspector.repair::<SizedRecord>().await;
+            assert!(matches!(result, Err(InspectError::Ledger { .. })));
+        }
+    })
+    .await;
+}
@@ -17,10 +17,15 @@ type FilesystemUnderTest = ProductionFilesystem;
 
 mod acknowledgements;
 mod basic;
+mod compression;
+mod encryption;
+mod inspect;
 mod invariants;
 mod known_errors;
 mod model;
 mod record;
+mod retention;
+mod shared;
 mod size_limits;
 
 #[async_trait]
@@ -261,6 +261,16 @@ impl FilesystemInner {
     fn delete_file(&mut self, path: &Path) -> bool {
         self.files.remove(path).is_some()
     }
+
+    fn rename_file(&mut self, from: &Path, to: &Path) -> bool {
+        match self.files.remove(from) {
+            Some(file) => {
+                self.files.insert(to.to_owned(), file);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 /// A `Filesystem` that tracks files in memory and allows introspection from the outside.
@@ -334,4 +344,13 @@ impl Filesystem for TestFilesystem {
             Err(io_err_not_found())
         }
     }
+
+    async fn rename_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.rename_file(from, to) {
+            Ok(())
+        } else {
+            Err(io_err_not_found())
+        }
+    }
 }
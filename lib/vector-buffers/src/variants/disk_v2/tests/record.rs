@@ -2,7 +2,12 @@ use std::io::Cursor;
 
 use crate::{
     test::common::SizedRecord,
-    variants::disk_v2::{reader::RecordReader, writer::RecordWriter},
+    variants::disk_v2::{
+        common::DiskBufferCompression,
+        encryption::DiskBufferEncryptionKey,
+        reader::{ReaderError, RecordReader},
+        writer::{RecordWriter, WriterError},
+    },
 };
 
 #[tokio::test]
@@ -10,8 +15,16 @@ async fn roundtrip_through_record_writer_and_record_reader() {
     // Create a duplex stream that's more than big enough to ship a record through.
     let (writer_io, reader_io) = tokio::io::duplex(4096);
 
-    let mut record_writer = RecordWriter::new(writer_io, 0, 16_384, u64::MAX, 2048);
-    let mut record_reader = RecordReader::new(reader_io);
+    let mut record_writer = RecordWriter::new(
+        writer_io,
+        0,
+        16_384,
+        u64::MAX,
+        2048,
+        DiskBufferCompression::None,
+        None,
+    );
+    let mut record_reader = RecordReader::new(reader_io, DiskBufferCompression::None, None);
 
     let record = SizedRecord(73);
 
@@ -38,11 +51,133 @@ async fn roundtrip_through_record_writer_and_record_reader() {
     assert_eq!(record, roundtrip_record);
 }
 
+#[tokio::test]
+async fn roundtrip_compressed_record() {
+    let (writer_io, reader_io) = tokio::io::duplex(4096);
+
+    let mut record_writer = RecordWriter::new(
+        writer_io,
+        0,
+        16_384,
+        u64::MAX,
+        2048,
+        DiskBufferCompression::Zstd,
+        None,
+    );
+    let mut record_reader = RecordReader::new(reader_io, DiskBufferCompression::Zstd, None);
+
+    // The record is well within the record size limit once compressed, but the limit applies to
+    // the encoded record before compression, so it's still rejected.
+    let oversized_record = SizedRecord(4096);
+    assert!(matches!(
+        record_writer.write_record(1, oversized_record).await,
+        Err(WriterError::FailedToEncode { .. } | WriterError::RecordTooLarge { .. })
+    ));
+
+    let record = SizedRecord(1024);
+    let (bytes_written, _) = record_writer
+        .write_record(314, record.clone())
+        .await
+        .expect("write should not fail");
+    record_writer.flush().await.expect("flush should not fail");
+    assert!(bytes_written < 1024);
+
+    let read_token = record_reader
+        .try_next_record(false)
+        .await
+        .expect("read should not fail")
+        .expect("record should be present");
+    assert_eq!(bytes_written, read_token.record_bytes());
+
+    let roundtrip_record = record_reader
+        .read_record(read_token)
+        .expect("read should not fail");
+    assert_eq!(record, roundtrip_record);
+    assert_eq!(1028, record_reader.last_decoded_len());
+}
+
+#[tokio::test]
+async fn roundtrip_encrypted_record() {
+    let (writer_io, reader_io) = tokio::io::duplex(8192);
+
+    let encryption_key =
+        DiskBufferEncryptionKey::from_bytes(&[7; 32]).expect("key should be valid");
+    let mut record_writer = RecordWriter::new(
+        writer_io,
+        0,
+        16_384,
+        u64::MAX,
+        2048,
+        DiskBufferCompression::Zstd,
+        Some(encryption_key.clone()),
+    );
+    let mut record_reader =
+        RecordReader::new(reader_io, DiskBufferCompression::Zstd, Some(encryption_key));
+
+    // Records are compressed before being encrypted, so they still take up less space on disk.
+    let record = SizedRecord(1024);
+    let (bytes_written, _) = record_writer
+        .write_record(314, record.clone())
+        .await
+        .expect("write should not fail");
+    record_writer.flush().await.expect("flush should not fail");
+    assert!(bytes_written < 1024);
+
+    let read_token = record_reader
+        .try_next_record(false)
+        .await
+        .expect("read should not fail")
+        .expect("record should be present");
+    let roundtrip_record = record_reader
+        .read_record(read_token)
+        .expect("read should not fail");
+    assert_eq!(record, roundtrip_record);
+    assert_eq!(1028, record_reader.last_decoded_len());
+}
+
+#[tokio::test]
+async fn encrypted_record_fails_to_decrypt_with_wrong_key() {
+    let (writer_io, reader_io) = tokio::io::duplex(4096);
+
+    let mut record_writer = RecordWriter::new(
+        writer_io,
+        0,
+        16_384,
+        u64::MAX,
+        2048,
+        DiskBufferCompression::None,
+        Some(DiskBufferEncryptionKey::from_bytes(&[7; 32]).expect("key should be valid")),
+    );
+    let mut record_reader = RecordReader::<_, SizedRecord>::new(
+        reader_io,
+        DiskBufferCompression::None,
+        Some(DiskBufferEncryptionKey::from_bytes(&[8; 32]).expect("key should be valid")),
+    );
+
+    record_writer
+        .write_record(314, SizedRecord(73))
+        .await
+        .expect("write should not fail");
+    record_writer.flush().await.expect("flush should not fail");
+
+    // The record itself is intact, so it passes its checksum, but can't be decrypted.
+    let read_token = record_reader
+        .try_next_record(false)
+        .await
+        .expect("read should not fail")
+        .expect("record should be present");
+    assert_eq!(
+        record_reader.read_record(read_token),
+        Err(ReaderError::Decryption)
+    );
+}
+
 #[tokio::test]
 async fn record_reader_always_returns_none_when_no_data() {
     let reader_io = Cursor::new(Vec::new());
 
-    let mut record_reader = RecordReader::<_, SizedRecord>::new(reader_io);
+    let mut record_reader =
+        RecordReader::<_, SizedRecord>::new(reader_io, DiskBufferCompression::None, None);
     let read_token = record_reader
         .try_next_record(false)
         .await
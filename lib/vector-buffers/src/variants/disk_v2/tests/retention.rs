@@ -0,0 +1,292 @@
+use std::{num::NonZeroU64, path::Path, sync::Arc, time::UNIX_EPOCH};
+
+use super::FilesystemUnderTest;
+use crate::{
+    assert_reader_writer_v2_file_positions, await_timeout,
+    buffer_usage_data::BufferUsageHandle,
+    test::common::{with_temp_dir, SizedRecord},
+    variants::disk_v2::{
+        compression_migration::try_disk_v2_compression_migration, get_disk_v2_data_dir_path,
+        Buffer, DiskBufferCompression, DiskBufferConfigBuilder, DiskBufferInspector,
+        DiskBufferRetention, Ledger, Reader, RetainedDataFileFormat, Writer,
+    },
+    Acker,
+};
+
+async fn create_buffer_v2_with_retention<P>(
+    data_dir: P,
+    retention: DiskBufferRetention,
+) -> (
+    Writer<SizedRecord, FilesystemUnderTest>,
+    Reader<SizedRecord, FilesystemUnderTest>,
+    Acker,
+    Arc<Ledger<FilesystemUnderTest>>,
+)
+where
+    P: AsRef<Path>,
+{
+    // Small enough that two of our records fit in a data file, but a third does not.
+    let config = DiskBufferConfigBuilder::from_path(data_dir)
+        .max_data_file_size(172)
+        .retention(Some(retention))
+        .build()
+        .expect("creating buffer should not fail");
+
+    Buffer::from_config_inner(config, BufferUsageHandle::noop())
+        .await
+        .expect("should not fail to create buffer")
+}
+
+/// Writes two records to the first data file, and one to the second, and then reads all three,
+/// acknowledging the first two, which releases the first data file.
+async fn release_first_data_file(
+    writer: &mut Writer<SizedRecord, FilesystemUnderTest>,
+    reader: &mut Reader<SizedRecord, FilesystemUnderTest>,
+    acker: &Acker,
+    ledger: &Ledger<FilesystemUnderTest>,
+) {
+    for record_size in [32, 33, 34] {
+        writer
+            .write_record(SizedRecord(record_size))
+            .await
+            .expect("write should not fail");
+        writer.flush().await.expect("flush should not fail");
+    }
+    assert_reader_writer_v2_file_positions!(ledger, 0, 1);
+
+    for record_size in [32, 33] {
+        let read = await_timeout!(reader.next(), 2).expect("read should not fail");
+        assert_eq!(read, Some(SizedRecord(record_size)));
+        acker.ack(1);
+    }
+    let read = await_timeout!(reader.next(), 2).expect("read should not fail");
+    assert_eq!(read, Some(SizedRecord(34)));
+    assert_reader_writer_v2_file_positions!(ledger, 1, 1);
+}
+
+#[tokio::test]
+async fn acknowledged_data_file_is_retained() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let retention = DiskBufferRetention {
+                max_age_secs: NonZeroU64::new(3600),
+                max_size: None,
+            };
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_retention(data_dir.clone(), retention).await;
+            let first_data_file_path = ledger.get_data_file_path(0);
+
+            release_first_data_file(&mut writer, &mut reader, &acker, &ledger).await;
+            assert!(!first_data_file_path.exists());
+
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let retained = inspector
+                .retained_data_files()
+                .await
+                .expect("listing retained data files should not fail");
+            assert_eq!(retained.len(), 1);
+            assert!(retained[0].size > 0);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn retained_data_files_pruned_by_size() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            // Any data file is bigger than this, so none of them are retained for long.
+            let retention = DiskBufferRetention {
+                max_age_secs: None,
+                max_size: NonZeroU64::new(1),
+            };
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_retention(data_dir.clone(), retention).await;
+
+            release_first_data_file(&mut writer, &mut reader, &acker, &ledger).await;
+
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let retained = inspector
+                .retained_data_files()
+                .await
+                .expect("listing retained data files should not fail");
+            assert!(retained.is_empty());
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn retained_data_files_pruned_on_load() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let retention = DiskBufferRetention {
+                max_age_secs: NonZeroU64::new(3600),
+                max_size: None,
+            };
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_retention(data_dir.clone(), retention).await;
+            release_first_data_file(&mut writer, &mut reader, &acker, &ledger).await;
+
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            // Loading the buffer with a retention that the retained data file exceeds prunes it,
+            // even though no other data file gets retained.
+            let retention = DiskBufferRetention {
+                max_age_secs: None,
+                max_size: NonZeroU64::new(1),
+            };
+            let (_writer, _reader, _acker, _ledger) =
+                create_buffer_v2_with_retention(data_dir.clone(), retention).await;
+
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let retained = inspector
+                .retained_data_files()
+                .await
+                .expect("listing retained data files should not fail");
+            assert!(retained.is_empty());
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn retained_records_replayed_after_unread_records() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let retention = DiskBufferRetention {
+                max_age_secs: NonZeroU64::new(3600),
+                max_size: None,
+            };
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_retention(data_dir.clone(), retention).await;
+            release_first_data_file(&mut writer, &mut reader, &acker, &ledger).await;
+
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let summary = inspector
+                .replay_retained::<SizedRecord>(UNIX_EPOCH)
+                .await
+                .expect("replaying should not fail");
+            assert_eq!(summary.data_files, 1);
+            assert_eq!(summary.records, 2);
+            assert_eq!(summary.events, 2);
+
+            // Replayed data files are retained again once their records are acknowledged, so
+            // they're no longer retained in the meantime.
+            let retained = inspector
+                .retained_data_files()
+                .await
+                .expect("listing retained data files should not fail");
+            assert!(retained.is_empty());
+
+            // The record that was never acknowledged comes first, followed by the replayed ones.
+            let (mut writer, mut reader, acker, _ledger) =
+                create_buffer_v2_with_retention(data_dir, retention).await;
+            writer.close();
+
+            for record_size in [34, 32, 33] {
+                let read = await_timeout!(reader.next(), 2).expect("read should not fail");
+                assert_eq!(read, Some(SizedRecord(record_size)));
+                acker.ack(1);
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn retained_data_files_carried_over_by_compression_migration() {
+    with_temp_dir(|dir| {
+        let base_dir = dir.to_path_buf();
+
+        async move {
+            let data_dir = get_disk_v2_data_dir_path(&base_dir, "test");
+            let retention = DiskBufferRetention {
+                max_age_secs: NonZeroU64::new(3600),
+                max_size: None,
+            };
+            let (mut writer, mut reader, acker, ledger) =
+                create_buffer_v2_with_retention(data_dir.clone(), retention).await;
+            release_first_data_file(&mut writer, &mut reader, &acker, &ledger).await;
+
+            drop(writer);
+            drop(reader);
+            drop(acker);
+            drop(ledger);
+
+            try_disk_v2_compression_migration::<SizedRecord>(
+                &base_dir,
+                "test",
+                DiskBufferCompression::Zstd,
+                None,
+            )
+            .await
+            .expect("migration should not fail");
+
+            // The retained data file is kept in the format it was written in, so it can still be
+            // replayed into the migrated buffer.
+            let inspector = DiskBufferInspector::open(&data_dir, None)
+                .await
+                .expect("opening buffer should not fail");
+            let retained = inspector
+                .retained_data_files()
+                .await
+                .expect("listing retained data files should not fail");
+            assert_eq!(retained.len(), 1);
+            assert_eq!(
+                retained[0].format,
+                Some(RetainedDataFileFormat {
+                    compression: DiskBufferCompression::None,
+                    encrypted: false,
+                })
+            );
+
+            let summary = inspector
+                .replay_retained::<SizedRecord>(UNIX_EPOCH)
+                .await
+                .expect("replaying should not fail");
+            assert_eq!(summary.records, 2);
+
+            let config = DiskBufferConfigBuilder::from_path(data_dir)
+                .compression(DiskBufferCompression::Zstd)
+                .build()
+                .expect("creating buffer should not fail");
+            let (mut writer, mut reader, acker, _ledger) =
+                Buffer::<SizedRecord>::from_config_inner(config, BufferUsageHandle::noop())
+                    .await
+                    .expect("should not fail to load migrated buffer");
+            writer.close();
+
+            for record_size in [34, 32, 33] {
+                let read = await_timeout!(reader.next(), 2).expect("read should not fail");
+                assert_eq!(read, Some(SizedRecord(record_size)));
+                acker.ack(1);
+            }
+        }
+    })
+    .await;
+}
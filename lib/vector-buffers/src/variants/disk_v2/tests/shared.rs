@@ -0,0 +1,182 @@
+use std::{path::Path, sync::Arc};
+
+use super::FilesystemUnderTest;
+use crate::{
+    assert_reader_writer_v2_file_positions, await_timeout,
+    buffer_usage_data::BufferUsageHandle,
+    test::common::{with_temp_dir, SizedRecord},
+    variants::disk_v2::{
+        Buffer, BufferError, DiskBufferConfigBuilder, Ledger, LedgerLoadCreateError, Writer,
+    },
+};
+
+async fn create_shared_buffer_v2<P>(
+    data_dir: P,
+    consumers: &[&str],
+) -> (
+    Writer<SizedRecord, FilesystemUnderTest>,
+    Arc<Ledger<FilesystemUnderTest>>,
+)
+where
+    P: AsRef<Path>,
+{
+    // Small enough that two of our records fit in a data file, but a third does not.
+    let config = DiskBufferConfigBuilder::from_path(data_dir)
+        .max_data_file_size(172)
+        .consumers(consumers.iter().map(ToString::to_string).collect())
+        .build()
+        .expect("creating buffer should not fail");
+
+    Buffer::from_config_shared(config, BufferUsageHandle::noop())
+        .await
+        .expect("should not fail to create buffer")
+}
+
+#[tokio::test]
+async fn consumers_read_independently() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, ledger) = create_shared_buffer_v2(data_dir, &["a", "b"]).await;
+            let (mut reader_a, acker_a, _) =
+                Buffer::attach_consumer(&ledger, "a", BufferUsageHandle::noop())
+                    .await
+                    .expect("attaching consumer should not fail");
+            let (mut reader_b, acker_b, _) =
+                Buffer::attach_consumer(&ledger, "b", BufferUsageHandle::noop())
+                    .await
+                    .expect("attaching consumer should not fail");
+
+            for record_size in [32, 33] {
+                writer
+                    .write_record(SizedRecord(record_size))
+                    .await
+                    .expect("write should not fail");
+            }
+            writer.flush().await.expect("flush should not fail");
+            writer.close();
+
+            // Consumer A reads everything before consumer B reads anything, and both see every
+            // record.
+            for (reader, acker) in [(&mut reader_a, &acker_a), (&mut reader_b, &acker_b)] {
+                for record_size in [32, 33] {
+                    let read = await_timeout!(reader.next(), 2).expect("read should not fail");
+                    assert_eq!(read, Some(SizedRecord(record_size)));
+                    acker.ack(1);
+                }
+                let read = await_timeout!(reader.next(), 2).expect("read should not fail");
+                assert_eq!(read, None);
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn data_file_deleted_once_every_consumer_acked() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (mut writer, ledger) = create_shared_buffer_v2(data_dir, &["a", "b"]).await;
+            let (mut reader_a, acker_a, ledger_a) =
+                Buffer::attach_consumer(&ledger, "a", BufferUsageHandle::noop())
+                    .await
+                    .expect("attaching consumer should not fail");
+            let (mut reader_b, acker_b, ledger_b) =
+                Buffer::attach_consumer(&ledger, "b", BufferUsageHandle::noop())
+                    .await
+                    .expect("attaching consumer should not fail");
+
+            // Write two records to the first data file, and one to the second.
+            for record_size in [32, 33, 34] {
+                writer
+                    .write_record(SizedRecord(record_size))
+                    .await
+                    .expect("write should not fail");
+                writer.flush().await.expect("flush should not fail");
+            }
+            let first_data_file_path = ledger.get_data_file_path(0);
+            assert_reader_writer_v2_file_positions!(ledger, 0, 1);
+
+            // Consumer A moving on to the second data file doesn't delete the first one, as consumer
+            // B has yet to read it.
+            for record_size in [32, 33] {
+                let read = await_timeout!(reader_a.next(), 2).expect("read should not fail");
+                assert_eq!(read, Some(SizedRecord(record_size)));
+                acker_a.ack(1);
+            }
+            let read = await_timeout!(reader_a.next(), 2).expect("read should not fail");
+            assert_eq!(read, Some(SizedRecord(34)));
+            assert_reader_writer_v2_file_positions!(ledger_a, 1, 1);
+            assert_reader_writer_v2_file_positions!(ledger, 0, 1);
+            assert!(first_data_file_path.exists());
+
+            // Once consumer B moves on as well, the first data file is deleted.
+            for record_size in [32, 33] {
+                let read = await_timeout!(reader_b.next(), 2).expect("read should not fail");
+                assert_eq!(read, Some(SizedRecord(record_size)));
+                acker_b.ack(1);
+            }
+            let read = await_timeout!(reader_b.next(), 2).expect("read should not fail");
+            assert_eq!(read, Some(SizedRecord(34)));
+            assert_reader_writer_v2_file_positions!(ledger_b, 1, 1);
+            assert_reader_writer_v2_file_positions!(ledger, 1, 1);
+            assert!(!first_data_file_path.exists());
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn consumer_only_attaches_once() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (_writer, ledger) = create_shared_buffer_v2(data_dir, &["a"]).await;
+            let attached =
+                Buffer::<SizedRecord>::attach_consumer(&ledger, "a", BufferUsageHandle::noop())
+                    .await
+                    .expect("attaching consumer should not fail");
+
+            let result =
+                Buffer::<SizedRecord>::attach_consumer(&ledger, "a", BufferUsageHandle::noop())
+                    .await;
+            assert!(matches!(
+                result,
+                Err(BufferError::LedgerError {
+                    source: LedgerLoadCreateError::ConsumerAlreadyAttached { .. }
+                })
+            ));
+
+            // Once the first reader is gone, the consumer can be attached again.
+            drop(attached);
+            Buffer::<SizedRecord>::attach_consumer(&ledger, "a", BufferUsageHandle::noop())
+                .await
+                .expect("attaching consumer should not fail");
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn unconfigured_consumer_removed_on_load() {
+    with_temp_dir(|dir| {
+        let data_dir = dir.to_path_buf();
+
+        async move {
+            let (writer, ledger) = create_shared_buffer_v2(data_dir.clone(), &["a", "b"]).await;
+            assert!(data_dir.join("buffer-consumer-a.db").exists());
+            assert!(data_dir.join("buffer-consumer-b.db").exists());
+            drop(writer);
+            drop(ledger);
+
+            let (_writer, _ledger) = create_shared_buffer_v2(data_dir.clone(), &["a"]).await;
+            assert!(data_dir.join("buffer-consumer-a.db").exists());
+            assert!(!data_dir.join("buffer-consumer-b.db").exists());
+        }
+    })
+    .await;
+}
@@ -24,7 +24,8 @@ use snafu::{ResultExt, Snafu};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 use super::{
-    common::{create_crc32c_hasher, DiskBufferConfig},
+    common::{create_crc32c_hasher, DiskBufferCompression, DiskBufferConfig},
+    encryption::DiskBufferEncryptionKey,
     io::Filesystem,
     ledger::Ledger,
     record::{validate_record_archive, Record, RecordStatus},
@@ -32,7 +33,9 @@ use super::{
 use crate::{
     encoding::{AsMetadata, Encodable},
     variants::disk_v2::{
-        io::AsyncFile, reader::decode_record_payload, record::try_as_record_archive,
+        io::AsyncFile,
+        reader::{decode_record_payload, ReaderError},
+        record::try_as_record_archive,
     },
     Bufferable,
 };
@@ -105,6 +108,20 @@ where
     #[snafu(display("failed to serialize encoded record to buffer: {}", reason))]
     FailedToSerialize { reason: String },
 
+    /// The writer failed to compress the encoded record.
+    ///
+    /// Compression of an in-memory buffer should not fail in practice, short of the system being
+    /// unable to allocate enough memory for the compressor.
+    #[snafu(display("failed to compress encoded record: {}", source))]
+    FailedToCompress { source: io::Error },
+
+    /// The writer failed to encrypt the encoded record.
+    ///
+    /// This should only occur if the system is unable to provide the random bytes needed for the
+    /// nonce of the record.
+    #[snafu(display("failed to encrypt encoded record"))]
+    FailedToEncrypt,
+
     /// The writer failed to validate the last written record.
     ///
     /// Specifically, for `Writer`, this can only ever be returned when creating the buffer, during
@@ -114,6 +131,16 @@ where
     #[snafu(display("failed to validate the last written record: {}", reason))]
     FailedToValidate { reason: String },
 
+    /// The writer failed to decrypt the last written record.
+    ///
+    /// Like `FailedToValidate`, this can only ever be returned when creating the buffer.  As the
+    /// record passed its checksum, and the fingerprint of the encryption key in the ledger matched,
+    /// the record was most likely altered on disk by something other than Vector.
+    #[snafu(display(
+        "failed to decrypt the last written record: it was not written with the configured encryption key, or was tampered with"
+    ))]
+    FailedToDecrypt,
+
     /// The writer entered an inconsistent state that represents an unrecoverable error.
     ///
     /// In some cases, like expecting to be able to decode an event we just encoded, we might hit an
@@ -206,6 +233,7 @@ where
 pub(super) struct FlushResult {
     pub events_flushed: u64,
     pub bytes_flushed: u64,
+    pub logical_bytes_flushed: u64,
 }
 
 /// Wraps an [`AsyncWrite`] value and buffers individual writes, while signalling implicit flushes.
@@ -217,11 +245,13 @@ pub(super) struct FlushResult {
 ///
 /// If an implicit buffer flush must be performed before a write can complete, or a manual flush is
 /// requested, we return this information to the caller, letting them know how many events, and how
-/// many bytes, were flushed.
+/// many bytes, were flushed.  As records may be compressed, we also track the logical size of the
+/// records, which is their encoded size before compression.
 struct TrackingBufWriter<W> {
     inner: W,
     buf: Vec<u8>,
     unflushed_events: usize,
+    unflushed_logical_bytes: usize,
 }
 
 impl<W: AsyncWrite + Unpin> TrackingBufWriter<W> {
@@ -231,6 +261,7 @@ impl<W: AsyncWrite + Unpin> TrackingBufWriter<W> {
             inner,
             buf: Vec::with_capacity(cap),
             unflushed_events: 0,
+            unflushed_logical_bytes: 0,
         }
     }
 
@@ -255,7 +286,12 @@ impl<W: AsyncWrite + Unpin> TrackingBufWriter<W> {
     ///
     /// If a write to the inner writer occurs, and that write encounters an error, an error variant
     /// will be returned describing the error.
-    async fn write(&mut self, event_count: usize, buf: &[u8]) -> io::Result<Option<FlushResult>> {
+    async fn write(
+        &mut self,
+        event_count: usize,
+        logical_len: usize,
+        buf: &[u8],
+    ) -> io::Result<Option<FlushResult>> {
         let mut flush_result = None;
 
         // If this write would cause us to exceed our internal buffer capacity, flush whatever we
@@ -271,9 +307,11 @@ impl<W: AsyncWrite + Unpin> TrackingBufWriter<W> {
             let flush_result = flush_result.get_or_insert(FlushResult::default());
             flush_result.events_flushed += event_count as u64;
             flush_result.bytes_flushed += buf.len() as u64;
+            flush_result.logical_bytes_flushed += logical_len as u64;
         } else {
             self.buf.extend_from_slice(buf);
             self.unflushed_events += event_count;
+            self.unflushed_logical_bytes += logical_len;
         }
 
         Ok(flush_result)
@@ -299,15 +337,18 @@ impl<W: AsyncWrite + Unpin> TrackingBufWriter<W> {
 
         let events_flushed = self.unflushed_events as u64;
         let bytes_flushed = self.buf.len() as u64;
+        let logical_bytes_flushed = self.unflushed_logical_bytes as u64;
 
         let result = self.inner.write_all(&self.buf[..]).await;
         self.unflushed_events = 0;
+        self.unflushed_logical_bytes = 0;
         self.buf.clear();
 
         result.map(|_| {
             Some(FlushResult {
                 events_flushed,
                 bytes_flushed,
+                logical_bytes_flushed,
             })
         })
     }
@@ -333,6 +374,7 @@ impl<W: fmt::Debug> fmt::Debug for TrackingBufWriter<W> {
                 &format_args!("{}/{}", self.buf.len(), self.buf.capacity()),
             )
             .field("unflushed_events", &self.unflushed_events)
+            .field("unflushed_logical_bytes", &self.unflushed_logical_bytes)
             .finish()
     }
 }
@@ -342,9 +384,13 @@ impl<W: fmt::Debug> fmt::Debug for TrackingBufWriter<W> {
 pub(super) struct RecordWriter<W, T> {
     writer: TrackingBufWriter<W>,
     encode_buf: Vec<u8>,
+    compress_buf: Vec<u8>,
+    encrypt_buf: Vec<u8>,
     ser_buf: AlignedVec,
     ser_scratch: AlignedVec,
     checksummer: Hasher,
+    compression: DiskBufferCompression,
+    encryption_key: Option<DiskBufferEncryptionKey>,
     max_record_size: usize,
     current_data_file_size: u64,
     max_data_file_size: u64,
@@ -366,13 +412,19 @@ where
         write_buffer_size: usize,
         max_data_file_size: u64,
         max_record_size: usize,
+        compression: DiskBufferCompression,
+        encryption_key: Option<DiskBufferEncryptionKey>,
     ) -> Self {
         Self {
             writer: TrackingBufWriter::with_capacity(write_buffer_size, writer),
             encode_buf: Vec::with_capacity(16_384),
+            compress_buf: Vec::new(),
+            encrypt_buf: Vec::new(),
             ser_buf: AlignedVec::with_capacity(16_384),
             ser_scratch: AlignedVec::with_capacity(16_384),
             checksummer: create_crc32c_hasher(),
+            compression,
+            encryption_key,
             max_record_size,
             current_data_file_size,
             max_data_file_size,
@@ -399,9 +451,13 @@ where
 
     /// Archives a record.
     ///
-    /// This encodes the record, as well as serializes it into its archival format that will be
-    /// stored on disk.  The total size of the archived record, including the length delimiter
-    /// inserted before the archived record, will be returned.
+    /// This encodes the record, compresses and encrypts it if compression and encryption are
+    /// enabled, as well as serializes it
+    /// into its archival format that will be stored on disk.  The total size of the archived
+    /// record, including the length delimiter inserted before the archived record, will be
+    /// returned.
+    ///
+    /// The record size limit applies to the encoded record, before compression.
     ///
     /// # Errors
     ///
@@ -439,9 +495,32 @@ where
             });
         }
 
+        // Compress the encoded record, if need be.  The checksum covers the payload as stored, so
+        // that corruption can be detected before we even try to decompress it.
+        let mut payload = match self.compression {
+            DiskBufferCompression::None => &self.encode_buf[..],
+            compression => {
+                self.compress_buf.clear();
+                compression
+                    .compress(&self.encode_buf, &mut self.compress_buf)
+                    .context(FailedToCompressSnafu)?;
+                &self.compress_buf[..]
+            }
+        };
+
+        // Then encrypt it, if need be, which must happen after compression as ciphertext doesn't
+        // compress.  Likewise, the checksum covers the encrypted payload, so that corruption can be
+        // told apart from a payload that fails to authenticate.
+        if let Some(encryption_key) = &self.encryption_key {
+            self.encrypt_buf.clear();
+            encryption_key
+                .encrypt(id, payload, &mut self.encrypt_buf)
+                .map_err(|_| WriterError::FailedToEncrypt)?;
+            payload = &self.encrypt_buf[..];
+        }
+
         let metadata = T::get_metadata().into_u32();
-        let wrapped_record =
-            Record::with_checksum(id, metadata, &self.encode_buf, &self.checksummer);
+        let wrapped_record = Record::with_checksum(id, metadata, payload, &self.checksummer);
 
         // Push 8 dummy bytes where our length delimiter will sit.  We'll fix this up after
         // serialization.  Notably, `AlignedSerializer` will report the serializer position as
@@ -548,7 +627,7 @@ where
         let serialized_len = self.archive_record(id, record)?;
         let flush_result = self
             .writer
-            .write(event_count, self.ser_buf.as_slice())
+            .write(event_count, self.encode_buf.len(), self.ser_buf.as_slice())
             .await
             .context(IoSnafu)?;
 
@@ -558,6 +637,11 @@ where
         Ok((serialized_len, flush_result))
     }
 
+    /// Gets the encoded size of the last record archived, before compression.
+    pub fn last_encoded_len(&self) -> usize {
+        self.encode_buf.len()
+    }
+
     /// Flushes the writer.
     ///
     /// This flushes both the internal buffered writer and the underlying writer object.
@@ -599,6 +683,7 @@ where
     unflushed_events: u64,
     data_file_size: u64,
     unflushed_bytes: u64,
+    unflushed_logical_bytes: u64,
     data_file_full: bool,
     skip_to_next: bool,
     ready_to_write: bool,
@@ -622,6 +707,7 @@ where
             data_file_size: 0,
             data_file_full: false,
             unflushed_bytes: 0,
+            unflushed_logical_bytes: 0,
             skip_to_next: false,
             ready_to_write: false,
             next_record_id,
@@ -634,17 +720,27 @@ where
         self.next_record_id.wrapping_add(self.unflushed_events)
     }
 
-    fn track_write(&mut self, event_count: usize, record_size: u64) {
+    fn track_write(&mut self, event_count: usize, record_size: u64, logical_size: u64) {
         self.data_file_size += record_size;
         self.unflushed_events += event_count as u64;
         self.unflushed_bytes += record_size;
+        self.unflushed_logical_bytes += logical_size;
     }
 
     fn flush_write_state(&mut self) {
-        self.flush_write_state_partial(self.unflushed_events, self.unflushed_bytes);
+        self.flush_write_state_partial(
+            self.unflushed_events,
+            self.unflushed_bytes,
+            self.unflushed_logical_bytes,
+        );
     }
 
-    fn flush_write_state_partial(&mut self, flushed_events: u64, flushed_bytes: u64) {
+    fn flush_write_state_partial(
+        &mut self,
+        flushed_events: u64,
+        flushed_bytes: u64,
+        flushed_logical_bytes: u64,
+    ) {
         assert!(
             flushed_events <= self.unflushed_events,
             "tried to flush more events than are currently unflushed"
@@ -653,6 +749,10 @@ where
             flushed_bytes <= self.unflushed_bytes,
             "tried to flush more bytes than are currently unflushed"
         );
+        assert!(
+            flushed_logical_bytes <= self.unflushed_logical_bytes,
+            "tried to flush more logical bytes than are currently unflushed"
+        );
 
         self.next_record_id = self
             .ledger
@@ -660,8 +760,10 @@ where
             .increment_next_writer_record_id(flushed_events);
         self.unflushed_events -= flushed_events;
         self.unflushed_bytes -= flushed_bytes;
+        self.unflushed_logical_bytes -= flushed_logical_bytes;
 
-        self.ledger.track_write(flushed_events, flushed_bytes);
+        self.ledger
+            .track_write(flushed_events, flushed_bytes, flushed_logical_bytes);
     }
 
     fn can_write(&mut self) -> bool {
@@ -752,10 +854,16 @@ where
                 // next writer record ID should be.
                 let record = try_as_record_archive(data_file_mmap.as_ref())
                     .expect("record was already validated");
-                let item = decode_record_payload::<T>(record).map_err(|e| {
-                    WriterError::FailedToValidate {
+                let item = decode_record_payload::<T>(
+                    record,
+                    self.config.compression,
+                    self.config.encryption_key.as_ref(),
+                )
+                .map_err(|e| match e {
+                    ReaderError::Decryption => WriterError::FailedToDecrypt,
+                    e => WriterError::FailedToValidate {
                         reason: e.to_string(),
-                    }
+                    },
                 })?;
 
                 // Since we have a valid record, checksum and all, see if the writer record ID
@@ -996,6 +1104,8 @@ where
                     self.config.write_buffer_size,
                     self.config.max_data_file_size,
                     self.config.max_record_size,
+                    self.config.compression,
+                    self.config.encryption_key.clone(),
                 ));
                 self.data_file_size = data_file_size;
 
@@ -1057,7 +1167,7 @@ where
         // Grab the next record ID and attempt to write the record.
         let record_id = self.get_next_record_id();
 
-        let (bytes_written, flush_result) = loop {
+        let (bytes_written, logical_bytes_written, flush_result) = loop {
             // Make sure we have an open data file to write to, which might also be us opening the
             // next data file because our first attempt at writing had to finalize a data file that
             // was already full.
@@ -1068,7 +1178,9 @@ where
                 .as_mut()
                 .expect("writer should exist after `ensure_ready_for_write`");
             match writer.write_record(record_id, record).await {
-                Ok(result) => break result,
+                Ok((bytes_written, flush_result)) => {
+                    break (bytes_written, writer.last_encoded_len(), flush_result)
+                }
                 Err(WriterError::DataFileFull {
                     record: old_record,
                     serialized_size,
@@ -1095,13 +1207,21 @@ where
         // record ID generation.  We do this after the write appears to succeed to avoid issues with
         // setting the ledger state to a record ID that we may never have actually written, which
         // could lead to record ID gaps.
-        self.track_write(record_events.get(), bytes_written as u64);
+        self.track_write(
+            record_events.get(),
+            bytes_written as u64,
+            logical_bytes_written as u64,
+        );
 
         // If we did flush some buffered writes during this write, however, we now compensate for
         // that after updating our internal state.  We'll also notify the reader, too, since the
         // data should be available to read:
         if let Some(flush_result) = flush_result {
-            self.flush_write_state_partial(flush_result.events_flushed, flush_result.bytes_flushed);
+            self.flush_write_state_partial(
+                flush_result.events_flushed,
+                flush_result.bytes_flushed,
+                flush_result.logical_bytes_flushed,
+            );
             self.ledger.notify_writer_waiters();
         }
 
@@ -6,18 +6,29 @@ use crate::{
     buffer_usage_data::BufferUsageHandle,
     topology::{
         builder::IntoBuffer,
-        channel::{limited, ReceiverAdapter, SenderAdapter},
+        channel::{limited_by_size, ReceiverAdapter, SenderAdapter},
     },
     Acker, Bufferable,
 };
 
 pub struct MemoryBuffer {
-    capacity: NonZeroUsize,
+    max_events: Option<NonZeroUsize>,
+    max_size: Option<NonZeroUsize>,
 }
 
 impl MemoryBuffer {
+    /// Creates a memory buffer that holds up to `capacity` events.
     pub fn new(capacity: NonZeroUsize) -> Self {
-        MemoryBuffer { capacity }
+        Self::with_limits(Some(capacity), None)
+    }
+
+    /// Creates a memory buffer that holds up to `max_events` events, and up to `max_size` bytes of
+    /// events, as measured by `ByteSizeOf`.
+    pub fn with_limits(max_events: Option<NonZeroUsize>, max_size: Option<NonZeroUsize>) -> Self {
+        MemoryBuffer {
+            max_events,
+            max_size,
+        }
     }
 }
 
@@ -31,9 +42,12 @@ where
         usage_handle: BufferUsageHandle,
     ) -> Result<(SenderAdapter<T>, ReceiverAdapter<T>, Option<Acker>), Box<dyn Error + Send + Sync>>
     {
-        usage_handle.set_buffer_limits(None, Some(self.capacity.get()));
+        let max_events = self.max_events.map(NonZeroUsize::get);
+        let max_size = self.max_size.map(NonZeroUsize::get);
+
+        usage_handle.set_buffer_limits(max_size.map(|max_size| max_size as u64), max_events);
 
-        let (tx, rx) = limited(self.capacity.get());
+        let (tx, rx) = limited_by_size(max_events, max_size);
         Ok((tx.into(), rx.into(), None))
     }
 }
@@ -2,7 +2,11 @@ pub(crate) mod disk_v1;
 pub use disk_v1::DiskV1Buffer;
 
 pub(crate) mod disk_v2;
-pub use disk_v2::DiskV2Buffer;
+pub use disk_v2::{
+    DataFileSummary, DiskBufferCompression, DiskBufferEncryptionKey, DiskBufferInspector,
+    DiskBufferRetention, DiskV2Buffer, InspectError, LedgerSnapshot, ReplaySummary,
+    RetainedDataFile, RetainedDataFileFormat,
+};
 
 pub(crate) mod in_memory;
 pub use in_memory::MemoryBuffer;
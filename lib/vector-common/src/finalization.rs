@@ -3,7 +3,14 @@
 //! as it flows through transforms, being duplicated and merged, and
 //! then report its status when the last copy is delivered or dropped.
 
-use std::{cmp, future::Future, mem, pin::Pin, sync::Arc, task::Poll};
+use std::{
+    cmp, fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
 
 use crossbeam_utils::atomic::AtomicCell;
 use futures::future::FutureExt;
@@ -76,6 +83,15 @@ impl EventFinalizers {
         self.0.extend(other.0.into_iter());
     }
 
+    /// Splits the collection in two at the given index, returning the event finalizers from `at`
+    /// onward, as with [`Vec::split_off`].
+    ///
+    /// If `at` is past the end of the collection, the returned collection is empty.
+    #[must_use]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        Self(self.0.split_off(cmp::min(at, self.0.len())))
+    }
+
     /// Updates the status of all event finalizers in the collection.
     pub fn update_status(&self, status: EventStatus) {
         for finalizer in &self.0 {
@@ -83,6 +99,18 @@ impl EventFinalizers {
         }
     }
 
+    /// Marks all event finalizers in the collection as rejected, for the given reason.
+    ///
+    /// The rejection is recorded on the underlying batches, for the receivers of their status to
+    /// inspect. When a batch has events rejected for more than one reason, the first one recorded
+    /// is kept.
+    pub fn reject(&self, rejection: Rejection) {
+        let rejection = Arc::new(rejection);
+        for finalizer in &self.0 {
+            finalizer.reject(&rejection);
+        }
+    }
+
     /// Consumes all event finalizers and updates their underlying batches immediately.
     pub fn update_sources(&mut self) {
         let finalizers = mem::take(&mut self.0);
@@ -130,6 +158,12 @@ impl EventFinalizer {
             .unwrap_or_else(|_| unreachable!());
     }
 
+    /// Marks the event finalizer as rejected, recording the reason on the underlying batch.
+    pub fn reject(&self, rejection: &Arc<Rejection>) {
+        self.update_status(EventStatus::Rejected);
+        self.batch.set_rejection(rejection);
+    }
+
     /// Updates the underlying batch status with the status of the event finalizer.
     ///
     /// In doing so, the event finalizer is marked as "recorded", which prevents any further updates to it.
@@ -148,17 +182,72 @@ impl Drop for EventFinalizer {
     }
 }
 
+/// The reason events were rejected by a sink.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rejection {
+    message: String,
+    status_code: Option<u16>,
+}
+
+impl Rejection {
+    /// Creates a new `Rejection` with the given message.
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            status_code: None,
+        }
+    }
+
+    /// Sets the status code, such as an HTTP status code, that the downstream service responded with.
+    #[must_use]
+    pub fn with_status_code(mut self, status_code: u16) -> Self {
+        self.status_code = Some(status_code);
+        self
+    }
+
+    /// Gets the message describing why the events were rejected.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Gets the status code that the downstream service responded with, if any.
+    #[must_use]
+    pub fn status_code(&self) -> Option<u16> {
+        self.status_code
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status_code {
+            Some(status_code) => write!(f, "{} (status code {})", self.message, status_code),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 /// A convenience newtype wrapper for the one-shot receiver for an
 /// individual batch status.
+///
+/// Once the status has been received, the reason the batch was rejected, if it was, is available
+/// from [`BatchStatusReceiver::rejection`].
 #[pin_project::pin_project]
-pub struct BatchStatusReceiver(oneshot::Receiver<BatchStatus>);
+pub struct BatchStatusReceiver {
+    receiver: oneshot::Receiver<(BatchStatus, Option<Arc<Rejection>>)>,
+    rejection: Option<Arc<Rejection>>,
+}
 
 impl Future for BatchStatusReceiver {
     type Output = BatchStatus;
     fn poll(mut self: Pin<&mut Self>, ctx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
-        match self.0.poll_unpin(ctx) {
+        match self.receiver.poll_unpin(ctx) {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(Ok(status)) => Poll::Ready(status),
+            Poll::Ready(Ok((status, rejection))) => {
+                self.rejection = rejection;
+                Poll::Ready(status)
+            }
             Poll::Ready(Err(error)) => {
                 error!(%error, "Batch status receiver dropped before sending.");
                 Poll::Ready(BatchStatus::Errored)
@@ -175,7 +264,18 @@ impl BatchStatusReceiver {
     /// - `TryRecvError::Empty` if no value has been sent yet.
     /// - `TryRecvError::Closed` if the sender has dropped without sending a value.
     pub fn try_recv(&mut self) -> Result<BatchStatus, oneshot::error::TryRecvError> {
-        self.0.try_recv()
+        let (status, rejection) = self.receiver.try_recv()?;
+        self.rejection = rejection;
+        Ok(status)
+    }
+
+    /// Gets the reason the batch was rejected.
+    ///
+    /// This is only available once the status of the batch has been received, and the batch was
+    /// rejected with a reason.
+    #[must_use]
+    pub fn rejection(&self) -> Option<&Rejection> {
+        self.rejection.as_deref()
     }
 }
 
@@ -192,9 +292,14 @@ impl BatchNotifier {
         let (sender, receiver) = oneshot::channel();
         let notifier = OwnedBatchNotifier {
             status: AtomicCell::new(BatchStatus::Delivered),
+            rejection: Mutex::new(None),
             notifier: Some(sender),
         };
-        (Self(Arc::new(notifier)), BatchStatusReceiver(receiver))
+        let receiver = BatchStatusReceiver {
+            receiver,
+            rejection: None,
+        };
+        (Self(Arc::new(notifier)), receiver)
     }
 
     /// Optionally creates a new `BatchNotifier` along with the receiver used to await its finalization status.
@@ -235,13 +340,22 @@ impl BatchNotifier {
                 .unwrap_or_else(|_| unreachable!());
         }
     }
+
+    /// Records the reason events in the batch were rejected, unless one was already recorded.
+    fn set_rejection(&self, rejection: &Arc<Rejection>) {
+        let mut current = self.0.rejection.lock().expect("poisoned lock");
+        if current.is_none() {
+            *current = Some(Arc::clone(rejection));
+        }
+    }
 }
 
 /// The non-shared data underlying the shared `BatchNotifier`
 #[derive(Debug)]
 pub struct OwnedBatchNotifier {
     status: AtomicCell<BatchStatus>,
-    notifier: Option<oneshot::Sender<BatchStatus>>,
+    rejection: Mutex<Option<Arc<Rejection>>>,
+    notifier: Option<oneshot::Sender<(BatchStatus, Option<Arc<Rejection>>)>>,
 }
 
 impl OwnedBatchNotifier {
@@ -249,9 +363,13 @@ impl OwnedBatchNotifier {
     fn send_status(&mut self) {
         if let Some(notifier) = self.notifier.take() {
             let status = self.status.load();
+            let rejection = match self.rejection.get_mut() {
+                Ok(rejection) => rejection.take(),
+                Err(poisoned) => poisoned.into_inner().take(),
+            };
             // Ignore the error case, as it will happen during normal
             // source shutdown and we can't detect that here.
-            let _ = notifier.send(status);
+            let _ = notifier.send((status, rejection));
         }
     }
 }
@@ -492,6 +610,31 @@ mod tests {
         assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
     }
 
+    #[test]
+    fn rejection_reaches_receiver() {
+        let (mut fin, mut receiver) = make_finalizer();
+        let fin2 = fin.clone();
+        fin.reject(Rejection::new("first").with_status_code(400));
+        fin2.reject(Rejection::new("second"));
+        fin.update_sources();
+        assert_eq!(receiver.try_recv(), Err(Empty));
+        drop(fin2);
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Rejected));
+
+        let rejection = receiver.rejection().expect("rejection should be recorded");
+        assert_eq!(rejection.message(), "first");
+        assert_eq!(rejection.status_code(), Some(400));
+    }
+
+    #[test]
+    fn no_rejection_when_delivered() {
+        let (fin, mut receiver) = make_finalizer();
+        fin.update_status(EventStatus::Delivered);
+        drop(fin);
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+        assert_eq!(receiver.rejection(), None);
+    }
+
     fn make_finalizer() -> (EventFinalizers, BatchStatusReceiver) {
         let (batch, receiver) = BatchNotifier::new_with_receiver();
         let finalizer = EventFinalizers::new(EventFinalizer::new(batch));
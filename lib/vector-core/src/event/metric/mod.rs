@@ -28,6 +28,11 @@ pub use self::series::*;
 mod value;
 pub use self::value::*;
 
+#[cfg(feature = "vrl")]
+mod vrl;
+#[cfg(feature = "vrl")]
+pub use self::vrl::METRIC_VALUE_FIELDS;
+
 pub type MetricTags = BTreeMap<String, String>;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
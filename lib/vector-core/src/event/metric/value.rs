@@ -472,7 +472,8 @@ impl From<AgentDDSketch> for MetricValue {
     }
 }
 
-// Currently, VRL can only read the type of the value and doesn't consider any actual metric values.
+// This converts to the type of the value, as exposed by the `type` field of metrics in VRL. The value
+// itself is converted by `MetricValue::to_vrl`.
 #[cfg(feature = "vrl")]
 impl From<MetricValue> for ::value::Value {
     fn from(value: MetricValue) -> Self {
@@ -525,7 +526,8 @@ impl ByteSizeOf for MetricSketch {
     }
 }
 
-// Currently, VRL can only read the type of the value and doesn't consider ny actual metric values.
+// This converts to the type of the sketch, as exposed by the `type` field of metrics in VRL. Sketches
+// have no VRL representation of their value.
 #[cfg(feature = "vrl")]
 impl From<MetricSketch> for ::value::Value {
     fn from(value: MetricSketch) -> Self {
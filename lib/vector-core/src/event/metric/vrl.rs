@@ -0,0 +1,559 @@
+//! Conversions between metrics and their VRL representation.
+//!
+//! A metric is represented as an object with the `name`, `namespace`, `tags`, `timestamp`, `kind`
+//! and `type` fields, with its value in a field named after the type of the metric, matching the
+//! JSON encoding of metrics:
+//!
+//! ```text
+//! { "name": "requests", "kind": "incremental", "type": "counter", "counter": { "value": 1.0 } }
+//! ```
+//!
+//! Sketches have no VRL representation, so their value can't be read or written.
+
+use std::{borrow::Cow, collections::BTreeMap, convert::TryFrom};
+
+use ::value::Value;
+use vrl_lib::prelude::VrlValueConvert;
+
+use super::{Bucket, Metric, MetricKind, MetricTags, MetricValue, Quantile, Sample, StatisticKind};
+use crate::event::EventMetadata;
+
+/// The fields holding the value of a metric, one for each type of metric.
+pub const METRIC_VALUE_FIELDS: [&str; 6] = [
+    "counter",
+    "gauge",
+    "set",
+    "distribution",
+    "aggregated_histogram",
+    "aggregated_summary",
+];
+
+impl MetricValue {
+    /// Gets the field holding this value in the VRL representation of a metric.
+    ///
+    /// Returns `None` for sketches, which have no VRL representation.
+    pub fn vrl_field(&self) -> Option<&'static str> {
+        match self {
+            Self::Counter { .. } => Some("counter"),
+            Self::Gauge { .. } => Some("gauge"),
+            Self::Set { .. } => Some("set"),
+            Self::Distribution { .. } => Some("distribution"),
+            Self::AggregatedHistogram { .. } => Some("aggregated_histogram"),
+            Self::AggregatedSummary { .. } => Some("aggregated_summary"),
+            Self::Sketch { .. } => None,
+        }
+    }
+
+    /// Converts this value to its VRL representation.
+    ///
+    /// Returns `None` for sketches, which have no VRL representation.
+    pub fn to_vrl(&self) -> Option<Value> {
+        let value = match self {
+            Self::Counter { value } | Self::Gauge { value } => object([("value", float(*value))]),
+            Self::Set { values } => object([(
+                "values",
+                values
+                    .iter()
+                    .map(|value| Value::from(value.as_str()))
+                    .collect(),
+            )]),
+            Self::Distribution { samples, statistic } => object([
+                (
+                    "samples",
+                    samples
+                        .iter()
+                        .map(|sample| {
+                            object([("value", float(sample.value)), ("rate", sample.rate.into())])
+                        })
+                        .collect(),
+                ),
+                (
+                    "statistic",
+                    match statistic {
+                        StatisticKind::Histogram => "histogram",
+                        StatisticKind::Summary => "summary",
+                    }
+                    .into(),
+                ),
+            ]),
+            Self::AggregatedHistogram {
+                buckets,
+                count,
+                sum,
+            } => object([
+                (
+                    "buckets",
+                    buckets
+                        .iter()
+                        .map(|bucket| {
+                            object([
+                                ("upper_limit", float(bucket.upper_limit)),
+                                ("count", bucket.count.into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+                ("count", (*count).into()),
+                ("sum", float(*sum)),
+            ]),
+            Self::AggregatedSummary {
+                quantiles,
+                count,
+                sum,
+            } => object([
+                (
+                    "quantiles",
+                    quantiles
+                        .iter()
+                        .map(|quantile| {
+                            object([
+                                ("quantile", float(quantile.quantile)),
+                                ("value", float(quantile.value)),
+                            ])
+                        })
+                        .collect(),
+                ),
+                ("count", (*count).into()),
+                ("sum", float(*sum)),
+            ]),
+            Self::Sketch { .. } => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Parses the VRL representation of a value, held in the given field.
+    ///
+    /// # Errors
+    ///
+    /// If the field isn't one of [`METRIC_VALUE_FIELDS`], or the value doesn't match the type of
+    /// metric, an error is returned.
+    pub fn from_vrl(field: &str, value: Value) -> Result<Self, String> {
+        let mut fields = Fields::new(field.to_owned(), value)?;
+
+        let value = match field {
+            "counter" => Self::Counter {
+                value: fields.float("value")?,
+            },
+            "gauge" => Self::Gauge {
+                value: fields.float("value")?,
+            },
+            "set" => Self::Set {
+                values: fields
+                    .array("values")?
+                    .into_iter()
+                    .map(|(path, value)| string(&path, &value))
+                    .collect::<Result<_, _>>()?,
+            },
+            "distribution" => Self::Distribution {
+                samples: fields.objects("samples", |sample| {
+                    Ok(Sample {
+                        value: sample.float("value")?,
+                        rate: sample.count("rate")?,
+                    })
+                })?,
+                statistic: match fields.string("statistic")?.as_str() {
+                    "histogram" => StatisticKind::Histogram,
+                    "summary" => StatisticKind::Summary,
+                    statistic => {
+                        return Err(format!(
+                            "invalid field {}.statistic: expected histogram or summary, got {}",
+                            field, statistic
+                        ))
+                    }
+                },
+            },
+            "aggregated_histogram" => Self::AggregatedHistogram {
+                buckets: fields.objects("buckets", |bucket| {
+                    Ok(Bucket {
+                        upper_limit: bucket.float("upper_limit")?,
+                        count: bucket.count("count")?,
+                    })
+                })?,
+                count: fields.count("count")?,
+                sum: fields.float("sum")?,
+            },
+            "aggregated_summary" => Self::AggregatedSummary {
+                quantiles: fields.objects("quantiles", |quantile| {
+                    Ok(Quantile {
+                        quantile: quantile.float("quantile")?,
+                        value: quantile.float("value")?,
+                    })
+                })?,
+                count: fields.count("count")?,
+                sum: fields.float("sum")?,
+            },
+            _ => {
+                return Err(format!(
+                    "invalid metric type {}, expected one of {}",
+                    field,
+                    METRIC_VALUE_FIELDS.join(", ")
+                ))
+            }
+        };
+
+        fields.finish()?;
+
+        Ok(value)
+    }
+}
+
+impl Metric {
+    /// Converts this metric to its VRL representation.
+    ///
+    /// The value of sketches is left out, as it has no VRL representation.
+    pub fn to_vrl(&self) -> Value {
+        let mut map = BTreeMap::new();
+
+        map.insert("name".to_owned(), self.name().to_owned().into());
+        map.insert("kind".to_owned(), self.kind().into());
+        map.insert("type".to_owned(), self.value().clone().into());
+
+        if let Some(namespace) = self.namespace() {
+            map.insert("namespace".to_owned(), namespace.to_owned().into());
+        }
+
+        if let Some(timestamp) = self.timestamp() {
+            map.insert("timestamp".to_owned(), timestamp.into());
+        }
+
+        if let Some(tags) = self.tags() {
+            map.insert(
+                "tags".to_owned(),
+                tags.iter()
+                    .map(|(tag, value)| (tag.clone(), value.clone().into()))
+                    .collect::<BTreeMap<_, _>>()
+                    .into(),
+            );
+        }
+
+        if let (Some(field), Some(value)) = (self.value().vrl_field(), self.value().to_vrl()) {
+            map.insert(field.to_owned(), value);
+        }
+
+        map.into()
+    }
+
+    /// Parses the VRL representation of a metric.
+    ///
+    /// # Errors
+    ///
+    /// If a required field is missing, a field is invalid, or the metric doesn't hold exactly one
+    /// value, an error is returned.
+    pub fn from_vrl(value: Value, metadata: EventMetadata) -> Result<Self, String> {
+        let mut fields = Fields::new(String::new(), value)?;
+
+        let name = fields.string("name")?;
+        let kind = MetricKind::try_from(fields.take("kind")?)?;
+        let namespace = fields
+            .optional("namespace")
+            .map(|value| string("namespace", &value))
+            .transpose()?;
+        let timestamp = fields
+            .optional("timestamp")
+            .map(|value| value.try_timestamp().map_err(|e| invalid("timestamp", &e)))
+            .transpose()?;
+        let tags = fields
+            .optional("tags")
+            .map(|value| {
+                value
+                    .try_object()
+                    .map_err(|e| invalid("tags", &e))?
+                    .into_iter()
+                    .map(|(tag, value)| {
+                        let value = string(&format!("tags.{}", tag), &value)?;
+                        Ok((tag, value))
+                    })
+                    .collect::<Result<MetricTags, String>>()
+            })
+            .transpose()?;
+        // The type is derived from the field holding the value.
+        let _type = fields.optional("type");
+
+        let mut values = METRIC_VALUE_FIELDS
+            .iter()
+            .filter_map(|field| fields.optional(field).map(|value| (*field, value)))
+            .collect::<Vec<_>>();
+        if values.len() != 1 {
+            return Err(format!(
+                "a metric must have exactly one of the fields {}",
+                METRIC_VALUE_FIELDS.join(", ")
+            ));
+        }
+        let (field, value) = values.remove(0);
+        let value = MetricValue::from_vrl(field, value)?;
+
+        fields.finish()?;
+
+        Ok(Self::new_with_metadata(name, kind, value, metadata)
+            .with_namespace(namespace)
+            .with_timestamp(timestamp)
+            .with_tags(tags))
+    }
+}
+
+/// The fields of an object being parsed, along with its path for error messages.
+struct Fields {
+    path: String,
+    fields: BTreeMap<String, Value>,
+}
+
+impl Fields {
+    fn new(path: String, value: Value) -> Result<Self, String> {
+        let fields = value.try_object().map_err(|e| {
+            if path.is_empty() {
+                format!("invalid metric: {}", e)
+            } else {
+                invalid(&path, &e)
+            }
+        })?;
+
+        Ok(Self { path, fields })
+    }
+
+    fn path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+
+    /// Take a field, treating `null` as a missing field.
+    fn optional(&mut self, key: &str) -> Option<Value> {
+        self.fields.remove(key).filter(|value| !value.is_null())
+    }
+
+    fn take(&mut self, key: &str) -> Result<Value, String> {
+        self.optional(key)
+            .ok_or_else(|| format!("missing field {}", self.path(key)))
+    }
+
+    fn string(&mut self, key: &str) -> Result<String, String> {
+        let value = self.take(key)?;
+        string(&self.path(key), &value)
+    }
+
+    fn float(&mut self, key: &str) -> Result<f64, String> {
+        self.take(key)?
+            .try_into_f64()
+            .map_err(|e| invalid(&self.path(key), &e))
+    }
+
+    fn count(&mut self, key: &str) -> Result<u32, String> {
+        let count = self
+            .take(key)?
+            .try_integer()
+            .map_err(|e| invalid(&self.path(key), &e))?;
+
+        u32::try_from(count).map_err(|_| {
+            format!(
+                "invalid field {}: expected a count between 0 and {}, got {}",
+                self.path(key),
+                u32::MAX,
+                count
+            )
+        })
+    }
+
+    /// Take an array field, along with the path of each of its elements.
+    fn array(&mut self, key: &str) -> Result<Vec<(String, Value)>, String> {
+        let path = self.path(key);
+        let array = self
+            .take(key)?
+            .try_array()
+            .map_err(|e| invalid(&path, &e))?;
+
+        Ok(array
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| (format!("{}[{}]", path, index), value))
+            .collect())
+    }
+
+    /// Take an array field of objects, parsing each object with the given function.
+    fn objects<T>(
+        &mut self,
+        key: &str,
+        parse: impl Fn(&mut Fields) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        self.array(key)?
+            .into_iter()
+            .map(|(path, value)| {
+                let mut fields = Fields::new(path, value)?;
+                let parsed = parse(&mut fields)?;
+                fields.finish()?;
+                Ok(parsed)
+            })
+            .collect()
+    }
+
+    /// Ensure all fields have been taken.
+    fn finish(self) -> Result<(), String> {
+        match self.fields.keys().next() {
+            Some(key) => Err(format!("unknown field {}", self.path(key))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn string(path: &str, value: &Value) -> Result<String, String> {
+    value
+        .try_bytes_utf8_lossy()
+        .map(Cow::into_owned)
+        .map_err(|e| invalid(path, &e))
+}
+
+fn invalid(path: &str, error: &impl std::fmt::Display) -> String {
+    format!("invalid field {}: {}", path, error)
+}
+
+fn float(value: f64) -> Value {
+    Value::from_f64_or_zero(value)
+}
+
+fn object<const N: usize>(fields: [(&str, Value); N]) -> Value {
+    fields
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect::<BTreeMap<_, _>>()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{offset::TimeZone, Utc};
+    use vector_common::btreemap;
+
+    use super::*;
+    use crate::samples;
+
+    #[test]
+    fn metric_value_round_trip() {
+        let values = vec![
+            MetricValue::Counter { value: 1.5 },
+            MetricValue::Gauge { value: -2.0 },
+            MetricValue::Set {
+                values: vec!["a".to_owned(), "b".to_owned()].into_iter().collect(),
+            },
+            MetricValue::Distribution {
+                samples: samples![1.0 => 2, 3.0 => 1],
+                statistic: StatisticKind::Summary,
+            },
+            MetricValue::AggregatedHistogram {
+                buckets: vec![
+                    Bucket {
+                        upper_limit: 1.0,
+                        count: 3,
+                    },
+                    Bucket {
+                        upper_limit: 5.0,
+                        count: 4,
+                    },
+                ],
+                count: 7,
+                sum: 12.5,
+            },
+            MetricValue::AggregatedSummary {
+                quantiles: vec![Quantile {
+                    quantile: 0.5,
+                    value: 2.0,
+                }],
+                count: 3,
+                sum: 6.0,
+            },
+        ];
+
+        for value in values {
+            let field = value.vrl_field().unwrap();
+            let vrl = value.to_vrl().unwrap();
+
+            assert_eq!(MetricValue::from_vrl(field, vrl), Ok(value));
+        }
+    }
+
+    #[test]
+    fn metric_value_from_vrl() {
+        assert_eq!(
+            MetricValue::from_vrl("counter", btreemap! { "value" => 2 }.into()),
+            Ok(MetricValue::Counter { value: 2.0 })
+        );
+        assert_eq!(
+            MetricValue::from_vrl("gauge", btreemap! { "value" => "2" }.into()),
+            Err("invalid field gauge.value: can't coerce string into float".to_owned())
+        );
+        assert_eq!(
+            MetricValue::from_vrl("counter", Value::Object(BTreeMap::new())),
+            Err("missing field counter.value".to_owned())
+        );
+        assert_eq!(
+            MetricValue::from_vrl("counter", btreemap! { "value" => 1, "rate" => 1 }.into()),
+            Err("unknown field counter.rate".to_owned())
+        );
+        assert_eq!(
+            MetricValue::from_vrl(
+                "distribution",
+                btreemap! {
+                    "samples" => vec![btreemap! { "value" => 1, "rate" => -1 }],
+                    "statistic" => "histogram",
+                }
+                .into()
+            ),
+            Err(
+                "invalid field distribution.samples[0].rate: expected a count between 0 and 4294967295, got -1"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn metric_round_trip() {
+        let metric = Metric::new(
+            "requests",
+            MetricKind::Incremental,
+            MetricValue::Counter { value: 1.0 },
+        )
+        .with_namespace(Some("app"))
+        .with_tags(Some(
+            vec![("host".to_owned(), "localhost".to_owned())]
+                .into_iter()
+                .collect(),
+        ))
+        .with_timestamp(Some(Utc.ymd(2022, 8, 1).and_hms(12, 0, 0)));
+
+        let vrl = metric.to_vrl();
+
+        assert_eq!(
+            vrl,
+            btreemap! {
+                "name" => "requests",
+                "namespace" => "app",
+                "tags" => btreemap! { "host" => "localhost" },
+                "timestamp" => Utc.ymd(2022, 8, 1).and_hms(12, 0, 0),
+                "kind" => "incremental",
+                "type" => "counter",
+                "counter" => btreemap! { "value" => 1.0 },
+            }
+            .into()
+        );
+        assert_eq!(Metric::from_vrl(vrl, EventMetadata::default()), Ok(metric));
+    }
+
+    #[test]
+    fn metric_from_vrl_requires_one_value() {
+        let metric = btreemap! {
+            "name" => "requests",
+            "kind" => "absolute",
+            "counter" => btreemap! { "value" => 1.0 },
+            "gauge" => btreemap! { "value" => 1.0 },
+        };
+
+        assert_eq!(
+            Metric::from_vrl(metric.into(), EventMetadata::default()),
+            Err(format!(
+                "a metric must have exactly one of the fields {}",
+                METRIC_VALUE_FIELDS.join(", ")
+            ))
+        );
+    }
+}
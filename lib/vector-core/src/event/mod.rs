@@ -8,7 +8,7 @@ pub use ::value::Value;
 pub use array::{into_event_stream, EventArray, EventContainer, LogArray, MetricArray, TraceArray};
 pub use finalization::{
     BatchNotifier, BatchStatus, BatchStatusReceiver, EventFinalizer, EventFinalizers, EventStatus,
-    Finalizable,
+    Finalizable, Rejection,
 };
 pub use log_event::LogEvent;
 pub use metadata::{EventMetadata, WithMetadata};
@@ -40,6 +40,8 @@ mod test;
 mod trace;
 pub mod util;
 #[cfg(feature = "vrl")]
+pub mod vrl_functions;
+#[cfg(feature = "vrl")]
 mod vrl_target;
 
 pub const PARTIAL: &str = "_partial";
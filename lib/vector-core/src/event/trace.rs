@@ -99,6 +99,36 @@ impl TraceEvent {
     ) -> Option<Value> {
         self.0.insert(key.as_ref(), value.into())
     }
+
+    /// Returns an iterator over the spans of the trace.
+    ///
+    /// Spans are held in the `spans` array of the trace. Elements of that array that aren't objects
+    /// are skipped.
+    pub fn spans(&self) -> impl Iterator<Item = &BTreeMap<String, Value>> {
+        self.get("spans")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object)
+    }
+
+    /// Returns an iterator over mutable references to the spans of the trace.
+    pub fn spans_mut(&mut self) -> impl Iterator<Item = &mut BTreeMap<String, Value>> {
+        self.get_mut("spans")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object_mut)
+    }
+
+    /// Retains only the spans for which the predicate returns `true`.
+    ///
+    /// Elements of the `spans` array that aren't objects are removed.
+    pub fn retain_spans(&mut self, mut f: impl FnMut(&BTreeMap<String, Value>) -> bool) {
+        if let Some(spans) = self.get_mut("spans").and_then(Value::as_array_mut) {
+            spans.retain(|span| span.as_object().map_or(false, &mut f));
+        }
+    }
 }
 
 impl From<LogEvent> for TraceEvent {
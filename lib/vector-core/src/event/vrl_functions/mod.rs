@@ -0,0 +1,15 @@
+//! VRL functions for working with metric events.
+//!
+//! These functions operate on the VRL representation of metrics, as exposed by [`VrlTarget`].
+//!
+//! [`VrlTarget`]: super::VrlTarget
+
+pub mod new_metric;
+pub mod to_absolute_metric;
+
+pub fn all() -> Vec<Box<dyn vrl_lib::Function>> {
+    vec![
+        Box::new(new_metric::NewMetric) as _,
+        Box::new(to_absolute_metric::ToAbsoluteMetric) as _,
+    ]
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+
+use ::value::Value;
+use vrl_lib::prelude::*;
+
+use crate::event::{metric::METRIC_VALUE_FIELDS, EventMetadata, Metric};
+
+fn kinds() -> Vec<Value> {
+    vec![value!("absolute"), value!("incremental")]
+}
+
+fn types() -> Vec<Value> {
+    METRIC_VALUE_FIELDS
+        .iter()
+        .map(|field| (*field).into())
+        .collect()
+}
+
+fn new_metric(
+    name: Value,
+    kind: Value,
+    metric_type: &str,
+    value: Value,
+    namespace: Option<Value>,
+    tags: Option<Value>,
+) -> Resolved {
+    // Counters, gauges and sets can be given their value directly, rather than as an object.
+    let value = match (metric_type, value) {
+        ("counter" | "gauge", value @ (Value::Integer(_) | Value::Float(_))) => {
+            Value::from(BTreeMap::from([("value".to_owned(), value)]))
+        }
+        ("set", value @ Value::Array(_)) => {
+            Value::from(BTreeMap::from([("values".to_owned(), value)]))
+        }
+        (_, value) => value,
+    };
+
+    let mut fields = BTreeMap::from([
+        ("name".to_owned(), name),
+        ("kind".to_owned(), kind),
+        (metric_type.to_owned(), value),
+    ]);
+    if let Some(namespace) = namespace {
+        fields.insert("namespace".to_owned(), namespace);
+    }
+    if let Some(tags) = tags {
+        fields.insert("tags".to_owned(), tags);
+    }
+
+    let metric = Metric::from_vrl(fields.into(), EventMetadata::default())?;
+
+    Ok(metric.to_vrl())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NewMetric;
+
+impl Function for NewMetric {
+    fn identifier(&self) -> &'static str {
+        "new_metric"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "name",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "kind",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "type",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "value",
+                kind: kind::ANY,
+                required: true,
+            },
+            Parameter {
+                keyword: "namespace",
+                kind: kind::BYTES | kind::NULL,
+                required: false,
+            },
+            Parameter {
+                keyword: "tags",
+                kind: kind::OBJECT | kind::NULL,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "counter",
+                source: r#"new_metric!("requests", "incremental", "counter", 1, tags: {"host": "localhost"})"#,
+                result: Ok(
+                    r#"{"name": "requests", "kind": "incremental", "type": "counter", "tags": {"host": "localhost"}, "counter": {"value": 1.0}}"#,
+                ),
+            },
+            Example {
+                title: "aggregated histogram",
+                source: r#"new_metric!("latency", "absolute", "aggregated_histogram", {"buckets": [{"upper_limit": 0.5, "count": 4}, {"upper_limit": 1.0, "count": 1}], "count": 5, "sum": 1.7})"#,
+                result: Ok(
+                    r#"{"name": "latency", "kind": "absolute", "type": "aggregated histogram", "aggregated_histogram": {"buckets": [{"upper_limit": 0.5, "count": 4}, {"upper_limit": 1.0, "count": 1}], "count": 5, "sum": 1.7}}"#,
+                ),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let name = arguments.required("name");
+        let kind = arguments.required_enum("kind", &kinds())?;
+        let metric_type = arguments
+            .required_enum("type", &types())?
+            .try_bytes_utf8_lossy()
+            .expect("type not bytes")
+            .into_owned();
+        let value = arguments.required("value");
+        let namespace = arguments.optional("namespace");
+        let tags = arguments.optional("tags");
+
+        Ok(Box::new(NewMetricFn {
+            name,
+            kind,
+            metric_type,
+            value,
+            namespace,
+            tags,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NewMetricFn {
+    name: Box<dyn Expression>,
+    kind: Value,
+    metric_type: String,
+    value: Box<dyn Expression>,
+    namespace: Option<Box<dyn Expression>>,
+    tags: Option<Box<dyn Expression>>,
+}
+
+impl Expression for NewMetricFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let name = self.name.resolve(ctx)?;
+        let value = self.value.resolve(ctx)?;
+        let namespace = self
+            .namespace
+            .as_ref()
+            .map(|expr| expr.resolve(ctx))
+            .transpose()?;
+        let tags = self
+            .tags
+            .as_ref()
+            .map(|expr| expr.resolve(ctx))
+            .transpose()?;
+
+        new_metric(
+            name,
+            self.kind.clone(),
+            &self.metric_type,
+            value,
+            namespace,
+            tags,
+        )
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::object(Collection::any()).fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vector_common::btreemap;
+
+    use super::*;
+
+    #[test]
+    fn counter() {
+        assert_eq!(
+            new_metric(
+                "requests".into(),
+                "incremental".into(),
+                "counter",
+                2.into(),
+                Some("app".into()),
+                Some(btreemap! { "host" => "localhost" }.into()),
+            ),
+            Ok(btreemap! {
+                "name" => "requests",
+                "namespace" => "app",
+                "kind" => "incremental",
+                "type" => "counter",
+                "tags" => btreemap! { "host" => "localhost" },
+                "counter" => btreemap! { "value" => 2.0 },
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            new_metric(
+                "users".into(),
+                "absolute".into(),
+                "set",
+                vec!["bob", "alice", "bob"].into(),
+                None,
+                Some(Value::Null),
+            ),
+            Ok(btreemap! {
+                "name" => "users",
+                "kind" => "absolute",
+                "type" => "set",
+                "set" => btreemap! { "values" => vec!["alice", "bob"] },
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        assert_eq!(
+            new_metric(
+                "latency".into(),
+                "absolute".into(),
+                "distribution",
+                1.into(),
+                None,
+                None,
+            ),
+            Err("invalid field distribution: expected object, got integer".into())
+        );
+    }
+}
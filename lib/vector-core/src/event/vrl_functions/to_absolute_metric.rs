@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use ::value::Value;
+use vrl_lib::prelude::*;
+
+use crate::event::{
+    metric::{MetricSeries, MetricValue},
+    EventMetadata, Metric, MetricKind,
+};
+
+/// How long the total of a series is kept after it was last updated, unless `ttl_secs` is given.
+const DEFAULT_TTL_SECS: i64 = 3600;
+
+/// The running totals of incremental metrics, by series, along with when each was last updated.
+///
+/// The totals of series that haven't been updated for longer than the TTL are dropped, so that
+/// series that come and go don't keep growing the totals.
+#[derive(Debug)]
+struct Totals {
+    series: HashMap<MetricSeries, (MetricValue, Instant)>,
+    ttl: Duration,
+    last_expired: Instant,
+}
+
+impl Totals {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            series: HashMap::new(),
+            ttl,
+            last_expired: Instant::now(),
+        }
+    }
+
+    /// Drops the totals of the series that have expired, at most once per TTL, so that a program
+    /// handling lots of series doesn't check every one of them for each metric.
+    fn expire(&mut self, now: Instant) {
+        if now.duration_since(self.last_expired) < self.ttl {
+            return;
+        }
+
+        let ttl = self.ttl;
+        self.series
+            .retain(|_, (_, updated_at)| now.duration_since(*updated_at) <= ttl);
+        self.last_expired = now;
+    }
+}
+
+type SharedTotals = Arc<Mutex<Totals>>;
+
+fn to_absolute_metric(totals: &SharedTotals, value: Value) -> Resolved {
+    let mut metric = Metric::from_vrl(value, EventMetadata::default())?;
+
+    if metric.kind() == MetricKind::Incremental {
+        let mut totals = totals.lock().expect("totals lock poisoned");
+        let now = Instant::now();
+        totals.expire(now);
+
+        let ttl = totals.ttl;
+        match totals.series.get_mut(metric.series()) {
+            Some((total, updated_at))
+                if now.duration_since(*updated_at) <= ttl && total.add(metric.value()) =>
+            {
+                *updated_at = now;
+                metric = metric.with_value(total.clone());
+            }
+            // The first value of a series, a series that expired, or a series that changed its
+            // type, becomes the new reference value.
+            _ => {
+                totals
+                    .series
+                    .insert(metric.series().clone(), (metric.value().clone(), now));
+            }
+        }
+
+        metric = metric.into_absolute();
+    }
+
+    Ok(metric.to_vrl())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ToAbsoluteMetric;
+
+impl Function for ToAbsoluteMetric {
+    fn identifier(&self) -> &'static str {
+        "to_absolute_metric"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT,
+                required: true,
+            },
+            Parameter {
+                keyword: "ttl_secs",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "incremental counter",
+            source: r#"to_absolute_metric!({"name": "requests", "kind": "incremental", "counter": {"value": 2}})"#,
+            result: Ok(
+                r#"{"name": "requests", "kind": "absolute", "type": "counter", "counter": {"value": 2.0}}"#,
+            ),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let ttl_secs = match arguments.optional_literal("ttl_secs")? {
+            Some(literal) => {
+                let value = literal.to_value();
+                match value.as_integer() {
+                    Some(ttl_secs) if ttl_secs > 0 => ttl_secs,
+                    _ => {
+                        return Err(vrl_lib::function::Error::InvalidArgument {
+                            keyword: "ttl_secs",
+                            value,
+                            error: "must be a positive number of seconds",
+                        }
+                        .into())
+                    }
+                }
+            }
+            None => DEFAULT_TTL_SECS,
+        };
+        let ttl = Duration::from_secs(ttl_secs.unsigned_abs());
+
+        Ok(Box::new(ToAbsoluteMetricFn {
+            value,
+            totals: Arc::new(Mutex::new(Totals::new(ttl))),
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ToAbsoluteMetricFn {
+    value: Box<dyn Expression>,
+    totals: SharedTotals,
+}
+
+impl Expression for ToAbsoluteMetricFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        to_absolute_metric(&self.totals, value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::object(Collection::any()).fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vector_common::btreemap;
+
+    use super::*;
+
+    fn totals() -> SharedTotals {
+        Arc::new(Mutex::new(Totals::new(Duration::from_secs(3600))))
+    }
+
+    fn counter(kind: &str, value: f64) -> Value {
+        btreemap! {
+            "name" => "requests",
+            "kind" => kind,
+            "counter" => btreemap! { "value" => value },
+        }
+        .into()
+    }
+
+    fn absolute(value: f64) -> Value {
+        btreemap! {
+            "name" => "requests",
+            "kind" => "absolute",
+            "type" => "counter",
+            "counter" => btreemap! { "value" => value },
+        }
+        .into()
+    }
+
+    #[test]
+    fn accumulates_incremental_metrics() {
+        let totals = totals();
+
+        assert_eq!(
+            to_absolute_metric(&totals, counter("incremental", 1.0)),
+            Ok(absolute(1.0))
+        );
+        assert_eq!(
+            to_absolute_metric(&totals, counter("incremental", 2.5)),
+            Ok(absolute(3.5))
+        );
+        // Absolute metrics pass through without affecting the totals.
+        assert_eq!(
+            to_absolute_metric(&totals, counter("absolute", 10.0)),
+            Ok(absolute(10.0))
+        );
+        assert_eq!(
+            to_absolute_metric(&totals, counter("incremental", 1.0)),
+            Ok(absolute(4.5))
+        );
+    }
+
+    #[test]
+    fn series_are_separate() {
+        let totals = totals();
+        let tagged: Value = btreemap! {
+            "name" => "requests",
+            "kind" => "incremental",
+            "tags" => btreemap! { "host" => "a" },
+            "counter" => btreemap! { "value" => 5.0 },
+        }
+        .into();
+
+        to_absolute_metric(&totals, counter("incremental", 1.0)).unwrap();
+        to_absolute_metric(&totals, tagged).unwrap();
+
+        assert_eq!(
+            to_absolute_metric(&totals, counter("incremental", 1.0)),
+            Ok(absolute(2.0))
+        );
+    }
+
+    #[test]
+    fn type_change_resets_total() {
+        let totals = totals();
+        let gauge: Value = btreemap! {
+            "name" => "requests",
+            "kind" => "incremental",
+            "gauge" => btreemap! { "value" => 3.0 },
+        }
+        .into();
+
+        to_absolute_metric(&totals, counter("incremental", 1.0)).unwrap();
+        to_absolute_metric(&totals, gauge).unwrap();
+
+        assert_eq!(
+            to_absolute_metric(&totals, counter("incremental", 1.0)),
+            Ok(absolute(1.0))
+        );
+    }
+
+    #[test]
+    fn invalid_metric() {
+        let totals = totals();
+
+        assert_eq!(
+            to_absolute_metric(&totals, btreemap! { "name" => "requests" }.into()),
+            Err("missing field kind".into())
+        );
+    }
+
+    #[test]
+    fn expired_series_reset_total() {
+        let totals = Arc::new(Mutex::new(Totals::new(Duration::ZERO)));
+
+        to_absolute_metric(&totals, counter("incremental", 1.0)).unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert_eq!(
+            to_absolute_metric(&totals, counter("incremental", 1.0)),
+            Ok(absolute(1.0))
+        );
+        // Expired series are dropped, rather than kept around until they're seen again.
+        assert_eq!(totals.lock().unwrap().series.len(), 1);
+    }
+}
@@ -4,13 +4,21 @@ use lookup::{LookupBuf, SegmentBuf};
 use snafu::Snafu;
 use vrl_lib::{prelude::VrlValueConvert, MetadataTarget, ProgramInfo, SecretTarget};
 
-use super::{Event, EventMetadata, LogEvent, Metric, MetricKind, TraceEvent, Value};
+use super::{
+    metric::METRIC_VALUE_FIELDS, Event, EventMetadata, LogEvent, Metric, MetricKind, MetricValue,
+    TraceEvent, Value,
+};
 use crate::config::log_schema;
 
-const VALID_METRIC_PATHS_SET: &str = ".name, .namespace, .timestamp, .kind, .tags";
+const VALID_METRIC_PATHS_SET: &str =
+    ".name, .namespace, .timestamp, .kind, .tags, .counter, .gauge, \
+    .set, .distribution, .aggregated_histogram, .aggregated_summary";
 
-/// We can get the `type` of the metric in Remap, but can't set it.
-const VALID_METRIC_PATHS_GET: &str = ".name, .namespace, .timestamp, .kind, .tags, .type";
+/// We can get the `type` of the metric in Remap, but can't set it. The type changes by setting the
+/// value of another type of metric instead.
+const VALID_METRIC_PATHS_GET: &str =
+    ".name, .namespace, .timestamp, .kind, .tags, .counter, .gauge, \
+    .set, .distribution, .aggregated_histogram, .aggregated_summary, .type";
 
 /// Metrics aren't interested in paths that have a length longer than 3.
 ///
@@ -139,7 +147,7 @@ impl VrlTarget {
                 v => {
                     let mut log = LogEvent::new_with_metadata(metadata);
                     log.insert(log_schema().message_key(), v);
-                    TargetEvents::One(log.into())
+                    TargetEvents::One(TraceEvent::from(log).into())
                 }
             },
             VrlTarget::Metric { metric, .. } => TargetEvents::One(Event::Metric(metric)),
@@ -164,16 +172,31 @@ impl VrlTarget {
 impl vrl_lib::Target for VrlTarget {
     fn target_insert(&mut self, path: &LookupBuf, value: ::value::Value) -> Result<(), String> {
         match self {
-            VrlTarget::LogEvent(ref mut log, _) | VrlTarget::Trace(ref mut log, _) => {
+            VrlTarget::LogEvent(ref mut log, _) => {
                 log.insert_by_path(path, value);
                 Ok(())
             }
+            VrlTarget::Trace(ref mut trace, _) => {
+                validate_trace_insert(path, &value)?;
+                trace.insert_by_path(path, value);
+                Ok(())
+            }
             VrlTarget::Metric {
                 ref mut metric,
                 value: metric_value,
             } => {
+                // Assigning to the root replaces the entire metric.
                 if path.is_root() {
-                    return Err(MetricPathError::SetPathError.to_string());
+                    *metric = Metric::from_vrl(value, metric.metadata().clone())?;
+                    *metric_value = metric.to_vrl();
+
+                    return Ok(());
+                }
+
+                if let Some(field) = metric_value_field(path) {
+                    return update_metric_value(metric, metric_value, field, |data| {
+                        data.insert_by_path(path, value);
+                    });
                 }
 
                 if let Some(paths) = path.to_alternative_components(MAX_METRIC_PATH_DEPTH).get(0) {
@@ -268,6 +291,17 @@ impl vrl_lib::Target for VrlTarget {
                     return Err(MetricPathError::SetPathError.to_string());
                 }
 
+                // The value itself can't be removed, only the fields within it.
+                if let Some(field) = metric_value_field(path) {
+                    if path.len() == 1 {
+                        return Err(MetricPathError::RemoveValueError { field }.to_string());
+                    }
+
+                    return update_metric_value(metric, value, field, |data| {
+                        data.remove_by_path(path, false)
+                    });
+                }
+
                 if let Some(paths) = path.to_alternative_components(MAX_METRIC_PATH_DEPTH).get(0) {
                     let removed_value = match paths.as_slice() {
                         ["namespace"] => metric.series.name.namespace.take().map(Into::into),
@@ -338,6 +372,7 @@ impl SecretTarget for VrlTarget {
 /// - tags
 /// - tags.<tagname>
 /// - type
+/// - the field holding the value, such as `counter`, and any path within it
 ///
 /// Any other paths result in a `MetricPathError::InvalidPath` being returned.
 fn target_get_metric<'a>(path: &LookupBuf, value: &'a Value) -> Result<Option<&'a Value>, String> {
@@ -345,6 +380,10 @@ fn target_get_metric<'a>(path: &LookupBuf, value: &'a Value) -> Result<Option<&'
         return Ok(Some(value));
     }
 
+    if metric_value_field(path).is_some() {
+        return Ok(value.get_by_path(path));
+    }
+
     let value = value.get_by_path(path);
 
     for paths in path.to_alternative_components(MAX_METRIC_PATH_DEPTH) {
@@ -416,47 +455,12 @@ fn precompute_metric_value(metric: &Metric, info: &ProgramInfo) -> Value {
     let mut set_namespace = false;
     let mut set_timestamp = false;
     let mut set_tags = false;
+    let mut set_value = false;
 
     for path in &info.target_queries {
         // Accessing a root path requires us to pre-populate all fields.
         if path.is_root() {
-            if !set_name {
-                map.insert("name".to_owned(), metric.name().to_owned().into());
-            }
-
-            if !set_kind {
-                map.insert("kind".to_owned(), metric.kind().into());
-            }
-
-            if !set_type {
-                map.insert("type".to_owned(), metric.value().clone().into());
-            }
-
-            if !set_namespace {
-                if let Some(namespace) = metric.namespace() {
-                    map.insert("namespace".to_owned(), namespace.to_owned().into());
-                }
-            }
-
-            if !set_timestamp {
-                if let Some(timestamp) = metric.timestamp() {
-                    map.insert("timestamp".to_owned(), timestamp.into());
-                }
-            }
-
-            if !set_tags {
-                if let Some(tags) = metric.tags().cloned() {
-                    map.insert(
-                        "tags".to_owned(),
-                        tags.into_iter()
-                            .map(|(tag, value)| (tag, value.into()))
-                            .collect::<BTreeMap<_, _>>()
-                            .into(),
-                    );
-                }
-            }
-
-            break;
+            return metric.to_vrl();
         }
 
         // For non-root paths, we contiuously populate the value with the
@@ -500,6 +504,14 @@ fn precompute_metric_value(metric: &Metric, info: &ProgramInfo) -> Value {
                             .into(),
                     );
                 }
+                field if !set_value && METRIC_VALUE_FIELDS.contains(&field) => {
+                    set_value = true;
+                    if let (Some(field), Some(value)) =
+                        (metric.value().vrl_field(), metric.value().to_vrl())
+                    {
+                        map.insert(field.to_owned(), value);
+                    }
+                }
                 _ => {}
             }
         }
@@ -508,11 +520,122 @@ fn precompute_metric_value(metric: &Metric, info: &ProgramInfo) -> Value {
     map.into()
 }
 
+/// Checks that assigning the value at the given path keeps the trace well-formed.
+///
+/// A trace is an object, and its spans are held as objects in the `spans` array. Assigning an
+/// array to the root emits one trace per element, so each element must be an object.
+fn validate_trace_insert(path: &LookupBuf, value: &Value) -> Result<(), String> {
+    let mut segments = path.iter();
+
+    let valid = match (segments.next(), segments.next(), segments.next()) {
+        (None, _, _) => match value {
+            Value::Object(trace) => trace.get("spans").map_or(true, is_spans),
+            Value::Array(traces) => traces.iter().all(|trace| {
+                trace
+                    .as_object()
+                    .map_or(false, |trace| trace.get("spans").map_or(true, is_spans))
+            }),
+            _ => false,
+        },
+        (Some(SegmentBuf::Field(field)), None, _) if field.as_str() == "spans" => is_spans(value),
+        (Some(SegmentBuf::Field(field)), Some(SegmentBuf::Index(_)), None)
+            if field.as_str() == "spans" =>
+        {
+            value.is_object()
+        }
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(TracePathError {
+            path: &path.to_string(),
+        }
+        .to_string())
+    }
+}
+
+fn is_spans(value: &Value) -> bool {
+    value
+        .as_array()
+        .map_or(false, |spans| spans.iter().all(Value::is_object))
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display(
+    "invalid value for trace path .{}: traces must be objects, with their spans held as objects in \
+    the .spans array",
+    path
+))]
+struct TracePathError<'a> {
+    path: &'a str,
+}
+
+/// Gets the field holding the value of the metric that the path points into, if any.
+fn metric_value_field(path: &LookupBuf) -> Option<&'static str> {
+    match path.iter().next() {
+        Some(SegmentBuf::Field(field)) => METRIC_VALUE_FIELDS
+            .iter()
+            .find(|value_field| **value_field == field.as_str())
+            .copied(),
+        _ => None,
+    }
+}
+
+/// Updates the value of the metric through its VRL representation, held in the given field.
+///
+/// If the metric holds a value of another type, the update starts from an empty value of the
+/// given type, changing the type of the metric if the result is valid. The pre-computed VRL
+/// representation of the metric is kept in sync with the new value.
+fn update_metric_value<T>(
+    metric: &mut Metric,
+    precomputed: &mut Value,
+    field: &'static str,
+    update: impl FnOnce(&mut Value) -> T,
+) -> Result<T, String> {
+    let current = metric
+        .value()
+        .to_vrl()
+        .filter(|_| metric.value().vrl_field() == Some(field))
+        .unwrap_or_else(|| Value::Object(BTreeMap::new()));
+    let mut data = Value::Object(BTreeMap::from([(field.to_owned(), current)]));
+
+    let result = update(&mut data);
+
+    let value = data
+        .into_object()
+        .and_then(|mut data| data.remove(field))
+        .ok_or(MetricPathError::RemoveValueError { field })
+        .map_err(|e| e.to_string())?;
+    metric.data.value = MetricValue::from_vrl(field, value)?;
+
+    if let Value::Object(map) = precomputed {
+        for value_field in METRIC_VALUE_FIELDS {
+            map.remove(value_field);
+        }
+        if let Some(value) = metric.value().to_vrl() {
+            map.insert(field.to_owned(), value);
+        }
+        if map.contains_key("type") {
+            map.insert("type".to_owned(), metric.value().clone().into());
+        }
+    }
+
+    Ok(result)
+}
+
 #[derive(Debug, Snafu)]
 enum MetricPathError<'a> {
     #[snafu(display("cannot set root path"))]
     SetPathError,
 
+    #[snafu(display(
+        "cannot remove the value of the metric, remove fields within .{} instead",
+        field
+    ))]
+    RemoveValueError { field: &'a str },
+
     #[snafu(display("invalid path {}: expected one of {}", path, expected))]
     InvalidPath { path: &'a str, expected: &'a str },
 }
@@ -525,7 +648,7 @@ mod test {
     use vrl_lib::Target;
 
     use super::{
-        super::{metric::MetricTags, MetricValue},
+        super::{metric::MetricTags, MetricValue, StatisticKind},
         *,
     };
 
@@ -1014,16 +1137,22 @@ mod test {
             MetricValue::Counter { value: 1.23 },
         );
 
-        let validpaths_get = vec![
+        let validpaths_set = vec![
             ".name",
             ".namespace",
             ".timestamp",
             ".kind",
             ".tags",
-            ".type",
+            ".counter",
+            ".gauge",
+            ".set",
+            ".distribution",
+            ".aggregated_histogram",
+            ".aggregated_summary",
         ];
 
-        let validpaths_set = vec![".name", ".namespace", ".timestamp", ".kind", ".tags"];
+        let mut validpaths_get = validpaths_set.clone();
+        validpaths_get.push(".type");
 
         let info = ProgramInfo {
             fallible: false,
@@ -1065,4 +1194,251 @@ mod test {
             target.target_get(&LookupBuf::from_str("tags.foo.flork").unwrap())
         );
     }
+
+    fn into_metric(target: VrlTarget) -> Metric {
+        match target.into_events() {
+            TargetEvents::One(Event::Metric(metric)) => metric,
+            _ => panic!("expected a single metric"),
+        }
+    }
+
+    #[test]
+    fn metric_value_fields() {
+        let metric = Metric::new(
+            "name",
+            MetricKind::Absolute,
+            MetricValue::Distribution {
+                samples: crate::samples![1.0 => 1, 2.0 => 3],
+                statistic: StatisticKind::Histogram,
+            },
+        );
+
+        let info = ProgramInfo {
+            fallible: false,
+            abortable: false,
+            target_queries: vec!["distribution".into()],
+            target_assignments: vec![],
+        };
+        let mut target = VrlTarget::new(Event::Metric(metric), &info);
+        let path = |path: &str| LookupBuf::from_str(path).unwrap();
+
+        assert_eq!(
+            Ok(Some(::value::Value::from(2.0))),
+            target
+                .target_get(&path("distribution.samples[1].value"))
+                .map(Option::<&Value>::cloned)
+        );
+
+        assert_eq!(
+            Ok(()),
+            target.target_insert(&path("distribution.samples[0].rate"), 5.into())
+        );
+        assert_eq!(
+            Ok(Some(btreemap! { "value" => 1.0, "rate" => 5 }.into())),
+            target
+                .target_get(&path("distribution.samples[0]"))
+                .map(Option::<&Value>::cloned)
+        );
+
+        assert_eq!(
+            Ok(Some(btreemap! { "value" => 2.0, "rate" => 3 }.into())),
+            target.target_remove(&path("distribution.samples[1]"), false)
+        );
+
+        assert_eq!(
+            Err("missing field distribution.samples[0].value".to_owned()),
+            target.target_remove(&path("distribution.samples[0].value"), false)
+        );
+        assert_eq!(
+            Err(
+                "cannot remove the value of the metric, remove fields within .distribution instead"
+                    .to_owned()
+            ),
+            target.target_remove(&path("distribution"), false)
+        );
+        assert_eq!(
+            Err(
+                "invalid field distribution.statistic: expected histogram or summary, got median"
+                    .to_owned()
+            ),
+            target.target_insert(&path("distribution.statistic"), "median".into())
+        );
+
+        assert_eq!(
+            &MetricValue::Distribution {
+                samples: crate::samples![1.0 => 5],
+                statistic: StatisticKind::Histogram,
+            },
+            into_metric(target).value()
+        );
+    }
+
+    #[test]
+    fn metric_change_type() {
+        let metric = Metric::new(
+            "name",
+            MetricKind::Absolute,
+            MetricValue::Counter { value: 1.23 },
+        );
+
+        let info = ProgramInfo {
+            fallible: false,
+            abortable: false,
+            target_queries: vec!["type".into(), "counter".into(), "gauge".into()],
+            target_assignments: vec![],
+        };
+        let mut target = VrlTarget::new(Event::Metric(metric), &info);
+        let path = |path: &str| LookupBuf::from_str(path).unwrap();
+
+        assert_eq!(Ok(()), target.target_insert(&path("gauge.value"), 2.into()));
+
+        assert_eq!(
+            Ok(Some("gauge".into())),
+            target
+                .target_get(&path("type"))
+                .map(Option::<&Value>::cloned)
+        );
+        assert_eq!(
+            Ok(None),
+            target
+                .target_get(&path("counter"))
+                .map(Option::<&Value>::cloned)
+        );
+        assert_eq!(
+            Ok(Some(btreemap! { "value" => 2.0 }.into())),
+            target
+                .target_get(&path("gauge"))
+                .map(Option::<&Value>::cloned)
+        );
+        assert_eq!(
+            &MetricValue::Gauge { value: 2.0 },
+            into_metric(target).value()
+        );
+    }
+
+    #[test]
+    fn metric_root_insert() {
+        let metric = Metric::new(
+            "name",
+            MetricKind::Absolute,
+            MetricValue::Counter { value: 1.23 },
+        );
+
+        let info = ProgramInfo {
+            fallible: false,
+            abortable: false,
+            target_queries: vec![LookupBuf::root()],
+            target_assignments: vec![],
+        };
+        let mut target = VrlTarget::new(Event::Metric(metric), &info);
+
+        assert_eq!(
+            Err("invalid metric: expected object, got string".to_owned()),
+            target.target_insert(&LookupBuf::root(), "foo".into())
+        );
+
+        let value: ::value::Value = btreemap! {
+            "name" => "new",
+            "kind" => "incremental",
+            "tags" => btreemap! { "host" => "localhost" },
+            "set" => btreemap! { "values" => vec!["a", "b"] },
+        }
+        .into();
+        assert_eq!(Ok(()), target.target_insert(&LookupBuf::root(), value));
+
+        let expected = Metric::new(
+            "new",
+            MetricKind::Incremental,
+            MetricValue::Set {
+                values: vec!["a".to_owned(), "b".to_owned()].into_iter().collect(),
+            },
+        )
+        .with_tags(Some({
+            let mut map = MetricTags::new();
+            map.insert("host".to_string(), "localhost".to_string());
+            map
+        }));
+
+        assert_eq!(
+            Ok(Some(expected.to_vrl())),
+            target
+                .target_get(&LookupBuf::root())
+                .map(Option::<&Value>::cloned)
+        );
+        assert_eq!(expected, into_metric(target));
+    }
+
+    #[test]
+    fn trace_insert() {
+        let trace = TraceEvent::from(btreemap! {
+            "service" => "api",
+            "spans" => vec![btreemap! { "name" => "GET /health" }],
+        });
+        let info = ProgramInfo {
+            fallible: false,
+            abortable: false,
+            target_queries: vec![],
+            target_assignments: vec![],
+        };
+        let mut target = VrlTarget::new(Event::Trace(trace), &info);
+
+        let cases = vec![
+            ("service", Value::from("web"), true),
+            ("spans[0].name", Value::from("GET /"), true),
+            ("spans[1]", btreemap! { "name" => "db" }.into(), true),
+            ("spans[1]", Value::from("db"), false),
+            ("spans", Value::from("db"), false),
+            ("spans", vec![Value::from(1)].into(), false),
+            ("spans", Value::Array(vec![]), true),
+            (".", Value::from("trace"), false),
+            (".", btreemap! { "spans" => "db" }.into(), false),
+            (".", vec![Value::from(1)].into(), false),
+        ];
+
+        for (path, value, valid) in cases {
+            let path = match path {
+                "." => LookupBuf::root(),
+                path => LookupBuf::from_str(path).unwrap(),
+            };
+            assert_eq!(
+                valid,
+                target.target_insert(&path, value).is_ok(),
+                "{}",
+                path
+            );
+        }
+
+        match target.into_events() {
+            TargetEvents::One(Event::Trace(trace)) => {
+                assert_eq!(trace.get("service"), Some(&Value::from("web")));
+                assert_eq!(trace.spans().count(), 0);
+            }
+            _ => panic!("expected a single trace"),
+        }
+    }
+
+    #[test]
+    fn trace_spans() {
+        let mut trace = TraceEvent::from(btreemap! {
+            "spans" => vec![
+                Value::from(btreemap! { "name" => "GET /health", "service" => "api" }),
+                Value::from(btreemap! { "name" => "GET /", "service" => "api" }),
+                Value::from("invalid"),
+            ],
+        });
+
+        assert_eq!(trace.spans().count(), 2);
+
+        trace.retain_spans(|span| span.get("name") != Some(&Value::from("GET /health")));
+        for span in trace.spans_mut() {
+            span.insert("service".to_owned(), "web".into());
+        }
+
+        assert_eq!(
+            trace.get("spans"),
+            Some(&Value::from(vec![Value::from(
+                btreemap! { "name" => "GET /", "service" => "web" }
+            )]))
+        );
+    }
 }
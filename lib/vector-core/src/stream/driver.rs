@@ -1,4 +1,5 @@
 use std::{
+    any::Any,
     collections::{BinaryHeap, VecDeque},
     fmt,
     num::NonZeroUsize,
@@ -15,7 +16,7 @@ use vector_common::internal_event::BytesSent;
 
 use super::FuturesUnorderedChunked;
 use crate::{
-    event::{EventStatus, Finalizable},
+    event::{EventFinalizers, EventStatus, Finalizable, Rejection},
     internal_event::{emit, EventsSent},
 };
 
@@ -144,6 +145,40 @@ pub trait DriverResponse {
     fn bytes_sent(&self) -> Option<BytesSent> {
         None
     }
+
+    /// Gets the reason the request was rejected, when `event_status` is `EventStatus::Rejected`.
+    ///
+    /// The reason is attached to the finalizers of the rejected events, which allows routing them,
+    /// along with the reason, to a dead-letter output.
+    fn rejection(&self) -> Option<Rejection> {
+        None
+    }
+
+    /// Updates the finalizers of the events in the request with the outcome of the request.
+    ///
+    /// By default, every event gets the status of the response, and is rejected for the reason
+    /// given by `rejection` if the request was rejected.  Responses that report the outcome of each
+    /// event separately can override this to only reject the events that failed.
+    fn finalize(&self, finalizers: EventFinalizers) {
+        match self.event_status() {
+            EventStatus::Rejected => finalizers.reject(
+                self.rejection()
+                    .unwrap_or_else(|| Rejection::new("Request was rejected.")),
+            ),
+            status => finalizers.update_status(status),
+        }
+    }
+}
+
+/// Gets a message describing an error returned by a service.
+///
+/// Services are only required to return errors that implement `Debug`, but most return boxed
+/// errors, which have a more useful `Display` implementation.
+fn error_message<E: fmt::Debug + 'static>(error: &E) -> String {
+    match (error as &dyn Any).downcast_ref::<Box<dyn std::error::Error + Send + Sync>>() {
+        Some(error) => error.to_string(),
+        None => format!("{:?}", error),
+    }
 }
 
 /// Drives the interaction between a stream of items and a service which processes them
@@ -289,11 +324,11 @@ where
                                 match result {
                                     Err(error) => {
                                         error!(message = "Service call failed.", ?error, request_id);
-                                        finalizers.update_status(EventStatus::Rejected);
+                                        finalizers.reject(Rejection::new(error_message(&error)));
                                     },
                                     Ok(response) => {
                                         trace!(message = "Service call succeeded.", request_id);
-                                        finalizers.update_status(response.event_status());
+                                        response.finalize(finalizers);
                                         if response.event_status() == EventStatus::Delivered {
                                             if let Some(bytes_sent) = response.bytes_sent() {
                                                 emit(bytes_sent);
@@ -350,7 +385,7 @@ mod tests {
     use vector_buffers::{Ackable, Acker};
     use vector_common::internal_event::EventsSent;
 
-    use super::{Driver, DriverResponse};
+    use super::{error_message, Driver, DriverResponse};
     use crate::{
         event::{EventFinalizers, EventStatus, Finalizable},
         stream::driver::AcknowledgementTracker,
@@ -627,4 +662,14 @@ mod tests {
             Err(()) => panic!("driver unexpectedly returned with error!"),
         }
     }
+
+    #[test]
+    fn error_message_prefers_display() {
+        #[derive(Debug)]
+        struct DebugOnly;
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "entry out of order".into();
+        assert_eq!(error_message(&boxed), "entry out of order");
+        assert_eq!(error_message(&DebugOnly), "DebugOnly");
+    }
 }
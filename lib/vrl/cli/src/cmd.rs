@@ -8,18 +8,30 @@ use std::{
 };
 
 use ::value::Value;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use value::Secrets;
 use vector_common::TimeZone;
-use vrl::{diagnostic::Formatter, state, Program, Runtime, Target, VrlRuntime};
+use vrl::{profile::Profile, state, Program, Runtime, Source, Target, Vm, VrlRuntime};
 
+#[cfg(feature = "lsp")]
+use super::lsp;
 #[cfg(feature = "repl")]
-use super::repl;
-use super::Error;
+use super::{debug, repl};
+use super::{fmt, test, Error};
+
+/// The number of most expensive expressions printed when profiling.
+const PROFILE_ENTRIES: usize = 10;
 
 #[derive(Parser, Debug)]
-#[clap(name = "VRL", about = "Vector Remap Language CLI")]
+#[clap(
+    name = "VRL",
+    about = "Vector Remap Language CLI",
+    args_conflicts_with_subcommands = true
+)]
 pub struct Opts {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The VRL program to execute. The program ".foo = true", for example, sets the event object's
     /// `foo` field to `true`.
     #[clap(name = "PROGRAM")]
@@ -49,24 +61,52 @@ pub struct Opts {
     // Should the CLI emit warnings
     #[clap(long = "print-warnings")]
     print_warnings: bool,
+
+    /// Record the time spent resolving each expression and function call, and print the most
+    /// expensive ones once all events are handled. Only supported by the `ast` runtime.
+    #[clap(long)]
+    profile: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run VRL programs against input events, and compare the resulting events with the expected
+    /// output.
+    Test(test::Opts),
+
+    /// Format VRL programs, or the programs embedded in Vector configuration files.
+    Fmt(fmt::Opts),
+
+    /// Step through a VRL program run against input events, with breakpoints, and inspect its
+    /// variables and the event along the way.
+    #[cfg(feature = "repl")]
+    Debug(debug::Opts),
+
+    /// Start a language server, communicating over stdio, to provide diagnostics, completion,
+    /// hover documentation and go-to-definition in editors.
+    #[cfg(feature = "lsp")]
+    Lsp(lsp::Opts),
 }
 
 impl Opts {
     fn timezone(&self) -> Result<TimeZone, Error> {
-        if let Some(ref tz) = self.timezone {
-            TimeZone::parse(tz)
-                .ok_or_else(|| Error::Parse(format!("unable to parse timezone: {}", tz)))
-        } else {
-            Ok(TimeZone::default())
-        }
+        parse_timezone(self.timezone.as_deref())
     }
 
-    fn read_program(&self) -> Result<String, Error> {
+    fn read_program(&self) -> Result<Source, Error> {
         match self.program.as_ref() {
-            Some(source) => Ok(source.clone()),
+            Some(source) => Ok(Source::new(source.clone())),
             None => match self.program_file.as_ref() {
-                Some(path) => read(File::open(path)?),
-                None => Ok("".to_owned()),
+                Some(path) => {
+                    let source = Source::new(read(File::open(path)?)?);
+
+                    // Imports are resolved relative to the program file.
+                    Ok(match path.parent() {
+                        Some(dir) => source.with_base_dir(dir),
+                        None => source,
+                    })
+                }
+                None => Ok(Source::new("")),
             },
         }
     }
@@ -93,6 +133,16 @@ impl Opts {
 
 #[must_use]
 pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match &opts.command {
+        Some(Command::Test(opts)) => return test::cmd(opts),
+        Some(Command::Fmt(opts)) => return fmt::cmd(opts),
+        #[cfg(feature = "repl")]
+        Some(Command::Debug(opts)) => return debug::cmd(opts),
+        #[cfg(feature = "lsp")]
+        Some(Command::Lsp(opts)) => return lsp::cmd(opts),
+        None => {}
+    }
+
     match run(opts) {
         Ok(_) => exitcode::OK,
         Err(err) => {
@@ -119,18 +169,29 @@ fn run(opts: &Opts) -> Result<(), Error> {
 
         repl(repl_objects, tz, opts.runtime)
     } else {
+        if opts.profile && opts.runtime != VrlRuntime::Ast {
+            return Err(Error::ProfileRuntime);
+        }
+
         let objects = opts.read_into_objects()?;
-        let source = opts.read_program()?;
-        let (program, warnings) = vrl::compile(&source, &stdlib::all()).map_err(|diagnostics| {
-            Error::Parse(Formatter::new(&source, diagnostics).colored().to_string())
-        })?;
+        let mut source = opts.read_program()?;
+        let mut state = state::ExternalEnv::default();
+        let (program, warnings) =
+            source
+                .compile(&stdlib::all(), &mut state)
+                .map_err(|diagnostics| {
+                    Error::Parse(source.formatter(diagnostics).colored().to_string())
+                })?;
 
         #[allow(clippy::print_stderr)]
         if opts.print_warnings {
-            let warnings = Formatter::new(&source, warnings).colored().to_string();
+            let warnings = source.formatter(warnings).colored().to_string();
             eprintln!("{warnings}")
         }
 
+        let vm = build_vm(&program, opts.runtime);
+        let mut profile = Profile::default();
+
         for mut object in objects {
             let mut metadata = Value::Object(BTreeMap::new());
             let mut secrets = Secrets::new();
@@ -140,9 +201,12 @@ fn run(opts: &Opts) -> Result<(), Error> {
                 secrets: &mut secrets,
             };
             let state = state::Runtime::default();
-            let runtime = Runtime::new(state);
+            let mut runtime = Runtime::new(state);
+            if opts.profile {
+                runtime.enable_profiling();
+            }
 
-            let result = execute(&mut target, &program, tz, runtime, opts.runtime).map(|v| {
+            let result = execute(&mut target, &program, vm.as_ref(), tz, &mut runtime).map(|v| {
                 if opts.print_object {
                     object.to_string()
                 } else {
@@ -156,6 +220,21 @@ fn run(opts: &Opts) -> Result<(), Error> {
                 Ok(ok) => println!("{}", ok),
                 Err(err) => eprintln!("{}", err),
             }
+
+            if let Some(run) = runtime.take_profile() {
+                profile.merge(run);
+            }
+        }
+
+        #[allow(clippy::print_stderr)]
+        if opts.profile {
+            let report = source.formatter(profile.diagnostics(PROFILE_ENTRIES));
+            eprintln!(
+                "{} runs took {:?} in total.\n{}",
+                profile.runs(),
+                profile.duration(),
+                report.colored()
+            );
         }
 
         Ok(())
@@ -185,21 +264,43 @@ fn repl(_objects: Vec<Value>, _timezone: TimeZone, _vrl_runtime: VrlRuntime) ->
     Err(Error::ReplFeature)
 }
 
-fn execute(
+pub(crate) fn parse_timezone(timezone: Option<&str>) -> Result<TimeZone, Error> {
+    match timezone {
+        Some(tz) => TimeZone::parse(tz)
+            .ok_or_else(|| Error::Parse(format!("unable to parse timezone: {}", tz))),
+        None => Ok(TimeZone::default()),
+    }
+}
+
+/// Builds the VM for the given program, if it's to be run with the VM runtime.
+///
+/// The VM only needs to be built once, to run the program against any number of objects.
+pub(crate) fn build_vm(program: &Program, vrl_runtime: VrlRuntime) -> Option<Vm> {
+    match vrl_runtime {
+        VrlRuntime::Ast => None,
+        VrlRuntime::Vm => Some(Vm::new(program)),
+    }
+}
+
+/// Runs the program against the given object, with the VM built for it if there is one.
+pub(crate) fn execute(
     object: &mut impl Target,
     program: &Program,
+    vm: Option<&Vm>,
     timezone: TimeZone,
-    mut runtime: Runtime,
-    vrl_runtime: VrlRuntime,
+    runtime: &mut Runtime,
 ) -> Result<Value, Error> {
-    match vrl_runtime {
-        VrlRuntime::Ast => runtime
+    match vm {
+        None => runtime
             .resolve(object, program, &timezone)
             .map_err(Error::Runtime),
+        Some(vm) => runtime
+            .run_vm(vm, object, &timezone)
+            .map_err(Error::Runtime),
     }
 }
 
-fn serde_to_vrl(value: serde_json::Value) -> Value {
+pub(crate) fn serde_to_vrl(value: serde_json::Value) -> Value {
     use serde_json::Value as JsonValue;
 
     match value {
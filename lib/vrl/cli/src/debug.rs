@@ -0,0 +1,466 @@
+//! Step through a VRL program, one statement at a time.
+//!
+//! The program runs against each input event in turn. Whenever it pauses, at
+//! the first statement, a breakpoint, or after a step, the variables and the
+//! event can be inspected before resuming.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    process,
+};
+
+use ::value::Value;
+use ansi_term::Colour;
+use clap::Parser;
+use core::{TargetValue, TargetValueRef};
+use indoc::indoc;
+use lookup::LookupBuf;
+use parser::ast::Ident;
+use rustyline::{error::ReadlineError, Editor};
+use value::Secrets;
+use vrl::{
+    debug::{Branch, Debugger},
+    diagnostic::{Formatter, Span},
+    state, Context, Function, Runtime, Source,
+};
+
+use super::{
+    cmd::{parse_timezone, serde_to_vrl},
+    Error,
+};
+
+#[derive(Parser, Debug)]
+pub(crate) struct Opts {
+    /// The file containing the VRL program to debug.
+    #[clap(name = "PROGRAM", parse(from_os_str))]
+    program: PathBuf,
+
+    /// The file containing the event object(s) to run the program against, one JSON event per
+    /// line. An empty object is used if no file is given.
+    #[clap(short, long = "input", parse(from_os_str))]
+    input_file: Option<PathBuf>,
+
+    /// Lines of the program to pause at. If any breakpoint is set, the program runs until it
+    /// reaches one, otherwise it pauses at its first statement.
+    #[clap(short, long = "break")]
+    breakpoints: Vec<usize>,
+
+    /// The timezone used to parse dates.
+    #[clap(short = 'z', long)]
+    timezone: Option<String>,
+}
+
+#[must_use]
+pub(crate) fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match run(opts) {
+        Ok(()) => exitcode::OK,
+        Err(err) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", err);
+            }
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+#[allow(clippy::print_stdout)]
+fn run(opts: &Opts) -> Result<(), Error> {
+    let timezone = parse_timezone(opts.timezone.as_deref())?;
+
+    let mut functions = stdlib::all();
+    functions.extend(vector_vrl_functions::vrl_functions());
+
+    let mut source = Source::new(fs::read_to_string(&opts.program)?);
+    if let Some(dir) = opts.program.parent() {
+        source = source.with_base_dir(dir);
+    }
+
+    let (program, _) = source
+        .compile(&functions, &mut state::ExternalEnv::default())
+        .map_err(|diagnostics| Error::Parse(source.formatter(diagnostics).colored().to_string()))?;
+
+    let objects = match &opts.input_file {
+        Some(path) => fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_to_vrl(serde_json::from_str(line)?)))
+            .collect::<Result<Vec<_>, Error>>()?,
+        None => vec![],
+    };
+    let objects = if objects.is_empty() {
+        vec![Value::Object(BTreeMap::new())]
+    } else {
+        objects
+    };
+
+    let mut session = Session::new(&source, &functions, &opts.breakpoints);
+
+    println!(
+        "Debugging {}, type `help` for the list of commands.",
+        opts.program.display()
+    );
+
+    let count = objects.len();
+    for (index, mut object) in objects.into_iter().enumerate() {
+        if count > 1 {
+            println!("\nevent {} of {}: {}", index + 1, count, object);
+        }
+
+        session.start();
+
+        let mut metadata = Value::Object(BTreeMap::new());
+        let mut secrets = Secrets::new();
+        let mut target = TargetValueRef {
+            value: &mut object,
+            metadata: &mut metadata,
+            secrets: &mut secrets,
+        };
+
+        let mut runtime = Runtime::default();
+        match runtime.resolve_with_debugger(&mut target, &program, &timezone, &mut session) {
+            Ok(value) => println!("\nresult: {}", value),
+            Err(err) => println!("\n{}: {}", Colour::Red.paint("error"), err),
+        }
+
+        println!("event: {}", object);
+    }
+
+    Ok(())
+}
+
+/// How far the program runs before pausing again.
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    /// Pause at the next statement.
+    Step,
+
+    /// Pause at the next statement not nested deeper than the given depth.
+    Next(usize),
+
+    /// Pause at the next statement outside the block of the given depth.
+    Finish(usize),
+
+    /// Only pause at breakpoints.
+    Continue,
+}
+
+struct Session<'a> {
+    source: &'a Source,
+    functions: &'a [Box<dyn Function>],
+    editor: Editor<()>,
+    breakpoints: BTreeSet<usize>,
+    mode: Mode,
+
+    /// The line and span of the last statement, if it's in the main source,
+    /// so that a line with several statements only breaks once.
+    last_statement: Option<(usize, Span)>,
+}
+
+impl<'a> Session<'a> {
+    fn new(source: &'a Source, functions: &'a [Box<dyn Function>], breakpoints: &[usize]) -> Self {
+        Self {
+            source,
+            functions,
+            editor: Editor::new(),
+            breakpoints: breakpoints.iter().copied().collect(),
+            mode: Mode::Step,
+            last_statement: None,
+        }
+    }
+
+    /// Prepare to run the program against the next event.
+    fn start(&mut self) {
+        self.last_statement = None;
+        self.mode = if self.breakpoints.is_empty() {
+            Mode::Step
+        } else {
+            Mode::Continue
+        };
+    }
+
+    /// Whether the statement at `span` hits a breakpoint.
+    ///
+    /// Once a line with a breakpoint is reached, any statement on it that
+    /// follows the previous one (such as the statements nested in it) doesn't
+    /// break again. Reaching the line anew, in the next iteration of a loop,
+    /// does.
+    fn hits_breakpoint(&mut self, span: Span) -> bool {
+        let line = self.source.line(span.start());
+        let line = match line.file {
+            None => line.number,
+            Some(_) => {
+                self.last_statement = None;
+                return false;
+            }
+        };
+
+        let previous = self.last_statement.replace((line, span));
+
+        self.breakpoints.contains(&line)
+            && !matches!(previous, Some((previous_line, previous_span))
+                if previous_line == line && span.start() > previous_span.start())
+    }
+
+    fn is_stepping(&self, depth: usize) -> bool {
+        match self.mode {
+            Mode::Step => true,
+            Mode::Next(next) => depth <= next,
+            Mode::Finish(finish) => depth < finish,
+            Mode::Continue => false,
+        }
+    }
+
+    /// Read and handle commands, until one of them resumes the program.
+    #[allow(clippy::print_stdout)]
+    fn prompt(&mut self, span: Span, depth: usize, ctx: &Context<'_>) {
+        loop {
+            let line = match self.editor.readline("(debug) ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => process::exit(exitcode::OK),
+                Err(err) => {
+                    println!("unable to read line: {}", err);
+                    process::exit(exitcode::IOERR);
+                }
+            };
+            self.editor.add_history_entry(line.as_str());
+
+            let (command, argument) = match line.trim().split_once(' ') {
+                Some((command, argument)) => (command, argument.trim()),
+                None => (line.trim(), ""),
+            };
+
+            self.mode = match command {
+                "s" | "step" => Mode::Step,
+                "n" | "next" => Mode::Next(depth),
+                "f" | "finish" => Mode::Finish(depth),
+                "c" | "continue" => Mode::Continue,
+                _ => {
+                    self.command(command, argument, span, ctx);
+                    continue;
+                }
+            };
+
+            return;
+        }
+    }
+
+    /// Handle a command that doesn't resume the program.
+    #[allow(clippy::print_stdout)]
+    fn command(&mut self, command: &str, argument: &str, span: Span, ctx: &Context<'_>) {
+        match command {
+            "b" | "break" => self.set_breakpoint(argument),
+            "d" | "delete" => self.delete_breakpoint(argument),
+            "p" | "print" => self.print(argument, ctx),
+            "v" | "vars" => print_variables(ctx),
+            "w" | "where" => self.print_location(span),
+            "h" | "help" => println!("{}", HELP_TEXT),
+            "q" | "quit" => process::exit(exitcode::OK),
+            "" => {}
+            _ => println!(
+                "unknown command `{}`, type `help` for the list of commands",
+                command
+            ),
+        }
+    }
+
+    #[allow(clippy::print_stdout)]
+    fn print_location(&self, span: Span) {
+        let line = self.source.line(span.start());
+        let location = match line.file {
+            Some(file) => format!("{}:{}", file, line.number),
+            None => format!("line {}", line.number),
+        };
+
+        println!("{} {}", Colour::Cyan.paint(location), line.text.trim());
+    }
+
+    #[allow(clippy::print_stdout)]
+    fn set_breakpoint(&mut self, argument: &str) {
+        if argument.is_empty() {
+            let lines = self
+                .breakpoints
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            if lines.is_empty() {
+                println!("no breakpoints");
+            } else {
+                println!("breakpoints at lines {}", lines.join(", "));
+            }
+            return;
+        }
+
+        match argument.parse::<usize>() {
+            Ok(line) if line > 0 => {
+                self.breakpoints.insert(line);
+                println!("breakpoint set at line {}", line);
+            }
+            _ => println!("invalid line number `{}`", argument),
+        }
+    }
+
+    #[allow(clippy::print_stdout)]
+    fn delete_breakpoint(&mut self, argument: &str) {
+        if argument.is_empty() {
+            self.breakpoints.clear();
+            println!("deleted all breakpoints");
+            return;
+        }
+
+        match argument.parse::<usize>() {
+            Ok(line) if self.breakpoints.remove(&line) => {
+                println!("deleted breakpoint at line {}", line);
+            }
+            _ => println!("no breakpoint at line `{}`", argument),
+        }
+    }
+
+    /// Print a variable, or the result of an expression resolved against a
+    /// copy of the event.
+    #[allow(clippy::print_stdout)]
+    fn print(&self, argument: &str, ctx: &Context<'_>) {
+        let argument = match argument {
+            "" => ".",
+            argument => argument,
+        };
+
+        if let Some(value) = ctx.state().variable(&Ident::new(argument)) {
+            println!("{}", value);
+            return;
+        }
+
+        match self.evaluate(argument, ctx) {
+            Ok(value) => println!("{}", value),
+            Err(err) => println!("{}", err),
+        }
+    }
+
+    fn evaluate(&self, expression: &str, ctx: &Context<'_>) -> Result<Value, String> {
+        let value = ctx
+            .target()
+            .target_get(&LookupBuf::root())?
+            .cloned()
+            .unwrap_or(Value::Null);
+        let mut target = TargetValue {
+            value,
+            metadata: Value::Object(BTreeMap::new()),
+            secrets: Secrets::new(),
+        };
+
+        let (program, _) = vrl::compile(expression, self.functions)
+            .map_err(|diagnostics| Formatter::new(expression, diagnostics).to_string())?;
+
+        Runtime::default()
+            .resolve(&mut target, &program, ctx.timezone())
+            .map_err(|err| err.to_string())
+    }
+}
+
+impl Debugger for Session<'_> {
+    #[allow(clippy::print_stdout)]
+    fn statement(&mut self, span: Span, depth: usize, ctx: &Context<'_>) {
+        let breakpoint = self.hits_breakpoint(span);
+
+        if !breakpoint && !self.is_stepping(depth) {
+            return;
+        }
+
+        if breakpoint {
+            println!("{}", Colour::Yellow.paint("breakpoint"));
+        }
+
+        self.print_location(span);
+        self.prompt(span, depth, ctx);
+    }
+
+    #[allow(clippy::print_stdout)]
+    fn branch(&mut self, branch: Branch, depth: usize, _: &Context<'_>) {
+        if !self.is_stepping(depth) {
+            return;
+        }
+
+        let message = match branch {
+            Branch::Consequent => "condition is true, taking the `if` branch",
+            Branch::Alternative => "condition is false, taking the `else` branch",
+            Branch::None => "condition is false, skipping the `if` branch",
+        };
+
+        println!("{}", Colour::Purple.paint(message));
+    }
+}
+
+#[allow(clippy::print_stdout)]
+fn print_variables(ctx: &Context<'_>) {
+    let variables = ctx
+        .state()
+        .variables()
+        .map(|(ident, value)| (ident.to_string(), value))
+        .collect::<BTreeMap<_, _>>();
+
+    if variables.is_empty() {
+        println!("no variables assigned");
+    }
+
+    for (ident, value) in variables {
+        println!("{} = {}", ident, value);
+    }
+}
+
+const HELP_TEXT: &str = indoc! {r#"
+    VRL debugger commands:
+      step, s            Resolve the current statement, pausing inside any block or function it calls
+      next, n            Resolve the current statement, and pause at the next one in the same block
+      finish, f          Resume until the current block is done
+      continue, c        Resume until the next breakpoint
+      break, b <line>    Set a breakpoint, or list the breakpoints if no line is given
+      delete, d <line>   Delete a breakpoint, or all of them if no line is given
+      print, p <expr>    Print a variable, or an expression resolved against a copy of the event
+      vars, v            Print all assigned variables
+      where, w           Print the current statement
+      quit, q            Terminate the program
+"#};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoint_in_one_line_loop() {
+        let program = "for _, x in [1, 2] { .a = x; .b = x }\n.c = 1\n";
+        let source = Source::new(program);
+        let mut session = Session::new(&source, &[], &[1]);
+
+        let span = |statement: &str| {
+            let start = program.find(statement).unwrap();
+            Span::new(start, start + statement.len())
+        };
+
+        session.start();
+
+        // The loop, and the first iteration of its body.
+        assert!(session.hits_breakpoint(span("for _, x in [1, 2] { .a = x; .b = x }")));
+        assert!(!session.hits_breakpoint(span(".a = x")));
+        assert!(!session.hits_breakpoint(span(".b = x")));
+
+        // The second iteration.
+        assert!(session.hits_breakpoint(span(".a = x")));
+        assert!(!session.hits_breakpoint(span(".b = x")));
+
+        assert!(!session.hits_breakpoint(span(".c = 1")));
+    }
+
+    #[test]
+    fn breakpoint_on_each_event() {
+        let source = Source::new(".a = 1\n");
+        let mut session = Session::new(&source, &[], &[1]);
+
+        for _ in 0..2 {
+            session.start();
+            assert!(session.hits_breakpoint(Span::new(0, 6)));
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! Format VRL programs into their canonical form.
+//!
+//! Besides `.vrl` files, Vector configuration files (`.toml`, `.yaml` and
+//! `.yml`) can be formatted, in which case the VRL programs embedded in their
+//! multi-line `source` strings are formatted in place.
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use vrl::diagnostic::{DiagnosticMessage, Formatter};
+
+use super::{test::discover, Error};
+
+#[derive(Parser, Debug)]
+pub(crate) struct Opts {
+    /// The VRL programs or Vector configuration files to format, or the directories to search for
+    /// `.vrl` files. If no path is given, a program is read from stdin, and written to stdout.
+    #[clap(name = "PATH", parse(from_os_str))]
+    paths: Vec<PathBuf>,
+
+    /// Don't write the formatted sources, but exit with an error if any of them isn't formatted.
+    #[clap(long)]
+    check: bool,
+}
+
+#[must_use]
+pub(crate) fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match run(opts) {
+        Ok(true) => exitcode::OK,
+        Ok(false) => exitcode::SOFTWARE,
+        Err(err) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", err);
+            }
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+/// Format all sources, returning whether all of them were valid (and, when
+/// checking, already formatted).
+#[allow(clippy::print_stdout, clippy::print_stderr)]
+fn run(opts: &Opts) -> Result<bool, Error> {
+    if opts.paths.is_empty() {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+
+        let formatted = format_program(&source)?;
+        if opts.check {
+            return Ok(check(Path::new("<stdin>"), &source, &formatted));
+        }
+
+        print!("{}", formatted);
+        return Ok(true);
+    }
+
+    let mut paths = vec![];
+    for path in &opts.paths {
+        discover(path, &mut paths)?;
+    }
+
+    let mut success = true;
+
+    for path in &paths {
+        let source = fs::read_to_string(path)?;
+
+        let formatted = match format_file(path, &source) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                success = false;
+                continue;
+            }
+        };
+
+        if opts.check {
+            success &= check(path, &source, &formatted);
+        } else if formatted != source {
+            fs::write(path, formatted)?;
+        }
+    }
+
+    Ok(success)
+}
+
+/// Print the difference between the source and its formatted form, returning
+/// whether they're the same.
+#[allow(clippy::print_stdout)]
+fn check(path: &Path, source: &str, formatted: &str) -> bool {
+    if source == formatted {
+        return true;
+    }
+
+    println!("Diff in {}:", path.display());
+    println!("{}", prettydiff::diff_lines(source, formatted));
+
+    false
+}
+
+fn format_file(path: &Path, source: &str) -> Result<String, Error> {
+    let programs = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml_programs(source),
+        Some("yaml" | "yml") => yaml_programs(source),
+        _ => return format_program(source),
+    };
+
+    let mut formatted = String::with_capacity(source.len());
+    let mut last = 0;
+
+    for program in programs {
+        let line = source[..program.start].lines().count() + 1;
+        let text = format_embedded(&source[program.start..program.end]).map_err(|err| {
+            Error::Parse(format!("in program starting at line {}:\n{}", line, err))
+        })?;
+
+        // Leave the program untouched, if it would no longer fit its string.
+        let text = match program.delimiter {
+            Some(delimiter) if text.contains(delimiter) => &source[program.start..program.end],
+            _ => &text,
+        };
+
+        formatted.push_str(&source[last..program.start]);
+        formatted.push_str(text);
+        last = program.end;
+    }
+
+    formatted.push_str(&source[last..]);
+
+    Ok(formatted)
+}
+
+fn format_program(source: &str) -> Result<String, Error> {
+    parser::format(source).map_err(|err| {
+        let diagnostics = vec![Box::new(err) as Box<dyn DiagnosticMessage>];
+        Error::Parse(Formatter::new(source, diagnostics).colored().to_string())
+    })
+}
+
+/// Format a program embedded in a configuration file, keeping its indentation.
+fn format_embedded(text: &str) -> Result<String, Error> {
+    // Any whitespace in front of the closing delimiter is kept.
+    let (body, tail) = match text.rfind('\n') {
+        Some(i) if text[i + 1..].trim().is_empty() => text.split_at(i + 1),
+        _ => (text, ""),
+    };
+
+    if body.trim().is_empty() {
+        return Ok(text.to_owned());
+    }
+
+    let indent = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..indentation(line)])
+        .min_by_key(|indent| indent.len())
+        .unwrap_or_default();
+
+    let program = body
+        .lines()
+        .map(|line| {
+            line.strip_prefix(indent)
+                .unwrap_or_else(|| line.trim_start())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut formatted = format_program(&program)?
+        .lines()
+        .map(|line| match line {
+            "" => String::new(),
+            line => format!("{}{}", indent, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    formatted.push('\n');
+    formatted.push_str(tail);
+
+    Ok(formatted)
+}
+
+/// A VRL program embedded in a configuration file.
+struct Embedded {
+    /// The start of the program, at the beginning of its first line.
+    start: usize,
+
+    /// The end of the program.
+    end: usize,
+
+    /// The delimiter closing the string holding the program, if any.
+    delimiter: Option<&'static str>,
+}
+
+/// Split the text into its lines, along with their offsets. The lines keep
+/// their line endings.
+fn lines(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line)
+        })
+        .collect()
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Find the programs in `source = """..."""` and `source = '''...'''` strings.
+///
+/// Basic strings holding escape sequences are skipped, as are the sources of
+/// `lua` transforms.
+fn toml_programs(config: &str) -> Vec<Embedded> {
+    let lines = lines(config);
+    let mut programs = vec![];
+
+    for (i, (offset, line)) in lines.iter().enumerate() {
+        let delimiter = match line
+            .trim()
+            .strip_prefix("source")
+            .and_then(|rest| rest.trim_start().strip_prefix('='))
+            .map(str::trim_start)
+        {
+            Some("\"\"\"") => "\"\"\"",
+            Some("'''") => "'''",
+            _ => continue,
+        };
+
+        let start = offset + line.len();
+        let end = match config[start..].find(delimiter) {
+            Some(end) => start + end,
+            None => continue,
+        };
+
+        let escaped = delimiter == "\"\"\"" && config[start..end].contains('\\');
+        let ambiguous = config[end + delimiter.len()..].starts_with(&delimiter[..1]);
+
+        if escaped || ambiguous || toml_table_is_lua(&lines, i) {
+            continue;
+        }
+
+        programs.push(Embedded {
+            start,
+            end,
+            delimiter: Some(delimiter),
+        });
+    }
+
+    programs
+}
+
+fn toml_table_is_lua(lines: &[(usize, &str)], line: usize) -> bool {
+    let is_header = |(_, line): &(usize, &str)| line.trim_start().starts_with('[');
+
+    let start = lines[..line].iter().rposition(is_header).unwrap_or(0);
+    let end = lines[line..]
+        .iter()
+        .position(is_header)
+        .map_or(lines.len(), |end| line + end);
+
+    lines[start..end]
+        .iter()
+        .any(|(_, line)| line.trim().replace(' ', "") == r#"type="lua""#)
+}
+
+/// Find the programs in `source: |` block scalars.
+///
+/// The sources of `lua` transforms are skipped.
+fn yaml_programs(config: &str) -> Vec<Embedded> {
+    let lines = lines(config);
+    let mut programs = vec![];
+
+    for (i, (offset, line)) in lines.iter().enumerate() {
+        let is_block = line
+            .trim()
+            .strip_prefix("source:")
+            .map_or(false, |rest| matches!(rest.trim(), "|" | "|-" | "|+"));
+
+        if !is_block {
+            continue;
+        }
+
+        let key_indent = indentation(line);
+        let block = lines[i + 1..]
+            .iter()
+            .take_while(|(_, line)| line.trim().is_empty() || indentation(line) > key_indent)
+            .collect::<Vec<_>>();
+
+        // Trailing empty lines are part of the document, not the program.
+        let end = match block.iter().rev().find(|(_, line)| !line.trim().is_empty()) {
+            Some((start, line)) => start + line.len(),
+            None => continue,
+        };
+
+        if yaml_mapping_is_lua(&lines, i, key_indent) {
+            continue;
+        }
+
+        programs.push(Embedded {
+            start: offset + line.len(),
+            end,
+            delimiter: None,
+        });
+    }
+
+    programs
+}
+
+fn yaml_mapping_is_lua(lines: &[(usize, &str)], line: usize, indent: usize) -> bool {
+    let in_mapping =
+        |(_, line): &&(usize, &str)| line.trim().is_empty() || indentation(line) >= indent;
+    let is_lua = |(_, line): &&(usize, &str)| {
+        indentation(line) == indent
+            && matches!(
+                line.trim().replace(' ', "").as_str(),
+                "type:lua" | r#"type:"lua""# | "type:'lua'"
+            )
+    };
+
+    lines[..line]
+        .iter()
+        .rev()
+        .take_while(in_mapping)
+        .any(|l| is_lua(&l))
+        || lines[line..]
+            .iter()
+            .take_while(in_mapping)
+            .any(|l| is_lua(&l))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> (PathBuf, String) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data/fmt")
+            .join(name);
+        let source = fs::read_to_string(&path).unwrap();
+
+        (path, source)
+    }
+
+    fn program_texts<'a>(config: &'a str, programs: &[Embedded]) -> Vec<&'a str> {
+        programs
+            .iter()
+            .map(|program| &config[program.start..program.end])
+            .collect()
+    }
+
+    #[test]
+    fn find_toml_programs() {
+        let (_, config) = fixture("config.toml");
+        let programs = toml_programs(&config);
+
+        // The program holding an escape sequence and the `lua` transform
+        // source are skipped.
+        assert_eq!(
+            program_texts(&config, &programs),
+            vec!["  .a=1\n  .b=upcase!( .c )\n", ".d=[ 1,2 ]\n"]
+        );
+        assert_eq!(programs[0].delimiter, Some("\"\"\""));
+        assert_eq!(programs[1].delimiter, Some("'''"));
+    }
+
+    #[test]
+    fn find_yaml_programs() {
+        let (_, config) = fixture("config.yaml");
+        let programs = yaml_programs(&config);
+
+        // The `lua` transform source is skipped.
+        assert_eq!(
+            program_texts(&config, &programs),
+            vec!["      .a=1\n\n      .b=upcase!( .c )\n"]
+        );
+        assert_eq!(programs[0].delimiter, None);
+    }
+
+    #[test]
+    fn skip_lua_sources() {
+        let toml = indoc::indoc! {r#"
+            [transforms.lua]
+            source = """
+            emit(event)
+            """
+            type="lua"
+        "#};
+        assert!(toml_programs(toml).is_empty());
+
+        let yaml = indoc::indoc! {r#"
+            lua:
+              source: |
+                emit(event)
+              type: "lua"
+            remap:
+              source: |
+                .a = 1
+              type: remap
+        "#};
+        assert_eq!(
+            program_texts(yaml, &yaml_programs(yaml)),
+            vec!["    .a = 1\n"]
+        );
+    }
+
+    #[test]
+    fn format_embedded_program() {
+        assert_eq!(
+            format_embedded("    .a=1\n\n    if .b { .c=2 }\n  ").unwrap(),
+            "    .a = 1\n\n    if .b {\n        .c = 2\n    }\n  "
+        );
+
+        // Blank programs are left untouched.
+        assert_eq!(format_embedded("\n  ").unwrap(), "\n  ");
+
+        assert!(format_embedded("  .a = ").is_err());
+    }
+
+    #[test]
+    fn format_config_files() {
+        for (name, formatted) in [
+            ("config.toml", "config.formatted.toml"),
+            ("config.yaml", "config.formatted.yaml"),
+        ] {
+            let (path, source) = fixture(name);
+            let (formatted_path, want) = fixture(formatted);
+
+            assert_eq!(format_file(&path, &source).unwrap(), want, "{}", name);
+            assert_eq!(
+                format_file(&formatted_path, &want).unwrap(),
+                want,
+                "{}",
+                formatted
+            );
+        }
+    }
+}
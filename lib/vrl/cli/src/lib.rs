@@ -14,7 +14,13 @@
 
 pub mod cmd;
 #[cfg(feature = "repl")]
+mod debug;
+mod fmt;
+#[cfg(feature = "lsp")]
+mod lsp;
+#[cfg(feature = "repl")]
 mod repl;
+mod test;
 
 pub use cmd::{cmd, Opts};
 
@@ -35,4 +41,11 @@ pub enum Error {
 
     #[error("repl feature disabled, program input required")]
     ReplFeature,
+
+    #[error("profiling is only supported by the ast runtime")]
+    ProfileRuntime,
+
+    #[cfg(feature = "lsp")]
+    #[error("language server error: {}", .0)]
+    Lsp(String),
 }
@@ -0,0 +1,205 @@
+//! An open VRL document, and the conversion between byte offsets into its
+//! source and the line/character positions used by the protocol.
+//!
+//! Characters are counted in UTF-16 code units, which is the only position
+//! encoding all clients support.
+
+use lsp_types::{Position, Range};
+use vrl::diagnostic::Span;
+
+#[derive(Debug)]
+pub(super) struct Document {
+    text: String,
+
+    /// The byte offset at which each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub(super) fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self { text, line_starts }
+    }
+
+    pub(super) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(super) fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self
+            .line_starts
+            .partition_point(|start| *start <= offset)
+            .saturating_sub(1);
+        let start = self.line_starts[line];
+        let character = self.text[start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+
+        Position::new(to_u32(line), to_u32(character))
+    }
+
+    pub(super) fn offset(&self, position: Position) -> usize {
+        let start = match self.line_starts.get(position.line as usize) {
+            Some(start) => *start,
+            None => return self.text.len(),
+        };
+        let line = self.text[start..].split('\n').next().unwrap_or_default();
+
+        let mut units = 0;
+        for (index, c) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + index;
+            }
+            units += c.len_utf16();
+        }
+
+        start + line.len()
+    }
+
+    pub(super) fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start()), self.position(span.end()))
+    }
+
+    /// Whether the span lies within the document, rather than in one of the
+    /// modules it imports.
+    pub(super) fn contains(&self, span: Span) -> bool {
+        span.end() <= self.text.len()
+    }
+
+    /// The identifier at the given offset, and the span it covers.
+    pub(super) fn ident_at(&self, offset: usize) -> Option<(&str, Span)> {
+        let offset = offset.min(self.text.len());
+        let start = self.text[..offset]
+            .rfind(|c| !is_ident_char(c))
+            .map_or(0, |index| index + 1);
+        let end = self.text[offset..]
+            .find(|c| !is_ident_char(c))
+            .map_or(self.text.len(), |index| offset + index);
+
+        (start < end).then(|| (&self.text[start..end], Span::new(start, end)))
+    }
+
+    /// Whether the identifier ending at the given offset is called as a
+    /// function, that is, whether it's followed by `(` or `!(`.
+    pub(super) fn is_call(&self, end: usize) -> bool {
+        let rest = &self.text[end..];
+
+        rest.strip_prefix('!').unwrap_or(rest).starts_with('(')
+    }
+
+    /// The name of the function call surrounding the given offset, and the
+    /// keywords of the arguments that were already passed to it.
+    ///
+    /// This is a textual approximation, as the program usually doesn't parse
+    /// while it's being edited. Parentheses and commas within string literals
+    /// are not accounted for.
+    pub(super) fn call_at(&self, offset: usize) -> Option<(&str, Vec<&str>)> {
+        let before = &self.text[..offset.min(self.text.len())];
+
+        let mut depth = 0_usize;
+        let mut arguments_start = None;
+        for (index, c) in before.char_indices().rev() {
+            match c {
+                ')' | ']' | '}' => depth += 1,
+                '(' if depth == 0 => {
+                    arguments_start = Some(index);
+                    break;
+                }
+                '[' | '{' if depth == 0 => return None,
+                '(' | '[' | '{' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        let open = arguments_start?;
+        let name_end = if before[..open].ends_with('!') {
+            open - 1
+        } else {
+            open
+        };
+        if !before[..name_end].ends_with(is_ident_char) {
+            return None;
+        }
+        let (name, _) = self.ident_at(name_end - 1)?;
+
+        let keywords = before[open + 1..]
+            .split(',')
+            .filter_map(|argument| argument.split_once(':'))
+            .map(|(keyword, _)| keyword.trim())
+            .filter(|keyword| !keyword.is_empty() && keyword.chars().all(is_ident_char))
+            .collect();
+
+        Some((name, keywords))
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn to_u32(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_offset_round_trip() {
+        // `é` is two bytes but one UTF-16 unit, `😀` four bytes but two units.
+        let document = Document::new("foo = 1\n.é = \"😀x\"\n\nbar".to_owned());
+
+        let cases = [
+            (0, Position::new(0, 0)),
+            (4, Position::new(0, 4)),
+            (7, Position::new(0, 7)),
+            (8, Position::new(1, 0)),
+            (11, Position::new(1, 2)),
+            (15, Position::new(1, 6)),
+            (19, Position::new(1, 8)),
+            (22, Position::new(2, 0)),
+            (23, Position::new(3, 0)),
+            (26, Position::new(3, 3)),
+        ];
+
+        for (offset, position) in cases {
+            assert_eq!(document.position(offset), position, "offset {}", offset);
+            assert_eq!(document.offset(position), offset, "position {:?}", position);
+        }
+    }
+
+    #[test]
+    fn position_past_the_end() {
+        let document = Document::new("foo\nbar".to_owned());
+
+        assert_eq!(document.position(100), Position::new(1, 3));
+        assert_eq!(document.offset(Position::new(1, 100)), 7);
+        assert_eq!(document.offset(Position::new(5, 0)), 7);
+    }
+
+    #[test]
+    fn range_of_span() {
+        let document = Document::new("é = 1\nfoo = é".to_owned());
+
+        assert_eq!(
+            document.range(Span::new(7, 10)),
+            Range::new(Position::new(1, 0), Position::new(1, 3))
+        );
+    }
+
+    #[test]
+    fn ident_at() {
+        let document = Document::new("foo = bar_baz + 1".to_owned());
+
+        assert_eq!(document.ident_at(0), Some(("foo", Span::new(0, 3))));
+        assert_eq!(document.ident_at(3), Some(("foo", Span::new(0, 3))));
+        assert_eq!(document.ident_at(8), Some(("bar_baz", Span::new(6, 13))));
+        assert_eq!(document.ident_at(5), None);
+    }
+}
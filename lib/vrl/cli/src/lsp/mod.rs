@@ -0,0 +1,502 @@
+//! A language server for VRL, speaking the Language Server Protocol over stdio.
+//!
+//! The server provides compiler diagnostics, completion of function names,
+//! parameters and variables, hover documentation for functions, and
+//! go-to-definition for variables and user-defined functions.
+//!
+//! Documents are always synchronized in full, and re-compiled on every change.
+
+mod document;
+mod symbols;
+
+use std::{collections::HashMap, fmt::Write as _};
+
+use clap::Parser;
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, LogMessage,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Request as _},
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    DiagnosticRelatedInformation, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, Documentation, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    LogMessageParams, MarkupContent, MarkupKind, MessageType, NumberOrString, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Url,
+};
+use vrl::{
+    diagnostic::{Diagnostic, DiagnosticList, Severity},
+    state, Function, Source,
+};
+
+use self::{document::Document, symbols::Symbols};
+use super::Error;
+
+#[derive(Parser, Debug)]
+pub(crate) struct Opts {
+    /// Communicate over stdio. This is the only supported transport, the flag
+    /// is accepted for editors that always pass it.
+    #[clap(long)]
+    #[allow(dead_code)]
+    stdio: bool,
+}
+
+#[must_use]
+pub(crate) fn cmd(_opts: &Opts) -> exitcode::ExitCode {
+    match run() {
+        Ok(()) => exitcode::OK,
+        Err(err) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", err);
+            }
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    };
+
+    connection
+        .initialize(serde_json::to_value(capabilities)?)
+        .map_err(|err| Error::Lsp(err.to_string()))?;
+
+    Server::new(&connection).run()?;
+    io_threads.join()?;
+
+    Ok(())
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    functions: Vec<Box<dyn Function>>,
+    documents: HashMap<Url, Document>,
+}
+
+impl<'a> Server<'a> {
+    fn new(connection: &'a Connection) -> Self {
+        let mut functions = stdlib::all();
+        functions.extend(vector_vrl_functions::vrl_functions());
+
+        Self {
+            connection,
+            functions,
+            documents: HashMap::new(),
+        }
+    }
+
+    fn run(mut self) -> Result<(), Error> {
+        for message in &self.connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if self
+                        .connection
+                        .handle_shutdown(&request)
+                        .map_err(|err| Error::Lsp(err.to_string()))?
+                    {
+                        return Ok(());
+                    }
+
+                    let response = self.handle_request(request);
+                    self.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let Request { id, method, params } = request;
+
+        match method.as_str() {
+            Completion::METHOD => respond(id, params, |params: &CompletionParams| {
+                self.completion(params)
+            }),
+            HoverRequest::METHOD => respond(id, params, |params: &HoverParams| self.hover(params)),
+            GotoDefinition::METHOD => respond(id, params, |params: &GotoDefinitionParams| {
+                self.definition(params)
+            }),
+            method => Response::new_err(
+                id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {}", method),
+            ),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    match self.notification_params(notification)? {
+                        Some(params) => params,
+                        None => return Ok(()),
+                    };
+                let document = params.text_document;
+
+                self.update(document.uri, document.text, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    match self.notification_params(notification)? {
+                        Some(params) => params,
+                        None => return Ok(()),
+                    };
+
+                // With full synchronization, the last change holds the entire document.
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(
+                        params.text_document.uri,
+                        change.text,
+                        Some(params.text_document.version),
+                    ),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    match self.notification_params(notification)? {
+                        Some(params) => params,
+                        None => return Ok(()),
+                    };
+                let uri = params.text_document.uri;
+
+                self.documents.remove(&uri);
+                self.publish_diagnostics(uri, vec![], None)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The params of the notification, or `None` if they're invalid, in which
+    /// case the notification is ignored, and the client is told about it.
+    fn notification_params<T: serde::de::DeserializeOwned>(
+        &self,
+        notification: Notification,
+    ) -> Result<Option<T>, Error> {
+        match serde_json::from_value(notification.params) {
+            Ok(params) => Ok(Some(params)),
+            Err(err) => {
+                let params = LogMessageParams {
+                    typ: MessageType::ERROR,
+                    message: format!("invalid params for {}: {}", notification.method, err),
+                };
+
+                self.send(Message::Notification(Notification::new(
+                    LogMessage::METHOD.to_owned(),
+                    params,
+                )))?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn send(&self, message: Message) -> Result<(), Error> {
+        self.connection
+            .sender
+            .send(message)
+            .map_err(|err| Error::Lsp(err.to_string()))
+    }
+
+    fn update(&mut self, uri: Url, text: String, version: Option<i32>) -> Result<(), Error> {
+        let document = Document::new(text);
+        let diagnostics = self.diagnostics(&uri, &document);
+
+        self.documents.insert(uri.clone(), document);
+        self.publish_diagnostics(uri, diagnostics, version)
+    }
+
+    fn publish_diagnostics(
+        &self,
+        uri: Url,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+        version: Option<i32>,
+    ) -> Result<(), Error> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, version);
+
+        self.send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_owned(),
+            params,
+        )))
+    }
+
+    // -------------------------------------------------------------------------
+    // diagnostics
+    // -------------------------------------------------------------------------
+
+    fn diagnostics(&self, uri: &Url, document: &Document) -> Vec<lsp_types::Diagnostic> {
+        let mut source = Source::new(document.text());
+
+        // Imports are resolved relative to the document.
+        if let Some(dir) = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.parent().map(ToOwned::to_owned))
+        {
+            source = source.with_base_dir(dir);
+        }
+
+        let mut state = state::ExternalEnv::default();
+        let diagnostics: DiagnosticList = match source.compile(&self.functions, &mut state) {
+            Ok((_, warnings)) => warnings,
+            Err(diagnostics) => diagnostics,
+        };
+
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| to_lsp_diagnostic(uri, document, &diagnostic))
+            .collect()
+    }
+
+    // -------------------------------------------------------------------------
+    // completion
+    // -------------------------------------------------------------------------
+
+    fn completion(&self, params: &CompletionParams) -> Option<CompletionResponse> {
+        let position = &params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+
+        let mut items = vec![];
+
+        // Within the arguments of a function call, the parameters that weren't
+        // passed yet come first.
+        if let Some((name, passed)) = document.call_at(offset) {
+            if let Some(function) = self.function(name) {
+                items.extend(
+                    function
+                        .parameters()
+                        .iter()
+                        .filter(|parameter| !passed.contains(&parameter.keyword))
+                        .enumerate()
+                        .map(|(index, parameter)| CompletionItem {
+                            label: format!("{}:", parameter.keyword),
+                            kind: Some(CompletionItemKind::FIELD),
+                            detail: Some(parameter_signature(parameter)),
+                            insert_text: Some(format!("{}: ", parameter.keyword)),
+                            sort_text: Some(format!("0{:02}", index)),
+                            ..CompletionItem::default()
+                        }),
+                );
+            }
+        }
+
+        let symbols = parser::parse(document.text())
+            .map(|program| Symbols::new(program, document.text().len()))
+            .unwrap_or_default();
+
+        items.extend(
+            symbols
+                .variables_at(offset)
+                .into_iter()
+                .map(|name| CompletionItem {
+                    label: name.to_owned(),
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    sort_text: Some(format!("1{}", name)),
+                    ..CompletionItem::default()
+                }),
+        );
+
+        items.extend(self.functions.iter().map(|function| CompletionItem {
+            label: function.identifier().to_owned(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some(signature(function.as_ref())),
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: examples(function.as_ref()),
+            })),
+            sort_text: Some(format!("2{}", function.identifier())),
+            ..CompletionItem::default()
+        }));
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    // -------------------------------------------------------------------------
+    // hover
+    // -------------------------------------------------------------------------
+
+    fn hover(&self, params: &HoverParams) -> Option<Hover> {
+        let position = &params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+
+        let (name, span) = document.ident_at(offset)?;
+        if !document.is_call(span.end()) {
+            return None;
+        }
+        let function = self.function(name)?;
+
+        let mut value = format!("```coffee\n{}\n```\n", signature(function));
+        let examples = examples(function);
+        if !examples.is_empty() {
+            value.push('\n');
+            value.push_str(&examples);
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(document.range(span)),
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // go-to-definition
+    // -------------------------------------------------------------------------
+
+    fn definition(&self, params: &GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = &params.text_document_position_params;
+        let uri = &position.text_document.uri;
+        let document = self.documents.get(uri)?;
+        let offset = document.offset(position.position);
+
+        let program = parser::parse(document.text()).ok()?;
+        let symbols = Symbols::new(program, document.text().len());
+        let definition = symbols.definition_at(offset)?;
+
+        Some(GotoDefinitionResponse::Scalar(Location::new(
+            uri.clone(),
+            document.range(definition.span),
+        )))
+    }
+
+    fn function(&self, name: &str) -> Option<&dyn Function> {
+        self.functions
+            .iter()
+            .find(|function| function.identifier() == name)
+            .map(AsRef::as_ref)
+    }
+}
+
+/// Responds to a request with the result of the handler, or with an error if
+/// the params of the request are invalid.
+fn respond<P, R>(
+    id: RequestId,
+    params: serde_json::Value,
+    handler: impl FnOnce(&P) -> R,
+) -> Response
+where
+    P: serde::de::DeserializeOwned,
+    R: serde::Serialize,
+{
+    match serde_json::from_value(params) {
+        Ok(params) => Response::new_ok(id, handler(&params)),
+        Err(err) => Response::new_err(
+            id,
+            lsp_server::ErrorCode::InvalidParams as i32,
+            format!("invalid params: {}", err),
+        ),
+    }
+}
+
+fn to_lsp_diagnostic(
+    uri: &Url,
+    document: &Document,
+    diagnostic: &Diagnostic,
+) -> lsp_types::Diagnostic {
+    let labels = diagnostic
+        .labels()
+        .iter()
+        .filter(|label| document.contains(label.span))
+        .collect::<Vec<_>>();
+
+    // Diagnostics for imported modules have no position within the document,
+    // and are shown at its start.
+    let primary = labels.iter().find(|label| label.primary);
+    let range = primary.map_or_else(Default::default, |label| document.range(label.span));
+
+    let mut message = diagnostic.message().to_owned();
+    if let Some(label) = primary.filter(|label| !label.message.is_empty()) {
+        write!(message, "\n{}", label.message).expect("write to string");
+    }
+    for note in diagnostic.notes() {
+        write!(message, "\n{}", note).expect("write to string");
+    }
+
+    let related_information = labels
+        .iter()
+        .filter(|label| !label.primary)
+        .map(|label| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), document.range(label.span)),
+            message: label.message.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    lsp_types::Diagnostic {
+        range,
+        severity: Some(match diagnostic.severity() {
+            Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Note => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(format!("E{:03}", diagnostic.code()))),
+        source: Some("vrl".to_owned()),
+        message,
+        related_information: (!related_information.is_empty()).then(|| related_information),
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+/// The signature of a function, such as `parse_json(value: string, [max_depth: integer])`.
+fn signature(function: &dyn Function) -> String {
+    let parameters = function
+        .parameters()
+        .iter()
+        .map(parameter_signature)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("{}({})", function.identifier(), parameters)
+}
+
+fn parameter_signature(parameter: &vrl::function::Parameter) -> String {
+    let signature = format!("{}: {}", parameter.keyword, parameter.kind());
+
+    if parameter.required {
+        signature
+    } else {
+        format!("[{}]", signature)
+    }
+}
+
+/// The examples of a function, rendered as Markdown.
+fn examples(function: &dyn Function) -> String {
+    let mut markdown = String::new();
+
+    for example in function.examples() {
+        let result = match example.result {
+            Ok(result) => result,
+            Err(error) => error,
+        };
+
+        write!(
+            markdown,
+            "**{}**\n\n```coffee\n{}\n# => {}\n```\n\n",
+            example.title, example.source, result
+        )
+        .expect("write to string");
+    }
+
+    markdown
+}
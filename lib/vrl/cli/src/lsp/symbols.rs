@@ -0,0 +1,361 @@
+//! Variable and function definitions, and the references to them, found by
+//! walking the AST of a program.
+
+use parser::{
+    ast::{
+        Assignment, AssignmentTarget, Block, Container, Expr, FunctionCall, Literal, Loop, Node,
+        Predicate, Program, QueryTarget, RootExpr, Unary,
+    },
+    StringSegment,
+};
+use vrl::diagnostic::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SymbolKind {
+    Variable,
+    Function,
+}
+
+#[derive(Debug)]
+pub(super) struct Definition {
+    pub(super) kind: SymbolKind,
+    pub(super) name: String,
+    pub(super) span: Span,
+
+    /// The region of the program in which the definition is visible.
+    scope: Span,
+}
+
+#[derive(Debug)]
+pub(super) struct Reference {
+    pub(super) kind: SymbolKind,
+    pub(super) name: String,
+    pub(super) span: Span,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Symbols {
+    definitions: Vec<Definition>,
+    references: Vec<Reference>,
+}
+
+impl Symbols {
+    pub(super) fn new(program: Program, len: usize) -> Self {
+        let mut symbols = Self::default();
+        let scope = Span::new(0, len);
+
+        for node in program {
+            match node.into_inner() {
+                RootExpr::Expr(expr) => symbols.expr(expr, scope),
+                RootExpr::FunctionDefinition(function) => {
+                    let (span, function) = function.take();
+
+                    symbols.define(SymbolKind::Function, &function.ident, scope);
+                    for parameter in &function.parameters {
+                        symbols.define(SymbolKind::Variable, parameter, span);
+                    }
+                    symbols.block(function.block, span);
+                }
+                RootExpr::Import(_) | RootExpr::Error(_) => {}
+            }
+        }
+
+        symbols
+    }
+
+    /// Find the definition of the symbol at the given offset.
+    ///
+    /// Offsets on a definition itself resolve to the first definition of the
+    /// same symbol, as variables are defined by their first assignment.
+    pub(super) fn definition_at(&self, offset: usize) -> Option<&Definition> {
+        let (kind, name, start) = self
+            .references
+            .iter()
+            .map(|reference| (reference.kind, &reference.name, reference.span))
+            .chain(
+                self.definitions
+                    .iter()
+                    .map(|definition| (definition.kind, &definition.name, definition.span)),
+            )
+            .find(|(_, _, span)| contains(*span, offset))
+            .map(|(kind, name, span)| (kind, name, span.start()))?;
+
+        // The innermost scope wins, so that closure variables shadow variables
+        // of the same name in the surrounding program.
+        self.definitions
+            .iter()
+            .filter(|definition| definition.kind == kind && &definition.name == name)
+            .filter(|definition| contains(definition.scope, start))
+            .min_by_key(|definition| {
+                (
+                    definition.scope.end() - definition.scope.start(),
+                    definition.span.start(),
+                )
+            })
+    }
+
+    /// The names of all variables visible at the given offset.
+    pub(super) fn variables_at(&self, offset: usize) -> Vec<&str> {
+        let mut names = self
+            .definitions
+            .iter()
+            .filter(|definition| definition.kind == SymbolKind::Variable)
+            .filter(|definition| contains(definition.scope, offset))
+            .map(|definition| definition.name.as_str())
+            .collect::<Vec<_>>();
+
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    fn define(&mut self, kind: SymbolKind, ident: &Node<impl AsRef<str>>, scope: Span) {
+        let name = ident.inner().as_ref().to_owned();
+        let start = ident.start();
+
+        self.definitions.push(Definition {
+            kind,
+            span: Span::new(start, start + name.len()),
+            name,
+            scope,
+        });
+    }
+
+    fn reference(&mut self, kind: SymbolKind, name: impl Into<String>, span: Span) {
+        let name = name.into();
+        let start = span.start();
+
+        self.references.push(Reference {
+            kind,
+            span: Span::new(start, start + name.len()),
+            name,
+        });
+    }
+
+    fn block(&mut self, block: Node<Block>, scope: Span) {
+        for expr in block.into_inner() {
+            self.expr(expr, scope);
+        }
+    }
+
+    fn expr(&mut self, node: Node<Expr>, scope: Span) {
+        let (span, expr) = node.take();
+
+        match expr {
+            Expr::Literal(literal) => {
+                if let Literal::String(template) = literal.into_inner() {
+                    for segment in template.0 {
+                        if let StringSegment::Template(name, span) = segment {
+                            self.reference(SymbolKind::Variable, name.trim(), span);
+                        }
+                    }
+                }
+            }
+            Expr::Container(container) => self.container(container.into_inner(), scope),
+            Expr::IfStatement(statement) => {
+                let statement = statement.into_inner();
+
+                self.predicate(statement.predicate.into_inner(), scope);
+                self.block(statement.consequent, scope);
+                if let Some(alternative) = statement.alternative {
+                    self.block(alternative, scope);
+                }
+            }
+            Expr::Op(op) => {
+                let op = op.into_inner();
+
+                self.expr(*op.0, scope);
+                self.expr(*op.2, scope);
+            }
+            Expr::Assignment(assignment) => match assignment.into_inner() {
+                Assignment::Single { target, expr, .. } => {
+                    self.assignment_target(target, scope);
+                    self.expr(*expr, scope);
+                }
+                Assignment::Infallible { ok, err, expr, .. } => {
+                    self.assignment_target(ok, scope);
+                    self.assignment_target(err, scope);
+                    self.expr(*expr, scope);
+                }
+            },
+            Expr::Query(query) => {
+                let (target_span, target) = query.into_inner().target.take();
+
+                match target {
+                    QueryTarget::Internal(ident) => {
+                        self.reference(SymbolKind::Variable, ident.into_inner(), target_span);
+                    }
+                    QueryTarget::FunctionCall(call) => self.function_call(call, scope),
+                    QueryTarget::Container(container) => self.container(container, scope),
+                    QueryTarget::External => {}
+                }
+            }
+            Expr::FunctionCall(call) => self.function_call(call.into_inner(), scope),
+            Expr::Variable(ident) => {
+                let (span, ident) = ident.take();
+                self.reference(SymbolKind::Variable, ident.into_inner(), span);
+            }
+            Expr::Unary(unary) => match unary.into_inner() {
+                Unary::Not(not) => self.expr(*not.into_inner().take().1, scope),
+            },
+            Expr::Abort(abort) => {
+                if let Some(message) = abort.into_inner().message {
+                    self.expr(*message, scope);
+                }
+            }
+            Expr::Loop(node) => match node.into_inner() {
+                Loop::For(for_loop) | Loop::Walk(for_loop) => {
+                    self.expr(*for_loop.expr, scope);
+
+                    // Loop variables are only visible within the loop.
+                    if let Some(key) = &for_loop.key {
+                        self.define(SymbolKind::Variable, key, span);
+                    }
+                    self.define(SymbolKind::Variable, &for_loop.value, span);
+                    self.block(for_loop.block, span);
+                }
+                Loop::While(while_loop) => {
+                    self.predicate(while_loop.predicate.into_inner(), scope);
+                    self.block(while_loop.block, scope);
+                }
+            },
+            Expr::LoopControl(_) => {}
+        }
+    }
+
+    fn container(&mut self, container: Container, scope: Span) {
+        match container {
+            Container::Group(group) => self.expr((*group).into_inner().0, scope),
+            Container::Block(block) => self.block(block, scope),
+            Container::Array(array) => {
+                for expr in array.into_inner() {
+                    self.expr(expr, scope);
+                }
+            }
+            Container::Object(object) => {
+                for (_, expr) in object.into_inner() {
+                    self.expr(expr, scope);
+                }
+            }
+        }
+    }
+
+    fn predicate(&mut self, predicate: Predicate, scope: Span) {
+        match predicate {
+            Predicate::One(expr) => self.expr(*expr, scope),
+            Predicate::Many(exprs) => {
+                for expr in exprs {
+                    self.expr(expr, scope);
+                }
+            }
+        }
+    }
+
+    fn assignment_target(&mut self, target: Node<AssignmentTarget>, scope: Span) {
+        let (span, target) = target.take();
+
+        if let AssignmentTarget::Internal(ident, _) = target {
+            self.define(SymbolKind::Variable, &Node::new(span, ident), scope);
+        }
+    }
+
+    fn function_call(&mut self, call: FunctionCall, scope: Span) {
+        let (span, ident) = call.ident.take();
+        self.reference(SymbolKind::Function, ident.into_inner(), span);
+
+        for argument in call.arguments {
+            self.expr(argument.into_inner().expr, scope);
+        }
+
+        // Closure variables are only visible within the closure.
+        if let Some(closure) = call.closure {
+            let (span, closure) = closure.take();
+
+            for variable in &closure.variables {
+                self.define(SymbolKind::Variable, variable, span);
+            }
+            self.block(closure.block, span);
+        }
+    }
+}
+
+fn contains(span: Span, offset: usize) -> bool {
+    span.start() <= offset && offset <= span.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(source: &str) -> Symbols {
+        let program = parser::parse(source).expect("program should parse");
+
+        Symbols::new(program, source.len())
+    }
+
+    #[test]
+    fn variable_definition() {
+        let source = "foo = 1\nfoo = 2\nbar = foo + 1";
+        let symbols = symbols(source);
+
+        // Both the reference and the redefinition resolve to the first assignment.
+        for offset in [source.rfind("foo").unwrap() + 1, 8] {
+            let definition = symbols.definition_at(offset).expect("definition");
+
+            assert_eq!(definition.kind, SymbolKind::Variable);
+            assert_eq!(definition.name, "foo");
+            assert_eq!(definition.span, Span::new(0, 3));
+        }
+    }
+
+    #[test]
+    fn closure_variable_shadows_program_variable() {
+        let source = "value = 1\nfor_each([1]) -> |_index, value| {\n  value\n}\nvalue";
+        let symbols = symbols(source);
+
+        let inner = symbols
+            .definition_at(source.find("  value").unwrap() + 2)
+            .expect("definition");
+        let closure_variable = source.find("value|").unwrap();
+        assert_eq!(
+            inner.span,
+            Span::new(closure_variable, closure_variable + 5)
+        );
+
+        let outer = symbols
+            .definition_at(source.rfind("value").unwrap())
+            .expect("definition");
+        assert_eq!(outer.span, Span::new(0, 5));
+
+        assert_eq!(symbols.variables_at(source.len()), vec!["value"]);
+        assert_eq!(
+            symbols.variables_at(source.find("  value").unwrap()),
+            vec!["_index", "value"]
+        );
+    }
+
+    #[test]
+    fn function_definition() {
+        let source = "fn double(value) {\n  value * 2\n}\ndouble(2)";
+        let symbols = symbols(source);
+
+        let function = symbols
+            .definition_at(source.rfind("double").unwrap())
+            .expect("definition");
+        assert_eq!(function.kind, SymbolKind::Function);
+        assert_eq!(function.span, Span::new(3, 9));
+
+        let parameter = symbols
+            .definition_at(source.find("value *").unwrap())
+            .expect("definition");
+        assert_eq!(parameter.kind, SymbolKind::Variable);
+        assert_eq!(parameter.span, Span::new(10, 15));
+    }
+
+    #[test]
+    fn no_definition() {
+        let symbols = symbols("foo = 1");
+
+        assert!(symbols.definition_at(6).is_none());
+    }
+}
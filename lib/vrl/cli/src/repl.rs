@@ -17,7 +17,7 @@ use rustyline::{
 use value::Secrets;
 use vector_common::TimeZone;
 use vector_vrl_functions::vrl_functions;
-use vrl::{diagnostic::Formatter, prelude::BTreeMap, state, Runtime, Target, VrlRuntime};
+use vrl::{diagnostic::Formatter, prelude::BTreeMap, state, Runtime, Target, Vm, VrlRuntime};
 
 // Create a list of all possible error values for potential docs lookup
 static ERRORS: Lazy<Vec<String>> = Lazy::new(|| {
@@ -183,6 +183,9 @@ fn execute(
         VrlRuntime::Ast => runtime
             .resolve(object, program, &timezone)
             .map_err(|err| err.to_string()),
+        VrlRuntime::Vm => runtime
+            .run_vm(&Vm::new(program), object, &timezone)
+            .map_err(|err| err.to_string()),
     }
 }
 
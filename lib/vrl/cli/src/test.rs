@@ -0,0 +1,356 @@
+//! Run VRL programs against fixtures, similar to the VRL test suite.
+//!
+//! Every `<name>.vrl` file found is a test. The program runs against the event
+//! in the sibling `<name>.input.json` file (or an empty object, if there is no
+//! such file), and the resulting event is compared with the event in the
+//! sibling `<name>.output.json` file.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ::value::Value;
+use ansi_term::Colour;
+use clap::Parser;
+use core::TargetValueRef;
+use value::Secrets;
+use vector_common::TimeZone;
+use vrl::{state, Function, Runtime, Source, VrlRuntime};
+
+use super::{
+    cmd::{build_vm, execute, parse_timezone, serde_to_vrl},
+    Error,
+};
+
+#[derive(Parser, Debug)]
+pub(crate) struct Opts {
+    /// The VRL programs to test, or the directories to search for `.vrl` files.
+    #[clap(name = "PATH", required = true, parse(from_os_str))]
+    paths: Vec<PathBuf>,
+
+    /// The timezone used to parse dates.
+    #[clap(short = 'z', long)]
+    timezone: Option<String>,
+
+    /// Should we use the VM to evaluate the VRL
+    #[clap(short, long = "runtime", default_value_t)]
+    runtime: VrlRuntime,
+
+    /// Don't print the difference between the expected and actual output of failed tests.
+    #[clap(long)]
+    no_diff: bool,
+}
+
+enum Outcome {
+    Passed,
+    Failed {
+        reason: &'static str,
+        details: Option<String>,
+    },
+    Invalid(String),
+}
+
+#[must_use]
+pub(crate) fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match run(opts) {
+        Ok(true) => exitcode::OK,
+        Ok(false) => exitcode::SOFTWARE,
+        Err(err) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", err);
+            }
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+/// Run all tests, returning whether all of them passed.
+#[allow(clippy::print_stdout)]
+fn run(opts: &Opts) -> Result<bool, Error> {
+    let timezone = parse_timezone(opts.timezone.as_deref())?;
+
+    let mut paths = vec![];
+    for path in &opts.paths {
+        discover(path, &mut paths)?;
+    }
+
+    if paths.is_empty() {
+        return Err(Error::Parse("no VRL programs found".to_owned()));
+    }
+
+    let mut functions = stdlib::all();
+    functions.extend(vector_vrl_functions::vrl_functions());
+
+    let mut failed_count = 0;
+
+    for path in &paths {
+        let name = path.display().to_string();
+        let dots = 60_usize.saturating_sub(name.len());
+
+        print!("  {}{}", name, Colour::Fixed(240).paint(".".repeat(dots)));
+
+        match run_test(path, &functions, timezone, opts) {
+            Outcome::Passed => println!("{}", Colour::Green.bold().paint("OK")),
+            Outcome::Failed { reason, details } => {
+                println!("{} ({})", Colour::Red.bold().paint("FAILED"), reason);
+                failed_count += 1;
+
+                if let Some(details) = details {
+                    println!("{}", details);
+                }
+            }
+            Outcome::Invalid(err) => {
+                println!("{}", Colour::Purple.bold().paint("INVALID"));
+                println!("{}", Colour::Red.paint(err));
+                failed_count += 1;
+            }
+        }
+    }
+
+    println!();
+
+    if failed_count > 0 {
+        println!(
+            "  Overall result: {}\n\n    Number failed: {} of {}\n",
+            Colour::Red.bold().paint("FAILED"),
+            failed_count,
+            paths.len(),
+        );
+    } else {
+        println!(
+            "  Overall result: {}\n",
+            Colour::Green.bold().paint("SUCCESS")
+        );
+    }
+
+    Ok(failed_count == 0)
+}
+
+/// Collect the `.vrl` files at the given path, recursing into directories.
+///
+/// Files that are passed explicitly are always collected, regardless of their
+/// extension.
+pub(crate) fn discover(path: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    if !path.is_dir() {
+        paths.push(path.to_owned());
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() || entry.extension().map_or(false, |ext| ext == "vrl") {
+            discover(&entry, paths)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_test(
+    path: &Path,
+    functions: &[Box<dyn Function>],
+    timezone: TimeZone,
+    opts: &Opts,
+) -> Outcome {
+    let mut source = match fs::read_to_string(path) {
+        Ok(source) => Source::new(source),
+        Err(err) => return Outcome::Invalid(format!("unable to read program: {}", err)),
+    };
+
+    // Imports are resolved relative to the program file.
+    if let Some(dir) = path.parent() {
+        source = source.with_base_dir(dir);
+    }
+
+    let mut object = match read_fixture(&path.with_extension("input.json")) {
+        Ok(Some(input)) => serde_to_vrl(input),
+        Ok(None) => Value::Object(BTreeMap::new()),
+        Err(err) => return Outcome::Invalid(format!("invalid input: {}", err)),
+    };
+
+    let want = match read_fixture(&path.with_extension("output.json")) {
+        Ok(Some(want)) => want,
+        Ok(None) => {
+            return Outcome::Invalid(format!(
+                "missing expected output: {}",
+                path.with_extension("output.json").display()
+            ))
+        }
+        Err(err) => return Outcome::Invalid(format!("invalid expected output: {}", err)),
+    };
+
+    let mut state = state::ExternalEnv::default();
+    let program = match source.compile(functions, &mut state) {
+        Ok((program, _)) => program,
+        Err(diagnostics) => {
+            return Outcome::Failed {
+                reason: "compilation",
+                details: Some(source.formatter(diagnostics).colored().to_string()),
+            }
+        }
+    };
+
+    let mut metadata = Value::Object(BTreeMap::new());
+    let mut secrets = Secrets::new();
+    let mut target = TargetValueRef {
+        value: &mut object,
+        metadata: &mut metadata,
+        secrets: &mut secrets,
+    };
+    let mut runtime = Runtime::new(state::Runtime::default());
+
+    let vm = build_vm(&program, opts.runtime);
+    if let Err(err) = execute(&mut target, &program, vm.as_ref(), timezone, &mut runtime) {
+        return Outcome::Failed {
+            reason: "runtime",
+            details: Some(err.to_string()),
+        };
+    }
+
+    let got = object.into_json_lossy();
+
+    if got == want {
+        return Outcome::Passed;
+    }
+
+    let details = (!opts.no_diff).then(|| {
+        let want = serde_json::to_string_pretty(&want).expect("valid json");
+        let got = serde_json::to_string_pretty(&got).expect("valid json");
+
+        prettydiff::diff_lines(&want, &got).to_string()
+    });
+
+    Outcome::Failed {
+        reason: "expectation",
+        details,
+    }
+}
+
+/// Read a JSON fixture, returning `None` if the fixture doesn't exist.
+fn read_fixture(path: &Path) -> Result<Option<serde_json::Value>, Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let fixture = fs::read_to_string(path)?;
+
+    Ok(Some(serde_json::from_str(&fixture)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/test")
+    }
+
+    fn run_fixture(name: &str) -> Outcome {
+        let opts = Opts {
+            paths: vec![],
+            timezone: None,
+            runtime: VrlRuntime::default(),
+            no_diff: false,
+        };
+
+        run_test(
+            &fixtures_dir().join(name),
+            &stdlib::all(),
+            TimeZone::default(),
+            &opts,
+        )
+    }
+
+    #[test]
+    fn discover_programs() {
+        let dir = fixtures_dir();
+        let mut paths = vec![];
+        discover(&dir, &mut paths).unwrap();
+
+        let paths = paths
+            .iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            vec![
+                "failed_compilation.vrl",
+                "failed_expectation.vrl",
+                "failed_runtime.vrl",
+                "invalid.vrl",
+                "invalid_input.vrl",
+                "nested/passed.vrl",
+                "passed.vrl",
+            ]
+        );
+    }
+
+    #[test]
+    fn discover_explicit_file() {
+        let path = fixtures_dir().join("nested/README.md");
+        let mut paths = vec![];
+        discover(&path, &mut paths).unwrap();
+
+        assert_eq!(paths, vec![path]);
+    }
+
+    #[test]
+    fn passed() {
+        assert!(matches!(run_fixture("passed.vrl"), Outcome::Passed));
+        assert!(matches!(run_fixture("nested/passed.vrl"), Outcome::Passed));
+    }
+
+    #[test]
+    fn failed_expectation() {
+        match run_fixture("failed_expectation.vrl") {
+            Outcome::Failed { reason, details } => {
+                assert_eq!(reason, "expectation");
+                assert!(details.unwrap().contains("foo"));
+            }
+            _ => panic!("expected a failed test"),
+        }
+    }
+
+    #[test]
+    fn failed_compilation() {
+        assert!(matches!(
+            run_fixture("failed_compilation.vrl"),
+            Outcome::Failed {
+                reason: "compilation",
+                details: Some(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn failed_runtime() {
+        assert!(matches!(
+            run_fixture("failed_runtime.vrl"),
+            Outcome::Failed {
+                reason: "runtime",
+                details: Some(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        match run_fixture("invalid.vrl") {
+            Outcome::Invalid(err) => assert!(err.starts_with("missing expected output")),
+            _ => panic!("expected an invalid test"),
+        }
+
+        match run_fixture("invalid_input.vrl") {
+            Outcome::Invalid(err) => assert!(err.starts_with("invalid input")),
+            _ => panic!("expected an invalid test"),
+        }
+    }
+}
@@ -1,17 +1,30 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use diagnostic::{DiagnosticList, DiagnosticMessage, Severity, Span};
 use lookup::LookupBuf;
-use parser::ast::{self, Node, QueryTarget};
+use parser::ast::{self, Ident, Node, QueryTarget};
 
 use crate::{
     expression::{
-        assignment, function_call, literal, predicate, query, Abort, Array, Assignment, Block,
-        Container, Error, Expr, Expression, FunctionArgument, FunctionCall, Group, IfStatement,
-        Literal, Noop, Not, Object, Op, Predicate, Query, Target, Unary, Variable,
+        assignment, function_call, literal, predicate, query,
+        user_function_call::{self, UserFunction},
+        Abort, Array, Assignment, Block, Container, Error, Expr, Expression, FunctionArgument,
+        FunctionCall, Group, IfStatement, Literal, Noop, Not, Object, Op, Predicate, Query, Target,
+        Unary, UserFunctionCall, Variable,
     },
     parser::ast::RootExpr,
     program::ProgramInfo,
     state::{ExternalEnv, LocalEnv},
-    Function, Program,
+    type_def::Details,
+    Function, Program, TypeDef,
+};
+#[cfg(feature = "expr-loop")]
+use crate::{
+    expression::{loop_control, ForLoop, LoopControl, WhileLoop},
+    value::{Collection, Kind},
 };
 
 pub(crate) type Diagnostics = Vec<Box<dyn DiagnosticMessage>>;
@@ -38,6 +51,47 @@ pub(crate) struct Compiler<'a> {
     /// back to `None`, if the parent expression of a fallible expression
     /// nullifies the fallibility of that expression.
     fallible_expression_error: Option<Box<dyn DiagnosticMessage>>,
+
+    /// The user-defined functions of the program, keyed by their identifier.
+    functions: HashMap<Ident, Node<ast::FunctionDefinition>>,
+
+    /// The user-defined functions compiled so far, or `None` if the body of
+    /// the function failed to compile.
+    ///
+    /// Each function is compiled once, when it is first called, and shared by
+    /// all its call sites. Any function that isn't called is compiled once the
+    /// program is compiled, to surface errors in its body.
+    compiled_functions: HashMap<Ident, Option<Arc<UserFunction>>>,
+
+    /// The user-defined functions whose body is currently being compiled.
+    ///
+    /// Used to reject recursive calls, which would break the guarantee that a
+    /// program always terminates.
+    function_stack: Vec<Ident>,
+
+    /// The number of loops the expression currently being compiled is nested
+    /// in, within the current closure or function body.
+    loop_depth: usize,
+
+    /// The types at the start of an iteration of each loop compiled so far,
+    /// keyed by the span of the loop, within the current function body.
+    ///
+    /// A loop nested in another loop is compiled again on each pass over the
+    /// outer loop. Starting from the types found by the previous pass keeps
+    /// the number of passes linear in the nesting depth.
+    #[cfg(feature = "expr-loop")]
+    loop_states: HashMap<Span, LoopState>,
+
+    /// Whether the expression about to be compiled is a statement, whose value
+    /// is discarded.
+    ///
+    /// Loop control expressions (`break` and `continue`) are only allowed as
+    /// statements.
+    statement: bool,
+
+    /// Whether the block about to be compiled (including the blocks of an
+    /// if-statement) is a statement, making its expressions statements too.
+    statement_block: bool,
 }
 
 impl<'a> Compiler<'a> {
@@ -52,6 +106,14 @@ impl<'a> Compiler<'a> {
             external_assignments: vec![],
             skip_missing_query_target: vec![],
             fallible_expression_error: None,
+            functions: HashMap::new(),
+            compiled_functions: HashMap::new(),
+            function_stack: vec![],
+            loop_depth: 0,
+            #[cfg(feature = "expr-loop")]
+            loop_states: HashMap::new(),
+            statement: false,
+            statement_block: false,
         }
     }
 
@@ -69,7 +131,7 @@ impl<'a> Compiler<'a> {
         ast: parser::Program,
         external: &mut ExternalEnv,
     ) -> Result<(Program, DiagnosticList), DiagnosticList> {
-        let expressions = self.compile_root_exprs(ast, external);
+        let (expressions, spans) = self.compile_root_exprs(ast, external);
 
         let (errors, warnings): (Vec<_>, Vec<_>) =
             self.diagnostics.into_iter().partition(|diagnostic| {
@@ -87,7 +149,7 @@ impl<'a> Compiler<'a> {
             target_assignments: self.external_assignments,
         };
 
-        let expressions = Block::new(expressions, self.local);
+        let expressions = Block::new(expressions, self.local).with_spans(spans);
 
         Ok((Program { expressions, info }, warnings.into()))
     }
@@ -95,10 +157,12 @@ impl<'a> Compiler<'a> {
     fn compile_exprs(
         &mut self,
         nodes: impl IntoIterator<Item = Node<ast::Expr>>,
+        statement: bool,
         external: &mut ExternalEnv,
     ) -> Option<Vec<Expr>> {
         let mut exprs = vec![];
         for node in nodes {
+            self.statement = statement;
             let expr = self.compile_expr(node, external)?;
             let type_def = expr.type_def((&self.local, external));
             exprs.push(expr);
@@ -115,23 +179,35 @@ impl<'a> Compiler<'a> {
 
     fn compile_expr(&mut self, node: Node<ast::Expr>, external: &mut ExternalEnv) -> Option<Expr> {
         use ast::Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Op, Query, Unary,
-            Variable,
+            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Loop, LoopControl,
+            Op, Query, Unary, Variable,
         };
 
         let span = node.span();
+        let statement = std::mem::take(&mut self.statement);
 
         let expr = match node.into_inner() {
             Literal(node) => self.compile_literal(node, external),
-            Container(node) => self.compile_container(node, external).map(Into::into),
-            IfStatement(node) => self.compile_if_statement(node, external).map(Into::into),
+            Container(node) => {
+                self.statement_block = statement;
+                self.compile_container(node, external).map(Into::into)
+            }
+            IfStatement(node) => {
+                self.statement_block = statement;
+                self.compile_if_statement(node, external).map(Into::into)
+            }
             Op(node) => self.compile_op(node, external).map(Into::into),
             Assignment(node) => self.compile_assignment(node, external).map(Into::into),
             Query(node) => self.compile_query(node, external).map(Into::into),
+            FunctionCall(node) if self.is_user_function(&node) => {
+                self.compile_user_function_call(node, external)
+            }
             FunctionCall(node) => self.compile_function_call(node, external).map(Into::into),
             Variable(node) => self.compile_variable(node, external).map(Into::into),
             Unary(node) => self.compile_unary(node, external).map(Into::into),
             Abort(node) => self.compile_abort(node, external).map(Into::into),
+            Loop(node) => self.compile_loop(node, external),
+            LoopControl(node) => self.compile_loop_control(node, statement).map(Into::into),
         }?;
 
         // If the previously compiled expression is fallible, _and_ we are
@@ -204,9 +280,14 @@ impl<'a> Compiler<'a> {
     ) -> Option<Container> {
         use ast::Container::{Array, Block, Group, Object};
 
+        let statement = std::mem::take(&mut self.statement_block);
+
         let variant = match node.into_inner() {
             Group(node) => self.compile_group(*node, external)?.into(),
-            Block(node) => self.compile_block(node, external)?.into(),
+            Block(node) => {
+                self.statement_block = statement;
+                self.compile_block(node, external)?.into()
+            }
             Array(node) => self.compile_array(node, external)?.into(),
             Object(node) => self.compile_object(node, external)?.into(),
         };
@@ -228,8 +309,31 @@ impl<'a> Compiler<'a> {
         &mut self,
         nodes: impl IntoIterator<Item = Node<ast::RootExpr>>,
         external: &mut ExternalEnv,
-    ) -> Vec<Expr> {
+    ) -> (Vec<Expr>, Vec<Span>) {
         let mut node_exprs = vec![];
+        let mut spans = vec![];
+
+        // Function definitions are registered before any other expression is
+        // compiled, so that functions can be called before they are defined.
+        let nodes = nodes
+            .into_iter()
+            .filter_map(|node| {
+                let span = node.span();
+
+                match node.into_inner() {
+                    RootExpr::FunctionDefinition(node) => {
+                        self.compile_function_definition(node);
+                        None
+                    }
+                    RootExpr::Import(node) => {
+                        self.diagnostics
+                            .push(Box::new(Error::UnresolvedImport { span: node.span() }));
+                        None
+                    }
+                    root_expr => Some(Node::new(span, root_expr)),
+                }
+            })
+            .collect::<Vec<_>>();
 
         // After a terminating expression, the state is stored, but the remaining expressions are checked.
         let mut terminated_state = None;
@@ -237,6 +341,7 @@ impl<'a> Compiler<'a> {
         for root_expr in nodes {
             match root_expr.into_inner() {
                 RootExpr::Expr(node_expr) => {
+                    let span = node_expr.span();
                     self.fallible_expression_error = None;
 
                     if let Some(expr) = self.compile_expr(node_expr, external) {
@@ -247,6 +352,7 @@ impl<'a> Compiler<'a> {
                         if terminated_state.is_none() {
                             let type_def = expr.type_def((&self.local, external));
                             node_exprs.push(expr);
+                            spans.push(span);
                             // an expression that has the "never" type is a terminating expression
                             if type_def.is_never() {
                                 terminated_state =
@@ -256,9 +362,14 @@ impl<'a> Compiler<'a> {
                     }
                 }
                 RootExpr::Error(err) => self.handle_parser_error(err),
+                RootExpr::FunctionDefinition(..) | RootExpr::Import(..) => {
+                    unreachable!("handled above")
+                }
             }
         }
 
+        self.compile_uncalled_functions(external);
+
         if let Some((local, details)) = terminated_state {
             self.local = local;
             external.update_target(details);
@@ -266,8 +377,10 @@ impl<'a> Compiler<'a> {
 
         if node_exprs.is_empty() {
             node_exprs.push(Expr::Noop(Noop));
+            spans.push(Span::default());
         }
-        node_exprs
+
+        (node_exprs, spans)
     }
 
     fn compile_block(
@@ -280,11 +393,14 @@ impl<'a> Compiler<'a> {
         // scoped to the block, and must not be visible to the rest of the
         // program.
         let local_snapshot = self.local.clone();
+        let statement = std::mem::take(&mut self.statement_block);
 
         // We can now start compiling the expressions within the block, which
         // will use the existing local state of the compiler, as blocks have
         // access to any state of their parent expressions.
-        let exprs = match self.compile_exprs(node.into_inner().into_iter(), external) {
+        let nodes = node.into_inner().into_iter().collect::<Vec<_>>();
+        let mut spans = nodes.iter().map(Node::span).collect::<Vec<_>>();
+        let exprs = match self.compile_exprs(nodes, statement, external) {
             Some(exprs) => exprs,
             None => {
                 self.local = local_snapshot.apply_child_scope(self.local.clone());
@@ -292,10 +408,13 @@ impl<'a> Compiler<'a> {
             }
         };
 
+        // Expressions after a terminating expression aren't compiled.
+        spans.truncate(exprs.len());
+
         // Now that we've compiled the expressions, we pass them into the block,
         // and also a copy of the local state, which includes any state added by
         // the compiled expressions in the block.
-        let block = Block::new(exprs, self.local.clone());
+        let block = Block::new(exprs, self.local.clone()).with_spans(spans);
 
         // Take the local state snapshot captured before we started compiling
         // the block, and merge back into it any mutations that happened to
@@ -311,7 +430,7 @@ impl<'a> Compiler<'a> {
         node: Node<ast::Array>,
         external: &mut ExternalEnv,
     ) -> Option<Array> {
-        let exprs = self.compile_exprs(node.into_inner().into_iter(), external)?;
+        let exprs = self.compile_exprs(node.into_inner().into_iter(), false, external)?;
 
         Some(Array::new(exprs))
     }
@@ -348,6 +467,8 @@ impl<'a> Compiler<'a> {
             alternative,
         } = node.into_inner();
 
+        let statement = std::mem::take(&mut self.statement_block);
+
         let predicate = self
             .compile_predicate(predicate, external)?
             .map_err(|err| self.diagnostics.push(Box::new(err)))
@@ -356,6 +477,7 @@ impl<'a> Compiler<'a> {
         let original_locals = self.local.clone();
         let original_external = external.target().clone();
 
+        self.statement_block = statement;
         let consequent = self.compile_block(consequent, external)?;
 
         match alternative {
@@ -366,6 +488,7 @@ impl<'a> Compiler<'a> {
                 self.local = original_locals;
                 external.update_target(original_external);
 
+                self.statement_block = statement;
                 let else_block = self.compile_block(block, external)?;
 
                 // assignments must be the result of either the if or else block, but not the original value
@@ -414,7 +537,7 @@ impl<'a> Compiler<'a> {
 
         let exprs = match predicate {
             One(node) => vec![self.compile_expr(*node, external)?],
-            Many(nodes) => self.compile_exprs(nodes, external)?,
+            Many(nodes) => self.compile_exprs(nodes, false, external)?,
         };
 
         Some(Predicate::new(
@@ -644,6 +767,12 @@ impl<'a> Compiler<'a> {
                 let container = self.compile_container(Node::new(span, container), external)?;
                 Target::Container(container)
             }
+            FunctionCall(call) if self.functions.contains_key(call.ident.inner()) => {
+                // A user-defined function is queried as a group expression, as
+                // the function-call target only supports built-in functions.
+                let expr = self.compile_user_function_call(Node::new(span, call), external)?;
+                Target::Container(Container::new(Group::new(expr).into()))
+            }
             FunctionCall(call) => {
                 let call = self.compile_function_call(Node::new(span, call), external)?;
                 Target::FunctionCall(call)
@@ -724,7 +853,14 @@ impl<'a> Compiler<'a> {
                 None => None,
                 Some(block) => {
                     let span = block.span();
-                    match self.compile_block(block, external) {
+
+                    // Loop control expressions can't cross the closure
+                    // boundary, as the closure is called by the function.
+                    let loop_depth = std::mem::take(&mut self.loop_depth);
+                    let block = self.compile_block(block, external);
+                    self.loop_depth = loop_depth;
+
+                    match block {
                         Some(block) => Some(Node::new(span, block)),
                         None => return None,
                     }
@@ -744,6 +880,253 @@ impl<'a> Compiler<'a> {
         })
     }
 
+    #[cfg(feature = "expr-function_call")]
+    fn compile_function_definition(&mut self, node: Node<ast::FunctionDefinition>) {
+        use user_function_call::Error;
+
+        let ident_span = node.ident.span();
+        let ident = node.ident.inner().clone();
+
+        let error = if let Some(previous) = self.functions.get(&ident) {
+            Some(Error::DuplicateDefinition {
+                ident_span,
+                previous_span: previous.ident.span(),
+            })
+        } else if self.fns.iter().any(|f| f.identifier() == ident.as_ref()) {
+            Some(Error::ShadowsBuiltin { ident_span, ident })
+        } else {
+            let mut parameters = HashSet::new();
+
+            node.parameters
+                .iter()
+                .find(|parameter| !parameters.insert(parameter.inner()))
+                .map(|parameter| Error::DuplicateParameter {
+                    parameter_span: parameter.span(),
+                })
+        };
+
+        match error {
+            Some(error) => self.diagnostics.push(Box::new(error)),
+            None => {
+                self.functions.insert(node.ident.inner().clone(), node);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "expr-function_call"))]
+    fn compile_function_definition(&mut self, node: Node<ast::FunctionDefinition>) {
+        // Guard against `dead_code` lint, to avoid having to sprinkle
+        // attributes all over the place.
+        let _ = (
+            &self.functions,
+            &self.compiled_functions,
+            &self.function_stack,
+        );
+
+        self.handle_missing_feature_error(node.span(), "expr-function_call");
+    }
+
+    fn is_user_function(&self, node: &Node<ast::FunctionCall>) -> bool {
+        self.functions.contains_key(node.ident.inner())
+    }
+
+    #[cfg(feature = "expr-function_call")]
+    fn compile_user_function_call(
+        &mut self,
+        node: Node<ast::FunctionCall>,
+        external: &mut ExternalEnv,
+    ) -> Option<Expr> {
+        use user_function_call::{order_arguments, Error};
+
+        let (call_span, call) = node.take();
+        let ast::FunctionCall {
+            ident,
+            abort_on_error,
+            arguments,
+            closure,
+        } = call;
+
+        let ident = ident.into_inner();
+
+        if let Some(closure) = closure {
+            let error = function_call::Error::UnexpectedClosure {
+                call_span,
+                closure_span: closure.span(),
+            };
+
+            self.diagnostics.push(Box::new(error));
+            return None;
+        }
+
+        if self.function_stack.contains(&ident) {
+            self.diagnostics
+                .push(Box::new(Error::Recursion { call_span, ident }));
+            return None;
+        }
+
+        let function = self.compile_function(&ident, external);
+
+        let arguments = arguments
+            .into_iter()
+            .map(|node| {
+                let ast::FunctionArgument { ident, expr } = node.into_inner();
+                let expr_span = expr.span();
+                let expr = self.compile_expr(expr, external)?;
+
+                if expr.type_def((&self.local, external)).is_fallible() {
+                    let error = function_call::Error::FallibleArgument { expr_span };
+                    self.diagnostics.push(Box::new(error));
+                    return None;
+                }
+
+                Some((ident, expr))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // Any error in the function body has already been reported.
+        let function = function?;
+
+        let arguments = order_arguments(call_span, &ident, function.parameters(), arguments)
+            .map_err(|err| self.diagnostics.push(Box::new(err)))
+            .ok()?;
+
+        if abort_on_error {
+            self.fallible = true;
+        }
+
+        let call = UserFunctionCall::new(
+            call_span,
+            abort_on_error,
+            arguments,
+            Arc::clone(&function),
+            (&self.local, external),
+        )
+        .map_err(|err| self.diagnostics.push(Box::new(err)))
+        .ok()?;
+
+        // The arguments resolve before the body, so they see the target as it
+        // was before the call.
+        function.apply_target_mutations(external);
+
+        Some(call.into())
+    }
+
+    #[cfg(not(feature = "expr-function_call"))]
+    fn compile_user_function_call(
+        &mut self,
+        node: Node<ast::FunctionCall>,
+        _: &mut ExternalEnv,
+    ) -> Option<Expr> {
+        self.handle_missing_feature_error(node.span(), "expr-function_call")
+    }
+
+    /// Compile a user-defined function, unless it was compiled before.
+    ///
+    /// The body is compiled in a new local scope that only contains the
+    /// function parameters. As the function is shared by all its call sites,
+    /// its parameters and the target can be of any type. Each call site then
+    /// applies the mutations the body makes to the target.
+    #[cfg(feature = "expr-function_call")]
+    fn compile_function(
+        &mut self,
+        ident: &Ident,
+        external: &mut ExternalEnv,
+    ) -> Option<Arc<UserFunction>> {
+        if let Some(function) = self.compiled_functions.get(ident) {
+            return function.clone();
+        }
+
+        let ast::FunctionDefinition {
+            parameters, block, ..
+        } = self.functions.get(ident).cloned()?.into_inner();
+
+        let parameters = parameters
+            .into_iter()
+            .map(Node::into_inner)
+            .collect::<Vec<_>>();
+
+        let mut local = LocalEnv::default();
+        for parameter in &parameters {
+            let details = Details {
+                type_def: TypeDef::any().infallible(),
+                value: None,
+            };
+
+            local.insert_variable(parameter.clone(), details);
+        }
+
+        // Any fallibility of the function body is tracked by the function
+        // call itself, so we set aside the fallible expression state of the
+        // caller while compiling the body.
+        let caller_local = std::mem::replace(&mut self.local, local);
+        let caller_fallible_expression_error = self.fallible_expression_error.take();
+        let caller_loop_depth = std::mem::take(&mut self.loop_depth);
+        #[cfg(feature = "expr-loop")]
+        let caller_loop_states = std::mem::take(&mut self.loop_states);
+        let caller_target = external.target().clone();
+        let caller_target_mutations = external.start_target_mutations();
+        external.update_target(Details {
+            type_def: TypeDef::any(),
+            value: None,
+        });
+        self.function_stack.push(ident.clone());
+
+        let block = self.compile_block(block, external);
+
+        self.function_stack.pop();
+        let mutated_paths = external.end_target_mutations(caller_target_mutations);
+        let target = external.target().type_def.clone();
+        external.update_target(caller_target);
+        #[cfg(feature = "expr-loop")]
+        {
+            self.loop_states = caller_loop_states;
+        }
+        self.loop_depth = caller_loop_depth;
+        self.fallible_expression_error = caller_fallible_expression_error;
+        self.local = caller_local;
+
+        let function = block.map(|block| {
+            Arc::new(UserFunction::new(
+                ident.clone(),
+                parameters,
+                block,
+                target,
+                &mutated_paths,
+            ))
+        });
+
+        self.compiled_functions
+            .insert(ident.clone(), function.clone());
+
+        function
+    }
+
+    /// Compile any user-defined function that isn't called by the program, to
+    /// surface any errors it contains.
+    ///
+    /// As these functions have no call site, the changes they make to the type
+    /// of the external target are never applied.
+    #[cfg(feature = "expr-function_call")]
+    fn compile_uncalled_functions(&mut self, external: &mut ExternalEnv) {
+        let mut uncalled = self
+            .functions
+            .iter()
+            .filter(|(ident, _)| !self.compiled_functions.contains_key(*ident))
+            .map(|(ident, definition)| (ident.clone(), definition.span()))
+            .collect::<Vec<_>>();
+
+        uncalled.sort_by_key(|(_, span)| *span);
+
+        // Functions called from the body of a function compiled in a previous
+        // iteration are only compiled once.
+        for (ident, _) in uncalled {
+            let _ = self.compile_function(&ident, external);
+        }
+    }
+
+    #[cfg(not(feature = "expr-function_call"))]
+    fn compile_uncalled_functions(&mut self, _: &mut ExternalEnv) {}
+
     #[cfg(feature = "expr-function_call")]
     fn compile_function_argument(
         &mut self,
@@ -851,6 +1234,254 @@ impl<'a> Compiler<'a> {
         self.handle_missing_feature_error(node.span(), "expr-abort")
     }
 
+    #[cfg(feature = "expr-loop")]
+    fn compile_loop(&mut self, node: Node<ast::Loop>, external: &mut ExternalEnv) -> Option<Expr> {
+        let (span, node) = node.take();
+
+        match node {
+            ast::Loop::For(node) => self
+                .compile_for_loop(span, node, false, external)
+                .map(Into::into),
+            ast::Loop::Walk(node) => self
+                .compile_for_loop(span, node, true, external)
+                .map(Into::into),
+            ast::Loop::While(node) => self
+                .compile_while_loop(span, node, external)
+                .map(Into::into),
+        }
+    }
+
+    #[cfg(not(feature = "expr-loop"))]
+    fn compile_loop(&mut self, node: Node<ast::Loop>, _: &mut ExternalEnv) -> Option<Expr> {
+        // Guard against `dead_code` lint, to avoid having to sprinkle
+        // attributes all over the place.
+        let _ = self.loop_depth;
+
+        self.handle_missing_feature_error(node.span(), "expr-loop")
+    }
+
+    #[cfg(feature = "expr-loop")]
+    fn compile_for_loop(
+        &mut self,
+        span: Span,
+        node: ast::ForLoop,
+        recursive: bool,
+        external: &mut ExternalEnv,
+    ) -> Option<ForLoop> {
+        let ast::ForLoop {
+            key,
+            value,
+            expr,
+            block,
+        } = node;
+
+        // The collection is resolved once, before the loop starts, so it
+        // isn't part of the loop body.
+        let expr_span = expr.span();
+        let expr = self.compile_expr(*expr, external)?;
+        let type_def = expr.type_def((&self.local, external));
+
+        let (key_type_def, value_type_def) =
+            ForLoop::variable_type_defs(recursive, expr_span, &type_def)
+                .map_err(|err| self.diagnostics.push(Box::new(err)))
+                .ok()?;
+
+        // Variables named `_` aren't bound.
+        let key = key.map(Node::into_inner).filter(|ident| !ident.is_empty());
+        let value = Some(value.into_inner()).filter(|ident| !ident.is_empty());
+
+        let variables = key
+            .iter()
+            .map(|ident| (ident.clone(), key_type_def.clone()))
+            .chain(
+                value
+                    .iter()
+                    .map(|ident| (ident.clone(), value_type_def.clone())),
+            )
+            .collect::<Vec<_>>();
+
+        let block = self.compile_loop_body(span, external, |compiler, external| {
+            // The loop variables are scoped to the loop body, shadowing any
+            // existing variables of the same name.
+            let local_snapshot = compiler.local.clone();
+
+            for (ident, type_def) in &variables {
+                let details = Details {
+                    type_def: type_def.clone(),
+                    value: None,
+                };
+
+                compiler.local.insert_variable(ident.clone(), details);
+            }
+
+            compiler.statement_block = true;
+            let block = compiler.compile_block(block.clone(), external);
+
+            for (ident, _) in &variables {
+                match local_snapshot.variable(ident) {
+                    Some(details) => compiler
+                        .local
+                        .insert_variable(ident.clone(), details.clone()),
+                    None => {
+                        compiler.local.remove_variable(ident);
+                    }
+                }
+            }
+
+            block
+        })?;
+
+        Some(ForLoop::new(recursive, key, value, expr, block))
+    }
+
+    #[cfg(feature = "expr-loop")]
+    fn compile_while_loop(
+        &mut self,
+        span: Span,
+        node: ast::WhileLoop,
+        external: &mut ExternalEnv,
+    ) -> Option<WhileLoop> {
+        let ast::WhileLoop { predicate, block } = node;
+
+        self.compile_loop_body(span, external, |compiler, external| {
+            // The predicate is resolved before each iteration, so it's part of
+            // the loop body.
+            let predicate = compiler
+                .compile_predicate(predicate.clone(), external)?
+                .map_err(|err| compiler.diagnostics.push(Box::new(err)))
+                .ok()?;
+
+            compiler.statement_block = true;
+            let block = compiler.compile_block(block.clone(), external)?;
+
+            Some(WhileLoop::new(span, predicate, block))
+        })
+    }
+
+    /// Compile the body of a loop, using the given closure.
+    ///
+    /// As the body can run any number of times, the types of the variables
+    /// and the external target at the start of an iteration are the union of
+    /// their types before the loop, and at the end of any previous iteration.
+    /// The body is compiled again until these types reach a fixpoint. Once the
+    /// loop took the maximum number of passes, any type still changing is
+    /// widened to `any`, and the next pass is the last.
+    ///
+    /// If the loop was compiled before, as part of a previous pass over an
+    /// enclosing loop, the types start from the fixpoint found back then, and
+    /// the passes taken so far count towards the maximum.
+    ///
+    /// Only the diagnostics of the last pass are kept.
+    #[cfg(feature = "expr-loop")]
+    fn compile_loop_body<T>(
+        &mut self,
+        span: Span,
+        external: &mut ExternalEnv,
+        mut compile: impl FnMut(&mut Self, &mut ExternalEnv) -> Option<T>,
+    ) -> Option<T> {
+        const MAX_PASSES: usize = 3;
+
+        let diagnostics = self.diagnostics.len();
+        let fallible_expression_error = self.fallible_expression_error.take();
+        let mut local = self.local.clone();
+        let mut target = external.target().clone();
+        let mut passes = 0;
+        let mut widened = false;
+
+        if let Some(state) = self.loop_states.remove(&span) {
+            local = local.merge(state.local);
+            target = target.merge(state.target);
+            passes = state.passes;
+        }
+
+        self.loop_depth += 1;
+
+        let result = loop {
+            passes += 1;
+
+            self.local = local.clone();
+            external.update_target(target.clone());
+            self.diagnostics.truncate(diagnostics);
+            self.fallible_expression_error = None;
+
+            let result = compile(self, external);
+
+            let next_local = local.clone().merge(self.local.clone());
+            let next_target = target.clone().merge(external.target().clone());
+
+            let stable = next_local == local && next_target == target;
+            if result.is_none() || stable || widened {
+                local = next_local;
+                target = next_target;
+                break result;
+            }
+
+            if passes >= MAX_PASSES {
+                widened = true;
+                local = widen_local(&local, next_local);
+                target = next_target.merge(Details {
+                    type_def: Kind::object(Collection::any()).into(),
+                    value: None,
+                });
+            } else {
+                local = next_local;
+                target = next_target;
+            }
+        };
+
+        self.loop_depth -= 1;
+        self.loop_states.insert(
+            span,
+            LoopState {
+                local: local.clone(),
+                target: target.clone(),
+                passes,
+            },
+        );
+        self.local = local;
+        external.update_target(target);
+
+        // Any fallible expression before the loop takes precedence over those
+        // within its body.
+        if fallible_expression_error.is_some() {
+            self.fallible_expression_error = fallible_expression_error;
+        }
+
+        result
+    }
+
+    #[cfg(feature = "expr-loop")]
+    fn compile_loop_control(
+        &mut self,
+        node: Node<ast::LoopControl>,
+        statement: bool,
+    ) -> Option<LoopControl> {
+        use loop_control::Error;
+
+        let (span, control) = node.take();
+
+        let error = if self.loop_depth == 0 {
+            Some(Error::OutsideLoop { control, span })
+        } else if !statement {
+            Some(Error::NotStatement { control, span })
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => {
+                self.diagnostics.push(Box::new(error));
+                None
+            }
+            None => Some(LoopControl::new(control)),
+        }
+    }
+
+    #[cfg(not(feature = "expr-loop"))]
+    fn compile_loop_control(&mut self, node: Node<ast::LoopControl>, _: bool) -> Option<Expr> {
+        self.handle_missing_feature_error(node.span(), "expr-loop")
+    }
+
     fn handle_parser_error(&mut self, error: parser::Error) {
         self.diagnostics.push(Box::new(error));
     }
@@ -882,3 +1513,28 @@ impl<'a> Compiler<'a> {
         self.skip_missing_query_target.push(query);
     }
 }
+
+/// The types at the start of an iteration of a loop, once they reached a
+/// fixpoint, and the number of passes it took to compile the loop so far.
+#[cfg(feature = "expr-loop")]
+struct LoopState {
+    local: LocalEnv,
+    target: Details,
+    passes: usize,
+}
+
+/// Widen the type of any variable that changed between two passes over a loop
+/// body to `any`, to guarantee the types of the loop body converge.
+#[cfg(feature = "expr-loop")]
+fn widen_local(previous: &LocalEnv, mut next: LocalEnv) -> LocalEnv {
+    for (ident, details) in &mut next.bindings {
+        if previous.variable(ident) != Some(&*details) {
+            *details = Details {
+                type_def: TypeDef::any(),
+                value: None,
+            };
+        }
+    }
+
+    next
+}
@@ -1,11 +1,19 @@
 use vector_common::TimeZone;
 
-use crate::{state::Runtime, Target};
+use crate::{
+    debug::{Branch, Debugger},
+    state::Runtime,
+    Span, Target,
+};
 
 pub struct Context<'a> {
     target: &'a mut dyn Target,
     state: &'a mut Runtime,
     timezone: &'a TimeZone,
+    debugger: Option<&'a mut dyn Debugger>,
+
+    /// The number of blocks being resolved, only tracked while debugging.
+    depth: usize,
 }
 
 impl<'a> Context<'a> {
@@ -15,9 +23,18 @@ impl<'a> Context<'a> {
             target,
             state,
             timezone,
+            debugger: None,
+            depth: 0,
         }
     }
 
+    /// Attach a [`Debugger`], to be called while the program is resolved.
+    #[must_use]
+    pub fn with_debugger(mut self, debugger: &'a mut dyn Debugger) -> Self {
+        self.debugger = Some(debugger);
+        self
+    }
+
     /// Get a reference to the [`Target`].
     #[must_use]
     pub fn target(&self) -> &dyn Target {
@@ -45,4 +62,32 @@ impl<'a> Context<'a> {
     pub fn timezone(&self) -> &TimeZone {
         self.timezone
     }
+
+    pub(crate) fn is_debugging(&self) -> bool {
+        self.debugger.is_some()
+    }
+
+    pub(crate) fn enter_block(&mut self) {
+        self.depth += 1;
+    }
+
+    pub(crate) fn exit_block(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub(crate) fn debug_statement(&mut self, span: Span) {
+        // The debugger is set aside while it's called, so that it can be
+        // handed the rest of the context.
+        if let Some(debugger) = self.debugger.take() {
+            debugger.statement(span, self.depth, self);
+            self.debugger = Some(debugger);
+        }
+    }
+
+    pub(crate) fn debug_branch(&mut self, branch: Branch) {
+        if let Some(debugger) = self.debugger.take() {
+            debugger.branch(branch, self.depth, self);
+            self.debugger = Some(debugger);
+        }
+    }
 }
@@ -0,0 +1,157 @@
+//! Step through the resolution of a program.
+//!
+//! A [`Debugger`] attached to the [`Context`] is called before each statement
+//! of the program is resolved, and whenever an `if` statement picks a branch.
+//! As it's called synchronously, it can pause the program to inspect its
+//! state. Debugging is only supported by the AST runtime.
+
+use crate::{Context, Span};
+
+/// The branch of an `if` statement taken at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    /// The predicate is `true`, and the `if` block is resolved.
+    Consequent,
+
+    /// The predicate is `false`, and the `else` block is resolved.
+    Alternative,
+
+    /// The predicate is `false`, and there's no `else` block to resolve.
+    None,
+}
+
+/// Observes the resolution of a program, one statement at a time.
+pub trait Debugger {
+    /// Called before the statement at `span` is resolved.
+    ///
+    /// The `depth` is the number of blocks the statement is nested in,
+    /// starting at 1 for the statements at the root of the program. The body
+    /// of a user-defined function is nested in the block of its caller.
+    fn statement(&mut self, span: Span, depth: usize, ctx: &Context<'_>);
+
+    /// Called once the predicate of an `if` statement is resolved, with the
+    /// `depth` of the statement.
+    fn branch(&mut self, branch: Branch, depth: usize, ctx: &Context<'_>);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ::value::{Secrets, Value};
+    use vector_common::TimeZone;
+
+    use super::*;
+    use crate::{state::Runtime, TargetValue};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Statement(&'static str, usize),
+        Branch(Branch, usize),
+    }
+
+    /// Record the first line of each statement, and each branch taken.
+    struct Recorder {
+        source: &'static str,
+        events: Vec<Event>,
+    }
+
+    impl Debugger for Recorder {
+        fn statement(&mut self, span: Span, depth: usize, _: &Context<'_>) {
+            let text = self.source[span.start()..span.end()]
+                .lines()
+                .next()
+                .unwrap_or_default();
+
+            self.events.push(Event::Statement(text, depth));
+        }
+
+        fn branch(&mut self, branch: Branch, depth: usize, _: &Context<'_>) {
+            self.events.push(Event::Branch(branch, depth));
+        }
+    }
+
+    fn record(source: &'static str) -> Vec<Event> {
+        let ast = ::parser::parse(source).expect("valid program");
+        let (program, _) = crate::compile(ast, &[]).expect("program compiles");
+
+        let mut target = TargetValue {
+            value: Value::Object(BTreeMap::new()),
+            metadata: Value::Object(BTreeMap::new()),
+            secrets: Secrets::new(),
+        };
+        let mut state = Runtime::default();
+        let timezone = TimeZone::default();
+        let mut recorder = Recorder {
+            source,
+            events: vec![],
+        };
+
+        let mut ctx = Context::new(&mut target, &mut state, &timezone).with_debugger(&mut recorder);
+        program.resolve(&mut ctx).expect("program resolves");
+
+        recorder.events
+    }
+
+    #[test]
+    fn statements_and_branches() {
+        let events = record(indoc::indoc! {"
+            x = 1
+            if x == 1 {
+              .a = x
+            } else {
+              .b = x
+            }
+            if x == 2 { .c = x } else { .c = 0 }
+            if false { .d = x }
+            .e = x
+        "});
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Statement("x = 1", 1),
+                Event::Statement("if x == 1 {", 1),
+                Event::Branch(Branch::Consequent, 1),
+                Event::Statement(".a = x", 2),
+                Event::Statement("if x == 2 { .c = x } else { .c = 0 }", 1),
+                Event::Branch(Branch::Alternative, 1),
+                Event::Statement(".c = 0", 2),
+                Event::Statement("if false { .d = x }", 1),
+                Event::Branch(Branch::None, 1),
+                Event::Statement(".e = x", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let events = record(indoc::indoc! {"
+            fn inc(v) {
+              v + 1
+            }
+            for _, v in [1, 2] {
+              if true {
+                .a = inc(v)
+              }
+            }
+            .b = 1
+        "});
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Statement("for _, v in [1, 2] {", 1),
+                Event::Statement("if true {", 2),
+                Event::Branch(Branch::Consequent, 2),
+                Event::Statement(".a = inc(v)", 3),
+                Event::Statement("v + 1", 4),
+                Event::Statement("if true {", 2),
+                Event::Branch(Branch::Consequent, 2),
+                Event::Statement(".a = inc(v)", 3),
+                Event::Statement("v + 1", 4),
+                Event::Statement(".b = 1", 1),
+            ]
+        );
+    }
+}
@@ -6,6 +6,7 @@ use value::Value;
 
 use crate::{
     state::{ExternalEnv, LocalEnv},
+    vm::Vm,
     Context, Span, TypeDef,
 };
 
@@ -18,6 +19,8 @@ mod group;
 #[cfg(feature = "expr-if_statement")]
 mod if_statement;
 mod levenstein;
+#[cfg(feature = "expr-loop")]
+mod loop_control;
 mod noop;
 #[cfg(feature = "expr-unary")]
 mod not;
@@ -31,6 +34,8 @@ mod variable;
 #[cfg(feature = "expr-assignment")]
 pub(crate) mod assignment;
 pub(crate) mod container;
+#[cfg(feature = "expr-loop")]
+pub(crate) mod for_loop;
 #[cfg(feature = "expr-function_call")]
 pub(crate) mod function_call;
 #[cfg(feature = "expr-literal")]
@@ -39,6 +44,10 @@ pub(crate) mod literal;
 pub(crate) mod predicate;
 #[cfg(feature = "expr-query")]
 pub(crate) mod query;
+#[cfg(feature = "expr-function_call")]
+pub(crate) mod user_function_call;
+#[cfg(feature = "expr-loop")]
+pub(crate) mod while_loop;
 
 pub use core::{ExpressionError, Resolved};
 
@@ -49,6 +58,8 @@ pub use array::Array;
 pub use assignment::Assignment;
 pub use block::Block;
 pub use container::{Container, Variant};
+#[cfg(feature = "expr-loop")]
+pub use for_loop::ForLoop;
 pub use function_argument::FunctionArgument;
 #[cfg(feature = "expr-function_call")]
 pub use function_call::FunctionCall;
@@ -57,6 +68,8 @@ pub use group::Group;
 pub use if_statement::IfStatement;
 #[cfg(feature = "expr-literal")]
 pub use literal::Literal;
+#[cfg(feature = "expr-loop")]
+pub use loop_control::LoopControl;
 pub use noop::Noop;
 #[cfg(feature = "expr-unary")]
 pub use not::Not;
@@ -69,7 +82,11 @@ pub use predicate::Predicate;
 pub use query::{Query, Target};
 #[cfg(feature = "expr-unary")]
 pub use unary::Unary;
+#[cfg(feature = "expr-function_call")]
+pub use user_function_call::UserFunctionCall;
 pub use variable::Variable;
+#[cfg(feature = "expr-loop")]
+pub use while_loop::WhileLoop;
 
 pub trait Expression: Send + Sync + fmt::Debug + DynClone {
     /// Resolve an expression to a concrete [`Value`].
@@ -128,6 +145,14 @@ pub enum Expr {
     Query(Query),
     #[cfg(feature = "expr-function_call")]
     FunctionCall(FunctionCall),
+    #[cfg(feature = "expr-function_call")]
+    UserFunctionCall(UserFunctionCall),
+    #[cfg(feature = "expr-loop")]
+    ForLoop(ForLoop),
+    #[cfg(feature = "expr-loop")]
+    WhileLoop(WhileLoop),
+    #[cfg(feature = "expr-loop")]
+    LoopControl(LoopControl),
     Variable(Variable),
     Noop(Noop),
     #[cfg(feature = "expr-unary")]
@@ -140,8 +165,8 @@ impl Expr {
     pub fn as_str(&self) -> &str {
         use container::Variant::{Array, Block, Group, Object};
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Noop, Op, Query,
-            Unary, Variable,
+            Abort, Assignment, Container, ForLoop, FunctionCall, IfStatement, Literal, LoopControl,
+            Noop, Op, Query, Unary, UserFunctionCall, Variable, WhileLoop,
         };
 
         match self {
@@ -163,6 +188,14 @@ impl Expr {
             Query(..) => "query",
             #[cfg(feature = "expr-function_call")]
             FunctionCall(..) => "function call",
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(..) => "user-defined function call",
+            #[cfg(feature = "expr-loop")]
+            ForLoop(..) => "for-loop",
+            #[cfg(feature = "expr-loop")]
+            WhileLoop(..) => "while-loop",
+            #[cfg(feature = "expr-loop")]
+            LoopControl(..) => "loop control",
             Variable(..) => "variable call",
             Noop(..) => "noop",
             #[cfg(feature = "expr-unary")]
@@ -229,11 +262,51 @@ impl Expr {
     }
 }
 
+impl Expr {
+    /// Compile the expression to bytecode for the [`Vm`] runtime.
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        use Expr::{
+            Abort, Assignment, Container, ForLoop, FunctionCall, IfStatement, Literal, LoopControl,
+            Noop, Op, Query, Unary, UserFunctionCall, Variable, WhileLoop,
+        };
+
+        match self {
+            #[cfg(feature = "expr-literal")]
+            Literal(v) => v.compile_to_vm(vm),
+            Container(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-if_statement")]
+            IfStatement(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-op")]
+            Op(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-assignment")]
+            Assignment(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-query")]
+            Query(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-function_call")]
+            FunctionCall(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-loop")]
+            ForLoop(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-loop")]
+            WhileLoop(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-loop")]
+            LoopControl(v) => v.compile_to_vm(vm),
+            Variable(v) => v.compile_to_vm(vm),
+            Noop(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-unary")]
+            Unary(v) => v.compile_to_vm(vm),
+            #[cfg(feature = "expr-abort")]
+            Abort(v) => v.compile_to_vm(vm),
+        }
+    }
+}
+
 impl Expression for Expr {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Noop, Op, Query,
-            Unary, Variable,
+            Abort, Assignment, Container, ForLoop, FunctionCall, IfStatement, Literal, LoopControl,
+            Noop, Op, Query, Unary, UserFunctionCall, Variable, WhileLoop,
         };
 
         match self {
@@ -250,6 +323,14 @@ impl Expression for Expr {
             Query(v) => v.resolve(ctx),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.resolve(ctx),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.resolve(ctx),
+            #[cfg(feature = "expr-loop")]
+            ForLoop(v) => v.resolve(ctx),
+            #[cfg(feature = "expr-loop")]
+            WhileLoop(v) => v.resolve(ctx),
+            #[cfg(feature = "expr-loop")]
+            LoopControl(v) => v.resolve(ctx),
             Variable(v) => v.resolve(ctx),
             Noop(v) => v.resolve(ctx),
             #[cfg(feature = "expr-unary")]
@@ -261,8 +342,8 @@ impl Expression for Expr {
 
     fn as_value(&self) -> Option<Value> {
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Noop, Op, Query,
-            Unary, Variable,
+            Abort, Assignment, Container, ForLoop, FunctionCall, IfStatement, Literal, LoopControl,
+            Noop, Op, Query, Unary, UserFunctionCall, Variable, WhileLoop,
         };
 
         match self {
@@ -279,6 +360,14 @@ impl Expression for Expr {
             Query(v) => Expression::as_value(v),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => Expression::as_value(v),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => Expression::as_value(v),
+            #[cfg(feature = "expr-loop")]
+            ForLoop(v) => Expression::as_value(v),
+            #[cfg(feature = "expr-loop")]
+            WhileLoop(v) => Expression::as_value(v),
+            #[cfg(feature = "expr-loop")]
+            LoopControl(v) => Expression::as_value(v),
             Variable(v) => Expression::as_value(v),
             Noop(v) => Expression::as_value(v),
             #[cfg(feature = "expr-unary")]
@@ -290,8 +379,8 @@ impl Expression for Expr {
 
     fn type_def(&self, state: (&LocalEnv, &ExternalEnv)) -> TypeDef {
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Noop, Op, Query,
-            Unary, Variable,
+            Abort, Assignment, Container, ForLoop, FunctionCall, IfStatement, Literal, LoopControl,
+            Noop, Op, Query, Unary, UserFunctionCall, Variable, WhileLoop,
         };
 
         match self {
@@ -308,6 +397,14 @@ impl Expression for Expr {
             Query(v) => v.type_def(state),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.type_def(state),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.type_def(state),
+            #[cfg(feature = "expr-loop")]
+            ForLoop(v) => v.type_def(state),
+            #[cfg(feature = "expr-loop")]
+            WhileLoop(v) => v.type_def(state),
+            #[cfg(feature = "expr-loop")]
+            LoopControl(v) => v.type_def(state),
             Variable(v) => v.type_def(state),
             Noop(v) => v.type_def(state),
             #[cfg(feature = "expr-unary")]
@@ -321,8 +418,8 @@ impl Expression for Expr {
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Noop, Op, Query,
-            Unary, Variable,
+            Abort, Assignment, Container, ForLoop, FunctionCall, IfStatement, Literal, LoopControl,
+            Noop, Op, Query, Unary, UserFunctionCall, Variable, WhileLoop,
         };
 
         match self {
@@ -339,6 +436,14 @@ impl fmt::Display for Expr {
             Query(v) => v.fmt(f),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.fmt(f),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.fmt(f),
+            #[cfg(feature = "expr-loop")]
+            ForLoop(v) => v.fmt(f),
+            #[cfg(feature = "expr-loop")]
+            WhileLoop(v) => v.fmt(f),
+            #[cfg(feature = "expr-loop")]
+            LoopControl(v) => v.fmt(f),
             Variable(v) => v.fmt(f),
             Noop(v) => v.fmt(f),
             #[cfg(feature = "expr-unary")]
@@ -399,6 +504,34 @@ impl From<FunctionCall> for Expr {
     }
 }
 
+#[cfg(feature = "expr-function_call")]
+impl From<UserFunctionCall> for Expr {
+    fn from(function_call: UserFunctionCall) -> Self {
+        Expr::UserFunctionCall(function_call)
+    }
+}
+
+#[cfg(feature = "expr-loop")]
+impl From<ForLoop> for Expr {
+    fn from(for_loop: ForLoop) -> Self {
+        Expr::ForLoop(for_loop)
+    }
+}
+
+#[cfg(feature = "expr-loop")]
+impl From<WhileLoop> for Expr {
+    fn from(while_loop: WhileLoop) -> Self {
+        Expr::WhileLoop(while_loop)
+    }
+}
+
+#[cfg(feature = "expr-loop")]
+impl From<LoopControl> for Expr {
+    fn from(loop_control: LoopControl) -> Self {
+        Expr::LoopControl(loop_control)
+    }
+}
+
 impl From<Variable> for Expr {
     fn from(variable: Variable) -> Self {
         Expr::Variable(variable)
@@ -476,20 +609,24 @@ pub enum Error {
 
     #[error("expression type unavailable")]
     Missing { span: Span, feature: &'static str },
+
+    #[error("unresolved import")]
+    UnresolvedImport { span: Span },
 }
 
 impl DiagnosticMessage for Error {
     fn code(&self) -> usize {
-        use Error::{Fallible, Missing};
+        use Error::{Fallible, Missing, UnresolvedImport};
 
         match self {
             Fallible { .. } => 100,
+            UnresolvedImport { .. } => 140,
             Missing { .. } => 900,
         }
     }
 
     fn labels(&self) -> Vec<Label> {
-        use Error::{Fallible, Missing};
+        use Error::{Fallible, Missing, UnresolvedImport};
 
         match self {
             Fallible { span } => vec![
@@ -503,14 +640,22 @@ impl DiagnosticMessage for Error {
                     span,
                 ),
             ],
+            UnresolvedImport { span } => vec![
+                Label::primary("import was not resolved before compilation", span),
+                Label::context(
+                    "imports are only supported when loading a program from a file or config",
+                    span,
+                ),
+            ],
         }
     }
 
     fn notes(&self) -> Vec<Note> {
-        use Error::{Fallible, Missing};
+        use Error::{Fallible, Missing, UnresolvedImport};
 
         match self {
             Fallible { .. } => vec![Note::SeeErrorDocs],
+            UnresolvedImport { .. } => vec![Note::SeeLangDocs],
             Missing { .. } => vec![],
         }
     }
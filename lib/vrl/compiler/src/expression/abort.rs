@@ -8,6 +8,7 @@ use crate::{
     expression::{ExpressionError, Resolved},
     state::{ExternalEnv, LocalEnv},
     value::{Kind, VrlValueConvert},
+    vm::{OpCode, Vm},
     Context, Expression, Span, TypeDef,
 };
 
@@ -50,6 +51,17 @@ impl Abort {
 
         Ok(Self { span, message })
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        if let Some(message) = &self.message {
+            message.compile_to_vm(vm);
+        }
+
+        vm.write_opcode(OpCode::Abort {
+            span: self.span,
+            message: self.message.is_some(),
+        });
+    }
 }
 
 impl Expression for Abort {
@@ -15,6 +15,7 @@ use crate::{
     state::{ExternalEnv, LocalEnv},
     type_def::Details,
     value::kind::DefaultValue,
+    vm::{OpCode, Vm},
     Context, Expression, Span, TypeDef,
 };
 
@@ -186,6 +187,10 @@ impl Assignment {
 
         targets
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        self.variant.compile_to_vm(vm);
+    }
 }
 
 fn verify_mutable(
@@ -374,6 +379,7 @@ impl Target {
             }
 
             Self::External(path) => {
+                external.mark_target_mutated(path);
                 external.update_target(Details {
                     type_def: external
                         .target()
@@ -386,7 +392,7 @@ impl Target {
         }
     }
 
-    fn insert(&self, value: Value, ctx: &mut Context) {
+    pub(crate) fn insert(&self, value: Value, ctx: &mut Context) {
         use Target::{External, Internal, Noop};
 
         match self {
@@ -508,6 +514,55 @@ pub(crate) enum Variant<T, U> {
     },
 }
 
+impl Variant<Target, Expr> {
+    fn compile_to_vm(&self, vm: &mut Vm) {
+        use Variant::{Infallible, Single};
+
+        match self {
+            Single { target, expr } => {
+                expr.compile_to_vm(vm);
+
+                let target = vm.add_target(target.clone());
+                vm.write_opcode(OpCode::SetPath(target));
+            }
+            Infallible {
+                ok,
+                err,
+                expr,
+                default,
+            } => {
+                let ok = vm.add_target(ok.clone());
+                let err = vm.add_target(err.clone());
+
+                let handler = vm.emit_jump(OpCode::Try);
+                expr.compile_to_vm(vm);
+                vm.write_opcode(OpCode::EndTry);
+
+                // On success, the value is assigned to `ok`, and `null` to
+                // `err`, leaving the value on the stack.
+                vm.write_opcode(OpCode::SetPath(ok));
+                let null = vm.add_constant(Value::Null);
+                vm.write_opcode(OpCode::Constant(null));
+                vm.write_opcode(OpCode::SetPath(err));
+                vm.write_opcode(OpCode::Pop);
+                let end = vm.emit_jump(OpCode::Jump);
+
+                // On error, the default value is assigned to `ok`, and the
+                // error message to `err`, leaving the message on the stack.
+                vm.patch_jump(handler);
+                let default = vm.add_constant(default.clone());
+                vm.write_opcode(OpCode::Constant(default));
+                vm.write_opcode(OpCode::SetPath(ok));
+                vm.write_opcode(OpCode::Pop);
+                vm.write_opcode(OpCode::PushError);
+                vm.write_opcode(OpCode::SetPath(err));
+
+                vm.patch_jump(end);
+            }
+        }
+    }
+}
+
 impl<U> Expression for Variant<Target, U>
 where
     U: Expression + Clone,
@@ -1,15 +1,25 @@
 use std::fmt;
 
+use value::Value;
+
 use crate::{
     expression::{Expr, Resolved},
+    profile::{resolve_profiled, ProfileKind},
     state::{ExternalEnv, LocalEnv},
-    Context, Expression, TypeDef,
+    vm::{OpCode, Vm},
+    Context, Expression, Span, TypeDef,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Block {
     inner: Vec<Expr>,
 
+    /// The source spans of the expressions in the block, used to profile them.
+    ///
+    /// Blocks not compiled from source have no spans, in which case their
+    /// expressions aren't profiled.
+    spans: Vec<Span>,
+
     /// The local environment of the block.
     ///
     /// This allows any expressions within the block to mutate the local
@@ -21,17 +31,65 @@ pub struct Block {
 impl Block {
     #[must_use]
     pub fn new(inner: Vec<Expr>, local_env: LocalEnv) -> Self {
-        Self { inner, local_env }
+        Self {
+            inner,
+            spans: vec![],
+            local_env,
+        }
+    }
+
+    pub(crate) fn with_spans(mut self, spans: Vec<Span>) -> Self {
+        debug_assert!(spans.len() == self.inner.len());
+
+        self.spans = spans;
+        self
     }
 
     #[must_use]
     pub fn into_inner(self) -> Vec<Expr> {
         self.inner
     }
-}
 
-impl Expression for Block {
-    fn resolve(&self, ctx: &mut Context) -> Resolved {
+    /// The type definition of the block, using the given local environment
+    /// instead of the one the block was compiled with.
+    pub(crate) fn type_def_with_local(&self, local: &LocalEnv, external: &ExternalEnv) -> TypeDef {
+        let mut last = TypeDef::null();
+        let mut fallible = false;
+        let mut has_terminated = false;
+        for expr in &self.inner {
+            assert!(!has_terminated, "VRL block contains an expression after a terminating expression. This is an internal compiler error. Please submit a bug report.");
+            last = expr.type_def((local, external));
+            if last.is_never() {
+                has_terminated = true;
+            }
+            if last.is_fallible() {
+                fallible = true;
+            }
+        }
+
+        last.with_fallibility(fallible)
+    }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        let (last, other) = match self.inner.split_last() {
+            Some(exprs) => exprs,
+            None => {
+                let constant = vm.add_constant(Value::Null);
+                vm.write_opcode(OpCode::Constant(constant));
+                return;
+            }
+        };
+
+        // Only the value of the last expression remains on the stack.
+        for expr in other {
+            expr.compile_to_vm(vm);
+            vm.write_opcode(OpCode::Pop);
+        }
+
+        last.compile_to_vm(vm);
+    }
+
+    fn resolve_exprs(&self, ctx: &mut Context) -> Resolved {
         // NOTE:
         //
         // Technically, this invalidates the scoping invariant of variables
@@ -45,11 +103,53 @@ impl Expression for Block {
         // as it uses the same compiler as this AST runtime.
         let (last, other) = self.inner.split_last().expect("at least one expression");
 
-        other
-            .iter()
-            .try_for_each(|expr| expr.resolve(ctx).map(|_| ()))?;
+        for (index, expr) in other.iter().enumerate() {
+            self.resolve_expr(index, expr, ctx)?;
+
+            // A `break` or `continue` skips the remainder of the loop body,
+            // including any blocks it's nested in.
+            #[cfg(feature = "expr-loop")]
+            if ctx.state().is_loop_interrupted() {
+                return Ok(Value::Null);
+            }
+        }
+
+        self.resolve_expr(other.len(), last, ctx)
+    }
+
+    fn resolve_expr(&self, index: usize, expr: &Expr, ctx: &mut Context) -> Resolved {
+        let span = match self.spans.get(index) {
+            Some(span) => *span,
+            None => return expr.resolve(ctx),
+        };
+
+        ctx.debug_statement(span);
 
-        last.resolve(ctx)
+        match expr {
+            // Function calls record their own timings.
+            #[cfg(feature = "expr-function_call")]
+            Expr::FunctionCall(_) | Expr::UserFunctionCall(_) => expr.resolve(ctx),
+            _ => resolve_profiled(
+                ctx,
+                span,
+                || ProfileKind::Expression,
+                |ctx| expr.resolve(ctx),
+            ),
+        }
+    }
+}
+
+impl Expression for Block {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        if !ctx.is_debugging() {
+            return self.resolve_exprs(ctx);
+        }
+
+        ctx.enter_block();
+        let resolved = self.resolve_exprs(ctx);
+        ctx.exit_block();
+
+        resolved
     }
 
     /// If an expression has a "never" type, it is considered a "terminating" expression.
@@ -65,21 +165,7 @@ impl Expression for Block {
     /// VRL is allowed to have expressions after a terminating expression, but the compiler
     /// MUST not include them in a block expression when compiled.
     fn type_def(&self, (_, external): (&LocalEnv, &ExternalEnv)) -> TypeDef {
-        let mut last = TypeDef::null();
-        let mut fallible = false;
-        let mut has_terminated = false;
-        for expr in &self.inner {
-            assert!(!has_terminated, "VRL block contains an expression after a terminating expression. This is an internal compiler error. Please submit a bug report.");
-            last = expr.type_def((&self.local_env, external));
-            if last.is_never() {
-                has_terminated = true;
-            }
-            if last.is_fallible() {
-                fallible = true;
-            }
-        }
-
-        last.with_fallibility(fallible)
+        self.type_def_with_local(&self.local_env, external)
     }
 }
 
@@ -0,0 +1,250 @@
+use std::fmt;
+
+use diagnostic::{DiagnosticMessage, Label, Note, Urls};
+use value::Value;
+
+use crate::{
+    expression::{Block, Expr, ExpressionError, Resolved},
+    parser::{ast::LoopControl, Ident},
+    state::{ExternalEnv, LocalEnv},
+    value::{Collection, Kind},
+    vm::{OpCode, Vm},
+    Context, Expression, Span, TypeDef,
+};
+
+/// A loop over the elements of an array, or the fields of an object, using
+/// `for key, value in collection { ... }`.
+///
+/// A recursive loop (using `walk` instead of `for`) iterates over all values
+/// nested within the collection instead, in depth-first order. Its key is the
+/// path to the value, as an array of field names and indices.
+///
+/// The collection is resolved once, before the first iteration, so the number
+/// of iterations is bounded by its size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForLoop {
+    recursive: bool,
+    key: Option<Ident>,
+    value: Option<Ident>,
+    expr: Box<Expr>,
+    block: Block,
+}
+
+impl ForLoop {
+    pub(crate) fn new(
+        recursive: bool,
+        key: Option<Ident>,
+        value: Option<Ident>,
+        expr: Expr,
+        block: Block,
+    ) -> Self {
+        Self {
+            recursive,
+            key,
+            value,
+            expr: Box::new(expr),
+            block,
+        }
+    }
+
+    /// Get the type definitions of the key and value variables of a loop over
+    /// a collection of the given type.
+    pub(crate) fn variable_type_defs(
+        recursive: bool,
+        expr_span: Span,
+        type_def: &TypeDef,
+    ) -> Result<(TypeDef, TypeDef), Error> {
+        if !type_def.is_collection() {
+            return Err(Error::NonIterable {
+                kind: type_def.kind().clone(),
+                expr_span,
+            });
+        }
+
+        // Nested values can be of any type, and their path can contain both
+        // field names and indices.
+        if recursive {
+            let path = Kind::array(Collection::from_unknown(Kind::bytes().or_integer()));
+
+            return Ok((path.into(), TypeDef::any()));
+        }
+
+        let mut key = Kind::never();
+        let mut value = Kind::never();
+
+        if let Some(object) = type_def.as_object() {
+            key.add_bytes();
+            value.merge_keep(object.reduced_kind(), false);
+        }
+
+        if let Some(array) = type_def.as_array() {
+            key.add_integer();
+            value.merge_keep(array.reduced_kind(), false);
+        }
+
+        Ok((key.into(), value.into()))
+    }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        self.expr.compile_to_vm(vm);
+
+        let variables = self.key.iter().chain(&self.value).cloned().collect();
+        let index = vm.add_for_loop(variables, self.key.is_some(), self.recursive);
+
+        vm.write_opcode(OpCode::EnterLoop(index));
+        let start = vm.begin_loop();
+        let end = vm.emit_jump(OpCode::Iterate);
+
+        self.block.compile_to_vm(vm);
+        vm.write_opcode(OpCode::Pop);
+        vm.write_opcode(OpCode::Jump(start));
+
+        vm.patch_jump(end);
+        vm.end_loop();
+        vm.write_opcode(OpCode::ExitLoop);
+
+        let constant = vm.add_constant(Value::Null);
+        vm.write_opcode(OpCode::Constant(constant));
+    }
+
+    fn iterate(&self, collection: Value, ctx: &mut Context) -> Result<(), ExpressionError> {
+        for (key, value) in entries(collection, self.recursive) {
+            if let Some(ident) = &self.key {
+                ctx.state_mut().insert_variable(ident.clone(), key);
+            }
+
+            if let Some(ident) = &self.value {
+                ctx.state_mut().insert_variable(ident.clone(), value);
+            }
+
+            self.block.resolve(ctx)?;
+
+            if let Some(LoopControl::Break) = ctx.state_mut().take_loop_control() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Expression for ForLoop {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let collection = self.expr.resolve(ctx)?;
+
+        // The loop variables are scoped to the loop, so any variables they
+        // shadow are restored once the loop ends.
+        let previous = self
+            .key
+            .iter()
+            .chain(&self.value)
+            .map(|ident| (ident.clone(), ctx.state().variable(ident).cloned()))
+            .collect::<Vec<_>>();
+
+        let result = self.iterate(collection, ctx);
+
+        for (ident, value) in previous {
+            ctx.state_mut().restore_variable(ident, value);
+        }
+
+        result.map(|()| Value::Null)
+    }
+
+    fn type_def(&self, state: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        let fallible =
+            self.expr.type_def(state).is_fallible() || self.block.type_def(state).is_fallible();
+
+        TypeDef::null().with_fallibility(fallible)
+    }
+}
+
+impl fmt::Display for ForLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.recursive { "walk " } else { "for " })?;
+
+        if let Some(key) = &self.key {
+            write!(f, "{}, ", key)?;
+        }
+
+        match &self.value {
+            Some(value) => value.fmt(f)?,
+            None => f.write_str("_")?,
+        }
+
+        write!(f, " in {} {}", self.expr, self.block)
+    }
+}
+
+/// Get the key/value pairs a loop iterates over.
+///
+/// Values that aren't collections have no entries.
+pub(crate) fn entries(collection: Value, recursive: bool) -> Vec<(Value, Value)> {
+    if !recursive {
+        return children(collection);
+    }
+
+    let mut entries = vec![];
+    walk(collection, &mut vec![], &mut entries);
+    entries
+}
+
+fn children(value: Value) -> Vec<(Value, Value)> {
+    match value {
+        Value::Object(object) => object
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect(),
+        Value::Array(array) => array
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| (index.into(), value))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn walk(value: Value, path: &mut Vec<Value>, entries: &mut Vec<(Value, Value)>) {
+    for (key, value) in children(value) {
+        path.push(key);
+        entries.push((Value::Array(path.clone()), value.clone()));
+        walk(value, path, entries);
+        path.pop();
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("non-iterable loop expression")]
+    NonIterable { kind: Kind, expr_span: Span },
+}
+
+impl DiagnosticMessage for Error {
+    fn code(&self) -> usize {
+        match self {
+            Error::NonIterable { .. } => 150,
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match self {
+            Error::NonIterable { kind, expr_span } => vec![
+                Label::primary(
+                    "this expression must resolve to an array or object",
+                    expr_span,
+                ),
+                Label::context(format!("instead it resolves to {}", kind), expr_span),
+            ],
+        }
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        match self {
+            Error::NonIterable { .. } => vec![
+                Note::CoerceValue,
+                Note::SeeDocs("loops".to_owned(), Urls::expression_docs_url("#loop")),
+            ],
+        }
+    }
+}
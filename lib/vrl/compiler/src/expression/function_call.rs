@@ -5,15 +5,17 @@ use diagnostic::{DiagnosticMessage, Label, Note, Urls};
 
 use super::Block;
 use crate::{
-    expression::{levenstein, ExpressionError, FunctionArgument},
+    expression::{levenstein, Expr, ExpressionError, FunctionArgument},
     function::{
         closure::{self, VariableKind},
         ArgumentList, Example, FunctionClosure, FunctionCompileContext, Parameter,
     },
     parser::{Ident, Node},
+    profile::{resolve_profiled, ProfileKind},
     state::{ExternalEnv, LocalEnv},
     type_def::Details,
     value::Kind,
+    vm::{OpCode, Vm},
     Context, Expression, Function, Resolved, Span, TypeDef,
 };
 
@@ -582,11 +584,24 @@ impl FunctionCall {
             .map(|arg| format!("{:?}", arg.inner()))
             .collect::<Vec<_>>()
     }
+
+    /// Function calls are resolved by the function's own compiled expression,
+    /// which in turn resolves its arguments.
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        let expr = vm.add_expression(Expr::FunctionCall(self.clone()));
+        vm.write_opcode(OpCode::Call(expr));
+    }
 }
 
 impl Expression for FunctionCall {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
-        self.expr.resolve(ctx).map_err(|err| match err {
+        resolve_profiled(
+            ctx,
+            self.span,
+            || ProfileKind::FunctionCall(self.ident.to_owned()),
+            |ctx| self.expr.resolve(ctx),
+        )
+        .map_err(|err| match err {
             #[cfg(feature = "expr-abort")]
             ExpressionError::Abort { .. } => {
                 panic!("abort errors must only be defined by `abort` statement")
@@ -3,6 +3,7 @@ use std::fmt;
 use crate::{
     expression::{Expr, Resolved},
     state::{ExternalEnv, LocalEnv},
+    vm::Vm,
     Context, Expression, TypeDef,
 };
 
@@ -17,6 +18,10 @@ impl Group {
             inner: Box::new(inner),
         }
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        self.inner.compile_to_vm(vm);
+    }
 }
 
 impl Expression for Group {
@@ -3,9 +3,11 @@ use std::fmt;
 use value::Value;
 
 use crate::{
+    debug::Branch,
     expression::{Block, Predicate, Resolved},
     state::{ExternalEnv, LocalEnv},
     value::VrlValueConvert,
+    vm::{OpCode, Vm},
     Context, Expression, TypeDef,
 };
 
@@ -16,10 +18,39 @@ pub struct IfStatement {
     pub alternative: Option<Block>,
 }
 
+impl IfStatement {
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        self.predicate.compile_to_vm(vm);
+        let alternative = vm.emit_jump(OpCode::JumpIfFalse);
+
+        self.consequent.compile_to_vm(vm);
+        let end = vm.emit_jump(OpCode::Jump);
+
+        vm.patch_jump(alternative);
+        match &self.alternative {
+            Some(block) => block.compile_to_vm(vm),
+            None => {
+                let constant = vm.add_constant(Value::Null);
+                vm.write_opcode(OpCode::Constant(constant));
+            }
+        }
+
+        vm.patch_jump(end);
+    }
+}
+
 impl Expression for IfStatement {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
         let predicate = self.predicate.resolve(ctx)?.try_boolean()?;
 
+        if ctx.is_debugging() {
+            ctx.debug_branch(match (predicate, &self.alternative) {
+                (true, _) => Branch::Consequent,
+                (false, Some(_)) => Branch::Alternative,
+                (false, None) => Branch::None,
+            });
+        }
+
         match predicate {
             true => self.consequent.resolve(ctx),
             false => self
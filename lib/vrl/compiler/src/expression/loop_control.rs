@@ -0,0 +1,104 @@
+use std::fmt;
+
+use diagnostic::{DiagnosticMessage, Label, Note, Urls};
+use value::Value;
+
+use crate::{
+    expression::Resolved,
+    parser::ast,
+    state::{ExternalEnv, LocalEnv},
+    vm::Vm,
+    Context, Expression, Span, TypeDef,
+};
+
+/// A `break` or `continue` expression, ending the current iteration of the
+/// innermost loop.
+///
+/// These expressions are only allowed as statements of a loop body, so that
+/// the value they resolve to is never used. This allows the tree-walking
+/// runtime to unwind to the loop by checking the loop state after each
+/// statement of a block, instead of through an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopControl {
+    control: ast::LoopControl,
+}
+
+impl LoopControl {
+    pub(crate) fn new(control: ast::LoopControl) -> Self {
+        Self { control }
+    }
+
+    pub(crate) fn compile_to_vm(self, vm: &mut Vm) {
+        match self.control {
+            ast::LoopControl::Break => vm.emit_break(),
+            ast::LoopControl::Continue => vm.emit_continue(),
+        }
+    }
+}
+
+impl Expression for LoopControl {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        ctx.state_mut().interrupt_loop(self.control);
+
+        Ok(Value::Null)
+    }
+
+    fn type_def(&self, _: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        TypeDef::never().infallible()
+    }
+}
+
+impl fmt::Display for LoopControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.control.fmt(f)
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("{control} outside of loop")]
+    OutsideLoop {
+        control: ast::LoopControl,
+        span: Span,
+    },
+
+    #[error("{control} used as a value")]
+    NotStatement {
+        control: ast::LoopControl,
+        span: Span,
+    },
+}
+
+impl DiagnosticMessage for Error {
+    fn code(&self) -> usize {
+        match self {
+            Error::OutsideLoop { .. } => 151,
+            Error::NotStatement { .. } => 152,
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match self {
+            Error::OutsideLoop { control, span } => vec![Label::primary(
+                format!("{} can only be used within a loop", control),
+                span,
+            )],
+            Error::NotStatement { control, span } => vec![
+                Label::primary(format!("{} can't be used as a value", control), span),
+                Label::context(
+                    "it must be a statement of the loop body, or of a block or if-statement within it",
+                    span,
+                ),
+            ],
+        }
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        vec![Note::SeeDocs(
+            "loops".to_owned(),
+            Urls::expression_docs_url("#loop"),
+        )]
+    }
+}
@@ -5,12 +5,20 @@ use value::Value;
 use crate::{
     expression::Resolved,
     state::{ExternalEnv, LocalEnv},
+    vm::{OpCode, Vm},
     Context, Expression, TypeDef,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Noop;
 
+impl Noop {
+    pub(crate) fn compile_to_vm(self, vm: &mut Vm) {
+        let constant = vm.add_constant(Value::Null);
+        vm.write_opcode(OpCode::Constant(constant));
+    }
+}
+
 impl Expression for Noop {
     fn resolve(&self, _: &mut Context) -> Resolved {
         Ok(Value::Null)
@@ -7,6 +7,7 @@ use crate::{
     parser::Node,
     state::{ExternalEnv, LocalEnv},
     value::{Kind, VrlValueConvert},
+    vm::{OpCode, Vm},
     Context, Expression, Span, TypeDef,
 };
 
@@ -34,6 +35,11 @@ impl Not {
             inner: Box::new(expr),
         })
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        self.inner.compile_to_vm(vm);
+        vm.write_opcode(OpCode::Not);
+    }
 }
 
 impl Expression for Not {
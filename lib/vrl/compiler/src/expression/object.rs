@@ -5,6 +5,7 @@ use value::Value;
 use crate::{
     expression::{Expr, Resolved},
     state::{ExternalEnv, LocalEnv},
+    vm::{OpCode, Vm},
     Context, Expression, TypeDef,
 };
 
@@ -18,6 +19,15 @@ impl Object {
     pub fn new(inner: BTreeMap<String, Expr>) -> Self {
         Self { inner }
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        for expr in self.inner.values() {
+            expr.compile_to_vm(vm);
+        }
+
+        let keys = vm.add_object_keys(self.inner.keys().cloned().collect());
+        vm.write_opcode(OpCode::CreateObject(keys));
+    }
 }
 
 impl Deref for Object {
@@ -8,6 +8,7 @@ use crate::{
     parser::{ast, Node},
     state::{ExternalEnv, LocalEnv},
     value::VrlValueArithmetic,
+    vm::{OpCode, Vm},
     Context, Expression, TypeDef,
 };
 
@@ -75,6 +76,70 @@ impl Op {
             opcode,
         })
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        use ast::Opcode::{Add, And, Div, Eq, Err, Ge, Gt, Le, Lt, Merge, Mul, Ne, Or, Rem, Sub};
+
+        match self.opcode {
+            Err => {
+                // The rhs is only evaluated if the lhs results in an error.
+                let handler = vm.emit_jump(OpCode::Try);
+                self.lhs.compile_to_vm(vm);
+                vm.write_opcode(OpCode::EndTry);
+                let end = vm.emit_jump(OpCode::Jump);
+
+                vm.patch_jump(handler);
+                self.rhs.compile_to_vm(vm);
+                vm.patch_jump(end);
+            }
+            Or => {
+                // The rhs is only evaluated if the lhs is `null` or `false`.
+                self.lhs.compile_to_vm(vm);
+                let truthy = vm.emit_jump(OpCode::JumpIfTruthy);
+
+                let handler = vm.emit_jump(OpCode::Try);
+                self.rhs.compile_to_vm(vm);
+                vm.write_opcode(OpCode::EndTry);
+                let end = vm.emit_jump(OpCode::Jump);
+
+                vm.patch_jump(handler);
+                vm.write_opcode(OpCode::RaiseOr);
+
+                vm.patch_jump(end);
+                vm.patch_jump(truthy);
+            }
+            And => {
+                // The rhs is only evaluated if the lhs is neither `null` nor
+                // `false`.
+                self.lhs.compile_to_vm(vm);
+                let falsy = vm.emit_jump(OpCode::JumpIfFalsy);
+
+                self.rhs.compile_to_vm(vm);
+                vm.write_opcode(OpCode::And);
+                vm.patch_jump(falsy);
+            }
+            opcode => {
+                self.lhs.compile_to_vm(vm);
+                self.rhs.compile_to_vm(vm);
+
+                vm.write_opcode(match opcode {
+                    Mul => OpCode::Multiply,
+                    Div => OpCode::Divide,
+                    Add => OpCode::Add,
+                    Sub => OpCode::Subtract,
+                    Rem => OpCode::Rem,
+                    Eq => OpCode::Equal,
+                    Ne => OpCode::NotEqual,
+                    Gt => OpCode::Greater,
+                    Ge => OpCode::GreaterEqual,
+                    Lt => OpCode::Less,
+                    Le => OpCode::LessEqual,
+                    Merge => OpCode::Merge,
+                    And | Or | Err => unreachable!(),
+                });
+            }
+        }
+    }
 }
 
 impl Expression for Op {
@@ -8,6 +8,7 @@ use crate::{
     parser::ast::Ident,
     state::{ExternalEnv, LocalEnv},
     type_def::Details,
+    vm::{Location, OpCode, Vm},
     Context, Expression, TypeDef,
 };
 
@@ -75,9 +76,30 @@ impl Query {
         );
 
         external.update_target(Details { type_def, value });
+        external.mark_target_mutated(&self.path);
 
         result
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        use Target::{Container, External, FunctionCall, Internal};
+
+        let location = match &self.target {
+            External => Location::External(self.path.clone()),
+            Internal(variable) => Location::Internal(variable.ident().clone(), self.path.clone()),
+            FunctionCall(call) => {
+                call.compile_to_vm(vm);
+                Location::Stack(self.path.clone())
+            }
+            Container(container) => {
+                container.compile_to_vm(vm);
+                Location::Stack(self.path.clone())
+            }
+        };
+
+        let location = vm.add_location(location);
+        vm.write_opcode(OpCode::GetPath(location));
+    }
 }
 
 impl Expression for Query {
@@ -3,6 +3,7 @@ use std::fmt;
 use crate::{
     expression::{Not, Resolved},
     state::{ExternalEnv, LocalEnv},
+    vm::Vm,
     Context, Expression, TypeDef,
 };
 
@@ -16,6 +17,14 @@ impl Unary {
     pub fn new(variant: Variant) -> Self {
         Self { variant }
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        use Variant::Not;
+
+        match &self.variant {
+            Not(v) => v.compile_to_vm(vm),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
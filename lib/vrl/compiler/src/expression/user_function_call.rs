@@ -0,0 +1,446 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use diagnostic::{DiagnosticMessage, Label, Note};
+use lookup::LookupBuf;
+
+use crate::{
+    expression::{Block, Expr, ExpressionError, Resolved},
+    parser::{Ident, Node},
+    profile::{resolve_profiled, ProfileKind},
+    state::{ExternalEnv, LocalEnv},
+    type_def::Details,
+    vm::{OpCode, Vm},
+    Context, Expression, Span, TypeDef,
+};
+
+/// A function defined in the program source (or one of its imports) using
+/// `fn name(params) { ... }`.
+///
+/// Each function is compiled once, with parameters of any type and a target of
+/// any type, and shared by all its call sites.
+#[derive(Debug, PartialEq)]
+pub(crate) struct UserFunction {
+    ident: Ident,
+    parameters: Vec<Ident>,
+    block: Block,
+
+    /// The type of the target once the body resolved.
+    target: TypeDef,
+
+    /// The paths of the target the body mutates, in the order they have to be
+    /// applied, with their type once the body resolved.
+    target_mutations: Vec<(LookupBuf, TypeDef)>,
+}
+
+impl UserFunction {
+    pub(crate) fn new(
+        ident: Ident,
+        parameters: Vec<Ident>,
+        block: Block,
+        target: TypeDef,
+        mutated_paths: &[LookupBuf],
+    ) -> Self {
+        // Only the last mutation of a path matters, as its type is the type
+        // the path has once the body resolved.
+        let mut target_mutations: Vec<(LookupBuf, TypeDef)> = vec![];
+        for path in mutated_paths.iter().rev() {
+            if target_mutations.iter().all(|(mutated, _)| mutated != path) {
+                let type_def = target.at_path(&path.to_lookup());
+                target_mutations.push((path.clone(), type_def));
+            }
+        }
+        target_mutations.reverse();
+
+        Self {
+            ident,
+            parameters,
+            block,
+            target,
+            target_mutations,
+        }
+    }
+
+    pub(crate) fn parameters(&self) -> &[Ident] {
+        &self.parameters
+    }
+
+    /// The type definition of the function body when called with the given
+    /// arguments.
+    ///
+    /// The parameters are bound to the types of the arguments. Any variable
+    /// the body derives from its parameters keeps the type it was compiled
+    /// with, so the result is at worst as broad as the compiled body. Queries
+    /// of the target resolve against the target the body was compiled with.
+    fn type_def(&self, arguments: &[Expr], state: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        let mut local = self.block.local_env.clone();
+        for (parameter, argument) in self.parameters.iter().zip(arguments) {
+            let details = Details {
+                type_def: argument.type_def(state).infallible(),
+                value: None,
+            };
+
+            local.insert_variable(parameter.clone(), details);
+        }
+
+        let external = ExternalEnv::new_with_kind(self.target.kind().clone());
+
+        self.block.type_def_with_local(&local, &external)
+    }
+
+    /// Apply the mutations the body makes to the target to the type of the
+    /// target at a call site.
+    ///
+    /// Each mutated path takes the type it has once the body resolved, which
+    /// is `any` for paths the body only mutates conditionally.
+    pub(crate) fn apply_target_mutations(&self, external: &mut ExternalEnv) {
+        for (path, type_def) in &self.target_mutations {
+            let target = external
+                .target()
+                .type_def
+                .clone()
+                .with_type_set_at_path(&path.to_lookup(), type_def.clone());
+
+            external.update_target(Details {
+                type_def: target,
+                value: None,
+            });
+            external.mark_target_mutated(path);
+        }
+    }
+}
+
+/// A call to a [`UserFunction`].
+///
+/// At runtime, the function body is resolved in its own variable scope, which
+/// only contains the function parameters.
+///
+/// The type definition of the call is computed once, from the types of the
+/// arguments, as the body's variables aren't part of the caller's local
+/// environment.
+#[derive(Clone, PartialEq)]
+pub struct UserFunctionCall {
+    span: Span,
+    abort_on_error: bool,
+    arguments: Vec<Expr>,
+    function: Arc<UserFunction>,
+    block_type_def: TypeDef,
+}
+
+impl UserFunctionCall {
+    pub(crate) fn new(
+        span: Span,
+        abort_on_error: bool,
+        arguments: Vec<Expr>,
+        function: Arc<UserFunction>,
+        state: (&LocalEnv, &ExternalEnv),
+    ) -> Result<Self, Error> {
+        let block_type_def = function.type_def(&arguments, state);
+
+        if abort_on_error && !block_type_def.is_fallible() {
+            return Err(Error::AbortInfallible { call_span: span });
+        }
+
+        Ok(Self {
+            span,
+            abort_on_error,
+            arguments,
+            function,
+            block_type_def,
+        })
+    }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        for argument in &self.arguments {
+            argument.compile_to_vm(vm);
+        }
+
+        let function = vm.add_function(
+            self.function.ident.clone(),
+            self.span,
+            self.function.parameters.clone(),
+        );
+
+        vm.write_opcode(OpCode::EnterFunction(function));
+        self.function.block.compile_to_vm(vm);
+        vm.write_opcode(OpCode::ExitFunction);
+    }
+
+    fn resolve_call(&self, ctx: &mut Context) -> Resolved {
+        let function = &self.function;
+
+        let mut variables = HashMap::with_capacity(function.parameters.len());
+        for (ident, argument) in function.parameters.iter().zip(&self.arguments) {
+            variables.insert(ident.clone(), argument.resolve(ctx)?);
+        }
+
+        // The function body can only see its own parameters, any variables of
+        // the caller are restored once the body resolves.
+        let caller = ctx.state_mut().swap_variables(variables);
+        let result = function.block.resolve(ctx);
+        ctx.state_mut().swap_variables(caller);
+
+        result.map_err(|err| annotate_error(&function.ident, self.span, err))
+    }
+}
+
+impl Expression for UserFunctionCall {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        resolve_profiled(
+            ctx,
+            self.span,
+            || ProfileKind::FunctionCall(self.function.ident.to_string()),
+            |ctx| self.resolve_call(ctx),
+        )
+    }
+
+    fn type_def(&self, _: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        let fallible = self.block_type_def.is_fallible() && !self.abort_on_error;
+
+        self.block_type_def.clone().with_fallibility(fallible)
+    }
+}
+
+impl fmt::Display for UserFunctionCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.function.ident.fmt(f)?;
+
+        if self.abort_on_error {
+            f.write_str("!")?;
+        }
+
+        f.write_str("(")?;
+
+        let mut iter = self.arguments.iter().peekable();
+        while let Some(argument) = iter.next() {
+            argument.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        f.write_str(")")
+    }
+}
+
+impl fmt::Debug for UserFunctionCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UserFunctionCall({})", self)
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("duplicate function definition")]
+    DuplicateDefinition {
+        ident_span: Span,
+        previous_span: Span,
+    },
+
+    #[error("function definition shadows built-in function")]
+    ShadowsBuiltin { ident_span: Span, ident: Ident },
+
+    #[error("duplicate function parameter")]
+    DuplicateParameter { parameter_span: Span },
+
+    #[error("recursive function call")]
+    Recursion { call_span: Span, ident: Ident },
+
+    #[error("wrong number of function arguments")]
+    WrongNumberOfArgs {
+        call_span: Span,
+        ident: Ident,
+        expected: usize,
+        supplied: usize,
+    },
+
+    #[error("unknown function argument keyword")]
+    UnknownKeyword {
+        keyword_span: Span,
+        parameters: Vec<Ident>,
+    },
+
+    #[error("can't abort infallible function")]
+    AbortInfallible { call_span: Span },
+}
+
+impl DiagnosticMessage for Error {
+    fn code(&self) -> usize {
+        use Error::{
+            AbortInfallible, DuplicateDefinition, DuplicateParameter, Recursion, ShadowsBuiltin,
+            UnknownKeyword, WrongNumberOfArgs,
+        };
+
+        match self {
+            DuplicateDefinition { .. } => 130,
+            ShadowsBuiltin { .. } => 131,
+            DuplicateParameter { .. } => 132,
+            Recursion { .. } => 133,
+            WrongNumberOfArgs { .. } => 134,
+            UnknownKeyword { .. } => 135,
+            AbortInfallible { .. } => 620,
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        use Error::{
+            AbortInfallible, DuplicateDefinition, DuplicateParameter, Recursion, ShadowsBuiltin,
+            UnknownKeyword, WrongNumberOfArgs,
+        };
+
+        match self {
+            DuplicateDefinition {
+                ident_span,
+                previous_span,
+            } => vec![
+                Label::primary("function is already defined", ident_span),
+                Label::context("previous definition here", previous_span),
+            ],
+            ShadowsBuiltin { ident_span, ident } => vec![
+                Label::primary(
+                    format!(r#"a built-in function named "{}" already exists"#, ident),
+                    ident_span,
+                ),
+                Label::context("use a different function name instead", ident_span),
+            ],
+            DuplicateParameter { parameter_span } => vec![Label::primary(
+                "parameter name is already used by this function",
+                parameter_span,
+            )],
+            Recursion { call_span, ident } => vec![
+                Label::primary(format!(r#"function "{}" calls itself"#, ident), call_span),
+                Label::context(
+                    "recursion is not allowed, to guarantee the program terminates",
+                    call_span,
+                ),
+            ],
+            WrongNumberOfArgs {
+                call_span,
+                ident,
+                expected,
+                supplied,
+            } => vec![
+                Label::primary(
+                    format!(
+                        r#"function "{}" expects {} argument(s), got {}"#,
+                        ident, expected, supplied
+                    ),
+                    call_span,
+                ),
+                Label::context("all function parameters are required", call_span),
+            ],
+            UnknownKeyword {
+                keyword_span,
+                parameters,
+            } => {
+                let parameters = parameters
+                    .iter()
+                    .map(|ident| format!(r#""{}""#, ident))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                vec![
+                    Label::primary("unknown keyword", keyword_span),
+                    Label::context(
+                        format!("this function accepts the keywords: {}", parameters),
+                        keyword_span,
+                    ),
+                ]
+            }
+            AbortInfallible { call_span } => vec![
+                Label::primary("this function can't fail", call_span),
+                Label::context("remove the error assertion (!)", call_span),
+            ],
+        }
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        vec![Note::SeeLangDocs]
+    }
+}
+
+/// Annotate an error raised while resolving the body of the function called
+/// at the given span.
+pub(crate) fn annotate_error(ident: &Ident, span: Span, err: ExpressionError) -> ExpressionError {
+    match err {
+        ExpressionError::Error {
+            message,
+            mut labels,
+            notes,
+        } => {
+            labels.push(Label::primary(message.clone(), span));
+
+            ExpressionError::Error {
+                message: format!(
+                    r#"function call error for "{}" at ({}:{}): {}"#,
+                    ident,
+                    span.start(),
+                    span.end(),
+                    message
+                ),
+                labels,
+                notes,
+            }
+        }
+        #[cfg(feature = "expr-abort")]
+        err @ ExpressionError::Abort { .. } => err,
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// Map the arguments of a call to the parameters of the called function, in
+/// parameter order.
+///
+/// Arguments can be passed positionally, or by keyword.
+pub(crate) fn order_arguments<T>(
+    call_span: Span,
+    ident: &Ident,
+    parameters: &[Ident],
+    arguments: Vec<(Option<Node<Ident>>, T)>,
+) -> Result<Vec<T>, Error> {
+    let supplied = arguments.len();
+    let wrong_number_of_args = || Error::WrongNumberOfArgs {
+        call_span,
+        ident: ident.clone(),
+        expected: parameters.len(),
+        supplied,
+    };
+
+    if supplied != parameters.len() {
+        return Err(wrong_number_of_args());
+    }
+
+    let mut ordered = parameters.iter().map(|_| None).collect::<Vec<_>>();
+    let mut positional = vec![];
+
+    for (keyword, argument) in arguments {
+        match keyword {
+            None => positional.push(argument),
+            Some(keyword) => {
+                let position = parameters
+                    .iter()
+                    .position(|param| param == keyword.inner())
+                    .ok_or_else(|| Error::UnknownKeyword {
+                        keyword_span: keyword.span(),
+                        parameters: parameters.to_vec(),
+                    })?;
+
+                if ordered[position].is_some() {
+                    return Err(wrong_number_of_args());
+                }
+
+                ordered[position] = Some(argument);
+            }
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    ordered
+        .into_iter()
+        .map(|argument| argument.or_else(|| positional.next()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(wrong_number_of_args)
+}
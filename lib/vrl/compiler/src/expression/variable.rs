@@ -1,12 +1,14 @@
 use std::fmt;
 
 use diagnostic::{DiagnosticMessage, Label};
+use lookup::LookupBuf;
 use value::Value;
 
 use crate::{
     expression::{levenstein, Resolved},
     parser::ast::Ident,
     state::{ExternalEnv, LocalEnv},
+    vm::{Location, OpCode, Vm},
     Context, Expression, Span, TypeDef,
 };
 
@@ -40,6 +42,11 @@ impl Variable {
     pub fn value(&self) -> Option<&Value> {
         self.value.as_ref()
     }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        let location = vm.add_location(Location::Internal(self.ident.clone(), LookupBuf::root()));
+        vm.write_opcode(OpCode::GetPath(location));
+    }
 }
 
 impl Expression for Variable {
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use diagnostic::Label;
+use value::Value;
+
+use crate::{
+    expression::{Block, ExpressionError, Predicate, Resolved},
+    parser::ast::LoopControl,
+    state::{ExternalEnv, LocalEnv},
+    value::VrlValueConvert,
+    vm::{OpCode, Vm},
+    Context, Expression, Span, TypeDef,
+};
+
+/// The maximum number of iterations of a single `while` loop.
+///
+/// Unlike a `for` loop, the number of iterations of a `while` loop can't be
+/// known in advance, so a runtime error is raised once it exceeds this limit,
+/// to guarantee that the program terminates.
+pub(crate) const MAX_ITERATIONS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileLoop {
+    span: Span,
+    predicate: Predicate,
+    block: Block,
+}
+
+impl WhileLoop {
+    pub(crate) fn new(span: Span, predicate: Predicate, block: Block) -> Self {
+        Self {
+            span,
+            predicate,
+            block,
+        }
+    }
+
+    pub(crate) fn compile_to_vm(&self, vm: &mut Vm) {
+        let index = vm.add_while_loop(self.span);
+
+        vm.write_opcode(OpCode::EnterLoop(index));
+        let start = vm.begin_loop();
+
+        self.predicate.compile_to_vm(vm);
+        let end = vm.emit_jump(OpCode::JumpIfFalse);
+        let limit = vm.emit_jump(OpCode::Iterate);
+
+        self.block.compile_to_vm(vm);
+        vm.write_opcode(OpCode::Pop);
+        vm.write_opcode(OpCode::Jump(start));
+
+        vm.patch_jump(end);
+        vm.patch_jump(limit);
+        vm.end_loop();
+        vm.write_opcode(OpCode::ExitLoop);
+
+        let constant = vm.add_constant(Value::Null);
+        vm.write_opcode(OpCode::Constant(constant));
+    }
+}
+
+/// The error returned once a `while` loop exceeds [`MAX_ITERATIONS`].
+pub(crate) fn limit_exceeded(span: Span) -> ExpressionError {
+    ExpressionError::Error {
+        message: format!(
+            "while loop exceeded the maximum of {} iterations",
+            MAX_ITERATIONS
+        ),
+        labels: vec![Label::primary("this loop didn't terminate", span)],
+        notes: vec![],
+    }
+}
+
+impl Expression for WhileLoop {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let mut iterations = 0;
+
+        while self.predicate.resolve(ctx)?.try_boolean()? {
+            iterations += 1;
+
+            if iterations > MAX_ITERATIONS {
+                return Err(limit_exceeded(self.span));
+            }
+
+            self.block.resolve(ctx)?;
+
+            if let Some(LoopControl::Break) = ctx.state_mut().take_loop_control() {
+                break;
+            }
+        }
+
+        Ok(Value::Null)
+    }
+
+    /// A `while` loop is always fallible, as it fails once it exceeds
+    /// [`MAX_ITERATIONS`].
+    fn type_def(&self, _: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        TypeDef::null().fallible()
+    }
+}
+
+impl fmt::Display for WhileLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "while {} {}", self.predicate, self.block)
+    }
+}
@@ -425,6 +425,14 @@ impl ArgumentList {
             .ok_or(Error::ExpectedFunctionClosure)
     }
 
+    /// Attach a closure, to test a function that takes one outside of a
+    /// compiled program (see [`test_closure`](crate::test_closure)).
+    #[must_use]
+    pub fn with_closure(mut self, closure: FunctionClosure) -> Self {
+        self.closure = Some(closure);
+        self
+    }
+
     #[cfg(feature = "expr-function_call")]
     pub(crate) fn keywords(&self) -> Vec<&'static str> {
         self.arguments.keys().copied().collect::<Vec<_>>()
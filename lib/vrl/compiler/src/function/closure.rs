@@ -258,6 +258,36 @@ where
         Ok(())
     }
 
+    /// Run the closure to completion, given the provided values, and the
+    /// runtime context, returning the value the closure resolves to.
+    ///
+    /// The values are bound to the closure variables in the order in which the
+    /// variables are defined. Unlike the other methods, the closure variables
+    /// are restored even if the closure fails.
+    pub fn run_values(
+        &self,
+        ctx: &mut Context,
+        values: Vec<Value>,
+    ) -> Result<Value, ExpressionError> {
+        let idents = (0..values.len())
+            .map(|index| self.ident(index))
+            .collect::<Vec<_>>();
+
+        let old_values = idents
+            .iter()
+            .zip(values)
+            .map(|(ident, value)| insert(ctx.state_mut(), *ident, value))
+            .collect::<Vec<_>>();
+
+        let result = (self.runner)(ctx);
+
+        for (ident, old_value) in idents.into_iter().zip(old_values) {
+            cleanup(ctx.state_mut(), ident, old_value);
+        }
+
+        result
+    }
+
     fn ident(&self, index: usize) -> Option<&Ident> {
         self.variables
             .get(index)
@@ -33,11 +33,14 @@ mod context;
 mod program;
 mod test_util;
 
+pub mod debug;
 pub mod expression;
 pub mod function;
+pub mod profile;
 pub mod state;
 pub mod type_def;
 pub mod value;
+pub mod vm;
 
 pub use core::{
     value, ExpressionError, MetadataTarget, Resolved, SecretTarget, Target, TargetValue,
@@ -53,8 +56,11 @@ pub use function::{Function, Parameter};
 pub use paste::paste;
 pub use program::{Program, ProgramInfo};
 use state::ExternalEnv;
+#[doc(hidden)]
+pub use test_util::__compile_closure;
 pub use type_def::TypeDef;
 use vector_config::configurable_component;
+pub use vm::Vm;
 
 pub type Result<T = (Program, DiagnosticList)> = std::result::Result<T, DiagnosticList>;
 
@@ -65,8 +71,14 @@ pub type Result<T = (Program, DiagnosticList)> = std::result::Result<T, Diagnost
 pub enum VrlRuntime {
     /// Tree-walking runtime.
     ///
-    /// This is the only, and default, runtime.
+    /// This is the default runtime.
     Ast,
+
+    /// Bytecode virtual machine runtime.
+    ///
+    /// The program is compiled to bytecode once, which is then executed for
+    /// each event.
+    Vm,
 }
 
 impl Default for VrlRuntime {
@@ -81,7 +93,8 @@ impl FromStr for VrlRuntime {
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s {
             "ast" => Ok(Self::Ast),
-            _ => Err("runtime must be ast."),
+            "vm" => Ok(Self::Vm),
+            _ => Err("runtime must be ast or vm."),
         }
     }
 }
@@ -93,6 +106,7 @@ impl Display for VrlRuntime {
             "{}",
             match self {
                 VrlRuntime::Ast => "ast",
+                VrlRuntime::Vm => "vm",
             }
         )
     }
@@ -0,0 +1,231 @@
+//! Collect the time spent resolving the expressions of a program.
+//!
+//! Profiling is opt-in, and only supported by the AST runtime. Once enabled
+//! on the [runtime state](crate::state::Runtime), each statement and function
+//! call records its invocation count and the (inclusive) time spent resolving
+//! it, keyed by its source span.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use diagnostic::{Diagnostic, DiagnosticList, Label, Severity, Span};
+
+use crate::{Context, Resolved};
+
+/// The kind of expression a profile entry was recorded for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileKind {
+    /// A statement in a block, or at the root of the program.
+    Expression,
+
+    /// A call to the function with the given name.
+    FunctionCall(String),
+}
+
+impl fmt::Display for ProfileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileKind::Expression => f.write_str("expression"),
+            ProfileKind::FunctionCall(ident) => write!(f, "function call `{}`", ident),
+        }
+    }
+}
+
+/// The invocation count and time spent resolving a single expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileEntry {
+    pub span: Span,
+    pub kind: ProfileKind,
+    pub invocations: u64,
+    pub duration: Duration,
+}
+
+/// The timings of the expressions of a program, over all runs since profiling
+/// was enabled, or since the profile was last taken.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    entries: HashMap<Span, ProfileEntry>,
+    runs: u64,
+    duration: Duration,
+}
+
+impl Profile {
+    /// The number of times the program was resolved.
+    #[must_use]
+    pub fn runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// The total time spent resolving the program.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.runs == 0 && self.entries.is_empty()
+    }
+
+    /// The recorded entries, the most expensive ones first.
+    #[must_use]
+    pub fn entries(&self) -> Vec<&ProfileEntry> {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.duration.cmp(&a.duration).then(a.span.cmp(&b.span)));
+        entries
+    }
+
+    /// Add the timings of another profile of the same program to this one.
+    pub fn merge(&mut self, other: Profile) {
+        self.runs += other.runs;
+        self.duration += other.duration;
+
+        for (span, entry) in other.entries {
+            self.entries
+                .entry(span)
+                .and_modify(|existing| {
+                    existing.invocations += entry.invocations;
+                    existing.duration += entry.duration;
+                })
+                .or_insert(entry);
+        }
+    }
+
+    /// Describe the `limit` most expensive entries as diagnostic notes,
+    /// pointing at their source, so they can be rendered with the diagnostic
+    /// formatter.
+    #[must_use]
+    pub fn diagnostics(&self, limit: usize) -> DiagnosticList {
+        let total = self.duration.as_secs_f64();
+
+        self.entries()
+            .into_iter()
+            .take(limit)
+            .map(|entry| {
+                let share = if total > 0.0 {
+                    entry.duration.as_secs_f64() / total * 100.0
+                } else {
+                    0.0
+                };
+                let per_invocation = entry.duration.as_secs_f64() / entry.invocations as f64;
+
+                Diagnostic::new(
+                    Severity::Note,
+                    0,
+                    format!(
+                        "{} took {:?} ({:.1}% of {} runs)",
+                        entry.kind, entry.duration, share, self.runs
+                    ),
+                    vec![Label::primary(
+                        format!(
+                            "{} invocations, {:?} per invocation",
+                            entry.invocations,
+                            Duration::from_secs_f64(per_invocation)
+                        ),
+                        entry.span,
+                    )],
+                    vec![],
+                )
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    pub(crate) fn record_run(&mut self, duration: Duration) {
+        self.runs += 1;
+        self.duration += duration;
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        span: Span,
+        kind: impl FnOnce() -> ProfileKind,
+        duration: Duration,
+    ) {
+        let entry = self.entries.entry(span).or_insert_with(|| ProfileEntry {
+            span,
+            kind: kind(),
+            invocations: 0,
+            duration: Duration::ZERO,
+        });
+
+        entry.invocations += 1;
+        entry.duration += duration;
+    }
+}
+
+/// Resolve the expression, recording its timing if profiling is enabled.
+pub(crate) fn resolve_profiled(
+    ctx: &mut Context,
+    span: Span,
+    kind: impl FnOnce() -> ProfileKind,
+    resolve: impl FnOnce(&mut Context) -> Resolved,
+) -> Resolved {
+    if !ctx.state().is_profiling() {
+        return resolve(ctx);
+    }
+
+    let start = Instant::now();
+    let resolved = resolve(ctx);
+    let elapsed = start.elapsed();
+
+    if let Some(profile) = ctx.state_mut().profile_mut() {
+        profile.record(span, kind, elapsed);
+    }
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_profiles() {
+        let mut profile = Profile::default();
+        profile.record(
+            Span::new(0, 5),
+            || ProfileKind::Expression,
+            Duration::from_millis(2),
+        );
+        profile.record_run(Duration::from_millis(3));
+
+        let mut other = Profile::default();
+        other.record(
+            Span::new(0, 5),
+            || ProfileKind::Expression,
+            Duration::from_millis(1),
+        );
+        other.record(
+            Span::new(6, 20),
+            || ProfileKind::FunctionCall("parse_json".to_owned()),
+            Duration::from_millis(4),
+        );
+        other.record_run(Duration::from_millis(6));
+
+        profile.merge(other);
+
+        assert_eq!(profile.runs(), 2);
+        assert_eq!(profile.duration(), Duration::from_millis(9));
+        assert_eq!(
+            profile.entries(),
+            vec![
+                &ProfileEntry {
+                    span: Span::new(6, 20),
+                    kind: ProfileKind::FunctionCall("parse_json".to_owned()),
+                    invocations: 1,
+                    duration: Duration::from_millis(4),
+                },
+                &ProfileEntry {
+                    span: Span::new(0, 5),
+                    kind: ProfileKind::Expression,
+                    invocations: 2,
+                    duration: Duration::from_millis(3),
+                },
+            ]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use lookup::LookupBuf;
 
 use crate::{
@@ -39,7 +41,19 @@ impl Program {
     ///
     /// Returns an error if the program resulted in a runtime error.
     pub fn resolve(&self, ctx: &mut Context) -> Resolved {
-        self.expressions.resolve(ctx)
+        if !ctx.state().is_profiling() {
+            return self.expressions.resolve(ctx);
+        }
+
+        let start = Instant::now();
+        let resolved = self.expressions.resolve(ctx);
+        let elapsed = start.elapsed();
+
+        if let Some(profile) = ctx.state_mut().profile_mut() {
+            profile.record_run(elapsed);
+        }
+
+        resolved
     }
 }
 
@@ -4,7 +4,9 @@ use anymap::AnyMap;
 use lookup::LookupBuf;
 use value::{Kind, Value};
 
-use crate::{parser::ast::Ident, type_def::Details, value::Collection};
+#[cfg(feature = "expr-loop")]
+use crate::parser::ast::LoopControl;
+use crate::{parser::ast::Ident, profile::Profile, type_def::Details, value::Collection};
 
 /// Local environment, limited to a given scope.
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -21,12 +23,16 @@ impl LocalEnv {
         self.bindings.get(ident)
     }
 
-    #[cfg(any(feature = "expr-assignment", feature = "expr-function_call"))]
+    #[cfg(any(
+        feature = "expr-assignment",
+        feature = "expr-function_call",
+        feature = "expr-loop"
+    ))]
     pub(crate) fn insert_variable(&mut self, ident: Ident, details: Details) {
         self.bindings.insert(ident, details);
     }
 
-    #[cfg(feature = "expr-function_call")]
+    #[cfg(any(feature = "expr-function_call", feature = "expr-loop"))]
     pub(crate) fn remove_variable(&mut self, ident: &Ident) -> Option<Details> {
         self.bindings.remove(ident)
     }
@@ -65,6 +71,10 @@ pub struct ExternalEnv {
 
     /// Custom context injected by the external environment
     custom: AnyMap,
+
+    /// The paths of the target whose type changed since tracking started, or
+    /// `None` if they aren't tracked.
+    target_mutations: Option<Vec<LookupBuf>>,
 }
 
 // temporary until paths can point to metadata
@@ -99,6 +109,7 @@ impl ExternalEnv {
             },
             custom: AnyMap::new(),
             read_only_paths: vec![],
+            target_mutations: None,
         }
     }
 
@@ -173,11 +184,47 @@ impl ExternalEnv {
         self.target().type_def.kind()
     }
 
-    #[cfg(any(feature = "expr-assignment", feature = "expr-query"))]
+    #[cfg(any(
+        feature = "expr-assignment",
+        feature = "expr-function_call",
+        feature = "expr-loop",
+        feature = "expr-query"
+    ))]
     pub(crate) fn update_target(&mut self, details: Details) {
         self.target = details;
     }
 
+    /// Record that the type of the target changed at the given path, if the
+    /// mutations of the target are tracked.
+    #[cfg(any(
+        feature = "expr-assignment",
+        feature = "expr-function_call",
+        feature = "expr-loop",
+        feature = "expr-query"
+    ))]
+    pub(crate) fn mark_target_mutated(&mut self, path: &LookupBuf) {
+        if let Some(mutations) = &mut self.target_mutations {
+            mutations.push(path.clone());
+        }
+    }
+
+    /// Start tracking the mutations of the target, returning the mutations
+    /// tracked so far, to pass to [`ExternalEnv::end_target_mutations`].
+    #[cfg(feature = "expr-function_call")]
+    pub(crate) fn start_target_mutations(&mut self) -> Option<Vec<LookupBuf>> {
+        self.target_mutations.replace(vec![])
+    }
+
+    /// Stop tracking the mutations of the target, returning the paths mutated
+    /// since tracking started, and restoring the previously tracked ones.
+    #[cfg(feature = "expr-function_call")]
+    pub(crate) fn end_target_mutations(
+        &mut self,
+        previous: Option<Vec<LookupBuf>>,
+    ) -> Vec<LookupBuf> {
+        std::mem::replace(&mut self.target_mutations, previous).unwrap_or_default()
+    }
+
     /// Sets the external context data for VRL functions to use.
     pub fn set_external_context<T: 'static>(&mut self, data: T) {
         self.custom.insert::<T>(data);
@@ -209,6 +256,14 @@ impl ExternalEnv {
 pub struct Runtime {
     /// The [`Value`] stored in each variable.
     variables: HashMap<Ident, Value>,
+
+    /// A `break` or `continue` expression that was resolved, but not yet
+    /// handled by its loop.
+    #[cfg(feature = "expr-loop")]
+    loop_control: Option<LoopControl>,
+
+    /// The timings of the resolved expressions, if profiling is enabled.
+    profile: Option<Profile>,
 }
 
 impl Runtime {
@@ -219,6 +274,11 @@ impl Runtime {
 
     pub fn clear(&mut self) {
         self.variables.clear();
+
+        #[cfg(feature = "expr-loop")]
+        {
+            self.loop_control = None;
+        }
     }
 
     #[must_use]
@@ -226,6 +286,11 @@ impl Runtime {
         self.variables.get(ident)
     }
 
+    /// The variables that are currently assigned, in no particular order.
+    pub fn variables(&self) -> impl Iterator<Item = (&Ident, &Value)> + '_ {
+        self.variables.iter()
+    }
+
     pub fn variable_mut(&mut self, ident: &Ident) -> Option<&mut Value> {
         self.variables.get_mut(ident)
     }
@@ -238,6 +303,43 @@ impl Runtime {
         self.variables.remove(ident);
     }
 
+    /// Replace all variables with the given set, returning the previous set.
+    ///
+    /// This is used to give user-defined functions their own variable scope.
+    #[cfg(feature = "expr-function_call")]
+    pub(crate) fn swap_variables(
+        &mut self,
+        variables: HashMap<Ident, Value>,
+    ) -> HashMap<Ident, Value> {
+        std::mem::replace(&mut self.variables, variables)
+    }
+
+    /// Restore a variable to a value returned by [`Runtime::swap_variable`].
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn restore_variable(&mut self, ident: Ident, value: Option<Value>) {
+        match value {
+            Some(value) => self.insert_variable(ident, value),
+            None => self.remove_variable(&ident),
+        }
+    }
+
+    /// Whether the innermost loop was interrupted by a `break` or `continue`
+    /// expression, which skips the rest of the current iteration.
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn is_loop_interrupted(&self) -> bool {
+        self.loop_control.is_some()
+    }
+
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn interrupt_loop(&mut self, control: LoopControl) {
+        self.loop_control = Some(control);
+    }
+
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn take_loop_control(&mut self) -> Option<LoopControl> {
+        self.loop_control.take()
+    }
+
     pub(crate) fn swap_variable(&mut self, ident: Ident, value: Value) -> Option<Value> {
         match self.variables.entry(ident) {
             Entry::Occupied(mut v) => Some(std::mem::replace(v.get_mut(), value)),
@@ -247,4 +349,32 @@ impl Runtime {
             }
         }
     }
+
+    /// Start recording the time spent resolving each expression.
+    ///
+    /// Unlike variables, the recorded profile is kept when the state is
+    /// cleared, so that it can be collected over many runs.
+    pub fn enable_profiling(&mut self) {
+        self.profile.get_or_insert_with(Profile::default);
+    }
+
+    #[must_use]
+    pub fn is_profiling(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    #[must_use]
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Take the profile recorded so far, leaving an empty profile to record
+    /// further runs into.
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.as_mut().map(std::mem::take)
+    }
+
+    pub(crate) fn profile_mut(&mut self) -> Option<&mut Profile> {
+        self.profile.as_mut()
+    }
 }
@@ -1,3 +1,12 @@
+use crate::{
+    compiler::Compiler,
+    function::FunctionClosure,
+    parser::Ident,
+    state::{ExternalEnv, LocalEnv},
+    type_def::Details,
+    value::Kind,
+};
+
 /// Create a boxed [`Expression`][crate::Expression] trait object from a given `Value`.
 ///
 /// Supports the same format as the [`value`] macro.
@@ -41,6 +50,46 @@ macro_rules! func_args {
     };
 }
 
+/// Compile a [`FunctionClosure`][crate::function::FunctionClosure] from the
+/// source of its block, with each closure variable bound to the given kind.
+///
+/// The block can't call any function.
+///
+/// ```ignore
+/// test_closure!(index: Kind::integer(), value: Kind::any() => "index == 0")
+/// ```
+#[macro_export]
+macro_rules! test_closure {
+    ($($variable:ident: $kind:expr),* => $source:expr) => {
+        $crate::__compile_closure(vec![$((stringify!($variable), $kind)),*], $source)
+    };
+}
+
+#[doc(hidden)]
+pub fn __compile_closure(variables: Vec<(&'static str, Kind)>, source: &str) -> FunctionClosure {
+    let mut local = LocalEnv::default();
+    for (variable, kind) in &variables {
+        let details = Details {
+            type_def: kind.clone().into(),
+            value: None,
+        };
+
+        local.insert_variable(Ident::new(*variable), details);
+    }
+
+    let ast = ::parser::parse(source).expect("valid closure block");
+    let (program, _) = Compiler::new_with_local_state(&[], local)
+        .compile(ast, &mut ExternalEnv::default())
+        .expect("closure block compiles");
+
+    let variables = variables
+        .into_iter()
+        .map(|(variable, _)| Ident::new(variable))
+        .collect::<Vec<_>>();
+
+    FunctionClosure::new(variables, program.expressions)
+}
+
 #[macro_export]
 macro_rules! bench_function {
     ($name:tt => $func:path; $($case:ident { args: $args:expr, want: $(Ok($ok:expr))? $(Err($err:expr))? $(,)* })+) => {
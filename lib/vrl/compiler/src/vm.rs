@@ -0,0 +1,14 @@
+//! A bytecode virtual machine runtime for VRL programs.
+//!
+//! Instead of walking the expression tree of a [`Program`](crate::Program) for
+//! every event, the tree is compiled once into a flat list of instructions,
+//! which are executed by a stack-based machine.
+//!
+//! Both runtimes share the same compiler, so a program that compiles for one
+//! runtime compiles for the other, and the two produce identical results.
+
+mod machine;
+mod opcode;
+
+pub use machine::Vm;
+pub(crate) use opcode::{Location, OpCode};
@@ -0,0 +1,609 @@
+use std::collections::HashMap;
+
+use value::Value;
+
+use super::{Location, OpCode};
+#[cfg(feature = "expr-assignment")]
+use crate::expression::assignment;
+#[cfg(feature = "expr-function_call")]
+use crate::expression::user_function_call;
+#[cfg(feature = "expr-loop")]
+use crate::expression::{for_loop, while_loop};
+use crate::{
+    expression::{Expr, ExpressionError, Resolved},
+    parser::Ident,
+    value::{Error, VrlValueArithmetic, VrlValueConvert},
+    Context, Expression, Program, Span,
+};
+
+/// A [`Program`] compiled to bytecode, to be run by the virtual machine.
+///
+/// A `Vm` holds no per-run state, so a single instance can be shared between
+/// any number of runtimes.
+#[derive(Debug, Clone, Default)]
+pub struct Vm {
+    instructions: Vec<OpCode>,
+    constants: Vec<Value>,
+    locations: Vec<Location>,
+    #[cfg(feature = "expr-assignment")]
+    targets: Vec<assignment::Target>,
+    expressions: Vec<Expr>,
+    object_keys: Vec<Vec<String>>,
+    functions: Vec<Function>,
+    #[cfg(feature = "expr-loop")]
+    loops: Vec<Loop>,
+
+    /// The loops enclosing the instructions currently being compiled, used to
+    /// resolve the targets of `break` and `continue`.
+    #[cfg(feature = "expr-loop")]
+    loop_targets: Vec<LoopTarget>,
+}
+
+/// A user-defined function called by the program.
+#[derive(Debug, Clone)]
+struct Function {
+    ident: Ident,
+    span: Span,
+    parameters: Vec<Ident>,
+}
+
+/// A loop in the program.
+#[cfg(feature = "expr-loop")]
+#[derive(Debug, Clone)]
+enum Loop {
+    For {
+        /// The key (if bound) and value variables.
+        variables: Vec<Ident>,
+        key: bool,
+        recursive: bool,
+    },
+    While {
+        span: Span,
+    },
+}
+
+#[cfg(feature = "expr-loop")]
+#[derive(Debug, Clone)]
+struct LoopTarget {
+    /// The position of the first instruction of an iteration.
+    start: usize,
+
+    /// The positions of the jumps of any `break` expressions, to be patched
+    /// once the end of the loop is known.
+    breaks: Vec<usize>,
+}
+
+impl Vm {
+    /// Compile the given program to bytecode.
+    #[must_use]
+    pub fn new(program: &Program) -> Self {
+        let mut vm = Self::default();
+        program.expressions.compile_to_vm(&mut vm);
+        vm
+    }
+
+    /// Run the compiled program to completion, returning its final value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program resulted in a runtime error.
+    pub fn run(&self, ctx: &mut Context) -> Resolved {
+        let mut machine = Machine {
+            vm: self,
+            ip: 0,
+            stack: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
+            error: None,
+        };
+
+        loop {
+            match machine.execute(ctx) {
+                Ok(value) => return Ok(value),
+                Err(err) => machine.recover(err, ctx)?,
+            }
+        }
+    }
+
+    pub(crate) fn write_opcode(&mut self, opcode: OpCode) {
+        self.instructions.push(opcode);
+    }
+
+    /// Write a jump instruction, returning its position so that its target can
+    /// be set using [`Vm::patch_jump`], once it is known.
+    pub(crate) fn emit_jump(&mut self, opcode: fn(usize) -> OpCode) -> usize {
+        self.write_opcode(opcode(0));
+        self.instructions.len() - 1
+    }
+
+    /// Point the jump instruction at the given position to the next
+    /// instruction to be written.
+    pub(crate) fn patch_jump(&mut self, position: usize) {
+        let target = self.instructions.len();
+
+        self.instructions[position] = match self.instructions[position] {
+            OpCode::Jump(_) => OpCode::Jump(target),
+            OpCode::JumpIfFalse(_) => OpCode::JumpIfFalse(target),
+            OpCode::JumpIfTruthy(_) => OpCode::JumpIfTruthy(target),
+            OpCode::JumpIfFalsy(_) => OpCode::JumpIfFalsy(target),
+            OpCode::Try(_) => OpCode::Try(target),
+            OpCode::Iterate(_) => OpCode::Iterate(target),
+            opcode => unreachable!("not a jump instruction: {:?}", opcode),
+        };
+    }
+
+    pub(crate) fn add_constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    pub(crate) fn add_location(&mut self, location: Location) -> usize {
+        self.locations.push(location);
+        self.locations.len() - 1
+    }
+
+    #[cfg(feature = "expr-assignment")]
+    pub(crate) fn add_target(&mut self, target: assignment::Target) -> usize {
+        self.targets.push(target);
+        self.targets.len() - 1
+    }
+
+    pub(crate) fn add_expression(&mut self, expr: Expr) -> usize {
+        self.expressions.push(expr);
+        self.expressions.len() - 1
+    }
+
+    pub(crate) fn add_object_keys(&mut self, keys: Vec<String>) -> usize {
+        self.object_keys.push(keys);
+        self.object_keys.len() - 1
+    }
+
+    pub(crate) fn add_function(
+        &mut self,
+        ident: Ident,
+        span: Span,
+        parameters: Vec<Ident>,
+    ) -> usize {
+        self.functions.push(Function {
+            ident,
+            span,
+            parameters,
+        });
+        self.functions.len() - 1
+    }
+
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn add_for_loop(
+        &mut self,
+        variables: Vec<Ident>,
+        key: bool,
+        recursive: bool,
+    ) -> usize {
+        self.loops.push(Loop::For {
+            variables,
+            key,
+            recursive,
+        });
+        self.loops.len() - 1
+    }
+
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn add_while_loop(&mut self, span: Span) -> usize {
+        self.loops.push(Loop::While { span });
+        self.loops.len() - 1
+    }
+
+    /// Mark the next instruction to be written as the start of an iteration of
+    /// a new loop, returning its position.
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn begin_loop(&mut self) -> usize {
+        let start = self.instructions.len();
+        self.loop_targets.push(LoopTarget {
+            start,
+            breaks: vec![],
+        });
+        start
+    }
+
+    /// Point any `break` expressions of the innermost loop to the next
+    /// instruction to be written.
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn end_loop(&mut self) {
+        let target = self.loop_targets.pop().expect("loop target");
+
+        for position in target.breaks {
+            self.patch_jump(position);
+        }
+    }
+
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn emit_break(&mut self) {
+        let position = self.emit_jump(OpCode::Jump);
+        self.loop_targets
+            .last_mut()
+            .expect("loop target")
+            .breaks
+            .push(position);
+    }
+
+    #[cfg(feature = "expr-loop")]
+    pub(crate) fn emit_continue(&mut self) {
+        let start = self.loop_targets.last().expect("loop target").start;
+        self.write_opcode(OpCode::Jump(start));
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// The state of a single run of a [`Vm`].
+struct Machine<'a> {
+    vm: &'a Vm,
+
+    /// The position of the next instruction to execute.
+    ip: usize,
+
+    stack: Vec<Value>,
+
+    /// The variables of the callers of any user-defined function, and the
+    /// state of any loop, currently being executed.
+    frames: Vec<Frame>,
+
+    handlers: Vec<Handler>,
+
+    /// The last error caught by an error handler.
+    error: Option<ExpressionError>,
+}
+
+enum Frame {
+    Function {
+        function: usize,
+        variables: HashMap<Ident, Value>,
+    },
+    #[cfg(feature = "expr-loop")]
+    Loop(LoopFrame),
+}
+
+#[cfg(feature = "expr-loop")]
+struct LoopFrame {
+    index: usize,
+
+    /// The values of the variables shadowed by the loop variables, to restore
+    /// once the loop ends.
+    shadowed: Vec<(Ident, Option<Value>)>,
+
+    /// The remaining entries of a `for` loop.
+    entries: std::vec::IntoIter<(Value, Value)>,
+
+    /// The number of iterations of a `while` loop so far.
+    iterations: usize,
+}
+
+struct Handler {
+    /// The instruction to continue execution at, once an error is caught.
+    target: usize,
+
+    /// The stack size and number of frames when the handler was registered,
+    /// to restore once an error is caught.
+    stack: usize,
+    frames: usize,
+}
+
+impl<'a> Machine<'a> {
+    fn execute(&mut self, ctx: &mut Context) -> Resolved {
+        let vm = self.vm;
+
+        while let Some(&opcode) = vm.instructions.get(self.ip) {
+            self.ip += 1;
+
+            match opcode {
+                OpCode::Constant(index) => {
+                    let value = vm.constants[index].clone();
+                    self.stack.push(value);
+                }
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetPath(index) => {
+                    let value = match &vm.locations[index] {
+                        Location::External(path) => {
+                            ctx.target().target_get(path).ok().flatten().cloned()
+                        }
+                        Location::Internal(ident, path) => ctx
+                            .state()
+                            .variable(ident)
+                            .and_then(|value| value.get_by_path(path))
+                            .cloned(),
+                        Location::Stack(path) => self.pop().get_by_path(path).cloned(),
+                    };
+
+                    self.stack.push(value.unwrap_or(Value::Null));
+                }
+                #[cfg(feature = "expr-assignment")]
+                OpCode::SetPath(index) => {
+                    let value = self.peek().clone();
+                    vm.targets[index].insert(value, ctx);
+                }
+                #[cfg(not(feature = "expr-assignment"))]
+                OpCode::SetPath(_) => unreachable!("assignments are disabled"),
+                OpCode::Add => self.binary(Value::try_add)?,
+                OpCode::Subtract => self.binary(Value::try_sub)?,
+                OpCode::Multiply => self.binary(Value::try_mul)?,
+                OpCode::Divide => self.binary(Value::try_div)?,
+                OpCode::Rem => self.binary(Value::try_rem)?,
+                OpCode::Merge => self.binary(Value::try_merge)?,
+                OpCode::Equal => {
+                    self.binary(|lhs, rhs| Ok::<_, Error>(lhs.eq_lossy(&rhs).into()))?;
+                }
+                OpCode::NotEqual => {
+                    self.binary(|lhs, rhs| Ok::<_, Error>((!lhs.eq_lossy(&rhs)).into()))?;
+                }
+                OpCode::Greater => self.binary(Value::try_gt)?,
+                OpCode::GreaterEqual => self.binary(Value::try_ge)?,
+                OpCode::Less => self.binary(Value::try_lt)?,
+                OpCode::LessEqual => self.binary(Value::try_le)?,
+                OpCode::And => self.binary(Value::try_and)?,
+                OpCode::Not => {
+                    let value = !self.pop().try_boolean()?;
+                    self.stack.push(value.into());
+                }
+                OpCode::CreateArray(len) => {
+                    let values = self.stack.split_off(self.stack.len() - len);
+                    self.stack.push(Value::Array(values));
+                }
+                OpCode::CreateObject(index) => {
+                    let keys = &vm.object_keys[index];
+                    let values = self.stack.split_off(self.stack.len() - keys.len());
+                    let object = keys.iter().cloned().zip(values).collect();
+
+                    self.stack.push(Value::Object(object));
+                }
+                OpCode::Jump(target) => self.ip = target,
+                OpCode::JumpIfFalse(target) => {
+                    if !self.pop().try_boolean()? {
+                        self.ip = target;
+                    }
+                }
+                OpCode::JumpIfTruthy(target) => match self.peek() {
+                    Value::Null | Value::Boolean(false) => {
+                        self.pop();
+                    }
+                    _ => self.ip = target,
+                },
+                OpCode::JumpIfFalsy(target) => {
+                    if let Value::Null | Value::Boolean(false) = self.peek() {
+                        self.pop();
+                        self.stack.push(false.into());
+                        self.ip = target;
+                    }
+                }
+                OpCode::Try(target) => self.handlers.push(Handler {
+                    target,
+                    stack: self.stack.len(),
+                    frames: self.frames.len(),
+                }),
+                OpCode::EndTry => {
+                    self.handlers.pop();
+                }
+                OpCode::PushError => {
+                    let error = self.error.take().expect("caught error");
+                    self.stack.push(error.to_string().into());
+                }
+                OpCode::RaiseOr => {
+                    let error = self.error.take().expect("caught error");
+                    return Err(Error::Or(error).into());
+                }
+                OpCode::Call(index) => {
+                    let value = vm.expressions[index].resolve(ctx)?;
+                    self.stack.push(value);
+                }
+                #[cfg(feature = "expr-function_call")]
+                OpCode::EnterFunction(index) => {
+                    let parameters = &vm.functions[index].parameters;
+                    let arguments = self.stack.split_off(self.stack.len() - parameters.len());
+                    let variables = parameters.iter().cloned().zip(arguments).collect();
+
+                    let variables = ctx.state_mut().swap_variables(variables);
+                    self.frames.push(Frame::Function {
+                        function: index,
+                        variables,
+                    });
+                }
+                #[cfg(feature = "expr-function_call")]
+                OpCode::ExitFunction => {
+                    if let Some(Frame::Function { variables, .. }) = self.frames.pop() {
+                        ctx.state_mut().swap_variables(variables);
+                    } else {
+                        unreachable!("function frame");
+                    }
+                }
+                #[cfg(not(feature = "expr-function_call"))]
+                OpCode::EnterFunction(_) | OpCode::ExitFunction => {
+                    unreachable!("function calls are disabled")
+                }
+                #[cfg(feature = "expr-loop")]
+                OpCode::EnterLoop(index) => self.enter_loop(index, ctx),
+                #[cfg(feature = "expr-loop")]
+                OpCode::Iterate(target) => self.iterate(target, ctx)?,
+                #[cfg(feature = "expr-loop")]
+                OpCode::ExitLoop => match self.frames.pop() {
+                    Some(Frame::Loop(frame)) => frame.exit(ctx),
+                    _ => unreachable!("loop frame"),
+                },
+                #[cfg(not(feature = "expr-loop"))]
+                OpCode::EnterLoop(_) | OpCode::Iterate(_) | OpCode::ExitLoop => {
+                    unreachable!("loops are disabled")
+                }
+                OpCode::Abort { span, message } => {
+                    let message = if message {
+                        Some(self.pop().try_bytes_utf8_lossy()?.to_string())
+                    } else {
+                        None
+                    };
+
+                    #[cfg(feature = "expr-abort")]
+                    return Err(ExpressionError::Abort { span, message });
+
+                    #[cfg(not(feature = "expr-abort"))]
+                    unreachable!("abort is disabled: {:?}, {:?}", span, message);
+                }
+            }
+        }
+
+        Ok(self.stack.pop().unwrap_or(Value::Null))
+    }
+
+    /// Recover from a runtime error, by continuing execution at the last
+    /// registered error handler.
+    ///
+    /// Any user-defined functions exited while unwinding have their caller's
+    /// variables restored, and the error annotated, similar to how the error
+    /// propagates through the expression tree. Any loops exited have their
+    /// shadowed variables restored.
+    ///
+    /// The error is returned if no error handler exists.
+    fn recover(
+        &mut self,
+        mut error: ExpressionError,
+        ctx: &mut Context,
+    ) -> Result<(), ExpressionError> {
+        let frames = self.handlers.last().map_or(0, |handler| handler.frames);
+
+        while self.frames.len() > frames {
+            match self.frames.pop().expect("frame") {
+                Frame::Function {
+                    function,
+                    variables,
+                } => error = self.vm.functions[function].exit(variables, error, ctx),
+                #[cfg(feature = "expr-loop")]
+                Frame::Loop(frame) => frame.exit(ctx),
+            }
+        }
+
+        let handler = match self.handlers.pop() {
+            Some(handler) => handler,
+            None => return Err(error),
+        };
+
+        self.stack.truncate(handler.stack);
+        self.ip = handler.target;
+        self.error = Some(error);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "expr-loop")]
+    fn enter_loop(&mut self, index: usize, ctx: &mut Context) {
+        let vm = self.vm;
+        let (shadowed, entries) = match &vm.loops[index] {
+            Loop::For {
+                variables,
+                recursive,
+                ..
+            } => {
+                let shadowed = variables
+                    .iter()
+                    .map(|ident| (ident.clone(), ctx.state().variable(ident).cloned()))
+                    .collect();
+                let entries = for_loop::entries(self.pop(), *recursive);
+
+                (shadowed, entries)
+            }
+            Loop::While { .. } => (vec![], vec![]),
+        };
+
+        self.frames.push(Frame::Loop(LoopFrame {
+            index,
+            shadowed,
+            entries: entries.into_iter(),
+            iterations: 0,
+        }));
+    }
+
+    #[cfg(feature = "expr-loop")]
+    fn iterate(&mut self, target: usize, ctx: &mut Context) -> Result<(), ExpressionError> {
+        let vm = self.vm;
+        let frame = match self.frames.last_mut() {
+            Some(Frame::Loop(frame)) => frame,
+            _ => unreachable!("loop frame"),
+        };
+
+        match &vm.loops[frame.index] {
+            Loop::For { variables, key, .. } => match frame.entries.next() {
+                Some((k, v)) => {
+                    let values = if *key { vec![k, v] } else { vec![v] };
+
+                    for (ident, value) in variables.iter().zip(values) {
+                        ctx.state_mut().insert_variable(ident.clone(), value);
+                    }
+                }
+                None => self.ip = target,
+            },
+            Loop::While { span } => {
+                frame.iterations += 1;
+
+                if frame.iterations > while_loop::MAX_ITERATIONS {
+                    return Err(while_loop::limit_exceeded(*span));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn binary<E>(
+        &mut self,
+        op: impl FnOnce(Value, Value) -> Result<Value, E>,
+    ) -> Result<(), ExpressionError>
+    where
+        E: Into<ExpressionError>,
+    {
+        let rhs = self.pop();
+        let lhs = self.pop();
+
+        self.stack.push(op(lhs, rhs).map_err(Into::into)?);
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    fn peek(&self) -> &Value {
+        self.stack.last().expect("stack underflow")
+    }
+}
+
+#[cfg(feature = "expr-loop")]
+impl LoopFrame {
+    fn exit(self, ctx: &mut Context) {
+        for (ident, value) in self.shadowed {
+            ctx.state_mut().restore_variable(ident, value);
+        }
+    }
+}
+
+impl Function {
+    /// Exit the function because of the given error, restoring the variables
+    /// of its caller.
+    #[cfg(feature = "expr-function_call")]
+    fn exit(
+        &self,
+        variables: HashMap<Ident, Value>,
+        error: ExpressionError,
+        ctx: &mut Context,
+    ) -> ExpressionError {
+        ctx.state_mut().swap_variables(variables);
+        user_function_call::annotate_error(&self.ident, self.span, error)
+    }
+
+    #[cfg(not(feature = "expr-function_call"))]
+    fn exit(
+        &self,
+        _: HashMap<Ident, Value>,
+        _: ExpressionError,
+        _: &mut Context,
+    ) -> ExpressionError {
+        unreachable!("function calls are disabled: {}", self.ident)
+    }
+}
@@ -0,0 +1,125 @@
+use lookup::LookupBuf;
+
+use crate::{parser::Ident, Span};
+
+/// A single instruction of the virtual machine.
+///
+/// Any data an instruction needs that can't be stored inline (such as values,
+/// paths or expressions) is stored in the [`Vm`](super::Vm), and referenced by
+/// its index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum OpCode {
+    /// Push the constant at the given index onto the stack.
+    Constant(usize),
+
+    /// Discard the value at the top of the stack.
+    Pop,
+
+    /// Push the value found at the [`Location`] with the given index.
+    GetPath(usize),
+
+    /// Store a copy of the value at the top of the stack in the assignment
+    /// target with the given index. The value remains on the stack.
+    SetPath(usize),
+
+    /// Pop two values, and push the result of applying the operation to them.
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Rem,
+    Merge,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    And,
+
+    /// Pop a boolean, and push its negation.
+    Not,
+
+    /// Pop the given number of values, and push them as an array.
+    CreateArray(usize),
+
+    /// Pop one value for each of the object keys stored at the given index, and
+    /// push them as an object.
+    CreateObject(usize),
+
+    /// Continue execution at the given instruction.
+    Jump(usize),
+
+    /// Pop a boolean, and continue execution at the given instruction if it
+    /// is `false`.
+    JumpIfFalse(usize),
+
+    /// Continue execution at the given instruction if the value at the top of
+    /// the stack is neither `null` nor `false`. Otherwise the value is
+    /// discarded.
+    JumpIfTruthy(usize),
+
+    /// Continue execution at the given instruction if the value at the top of
+    /// the stack is `null` or `false`, replacing it with `false`.
+    JumpIfFalsy(usize),
+
+    /// Register an error handler. If any error occurs before the matching
+    /// [`OpCode::EndTry`], execution continues at the given instruction.
+    Try(usize),
+
+    /// Remove the most recently registered error handler.
+    EndTry,
+
+    /// Push the message of the error caught by the last error handler.
+    PushError,
+
+    /// Raise the error caught by the last error handler, as the result of the
+    /// right-hand side of an `||` operation.
+    RaiseOr,
+
+    /// Resolve the function call expression with the given index, and push its
+    /// result.
+    Call(usize),
+
+    /// Pop the arguments of the user-defined function with the given index,
+    /// and bind them as the only variables of a new scope.
+    EnterFunction(usize),
+
+    /// Restore the variables of the scope active before the last
+    /// [`OpCode::EnterFunction`].
+    ExitFunction,
+
+    /// Enter the loop with the given index. A `for` loop pops the collection
+    /// to iterate over.
+    EnterLoop(usize),
+
+    /// Start the next iteration of the innermost loop.
+    ///
+    /// A `for` loop binds its variables to the next entry of the collection,
+    /// or continues execution at the given instruction if there are none left.
+    /// A `while` loop aborts the program once it exceeds the maximum number of
+    /// iterations.
+    Iterate(usize),
+
+    /// Restore the variables shadowed by the innermost loop, and leave it.
+    ExitLoop,
+
+    /// Abort the program, popping the abort message if there is one.
+    Abort {
+        span: Span,
+        message: bool,
+    },
+}
+
+/// A location from which the virtual machine can read a value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Location {
+    /// A path in the external target.
+    External(LookupBuf),
+
+    /// A path in a variable.
+    Internal(Ident, LookupBuf),
+
+    /// A path in the value popped from the top of the stack.
+    Stack(LookupBuf),
+}
@@ -60,6 +60,10 @@ impl Diagnostic {
         self.severity
     }
 
+    pub fn code(&self) -> usize {
+        self.code
+    }
+
     pub fn message(&self) -> &str {
         &self.message
     }
@@ -117,27 +121,42 @@ impl From<Box<dyn DiagnosticMessage>> for Diagnostic {
     }
 }
 
-impl From<Diagnostic> for diagnostic::Diagnostic<()> {
-    fn from(diag: Diagnostic) -> Self {
-        let mut notes = diag.notes.to_vec();
+impl Diagnostic {
+    /// Convert the diagnostic into a [`diagnostic::Diagnostic`], using
+    /// `resolve` to map each label span to the file it belongs to.
+    pub(crate) fn into_codespan<FileId>(
+        self,
+        resolve: impl Fn(Span) -> (FileId, Span),
+    ) -> diagnostic::Diagnostic<FileId> {
+        let mut notes = self.notes.to_vec();
 
         // not all codes have a page on the site yet
-        if diag.code >= 100 && diag.code <= 110 {
-            notes.push(Note::SeeCodeDocs(diag.code));
+        if self.code >= 100 && self.code <= 110 {
+            notes.push(Note::SeeCodeDocs(self.code));
         }
 
         notes.push(Note::SeeLangDocs);
 
         diagnostic::Diagnostic {
-            severity: diag.severity.into(),
-            code: Some(format!("E{:03}", diag.code)),
-            message: diag.message.to_string(),
-            labels: diag.labels.iter().cloned().map(Into::into).collect(),
+            severity: self.severity.into(),
+            code: Some(format!("E{:03}", self.code)),
+            message: self.message.to_string(),
+            labels: self
+                .labels
+                .into_iter()
+                .map(|label| label.into_codespan(&resolve))
+                .collect(),
             notes: notes.iter().map(ToString::to_string).collect(),
         }
     }
 }
 
+impl From<Diagnostic> for diagnostic::Diagnostic<()> {
+    fn from(diag: Diagnostic) -> Self {
+        diag.into_codespan(|span| ((), span))
+    }
+}
+
 // -----------------------------------------------------------------------------
 
 #[derive(Debug, Clone, Default, PartialEq)]
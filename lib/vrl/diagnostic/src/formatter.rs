@@ -1,23 +1,46 @@
 use std::fmt;
 
-use crate::DiagnosticList;
+use crate::{DiagnosticList, Span};
 
 /// A formatter to display diagnostics tied to a given source.
 pub struct Formatter<'a> {
     source: &'a str,
+    files: Vec<File<'a>>,
     diagnostics: DiagnosticList,
     color: bool,
 }
 
+/// An additional source file, whose spans start at `offset` within the span
+/// space shared with the main source.
+struct File<'a> {
+    name: String,
+    source: &'a str,
+    offset: usize,
+}
+
 impl<'a> Formatter<'a> {
     pub fn new(source: &'a str, diagnostics: impl Into<DiagnosticList>) -> Self {
         Self {
             source,
+            files: vec![],
             diagnostics: diagnostics.into(),
             color: false,
         }
     }
 
+    /// Register an additional named source file (e.g. an imported module).
+    ///
+    /// Any diagnostic label with a span in the `offset..offset + source.len()`
+    /// range is rendered against this file, instead of the main source.
+    pub fn with_file(mut self, name: impl Into<String>, source: &'a str, offset: usize) -> Self {
+        self.files.push(File {
+            name: name.into(),
+            source,
+            offset,
+        });
+        self
+    }
+
     pub fn colored(mut self) -> Self {
         self.color = true;
         self
@@ -36,14 +59,36 @@ impl<'a> fmt::Display for Formatter<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use std::str::from_utf8;
 
-        use codespan_reporting::{files::SimpleFile, term};
+        use codespan_reporting::{files::SimpleFiles, term};
         use termcolor::Buffer;
 
         if self.diagnostics.is_empty() {
             return Ok(());
         }
 
-        let file = SimpleFile::new("", self.source);
+        let mut files = SimpleFiles::new();
+        let main_id = files.add(String::new(), self.source);
+        let file_ids = self
+            .files
+            .iter()
+            .map(|file| {
+                let id = files.add(file.name.clone(), file.source);
+                (id, file.offset, file.offset + file.source.len())
+            })
+            .collect::<Vec<_>>();
+
+        let resolve = |span: Span| {
+            file_ids
+                .iter()
+                .find(|(_, start, end)| span.start() >= *start && span.start() <= *end)
+                .map_or((main_id, span), |(id, offset, _)| {
+                    (
+                        *id,
+                        Span::new(span.start() - offset, span.end().saturating_sub(*offset)),
+                    )
+                })
+        };
+
         let config = term::Config::default();
         let mut buffer = if self.color {
             Buffer::ansi()
@@ -54,8 +99,9 @@ impl<'a> fmt::Display for Formatter<'a> {
         f.write_str("\n")?;
 
         for diagnostic in self.diagnostics.iter() {
-            term::emit(&mut buffer, &config, &file, &diagnostic.to_owned().into())
-                .map_err(|_| fmt::Error)?;
+            let diagnostic = diagnostic.to_owned().into_codespan(&resolve);
+
+            term::emit(&mut buffer, &config, &files, &diagnostic).map_err(|_| fmt::Error)?;
         }
 
         // Diagnostic messages can contain whitespace at the end of some lines.
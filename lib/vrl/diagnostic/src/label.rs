@@ -27,18 +27,31 @@ impl Label {
     }
 }
 
-impl From<Label> for diagnostic::Label<()> {
-    fn from(label: Label) -> Self {
-        let style = match label.primary {
+impl Label {
+    /// Convert the label into a [`diagnostic::Label`], using `resolve` to map
+    /// the label span to the file it belongs to, and its span within that file.
+    pub(crate) fn into_codespan<FileId>(
+        self,
+        resolve: impl Fn(Span) -> (FileId, Span),
+    ) -> diagnostic::Label<FileId> {
+        let style = match self.primary {
             true => diagnostic::LabelStyle::Primary,
             false => diagnostic::LabelStyle::Secondary,
         };
 
+        let (file_id, span) = resolve(self.span);
+
         diagnostic::Label {
             style,
-            file_id: (),
-            range: label.span.start()..label.span.end(),
-            message: label.message,
+            file_id,
+            range: span.start()..span.end(),
+            message: self.message,
         }
     }
 }
+
+impl From<Label> for diagnostic::Label<()> {
+    fn from(label: Label) -> Self {
+        label.into_codespan(|span| ((), span))
+    }
+}
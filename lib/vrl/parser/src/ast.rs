@@ -187,6 +187,13 @@ impl IntoIterator for Program {
 pub enum RootExpr {
     Expr(Node<Expr>),
 
+    /// A user-defined function, callable from any other expression in the
+    /// program.
+    FunctionDefinition(Node<FunctionDefinition>),
+
+    /// An import of another source file, containing function definitions.
+    Import(Node<Import>),
+
     /// A special expression that is returned if a given expression could not be
     /// parsed. This allows the parser to continue on to the next expression.
     Error(Error),
@@ -194,10 +201,12 @@ pub enum RootExpr {
 
 impl fmt::Debug for RootExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use RootExpr::{Error, Expr};
+        use RootExpr::{Error, Expr, FunctionDefinition, Import};
 
         let value = match self {
             Expr(v) => format!("{:?}", v),
+            FunctionDefinition(v) => format!("{:?}", v),
+            Import(v) => format!("{:?}", v),
             Error(v) => format!("{:?}", v),
         };
 
@@ -207,10 +216,12 @@ impl fmt::Debug for RootExpr {
 
 impl fmt::Display for RootExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use RootExpr::{Error, Expr};
+        use RootExpr::{Error, Expr, FunctionDefinition, Import};
 
         match self {
             Expr(v) => v.fmt(f),
+            FunctionDefinition(v) => v.fmt(f),
+            Import(v) => v.fmt(f),
             Error(v) => v.fmt(f),
         }
     }
@@ -233,13 +244,15 @@ pub enum Expr {
     Variable(Node<Ident>),
     Unary(Node<Unary>),
     Abort(Node<Abort>),
+    Loop(Node<Loop>),
+    LoopControl(Node<LoopControl>),
 }
 
 impl fmt::Debug for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Op, Query, Unary,
-            Variable,
+            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Loop, LoopControl,
+            Op, Query, Unary, Variable,
         };
 
         let value = match self {
@@ -253,6 +266,8 @@ impl fmt::Debug for Expr {
             Variable(v) => format!("{:?}", v),
             Unary(v) => format!("{:?}", v),
             Abort(v) => format!("{:?}", v),
+            Loop(v) => format!("{:?}", v),
+            LoopControl(v) => format!("{:?}", v),
         };
 
         write!(f, "Expr({})", value)
@@ -262,8 +277,8 @@ impl fmt::Debug for Expr {
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Expr::{
-            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Op, Query, Unary,
-            Variable,
+            Abort, Assignment, Container, FunctionCall, IfStatement, Literal, Loop, LoopControl,
+            Op, Query, Unary, Variable,
         };
 
         match self {
@@ -277,6 +292,8 @@ impl fmt::Display for Expr {
             Variable(v) => v.fmt(f),
             Unary(v) => v.fmt(f),
             Abort(v) => v.fmt(f),
+            Loop(v) => v.fmt(f),
+            LoopControl(v) => v.fmt(f),
         }
     }
 }
@@ -667,6 +684,123 @@ impl fmt::Debug for Predicate {
     }
 }
 
+// -----------------------------------------------------------------------------
+// loop
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, PartialEq)]
+pub enum Loop {
+    /// Iterate over the elements of an array, or the fields of an object.
+    For(ForLoop),
+
+    /// Iterate over all values nested within an array or object, at any depth.
+    Walk(ForLoop),
+
+    /// Iterate for as long as the predicate resolves to `true`.
+    While(WhileLoop),
+}
+
+impl fmt::Display for Loop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loop::For(v) => write!(f, "for {}", v),
+            Loop::Walk(v) => write!(f, "walk {}", v),
+            Loop::While(v) => v.fmt(f),
+        }
+    }
+}
+
+impl fmt::Debug for Loop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loop::For(v) => write!(f, "For({:?})", v),
+            Loop::Walk(v) => write!(f, "Walk({:?})", v),
+            Loop::While(v) => write!(f, "While({:?})", v),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ForLoop {
+    pub key: Option<Node<Ident>>,
+    pub value: Node<Ident>,
+    pub expr: Box<Node<Expr>>,
+    pub block: Node<Block>,
+}
+
+impl fmt::Display for ForLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variable = |ident: &Node<Ident>| {
+            if ident.is_empty() {
+                "_".to_owned()
+            } else {
+                ident.to_string()
+            }
+        };
+
+        if let Some(key) = &self.key {
+            write!(f, "{}, ", variable(key))?;
+        }
+
+        write!(f, "{} in {} {}", variable(&self.value), self.expr, self.block)
+    }
+}
+
+impl fmt::Debug for ForLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}, {:?} in {:?} {:?}",
+            self.key, self.value, self.expr, self.block
+        )
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct WhileLoop {
+    pub predicate: Node<Predicate>,
+    pub block: Node<Block>,
+}
+
+impl fmt::Display for WhileLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "while {} {}", self.predicate, self.block)
+    }
+}
+
+impl fmt::Debug for WhileLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {:?}", self.predicate, self.block)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum LoopControl {
+    /// Stop the innermost loop.
+    Break,
+
+    /// Continue with the next iteration of the innermost loop.
+    Continue,
+}
+
+impl fmt::Display for LoopControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopControl::Break => f.write_str("break"),
+            LoopControl::Continue => f.write_str("continue"),
+        }
+    }
+}
+
+impl fmt::Debug for LoopControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopControl::Break => f.write_str("Break"),
+            LoopControl::Continue => f.write_str("Continue"),
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // operation
 // -----------------------------------------------------------------------------
@@ -1116,6 +1250,71 @@ impl fmt::Debug for FunctionClosure {
     }
 }
 
+// -----------------------------------------------------------------------------
+// function definition
+// -----------------------------------------------------------------------------
+
+/// A user-defined function.
+///
+/// The function body has access to the external target, but not to any
+/// variables defined outside of the function.
+#[derive(Clone, PartialEq)]
+pub struct FunctionDefinition {
+    pub ident: Node<Ident>,
+    pub parameters: Vec<Node<Ident>>,
+    pub block: Node<Block>,
+}
+
+impl fmt::Display for FunctionDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fn {}(", self.ident)?;
+
+        let mut iter = self.parameters.iter().peekable();
+        while let Some(param) = iter.next() {
+            param.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        f.write_str(") ")?;
+        self.block.fmt(f)
+    }
+}
+
+impl fmt::Debug for FunctionDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FunctionDefinition({:?}({:?}) {:?})",
+            self.ident, self.parameters, self.block
+        )
+    }
+}
+
+// -----------------------------------------------------------------------------
+// import
+// -----------------------------------------------------------------------------
+
+/// An import of another source file, relative to the importing source.
+#[derive(Clone, PartialEq)]
+pub struct Import {
+    pub path: Node<String>,
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, r#"import "{}""#, self.path)
+    }
+}
+
+impl fmt::Debug for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Import({:?})", self.path.inner())
+    }
+}
+
 // -----------------------------------------------------------------------------
 // unary
 // -----------------------------------------------------------------------------
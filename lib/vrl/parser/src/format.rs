@@ -0,0 +1,655 @@
+//! A pretty-printer, turning a VRL program back into its canonical source form.
+//!
+//! The printer works on the AST, but keeps the source text of literals, queries
+//! and assignment targets as they were written. Comments aren't part of the
+//! AST, so they're recovered from the gaps between the tokens of the source,
+//! and placed next to the closest expression.
+
+use diagnostic::Span;
+
+use crate::{
+    ast::{
+        Assignment, AssignmentTarget, Block, Container, Expr, ForLoop, FunctionArgument,
+        FunctionCall, Ident, IfStatement, Loop, Node, Predicate, QueryTarget, RootExpr, Unary,
+    },
+    lex::Lexer,
+    Error, Token,
+};
+
+/// The indentation of a single nesting level.
+const INDENT: &str = "    ";
+
+/// The width beyond which arrays, objects and function arguments are spread
+/// over multiple lines.
+const MAX_WIDTH: usize = 100;
+
+/// Format the given VRL source.
+///
+/// Comments are kept. Arrays, objects and function arguments stay on a single
+/// line, unless they already span multiple lines, contain comments, or would
+/// grow beyond 100 characters.
+///
+/// # Errors
+///
+/// Returns an error if the source can't be parsed.
+pub fn format(source: &str) -> Result<String, Error> {
+    let program = crate::parse(source)?;
+
+    if let Some(error) = program.iter().find_map(|expr| match expr.inner() {
+        RootExpr::Error(error) => Some(error.clone()),
+        _ => None,
+    }) {
+        return Err(error);
+    }
+
+    let mut formatter = Formatter {
+        source,
+        comments: comments(source)?,
+        next_comment: 0,
+        out: String::new(),
+        indent: 0,
+    };
+
+    let items = program.iter().map(Item::Root).collect::<Vec<_>>();
+    formatter.lines(&items, source.len(), "");
+
+    let out = formatter.out.trim();
+    if out.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(format!("{}\n", out))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Comment<'a> {
+    start: usize,
+    end: usize,
+    text: &'a str,
+}
+
+/// Collect the comments of the source, in order.
+///
+/// Comments are skipped by the lexer, so they can only appear in between two
+/// tokens (or after the last one).
+fn comments(source: &str) -> Result<Vec<Comment<'_>>, Error> {
+    let mut comments = vec![];
+    let mut end = 0;
+
+    for token in Lexer::new(source) {
+        let (start, token, token_end) = token?;
+
+        // Query boundaries don't represent physical characters.
+        if matches!(token, Token::LQuery | Token::RQuery) {
+            continue;
+        }
+
+        comments.extend(comment(source, end, start));
+        end = token_end;
+    }
+
+    comments.extend(comment(source, end, source.len()));
+
+    Ok(comments)
+}
+
+fn comment(source: &str, start: usize, end: usize) -> Option<Comment<'_>> {
+    let gap = source.get(start..end)?;
+    let start = start + gap.find('#')?;
+    let text = source[start..end]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_end();
+
+    Some(Comment {
+        start,
+        end: start + text.len(),
+        text,
+    })
+}
+
+/// An entry in a list that's formatted one entry per line.
+#[derive(Clone, Copy)]
+enum Item<'b> {
+    Root(&'b Node<RootExpr>),
+    Expr(&'b Node<Expr>),
+    Field(&'b Node<String>, &'b Node<Expr>),
+    Argument(&'b Node<FunctionArgument>),
+}
+
+impl Item<'_> {
+    fn span(&self) -> Span {
+        match self {
+            Item::Root(node) => node.span(),
+            Item::Expr(node) => node.span(),
+            Item::Field(key, value) => Span::new(key.start(), value.end()),
+            Item::Argument(node) => node.span(),
+        }
+    }
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: Vec<Comment<'a>>,
+    next_comment: usize,
+    out: String,
+    indent: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn text(&self, span: Span) -> &'a str {
+        self.source[span.start()..span.end()].trim()
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    fn column(&self) -> usize {
+        let line = self.out.rfind('\n').map_or(0, |i| i + 1);
+        self.out[line..].chars().count()
+    }
+
+    /// Get the next unplaced comment, if it starts before the given position.
+    fn peek_comment(&self, before: usize) -> Option<Comment<'a>> {
+        self.comments
+            .get(self.next_comment)
+            .filter(|comment| comment.start < before)
+            .copied()
+    }
+
+    /// Whether the source has an empty line between the two positions.
+    fn has_blank_line(&self, from: Option<usize>, to: usize) -> bool {
+        from.and_then(|from| self.source.get(from..to))
+            .map_or(false, |gap| gap.matches('\n').count() > 1)
+    }
+
+    /// Place all comments starting before the given position.
+    ///
+    /// A comment on the same line as the previous entry is kept at the end of
+    /// that line, any other comment gets a line of its own.
+    fn comments_before(&mut self, before: usize, previous: &mut Option<usize>) {
+        while let Some(comment) = self.peek_comment(before) {
+            self.next_comment += 1;
+
+            let same_line = previous
+                .and_then(|from| self.source.get(from..comment.start))
+                .map_or(false, |gap| !gap.contains('\n'));
+
+            if same_line {
+                self.write(" ");
+            } else {
+                if self.has_blank_line(*previous, comment.start) {
+                    self.out.push('\n');
+                }
+                self.newline();
+            }
+
+            self.write(comment.text);
+            *previous = Some(comment.end);
+        }
+    }
+
+    /// Format the items one per line, each followed by the separator.
+    ///
+    /// Comments up to the `end` position are placed in between the items, and
+    /// at most one empty line between two items is kept.
+    fn lines(&mut self, items: &[Item<'_>], end: usize, separator: &str) {
+        let mut previous = None;
+
+        for item in items {
+            let span = item.span();
+
+            self.comments_before(span.start(), &mut previous);
+
+            if self.has_blank_line(previous, span.start()) {
+                self.out.push('\n');
+            }
+
+            self.newline();
+            self.item(*item);
+            self.write(separator);
+
+            // Comments within the item that couldn't be placed anywhere else
+            // are moved after it.
+            previous = Some(span.start());
+            self.comments_before(span.end(), &mut previous);
+            previous = Some(span.end());
+        }
+
+        self.comments_before(end, &mut previous);
+    }
+
+    /// Format a delimited, comma-separated list of items.
+    ///
+    /// On a single line, the `padding` separates the delimiters from the items.
+    fn list(&mut self, open: &str, items: &[Item<'_>], span: Span, close: &str, padding: &str) {
+        let has_comments = self.peek_comment(span.end()).is_some();
+
+        if !has_comments && !self.text(span).contains('\n') {
+            let mark = self.out.len();
+
+            self.write(open);
+            if !items.is_empty() {
+                self.write(padding);
+            }
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    self.write(", ");
+                }
+                self.item(*item);
+            }
+            if !items.is_empty() {
+                self.write(padding);
+            }
+            self.write(close);
+
+            if !self.out[mark..].contains('\n') && self.column() <= MAX_WIDTH {
+                return;
+            }
+
+            self.out.truncate(mark);
+        }
+
+        self.write(open);
+        self.indent += 1;
+        self.lines(items, span.end(), ",");
+        self.indent -= 1;
+        self.newline();
+        self.write(close);
+    }
+
+    fn item(&mut self, item: Item<'_>) {
+        match item {
+            Item::Root(node) => match node.inner() {
+                RootExpr::Expr(expr) => self.expr(expr),
+                RootExpr::FunctionDefinition(definition) => {
+                    self.write("fn ");
+                    self.write(&definition.ident.0);
+                    self.write("(");
+                    self.idents(&definition.parameters);
+                    self.write(") ");
+                    self.block(&definition.block);
+                }
+                RootExpr::Import(import) => {
+                    self.write("import ");
+                    self.write(self.text(import.path.span()));
+                }
+                RootExpr::Error(_) => self.write(self.text(node.span())),
+            },
+            Item::Expr(expr) => self.expr(expr),
+            Item::Field(key, value) => {
+                self.write(self.text(key.span()));
+                self.write(": ");
+                self.expr(value);
+            }
+            Item::Argument(argument) => {
+                if let Some(ident) = &argument.ident {
+                    self.write(&ident.0);
+                    self.write(": ");
+                }
+                self.expr(&argument.expr);
+            }
+        }
+    }
+
+    fn idents(&mut self, idents: &[Node<Ident>]) {
+        let idents = idents
+            .iter()
+            .map(|ident| if ident.is_empty() { "_" } else { &ident.0 })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.write(&idents);
+    }
+
+    fn expr(&mut self, expr: &Node<Expr>) {
+        let span = expr.span();
+
+        match expr.inner() {
+            Expr::Literal(_) | Expr::Variable(_) => self.write(self.text(span)),
+            Expr::Container(container) => self.container(container, container.span()),
+            Expr::IfStatement(statement) => self.if_statement(statement),
+            Expr::Op(op) => {
+                self.expr(&op.0);
+                self.write(" ");
+                self.write(op.1.as_str());
+                self.write(" ");
+                self.expr(&op.2);
+            }
+            Expr::Assignment(assignment) => match assignment.inner() {
+                Assignment::Single { target, op, expr } => {
+                    self.assignment_target(target);
+                    self.write(&format!(" {} ", op));
+                    self.expr(expr);
+                }
+                Assignment::Infallible { ok, err, op, expr } => {
+                    self.assignment_target(ok);
+                    self.write(", ");
+                    self.assignment_target(err);
+                    self.write(&format!(" {} ", op));
+                    self.expr(expr);
+                }
+            },
+            Expr::Query(query) => match query.target.inner() {
+                QueryTarget::Internal(_) | QueryTarget::External => {
+                    self.write(self.text(query.span()));
+                }
+                QueryTarget::FunctionCall(call) => {
+                    self.function_call(call, query.target.span());
+                    self.write(self.text(query.path.span()));
+                }
+                QueryTarget::Container(container) => {
+                    self.container(container, query.target.span());
+                    self.write(self.text(query.path.span()));
+                }
+            },
+            Expr::FunctionCall(call) => self.function_call(call, call.span()),
+            Expr::Unary(unary) => match unary.inner() {
+                Unary::Not(not) => {
+                    self.write("!");
+                    self.expr(&not.1);
+                }
+            },
+            Expr::Abort(abort) => {
+                self.write("abort");
+                if let Some(message) = &abort.message {
+                    self.write(" ");
+                    self.expr(message);
+                }
+            }
+            Expr::Loop(node) => match node.inner() {
+                Loop::For(for_loop) => self.for_loop("for ", for_loop),
+                Loop::Walk(for_loop) => self.for_loop("walk ", for_loop),
+                Loop::While(while_loop) => {
+                    self.write("while ");
+                    self.predicate(&while_loop.predicate);
+                    self.write(" ");
+                    self.block(&while_loop.block);
+                }
+            },
+            Expr::LoopControl(control) => self.write(&control.to_string()),
+        }
+    }
+
+    fn assignment_target(&mut self, target: &Node<AssignmentTarget>) {
+        match target.inner() {
+            AssignmentTarget::Noop => self.write("_"),
+            _ => self.write(self.text(target.span())),
+        }
+    }
+
+    fn container(&mut self, container: &Container, span: Span) {
+        match container {
+            Container::Group(group) => {
+                self.write("(");
+                self.expr(&group.0);
+                self.write(")");
+            }
+            Container::Block(block) => self.block(block),
+            Container::Array(array) => {
+                let items = array.0.iter().map(Item::Expr).collect::<Vec<_>>();
+                self.list("[", &items, span, "]", "");
+            }
+            Container::Object(object) => {
+                let items = object
+                    .0
+                    .iter()
+                    .map(|(key, value)| Item::Field(key, value))
+                    .collect::<Vec<_>>();
+
+                self.list("{", &items, span, "}", " ");
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Node<Block>) {
+        let items = block.0.iter().map(Item::Expr).collect::<Vec<_>>();
+
+        self.write("{");
+        self.indent += 1;
+        self.lines(&items, block.end(), "");
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement) {
+        self.write("if ");
+        self.predicate(&statement.predicate);
+        self.write(" ");
+        self.block(&statement.consequent);
+
+        if let Some(alternative) = &statement.alternative {
+            self.write(" else ");
+
+            // An `else if` is parsed into an alternative block holding only the
+            // nested if-statement, sharing its span.
+            match alternative.0.as_slice() {
+                [expr] if expr.span() == alternative.span() => match expr.inner() {
+                    Expr::IfStatement(statement) => self.if_statement(statement),
+                    _ => self.block(alternative),
+                },
+                _ => self.block(alternative),
+            }
+        }
+    }
+
+    fn predicate(&mut self, predicate: &Predicate) {
+        match predicate {
+            Predicate::One(expr) => self.expr(expr),
+            Predicate::Many(exprs) => {
+                self.write("(");
+                for (i, expr) in exprs.iter().enumerate() {
+                    if i > 0 {
+                        self.write("; ");
+                    }
+                    self.expr(expr);
+                }
+                self.write(")");
+            }
+        }
+    }
+
+    fn for_loop(&mut self, keyword: &str, for_loop: &ForLoop) {
+        self.write(keyword);
+        if let Some(key) = &for_loop.key {
+            self.idents(std::slice::from_ref(key));
+            self.write(", ");
+        }
+        self.idents(std::slice::from_ref(&for_loop.value));
+        self.write(" in ");
+        self.expr(&for_loop.expr);
+        self.write(" ");
+        self.block(&for_loop.block);
+    }
+
+    fn function_call(&mut self, call: &FunctionCall, span: Span) {
+        self.write(&call.ident.0);
+        if call.abort_on_error {
+            self.write("!");
+        }
+
+        let end = call.closure.as_ref().map_or(span.end(), Node::start);
+        let items = call
+            .arguments
+            .iter()
+            .map(Item::Argument)
+            .collect::<Vec<_>>();
+        self.list("(", &items, Span::new(call.ident.end(), end), ")", "");
+
+        if let Some(closure) = &call.closure {
+            self.write(" -> |");
+            self.idents(&closure.variables);
+            self.write("| ");
+            self.block(&closure.block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_program() {
+        let cases = [
+            (".foo=1\n", ".foo = 1\n"),
+            (
+                "  .a  =  .b  ;  .c=  upcase!( .d )",
+                ".a = .b\n.c = upcase!(.d)\n",
+            ),
+            ("x = [ 1,2 ,3 ]", "x = [1, 2, 3]\n"),
+            (r#"{"a":1,"b":[true]}"#, "{ \"a\": 1, \"b\": [true] }\n"),
+            ("x = {}\ny = []\nz = now()", "x = {}\ny = []\nz = now()\n"),
+            (
+                "if .a { .b = 1 } else if .c { .d = 2 } else { .e = 3 }",
+                "if .a {\n    .b = 1\n} else if .c {\n    .d = 2\n} else {\n    .e = 3\n}\n",
+            ),
+            (
+                "if .a { .b = 1 } else { if .c { .d = 2 } }",
+                "if .a {\n    .b = 1\n} else {\n    if .c {\n        .d = 2\n    }\n}\n",
+            ),
+            (
+                "for_each(.a) -> |k,v| { log(k) }",
+                "for_each(.a) -> |k, v| {\n    log(k)\n}\n",
+            ),
+            (
+                "for _, v in .a { if v == 1 { break } }",
+                "for _, v in .a {\n    if v == 1 {\n        break\n    }\n}\n",
+            ),
+            (
+                "fn add(a,b) { a+b }\nadd(1,2)",
+                "fn add(a, b) {\n    a + b\n}\nadd(1, 2)\n",
+            ),
+            (
+                "ok,err=parse_json(.message)",
+                "ok, err = parse_json(.message)\n",
+            ),
+            ("parse_json!(.x).y", "parse_json!(.x).y\n"),
+            ("! (.a||.b)", "!(.a || .b)\n"),
+            ("abort   \"nope\"", "abort \"nope\"\n"),
+            (
+                "while (x = 1; x < 2) { x = 3 }",
+                "while (x = 1; x < 2) {\n    x = 3\n}\n",
+            ),
+        ];
+
+        for (source, want) in cases {
+            assert_eq!(format(source).unwrap(), want, "source: {}", source);
+        }
+    }
+
+    #[test]
+    fn format_multiline() {
+        let source = indoc(
+            r#"
+            x = {
+              "a": 1, "b": 2 }
+
+
+            y = contains(.message,
+                "foo")
+        "#,
+        );
+
+        let want = indoc(
+            r#"
+            x = {
+                "a": 1,
+                "b": 2,
+            }
+
+            y = contains(
+                .message,
+                "foo",
+            )
+        "#,
+        );
+
+        assert_eq!(format(&source).unwrap(), want);
+    }
+
+    #[test]
+    fn format_comments() {
+        let source = indoc(
+            r"
+            # leading
+            .a = 1 # trailing
+
+            if .b { # opening
+              # inside
+              .c = [
+                1, # one
+                # two
+                2
+              ]
+              # closing
+            }
+            # final
+        ",
+        );
+
+        let want = indoc(
+            r"
+            # leading
+            .a = 1 # trailing
+
+            if .b {
+                # opening
+                # inside
+                .c = [
+                    1, # one
+                    # two
+                    2,
+                ]
+                # closing
+            }
+            # final
+        ",
+        );
+
+        assert_eq!(format(&source).unwrap(), want);
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        let sources = [
+            "x = [{ .a = 1 }, 2]",
+            "if .a { # why\n.b = 1 }",
+            "y = upcase(.message) ?? \"default\" # fallback",
+            "z = merge({\"a\": 1}, {\"b\": [1, 2, 3], \"c\": {\"d\": null}}, deep: true)",
+        ];
+
+        for source in sources {
+            let once = format(source).unwrap();
+            assert_eq!(format(&once).unwrap(), once, "source: {}", source);
+        }
+    }
+
+    #[test]
+    fn format_invalid_program() {
+        assert!(format(".a = ").is_err());
+    }
+
+    fn indoc(text: &str) -> String {
+        let text = text.trim_start_matches('\n');
+        let indent = text.len() - text.trim_start().len();
+
+        text.lines()
+            .map(|line| line.get(indent..).unwrap_or_default().trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_end()
+            .to_owned()
+            + "\n"
+    }
+}
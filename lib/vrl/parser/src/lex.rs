@@ -45,6 +45,49 @@ pub enum Error {
     UnexpectedParseError(String),
 }
 
+impl Error {
+    /// Shift the location of the error by `offset` bytes.
+    ///
+    /// This is used when lexing a source whose spans don't start at zero (see
+    /// [`crate::parse_with_offset`]). Parse errors are left untouched, as they
+    /// are built from already-shifted token locations.
+    #[must_use]
+    pub(crate) fn with_offset(self, offset: usize) -> Self {
+        use Error::{
+            EscapeChar, Literal, NumericLiteral, ParseError, ReservedKeyword, StringLiteral,
+            UnexpectedParseError,
+        };
+
+        match self {
+            ReservedKeyword {
+                start,
+                keyword,
+                end,
+            } => ReservedKeyword {
+                start: start + offset,
+                keyword,
+                end: end + offset,
+            },
+            NumericLiteral { start, error, end } => NumericLiteral {
+                start: start + offset,
+                error,
+                end: end + offset,
+            },
+            StringLiteral { start } => StringLiteral {
+                start: start + offset,
+            },
+            Literal { start } => Literal {
+                start: start + offset,
+            },
+            EscapeChar { start, ch } => EscapeChar {
+                start: start + offset,
+                ch,
+            },
+            err @ (ParseError { .. } | UnexpectedParseError(..)) => err,
+        }
+    }
+}
+
 impl DiagnosticMessage for Error {
     fn code(&self) -> usize {
         use Error::{
@@ -116,6 +159,8 @@ impl DiagnosticMessage for Error {
                         r#""null""#,
                         r#""true""#,
                         r#""if""#,
+                        r#""fn""#,
+                        r#""import""#,
                     ];
                     let is_any_ident = any_ident
                         .iter()
@@ -253,6 +298,14 @@ pub enum Token<S> {
     False,
     True,
     Abort,
+    Fn,
+    Import,
+    For,
+    In,
+    While,
+    Walk,
+    Break,
+    Continue,
 
     // tokens
     Colon,
@@ -312,11 +365,12 @@ pub enum Token<S> {
 impl<S> Token<S> {
     pub(crate) fn map<R>(self, f: impl Fn(S) -> R) -> Token<R> {
         use self::Token::{
-            Abort, Arrow, Bang, Colon, Comma, Dot, Else, Equals, Escape, False, FloatLiteral,
-            FunctionCall, Identifier, If, IntegerLiteral, InvalidToken, LBrace, LBracket, LParen,
-            LQuery, MergeEquals, Newline, Null, Operator, PathField, Question, RBrace, RBracket,
-            RParen, RQuery, RawStringLiteral, RegexLiteral, ReservedIdentifier, SemiColon,
-            StringLiteral, TimestampLiteral, True, Underscore,
+            Abort, Arrow, Bang, Break, Colon, Comma, Continue, Dot, Else, Equals, Escape, False,
+            FloatLiteral, Fn, For, FunctionCall, Identifier, If, Import, In, IntegerLiteral,
+            InvalidToken, LBrace, LBracket, LParen, LQuery, MergeEquals, Newline, Null, Operator,
+            PathField, Question, RBrace, RBracket, RParen, RQuery, RawStringLiteral, RegexLiteral,
+            ReservedIdentifier, SemiColon, StringLiteral, TimestampLiteral, True, Underscore, Walk,
+            While,
         };
 
         match self {
@@ -345,6 +399,14 @@ impl<S> Token<S> {
             Null => Null,
             True => True,
             Abort => Abort,
+            Fn => Fn,
+            Import => Import,
+            For => For,
+            In => In,
+            While => While,
+            Walk => Walk,
+            Break => Break,
+            Continue => Continue,
 
             // tokens
             Colon => Colon,
@@ -379,11 +441,12 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use self::Token::{
-            Abort, Arrow, Bang, Colon, Comma, Dot, Else, Equals, Escape, False, FloatLiteral,
-            FunctionCall, Identifier, If, IntegerLiteral, InvalidToken, LBrace, LBracket, LParen,
-            LQuery, MergeEquals, Newline, Null, Operator, PathField, Question, RBrace, RBracket,
-            RParen, RQuery, RawStringLiteral, RegexLiteral, ReservedIdentifier, SemiColon,
-            StringLiteral, TimestampLiteral, True, Underscore,
+            Abort, Arrow, Bang, Break, Colon, Comma, Continue, Dot, Else, Equals, Escape, False,
+            FloatLiteral, Fn, For, FunctionCall, Identifier, If, Import, In, IntegerLiteral,
+            InvalidToken, LBrace, LBracket, LParen, LQuery, MergeEquals, Newline, Null, Operator,
+            PathField, Question, RBrace, RBracket, RParen, RQuery, RawStringLiteral, RegexLiteral,
+            ReservedIdentifier, SemiColon, StringLiteral, TimestampLiteral, True, Underscore, Walk,
+            While,
         };
 
         let s = match *self {
@@ -406,6 +469,14 @@ where
             Null => "Null",
             True => "True",
             Abort => "Abort",
+            Fn => "Fn",
+            Import => "Import",
+            For => "For",
+            In => "In",
+            While => "While",
+            Walk => "Walk",
+            Break => "Break",
+            Continue => "Continue",
 
             // tokens
             Colon => "Colon",
@@ -440,7 +511,8 @@ impl<'input> Token<&'input str> {
     /// Returns either a literal, reserved, or generic identifier.
     fn ident(s: &'input str) -> Self {
         use Token::{
-            Abort, Else, False, Identifier, If, Null, PathField, ReservedIdentifier, True,
+            Abort, Break, Continue, Else, False, Fn, For, Identifier, If, Import, In, Null,
+            PathField, ReservedIdentifier, True, Walk, While,
         };
 
         match s {
@@ -450,14 +522,20 @@ impl<'input> Token<&'input str> {
             "false" => False,
             "null" => Null,
             "abort" => Abort,
+            "fn" => Fn,
+            "import" => Import,
+            "for" => For,
+            "in" => In,
+            "while" => While,
+            "walk" => Walk,
+            "break" => Break,
+            "continue" => Continue,
 
             // reserved identifiers
-            "array" | "bool" | "boolean" | "break" | "continue" | "do" | "emit" | "float"
-            | "for" | "forall" | "foreach" | "all" | "each" | "any" | "try" | "undefined"
-            | "int" | "integer" | "iter" | "object" | "regex" | "return" | "string"
-            | "traverse" | "timestamp" | "duration" | "unless" | "walk" | "while" | "loop" => {
-                ReservedIdentifier(s)
-            }
+            "array" | "bool" | "boolean" | "do" | "emit" | "float" | "forall" | "foreach"
+            | "all" | "each" | "any" | "try" | "undefined" | "int" | "integer" | "iter"
+            | "object" | "regex" | "return" | "string" | "traverse" | "timestamp" | "duration"
+            | "unless" | "loop" => ReservedIdentifier(s),
 
             _ if s.contains('@') => PathField(s),
 
@@ -2098,4 +2176,71 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn function_definition() {
+        test(
+            data("fn add(a, b) { a + b }"),
+            vec![
+                ("~~                    ", Token::Fn),
+                ("   ~~~                ", FunctionCall("add")),
+                ("      ~               ", LParen),
+                ("       ~              ", Identifier("a")),
+                ("        ~             ", Comma),
+                ("          ~           ", Identifier("b")),
+                ("           ~          ", RParen),
+                ("             ~        ", LBrace),
+                ("               ~      ", Identifier("a")),
+                ("                 ~    ", Operator("+")),
+                ("                   ~  ", Identifier("b")),
+                ("                     ~", RBrace),
+            ],
+        );
+    }
+
+    #[test]
+    fn import() {
+        test(
+            data(r#"import "common.vrl""#),
+            vec![
+                (r#"~~~~~~             "#, Token::Import),
+                (r#"       ~~~~~~~~~~~~"#, StringLiteral(StringLiteralToken("common.vrl"))),
+            ],
+        );
+    }
+
+    #[test]
+    fn for_loop() {
+        test(
+            data("for k, v in .foo { break }"),
+            vec![
+                ("~~~                       ", Token::For),
+                ("    ~                     ", Identifier("k")),
+                ("     ~                    ", Comma),
+                ("       ~                  ", Identifier("v")),
+                ("         ~~               ", Token::In),
+                ("            ~             ", LQuery),
+                ("            ~             ", Dot),
+                ("             ~~~          ", Identifier("foo")),
+                ("               ~          ", RQuery),
+                ("                 ~        ", LBrace),
+                ("                   ~~~~~  ", Token::Break),
+                ("                         ~", RBrace),
+            ],
+        );
+    }
+
+    #[test]
+    fn while_loop() {
+        test(
+            data("while true { continue }"),
+            vec![
+                ("~~~~~                  ", Token::While),
+                ("      ~~~~             ", True),
+                ("           ~           ", LBrace),
+                ("             ~~~~~~~~  ", Token::Continue),
+                ("                      ~", RBrace),
+            ],
+        );
+    }
 }
@@ -37,21 +37,37 @@ mod arbitrary;
 #[cfg(feature = "fuzz")]
 mod arbitrary_depth;
 pub mod ast;
+mod format;
 mod lex;
 mod template_string;
 
 pub use ast::{Literal, Program};
 pub use diagnostic::Span;
+pub use format::format;
 pub use lex::{Error, Token};
 use lookup::LookupBuf;
+pub use template_string::{StringSegment, TemplateString};
 
 pub fn parse(input: impl AsRef<str>) -> Result<Program, Error> {
-    let lexer = lex::Lexer::new(input.as_ref());
+    parse_with_offset(input, 0)
+}
+
+/// Parse a program, starting its spans at the given `offset`.
+///
+/// This allows multiple sources (such as imported modules) to share a single
+/// span space, so that any diagnostic can be traced back to the source it
+/// originated from.
+pub fn parse_with_offset(input: impl AsRef<str>, offset: usize) -> Result<Program, Error> {
+    let lexer = lex::Lexer::new(input.as_ref()).map(move |result| {
+        result
+            .map(|(start, token, end)| (start + offset, token, end + offset))
+            .map_err(|err| err.with_offset(offset))
+    });
 
     parser::ProgramParser::new()
         .parse(input.as_ref(), lexer)
         .map_err(|source| Error::ParseError {
-            span: Span::new(0, input.as_ref().len()),
+            span: Span::new(offset, offset + input.as_ref().len()),
             source: source
                 .map_token(|t| t.map(ToOwned::to_owned))
                 .map_error(|err| err.to_string()),
@@ -0,0 +1,140 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn chunks(value: Value, chunk_size: Value) -> Resolved {
+    let chunk_size = chunk_size.try_integer()?;
+
+    if chunk_size < 1 {
+        return Err(format!(r#""chunk_size" must be at least 1, got {}"#, chunk_size).into());
+    }
+
+    Ok(value
+        .try_array()?
+        .chunks(chunk_size as usize)
+        .map(|chunk| Value::Array(chunk.to_vec()))
+        .collect::<Vec<_>>()
+        .into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Chunks;
+
+impl Function for Chunks {
+    fn identifier(&self) -> &'static str {
+        "chunks"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "chunk_size",
+                kind: kind::INTEGER,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "split array into chunks",
+                source: r#"chunks!([1, 2, 3, 4, 5], 2)"#,
+                result: Ok("[[1, 2], [3, 4], [5]]"),
+            },
+            Example {
+                title: "invalid chunk size",
+                source: r#"chunks!([1, 2], 0)"#,
+                result: Err(
+                    r#"function call error for "chunks" at (0:18): "chunk_size" must be at least 1, got 0"#,
+                ),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let chunk_size = arguments.required("chunk_size");
+
+        Ok(Box::new(ChunksFn { value, chunk_size }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChunksFn {
+    value: Box<dyn Expression>,
+    chunk_size: Box<dyn Expression>,
+}
+
+impl Expression for ChunksFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let chunk_size = self.chunk_size.resolve(ctx)?;
+
+        chunks(value, chunk_size)
+    }
+
+    fn type_def(&self, state: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let item = self
+            .value
+            .type_def(state)
+            .as_array()
+            .map_or_else(Kind::any, Collection::reduced_kind);
+
+        // The chunk size is only known at runtime, so it can be invalid.
+        TypeDef::array(Collection::from_unknown(Kind::array(
+            Collection::from_unknown(item),
+        )))
+        .fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        chunks => Chunks;
+
+        even {
+            args: func_args![value: value!([1, 2, 3, 4]), chunk_size: 2],
+            want: Ok(value!([[1, 2], [3, 4]])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(
+                Collection::from_unknown(Kind::integer()),
+            ))).fallible(),
+        }
+
+        remainder {
+            args: func_args![value: value!(["a", "b", "c"]), chunk_size: 2],
+            want: Ok(value!([["a", "b"], ["c"]])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(
+                Collection::from_unknown(Kind::bytes()),
+            ))).fallible(),
+        }
+
+        larger_than_array {
+            args: func_args![value: value!([1]), chunk_size: 10],
+            want: Ok(value!([[1]])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(
+                Collection::from_unknown(Kind::integer()),
+            ))).fallible(),
+        }
+
+        invalid_chunk_size {
+            args: func_args![value: value!([1]), chunk_size: 0],
+            want: Err(r#""chunk_size" must be at least 1, got 0"#),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(
+                Collection::from_unknown(Kind::integer()),
+            ))).fallible(),
+        }
+    ];
+}
@@ -0,0 +1,82 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn crc32(value: Value) -> Resolved {
+    let value = value.try_bytes()?;
+
+    Ok(i64::from(crc32fast::hash(&value)).into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32;
+
+impl Function for Crc32 {
+    fn identifier(&self) -> &'static str {
+        "crc32"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "crc32",
+            source: r#"crc32("foobar")"#,
+            result: Ok("2666930069"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(Crc32Fn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Crc32Fn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for Crc32Fn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        crc32(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::integer().infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        crc32 => Crc32;
+
+        crc32 {
+            args: func_args![value: "foo"],
+            want: Ok(2_356_372_769_i64),
+            tdef: TypeDef::integer().infallible(),
+        }
+
+        empty {
+            args: func_args![value: ""],
+            want: Ok(0),
+            tdef: TypeDef::integer().infallible(),
+        }
+    ];
+}
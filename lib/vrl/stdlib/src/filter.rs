@@ -0,0 +1,201 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn filter<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    match value {
+        Value::Object(object) => {
+            let mut filtered = BTreeMap::new();
+
+            for (key, value) in object {
+                let keep = runner
+                    .run_values(ctx, vec![key.clone().into(), value.clone()])?
+                    .try_boolean()?;
+
+                if keep {
+                    filtered.insert(key, value);
+                }
+            }
+
+            Ok(filtered.into())
+        }
+        Value::Array(array) => {
+            let mut filtered = Vec::with_capacity(array.len());
+
+            for (index, value) in array.into_iter().enumerate() {
+                let keep = runner
+                    .run_values(ctx, vec![index.into(), value.clone()])?
+                    .try_boolean()?;
+
+                if keep {
+                    filtered.push(value);
+                }
+            }
+
+            Ok(filtered.into())
+        }
+        value => Err(value::Error::Expected {
+            got: value.kind(),
+            expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+        }
+        .into()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Filter;
+
+impl Function for Filter {
+    fn identifier(&self) -> &'static str {
+        "filter"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT | kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "filter array",
+                source: r#"filter([1, 2, 3, 4]) -> |_index, value| { value > 2 }"#,
+                result: Ok("[3, 4]"),
+            },
+            Example {
+                title: "filter object",
+                source: r#"filter({ "a": 1, "b": null, "c": 3 }) -> |_key, value| { value != null }"#,
+                result: Ok(r#"{ "a": 1, "c": 3 }"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(FilterFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::boolean()),
+                example: Example {
+                    title: "filter array",
+                    source: r#"filter([1, 2, 3]) -> |index, _value| { index == 0 }"#,
+                    result: Ok("[1]"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for FilterFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        filter(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let value = self.value.type_def(ctx);
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+
+        // Any element can be removed, so only the kind of the remaining
+        // elements is known, not their position.
+        let mut kind = Kind::never();
+
+        if let Some(object) = value.as_object() {
+            kind.add_object(Collection::from_unknown(object.reduced_kind()));
+        }
+
+        if let Some(array) = value.as_array() {
+            kind.add_array(Collection::from_unknown(array.reduced_kind()));
+        }
+
+        TypeDef::from(kind).with_fallibility(fallible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        filter => Filter;
+
+        array {
+            args: ArgumentList::from(func_args![value: value!([1, 2, 3, 4])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::integer() => "value > 2")),
+            want: Ok(value!([3, 4])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::integer())).infallible(),
+        }
+
+        object {
+            args: ArgumentList::from(func_args![value: value!({"a": 1, "b": null, "c": 3})])
+                .with_closure(test_closure!(key: Kind::bytes(), value: Kind::any() => "value != null")),
+            want: Ok(value!({"a": 1, "c": 3})),
+            tdef: TypeDef::object(Collection::from_unknown(Kind::integer().or_null())).infallible(),
+        }
+
+        empty_array {
+            args: ArgumentList::from(func_args![value: Value::Array(vec![])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::any() => "true")),
+            want: Ok(Value::Array(vec![])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::never())).infallible(),
+        }
+
+        empty_object {
+            args: ArgumentList::from(func_args![value: Value::Object(BTreeMap::new())])
+                .with_closure(test_closure!(key: Kind::bytes(), value: Kind::any() => "true")),
+            want: Ok(Value::Object(BTreeMap::new())),
+            tdef: TypeDef::object(Collection::from_unknown(Kind::never())).infallible(),
+        }
+
+        not_a_collection {
+            args: ArgumentList::from(func_args![value: 1])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::any() => "true")),
+            want: Err("expected array or object, got integer"),
+            tdef: TypeDef::never().infallible(),
+        }
+
+        closure_not_boolean {
+            args: ArgumentList::from(func_args![value: value!([1, 2])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::integer() => "value")),
+            want: Err("expected boolean, got integer"),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::integer())).infallible(),
+        }
+    ];
+}
@@ -0,0 +1,148 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn flat_map<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let mut mapped = vec![];
+
+    for (index, value) in value.try_array()?.into_iter().enumerate() {
+        let values = runner.run_values(ctx, vec![index.into(), value])?;
+        mapped.extend(values.try_array()?);
+    }
+
+    Ok(mapped.into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlatMap;
+
+impl Function for FlatMap {
+    fn identifier(&self) -> &'static str {
+        "flat_map"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "duplicate items",
+                source: r#"flat_map(["a", "b"]) -> |_index, value| { [value, value] }"#,
+                result: Ok(r#"["a", "a", "b", "b"]"#),
+            },
+            Example {
+                title: "split strings",
+                source: r#"flat_map(["a,b", "c"]) -> |_index, value| { split(value, ",") }"#,
+                result: Ok(r#"["a", "b", "c"]"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(FlatMapFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::array(Collection::any())),
+                example: Example {
+                    title: "repeat items",
+                    source: r#"flat_map([1, 2]) -> |index, value| { [index, value] }"#,
+                    result: Ok("[0, 1, 1, 2]"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FlatMapFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for FlatMapFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        flat_map(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let closure = self.closure.block.type_def(ctx);
+        let item = closure
+            .as_array()
+            .map_or_else(Kind::any, Collection::reduced_kind);
+
+        TypeDef::array(Collection::from_unknown(item)).with_fallibility(closure.is_fallible())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        flat_map => FlatMap;
+
+        array {
+            args: ArgumentList::from(func_args![value: value!([1, 2])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::integer() => "[index, value]")),
+            want: Ok(value!([0, 1, 1, 2])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::integer())).infallible(),
+        }
+
+        empty_array {
+            args: ArgumentList::from(func_args![value: Value::Array(vec![])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::any() => "[value]")),
+            want: Ok(Value::Array(vec![])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::any())).infallible(),
+        }
+
+        not_an_array {
+            args: ArgumentList::from(func_args![value: "foo"])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::any() => "[value]")),
+            want: Err("expected array, got string"),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::any())).infallible(),
+        }
+
+        closure_not_array {
+            args: ArgumentList::from(func_args![value: value!([1, 2])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::integer() => "value")),
+            want: Err("expected array, got integer"),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::any())).infallible(),
+        }
+    ];
+}
@@ -1,16 +1,19 @@
 use ::value::Value;
 use chrono::{
     format::{strftime::StrftimeItems, Item},
-    DateTime, Utc,
+    DateTime, Local, Utc,
 };
+use vector_common::TimeZone;
 use vrl::prelude::*;
 
-fn format_timestamp(bytes: Value, ts: Value) -> Resolved {
+use crate::util::timezone_argument;
+
+fn format_timestamp(bytes: Value, ts: Value, timezone: Option<&TimeZone>) -> Resolved {
     let bytes = bytes.try_bytes()?;
     let format = String::from_utf8_lossy(&bytes);
     let ts = ts.try_timestamp()?;
 
-    try_format(&ts, &format).map(Into::into)
+    try_format(&ts, &format, timezone).map(Into::into)
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -33,6 +36,11 @@ impl Function for FormatTimestamp {
                 kind: kind::BYTES,
                 required: true,
             },
+            Parameter {
+                keyword: "timezone",
+                kind: kind::BYTES,
+                required: false,
+            },
         ]
     }
 
@@ -44,16 +52,28 @@ impl Function for FormatTimestamp {
     ) -> Compiled {
         let value = arguments.required("value");
         let format = arguments.required("format");
+        let timezone = timezone_argument(&mut arguments)?;
 
-        Ok(Box::new(FormatTimestampFn { value, format }))
+        Ok(Box::new(FormatTimestampFn {
+            value,
+            format,
+            timezone,
+        }))
     }
 
     fn examples(&self) -> &'static [Example] {
-        &[Example {
-            title: "format timestamp",
-            source: r#"format_timestamp!(t'2021-02-10T23:32:00+00:00', "%d %B %Y %H:%M")"#,
-            result: Ok("10 February 2021 23:32"),
-        }]
+        &[
+            Example {
+                title: "format timestamp",
+                source: r#"format_timestamp!(t'2021-02-10T23:32:00+00:00', "%d %B %Y %H:%M")"#,
+                result: Ok("10 February 2021 23:32"),
+            },
+            Example {
+                title: "format timestamp in a timezone",
+                source: r#"format_timestamp!(t'2021-02-10T23:32:00+00:00', "%d %B %Y %H:%M %:z", timezone: "Europe/Paris")"#,
+                result: Ok("11 February 2021 00:32 +01:00"),
+            },
+        ]
     }
 }
 
@@ -61,6 +81,7 @@ impl Function for FormatTimestamp {
 struct FormatTimestampFn {
     value: Box<dyn Expression>,
     format: Box<dyn Expression>,
+    timezone: Option<TimeZone>,
 }
 
 impl Expression for FormatTimestampFn {
@@ -68,7 +89,7 @@ impl Expression for FormatTimestampFn {
         let bytes = self.format.resolve(ctx)?;
         let ts = self.value.resolve(ctx)?;
 
-        format_timestamp(bytes, ts)
+        format_timestamp(bytes, ts, self.timezone.as_ref())
     }
 
     fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
@@ -76,7 +97,7 @@ impl Expression for FormatTimestampFn {
     }
 }
 
-fn try_format(dt: &DateTime<Utc>, format: &str) -> Result<String> {
+fn try_format(dt: &DateTime<Utc>, format: &str, timezone: Option<&TimeZone>) -> Result<String> {
     let items = StrftimeItems::new(format)
         .map(|item| match item {
             Item::Error => Err("invalid format".into()),
@@ -84,12 +105,20 @@ fn try_format(dt: &DateTime<Utc>, format: &str) -> Result<String> {
         })
         .collect::<Result<Vec<_>>>()?;
 
-    Ok(dt.format_with_items(items.into_iter()).to_string())
+    let formatted = match timezone {
+        None => dt.format_with_items(items.into_iter()),
+        Some(TimeZone::Local) => dt
+            .with_timezone(&Local)
+            .format_with_items(items.into_iter()),
+        Some(TimeZone::Named(tz)) => dt.with_timezone(tz).format_with_items(items.into_iter()),
+    };
+
+    Ok(formatted.to_string())
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::TimeZone;
+    use chrono::TimeZone as _;
 
     use super::*;
 
@@ -116,5 +145,21 @@ mod tests {
             want: Ok(value!("1970-01-01T00:00:10+00:00")),
             tdef: TypeDef::bytes().fallible(),
         }
+
+        date_in_timezone {
+            args: func_args![value: Utc.timestamp(10, 0),
+                             format: "%+",
+                             timezone: "Asia/Tokyo"],
+            want: Ok(value!("1970-01-01T09:00:10+09:00")),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        invalid_timezone {
+            args: func_args![value: Utc.timestamp(10, 0),
+                             format: "%+",
+                             timezone: "Mars/Olympus_Mons"],
+            want: Err("invalid argument"),
+            tdef: TypeDef::bytes().fallible(),
+        }
     ];
 }
@@ -0,0 +1,164 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn group_by<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+    for (index, value) in value.try_array()?.into_iter().enumerate() {
+        let key = runner
+            .run_values(ctx, vec![index.into(), value.clone()])?
+            .try_bytes_utf8_lossy()?
+            .into_owned();
+
+        groups.entry(key).or_default().push(value);
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(key, group)| (key, Value::Array(group)))
+        .collect::<BTreeMap<_, _>>()
+        .into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GroupBy;
+
+impl Function for GroupBy {
+    fn identifier(&self) -> &'static str {
+        "group_by"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "group by field",
+                source: r#"group_by([{ "level": "info", "id": 1 }, { "level": "error", "id": 2 }, { "level": "info", "id": 3 }]) -> |_index, value| { value.level }"#,
+                result: Ok(
+                    r#"{ "error": [{ "id": 2, "level": "error" }], "info": [{ "id": 1, "level": "info" }, { "id": 3, "level": "info" }] }"#,
+                ),
+            },
+            Example {
+                title: "group by type",
+                source: r#"group_by([1, "a", 2]) -> |_index, value| { if is_integer(value) { "numbers" } else { "strings" } }"#,
+                result: Ok(r#"{ "numbers": [1, 2], "strings": ["a"] }"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(GroupByFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::bytes()),
+                example: Example {
+                    title: "group by parity",
+                    source: r#"group_by([1, 2, 3]) -> |index, _value| { if index == 1 { "even" } else { "odd" } }"#,
+                    result: Ok(r#"{ "even": [2], "odd": [1, 3] }"#),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GroupByFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for GroupByFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        group_by(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let item = self
+            .value
+            .type_def(ctx)
+            .as_array()
+            .map_or_else(Kind::any, Collection::reduced_kind);
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+
+        // Each group is a non-empty array of items, keyed by the value the
+        // closure resolved to for those items.
+        let group = Kind::array(Collection::from_unknown(item));
+
+        TypeDef::object(Collection::from_unknown(group)).with_fallibility(fallible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        group_by => GroupBy;
+
+        array {
+            args: ArgumentList::from(func_args![value: value!([1, 2, 3])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::integer() => r#"if index == 1 { "even" } else { "odd" }"#)),
+            want: Ok(value!({"even": [2], "odd": [1, 3]})),
+            tdef: TypeDef::object(Collection::from_unknown(Kind::array(Collection::from_unknown(Kind::integer())))).infallible(),
+        }
+
+        empty_array {
+            args: ArgumentList::from(func_args![value: Value::Array(vec![])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::any() => r#""key""#)),
+            want: Ok(Value::Object(BTreeMap::new())),
+            tdef: TypeDef::object(Collection::from_unknown(Kind::array(Collection::from_unknown(Kind::never())))).infallible(),
+        }
+
+        not_an_array {
+            args: ArgumentList::from(func_args![value: "foo"])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::any() => r#""key""#)),
+            want: Err("expected array, got string"),
+            tdef: TypeDef::object(Collection::from_unknown(Kind::array(Collection::from_unknown(Kind::any())))).infallible(),
+        }
+
+        key_not_string {
+            args: ArgumentList::from(func_args![value: value!([1, 2])])
+                .with_closure(test_closure!(index: Kind::integer(), value: Kind::integer() => "value")),
+            want: Err("expected string, got integer"),
+            tdef: TypeDef::object(Collection::from_unknown(Kind::array(Collection::from_unknown(Kind::integer())))).infallible(),
+        }
+    ];
+}
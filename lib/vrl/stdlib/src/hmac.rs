@@ -0,0 +1,203 @@
+use ::hmac::{digest::KeyInit, Hmac as HmacDigest, Mac};
+use ::value::Value;
+use sha_2::{Sha256, Sha512};
+use vrl::prelude::*;
+
+fn hmac(value: Value, key: Value, algorithm: &Bytes, encoding: &Bytes) -> Resolved {
+    let value = value.try_bytes()?;
+    let key = key.try_bytes()?;
+    let signature = match algorithm.as_ref() {
+        b"SHA-1" => sign::<HmacDigest<::sha1::Sha1>>(&key, &value),
+        b"SHA-256" => sign::<HmacDigest<Sha256>>(&key, &value),
+        b"SHA-512" => sign::<HmacDigest<Sha512>>(&key, &value),
+        _ => unreachable!("enum invariant"),
+    };
+    let signature = match encoding.as_ref() {
+        b"hex" => hex::encode(signature),
+        b"base64" => base64::encode(signature),
+        _ => unreachable!("enum invariant"),
+    };
+
+    Ok(signature.into())
+}
+
+fn algorithms() -> Vec<Value> {
+    vec![value!("SHA-1"), value!("SHA-256"), value!("SHA-512")]
+}
+
+fn encodings() -> Vec<Value> {
+    vec![value!("hex"), value!("base64")]
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Hmac;
+
+impl Function for Hmac {
+    fn identifier(&self) -> &'static str {
+        "hmac"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "key",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "algorithm",
+                kind: kind::BYTES,
+                required: false,
+            },
+            Parameter {
+                keyword: "encoding",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "default algorithm",
+                source: r#"hmac("foo", "bar")"#,
+                result: Ok("147933218aaabc0b8b10a2b3a5c34684c8d94341bcf10a4736dc7270f7741851"),
+            },
+            Example {
+                title: "custom algorithm and encoding",
+                source: r#"hmac("foo", "bar", algorithm: "SHA-1", encoding: "base64")"#,
+                result: Ok("hdFVxV7ShqMAvRzxJN4I2H6RTzo="),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let key = arguments.required("key");
+        let algorithm = arguments
+            .optional_enum("algorithm", &algorithms())?
+            .unwrap_or_else(|| value!("SHA-256"))
+            .try_bytes()
+            .expect("algorithm not bytes");
+        let encoding = arguments
+            .optional_enum("encoding", &encodings())?
+            .unwrap_or_else(|| value!("hex"))
+            .try_bytes()
+            .expect("encoding not bytes");
+
+        Ok(Box::new(HmacFn {
+            value,
+            key,
+            algorithm,
+            encoding,
+        }))
+    }
+
+    fn compile_argument(
+        &self,
+        _args: &[(&'static str, Option<FunctionArgument>)],
+        _ctx: &mut FunctionCompileContext,
+        name: &str,
+        expr: Option<&expression::Expr>,
+    ) -> CompiledArgument {
+        match (name, expr) {
+            ("algorithm", Some(expr)) => {
+                let algorithm = expr
+                    .as_enum("algorithm", algorithms())?
+                    .try_bytes()
+                    .expect("algorithm not bytes");
+
+                Ok(Some(Box::new(algorithm) as _))
+            }
+            ("algorithm", None) => Ok(Some(Box::new(Bytes::from("SHA-256")) as _)),
+            ("encoding", Some(expr)) => {
+                let encoding = expr
+                    .as_enum("encoding", encodings())?
+                    .try_bytes()
+                    .expect("encoding not bytes");
+
+                Ok(Some(Box::new(encoding) as _))
+            }
+            ("encoding", None) => Ok(Some(Box::new(Bytes::from("hex")) as _)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HmacFn {
+    value: Box<dyn Expression>,
+    key: Box<dyn Expression>,
+    algorithm: Bytes,
+    encoding: Bytes,
+}
+
+impl Expression for HmacFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let key = self.key.resolve(ctx)?;
+
+        hmac(value, key, &self.algorithm, &self.encoding)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::bytes().infallible()
+    }
+}
+
+#[inline]
+fn sign<T: Mac + KeyInit>(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut mac = <T as Mac>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(value);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        hmac => Hmac;
+
+        default {
+            args: func_args![value: "foo", key: "bar"],
+            want: Ok("147933218aaabc0b8b10a2b3a5c34684c8d94341bcf10a4736dc7270f7741851"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        sha1 {
+            args: func_args![value: "foo", key: "bar", algorithm: "SHA-1"],
+            want: Ok("85d155c55ed286a300bd1cf124de08d87e914f3a"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        sha512 {
+            args: func_args![value: "foo", key: "bar", algorithm: "SHA-512"],
+            want: Ok("24257d7210582a65c731ec55159c8184cc24c02489453e58587f71f44c23a2d61b4b72154a89d17b2d49448a8452ea066f4fc56a2bcead45c088572ffccdb3d8"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        base64_encoding {
+            args: func_args![value: "foo", key: "bar", encoding: "base64"],
+            want: Ok("FHkzIYqqvAuLEKKzpcNGhMjZQ0G88QpHNtxycPd0GFE="),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        empty_key {
+            args: func_args![value: "foo", key: ""],
+            want: Ok("0c0d98f7e3d9d45e72e8877bc1b104327efb9c07b18f2ffeced76d81307f1fff"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+    ];
+}
@@ -40,10 +40,14 @@ mod assert_eq;
 mod boolean;
 #[cfg(feature = "ceil")]
 mod ceil;
+#[cfg(feature = "chunks")]
+mod chunks;
 #[cfg(feature = "compact")]
 mod compact;
 #[cfg(feature = "contains")]
 mod contains;
+#[cfg(feature = "crc32")]
+mod crc32;
 #[cfg(feature = "decode_base64")]
 mod decode_base64;
 #[cfg(feature = "decode_percent")]
@@ -70,8 +74,12 @@ mod encrypt;
 mod ends_with;
 #[cfg(feature = "exists")]
 mod exists;
+#[cfg(feature = "filter")]
+mod filter;
 #[cfg(feature = "find")]
 mod find;
+#[cfg(feature = "flat_map")]
+mod flat_map;
 #[cfg(feature = "flatten")]
 mod flatten;
 #[cfg(feature = "float")]
@@ -92,6 +100,10 @@ mod get;
 mod get_env_var;
 #[cfg(feature = "get_hostname")]
 mod get_hostname;
+#[cfg(feature = "group_by")]
+mod group_by;
+#[cfg(feature = "hmac")]
+mod hmac;
 #[cfg(feature = "includes")]
 mod includes;
 #[cfg(feature = "integer")]
@@ -178,6 +190,8 @@ mod parse_aws_alb_log;
 mod parse_aws_cloudwatch_log_subscription_message;
 #[cfg(feature = "parse_aws_vpc_flow_log")]
 mod parse_aws_vpc_flow_log;
+#[cfg(feature = "parse_cef")]
+mod parse_cef;
 #[cfg(feature = "parse_common_log")]
 mod parse_common_log;
 #[cfg(feature = "parse_csv")]
@@ -198,6 +212,8 @@ mod parse_json;
 mod parse_key_value;
 #[cfg(feature = "parse_klog")]
 mod parse_klog;
+#[cfg(feature = "parse_leef")]
+mod parse_leef;
 #[cfg(feature = "parse_linux_authorization")]
 mod parse_linux_authorization;
 #[cfg(feature = "parse_logfmt")]
@@ -222,6 +238,8 @@ mod parse_tokens;
 mod parse_url;
 #[cfg(feature = "parse_user_agent")]
 mod parse_user_agent;
+#[cfg(feature = "parse_windows_event_xml")]
+mod parse_windows_event_xml;
 #[cfg(feature = "parse_xml")]
 mod parse_xml;
 #[cfg(feature = "push")]
@@ -230,6 +248,8 @@ mod push;
 mod random_bytes;
 #[cfg(feature = "redact")]
 mod redact;
+#[cfg(feature = "reduce")]
+mod reduce;
 #[cfg(feature = "remove")]
 mod remove;
 #[cfg(feature = "replace")]
@@ -238,6 +258,8 @@ mod replace;
 mod reverse_dns;
 #[cfg(feature = "round")]
 mod round;
+#[cfg(feature = "seahash")]
+mod seahash;
 #[cfg(feature = "set")]
 mod set;
 #[cfg(feature = "sha1")]
@@ -248,6 +270,8 @@ mod sha2;
 mod sha3;
 #[cfg(feature = "slice")]
 mod slice;
+#[cfg(feature = "sort_by")]
+mod sort_by;
 #[cfg(feature = "split")]
 mod split;
 #[cfg(feature = "starts_with")]
@@ -268,6 +292,12 @@ mod tally;
 mod tally_value;
 #[cfg(feature = "timestamp")]
 mod timestamp;
+#[cfg(feature = "timestamp_add")]
+mod timestamp_add;
+#[cfg(feature = "timestamp_diff")]
+mod timestamp_diff;
+#[cfg(feature = "timestamp_sub")]
+mod timestamp_sub;
 #[cfg(feature = "to_bool")]
 mod to_bool;
 #[cfg(feature = "to_float")]
@@ -290,6 +320,8 @@ mod to_timestamp;
 mod to_unix_timestamp;
 #[cfg(feature = "truncate")]
 mod truncate;
+#[cfg(feature = "truncate_timestamp")]
+mod truncate_timestamp;
 #[cfg(feature = "type_def")]
 mod type_def;
 #[cfg(feature = "unique")]
@@ -300,6 +332,12 @@ mod unnest;
 mod upcase;
 #[cfg(feature = "uuid_v4")]
 mod uuid_v4;
+#[cfg(feature = "validate_json_schema")]
+mod validate_json_schema;
+#[cfg(feature = "xxhash")]
+mod xxhash;
+#[cfg(feature = "zip")]
+mod zip;
 
 // -----------------------------------------------------------------------------
 
@@ -313,10 +351,14 @@ pub use assert_eq::AssertEq;
 pub use boolean::Boolean;
 #[cfg(feature = "ceil")]
 pub use ceil::Ceil;
+#[cfg(feature = "chunks")]
+pub use chunks::Chunks;
 #[cfg(feature = "compact")]
 pub use compact::Compact;
 #[cfg(feature = "contains")]
 pub use contains::Contains;
+#[cfg(feature = "crc32")]
+pub use crc32::Crc32;
 #[cfg(feature = "decode_base64")]
 pub use decode_base64::DecodeBase64;
 #[cfg(feature = "decode_percent")]
@@ -343,8 +385,12 @@ pub use encrypt::Encrypt;
 pub use ends_with::EndsWith;
 #[cfg(feature = "exists")]
 pub use exists::Exists;
+#[cfg(feature = "filter")]
+pub use filter::Filter;
 #[cfg(feature = "find")]
 pub use find::Find;
+#[cfg(feature = "flat_map")]
+pub use flat_map::FlatMap;
 #[cfg(feature = "flatten")]
 pub use flatten::Flatten;
 #[cfg(feature = "float")]
@@ -365,6 +411,10 @@ pub use get::Get;
 pub use get_env_var::GetEnvVar;
 #[cfg(feature = "get_hostname")]
 pub use get_hostname::GetHostname;
+#[cfg(feature = "group_by")]
+pub use group_by::GroupBy;
+#[cfg(feature = "hmac")]
+pub use hmac::Hmac;
 #[cfg(feature = "includes")]
 pub use includes::Includes;
 #[cfg(feature = "integer")]
@@ -441,6 +491,8 @@ pub use parse_aws_alb_log::ParseAwsAlbLog;
 pub use parse_aws_cloudwatch_log_subscription_message::ParseAwsCloudWatchLogSubscriptionMessage;
 #[cfg(feature = "parse_aws_vpc_flow_log")]
 pub use parse_aws_vpc_flow_log::ParseAwsVpcFlowLog;
+#[cfg(feature = "parse_cef")]
+pub use parse_cef::ParseCef;
 #[cfg(feature = "parse_common_log")]
 pub use parse_common_log::ParseCommonLog;
 #[cfg(feature = "parse_csv")]
@@ -461,6 +513,8 @@ pub use parse_json::ParseJson;
 pub use parse_key_value::ParseKeyValue;
 #[cfg(feature = "parse_klog")]
 pub use parse_klog::ParseKlog;
+#[cfg(feature = "parse_leef")]
+pub use parse_leef::ParseLeef;
 #[cfg(feature = "parse_linux_authorization")]
 pub use parse_linux_authorization::ParseLinuxAuthorization;
 #[cfg(feature = "parse_logfmt")]
@@ -485,6 +539,8 @@ pub use parse_tokens::ParseTokens;
 pub use parse_url::ParseUrl;
 #[cfg(feature = "parse_user_agent")]
 pub use parse_user_agent::ParseUserAgent;
+#[cfg(feature = "parse_windows_event_xml")]
+pub use parse_windows_event_xml::ParseWindowsEventXml;
 #[cfg(feature = "parse_xml")]
 pub use parse_xml::ParseXml;
 #[cfg(feature = "push")]
@@ -495,6 +551,8 @@ pub use r#match::Match;
 pub use random_bytes::RandomBytes;
 #[cfg(feature = "redact")]
 pub use redact::Redact;
+#[cfg(feature = "reduce")]
+pub use reduce::Reduce;
 #[cfg(feature = "remove")]
 pub use remove::Remove;
 #[cfg(feature = "replace")]
@@ -503,6 +561,8 @@ pub use replace::Replace;
 pub use reverse_dns::ReverseDns;
 #[cfg(feature = "round")]
 pub use round::Round;
+#[cfg(feature = "seahash")]
+pub use seahash::Seahash;
 #[cfg(feature = "set")]
 pub use set::Set;
 #[cfg(feature = "sha2")]
@@ -511,6 +571,8 @@ pub use sha2::Sha2;
 pub use sha3::Sha3;
 #[cfg(feature = "slice")]
 pub use slice::Slice;
+#[cfg(feature = "sort_by")]
+pub use sort_by::SortBy;
 #[cfg(feature = "split")]
 pub use split::Split;
 #[cfg(feature = "starts_with")]
@@ -531,6 +593,12 @@ pub use tally::Tally;
 pub use tally_value::TallyValue;
 #[cfg(feature = "timestamp")]
 pub use timestamp::Timestamp;
+#[cfg(feature = "timestamp_add")]
+pub use timestamp_add::TimestampAdd;
+#[cfg(feature = "timestamp_diff")]
+pub use timestamp_diff::TimestampDiff;
+#[cfg(feature = "timestamp_sub")]
+pub use timestamp_sub::TimestampSub;
 #[cfg(feature = "to_bool")]
 pub use to_bool::ToBool;
 #[cfg(feature = "to_float")]
@@ -553,6 +621,8 @@ pub use to_timestamp::ToTimestamp;
 pub use to_unix_timestamp::ToUnixTimestamp;
 #[cfg(feature = "truncate")]
 pub use truncate::Truncate;
+#[cfg(feature = "truncate_timestamp")]
+pub use truncate_timestamp::TruncateTimestamp;
 #[cfg(feature = "type_def")]
 pub use type_def::TypeDef;
 #[cfg(feature = "unique")]
@@ -563,6 +633,12 @@ pub use unnest::Unnest;
 pub use upcase::Upcase;
 #[cfg(feature = "uuid_v4")]
 pub use uuid_v4::UuidV4;
+#[cfg(feature = "validate_json_schema")]
+pub use validate_json_schema::{load_json_schema, JsonSchemaError, ValidateJsonSchema};
+#[cfg(feature = "xxhash")]
+pub use xxhash::Xxhash;
+#[cfg(feature = "zip")]
+pub use zip::Zip;
 
 #[cfg(feature = "array")]
 pub use crate::array::Array;
@@ -586,10 +662,14 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Boolean),
         #[cfg(feature = "ceil")]
         Box::new(Ceil),
+        #[cfg(feature = "chunks")]
+        Box::new(Chunks),
         #[cfg(feature = "compact")]
         Box::new(Compact),
         #[cfg(feature = "contains")]
         Box::new(Contains),
+        #[cfg(feature = "crc32")]
+        Box::new(Crc32),
         #[cfg(feature = "decode_base64")]
         Box::new(DecodeBase64),
         #[cfg(feature = "decode_percent")]
@@ -616,8 +696,12 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(EndsWith),
         #[cfg(feature = "exists")]
         Box::new(Exists),
+        #[cfg(feature = "filter")]
+        Box::new(Filter),
         #[cfg(feature = "find")]
         Box::new(Find),
+        #[cfg(feature = "flat_map")]
+        Box::new(FlatMap),
         #[cfg(feature = "flatten")]
         Box::new(Flatten),
         #[cfg(feature = "float")]
@@ -638,6 +722,10 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(GetEnvVar),
         #[cfg(feature = "get_hostname")]
         Box::new(GetHostname),
+        #[cfg(feature = "group_by")]
+        Box::new(GroupBy),
+        #[cfg(feature = "hmac")]
+        Box::new(Hmac),
         #[cfg(feature = "includes")]
         Box::new(Includes),
         #[cfg(feature = "integer")]
@@ -720,6 +808,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseAwsCloudWatchLogSubscriptionMessage),
         #[cfg(feature = "parse_aws_vpc_flow_log")]
         Box::new(ParseAwsVpcFlowLog),
+        #[cfg(feature = "parse_cef")]
+        Box::new(ParseCef),
         #[cfg(feature = "parse_common_log")]
         Box::new(ParseCommonLog),
         #[cfg(feature = "parse_csv")]
@@ -740,6 +830,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseKeyValue),
         #[cfg(feature = "parse_klog")]
         Box::new(ParseKlog),
+        #[cfg(feature = "parse_leef")]
+        Box::new(ParseLeef),
         #[cfg(feature = "parse_linux_authorization")]
         Box::new(ParseLinuxAuthorization),
         #[cfg(feature = "parse_logfmt")]
@@ -764,6 +856,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseUrl),
         #[cfg(feature = "parse_user_agent")]
         Box::new(ParseUserAgent),
+        #[cfg(feature = "parse_windows_event_xml")]
+        Box::new(ParseWindowsEventXml),
         #[cfg(feature = "parse_xml")]
         Box::new(ParseXml),
         #[cfg(feature = "push")]
@@ -772,6 +866,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(RandomBytes),
         #[cfg(feature = "redact")]
         Box::new(Redact),
+        #[cfg(feature = "reduce")]
+        Box::new(Reduce),
         #[cfg(feature = "remove")]
         Box::new(Remove),
         #[cfg(feature = "replace")]
@@ -780,6 +876,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ReverseDns),
         #[cfg(feature = "round")]
         Box::new(Round),
+        #[cfg(feature = "seahash")]
+        Box::new(Seahash),
         #[cfg(feature = "set")]
         Box::new(Set),
         #[cfg(feature = "sha1")]
@@ -790,6 +888,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Sha3),
         #[cfg(feature = "slice")]
         Box::new(Slice),
+        #[cfg(feature = "sort_by")]
+        Box::new(SortBy),
         #[cfg(feature = "split")]
         Box::new(Split),
         #[cfg(feature = "starts_with")]
@@ -810,6 +910,12 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(TagTypesExternally),
         #[cfg(feature = "timestamp")]
         Box::new(Timestamp),
+        #[cfg(feature = "timestamp_add")]
+        Box::new(TimestampAdd),
+        #[cfg(feature = "timestamp_diff")]
+        Box::new(TimestampDiff),
+        #[cfg(feature = "timestamp_sub")]
+        Box::new(TimestampSub),
         #[cfg(feature = "to_bool")]
         Box::new(ToBool),
         #[cfg(feature = "to_float")]
@@ -832,6 +938,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ToUnixTimestamp),
         #[cfg(feature = "truncate")]
         Box::new(Truncate),
+        #[cfg(feature = "truncate_timestamp")]
+        Box::new(TruncateTimestamp),
         #[cfg(feature = "type_def")]
         Box::new(TypeDef),
         #[cfg(feature = "unique")]
@@ -842,5 +950,11 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Upcase),
         #[cfg(feature = "uuid_v4")]
         Box::new(UuidV4),
+        #[cfg(feature = "validate_json_schema")]
+        Box::new(ValidateJsonSchema),
+        #[cfg(feature = "xxhash")]
+        Box::new(Xxhash),
+        #[cfg(feature = "zip")]
+        Box::new(Zip),
     ]
 }
@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util::split_header;
+
+// Information about the Common Event Format taken from the
+// - ArcSight CEF specification: https://www.microfocus.com/documentation/arcsight/arcsight-smartconnectors/pdfdoc/common-event-format-v25/common-event-format-v25.pdf
+
+/// The header fields following the `CEF:` prefix, in order.
+const HEADER_FIELDS: [&str; 7] = [
+    "cef_version",
+    "device_vendor",
+    "device_product",
+    "device_version",
+    "device_event_class_id",
+    "name",
+    "severity",
+];
+
+fn parse_cef(value: Value, translate_custom_fields: bool) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let input = String::from_utf8_lossy(&bytes);
+
+    // Anything in front of the header, such as a syslog prefix, is ignored.
+    let start = input
+        .find("CEF:")
+        .ok_or("unable to parse CEF: missing \"CEF:\" header")?;
+    let (header, extension) = split_header(&input[start + 4..], HEADER_FIELDS.len())
+        .ok_or("unable to parse CEF: incomplete header")?;
+
+    let mut result = BTreeMap::new();
+
+    for (key, field) in HEADER_FIELDS.iter().zip(header) {
+        let value: Value = match *key {
+            "cef_version" => field
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| format!("unable to parse CEF: invalid version {:?}", field))?
+                .into(),
+            _ => field.into(),
+        };
+
+        result.insert((*key).to_owned(), value);
+    }
+
+    let mut extensions = parse_extensions(extension);
+
+    if translate_custom_fields {
+        translate(&mut extensions);
+    }
+
+    result.insert(
+        "extensions".to_owned(),
+        extensions
+            .into_iter()
+            .map(|(key, value)| (key, value.into()))
+            .collect::<BTreeMap<_, Value>>()
+            .into(),
+    );
+
+    Ok(result.into())
+}
+
+/// Parse the space-separated `key=value` pairs of the extension.
+///
+/// Values can contain spaces, so a value ends where the next key starts. A
+/// key is a word directly in front of an unescaped `=`, following a space.
+/// Any `=` that isn't preceded by a valid key is considered part of the
+/// value.
+fn parse_extensions(input: &str) -> BTreeMap<String, String> {
+    let input = input.trim();
+
+    // The start of each key, and the position of the `=` following it.
+    let mut keys: Vec<(usize, usize)> = vec![];
+    let mut escaped = false;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '=' => {
+                let start = input[..index].rfind(' ').map_or(0, |space| space + 1);
+                let follows_value = keys.last().map_or(true, |&(_, previous)| start > previous);
+
+                if follows_value && is_key(&input[start..index]) {
+                    keys.push((start, index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    keys.iter()
+        .enumerate()
+        .map(|(i, &(start, separator))| {
+            let end = keys.get(i + 1).map_or(input.len(), |&(next, _)| next);
+
+            (
+                input[start..separator].to_owned(),
+                unescape(input[separator + 1..end].trim_end()),
+            )
+        })
+        .collect()
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '[' | ']'))
+}
+
+/// Unescape an extension value.
+///
+/// Unknown escape sequences are kept as-is.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some(c @ ('=' | '\\')) => unescaped.push(c),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/// Replace custom fields, such as `cs1`, with their label.
+///
+/// Every extension `<key>` that has a matching `<key>Label` extension is
+/// renamed to the value of that label.
+fn translate(extensions: &mut BTreeMap<String, String>) {
+    let custom_keys = extensions
+        .keys()
+        .filter_map(|key| key.strip_suffix("Label"))
+        .filter(|key| extensions.contains_key(*key))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+
+    for key in custom_keys {
+        let label = extensions.remove(&format!("{}Label", key));
+        let value = extensions.remove(&key);
+
+        if let (Some(label), Some(value)) = (label, value) {
+            extensions.insert(label, value);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseCef;
+
+impl Function for ParseCef {
+    fn identifier(&self) -> &'static str {
+        "parse_cef"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "translate_custom_fields",
+                kind: kind::BOOLEAN,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "parse CEF",
+                source: r#"parse_cef!("CEF:0|Security|threatmanager|1.0|100|worm successfully stopped|10|src=10.0.0.1 dst=2.1.2.2 spt=1232")"#,
+                result: Ok(indoc! { r#"{
+                    "cef_version": 0,
+                    "device_vendor": "Security",
+                    "device_product": "threatmanager",
+                    "device_version": "1.0",
+                    "device_event_class_id": "100",
+                    "name": "worm successfully stopped",
+                    "severity": "10",
+                    "extensions": {
+                        "src": "10.0.0.1",
+                        "dst": "2.1.2.2",
+                        "spt": "1232"
+                    }
+                }"# }),
+            },
+            Example {
+                title: "translate custom fields",
+                source: r#"parse_cef!("CEF:0|Vendor|Product|1.0|login|User logged in|3|suser=alice cs1Label=department cs1=Research and development", translate_custom_fields: true)"#,
+                result: Ok(indoc! { r#"{
+                    "cef_version": 0,
+                    "device_vendor": "Vendor",
+                    "device_product": "Product",
+                    "device_version": "1.0",
+                    "device_event_class_id": "login",
+                    "name": "User logged in",
+                    "severity": "3",
+                    "extensions": {
+                        "department": "Research and development",
+                        "suser": "alice"
+                    }
+                }"# }),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let translate_custom_fields = arguments.optional("translate_custom_fields");
+
+        Ok(Box::new(ParseCefFn {
+            value,
+            translate_custom_fields,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseCefFn {
+    value: Box<dyn Expression>,
+    translate_custom_fields: Option<Box<dyn Expression>>,
+}
+
+impl Expression for ParseCefFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let translate_custom_fields = match &self.translate_custom_fields {
+            Some(expr) => expr.resolve(ctx)?.try_boolean()?,
+            None => false,
+        };
+
+        parse_cef(value, translate_custom_fields)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::object(inner_kind()).fallible()
+    }
+}
+
+fn inner_kind() -> BTreeMap<Field, Kind> {
+    BTreeMap::from([
+        (Field::from("cef_version"), Kind::integer()),
+        (Field::from("device_vendor"), Kind::bytes()),
+        (Field::from("device_product"), Kind::bytes()),
+        (Field::from("device_version"), Kind::bytes()),
+        (Field::from("device_event_class_id"), Kind::bytes()),
+        (Field::from("name"), Kind::bytes()),
+        (Field::from("severity"), Kind::bytes()),
+        (
+            Field::from("extensions"),
+            Kind::object(Collection::from_unknown(Kind::bytes())),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions() {
+        let cases = [
+            ("", vec![]),
+            ("a=1", vec![("a", "1")]),
+            ("a=1 b=2", vec![("a", "1"), ("b", "2")]),
+            (
+                "msg=hello world  act=blocked ",
+                vec![("act", "blocked"), ("msg", "hello world")],
+            ),
+            (r#"msg=a\=b\\c\nd"#, vec![("msg", "a=b\\c\nd")]),
+            (
+                "url=http://x?a=1&b=2 dst=1.2.3.4",
+                vec![("dst", "1.2.3.4"), ("url", "http://x?a=1&b=2")],
+            ),
+            ("cs1=a=b", vec![("cs1", "a=b")]),
+            ("a= b=", vec![("a", ""), ("b", "")]),
+            (r#"path=C:\temp\x"#, vec![("path", r#"C:\temp\x"#)]),
+        ];
+
+        for (input, want) in cases {
+            let want = want
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect::<BTreeMap<_, _>>();
+
+            assert_eq!(parse_extensions(input), want, "{}", input);
+        }
+    }
+
+    test_function![
+        parse_cef => ParseCef;
+
+        default {
+            args: func_args![value: "CEF:0|Security|threatmanager|1.0|100|worm successfully stopped|10|src=10.0.0.1 dst=2.1.2.2 spt=1232"],
+            want: Ok(value!({
+                cef_version: 0,
+                device_vendor: "Security",
+                device_product: "threatmanager",
+                device_version: "1.0",
+                device_event_class_id: "100",
+                name: "worm successfully stopped",
+                severity: "10",
+                extensions: {
+                    src: "10.0.0.1",
+                    dst: "2.1.2.2",
+                    spt: "1232",
+                },
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        escaped_header {
+            args: func_args![value: r#"<134>Feb 14 19:04:54 host CEF:1|Vendor\|Inc|Product\\Suite|2.0|id|name|Low|"#],
+            want: Ok(value!({
+                cef_version: 1,
+                device_vendor: "Vendor|Inc",
+                device_product: "Product\\Suite",
+                device_version: "2.0",
+                device_event_class_id: "id",
+                name: "name",
+                severity: "Low",
+                extensions: {},
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        missing_extension_delimiter {
+            args: func_args![value: "CEF:0|a|b|c|d|e|5"],
+            want: Ok(value!({
+                cef_version: 0,
+                device_vendor: "a",
+                device_product: "b",
+                device_version: "c",
+                device_event_class_id: "d",
+                name: "e",
+                severity: "5",
+                extensions: {},
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        translate_custom_fields {
+            args: func_args![
+                value: "CEF:0|a|b|c|d|e|5|cs1Label=department cs1=R&D cn1=7",
+                translate_custom_fields: true,
+            ],
+            want: Ok(value!({
+                cef_version: 0,
+                device_vendor: "a",
+                device_product: "b",
+                device_version: "c",
+                device_event_class_id: "d",
+                name: "e",
+                severity: "5",
+                extensions: {
+                    department: "R&D",
+                    cn1: "7",
+                },
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        missing_header {
+            args: func_args![value: "foo"],
+            want: Err("unable to parse CEF: missing \"CEF:\" header"),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        incomplete_header {
+            args: func_args![value: "CEF:0|a|b|c"],
+            want: Err("unable to parse CEF: incomplete header"),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        invalid_version {
+            args: func_args![value: "CEF:x|a|b|c|d|e|5|"],
+            want: Err("unable to parse CEF: invalid version \"x\""),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+    ];
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, str::FromStr};
+use std::str::FromStr;
 
 use ::value::Value;
 use once_cell::sync::Lazy;
@@ -6,6 +6,8 @@ use regex::Regex;
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use vrl::prelude::*;
 
+use crate::util::duration_unit_nanos;
+
 fn parse_duration(bytes: Value, unit: Value) -> Resolved {
     let bytes = bytes.try_bytes()?;
     let value = String::from_utf8_lossy(&bytes);
@@ -13,8 +15,8 @@ fn parse_duration(bytes: Value, unit: Value) -> Resolved {
         let bytes = unit.try_bytes()?;
         let string = String::from_utf8_lossy(&bytes);
 
-        UNITS
-            .get(string.as_ref())
+        duration_unit_nanos(&string)
+            .map(Decimal::from)
             .ok_or(format!("unknown unit format: '{}'", string))?
     };
     let captures = RE
@@ -22,8 +24,8 @@ fn parse_duration(bytes: Value, unit: Value) -> Resolved {
         .ok_or(format!("unable to parse duration: '{}'", value))?;
     let value = Decimal::from_str(&captures["value"])
         .map_err(|error| format!("unable to parse number: {}", error))?;
-    let unit = UNITS
-        .get(&captures["unit"])
+    let unit = duration_unit_nanos(&captures["unit"])
+        .map(Decimal::from)
         .ok_or(format!("unknown duration unit: '{}'", &captures["unit"]))?;
     let number = value * unit / conversion_factor;
     let number = number
@@ -44,24 +46,6 @@ static RE: Lazy<Regex> = Lazy::new(|| {
     .unwrap()
 });
 
-static UNITS: Lazy<HashMap<String, Decimal>> = Lazy::new(|| {
-    vec![
-        ("ns", Decimal::new(1, 9)),
-        ("us", Decimal::new(1, 6)),
-        ("µs", Decimal::new(1, 6)),
-        ("ms", Decimal::new(1, 3)),
-        ("cs", Decimal::new(1, 2)),
-        ("ds", Decimal::new(1, 1)),
-        ("s", Decimal::new(1, 0)),
-        ("m", Decimal::new(60, 0)),
-        ("h", Decimal::new(3_600, 0)),
-        ("d", Decimal::new(86_400, 0)),
-    ]
-    .into_iter()
-    .map(|(k, v)| (k.to_owned(), v))
-    .collect()
-});
-
 #[derive(Clone, Copy, Debug)]
 pub struct ParseDuration;
 
@@ -181,6 +165,13 @@ mod tests {
             tdef: TypeDef::float().fallible(),
         }
 
+        case_insensitive {
+            args: func_args![value: "1S",
+                             unit: "MS"],
+            want: Ok(1000.0),
+            tdef: TypeDef::float().fallible(),
+        }
+
         error_invalid {
             args: func_args![value: "foo",
                              unit: "ms"],
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util::split_header;
+
+// Information about the Log Event Extended Format taken from the
+// - IBM QRadar LEEF documentation: https://www.ibm.com/docs/en/dsm?topic=leef-overview
+
+/// The header fields following the `LEEF:` prefix, in order.
+const HEADER_FIELDS: [&str; 5] = [
+    "leef_version",
+    "vendor",
+    "product_name",
+    "product_version",
+    "event_id",
+];
+
+fn parse_leef(value: Value) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let input = String::from_utf8_lossy(&bytes);
+
+    // Anything in front of the header, such as a syslog prefix, is ignored.
+    let start = input
+        .find("LEEF:")
+        .ok_or("unable to parse LEEF: missing \"LEEF:\" header")?;
+    let input = &input[start + 5..];
+
+    // LEEF 2.0 adds an optional delimiter field to the header, which defaults
+    // to a tab if it is empty.
+    let version = input.split('|').next().unwrap_or_default().trim();
+    let (count, has_delimiter) = match version {
+        "1.0" => (HEADER_FIELDS.len(), false),
+        "2.0" => (HEADER_FIELDS.len() + 1, true),
+        _ => return Err(format!("unable to parse LEEF: unsupported version {:?}", version).into()),
+    };
+
+    let (mut header, attributes) =
+        split_header(input, count).ok_or("unable to parse LEEF: incomplete header")?;
+
+    let delimiter = if has_delimiter {
+        parse_delimiter(&header.pop().unwrap_or_default())?
+    } else {
+        '\t'
+    };
+
+    let mut result = HEADER_FIELDS
+        .iter()
+        .zip(header)
+        .map(|(key, field)| ((*key).to_owned(), Value::from(field)))
+        .collect::<BTreeMap<_, _>>();
+
+    let attributes = attributes
+        .split(delimiter)
+        .map(|attribute| attribute.trim_end_matches(&['\r', '\n'][..]))
+        .filter(|attribute| !attribute.is_empty())
+        .filter_map(|attribute| attribute.split_once('='))
+        .map(|(key, value)| (key.trim().to_owned(), Value::from(value)))
+        .collect::<BTreeMap<_, _>>();
+
+    result.insert("attributes".to_owned(), attributes.into());
+
+    Ok(result.into())
+}
+
+/// Parse the attribute delimiter of a LEEF 2.0 header.
+///
+/// The delimiter is either a single character, or its hex code point in the
+/// form `xHH` or `0xHH`.
+fn parse_delimiter(delimiter: &str) -> Result<char> {
+    let mut chars = delimiter.chars();
+
+    match (chars.next(), chars.next()) {
+        (None, _) => return Ok('\t'),
+        (Some(c), None) => return Ok(c),
+        _ => {}
+    }
+
+    delimiter
+        .strip_prefix("0x")
+        .or_else(|| delimiter.strip_prefix('x'))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+        .ok_or_else(|| format!("unable to parse LEEF: invalid delimiter {:?}", delimiter).into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseLeef;
+
+impl Function for ParseLeef {
+    fn identifier(&self) -> &'static str {
+        "parse_leef"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "parse LEEF 1.0",
+                source: r#"parse_leef!("LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|src=10.50.1.1\tdst=2.10.20.20\tsev=5")"#,
+                result: Ok(indoc! { r#"{
+                    "leef_version": "1.0",
+                    "vendor": "Microsoft",
+                    "product_name": "MSExchange",
+                    "product_version": "4.0 SP1",
+                    "event_id": "15345",
+                    "attributes": {
+                        "src": "10.50.1.1",
+                        "dst": "2.10.20.20",
+                        "sev": "5"
+                    }
+                }"# }),
+            },
+            Example {
+                title: "parse LEEF 2.0 with a custom delimiter",
+                source: r#"parse_leef!("LEEF:2.0|Lancope|StealthWatch|1.0|41|^|src=10.0.1.8^dst=10.0.0.5^sev=5")"#,
+                result: Ok(indoc! { r#"{
+                    "leef_version": "2.0",
+                    "vendor": "Lancope",
+                    "product_name": "StealthWatch",
+                    "product_version": "1.0",
+                    "event_id": "41",
+                    "attributes": {
+                        "src": "10.0.1.8",
+                        "dst": "10.0.0.5",
+                        "sev": "5"
+                    }
+                }"# }),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseLeefFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseLeefFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseLeefFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_leef(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::object(inner_kind()).fallible()
+    }
+}
+
+fn inner_kind() -> BTreeMap<Field, Kind> {
+    BTreeMap::from([
+        (Field::from("leef_version"), Kind::bytes()),
+        (Field::from("vendor"), Kind::bytes()),
+        (Field::from("product_name"), Kind::bytes()),
+        (Field::from("product_version"), Kind::bytes()),
+        (Field::from("event_id"), Kind::bytes()),
+        (
+            Field::from("attributes"),
+            Kind::object(Collection::from_unknown(Kind::bytes())),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        parse_leef => ParseLeef;
+
+        version_1 {
+            args: func_args![value: "LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|src=10.50.1.1\tdst=2.10.20.20\tmsg=a=b c\t\t"],
+            want: Ok(value!({
+                leef_version: "1.0",
+                vendor: "Microsoft",
+                product_name: "MSExchange",
+                product_version: "4.0 SP1",
+                event_id: "15345",
+                attributes: {
+                    src: "10.50.1.1",
+                    dst: "2.10.20.20",
+                    msg: "a=b c",
+                },
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        version_2_hex_delimiter {
+            args: func_args![value: "<13>Jan 18 11:07:53 host LEEF:2.0|Vendor\\|Inc|Product|1.0|id|0x7C|a=1|b=2"],
+            want: Ok(value!({
+                leef_version: "2.0",
+                vendor: "Vendor|Inc",
+                product_name: "Product",
+                product_version: "1.0",
+                event_id: "id",
+                attributes: {
+                    a: "1",
+                    b: "2",
+                },
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        version_2_default_delimiter {
+            args: func_args![value: "LEEF:2.0|Vendor|Product|1.0|id||a=1\tb=2"],
+            want: Ok(value!({
+                leef_version: "2.0",
+                vendor: "Vendor",
+                product_name: "Product",
+                product_version: "1.0",
+                event_id: "id",
+                attributes: {
+                    a: "1",
+                    b: "2",
+                },
+            })),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        unsupported_version {
+            args: func_args![value: "LEEF:3.0|Vendor|Product|1.0|id|"],
+            want: Err("unable to parse LEEF: unsupported version \"3.0\""),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        invalid_delimiter {
+            args: func_args![value: "LEEF:2.0|Vendor|Product|1.0|id|xZZ|a=1"],
+            want: Err("unable to parse LEEF: invalid delimiter \"xZZ\""),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        incomplete_header {
+            args: func_args![value: "LEEF:1.0|Vendor"],
+            want: Err("unable to parse LEEF: incomplete header"),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+    ];
+}
@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+
+use ::value::Value;
+use chrono::{DateTime, Utc};
+use roxmltree::{Document, Node};
+use vrl::prelude::*;
+
+use crate::parse_xml::{process_node, trim_xml, ParseXmlConfig};
+
+// Information about the Windows event schema taken from the
+// - Windows event schema documentation: https://docs.microsoft.com/en-us/windows/win32/wes/eventschema-schema
+
+fn parse_windows_event_xml(value: Value) -> Resolved {
+    let string = value.try_bytes_utf8_lossy()?;
+    let xml = trim_xml(&string);
+    let doc = Document::parse(&xml).map_err(|e| format!("unable to parse xml: {}", e))?;
+
+    let event = doc.root_element();
+    if event.tag_name().name() != "Event" {
+        return Err(format!(
+            r#"unable to parse Windows event: expected root element "Event", got "{}""#,
+            event.tag_name().name()
+        )
+        .into());
+    }
+
+    let result = event
+        .children()
+        .filter(Node::is_element)
+        .map(|section| {
+            let name = section.tag_name().name();
+            let value = match name {
+                "System" => parse_system(section),
+                "EventData" => parse_event_data(section),
+                // Sections such as `UserData` and `RenderingInfo` have no fixed
+                // schema, so they are parsed like any other XML.
+                _ => process_node(section, &ParseXmlConfig::default()),
+            };
+
+            (snake_case(name), value)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    Ok(result.into())
+}
+
+/// Parse the `System` section, which contains the fields common to all
+/// events.
+///
+/// Fields without attributes are converted to their value, and fields with
+/// attributes to an object of those attributes.
+fn parse_system(node: Node) -> Value {
+    let mut fields = BTreeMap::new();
+
+    for field in node.children().filter(Node::is_element) {
+        let name = field.tag_name().name();
+        let text = field.text().unwrap_or_default();
+
+        match name {
+            "TimeCreated" => {
+                let time = field.attribute("SystemTime").unwrap_or_default();
+                let value: Value = DateTime::parse_from_rfc3339(time)
+                    .map_or_else(|_| time.into(), |time| time.with_timezone(&Utc).into());
+
+                fields.insert("time_created".to_owned(), value);
+            }
+            // Events from classic event sources qualify the event ID.
+            "EventID" => {
+                fields.insert("event_id".to_owned(), scalar(text));
+
+                if let Some(qualifiers) = field.attribute("Qualifiers") {
+                    fields.insert("event_id_qualifiers".to_owned(), scalar(qualifiers));
+                }
+            }
+            _ if field.attributes().is_empty() => {
+                fields.insert(snake_case(name), scalar(text));
+            }
+            _ => {
+                let mut attributes = field
+                    .attributes()
+                    .iter()
+                    .map(|attr| (snake_case(attr.name()), scalar(attr.value())))
+                    .collect::<BTreeMap<_, _>>();
+
+                if !text.is_empty() {
+                    attributes.insert("value".to_owned(), scalar(text));
+                }
+
+                fields.insert(snake_case(name), attributes.into());
+            }
+        }
+    }
+
+    fields.into()
+}
+
+/// Parse the `EventData` section.
+///
+/// Named `Data` elements are keyed by their name, and the values of unnamed
+/// `Data` elements are collected in the `data` array. Values are kept as
+/// strings, as their meaning depends on the event.
+fn parse_event_data(node: Node) -> Value {
+    let mut data = BTreeMap::new();
+    let mut unnamed = vec![];
+
+    for field in node.children().filter(Node::is_element) {
+        let value = Value::from(field.text().unwrap_or_default());
+
+        match (field.tag_name().name(), field.attribute("Name")) {
+            ("Data", Some(name)) => {
+                data.insert(name.to_owned(), value);
+            }
+            ("Data", None) => unnamed.push(value),
+            (name, _) => {
+                data.insert(snake_case(name), value);
+            }
+        }
+    }
+
+    if !unnamed.is_empty() {
+        data.insert("data".to_owned(), unnamed.into());
+    }
+
+    data.into()
+}
+
+/// Parse integers, falling back to a string.
+fn scalar(text: &str) -> Value {
+    text.parse::<i64>().map_or_else(|_| text.into(), Into::into)
+}
+
+/// Convert a `PascalCase` name, such as `EventRecordID`, to `snake_case`.
+fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).map_or(false, |c| c.is_lowercase());
+
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lowercase)
+            {
+                snake.push('_');
+            }
+        }
+
+        snake.extend(c.to_lowercase());
+    }
+
+    snake
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseWindowsEventXml;
+
+impl Function for ParseWindowsEventXml {
+    fn identifier(&self) -> &'static str {
+        "parse_windows_event_xml"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "parse Windows event",
+            source: indoc! {r#"
+                value = s'<Event xmlns="http://schemas.microsoft.com/win/2004/08/events/event"><System><Provider Name="Microsoft-Windows-Security-Auditing" Guid="{54849625-5478-4994-a5ba-3e3b0328c30d}"/><EventID>4624</EventID><TimeCreated SystemTime="2022-08-02T12:34:56.789Z"/><EventRecordID>1234</EventRecordID><Channel>Security</Channel><Computer>dc01.example.com</Computer></System><EventData><Data Name="TargetUserName">alice</Data><Data Name="LogonType">2</Data></EventData></Event>'
+
+                parse_windows_event_xml!(value)
+            "#},
+            result: Ok(indoc! { r#"{
+                "system": {
+                    "provider": {
+                        "name": "Microsoft-Windows-Security-Auditing",
+                        "guid": "{54849625-5478-4994-a5ba-3e3b0328c30d}"
+                    },
+                    "event_id": 4624,
+                    "time_created": "2022-08-02T12:34:56.789Z",
+                    "event_record_id": 1234,
+                    "channel": "Security",
+                    "computer": "dc01.example.com"
+                },
+                "event_data": {
+                    "TargetUserName": "alice",
+                    "LogonType": "2"
+                }
+            }"# }),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseWindowsEventXmlFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseWindowsEventXmlFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseWindowsEventXmlFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_windows_event_xml(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        type_def()
+    }
+}
+
+fn type_def() -> TypeDef {
+    TypeDef::object(Collection::from_unknown(inner_kind())).fallible()
+}
+
+/// The `System` and `EventData` sections are always objects, but sections
+/// parsed like any other XML are scalars if they only hold text.
+fn inner_kind() -> Kind {
+    Kind::null()
+        | Kind::bytes()
+        | Kind::integer()
+        | Kind::float()
+        | Kind::boolean()
+        | Kind::object(Collection::any())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn to_snake_case() {
+        let cases = [
+            ("EventID", "event_id"),
+            ("EventRecordID", "event_record_id"),
+            ("TimeCreated", "time_created"),
+            ("SystemTime", "system_time"),
+            ("UserData", "user_data"),
+            ("Level", "level"),
+            ("ID", "id"),
+        ];
+
+        for (name, want) in cases {
+            assert_eq!(snake_case(name), want);
+        }
+    }
+
+    test_function![
+        parse_windows_event_xml => ParseWindowsEventXml;
+
+        security_event {
+            args: func_args![value: r#"
+                <Event xmlns="http://schemas.microsoft.com/win/2004/08/events/event">
+                  <System>
+                    <Provider Name="Microsoft-Windows-Security-Auditing" Guid="{54849625-5478-4994-a5ba-3e3b0328c30d}"/>
+                    <EventID>4625</EventID>
+                    <Level>0</Level>
+                    <Keywords>0x8010000000000000</Keywords>
+                    <TimeCreated SystemTime="2022-08-02T12:34:56.7890123Z"/>
+                    <Execution ProcessID="700" ThreadID="4012"/>
+                    <Security/>
+                  </System>
+                  <EventData>
+                    <Data Name="TargetUserName">alice</Data>
+                    <Data Name="IpAddress">10.0.0.1</Data>
+                    <Data Name="SubjectLogonId">0x3e7</Data>
+                    <Data Name="Empty"/>
+                  </EventData>
+                </Event>
+            "#],
+            want: Ok(value!({
+                system: {
+                    provider: {
+                        name: "Microsoft-Windows-Security-Auditing",
+                        guid: "{54849625-5478-4994-a5ba-3e3b0328c30d}",
+                    },
+                    event_id: 4625,
+                    level: 0,
+                    keywords: "0x8010000000000000",
+                    time_created: (Utc.ymd(2022, 8, 2).and_hms_nano(12, 34, 56, 789_012_300)),
+                    execution: {
+                        process_id: 700,
+                        thread_id: 4012,
+                    },
+                    security: "",
+                },
+                event_data: {
+                    TargetUserName: "alice",
+                    IpAddress: "10.0.0.1",
+                    SubjectLogonId: "0x3e7",
+                    Empty: "",
+                },
+            })),
+            tdef: type_def(),
+        }
+
+        classic_event {
+            args: func_args![value: r#"<Event xmlns="http://schemas.microsoft.com/win/2004/08/events/event"><System><Provider Name="Service Control Manager"/><EventID Qualifiers="16384">7036</EventID></System><EventData><Data>Windows Update</Data><Data>stopped</Data><Binary>770075</Binary></EventData><RenderingInfo Culture="en-US"><Message>The service entered the stopped state.</Message></RenderingInfo></Event>"#],
+            want: Ok(value!({
+                system: {
+                    provider: {
+                        name: "Service Control Manager",
+                    },
+                    event_id: 7036,
+                    event_id_qualifiers: 16384,
+                },
+                event_data: {
+                    data: ["Windows Update", "stopped"],
+                    binary: "770075",
+                },
+                rendering_info: {
+                    "@Culture": "en-US",
+                    Message: "The service entered the stopped state.",
+                },
+            })),
+            tdef: type_def(),
+        }
+
+        text_section {
+            args: func_args![value: r#"<Event><System/><UserData>stopped</UserData></Event>"#],
+            want: Ok(value!({
+                system: {},
+                user_data: "stopped",
+            })),
+            tdef: type_def(),
+        }
+
+        not_an_event {
+            args: func_args![value: "<foo/>"],
+            want: Err(r#"unable to parse Windows event: expected root element "Event", got "foo""#),
+            tdef: type_def(),
+        }
+    ];
+}
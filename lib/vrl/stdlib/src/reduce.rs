@@ -0,0 +1,183 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn reduce<T>(
+    value: Value,
+    initial: Value,
+    ctx: &mut Context,
+    runner: closure::Runner<T>,
+) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let values: Vec<Value> = match value {
+        Value::Object(object) => object.into_values().collect(),
+        Value::Array(array) => array,
+        value => {
+            return Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+            }
+            .into())
+        }
+    };
+
+    values.into_iter().try_fold(initial, |accumulator, value| {
+        runner.run_values(ctx, vec![accumulator, value])
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Reduce;
+
+impl Function for Reduce {
+    fn identifier(&self) -> &'static str {
+        "reduce"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT | kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "initial",
+                kind: kind::ANY,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "sum array",
+                source: r#"reduce([1, 2, 3], 0) -> |sum, value| { int!(sum) + value }"#,
+                result: Ok("6"),
+            },
+            Example {
+                title: "concatenate object values",
+                source: r#"reduce({ "a": "foo", "b": "bar" }, "") -> |result, value| { string!(result) + value }"#,
+                result: Ok(r#""foobar""#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let initial = arguments.required("initial");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(ReduceFn {
+            value,
+            initial,
+            closure,
+        }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    // The accumulator is either the initial value, or the
+                    // result of any previous run of the closure.
+                    Variable {
+                        kind: VariableKind::Exact(Kind::any()),
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::any()),
+                example: Example {
+                    title: "count array items",
+                    source: r#"reduce(["a", "b"], 0) -> |count, _value| { int!(count) + 1 }"#,
+                    result: Ok("2"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReduceFn {
+    value: Box<dyn Expression>,
+    initial: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for ReduceFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let initial = self.initial.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        reduce(value, initial, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let initial = self.initial.type_def(ctx);
+        let closure = self.closure.block.type_def(ctx);
+
+        // The result is the initial value if the collection is empty, or the
+        // result of the last run of the closure otherwise.
+        initial.merge_deep(closure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        reduce => Reduce;
+
+        array {
+            args: ArgumentList::from(func_args![value: value!([1, 2, 3]), initial: 0])
+                .with_closure(test_closure!(sum: Kind::integer(), value: Kind::integer() => "sum + value")),
+            want: Ok(6),
+            tdef: TypeDef::integer().infallible(),
+        }
+
+        object {
+            args: ArgumentList::from(func_args![value: value!({"a": "foo", "b": "bar"}), initial: ""])
+                .with_closure(test_closure!(result: Kind::bytes(), value: Kind::bytes() => "result + value")),
+            want: Ok("foobar"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        empty_array {
+            args: ArgumentList::from(func_args![value: Value::Array(vec![]), initial: "none"])
+                .with_closure(test_closure!(result: Kind::bytes(), value: Kind::any() => "result")),
+            want: Ok("none"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        empty_object {
+            args: ArgumentList::from(func_args![value: Value::Object(BTreeMap::new()), initial: "none"])
+                .with_closure(test_closure!(result: Kind::bytes(), value: Kind::any() => "result")),
+            want: Ok("none"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        not_a_collection {
+            args: ArgumentList::from(func_args![value: 1, initial: 0])
+                .with_closure(test_closure!(sum: Kind::integer(), value: Kind::integer() => "sum + value")),
+            want: Err("expected array or object, got integer"),
+            tdef: TypeDef::integer().infallible(),
+        }
+    ];
+}
@@ -0,0 +1,83 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn seahash(value: Value) -> Resolved {
+    let value = value.try_bytes()?;
+
+    // Keeps all 64 bits of the hash, so about half of all hashes are negative.
+    Ok(i64::from_ne_bytes(::seahash::hash(&value).to_ne_bytes()).into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Seahash;
+
+impl Function for Seahash {
+    fn identifier(&self) -> &'static str {
+        "seahash"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "seahash",
+            source: r#"seahash("foobar")"#,
+            result: Ok("5348458858952426560"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(SeahashFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SeahashFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for SeahashFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        seahash(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::integer().infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        seahash => Seahash;
+
+        seahash {
+            args: func_args![value: "foo"],
+            want: Ok(4_413_582_353_838_009_230_i64),
+            tdef: TypeDef::integer().infallible(),
+        }
+
+        negative {
+            args: func_args![value: ""],
+            want: Ok(-3_953_938_083_091_587_911_i64),
+            tdef: TypeDef::integer().infallible(),
+        }
+    ];
+}
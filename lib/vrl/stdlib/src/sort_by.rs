@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+
+use ::value::Value;
+use vrl::prelude::*;
+
+fn sort_by<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let mut keyed = value
+        .try_array()?
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let key = runner.run_values(ctx, vec![index.into(), value.clone()])?;
+            Ok((key, value))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // The sort is stable, so items with equal keys keep their order.
+    keyed.sort_by(|(lhs, _), (rhs, _)| compare(lhs, rhs));
+
+    Ok(keyed
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect::<Vec<_>>()
+        .into())
+}
+
+/// Compare two sort keys.
+///
+/// Keys of different types are ordered by type: booleans first, then numbers,
+/// strings and timestamps. Integers and floats are compared by their numeric
+/// value.
+fn compare(lhs: &Value, rhs: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Boolean(_) => 0,
+            Value::Integer(_) | Value::Float(_) => 1,
+            Value::Bytes(_) => 2,
+            Value::Timestamp(_) => 3,
+            _ => 4,
+        }
+    }
+
+    match (lhs, rhs) {
+        (Value::Boolean(lhs), Value::Boolean(rhs)) => lhs.cmp(rhs),
+        (Value::Integer(lhs), Value::Integer(rhs)) => lhs.cmp(rhs),
+        (Value::Float(lhs), Value::Float(rhs)) => lhs.cmp(rhs),
+        (Value::Integer(lhs), Value::Float(rhs)) => {
+            NotNan::new(*lhs as f64).map_or(Ordering::Equal, |lhs| lhs.cmp(rhs))
+        }
+        (Value::Float(lhs), Value::Integer(rhs)) => {
+            NotNan::new(*rhs as f64).map_or(Ordering::Equal, |rhs| lhs.cmp(&rhs))
+        }
+        (Value::Bytes(lhs), Value::Bytes(rhs)) => lhs.cmp(rhs),
+        (Value::Timestamp(lhs), Value::Timestamp(rhs)) => lhs.cmp(rhs),
+        (lhs, rhs) => rank(lhs).cmp(&rank(rhs)),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SortBy;
+
+impl Function for SortBy {
+    fn identifier(&self) -> &'static str {
+        "sort_by"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "sort by field",
+                source: r#"sort_by([{ "name": "b", "age": 30 }, { "name": "a", "age": 20 }]) -> |_index, value| { value.age }"#,
+                result: Ok(r#"[{ "age": 20, "name": "a" }, { "age": 30, "name": "b" }]"#),
+            },
+            Example {
+                title: "sort by length",
+                source: r#"sort_by(["ccc", "a", "bb"]) -> |_index, value| { strlen(value) }"#,
+                result: Ok(r#"["a", "bb", "ccc"]"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(SortByFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(
+                    Kind::boolean()
+                        .or_integer()
+                        .or_float()
+                        .or_bytes()
+                        .or_timestamp(),
+                ),
+                example: Example {
+                    title: "sort descending",
+                    source: r#"sort_by([1, 3, 2]) -> |_index, value| { 0 - value }"#,
+                    result: Ok("[3, 2, 1]"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SortByFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for SortByFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        sort_by(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let item = self
+            .value
+            .type_def(ctx)
+            .as_array()
+            .map_or_else(Kind::any, Collection::reduced_kind);
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+
+        TypeDef::array(Collection::from_unknown(item)).with_fallibility(fallible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_mixed_keys() {
+        let mut keys = vec![
+            value!("b"),
+            value!(2.5),
+            value!(true),
+            value!(3),
+            value!("a"),
+            value!(1),
+        ];
+
+        keys.sort_by(compare);
+
+        assert_eq!(
+            keys,
+            vec![
+                value!(true),
+                value!(1),
+                value!(2.5),
+                value!(3),
+                value!("a"),
+                value!("b"),
+            ]
+        );
+    }
+}
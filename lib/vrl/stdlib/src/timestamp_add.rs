@@ -0,0 +1,154 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util;
+
+fn timestamp_add(value: Value, duration: Value) -> Resolved {
+    let timestamp = value.try_timestamp()?;
+    let duration = util::duration(duration)?;
+
+    timestamp
+        .checked_add_signed(duration)
+        .map(Into::into)
+        .ok_or_else(|| "timestamp out of range".into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampAdd;
+
+impl Function for TimestampAdd {
+    fn identifier(&self) -> &'static str {
+        "timestamp_add"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::TIMESTAMP,
+                required: true,
+            },
+            Parameter {
+                keyword: "duration",
+                kind: kind::BYTES | kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "add duration",
+                source: r#"timestamp_add!(t'2021-02-10T23:32:00Z', "1h30m")"#,
+                result: Ok("t'2021-02-11T01:02:00Z'"),
+            },
+            Example {
+                title: "add seconds",
+                source: "timestamp_add!(t'2021-02-10T23:32:00Z', 90)",
+                result: Ok("t'2021-02-10T23:33:30Z'"),
+            },
+            Example {
+                title: "add negative duration",
+                source: r#"timestamp_add!(t'2021-02-10T23:32:00Z', "-1d")"#,
+                result: Ok("t'2021-02-09T23:32:00Z'"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let duration = arguments.required("duration");
+
+        Ok(Box::new(TimestampAddFn { value, duration }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TimestampAddFn {
+    value: Box<dyn Expression>,
+    duration: Box<dyn Expression>,
+}
+
+impl Expression for TimestampAddFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let duration = self.duration.resolve(ctx)?;
+
+        timestamp_add(value, duration)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::timestamp().fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    test_function![
+        timestamp_add => TimestampAdd;
+
+        seconds {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: 90],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 33, 30)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        fractional_seconds {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: 1.5],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms_milli(23, 32, 1, 500)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        compound_duration {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "1h30m"],
+            want: Ok(Utc.ymd(2021, 2, 11).and_hms(1, 2, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        case_insensitive_units {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "1H30M"],
+            want: Ok(Utc.ymd(2021, 2, 11).and_hms(1, 2, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        fractional_duration {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "1.5d"],
+            want: Ok(Utc.ymd(2021, 2, 12).and_hms(11, 32, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        negative_duration {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "-250ms"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms_milli(23, 31, 59, 750)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        unknown_unit {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "1w"],
+            want: Err("unknown duration unit: 'w'"),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        invalid_duration {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "h"],
+            want: Err("unable to parse duration: 'h'"),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        out_of_range {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: i64::MAX],
+            want: Err("duration out of range"),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+    ];
+}
@@ -0,0 +1,162 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util::{duration_unit_nanos, DURATION_UNITS};
+
+fn timestamp_diff(end: Value, start: Value, unit_nanos: i64) -> Resolved {
+    let end = end.try_timestamp()?;
+    let start = start.try_timestamp()?;
+    let duration = end.signed_duration_since(start);
+
+    // Differences that don't fit in nanoseconds lose their sub-millisecond precision.
+    let nanos = duration.num_nanoseconds().map_or_else(
+        || duration.num_milliseconds() as f64 * 1e6,
+        |nanos| nanos as f64,
+    );
+
+    Ok(Value::from_f64_or_zero(nanos / unit_nanos as f64))
+}
+
+fn units() -> Vec<Value> {
+    DURATION_UNITS
+        .iter()
+        .map(|(unit, _)| (*unit).into())
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampDiff;
+
+impl Function for TimestampDiff {
+    fn identifier(&self) -> &'static str {
+        "timestamp_diff"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "end",
+                kind: kind::TIMESTAMP,
+                required: true,
+            },
+            Parameter {
+                keyword: "start",
+                kind: kind::TIMESTAMP,
+                required: true,
+            },
+            Parameter {
+                keyword: "unit",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "seconds",
+                source: "timestamp_diff(t'2021-02-10T23:32:00Z', t'2021-02-10T23:30:30Z')",
+                result: Ok("90.0"),
+            },
+            Example {
+                title: "hours",
+                source: r#"timestamp_diff(t'2021-02-10T23:32:00Z', t'2021-02-11T01:02:00Z', unit: "h")"#,
+                result: Ok("-1.5"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let end = arguments.required("end");
+        let start = arguments.required("start");
+        let unit_nanos = arguments
+            .optional_enum("unit", &units())?
+            .map_or(1_000_000_000, |unit| {
+                let unit = unit.try_bytes_utf8_lossy().expect("unit not bytes");
+
+                duration_unit_nanos(&unit).expect("validated enum")
+            });
+
+        Ok(Box::new(TimestampDiffFn {
+            end,
+            start,
+            unit_nanos,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TimestampDiffFn {
+    end: Box<dyn Expression>,
+    start: Box<dyn Expression>,
+    unit_nanos: i64,
+}
+
+impl Expression for TimestampDiffFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let end = self.end.resolve(ctx)?;
+        let start = self.start.resolve(ctx)?;
+
+        timestamp_diff(end, start, self.unit_nanos)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::float().infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    test_function![
+        timestamp_diff => TimestampDiff;
+
+        seconds {
+            args: func_args![end: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0),
+                             start: Utc.ymd(2021, 2, 10).and_hms(23, 30, 30)],
+            want: Ok(90.0),
+            tdef: TypeDef::float().infallible(),
+        }
+
+        milliseconds {
+            args: func_args![end: Utc.ymd(2021, 2, 10).and_hms_milli(23, 32, 0, 250),
+                             start: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0),
+                             unit: "ms"],
+            want: Ok(250.0),
+            tdef: TypeDef::float().infallible(),
+        }
+
+        negative {
+            args: func_args![end: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0),
+                             start: Utc.ymd(2021, 2, 11).and_hms(1, 2, 0),
+                             unit: "h"],
+            want: Ok(-1.5),
+            tdef: TypeDef::float().infallible(),
+        }
+
+        days {
+            args: func_args![end: Utc.ymd(2021, 3, 1).and_hms(0, 0, 0),
+                             start: Utc.ymd(2021, 2, 1).and_hms(0, 0, 0),
+                             unit: "d"],
+            want: Ok(28.0),
+            tdef: TypeDef::float().infallible(),
+        }
+
+        large {
+            args: func_args![end: Utc.ymd(2500, 1, 1).and_hms(0, 0, 0),
+                             start: Utc.ymd(1970, 1, 1).and_hms(0, 0, 0),
+                             unit: "d"],
+            want: Ok(193_579.0),
+            tdef: TypeDef::float().infallible(),
+        }
+    ];
+}
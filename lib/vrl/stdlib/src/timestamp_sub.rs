@@ -0,0 +1,119 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util;
+
+fn timestamp_sub(value: Value, duration: Value) -> Resolved {
+    let timestamp = value.try_timestamp()?;
+    let duration = util::duration(duration)?;
+
+    timestamp
+        .checked_sub_signed(duration)
+        .map(Into::into)
+        .ok_or_else(|| "timestamp out of range".into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampSub;
+
+impl Function for TimestampSub {
+    fn identifier(&self) -> &'static str {
+        "timestamp_sub"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::TIMESTAMP,
+                required: true,
+            },
+            Parameter {
+                keyword: "duration",
+                kind: kind::BYTES | kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "subtract duration",
+                source: r#"timestamp_sub!(t'2021-02-10T23:32:00Z', "1h30m")"#,
+                result: Ok("t'2021-02-10T22:02:00Z'"),
+            },
+            Example {
+                title: "subtract seconds",
+                source: "timestamp_sub!(t'2021-02-10T23:32:00Z', 90)",
+                result: Ok("t'2021-02-10T23:30:30Z'"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let duration = arguments.required("duration");
+
+        Ok(Box::new(TimestampSubFn { value, duration }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TimestampSubFn {
+    value: Box<dyn Expression>,
+    duration: Box<dyn Expression>,
+}
+
+impl Expression for TimestampSubFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let duration = self.duration.resolve(ctx)?;
+
+        timestamp_sub(value, duration)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::timestamp().fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    test_function![
+        timestamp_sub => TimestampSub;
+
+        seconds {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: 90],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 30, 30)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        compound_duration {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "1d 2h"],
+            want: Ok(Utc.ymd(2021, 2, 9).and_hms(21, 32, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        negative_duration {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "-30m"],
+            want: Ok(Utc.ymd(2021, 2, 11).and_hms(0, 2, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        unknown_unit {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 0), duration: "5 minutes"],
+            want: Err("unknown duration unit: 'minutes'"),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+    ];
+}
@@ -0,0 +1,253 @@
+use std::str::FromStr;
+
+use ::value::Value;
+use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone, Timelike, Utc};
+use vrl::prelude::*;
+
+use crate::util::timezone_argument;
+
+fn truncate_timestamp(
+    value: Value,
+    unit: Unit,
+    timezone: Option<&vector_common::TimeZone>,
+) -> Resolved {
+    let timestamp = value.try_timestamp()?;
+
+    let truncated = match timezone {
+        None => truncate(timestamp, &Utc, unit),
+        Some(vector_common::TimeZone::Local) => truncate(timestamp, &Local, unit),
+        Some(vector_common::TimeZone::Named(tz)) => truncate(timestamp, tz, unit),
+    };
+
+    truncated.map(Into::into)
+}
+
+/// Truncates the timestamp to the start of its second, minute, hour or day, in
+/// the local time of the given timezone.
+fn truncate<Tz: TimeZone>(
+    timestamp: DateTime<Utc>,
+    tz: &Tz,
+    unit: Unit,
+) -> std::result::Result<DateTime<Utc>, ExpressionError> {
+    let local = timestamp.with_timezone(tz).naive_local();
+    let (hour, minute, second) = match unit {
+        Unit::Second => (local.hour(), local.minute(), local.second()),
+        Unit::Minute => (local.hour(), local.minute(), 0),
+        Unit::Hour => (local.hour(), 0, 0),
+        Unit::Day => (0, 0, 0),
+    };
+    let truncated: NaiveDateTime = local
+        .date()
+        .and_hms_opt(hour, minute, second)
+        .expect("valid time");
+
+    // When clocks are turned back, the truncated local time occurs twice, in
+    // which case the last one that isn't after the timestamp is used.
+    match tz.from_local_datetime(&truncated) {
+        LocalResult::Single(dt) => Ok(dt.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, latest) => {
+            if latest.naive_utc() <= timestamp.naive_utc() {
+                Ok(latest.with_timezone(&Utc))
+            } else {
+                Ok(earliest.with_timezone(&Utc))
+            }
+        }
+        LocalResult::None => Err(format!("local time {} does not exist", truncated).into()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TruncateTimestamp;
+
+impl Function for TruncateTimestamp {
+    fn identifier(&self) -> &'static str {
+        "truncate_timestamp"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::TIMESTAMP,
+                required: true,
+            },
+            Parameter {
+                keyword: "unit",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "timezone",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "truncate to the hour",
+                source: r#"truncate_timestamp(t'2021-02-10T23:32:10Z', "hour")"#,
+                result: Ok("t'2021-02-10T23:00:00Z'"),
+            },
+            Example {
+                title: "truncate to the day in a timezone",
+                source: r#"truncate_timestamp!(t'2021-02-10T23:32:10Z', "day", timezone: "Europe/Paris")"#,
+                result: Ok("t'2021-02-10T23:00:00Z'"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let unit = arguments
+            .required_enum("unit", &Unit::all_value())
+            .map(|s| {
+                Unit::from_str(&s.try_bytes_utf8_lossy().expect("unit not bytes"))
+                    .expect("validated enum")
+            })?;
+        let timezone = timezone_argument(&mut arguments)?;
+
+        Ok(Box::new(TruncateTimestampFn {
+            value,
+            unit,
+            timezone,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TruncateTimestampFn {
+    value: Box<dyn Expression>,
+    unit: Unit,
+    timezone: Option<vector_common::TimeZone>,
+}
+
+impl Expression for TruncateTimestampFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        truncate_timestamp(value, self.unit, self.timezone.as_ref())
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        // Truncating in UTC always results in an existing time.
+        TypeDef::timestamp().with_fallibility(self.timezone.is_some())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Unit {
+    fn all_value() -> Vec<Value> {
+        use Unit::{Day, Hour, Minute, Second};
+
+        vec![Second, Minute, Hour, Day]
+            .into_iter()
+            .map(|u| u.as_str().into())
+            .collect::<Vec<_>>()
+    }
+
+    const fn as_str(self) -> &'static str {
+        use Unit::{Day, Hour, Minute, Second};
+
+        match self {
+            Second => "second",
+            Minute => "minute",
+            Hour => "hour",
+            Day => "day",
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        use Unit::{Day, Hour, Minute, Second};
+
+        match s {
+            "second" => Ok(Second),
+            "minute" => Ok(Minute),
+            "hour" => Ok(Hour),
+            "day" => Ok(Day),
+            _ => Err("unit not recognized"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        truncate_timestamp => TruncateTimestamp;
+
+        second {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms_milli(23, 32, 10, 500), unit: "second"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 32, 10)),
+            tdef: TypeDef::timestamp().infallible(),
+        }
+
+        minute {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "minute"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 32, 0)),
+            tdef: TypeDef::timestamp().infallible(),
+        }
+
+        hour {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "hour"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 0, 0)),
+            tdef: TypeDef::timestamp().infallible(),
+        }
+
+        day {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "day"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(0, 0, 0)),
+            tdef: TypeDef::timestamp().infallible(),
+        }
+
+        day_in_timezone {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "day", timezone: "Europe/Paris"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 0, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        hour_in_half_hour_timezone {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "hour", timezone: "Asia/Kolkata"],
+            want: Ok(Utc.ymd(2021, 2, 10).and_hms(23, 30, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        hour_after_clocks_turned_back {
+            // 01:30 EST, the second time 01:30 occurs on that day.
+            args: func_args![value: Utc.ymd(2021, 11, 7).and_hms(6, 30, 0), unit: "hour", timezone: "America/New_York"],
+            want: Ok(Utc.ymd(2021, 11, 7).and_hms(6, 0, 0)),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+
+        invalid_unit {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "week"],
+            want: Err(r#"invalid enum variant""#),
+            tdef: TypeDef::timestamp().infallible(),
+        }
+
+        invalid_timezone {
+            args: func_args![value: Utc.ymd(2021, 2, 10).and_hms(23, 32, 10), unit: "day", timezone: "Mars/Olympus_Mons"],
+            want: Err(r#"invalid argument"#),
+            tdef: TypeDef::timestamp().fallible(),
+        }
+    ];
+}
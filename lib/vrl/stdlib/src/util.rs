@@ -82,6 +82,46 @@ pub(crate) fn is_nullish(value: &::value::Value) -> bool {
     }
 }
 
+/// Splits the pipe-delimited header of a CEF or LEEF message into `count`
+/// fields, returning the unescaped fields and the remainder of the message.
+///
+/// Pipes and backslashes within header fields are escaped with a backslash.
+/// The delimiter after the last field is optional if nothing follows it.
+#[cfg(any(feature = "parse_cef", feature = "parse_leef"))]
+pub(crate) fn split_header(input: &str, count: usize) -> Option<(Vec<String>, &str)> {
+    let mut fields = Vec::with_capacity(count);
+    let mut field = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, c @ ('|' | '\\'))) => field.push(c),
+                Some((_, c)) => {
+                    field.push('\\');
+                    field.push(c);
+                }
+                None => field.push('\\'),
+            },
+            '|' => {
+                fields.push(std::mem::take(&mut field));
+
+                if fields.len() == count {
+                    return Some((fields, &input[index + 1..]));
+                }
+            }
+            c => field.push(c),
+        }
+    }
+
+    if fields.len() + 1 == count {
+        fields.push(field);
+        return Some((fields, ""));
+    }
+
+    None
+}
+
 #[cfg(any(feature = "decode_base64", feature = "encode_base64"))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Base64Charset {
@@ -122,3 +162,151 @@ impl std::str::FromStr for Base64Charset {
         }
     }
 }
+
+/// The units a duration can be expressed in, with their length in nanoseconds.
+#[cfg(any(
+    feature = "parse_duration",
+    feature = "timestamp_add",
+    feature = "timestamp_diff",
+    feature = "timestamp_sub"
+))]
+pub(crate) const DURATION_UNITS: &[(&str, i64)] = &[
+    ("ns", 1),
+    ("us", 1_000),
+    ("µs", 1_000),
+    ("ms", 1_000_000),
+    ("cs", 10_000_000),
+    ("ds", 100_000_000),
+    ("s", 1_000_000_000),
+    ("m", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("d", 86_400_000_000_000),
+];
+
+/// Returns the length in nanoseconds of a duration unit, ignoring its case.
+#[cfg(any(
+    feature = "parse_duration",
+    feature = "timestamp_add",
+    feature = "timestamp_diff",
+    feature = "timestamp_sub"
+))]
+pub(crate) fn duration_unit_nanos(unit: &str) -> Option<i64> {
+    DURATION_UNITS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+        .map(|(_, nanos)| *nanos)
+}
+
+/// Converts a duration into a [`chrono::Duration`].
+///
+/// Integers and floats are a number of seconds, while strings are parsed as a
+/// sequence of numbers and units, such as `1h30m` or `-250ms`.
+#[cfg(any(feature = "timestamp_add", feature = "timestamp_sub"))]
+pub(crate) fn duration(value: ::value::Value) -> Result<chrono::Duration, String> {
+    use ::value::Value;
+
+    let out_of_range = || "duration out of range".to_owned();
+
+    let nanos = match value {
+        Value::Integer(secs) => secs.checked_mul(1_000_000_000).ok_or_else(out_of_range)?,
+        Value::Float(secs) => {
+            let nanos = (secs.into_inner() * 1e9).round();
+            if !nanos.is_finite() || nanos.abs() >= i64::MAX as f64 {
+                return Err(out_of_range());
+            }
+            nanos as i64
+        }
+        Value::Bytes(bytes) => parse_duration_nanos(&String::from_utf8_lossy(&bytes))?,
+        value => return Err(format!("unable to use {} as a duration", value.kind())),
+    };
+
+    Ok(chrono::Duration::nanoseconds(nanos))
+}
+
+#[cfg(any(feature = "timestamp_add", feature = "timestamp_sub"))]
+fn parse_duration_nanos(input: &str) -> Result<i64, String> {
+    let invalid = || format!("unable to parse duration: '{}'", input);
+
+    let trimmed = input.trim();
+    let (negative, mut rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total: i64 = 0;
+
+    while !rest.is_empty() {
+        let is_number = |c: char| c.is_ascii_digit() || c == '.';
+
+        let (number, tail) = rest.split_at(rest.find(|c| !is_number(c)).unwrap_or(rest.len()));
+        let tail = tail.trim_start();
+        let (unit, tail) = tail.split_at(
+            tail.find(|c: char| is_number(c) || c.is_whitespace())
+                .unwrap_or(tail.len()),
+        );
+
+        if unit.is_empty() {
+            return Err(invalid());
+        }
+
+        let unit_nanos = duration_unit_nanos(unit)
+            .ok_or_else(|| format!("unknown duration unit: '{}'", unit))?;
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+
+        let whole = match whole {
+            "" => 0,
+            whole => whole.parse::<i64>().map_err(|_| invalid())?,
+        };
+        let fraction = match fraction {
+            "" => 0,
+            fraction => {
+                let fraction = format!("0.{}", fraction)
+                    .parse::<f64>()
+                    .map_err(|_| invalid())?;
+                (fraction * unit_nanos as f64).round() as i64
+            }
+        };
+
+        total = whole
+            .checked_mul(unit_nanos)
+            .and_then(|nanos| nanos.checked_add(fraction))
+            .and_then(|nanos| total.checked_add(nanos))
+            .ok_or_else(|| "duration out of range".to_owned())?;
+
+        rest = tail.trim_start();
+    }
+
+    Ok(if negative { -total } else { total })
+}
+
+/// Reads the optional `timezone` argument, which must be a literal timezone
+/// name, such as `Europe/Paris` or `local`.
+#[cfg(any(feature = "format_timestamp", feature = "truncate_timestamp"))]
+pub(crate) fn timezone_argument(
+    arguments: &mut vrl::prelude::ArgumentList,
+) -> Result<Option<vector_common::TimeZone>, vrl::function::Error> {
+    arguments
+        .optional_literal("timezone")?
+        .map(|literal| {
+            let value = literal.to_value();
+            let name = value
+                .try_bytes_utf8_lossy()
+                .expect("timezone not bytes")
+                .into_owned();
+
+            vector_common::TimeZone::parse(&name).ok_or(vrl::function::Error::InvalidArgument {
+                keyword: "timezone",
+                value,
+                error: "unknown timezone",
+            })
+        })
+        .transpose()
+}
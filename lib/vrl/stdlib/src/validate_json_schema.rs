@@ -0,0 +1,281 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+};
+
+use ::value::Value;
+use jsonschema::JSONSchema;
+use once_cell::sync::Lazy;
+use vrl::prelude::*;
+
+/// Compiled schemas, by the path and contents of the file they were read from.
+///
+/// Programs and conditions that use the same schema share a single compiled
+/// copy of it, for as long as any of them are around, while a schema file that
+/// has changed since is compiled anew.
+static SCHEMAS: Lazy<Mutex<HashMap<(PathBuf, String), Weak<JSONSchema>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn validate_json_schema(value: &Value, schema: &JSONSchema) -> Resolved {
+    let instance = serde_json::to_value(value)
+        .map_err(|error| format!("unable to convert value to JSON: {}", error))?;
+
+    let errors = match schema.validate(&instance) {
+        Ok(()) => vec![],
+        Err(errors) => errors
+            .map(|error| {
+                BTreeMap::from([
+                    ("instance_path".to_owned(), error.instance_path.to_string()),
+                    ("schema_path".to_owned(), error.schema_path.to_string()),
+                    ("message".to_owned(), error.to_string()),
+                ])
+                .into()
+            })
+            .collect(),
+    };
+
+    Ok(Value::Array(errors))
+}
+
+/// An error that occurred while loading a JSON Schema.
+#[derive(Debug)]
+pub enum JsonSchemaError {
+    /// The schema file couldn't be read.
+    Read { path: PathBuf, source: io::Error },
+    /// The schema file isn't valid JSON.
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// The schema isn't a valid JSON Schema.
+    Compile { path: PathBuf, message: String },
+}
+
+impl JsonSchemaError {
+    /// A short description of the error, without the path of the schema.
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            Self::Read { .. } => "unable to read JSON schema",
+            Self::Parse { .. } => "unable to parse JSON schema",
+            Self::Compile { .. } => "invalid JSON schema",
+        }
+    }
+}
+
+impl fmt::Display for JsonSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read { path, source } => {
+                write!(f, "{} {}: {}", self.reason(), path.display(), source)
+            }
+            Self::Parse { path, source } => {
+                write!(f, "{} {}: {}", self.reason(), path.display(), source)
+            }
+            Self::Compile { path, message } => {
+                write!(f, "{} {}: {}", self.reason(), path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonSchemaError {}
+
+/// Read and compile the JSON Schema at the given path.
+///
+/// This is also used by the `json_schema` condition, so that both load
+/// schemas the same way, and share the compiled schema when they use the same
+/// file.
+pub fn load_json_schema(path: &Path) -> Result<Arc<JSONSchema>, JsonSchemaError> {
+    let contents = fs::read_to_string(path).map_err(|source| JsonSchemaError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let key = (path.to_path_buf(), contents);
+    let mut schemas = SCHEMAS.lock().expect("poisoned lock");
+    if let Some(schema) = schemas.get(&key).and_then(Weak::upgrade) {
+        return Ok(schema);
+    }
+
+    let schema = serde_json::from_str(&key.1).map_err(|source| JsonSchemaError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let schema = JSONSchema::compile(&schema).map_err(|error| JsonSchemaError::Compile {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    })?;
+    let schema = Arc::new(schema);
+
+    // Drop the schemas that are no longer used by anything along the way.
+    schemas.retain(|_, schema| schema.strong_count() > 0);
+    schemas.insert(key, Arc::downgrade(&schema));
+
+    Ok(schema)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ValidateJsonSchema;
+
+impl Function for ValidateJsonSchema {
+    fn identifier(&self) -> &'static str {
+        "validate_json_schema"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::ANY,
+                required: true,
+            },
+            Parameter {
+                keyword: "schema_path",
+                kind: kind::BYTES,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "valid",
+                source: r#"validate_json_schema!({ "name": "alice", "age": 42 }, "tests/data/json_schema/user.schema.json")"#,
+                result: Ok("[]"),
+            },
+            Example {
+                title: "invalid",
+                source: r#"validate_json_schema!({ "name": "alice", "age": "old" }, "tests/data/json_schema/user.schema.json")"#,
+                result: Ok(indoc! {r#"[
+                    {"instance_path": "/age",
+                     "schema_path": "/properties/age/type",
+                     "message": "\"old\" is not of type \"integer\""}]"#}),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let schema_path = arguments.required_literal("schema_path")?.to_value();
+        let path = schema_path
+            .try_bytes_utf8_lossy()
+            .expect("schema path not bytes")
+            .into_owned();
+
+        // The schema is compiled along with the program, so that changes to
+        // the schema file are picked up whenever the program is recompiled.
+        let schema = load_json_schema(Path::new(&path)).map_err(|error| {
+            Box::new(vrl::function::Error::InvalidArgument {
+                keyword: "schema_path",
+                value: schema_path,
+                error: error.reason(),
+            }) as Box<dyn DiagnosticMessage>
+        })?;
+
+        Ok(Box::new(ValidateJsonSchemaFn { value, schema }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ValidateJsonSchemaFn {
+    value: Box<dyn Expression>,
+    schema: Arc<JSONSchema>,
+}
+
+impl Expression for ValidateJsonSchemaFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        validate_json_schema(&value, &self.schema)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::array(Collection::from_unknown(Kind::object(inner_kind()))).fallible()
+    }
+}
+
+fn inner_kind() -> BTreeMap<Field, Kind> {
+    BTreeMap::from([
+        ("instance_path".into(), Kind::bytes()),
+        ("schema_path".into(), Kind::bytes()),
+        ("message".into(), Kind::bytes()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use vector_common::btreemap;
+
+    use super::*;
+
+    const SCHEMA: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../../tests/data/json_schema/user.schema.json"
+    );
+
+    test_function![
+        validate_json_schema => ValidateJsonSchema;
+
+        valid {
+            args: func_args![value: btreemap! { "name" => "alice", "age" => 42 }, schema_path: SCHEMA],
+            want: Ok(Value::Array(vec![])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::object(inner_kind()))).fallible(),
+        }
+
+        invalid {
+            args: func_args![value: btreemap! { "name" => "alice", "age" => "old" }, schema_path: SCHEMA],
+            want: Ok(vec![Value::from(btreemap! {
+                "instance_path" => "/age",
+                "schema_path" => "/properties/age/type",
+                "message" => r#""old" is not of type "integer""#,
+            })]),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::object(inner_kind()))).fallible(),
+        }
+
+        missing_field {
+            args: func_args![value: btreemap! { "age" => 42 }, schema_path: SCHEMA],
+            want: Ok(vec![Value::from(btreemap! {
+                "instance_path" => "",
+                "schema_path" => "/required",
+                "message" => r#""name" is a required property"#,
+            })]),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::object(inner_kind()))).fallible(),
+        }
+
+        not_an_object {
+            args: func_args![value: "alice", schema_path: SCHEMA],
+            want: Ok(vec![Value::from(btreemap! {
+                "instance_path" => "",
+                "schema_path" => "/type",
+                "message" => r#""alice" is not of type "object""#,
+            })]),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::object(inner_kind()))).fallible(),
+        }
+    ];
+
+    #[test]
+    fn invalid_schema_path() {
+        let error = load_json_schema(Path::new("does/not/exist.json")).unwrap_err();
+
+        assert_eq!(error.reason(), "unable to read JSON schema");
+        assert!(error
+            .to_string()
+            .starts_with("unable to read JSON schema does/not/exist.json"));
+    }
+
+    #[test]
+    fn schema_shared_while_in_use() {
+        let schema = load_json_schema(Path::new(SCHEMA)).unwrap();
+        let again = load_json_schema(Path::new(SCHEMA)).unwrap();
+
+        assert!(Arc::ptr_eq(&schema, &again));
+    }
+}
@@ -0,0 +1,150 @@
+use std::hash::Hasher;
+
+use ::value::Value;
+use twox_hash::{XxHash32, XxHash64};
+use vrl::prelude::*;
+
+fn xxhash(value: Value, variant: &Bytes) -> Resolved {
+    let value = value.try_bytes()?;
+    let hash = match variant.as_ref() {
+        b"XXH32" => hash(XxHash32::with_seed(0), &value),
+        b"XXH64" => hash(XxHash64::with_seed(0), &value),
+        _ => unreachable!("enum invariant"),
+    };
+
+    // An XXH32 hash always fits, but an XXH64 hash above `i64::MAX` comes out
+    // as a negative integer.
+    Ok(i64::from_ne_bytes(hash.to_ne_bytes()).into())
+}
+
+fn variants() -> Vec<Value> {
+    vec![value!("XXH32"), value!("XXH64")]
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Xxhash;
+
+impl Function for Xxhash {
+    fn identifier(&self) -> &'static str {
+        "xxhash"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "variant",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "default variant",
+                source: r#"xxhash("foobar")"#,
+                result: Ok("-6725556575634347271"),
+            },
+            Example {
+                title: "custom variant",
+                source: r#"xxhash("foobar", "XXH32")"#,
+                result: Ok("3986901679"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let variant = arguments
+            .optional_enum("variant", &variants())?
+            .unwrap_or_else(|| value!("XXH64"))
+            .try_bytes()
+            .expect("variant not bytes");
+
+        Ok(Box::new(XxhashFn { value, variant }))
+    }
+
+    fn compile_argument(
+        &self,
+        _args: &[(&'static str, Option<FunctionArgument>)],
+        _ctx: &mut FunctionCompileContext,
+        name: &str,
+        expr: Option<&expression::Expr>,
+    ) -> CompiledArgument {
+        match (name, expr) {
+            ("variant", Some(expr)) => {
+                let variant = expr
+                    .as_enum("variant", variants())?
+                    .try_bytes()
+                    .expect("variant not bytes");
+
+                Ok(Some(Box::new(variant) as _))
+            }
+            ("variant", None) => Ok(Some(Box::new(Bytes::from("XXH64")) as _)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct XxhashFn {
+    value: Box<dyn Expression>,
+    variant: Bytes,
+}
+
+impl Expression for XxhashFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let variant = &self.variant;
+
+        xxhash(value, variant)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::integer().infallible()
+    }
+}
+
+#[inline]
+fn hash<T: Hasher>(mut hasher: T, value: &[u8]) -> u64 {
+    hasher.write(value);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        xxhash => Xxhash;
+
+        xxh64 {
+            args: func_args![value: "foo"],
+            want: Ok(3_728_699_739_546_630_719_i64),
+            tdef: TypeDef::integer().infallible(),
+        }
+
+        xxh64_empty {
+            args: func_args![value: "", variant: "XXH64"],
+            want: Ok(-1_205_034_819_632_174_695_i64),
+            tdef: TypeDef::integer().infallible(),
+        }
+
+        xxh32 {
+            args: func_args![value: "foo", variant: "XXH32"],
+            want: Ok(3_792_637_401_i64),
+            tdef: TypeDef::integer().infallible(),
+        }
+    ];
+}
@@ -0,0 +1,129 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn zip(array_0: Value, array_1: Value) -> Resolved {
+    Ok(array_0
+        .try_array()?
+        .into_iter()
+        .zip(array_1.try_array()?)
+        .map(|(item_0, item_1)| Value::Array(vec![item_0, item_1]))
+        .collect::<Vec<_>>()
+        .into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Zip;
+
+impl Function for Zip {
+    fn identifier(&self) -> &'static str {
+        "zip"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "array_0",
+                kind: kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "array_1",
+                kind: kind::ARRAY,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "zip arrays",
+                source: r#"zip(["a", "b"], [1, 2])"#,
+                result: Ok(r#"[["a", 1], ["b", 2]]"#),
+            },
+            Example {
+                title: "arrays of different length",
+                source: r#"zip(["a", "b", "c"], [1])"#,
+                result: Ok(r#"[["a", 1]]"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let array_0 = arguments.required("array_0");
+        let array_1 = arguments.required("array_1");
+
+        Ok(Box::new(ZipFn { array_0, array_1 }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ZipFn {
+    array_0: Box<dyn Expression>,
+    array_1: Box<dyn Expression>,
+}
+
+impl Expression for ZipFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let array_0 = self.array_0.resolve(ctx)?;
+        let array_1 = self.array_1.resolve(ctx)?;
+
+        zip(array_0, array_1)
+    }
+
+    fn type_def(&self, state: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let item = |expr: &dyn Expression| {
+            expr.type_def(state)
+                .as_array()
+                .map_or_else(Kind::any, Collection::reduced_kind)
+        };
+
+        let pair = Kind::array(BTreeMap::from([
+            (0.into(), item(self.array_0.as_ref())),
+            (1.into(), item(self.array_1.as_ref())),
+        ]));
+
+        TypeDef::array(Collection::from_unknown(pair)).infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        zip => Zip;
+
+        same_length {
+            args: func_args![array_0: value!(["a", "b"]), array_1: value!([1, 2])],
+            want: Ok(value!([["a", 1], ["b", 2]])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(BTreeMap::from([
+                (Index::from(0), Kind::bytes()),
+                (Index::from(1), Kind::integer()),
+            ])))),
+        }
+
+        different_length {
+            args: func_args![array_0: value!([true]), array_1: value!([1.5, 2.5])],
+            want: Ok(value!([[true, 1.5]])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(BTreeMap::from([
+                (Index::from(0), Kind::boolean()),
+                (Index::from(1), Kind::float()),
+            ])))),
+        }
+
+        empty {
+            args: func_args![array_0: value!([]), array_1: value!([1])],
+            want: Ok(value!([])),
+            tdef: TypeDef::array(Collection::from_unknown(Kind::array(BTreeMap::from([
+                (Index::from(0), Kind::never()),
+                (Index::from(1), Kind::integer()),
+            ])))),
+        }
+    ];
+}
@@ -16,7 +16,7 @@ use vector_common::TimeZone;
 use vrl::{
     diagnostic::Formatter,
     prelude::{BTreeMap, VrlValueConvert},
-    state, Runtime, SecretTarget, TargetValueRef, Terminate, VrlRuntime,
+    state, Runtime, SecretTarget, TargetValueRef, Terminate, Vm, VrlRuntime,
 };
 use vrl_tests::{docs, Test};
 
@@ -226,7 +226,7 @@ fn main() {
 
                 match result {
                     Ok(got) => {
-                        let got = vrl_value_to_json_value(got);
+                        let got = got.into_json_lossy();
                         let mut failed = false;
 
                         if !test.skip {
@@ -303,7 +303,7 @@ fn main() {
                                         }
                                     };
 
-                                let got = vrl_value_to_json_value(test.object.clone());
+                                let got = test.object.clone().into_json_lossy();
                                 if got == want {
                                     println!("{}{}", Colour::Green.bold().paint("OK"), timings);
                                 } else {
@@ -412,6 +412,11 @@ fn run_vrl(
             test_enrichment.finish_load();
             runtime.resolve(&mut target, &program, &timezone)
         }
+        VrlRuntime::Vm => {
+            let vm = Vm::new(&program);
+            test_enrichment.finish_load();
+            runtime.run_vm(&vm, &mut target, &timezone)
+        }
     }
 }
 
@@ -442,25 +447,3 @@ fn print_result(failed_count: usize) {
 
     std::process::exit(code)
 }
-
-fn vrl_value_to_json_value(value: Value) -> serde_json::Value {
-    use serde_json::Value::*;
-
-    match value {
-        v @ Value::Bytes(_) => String(v.try_bytes_utf8_lossy().unwrap().into_owned()),
-        Value::Integer(v) => v.into(),
-        Value::Float(v) => v.into_inner().into(),
-        Value::Boolean(v) => v.into(),
-        Value::Object(v) => v
-            .into_iter()
-            .map(|(k, v)| (k, vrl_value_to_json_value(v)))
-            .collect::<serde_json::Value>(),
-        Value::Array(v) => v
-            .into_iter()
-            .map(vrl_value_to_json_value)
-            .collect::<serde_json::Value>(),
-        Value::Timestamp(v) => v.to_rfc3339_opts(SecondsFormat::AutoSi, true).into(),
-        Value::Regex(v) => v.to_string().into(),
-        Value::Null => Null,
-    }
-}
@@ -5,7 +5,7 @@ use compiler::state;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use indoc::indoc;
 use vector_common::TimeZone;
-use vrl::Runtime;
+use vrl::{Runtime, Vm};
 
 struct Source {
     name: &'static str,
@@ -113,6 +113,22 @@ fn benchmark_vrl_runtimes(c: &mut Criterion) {
                 },
             )
         });
+
+        group.bench_with_input(BenchmarkId::new(source.name, "vm"), &(), |b, _| {
+            let state = state::Runtime::default();
+            let mut runtime = Runtime::new(state);
+            let vm = Vm::new(&program);
+            let target: Value = serde_json::from_str(source.target).expect("valid json");
+
+            b.iter_with_setup(
+                || target.clone(),
+                |mut obj| {
+                    let _ = black_box(runtime.run_vm(&vm, &mut obj, &tz));
+                    runtime.clear();
+                    obj
+                },
+            )
+        });
     }
 }
 
@@ -9,13 +9,15 @@
 
 pub mod prelude;
 mod runtime;
+mod source;
 
 pub use compiler::{
-    function, state, value, Context, Expression, Function, MetadataTarget, Program, ProgramInfo,
-    SecretTarget, Target, TargetValue, TargetValueRef, VrlRuntime,
+    debug, function, profile, state, value, Context, Expression, Function, MetadataTarget, Program,
+    ProgramInfo, SecretTarget, Target, TargetValue, TargetValueRef, Vm, VrlRuntime,
 };
 pub use diagnostic;
 pub use runtime::{Runtime, RuntimeResult, Terminate};
+pub use source::{Source, SourceLine};
 pub use vector_common::TimeZone;
 
 /// Compile a given source into the final [`Program`].
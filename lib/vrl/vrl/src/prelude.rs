@@ -27,8 +27,8 @@ pub use compiler::function::{
 pub use compiler::value::{VrlValueArithmetic, VrlValueConvert};
 // commonly used macros
 pub use compiler::{
-    bench_function, expr, expression::FunctionArgument, func_args, test_function, test_type_def,
-    type_def, value,
+    bench_function, expr, expression::FunctionArgument, func_args, test_closure, test_function,
+    test_type_def, type_def, value,
 };
 pub use diagnostic::DiagnosticMessage;
 pub use indoc::indoc;
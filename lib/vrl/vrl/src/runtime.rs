@@ -4,7 +4,7 @@ use compiler::ExpressionError;
 use lookup::LookupBuf;
 use value::Value;
 
-use crate::{state, Context, Program, Target, TimeZone};
+use crate::{debug::Debugger, profile::Profile, state, Context, Program, Target, TimeZone, Vm};
 
 pub type RuntimeResult = Result<Value, Terminate>;
 
@@ -64,6 +64,21 @@ impl Runtime {
         self.state.clear();
     }
 
+    /// Record the time spent resolving each expression of the programs
+    /// resolved by this runtime.
+    ///
+    /// Profiling is only supported when resolving programs, not when running
+    /// them in the [`Vm`].
+    pub fn enable_profiling(&mut self) {
+        self.state.enable_profiling();
+    }
+
+    /// Take the profile recorded since profiling was enabled, or since it was
+    /// last taken.
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.state.take_profile()
+    }
+
     /// Given the provided [`Target`], resolve the provided [`Program`] to
     /// completion.
     pub fn resolve(
@@ -72,27 +87,65 @@ impl Runtime {
         program: &Program,
         timezone: &TimeZone,
     ) -> RuntimeResult {
-        // Validate that the path is a value.
-        match target.target_get(&self.root_lookup) {
-            Ok(Some(_)) => {}
-            Ok(None) => {
-                return Err(Terminate::Error(
-                    "expected target object, got nothing".to_owned().into(),
-                ))
-            }
-            Err(err) => {
-                return Err(Terminate::Error(
-                    format!("error querying target object: {}", err).into(),
-                ))
-            }
-        };
+        self.validate_target(target)?;
 
         let mut ctx = Context::new(target, &mut self.state, timezone);
 
-        program.resolve(&mut ctx).map_err(|err| match err {
+        program.resolve(&mut ctx).map_err(Into::into)
+    }
+
+    /// Resolve the provided [`Program`] like [`Runtime::resolve`], calling the
+    /// [`Debugger`] before each statement is resolved.
+    pub fn resolve_with_debugger(
+        &mut self,
+        target: &mut dyn Target,
+        program: &Program,
+        timezone: &TimeZone,
+        debugger: &mut dyn Debugger,
+    ) -> RuntimeResult {
+        self.validate_target(target)?;
+
+        let mut ctx = Context::new(target, &mut self.state, timezone).with_debugger(debugger);
+
+        program.resolve(&mut ctx).map_err(Into::into)
+    }
+
+    /// Given the provided [`Target`], run the provided [`Vm`] to completion.
+    ///
+    /// The `Vm` is compiled from a [`Program`] using [`Vm::new`].
+    pub fn run_vm(
+        &mut self,
+        vm: &Vm,
+        target: &mut dyn Target,
+        timezone: &TimeZone,
+    ) -> RuntimeResult {
+        self.validate_target(target)?;
+
+        let mut ctx = Context::new(target, &mut self.state, timezone);
+
+        vm.run(&mut ctx).map_err(Into::into)
+    }
+
+    /// Validate that the path is a value.
+    fn validate_target(&self, target: &dyn Target) -> Result<(), Terminate> {
+        match target.target_get(&self.root_lookup) {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(Terminate::Error(
+                "expected target object, got nothing".to_owned().into(),
+            )),
+            Err(err) => Err(Terminate::Error(
+                format!("error querying target object: {}", err).into(),
+            )),
+        }
+    }
+}
+
+impl From<ExpressionError> for Terminate {
+    fn from(err: ExpressionError) -> Self {
+        match err {
             #[cfg(feature = "expr-abort")]
             ExpressionError::Abort { .. } => Terminate::Abort(err),
             err @ ExpressionError::Error { .. } => Terminate::Error(err),
-        })
+        }
     }
 }
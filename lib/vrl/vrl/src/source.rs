@@ -0,0 +1,358 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use diagnostic::{DiagnosticList, DiagnosticMessage, Formatter, Label, Note, Span};
+use parser::ast::{self, Node, RootExpr};
+
+use crate::{state, Function};
+
+/// A VRL program source, including any modules it imports.
+///
+/// Modules are imported using `import "path/to/module.vrl"`, and can only
+/// contain function definitions, or further imports. Relative import paths are
+/// resolved against the directory of the importing file, or the current
+/// working directory for programs that aren't loaded from a file.
+///
+/// All imported modules share the span space of the main source, so that any
+/// diagnostic can be rendered against the file it originated from, using
+/// [`Source::formatter`].
+#[derive(Debug, Clone)]
+pub struct Source {
+    source: String,
+    base_dir: Option<PathBuf>,
+    modules: Vec<Module>,
+}
+
+/// A line of a [`Source`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLine<'a> {
+    /// The imported module the line is in, or `None` for the main source.
+    pub file: Option<&'a str>,
+
+    /// The number of the line, starting at 1.
+    pub number: usize,
+
+    /// The text of the line, without its line ending.
+    pub text: &'a str,
+}
+
+#[derive(Debug, Clone)]
+struct Module {
+    name: String,
+    source: String,
+    offset: usize,
+}
+
+impl Source {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            base_dir: None,
+            modules: vec![],
+        }
+    }
+
+    /// Set the directory against which relative imports of the main source
+    /// are resolved.
+    #[must_use]
+    pub fn with_base_dir(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(base_dir.into());
+        self
+    }
+
+    /// The main source of the program.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Parse the source, resolve all its imports, and compile the result into
+    /// the final [`Program`](crate::Program).
+    pub fn compile(
+        &mut self,
+        fns: &[Box<dyn Function>],
+        state: &mut state::ExternalEnv,
+    ) -> compiler::Result {
+        self.modules.clear();
+
+        let ast = parser::parse(&self.source)
+            .map_err(|err| DiagnosticList::from(vec![Box::new(err) as Box<_>]))?;
+
+        let mut seen = HashSet::new();
+        let mut diagnostics: Vec<Box<dyn DiagnosticMessage>> = vec![];
+        let base_dir = self.base_dir.clone();
+
+        let root_exprs =
+            self.resolve_imports(ast, base_dir.as_deref(), false, &mut seen, &mut diagnostics);
+
+        if !diagnostics.is_empty() {
+            return Err(diagnostics.into());
+        }
+
+        compiler::compile_with_state(ast::Program(root_exprs), fns, state)
+    }
+
+    /// Get a formatter to display diagnostics returned by [`Source::compile`].
+    pub fn formatter(&self, diagnostics: impl Into<DiagnosticList>) -> Formatter<'_> {
+        self.modules.iter().fold(
+            Formatter::new(&self.source, diagnostics),
+            |formatter, module| formatter.with_file(&module.name, &module.source, module.offset),
+        )
+    }
+
+    /// Find the line holding the given offset, in the main source or any of
+    /// the imported modules.
+    pub fn line(&self, offset: usize) -> SourceLine<'_> {
+        let (file, source, offset) = self
+            .modules
+            .iter()
+            .rev()
+            .find(|module| offset >= module.offset)
+            .map_or((None, self.source.as_str(), offset), |module| {
+                (
+                    Some(module.name.as_str()),
+                    module.source.as_str(),
+                    offset - module.offset,
+                )
+            });
+
+        let offset = offset.min(source.len());
+        let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |i| offset + i);
+
+        SourceLine {
+            file,
+            number: source[..start].matches('\n').count() + 1,
+            text: &source[start..end],
+        }
+    }
+
+    fn resolve_imports(
+        &mut self,
+        program: ast::Program,
+        dir: Option<&Path>,
+        is_module: bool,
+        seen: &mut HashSet<PathBuf>,
+        diagnostics: &mut Vec<Box<dyn DiagnosticMessage>>,
+    ) -> Vec<Node<RootExpr>> {
+        let mut root_exprs = vec![];
+
+        for node in program.0 {
+            let span = node.span();
+
+            match node.into_inner() {
+                RootExpr::Import(import) => {
+                    let (span, path) = import.into_inner().path.take();
+                    let path = dir.map_or_else(|| PathBuf::from(&path), |dir| dir.join(&path));
+
+                    let (path, source) = match fs::canonicalize(&path)
+                        .and_then(|path| fs::read_to_string(&path).map(|source| (path, source)))
+                    {
+                        Ok(module) => module,
+                        Err(err) => {
+                            diagnostics.push(Box::new(Error::Read {
+                                span,
+                                path,
+                                error: err.to_string(),
+                            }));
+                            continue;
+                        }
+                    };
+
+                    // Each module is only imported once, which also resolves
+                    // any import cycles.
+                    if !seen.insert(path.clone()) {
+                        continue;
+                    }
+
+                    let offset = self.next_offset();
+                    let program = match parser::parse_with_offset(&source, offset) {
+                        Ok(program) => program,
+                        Err(err) => {
+                            diagnostics.push(Box::new(err));
+                            ast::Program(vec![])
+                        }
+                    };
+
+                    self.modules.push(Module {
+                        name: path.display().to_string(),
+                        source,
+                        offset,
+                    });
+
+                    let module_exprs =
+                        self.resolve_imports(program, path.parent(), true, seen, diagnostics);
+
+                    root_exprs.extend(module_exprs);
+                }
+                RootExpr::Expr(expr) if is_module => {
+                    diagnostics.push(Box::new(Error::InvalidModule { span: expr.span() }));
+                }
+                root_expr => root_exprs.push(Node::new(span, root_expr)),
+            }
+        }
+
+        root_exprs
+    }
+
+    /// The offset at which the next imported module starts.
+    ///
+    /// Each module is separated by a single position, so that the end of one
+    /// file never overlaps the start of the next.
+    fn next_offset(&self) -> usize {
+        self.modules.last().map_or(self.source.len(), |module| {
+            module.offset + module.source.len()
+        }) + 1
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[derive(thiserror::Error, Debug)]
+enum Error {
+    #[error("unable to read import")]
+    Read {
+        span: Span,
+        path: PathBuf,
+        error: String,
+    },
+
+    #[error("invalid module content")]
+    InvalidModule { span: Span },
+}
+
+impl DiagnosticMessage for Error {
+    fn code(&self) -> usize {
+        match self {
+            Error::Read { .. } => 141,
+            Error::InvalidModule { .. } => 142,
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match self {
+            Error::Read { span, path, error } => vec![
+                Label::primary(
+                    format!(r#"unable to read "{}": {}"#, path.display(), error),
+                    span,
+                ),
+                Label::context(
+                    "relative paths are resolved from the directory of the importing file",
+                    span,
+                ),
+            ],
+            Error::InvalidModule { span } => vec![
+                Label::primary("expression not allowed in imported module", span),
+                Label::context(
+                    "modules can only contain function definitions and imports",
+                    span,
+                ),
+            ],
+        }
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        vec![Note::SeeLangDocs]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use value::Value;
+
+    use super::*;
+    use crate::{state, Runtime, TimeZone};
+
+    fn modules_dir() -> &'static Path {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/modules"))
+    }
+
+    fn compile(source: &mut Source) -> compiler::Result {
+        source.compile(&[], &mut state::ExternalEnv::default())
+    }
+
+    #[test]
+    fn import_module() {
+        let mut source = Source::new(indoc::indoc! {r#"
+            import "greet.vrl"
+
+            greet("world")
+        "#})
+        .with_base_dir(modules_dir());
+
+        let (program, _) = compile(&mut source).expect("program compiles");
+
+        let mut target = Value::Null;
+        let mut runtime = Runtime::new(state::Runtime::default());
+        let value = runtime
+            .resolve(&mut target, &program, &TimeZone::default())
+            .expect("program resolves");
+
+        assert_eq!(value, "hello, world".into());
+    }
+
+    #[test]
+    fn import_missing_module() {
+        let mut source = Source::new(r#"import "missing.vrl""#).with_base_dir(modules_dir());
+
+        let diagnostics = compile(&mut source).expect_err("missing module");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), 141);
+        assert_eq!(diagnostics[0].labels()[0].span, Span::new(7, 20));
+    }
+
+    #[test]
+    fn import_module_with_error() {
+        let mut source = Source::new(r#"import "broken.vrl""#).with_base_dir(modules_dir());
+
+        let diagnostics = compile(&mut source).expect_err("invalid module");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), 701);
+
+        // The error is reported against the imported file, not the main
+        // source.
+        let span = diagnostics[0].labels()[0].span;
+        let line = source.line(span.start());
+        assert!(line.file.expect("imported file").ends_with("broken.vrl"));
+        assert_eq!(line.number, 2);
+        assert_eq!(line.text, "  undefined_variable");
+
+        let formatted = source.formatter(diagnostics).to_string();
+        assert!(formatted.contains("broken.vrl:2:3"));
+    }
+
+    #[test]
+    fn line() {
+        let mut source =
+            Source::new("import \"greet.vrl\"\n\ngreet(\"world\")\n").with_base_dir(modules_dir());
+        compile(&mut source).expect("program compiles");
+
+        let call = source.as_str().find("greet(").unwrap();
+        assert_eq!(
+            source.line(call + 2),
+            SourceLine {
+                file: None,
+                number: 3,
+                text: r#"greet("world")"#,
+            }
+        );
+        assert_eq!(source.line(0).number, 1);
+
+        // Modules start right after the main source.
+        let module = source.as_str().len() + 1;
+        let line = source.line(module);
+        assert!(line.file.expect("imported file").ends_with("greet.vrl"));
+        assert_eq!(line.number, 1);
+        assert_eq!(line.text, "fn greet(name) {");
+
+        let line = source.line(module + "fn greet(name) {\n".len() + 2);
+        assert_eq!(line.number, 2);
+        assert_eq!(line.text, r#"  "hello, " + name"#);
+    }
+}
@@ -10,5 +10,7 @@ set -euo pipefail
 (
   cd "$(dirname "${BASH_SOURCE[0]}")/../lib/vrl/tests"
 
-  cargo run -- --runtime=ast
+  for runtime in ast vm; do
+    cargo run -- --runtime="$runtime"
+  done
 )
@@ -0,0 +1,169 @@
+use async_graphql::{Context, Enum, Object, SimpleObject};
+use chrono::{DateTime, Utc};
+use tokio::sync::oneshot;
+
+use crate::{
+    config::{self, ComponentKey},
+    topology::control::{ControlCommand, ControlRequest, ControlResponse, ControlTx},
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(SimpleObject)]
+pub struct Diagnostic {
+    /// Whether the diagnostic prevented the change from being made
+    severity: DiagnosticSeverity,
+    message: String,
+}
+
+#[derive(SimpleObject)]
+pub struct ControlResult {
+    /// Whether the change was made
+    success: bool,
+    /// Errors that prevented the change from being made, and warnings about the change
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ControlResult {
+    fn failure(errors: Vec<String>) -> Self {
+        ControlResponse::failure(errors).into()
+    }
+}
+
+impl From<ControlResponse> for ControlResult {
+    fn from(response: ControlResponse) -> Self {
+        let errors = response.errors.into_iter().map(|message| Diagnostic {
+            severity: DiagnosticSeverity::Error,
+            message,
+        });
+        let warnings = response.warnings.into_iter().map(|message| Diagnostic {
+            severity: DiagnosticSeverity::Warning,
+            message,
+        });
+
+        Self {
+            success: response.success,
+            diagnostics: errors.chain(warnings).collect(),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl From<ConfigFormat> for config::Format {
+    fn from(format: ConfigFormat) -> Self {
+        match format {
+            ConfigFormat::Toml => config::Format::Toml,
+            ConfigFormat::Json => config::Format::Json,
+            ConfigFormat::Yaml => config::Format::Yaml,
+        }
+    }
+}
+
+/// Sends a control request to the running topology, and waits for its outcome.
+async fn send_request(ctx: &Context<'_>, request: ControlRequest) -> ControlResponse {
+    let control_tx = ctx.data_unchecked::<ControlTx>();
+    let (respond_to, response) = oneshot::channel();
+
+    if control_tx
+        .send(ControlCommand {
+            request,
+            respond_to,
+        })
+        .await
+        .is_err()
+    {
+        return ControlResponse::failure(vec!["Vector is shutting down.".to_string()]);
+    }
+
+    response
+        .await
+        .unwrap_or_else(|_| ControlResponse::failure(vec!["Vector is shutting down.".to_string()]))
+}
+
+#[derive(Default)]
+pub struct ControlMutation;
+
+#[Object]
+impl ControlMutation {
+    /// Reloads the configuration from disk, as on SIGHUP
+    async fn reload_config(&self, ctx: &Context<'_>) -> ControlResult {
+        send_request(ctx, ControlRequest::ReloadFromDisk)
+            .await
+            .into()
+    }
+
+    /// Replaces the running configuration with the given one. The configuration is validated, and
+    /// the old configuration is restored if any of the changed components fail to start
+    async fn apply_config(
+        &self,
+        ctx: &Context<'_>,
+        config: String,
+        #[graphql(default_with = "ConfigFormat::Toml")] format: ConfigFormat,
+    ) -> ControlResult {
+        let (config_builder, load_warnings) =
+            match config::load_builder_from_str(&config, format.into()) {
+                Ok(loaded) => loaded,
+                Err(errors) => return ControlResult::failure(errors),
+            };
+
+        let mut response =
+            send_request(ctx, ControlRequest::ReloadFromConfigBuilder(config_builder)).await;
+        if response.success {
+            let mut warnings = load_warnings;
+            warnings.append(&mut response.warnings);
+            response.warnings = warnings;
+        }
+
+        response.into()
+    }
+
+    /// Stops a source from sending events downstream, until it's resumed or the configuration is
+    /// reloaded
+    async fn pause_source(&self, ctx: &Context<'_>, id: String) -> ControlResult {
+        send_request(ctx, ControlRequest::PauseSource(ComponentKey::from(id)))
+            .await
+            .into()
+    }
+
+    /// Lets a paused source send events downstream again
+    async fn resume_source(&self, ctx: &Context<'_>, id: String) -> ControlResult {
+        send_request(ctx, ControlRequest::ResumeSource(ComponentKey::from(id)))
+            .await
+            .into()
+    }
+
+    /// Stops sending events to a sink, which keeps delivering the events it already has, until the
+    /// configuration is reloaded
+    async fn drain_sink(&self, ctx: &Context<'_>, id: String) -> ControlResult {
+        send_request(ctx, ControlRequest::DrainSink(ComponentKey::from(id)))
+            .await
+            .into()
+    }
+
+    /// Sends the events in the data files a sink's disk buffer retained, written to since the given
+    /// time, to the sink again, after the events it already has. Responds once every event has been
+    /// sent to the sink's buffer
+    async fn replay_sink(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        since: DateTime<Utc>,
+    ) -> ControlResult {
+        send_request(
+            ctx,
+            ControlRequest::ReplaySink(ComponentKey::from(id), since.into()),
+        )
+        .await
+        .into()
+    }
+}
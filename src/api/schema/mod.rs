@@ -1,4 +1,5 @@
 pub mod components;
+mod control;
 pub mod events;
 pub mod filter;
 mod health;
@@ -17,6 +18,9 @@ pub struct Query(
     meta::MetaQuery,
 );
 
+#[derive(MergedObject, Default)]
+pub struct Mutation(control::ControlMutation);
+
 #[derive(MergedSubscription, Default)]
 pub struct Subscription(
     health::HealthSubscription,
@@ -26,6 +30,15 @@ pub struct Subscription(
 );
 
 /// Build a new GraphQL schema, comprised of Query, Mutation and Subscription types
-pub fn build_schema() -> SchemaBuilder<Query, EmptyMutation, Subscription> {
+pub fn build_schema() -> SchemaBuilder<Query, Mutation, Subscription> {
+    Schema::build(
+        Query::default(),
+        Mutation::default(),
+        Subscription::default(),
+    )
+}
+
+/// Build a new GraphQL schema without the mutations, which is served unless they're enabled
+pub fn build_schema_without_mutations() -> SchemaBuilder<Query, EmptyMutation, Subscription> {
     Schema::build(Query::default(), EmptyMutation, Subscription::default())
 }
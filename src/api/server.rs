@@ -6,33 +6,56 @@ use std::{
 
 use async_graphql::{
     http::{playground_source, GraphQLPlaygroundConfig, WebSocketProtocols},
-    Data, Request, Schema,
+    Data, ObjectType, Request, Schema,
 };
 use async_graphql_warp::{graphql_protocol, GraphQLResponse, GraphQLWebSocket};
 use tokio::sync::oneshot;
-use warp::{filters::BoxedFilter, http::Response, ws::Ws, Filter, Reply};
+use warp::{
+    filters::BoxedFilter,
+    http::{
+        header::{AUTHORIZATION, WWW_AUTHENTICATE},
+        HeaderMap, Response, StatusCode,
+    },
+    reject::Reject,
+    ws::Ws,
+    Filter, Rejection, Reply,
+};
 
 use super::{handler, schema, ShutdownTx};
-use crate::{config, topology};
+use crate::{
+    config,
+    http::Auth,
+    tls::MaybeTlsSettings,
+    topology::{self, control::ControlTx},
+};
 
 pub struct Server {
     _shutdown: ShutdownTx,
     addr: SocketAddr,
+    protocol: &'static str,
 }
 
 impl Server {
-    /// Start the API server. This creates the routes and spawns a Warp server. The server is
-    /// gracefully shut down when Self falls out of scope by way of the oneshot sender closing.
-    pub fn start(
+    /// Start the API server. This creates the routes, binds the configured address, with TLS if
+    /// enabled, and spawns a Warp server. The server is gracefully shut down when Self falls out
+    /// of scope by way of the oneshot sender closing.
+    pub async fn start(
         config: &config::Config,
         watch_rx: topology::WatchRx,
         running: Arc<AtomicBool>,
-    ) -> Self {
-        let routes = make_routes(config.api.playground, watch_rx, running);
+        control_tx: ControlTx,
+    ) -> crate::Result<Self> {
+        let tls = MaybeTlsSettings::from_config(&config.api.tls, true)?;
+        let listener = tls
+            .bind(&config.api.address.expect("No socket address"))
+            .await?;
+        let addr = listener.local_addr()?;
+
+        let routes = make_routes(&config.api, watch_rx, running, control_tx)?;
 
         let (_shutdown, rx) = oneshot::channel();
-        let (addr, server) = warp::serve(routes).bind_with_graceful_shutdown(
-            config.api.address.expect("No socket address"),
+        let server = warp::serve(routes).serve_incoming_with_graceful_shutdown(
+            listener.accept_stream(),
             async {
                 rx.await.ok();
             },
@@ -44,7 +67,11 @@ impl Server {
         // Spawn the server in the background.
         tokio::spawn(server);
 
-        Self { _shutdown, addr }
+        Ok(Self {
+            _shutdown,
+            addr,
+            protocol: tls.http_protocol_name(),
+        })
     }
 
     /// Returns a copy of the SocketAddr that the server was started on.
@@ -52,6 +79,11 @@ impl Server {
         self.addr
     }
 
+    /// Returns the protocol the server is serving, which is `https` when TLS is enabled.
+    pub const fn protocol(&self) -> &'static str {
+        self.protocol
+    }
+
     /// Update the configuration of a running server. While this instance method doesn't
     /// directly involve `self`, it provides a neater API to expose an internal implementation
     /// detail than exposing the function of the sub-mod directly.
@@ -61,13 +93,14 @@ impl Server {
 }
 
 fn make_routes(
-    playground: bool,
+    api: &config::api::Options,
     watch_tx: topology::WatchRx,
     running: Arc<AtomicBool>,
-) -> BoxedFilter<(impl Reply,)> {
+    control_tx: ControlTx,
+) -> crate::Result<BoxedFilter<(impl Reply,)>> {
     // Routes...
 
-    // Health.
+    // Health. Left unauthenticated, so that it can be used for liveness checks.
     let health = warp::path("health")
         .and(with_shared(running))
         .and_then(handler::health);
@@ -75,46 +108,26 @@ fn make_routes(
     // 404.
     let not_found = warp::any().and_then(|| async { Err(warp::reject::not_found()) });
 
-    // GraphQL subscription handler. Creates a Warp WebSocket handler and for each connection,
-    // parses the required headers for GraphQL and builds per-connection context based on the
-    // provided `WatchTx` channel sender. This allows GraphQL resolvers to subscribe to
-    // topology changes.
-    let graphql_subscription_handler =
-        warp::ws()
-            .and(graphql_protocol())
-            .map(move |ws: Ws, protocol: WebSocketProtocols| {
-                let schema = schema::build_schema().finish();
-                let watch_tx = watch_tx.clone();
-
-                let reply = ws.on_upgrade(move |socket| {
-                    let mut data = Data::default();
-                    data.insert(watch_tx);
-
-                    GraphQLWebSocket::new(socket, schema, protocol)
-                        .with_data(data)
-                        .serve()
-                });
-
-                warp::reply::with_header(
-                    reply,
-                    "Sec-WebSocket-Protocol",
-                    protocol.sec_websocket_protocol(),
-                )
-            });
+    // The mutations change the running topology, so they're only part of the schema if they've
+    // been enabled, which the config only allows along with authentication.
+    let graphql = if api.mutations.enabled {
+        graphql_routes(schema::build_schema().finish(), watch_tx, control_tx)
+    } else {
+        graphql_routes(
+            schema::build_schema_without_mutations().finish(),
+            watch_tx,
+            control_tx,
+        )
+    };
 
-    // Handle GraphQL queries. Headers will first be parsed to determine whether the query is
-    // a subscription and if so, an attempt will be made to upgrade the connection to WebSockets.
-    // All other queries will fall back to the default HTTP handler.
-    let graphql_handler = warp::path("graphql").and(graphql_subscription_handler.or(
-        async_graphql_warp::graphql(schema::build_schema().finish()).and_then(
-            |(schema, request): (Schema<_, _, _>, Request)| async move {
-                Ok::<_, Infallible>(GraphQLResponse::from(schema.execute(request).await))
-            },
-        ),
-    ));
+    // Requests must carry the configured credentials, if any, before they reach the schema.
+    let graphql_handler = warp::path("graphql")
+        .and(with_auth(api.auth.as_ref())?)
+        .and(graphql);
 
-    // Provide a playground for executing GraphQL queries/mutations/subscriptions.
-    let graphql_playground = if playground {
+    // Provide a playground for executing GraphQL queries/mutations/subscriptions. The playground
+    // itself is a static page, and the credentials are set from within it, for `/graphql`.
+    let graphql_playground = if api.playground {
         warp::path("playground")
             .map(move || {
                 Response::builder()
@@ -130,10 +143,11 @@ fn make_routes(
 
     // Wire up the health + GraphQL endpoints. Provides a permissive CORS policy to allow for
     // cross-origin interaction with the Vector API.
-    health
+    Ok(health
         .or(graphql_handler)
         .or(graphql_playground)
         .or(not_found)
+        .recover(handle_unauthorized)
         .with(
             warp::cors()
                 .allow_any_origin()
@@ -146,6 +160,7 @@ fn make_routes(
                     "Access-Control-Allow-Origin",
                     "Access-Control-Request-Headers",
                     "Content-Type",
+                    "Authorization",
                     "X-Apollo-Tracing", // for Apollo GraphQL clients
                     "Pragma",
                     "Host",
@@ -154,6 +169,128 @@ fn make_routes(
                 ])
                 .allow_methods(vec!["POST", "GET"]),
         )
+        .boxed())
+}
+
+/// Rejection for requests that don't carry the credentials the API is configured with.
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+/// Rejects requests whose `Authorization` header doesn't match the configured credentials. Every
+/// request is let through if there are no credentials configured.
+fn with_auth(
+    auth: Option<&Auth>,
+) -> crate::Result<impl Filter<Extract = (), Error = Rejection> + Clone> {
+    let expected = match auth {
+        Some(auth) => {
+            let mut headers = HeaderMap::new();
+            auth.apply_headers_map(&mut headers);
+            let value = headers
+                .get(AUTHORIZATION)
+                .ok_or("Authorization header wasn't generated")?
+                .as_bytes()
+                .to_vec();
+            Some(Arc::new(value))
+        }
+        None => None,
+    };
+
+    Ok(warp::header::headers_cloned()
+        .and_then(move |headers: HeaderMap| {
+            let expected = expected.clone();
+            async move {
+                let authorized = expected.map_or(true, |expected| {
+                    headers.get(AUTHORIZATION).map_or(false, |value| {
+                        // Compared in constant time, so as not to leak how much of the
+                        // credentials matched.
+                        let value = value.as_bytes();
+                        value.len() == expected.len() && openssl::memcmp::eq(value, &expected)
+                    })
+                });
+
+                if authorized {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one())
+}
+
+/// Replies to requests without valid credentials with a `401 Unauthorized`, which lets clients
+/// know which authentication schemes the API accepts.
+async fn handle_unauthorized(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    if rejection.find::<Unauthorized>().is_none() {
+        return Err(rejection);
+    }
+
+    Ok(warp::reply::with_header(
+        warp::reply::with_status("Unauthorized", StatusCode::UNAUTHORIZED),
+        WWW_AUTHENTICATE,
+        r#"Basic realm="Vector API", Bearer realm="Vector API""#,
+    ))
+}
+
+/// Builds the GraphQL routes for the given schema.
+///
+/// Headers will first be parsed to determine whether the query is a subscription and if so, an
+/// attempt will be made to upgrade the connection to WebSockets. All other queries will fall back
+/// to the default HTTP handler. Mutations are given the `ControlTx` channel sender, to make
+/// changes to the running topology.
+fn graphql_routes<M>(
+    schema: Schema<schema::Query, M, schema::Subscription>,
+    watch_tx: topology::WatchRx,
+    control_tx: ControlTx,
+) -> BoxedFilter<(Box<dyn Reply>,)>
+where
+    M: ObjectType + 'static,
+{
+    // GraphQL subscription handler. Creates a Warp WebSocket handler and for each connection,
+    // parses the required headers for GraphQL and builds per-connection context based on the
+    // provided `WatchTx` channel sender. This allows GraphQL resolvers to subscribe to
+    // topology changes.
+    let subscription_schema = schema.clone();
+    let subscription_control_tx = control_tx.clone();
+    let graphql_subscription_handler =
+        warp::ws()
+            .and(graphql_protocol())
+            .map(move |ws: Ws, protocol: WebSocketProtocols| {
+                let schema = subscription_schema.clone();
+                let watch_tx = watch_tx.clone();
+                let control_tx = subscription_control_tx.clone();
+
+                let reply = ws.on_upgrade(move |socket| {
+                    let mut data = Data::default();
+                    data.insert(watch_tx);
+                    data.insert(control_tx);
+
+                    GraphQLWebSocket::new(socket, schema, protocol)
+                        .with_data(data)
+                        .serve()
+                });
+
+                warp::reply::with_header(
+                    reply,
+                    "Sec-WebSocket-Protocol",
+                    protocol.sec_websocket_protocol(),
+                )
+            });
+
+    graphql_subscription_handler
+        .or(
+            async_graphql_warp::graphql(schema).and_then(
+                move |(schema, request): (Schema<_, _, _>, Request)| {
+                    let request = request.data(control_tx.clone());
+                    async move {
+                        Ok::<_, Infallible>(GraphQLResponse::from(schema.execute(request).await))
+                    }
+                },
+            ),
+        )
+        .map(|reply| Box::new(reply) as Box<dyn Reply>)
         .boxed()
 }
 
@@ -162,3 +299,111 @@ fn with_shared(
 ) -> impl Filter<Extract = (Arc<AtomicBool>,), Error = Infallible> + Clone {
     warp::any().map(move || Arc::<AtomicBool>::clone(&shared))
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::{mpsc, watch};
+
+    use super::*;
+
+    fn routes(auth: Option<Auth>) -> BoxedFilter<(impl Reply,)> {
+        let api = config::api::Options {
+            auth,
+            ..Default::default()
+        };
+        let (_watch_tx, watch_rx) = watch::channel(topology::TapResource::default());
+        let (control_tx, _control_rx) = mpsc::channel(1);
+
+        make_routes(&api, watch_rx, Arc::new(AtomicBool::new(true)), control_tx)
+            .expect("routes should build")
+    }
+
+    fn basic_auth() -> Auth {
+        Auth::Basic {
+            user: "vector".to_string(),
+            password: "secret".to_string(),
+        }
+    }
+
+    fn graphql_request() -> warp::test::RequestBuilder {
+        warp::test::request()
+            .method("POST")
+            .path("/graphql")
+            .header("content-type", "application/json")
+            .body(r#"{"query":"{ health }"}"#)
+    }
+
+    #[tokio::test]
+    async fn graphql_without_auth_configured() {
+        let response = graphql_request().reply(&routes(None)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn graphql_without_credentials() {
+        let response = graphql_request().reply(&routes(Some(basic_auth()))).await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().contains_key(WWW_AUTHENTICATE));
+    }
+
+    #[tokio::test]
+    async fn graphql_with_wrong_credentials() {
+        let response = graphql_request()
+            .header(
+                AUTHORIZATION,
+                format!("Basic {}", base64::encode("vector:wrong")),
+            )
+            .reply(&routes(Some(basic_auth())))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Basic realm="Vector API", Bearer realm="Vector API""#
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_with_credentials() {
+        let response = graphql_request()
+            .header(
+                AUTHORIZATION,
+                format!("Basic {}", base64::encode("vector:secret")),
+            )
+            .reply(&routes(Some(basic_auth())))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn graphql_with_bearer_token() {
+        let auth = Auth::Bearer {
+            token: "secret".to_string(),
+        };
+
+        let wrong = graphql_request()
+            .header(AUTHORIZATION, "Bearer wrong")
+            .reply(&routes(Some(auth.clone())))
+            .await;
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+
+        let right = graphql_request()
+            .header(AUTHORIZATION, "Bearer secret")
+            .reply(&routes(Some(auth)))
+            .await;
+        assert_eq!(right.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn health_is_unauthenticated() {
+        let response = warp::test::request()
+            .path("/health")
+            .reply(&routes(Some(basic_auth())))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
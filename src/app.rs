@@ -22,11 +22,16 @@ use crate::service;
 #[cfg(feature = "api")]
 use crate::{api, internal_events::ApiStarted};
 use crate::{
+    buffer,
     cli::{handle_config_errors, Color, LogFormat, Opts, RootOpts, SubCommand},
     config::{self},
     generate, graph, heartbeat, list,
     signal::{self, SignalTo},
-    topology::{self, RunningTopology},
+    topology::{
+        self,
+        control::{ControlCommand, ControlRequest, ControlResponse},
+        RunningTopology,
+    },
     trace, unit_test, validate,
 };
 #[cfg(feature = "api-client")]
@@ -43,8 +48,10 @@ pub struct ApplicationConfig {
     pub config_paths: Vec<config::ConfigPath>,
     pub topology: RunningTopology,
     pub graceful_crash: mpsc::UnboundedReceiver<()>,
+    /// Requests to control the topology, made through the API.
+    pub control_rx: mpsc::Receiver<ControlCommand>,
     #[cfg(feature = "api")]
-    pub api: config::api::Options,
+    pub api_server: Option<api::Server>,
     #[cfg(feature = "enterprise")]
     pub enterprise: Option<EnterpriseReporter<BoxFuture<'static, ()>>>,
     pub signal_handler: signal::SignalHandler,
@@ -144,6 +151,7 @@ impl Application {
                     let code = match s {
                         SubCommand::Generate(g) => generate::cmd(&g),
                         SubCommand::Graph(g) => graph::cmd(&g),
+                        SubCommand::Buffer(b) => buffer::cmd(&b).await,
                         SubCommand::Config(c) => config::cmd(&c),
                         SubCommand::List(l) => list::cmd(&l),
                         SubCommand::Test(t) => unit_test::cmd(&t, &mut signal_handler).await,
@@ -219,17 +227,57 @@ impl Application {
                     .ok_or(exitcode::CONFIG)?;
 
                 #[cfg(feature = "api")]
-                let api = config.api;
+                let api = config.api.clone();
 
                 let result = topology::start_validated(config, diff, pieces).await;
                 let (topology, graceful_crash) = result.ok_or(exitcode::CONFIG)?;
 
+                // Requests to control the topology, made through the API.
+                let (control_tx, control_rx) = mpsc::channel(1);
+                #[cfg(not(feature = "api"))]
+                drop(control_tx);
+
+                // Configure the API server, if applicable.
+                #[cfg(feature = "api")]
+                // Assigned to prevent the API terminating when falling out of scope.
+                let api_server = if api.enabled {
+                    use std::sync::{atomic::AtomicBool, Arc};
+
+                    match api::Server::start(
+                        topology.config(),
+                        topology.watch(),
+                        Arc::<AtomicBool>::clone(&topology.running),
+                        control_tx,
+                    )
+                    .await
+                    {
+                        Ok(api_server) => {
+                            emit!(ApiStarted {
+                                addr: api_server.addr(),
+                                protocol: api_server.protocol(),
+                                playground: api.playground
+                            });
+                            Some(api_server)
+                        }
+                        Err(error) => {
+                            // The API is explicitly enabled, so we don't carry on without it.
+                            error!(message = "Failed to start the API server.", %error);
+                            topology.stop().await;
+                            return Err(exitcode::CONFIG);
+                        }
+                    }
+                } else {
+                    info!(message="API is disabled, enable by setting `api.enabled` to `true` and use commands like `vector top`.");
+                    None
+                };
+
                 Ok(ApplicationConfig {
                     config_paths,
                     topology,
                     graceful_crash,
+                    control_rx,
                     #[cfg(feature = "api")]
-                    api,
+                    api_server,
                     #[cfg(feature = "enterprise")]
                     enterprise,
                     signal_handler,
@@ -255,8 +303,10 @@ impl Application {
 
         let opts = self.opts;
 
+        let mut control_rx = self.config.control_rx;
+
         #[cfg(feature = "api")]
-        let api_config = self.config.api;
+        let api_server = self.config.api_server;
 
         #[cfg(feature = "enterprise")]
         let mut enterprise = self.config.enterprise;
@@ -272,132 +322,114 @@ impl Application {
             emit!(VectorStarted);
             tokio::spawn(heartbeat::heartbeat());
 
-            // Configure the API server, if applicable.
-            #[cfg(feature = "api")]
-            // Assigned to prevent the API terminating when falling out of scope.
-            let api_server = if api_config.enabled {
-                use std::sync::{Arc, atomic::AtomicBool};
-                emit!(ApiStarted {
-                    addr: api_config.address.unwrap(),
-                    playground: api_config.playground
-                });
-
-                Some(api::Server::start(topology.config(), topology.watch(), Arc::<AtomicBool>::clone(&topology.running)))
-            } else {
-                info!(message="API is disabled, enable by setting `api.enabled` to `true` and use commands like `vector top`.");
-                None
-            };
-
             let mut sources_finished = topology.sources_finished();
 
             let signal = loop {
                 tokio::select! {
                     Ok(signal) = signal_rx.recv() => {
-                        match signal {
-                            SignalTo::ReloadFromConfigBuilder(config_builder) => {
-                                match config_builder.build().map_err(handle_config_errors) {
-                                    Ok(mut new_config) => {
-                                        new_config.healthchecks.set_require_healthy(opts.require_healthy);
-
-                                        #[cfg(feature = "enterprise")]
-                                        // Augment config to enable observability within Datadog, if applicable.
-                                        match EnterpriseMetadata::try_from(&new_config) {
-                                            Ok(metadata) => {
-                                                if let Some(e) = report_on_reload(&mut new_config, metadata, config_paths.clone(), enterprise.as_ref()) {
-                                                    enterprise = Some(e);
-                                                }
-                                            },
-                                            Err(err) => {
-                                                if let EnterpriseError::MissingApiKey = err {
-                                                    emit!(VectorReloadError);
-                                                    continue;
-                                                }
-                                            },
-                                        }
-
-                                        match topology
-                                            .reload_config_and_respawn(new_config)
-                                            .await
-                                        {
-                                            Ok(true) => {
-                                                #[cfg(feature = "api")]
-                                                // Pass the new config to the API server.
-                                                if let Some(ref api_server) = api_server {
-                                                    api_server.update_config(topology.config());
-                                                }
-
-                                                emit!(VectorReloaded { config_paths: &config_paths })
-                                            },
-                                            Ok(false) => emit!(VectorReloadError),
-                                            // Trigger graceful shutdown for what remains of the topology
-                                            Err(()) => {
-                                                emit!(VectorReloadError);
-                                                emit!(VectorRecoveryError);
-                                                break SignalTo::Shutdown;
-                                            }
-                                        }
-                                        sources_finished = topology.sources_finished();
-                                    },
-                                    Err(_) => {
-                                        emit!(VectorConfigLoadError);
-                                    }
+                        let new_config = match signal {
+                            SignalTo::ReloadFromConfigBuilder(config_builder) => config_builder.build(),
+                            SignalTo::ReloadFromDisk => load_config_from_disk(&opts, &mut config_paths, &mut signal_handler).await,
+                            _ => break signal,
+                        };
+
+                        match new_config.map_err(handle_config_errors) {
+                            Ok(new_config) => {
+                                let reloaded = reload_topology(
+                                    &mut topology,
+                                    new_config,
+                                    opts.require_healthy,
+                                    &config_paths,
+                                    #[cfg(feature = "enterprise")]
+                                    &mut enterprise,
+                                    #[cfg(feature = "api")]
+                                    api_server.as_ref(),
+                                ).await;
+                                if reloaded.is_err() {
+                                    break SignalTo::Shutdown;
                                 }
+                                sources_finished = topology.sources_finished();
+                            },
+                            Err(_) => {
+                                emit!(VectorConfigLoadError);
                             }
-                            SignalTo::ReloadFromDisk => {
-                                // Reload paths
-                                config_paths = config::process_paths(&opts.config_paths_with_formats()).unwrap_or(config_paths);
-
-                                // Reload config
-                                let new_config = config::load_from_paths_with_provider_and_secrets(&config_paths, &mut signal_handler)
+                        }
+                    }
+                    Some(ControlCommand { request, respond_to }) = control_rx.recv() => {
+                        let new_config = match request {
+                            ControlRequest::ReloadFromDisk => {
+                                load_config_from_disk(&opts, &mut config_paths, &mut signal_handler)
                                     .await
-                                    .map_err(handle_config_errors).ok();
-
-                                if let Some(mut new_config) = new_config {
-                                    new_config.healthchecks.set_require_healthy(opts.require_healthy);
-
-                                    #[cfg(feature = "enterprise")]
-                                    match EnterpriseMetadata::try_from(&new_config) {
-                                        Ok(metadata) => {
-                                            if let Some(e) = report_on_reload(&mut new_config, metadata, config_paths.clone(), enterprise.as_ref()) {
-                                                enterprise = Some(e);
-                                            }
-                                        },
-                                        Err(err) => {
-                                            if let EnterpriseError::MissingApiKey = err {
-                                                emit!(VectorReloadError);
-                                                continue;
-                                            }
-                                        },
+                                    .map(|new_config| (new_config, Vec::new()))
+                            },
+                            ControlRequest::ReloadFromConfigBuilder(config_builder) => config_builder.build_with_warnings(),
+                            ControlRequest::PauseSource(key) => {
+                                // The requester may have stopped waiting for the response, which is fine.
+                                let _ = respond_to.send(topology.pause_source(&key).into());
+                                continue;
+                            },
+                            ControlRequest::ResumeSource(key) => {
+                                let _ = respond_to.send(topology.resume_source(&key).into());
+                                continue;
+                            },
+                            ControlRequest::DrainSink(key) => {
+                                let _ = respond_to.send(topology.drain_sink(&key).into());
+                                continue;
+                            },
+                            ControlRequest::ReplaySink(key, since) => {
+                                // Replaying can take a while, so it's left to run on its own and
+                                // responds once it's done.
+                                match topology.replay_sink(&key, since).await {
+                                    Ok(replay) => {
+                                        tokio::spawn(async move {
+                                            let response = replay.await.map(|_| ()).into();
+                                            let _ = respond_to.send(response);
+                                        });
+                                    }
+                                    Err(error) => {
+                                        let _ = respond_to.send(ControlResponse::failure(vec![error]));
                                     }
+                                }
+                                continue;
+                            },
+                        };
+
+                        let response = match new_config {
+                            Ok((new_config, warnings)) => {
+                                for warning in &warnings {
+                                    warn!("{}", warning);
+                                }
 
-                                    match topology
-                                        .reload_config_and_respawn(new_config)
-                                        .await
-                                    {
-                                        Ok(true) => {
-                                            #[cfg(feature = "api")]
-                                            // Pass the new config to the API server.
-                                            if let Some(ref api_server) = api_server {
-                                                api_server.update_config(topology.config());
-                                            }
-
-                                            emit!(VectorReloaded { config_paths: &config_paths })
-                                        },
-                                        Ok(false) => emit!(VectorReloadError),
-                                        // Trigger graceful shutdown for what remains of the topology
-                                        Err(()) => {
-                                            emit!(VectorReloadError);
-                                            emit!(VectorRecoveryError);
-                                            break SignalTo::Shutdown;
-                                        }
+                                let reloaded = reload_topology(
+                                    &mut topology,
+                                    new_config,
+                                    opts.require_healthy,
+                                    &config_paths,
+                                    #[cfg(feature = "enterprise")]
+                                    &mut enterprise,
+                                    #[cfg(feature = "api")]
+                                    api_server.as_ref(),
+                                ).await;
+                                sources_finished = topology.sources_finished();
+
+                                match reloaded {
+                                    Ok(errors) if errors.is_empty() => ControlResponse::success(warnings),
+                                    Ok(errors) => ControlResponse::failure(errors),
+                                    Err(()) => {
+                                        let _ = respond_to.send(ControlResponse::failure(vec![
+                                            "Failed to restore the old configuration, shutting down.".to_string(),
+                                        ]));
+                                        break SignalTo::Shutdown;
                                     }
-                                    sources_finished = topology.sources_finished();
-                                } else {
-                                    emit!(VectorConfigLoadError);
                                 }
+                            },
+                            Err(errors) => {
+                                handle_config_errors(errors.clone());
+                                emit!(VectorConfigLoadError);
+                                ControlResponse::failure(errors)
                             }
-                            _ => break signal,
-                        }
+                        };
+                        let _ = respond_to.send(response);
                     }
                     // Trigger graceful shutdown if a component crashed, or all sources have ended.
                     _ = graceful_crash.next() => break SignalTo::Shutdown,
@@ -428,3 +460,88 @@ impl Application {
         });
     }
 }
+
+/// Loads the configuration from the filesystem, picking up any change to the config paths first.
+async fn load_config_from_disk(
+    opts: &RootOpts,
+    config_paths: &mut Vec<config::ConfigPath>,
+    signal_handler: &mut signal::SignalHandler,
+) -> Result<config::Config, Vec<String>> {
+    // Reload paths
+    if let Some(new_config_paths) = config::process_paths(&opts.config_paths_with_formats()) {
+        *config_paths = new_config_paths;
+    }
+
+    // Reload config
+    config::load_from_paths_with_provider_and_secrets(config_paths, signal_handler).await
+}
+
+/// Reloads the running topology with the given configuration, returning the errors that prevented
+/// it from being reloaded, if any.
+///
+/// If the old configuration couldn't be restored either, `Err(())` is returned, and what remains of
+/// the topology should be shut down.
+async fn reload_topology(
+    topology: &mut RunningTopology,
+    mut new_config: config::Config,
+    require_healthy: Option<bool>,
+    config_paths: &[config::ConfigPath],
+    #[cfg(feature = "enterprise")] enterprise: &mut Option<
+        EnterpriseReporter<BoxFuture<'static, ()>>,
+    >,
+    #[cfg(feature = "api")] api_server: Option<&api::Server>,
+) -> Result<Vec<String>, ()> {
+    new_config.healthchecks.set_require_healthy(require_healthy);
+
+    #[cfg(feature = "enterprise")]
+    // Augment config to enable observability within Datadog, if applicable.
+    match EnterpriseMetadata::try_from(&new_config) {
+        Ok(metadata) => {
+            if let Some(e) = report_on_reload(
+                &mut new_config,
+                metadata,
+                config_paths.to_vec(),
+                enterprise.as_ref(),
+            ) {
+                *enterprise = Some(e);
+            }
+        }
+        Err(err) => {
+            if let EnterpriseError::MissingApiKey = err {
+                emit!(VectorReloadError);
+                return Ok(vec!["Datadog API key is missing.".to_string()]);
+            }
+        }
+    }
+
+    #[cfg(feature = "api")]
+    if topology.config().api != new_config.api {
+        warn!(message = "API options can't be changed while reloading the configuration; restart Vector for them to take effect.");
+    }
+
+    match topology
+        .reload_config_and_respawn_with_errors(new_config)
+        .await
+    {
+        Ok(errors) if errors.is_empty() => {
+            #[cfg(feature = "api")]
+            // Pass the new config to the API server.
+            if let Some(api_server) = api_server {
+                api_server.update_config(topology.config());
+            }
+
+            emit!(VectorReloaded { config_paths });
+            Ok(errors)
+        }
+        Ok(errors) => {
+            emit!(VectorReloadError);
+            Ok(errors)
+        }
+        // Trigger graceful shutdown for what remains of the topology
+        Err(()) => {
+            emit!(VectorReloadError);
+            emit!(VectorRecoveryError);
+            Err(())
+        }
+    }
+}
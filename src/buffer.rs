@@ -0,0 +1,338 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use vector_buffers::{
+    DataFileSummary, DiskBufferCompression, DiskBufferEncryption, DiskBufferInspector,
+    RetainedDataFile,
+};
+
+use crate::event::{EventArray, EventContainer};
+
+#[derive(Parser, Debug)]
+#[clap(rename_all = "kebab-case")]
+pub struct Opts {
+    /// Path to the disk buffer directory, such as `<data_dir>/buffer/v2/<sink ID>`.
+    ///
+    /// The buffer is opened read-only, so it can be inspected while Vector is running.
+    #[clap(parse(from_os_str))]
+    path: PathBuf,
+
+    /// Base64-encoded 256-bit key of an encrypted buffer. Required to dump or export its records.
+    #[clap(
+        long,
+        env = "VECTOR_BUFFER_ENCRYPTION_KEY",
+        conflicts_with = "encryption-key-file"
+    )]
+    encryption_key: Option<String>,
+
+    /// Path to a file containing the base64-encoded 256-bit key of an encrypted buffer.
+    #[clap(long, parse(from_os_str))]
+    encryption_key_file: Option<PathBuf>,
+
+    /// Print the records in the buffer to stdout as newline-delimited JSON, one event per line,
+    /// instead of the buffer summary.
+    #[clap(long, conflicts_with = "export")]
+    dump: bool,
+
+    /// Write the records in the buffer to the given file as newline-delimited JSON, one event per
+    /// line.
+    #[clap(long, parse(from_os_str))]
+    export: Option<PathBuf>,
+
+    /// Include records that have already been read and acknowledged, but not yet deleted, when
+    /// dumping or exporting records.
+    #[clap(long)]
+    all: bool,
+
+    /// Truncate data files with a corrupted tail to their last valid record.
+    ///
+    /// The records in a corrupted tail can't be read by Vector either way. Vector must not be
+    /// running with this buffer while it is repaired.
+    #[clap(long)]
+    repair: bool,
+
+    /// Replay the records in the retained data files written to since the given RFC 3339
+    /// timestamp, such as `2022-06-01T12:00:00Z`, so that the sink sends them again.
+    ///
+    /// Only buffers configured with `retention` keep data files once they've been acknowledged.
+    /// Data files are replayed whole: a data file last written to after the timestamp is replayed
+    /// in full, so records written before the timestamp may be sent again as well. Replayed records
+    /// are sent after any records the sink has yet to send. Vector must not be running with this
+    /// buffer while records are replayed; use the `replaySink` API mutation to replay records
+    /// while it is.
+    #[clap(long, conflicts_with_all = &["dump", "export"])]
+    replay_since: Option<DateTime<Utc>>,
+}
+
+impl Opts {
+    fn encryption(&self) -> Option<DiskBufferEncryption> {
+        (self.encryption_key.is_some() || self.encryption_key_file.is_some()).then(|| {
+            DiskBufferEncryption {
+                key: self.encryption_key.clone(),
+                key_file: self.encryption_key_file.clone(),
+            }
+        })
+    }
+}
+
+pub(crate) async fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    let encryption_key = match opts.encryption().map(|encryption| encryption.load_key()) {
+        None => None,
+        Some(Ok(key)) => Some(key),
+        Some(Err(error)) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", error);
+            }
+            return exitcode::CONFIG;
+        }
+    };
+
+    let inspector = match DiskBufferInspector::open(&opts.path, encryption_key).await {
+        Ok(inspector) => inspector,
+        Err(error) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("Failed to open buffer: {}", error);
+            }
+            return exitcode::NOINPUT;
+        }
+    };
+
+    if opts.repair {
+        match inspector.repair::<EventArray>().await {
+            Ok(repaired) => {
+                for summary in repaired {
+                    #[allow(clippy::print_stderr)]
+                    {
+                        eprintln!(
+                            "Truncated {} from {} to {} bytes.",
+                            summary.path.display(),
+                            summary.size,
+                            summary.valid_len
+                        );
+                    }
+                }
+            }
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to repair buffer: {}", error);
+                }
+                return exitcode::IOERR;
+            }
+        }
+    }
+
+    if let Some(since) = opts.replay_since {
+        return match inspector
+            .replay_retained::<EventArray>(SystemTime::from(since))
+            .await
+        {
+            Ok(summary) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!(
+                        "Replayed {} records ({} events) from {} retained data files.",
+                        summary.records, summary.events, summary.data_files
+                    );
+                }
+                exitcode::OK
+            }
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to replay retained records: {}", error);
+                }
+                exitcode::IOERR
+            }
+        };
+    }
+
+    let writing_records = opts.dump || opts.export.is_some();
+    if writing_records && !inspector.can_decode() {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!(
+                "The buffer is encrypted; its encryption key is required to read its records."
+            );
+        }
+        return exitcode::USAGE;
+    }
+
+    let mut output: Box<dyn Write> = match &opts.export {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to create {}: {}", path.display(), error);
+                }
+                return exitcode::CANTCREAT;
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    let data_files = match inspector.data_files().await {
+        Ok(data_files) => data_files,
+        Err(error) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("Failed to list data files: {}", error);
+            }
+            return exitcode::IOERR;
+        }
+    };
+
+    let ledger = inspector.ledger();
+    let mut summaries = Vec::with_capacity(data_files.len());
+    let mut write_error = None;
+    for (file_id, path) in data_files {
+        let result = inspector
+            .scan_data_file(file_id, &path, |record_id, events: EventArray| {
+                if !writing_records
+                    || write_error.is_some()
+                    || !(opts.all || ledger.is_unread(record_id))
+                {
+                    return;
+                }
+
+                for event in events.into_events() {
+                    let result = serde_json::to_writer(&mut output, &event)
+                        .map_err(io::Error::from)
+                        .and_then(|()| output.write_all(b"\n"));
+                    if let Err(error) = result {
+                        write_error = Some(error);
+                        return;
+                    }
+                }
+            })
+            .await;
+
+        match result {
+            Ok(summary) => summaries.push(summary),
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to read {}: {}", path.display(), error);
+                }
+                return exitcode::IOERR;
+            }
+        }
+    }
+
+    if let Some(error) = write_error.or_else(|| output.flush().err()) {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("Failed to write records: {}", error);
+        }
+        return exitcode::IOERR;
+    }
+
+    if !opts.dump {
+        let retained = match inspector.retained_data_files().await {
+            Ok(retained) => retained,
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to list retained data files: {}", error);
+                }
+                return exitcode::IOERR;
+            }
+        };
+        print_summary(&inspector, &summaries, &retained);
+    }
+
+    exitcode::OK
+}
+
+#[allow(clippy::print_stdout)]
+fn print_summary(
+    inspector: &DiskBufferInspector,
+    summaries: &[DataFileSummary],
+    retained: &[RetainedDataFile],
+) {
+    let ledger = inspector.ledger();
+    let compression = match inspector.compression() {
+        DiskBufferCompression::None => "none",
+        DiskBufferCompression::Zstd => "zstd",
+    };
+
+    println!("Ledger:");
+    println!("  Format version:        {}", ledger.version);
+    println!("  Compression:           {}", compression);
+    println!("  Encrypted:             {}", ledger.encrypted);
+    println!("  Writer next record ID: {}", ledger.writer_next_record_id);
+    println!(
+        "  Writer data file:      {}",
+        ledger.writer_current_data_file_id
+    );
+    println!("  Reader last record ID: {}", ledger.reader_last_record_id);
+    println!(
+        "  Reader data file:      {}",
+        ledger.reader_current_data_file_id
+    );
+    if let Some(unread_bytes) = ledger.unread_logical_bytes {
+        println!("  Unread bytes:          {}", unread_bytes);
+    }
+
+    println!("Data files:");
+    for summary in summaries {
+        let mut line = format!(
+            "  {}: {} bytes, {} records ({} unread)",
+            summary.path.display(),
+            summary.size,
+            summary.records,
+            summary.unread_records
+        );
+        if let (Some(events), Some(unread_events)) = (summary.events, summary.unread_events) {
+            line += &format!(", {} events ({} unread)", events, unread_events);
+        }
+        if summary.undecodable_records > 0 {
+            line += &format!(", {} undecodable records", summary.undecodable_records);
+        }
+        if let Some(error) = &summary.error {
+            line += &format!(", corrupted after byte {}: {}", summary.valid_len, error);
+        }
+        println!("{}", line);
+    }
+
+    let records = summaries.iter().map(|s| s.records).sum::<u64>();
+    let unread_records = summaries.iter().map(|s| s.unread_records).sum::<u64>();
+    let size = summaries.iter().map(|s| s.size).sum::<u64>();
+    println!(
+        "Total: {} data files, {} bytes, {} records ({} unread)",
+        summaries.len(),
+        size,
+        records,
+        unread_records
+    );
+    if summaries.iter().any(DataFileSummary::is_corrupted) {
+        println!("Some data files have a corrupted tail; run with `--repair` to truncate them.");
+    }
+
+    if !retained.is_empty() {
+        println!("Retained data files:");
+        for file in retained {
+            let modified = DateTime::<Utc>::from(file.modified);
+            println!(
+                "  {}: {} bytes, last written {}",
+                file.path.display(),
+                file.size,
+                modified.to_rfc3339()
+            );
+        }
+        println!(
+            "Total retained: {} data files, {} bytes",
+            retained.len(),
+            retained.iter().map(|file| file.size).sum::<u64>()
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crate::service;
 use crate::tap;
 #[cfg(feature = "api-client")]
 use crate::top;
-use crate::{config, generate, get_version, graph, list, unit_test, validate};
+use crate::{buffer, config, generate, get_version, graph, list, unit_test, validate};
 
 #[derive(Parser, Debug)]
 #[clap(rename_all = "kebab-case")]
@@ -193,6 +193,9 @@ pub enum SubCommand {
     /// Output the topology as visual representation using the DOT language which can be rendered by GraphViz
     Graph(graph::Opts),
 
+    /// Inspect, dump, or repair a disk buffer, without loading it
+    Buffer(buffer::Opts),
+
     /// Display topology and metrics in the console, for a local or remote Vector instance
     #[cfg(feature = "api-client")]
     Top(top::Opts),
@@ -210,6 +213,67 @@ pub enum SubCommand {
     Vrl(vrl_cli::Opts),
 }
 
+/// Options for connecting to the Vector GraphQL API server, shared by the commands that use it.
+#[cfg(feature = "api-client")]
+#[derive(Parser, Debug, Clone)]
+#[clap(rename_all = "kebab-case")]
+pub struct ApiClientOpts {
+    /// Bearer token to authenticate with the Vector GraphQL API server
+    #[clap(
+        long,
+        env = "VECTOR_API_TOKEN",
+        hide_env_values = true,
+        conflicts_with = "user"
+    )]
+    token: Option<String>,
+
+    /// User to authenticate with the Vector GraphQL API server, using basic authentication
+    #[clap(long, requires = "password")]
+    user: Option<String>,
+
+    /// Password to authenticate with the Vector GraphQL API server, using basic authentication
+    #[clap(
+        long,
+        env = "VECTOR_API_PASSWORD",
+        hide_env_values = true,
+        requires = "user"
+    )]
+    password: Option<String>,
+
+    /// PEM-encoded CA certificate to trust when connecting to the Vector GraphQL API server over
+    /// https, in addition to the system's trusted CAs
+    #[clap(long)]
+    ca_file: Option<PathBuf>,
+}
+
+#[cfg(feature = "api-client")]
+impl ApiClientOpts {
+    /// Credentials to authenticate with the Vector GraphQL API server, if any were given
+    pub fn auth(&self) -> Option<vector_api_client::Auth> {
+        use vector_api_client::Auth;
+
+        match (&self.token, &self.user, &self.password) {
+            (Some(token), _, _) => Some(Auth::Bearer {
+                token: token.clone(),
+            }),
+            (None, Some(user), Some(password)) => Some(Auth::Basic {
+                user: user.clone(),
+                password: password.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Loads the CA certificate to trust, if one was given
+    pub fn ca_certificate(&self) -> Result<Option<vector_api_client::CaCertificate>, String> {
+        self.ca_file
+            .as_deref()
+            .map(vector_api_client::CaCertificate::from_file)
+            .transpose()
+            .map_err(|error| format!("{:#}", error))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Color {
     Auto,
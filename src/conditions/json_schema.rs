@@ -0,0 +1,164 @@
+use std::{path::PathBuf, sync::Arc};
+
+use jsonschema::JSONSchema;
+use vector_config::configurable_component;
+use vector_core::event::Event;
+
+use crate::conditions::{Condition, Conditional, ConditionalConfig};
+
+/// A condition that validates events against a [JSON Schema](https://json-schema.org/).
+#[configurable_component]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JsonSchemaConfig {
+    /// The path of the JSON Schema to validate events against.
+    ///
+    /// The schema is read and compiled once, when the condition is built.
+    schema_path: PathBuf,
+}
+
+impl_generate_config_from_default!(JsonSchemaConfig);
+
+impl ConditionalConfig for JsonSchemaConfig {
+    fn build(&self, _enrichment_tables: &enrichment::TableRegistry) -> crate::Result<Condition> {
+        let schema = vrl_stdlib::load_json_schema(&self.schema_path)?;
+
+        Ok(Condition::JsonSchema(JsonSchema { schema }))
+    }
+}
+
+/// Checks whether events conform to a compiled JSON Schema.
+///
+/// Logs and traces are validated as their JSON representation, and metrics as
+/// their VRL representation.
+#[derive(Debug, Clone)]
+pub struct JsonSchema {
+    schema: Arc<JSONSchema>,
+}
+
+impl JsonSchema {
+    fn instance(event: &Event) -> serde_json::Value {
+        let value = match event {
+            Event::Log(log) => serde_json::to_value(log.value()),
+            Event::Trace(trace) => serde_json::to_value(trace.value()),
+            Event::Metric(metric) => serde_json::to_value(&metric.to_vrl()),
+        };
+
+        value.unwrap_or(serde_json::Value::Null)
+    }
+}
+
+impl Conditional for JsonSchema {
+    fn check(&self, e: Event) -> (bool, Event) {
+        let result = self.schema.is_valid(&Self::instance(&e));
+        (result, e)
+    }
+
+    fn check_with_context(&self, e: Event) -> (Result<(), String>, Event) {
+        let instance = Self::instance(&e);
+
+        let result = self.schema.validate(&instance).map_err(|errors| {
+            let errors = errors
+                .map(|error| match error.instance_path.to_string() {
+                    path if path.is_empty() => error.to_string(),
+                    path => format!("{}: {}", path, error),
+                })
+                .collect::<Vec<_>>();
+
+            format!("schema validation failed: [ {} ]", errors.join(", "))
+        });
+
+        (result, e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{
+        metric::{Metric, MetricKind, MetricValue},
+        LogEvent, Value,
+    };
+
+    const SCHEMA: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/json_schema/user.schema.json"
+    );
+
+    fn condition() -> Condition {
+        JsonSchemaConfig {
+            schema_path: SCHEMA.into(),
+        }
+        .build(&Default::default())
+        .unwrap()
+    }
+
+    fn log(fields: &[(&str, Value)]) -> Event {
+        let mut log = LogEvent::default();
+        for (field, value) in fields {
+            log.insert(*field, value.clone());
+        }
+        log.into()
+    }
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<JsonSchemaConfig>();
+    }
+
+    #[test]
+    fn check_conforming_log() {
+        let cond = condition();
+        let event = log(&[("name", "alice".into()), ("age", 42.into())]);
+
+        assert!(cond.check(event.clone()).0);
+        assert_eq!(cond.check_with_context(event).0, Ok(()));
+    }
+
+    #[test]
+    fn check_non_conforming_log() {
+        let cond = condition();
+        let event = log(&[("name", "alice".into()), ("age", "old".into())]);
+
+        assert!(!cond.check(event.clone()).0);
+        assert_eq!(
+            cond.check_with_context(event).0,
+            Err(r#"schema validation failed: [ /age: "old" is not of type "integer" ]"#.into())
+        );
+    }
+
+    #[test]
+    fn check_missing_field() {
+        let cond = condition();
+        let event = log(&[("age", 42.into())]);
+
+        assert_eq!(
+            cond.check_with_context(event).0,
+            Err(r#"schema validation failed: [ "name" is a required property ]"#.into())
+        );
+    }
+
+    #[test]
+    fn check_metric() {
+        let cond = condition();
+        let event = Event::from(Metric::new(
+            "alice",
+            MetricKind::Absolute,
+            MetricValue::Counter { value: 1.0 },
+        ));
+
+        assert!(cond.check(event).0);
+    }
+
+    #[test]
+    fn build_invalid_schema_path() {
+        let err = JsonSchemaConfig {
+            schema_path: "does/not/exist.json".into(),
+        }
+        .build(&Default::default())
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("unable to read JSON schema does/not/exist.json"));
+    }
+}
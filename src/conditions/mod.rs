@@ -6,6 +6,8 @@ mod check_fields;
 pub(self) mod datadog_search;
 pub(crate) mod is_log;
 pub(crate) mod is_metric;
+pub(crate) mod is_trace;
+mod json_schema;
 mod vrl;
 
 pub use self::vrl::VrlConfig;
@@ -14,6 +16,8 @@ use self::{
     datadog_search::{DatadogSearchConfig, DatadogSearchRunner},
     is_log::{check_is_log, check_is_log_with_context},
     is_metric::{check_is_metric, check_is_metric_with_context},
+    is_trace::{check_is_trace, check_is_trace_with_context},
+    json_schema::{JsonSchema, JsonSchemaConfig},
     vrl::Vrl,
 };
 
@@ -25,6 +29,9 @@ pub enum Condition {
     /// Matches an event if it is a metric.
     IsMetric,
 
+    /// Matches an event if it is a trace.
+    IsTrace,
+
     /// Matches an event with a [Vector Remap Language](https://vector.dev/docs/reference/vrl) (VRL) [boolean expression](https://vector.dev/docs/reference/vrl#boolean-expressions).
     Vrl(Vrl),
 
@@ -34,6 +41,9 @@ pub enum Condition {
     /// Matches an event with a [Datadog Search](https://docs.datadoghq.com/logs/explorer/search_syntax/) query.
     DatadogSearch(DatadogSearchRunner),
 
+    /// Matches an event if it conforms to a [JSON Schema](https://json-schema.org/).
+    JsonSchema(JsonSchema),
+
     /// Matches any event.
     ///
     /// Used only for internal testing.
@@ -54,9 +64,11 @@ impl Condition {
         match self {
             Condition::IsLog => check_is_log(e),
             Condition::IsMetric => check_is_metric(e),
+            Condition::IsTrace => check_is_trace(e),
             Condition::Vrl(x) => x.check(e),
             Condition::CheckFields(x) => x.check(e),
             Condition::DatadogSearch(x) => x.check(e),
+            Condition::JsonSchema(x) => x.check(e),
             Condition::AlwaysPass => (true, e),
             Condition::AlwaysFail => (false, e),
         }
@@ -70,9 +82,11 @@ impl Condition {
         match self {
             Condition::IsLog => check_is_log_with_context(e),
             Condition::IsMetric => check_is_metric_with_context(e),
+            Condition::IsTrace => check_is_trace_with_context(e),
             Condition::Vrl(x) => x.check_with_context(e),
             Condition::CheckFields(x) => x.check_with_context(e),
             Condition::DatadogSearch(x) => x.check_with_context(e),
+            Condition::JsonSchema(x) => x.check_with_context(e),
             Condition::AlwaysPass => (Ok(()), e),
             Condition::AlwaysFail => (Ok(()), e),
         }
@@ -100,6 +114,9 @@ pub enum ConditionConfig {
     /// Matches an event if it is a metric.
     IsMetric,
 
+    /// Matches an event if it is a trace.
+    IsTrace,
+
     /// Matches an event with a [Vector Remap Language](https://vector.dev/docs/reference/vrl) (VRL) [boolean expression](https://vector.dev/docs/reference/vrl#boolean-expressions).
     Vrl(#[configurable(derived)] VrlConfig),
 
@@ -108,6 +125,9 @@ pub enum ConditionConfig {
 
     /// Matches an event with a [Datadog Search](https://docs.datadoghq.com/logs/explorer/search_syntax/) query.
     DatadogSearch(#[configurable(derived)] DatadogSearchConfig),
+
+    /// Matches an event if it conforms to a [JSON Schema](https://json-schema.org/).
+    JsonSchema(#[configurable(derived)] JsonSchemaConfig),
 }
 
 impl ConditionConfig {
@@ -115,9 +135,11 @@ impl ConditionConfig {
         match self {
             ConditionConfig::IsLog => Ok(Condition::IsLog),
             ConditionConfig::IsMetric => Ok(Condition::IsMetric),
+            ConditionConfig::IsTrace => Ok(Condition::IsTrace),
             ConditionConfig::Vrl(x) => x.build(enrichment_tables),
             ConditionConfig::CheckFields(x) => x.build(enrichment_tables),
             ConditionConfig::DatadogSearch(x) => x.build(enrichment_tables),
+            ConditionConfig::JsonSchema(x) => x.build(enrichment_tables),
         }
     }
 }
@@ -246,4 +268,18 @@ mod tests {
             format!("{:?}", conf.condition)
         )
     }
+
+    #[test]
+    fn deserialize_anycondition_json_schema() {
+        let conf: Test = toml::from_str(indoc! {r#"
+            condition.type = "json_schema"
+            condition.schema_path = "schemas/user.json"
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            r#"Map(JsonSchema(JsonSchemaConfig { schema_path: "schemas/user.json" }))"#,
+            format!("{:?}", conf.condition)
+        )
+    }
 }
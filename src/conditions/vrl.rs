@@ -1,7 +1,9 @@
+use std::sync::Arc;
+
 use value::Value;
 use vector_common::TimeZone;
 use vector_config::configurable_component;
-use vrl::{diagnostic::Formatter, Program, Runtime, VrlRuntime};
+use vrl::{diagnostic::Formatter, Program, Runtime, Vm, VrlRuntime};
 
 use crate::event::TargetEvents;
 use crate::{
@@ -62,6 +64,12 @@ impl ConditionalConfig for VrlConfig {
             VrlRuntime::Ast => Ok(Condition::Vrl(Vrl {
                 program,
                 source: self.source.clone(),
+                vm: None,
+            })),
+            VrlRuntime::Vm => Ok(Condition::Vrl(Vrl {
+                vm: Some(Arc::new(Vm::new(&program))),
+                program,
+                source: self.source.clone(),
             })),
         }
     }
@@ -71,6 +79,7 @@ impl ConditionalConfig for VrlConfig {
 pub struct Vrl {
     pub(super) program: Program,
     pub(super) source: String,
+    pub(super) vm: Option<Arc<Vm>>,
 }
 
 impl Vrl {
@@ -79,7 +88,11 @@ impl Vrl {
         // TODO: use timezone from remap config
         let timezone = TimeZone::default();
 
-        let result = Runtime::default().resolve(&mut target, &self.program, &timezone);
+        let mut runtime = Runtime::default();
+        let result = match self.vm {
+            Some(ref vm) => runtime.run_vm(vm, &mut target, &timezone),
+            None => runtime.resolve(&mut target, &self.program, &timezone),
+        };
         let original_event = match target.into_events() {
             TargetEvents::One(event) => event,
             _ => panic!("Event was modified in a condition. This is an internal compiler error."),
@@ -2,7 +2,9 @@ use std::net::{Ipv4Addr, SocketAddr};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Copy, Clone)]
+use crate::{http::Auth, tls::TlsEnableableConfig};
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 #[serde(default, deny_unknown_fields)]
 pub struct Options {
     #[serde(default = "default_enabled")]
@@ -13,6 +15,16 @@ pub struct Options {
 
     #[serde(default = "default_playground")]
     pub playground: bool,
+
+    #[serde(default)]
+    pub mutations: MutationsOptions,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsEnableableConfig>,
+
+    /// Credentials that requests to the API must send, apart from requests to the health endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
 }
 
 impl Default for Options {
@@ -21,10 +33,22 @@ impl Default for Options {
             enabled: default_enabled(),
             playground: default_playground(),
             address: default_address(),
+            mutations: MutationsOptions::default(),
+            tls: None,
+            auth: None,
         }
     }
 }
 
+/// Options for the GraphQL mutations that change the running topology.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Copy, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MutationsOptions {
+    /// Whether the mutations are served. As they can change the running configuration, they're
+    /// only allowed along with `auth`.
+    pub enabled: bool,
+}
+
 const fn default_enabled() -> bool {
     false
 }
@@ -61,10 +85,27 @@ impl Options {
             }
         };
 
+        let tls = match (self.tls.take(), other.tls) {
+            (Some(a), Some(b)) if a != b => return Err("Conflicting `api` TLS options.".to_owned()),
+            (a, b) => a.or(b),
+        };
+
+        let auth = match (self.auth.take(), other.auth) {
+            (Some(a), Some(b)) if a != b => {
+                return Err("Conflicting `api` authentication options.".to_owned())
+            }
+            (a, b) => a.or(b),
+        };
+
         let options = Options {
             address,
             enabled: self.enabled | other.enabled,
             playground: self.playground & other.playground,
+            mutations: MutationsOptions {
+                enabled: self.mutations.enabled | other.mutations.enabled,
+            },
+            tls,
+            auth,
         };
 
         *self = options;
@@ -78,6 +119,9 @@ fn bool_merge() {
         enabled: true,
         address: None,
         playground: false,
+        mutations: MutationsOptions::default(),
+        tls: None,
+        auth: None,
     };
 
     a.merge(Options::default()).unwrap();
@@ -88,6 +132,9 @@ fn bool_merge() {
             enabled: true,
             address: default_address(),
             playground: false,
+            mutations: MutationsOptions::default(),
+            tls: None,
+            auth: None,
         }
     );
 }
@@ -99,6 +146,9 @@ fn bind_merge() {
         enabled: true,
         address: Some(address),
         playground: true,
+        mutations: MutationsOptions::default(),
+        tls: None,
+        auth: None,
     };
 
     a.merge(Options::default()).unwrap();
@@ -109,6 +159,9 @@ fn bind_merge() {
             enabled: true,
             address: Some(address),
             playground: true,
+            mutations: MutationsOptions::default(),
+            tls: None,
+            auth: None,
         }
     );
 }
@@ -127,3 +180,37 @@ fn bind_conflict() {
 
     assert!(a.merge(b).is_err());
 }
+
+#[test]
+fn auth_merge() {
+    let auth = Auth::Bearer {
+        token: "token".to_owned(),
+    };
+    let mut a = Options {
+        auth: Some(auth.clone()),
+        ..Options::default()
+    };
+
+    a.merge(Options::default()).unwrap();
+    assert_eq!(a.auth, Some(auth));
+}
+
+#[test]
+fn auth_conflict() {
+    let mut a = Options {
+        auth: Some(Auth::Bearer {
+            token: "a".to_owned(),
+        }),
+        ..Options::default()
+    };
+
+    let b = Options {
+        auth: Some(Auth::Basic {
+            user: "b".to_owned(),
+            password: "b".to_owned(),
+        }),
+        ..Options::default()
+    };
+
+    assert!(a.merge(b).is_err());
+}
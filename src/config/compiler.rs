@@ -53,6 +53,15 @@ pub fn compile(mut builder: ConfigBuilder) -> Result<(Config, Vec<String>), Vec<
         errors.extend(output_errors);
     }
 
+    if let Err(shared_buffer_errors) = validation::check_shared_buffers(&builder) {
+        errors.extend(shared_buffer_errors);
+    }
+
+    #[cfg(feature = "api")]
+    if let Err(api_errors) = validation::check_api(&builder) {
+        errors.extend(api_errors);
+    }
+
     #[cfg(feature = "enterprise")]
     let version = Some(builder.sha256_hash());
 
@@ -173,6 +182,9 @@ pub(super) fn expand_macros(
 }
 
 /// Expand globs in input lists
+///
+/// The dead-letter outputs of sinks are never matched by globs, as a sink with a wildcard input
+/// would otherwise consume its own dead-letter output. They have to be named explicitly instead.
 pub(crate) fn expand_globs(config: &mut ConfigBuilder) {
     let candidates = config
         .sources
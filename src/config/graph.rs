@@ -17,6 +17,7 @@ pub enum Node {
     },
     Sink {
         ty: DataType,
+        outputs: Vec<Output>,
     },
 }
 
@@ -86,6 +87,7 @@ impl Graph {
                 id.clone(),
                 Node::Sink {
                     ty: config.inner.input().data_type(),
+                    outputs: config.outputs(),
                 },
             );
         }
@@ -158,7 +160,7 @@ impl Graph {
         match self.nodes[key] {
             Node::Source { .. } => panic!("no inputs on sources"),
             Node::Transform { in_ty, .. } => in_ty,
-            Node::Sink { ty } => ty,
+            Node::Sink { ty, .. } => ty,
         }
     }
 
@@ -166,16 +168,17 @@ impl Graph {
     ///
     /// # Panics
     ///
-    /// Will panic if the given id is not present in the graph or identifies a sink, which can't
-    /// have inputs.
+    /// Will panic if the given id is not present in the graph or identifies an output that doesn't
+    /// exist.
     fn get_output_type(&self, id: &OutputId) -> DataType {
         match &self.nodes[&id.component] {
-            Node::Source { outputs } | Node::Transform { outputs, .. } => outputs
+            Node::Source { outputs }
+            | Node::Transform { outputs, .. }
+            | Node::Sink { outputs, .. } => outputs
                 .iter()
                 .find(|output| output.port == id.port)
                 .map(|output| output.ty)
                 .expect("output didn't exist"),
-            Node::Sink { .. } => panic!("no outputs on sinks"),
         }
     }
 
@@ -260,14 +263,12 @@ impl Graph {
         self.nodes
             .iter()
             .flat_map(|(key, node)| match node {
-                Node::Sink { .. } => vec![],
-                Node::Source { outputs } | Node::Transform { outputs, .. } => outputs
-                    .iter()
-                    .map(|output| OutputId {
-                        component: key.clone(),
-                        port: output.port.clone(),
-                    })
-                    .collect(),
+                Node::Source { outputs }
+                | Node::Transform { outputs, .. }
+                | Node::Sink { outputs, .. } => outputs.iter().map(|output| OutputId {
+                    component: key.clone(),
+                    port: output.port.clone(),
+                }),
             })
             .collect()
     }
@@ -345,7 +346,7 @@ impl Graph {
             .into_iter()
             .filter(|path| {
                 if let Some(key) = path.last() {
-                    matches!(self.nodes.get(key), Some(Node::Sink { ty: _ }))
+                    matches!(self.nodes.get(key), Some(Node::Sink { .. }))
                 } else {
                     false
                 }
@@ -407,7 +408,13 @@ mod test {
         fn add_sink(&mut self, id: &str, ty: DataType, inputs: Vec<&str>) {
             let id = ComponentKey::from(id);
             let inputs = clean_inputs(inputs);
-            self.nodes.insert(id.clone(), Node::Sink { ty });
+            self.nodes.insert(
+                id.clone(),
+                Node::Sink {
+                    ty,
+                    outputs: vec![],
+                },
+            );
             for from in inputs {
                 self.edges.push(Edge {
                     from,
@@ -416,6 +423,16 @@ mod test {
             }
         }
 
+        fn add_sink_output(&mut self, id: &str, name: &str, ty: DataType) {
+            let id = id.into();
+            match self.nodes.get_mut(&id) {
+                Some(Node::Sink { outputs, .. }) => {
+                    outputs.push(Output::default(ty).with_port(name))
+                }
+                _ => panic!("invalid sink"),
+            }
+        }
+
         fn test_add_input(&mut self, node: &str, input: &str) -> Result<(), String> {
             let available_inputs = self.input_map().unwrap();
             let expansions = IndexMap::new();
@@ -611,6 +628,43 @@ mod test {
         );
     }
 
+    #[test]
+    fn allows_sink_dead_letter_outputs() {
+        let mut graph = Graph::default();
+        graph.add_source("log_source", DataType::Log);
+        graph.add_sink("primary_sink", DataType::Log, vec!["log_source"]);
+        graph.add_sink_output("primary_sink", "dead_letter", DataType::Log);
+        graph.add_sink("fallback_sink", DataType::Log, vec![]);
+        graph.add_sink("metric_sink", DataType::Metric, vec![]);
+
+        assert_eq!(
+            Ok(()),
+            graph.test_add_input("fallback_sink", "primary_sink.dead_letter")
+        );
+        assert_eq!(Ok(()), graph.check_for_cycles());
+        assert_eq!(Ok(()), graph.typecheck());
+
+        let expected =
+            "Input \"primary_sink\" for sink \"metric_sink\" doesn't match any components.";
+        assert_eq!(
+            Err(expected.to_string()),
+            graph.test_add_input("metric_sink", "primary_sink")
+        );
+    }
+
+    #[test]
+    fn detects_cycles_through_sink_dead_letter_outputs() {
+        let mut graph = Graph::default();
+        graph.add_source("in", DataType::Log);
+        graph.add_sink("out", DataType::Log, vec!["in"]);
+        graph.add_sink_output("out", "dead_letter", DataType::Log);
+        graph.add_transform("retry", DataType::Log, DataType::Log, vec![]);
+
+        assert_eq!(Ok(()), graph.test_add_input("retry", "out.dead_letter"));
+        assert_eq!(Ok(()), graph.test_add_input("out", "retry"));
+        assert!(graph.check_for_cycles().is_err());
+    }
+
     #[test]
     fn disallows_ambiguous_inputs() {
         let mut graph = Graph::default();
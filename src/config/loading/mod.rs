@@ -243,8 +243,15 @@ pub fn load_secret_backends_from_paths(
     loader_from_paths(SecretBackendLoader::new(), config_paths)
 }
 
+pub fn load_builder_from_str(
+    input: &str,
+    format: Format,
+) -> Result<(ConfigBuilder, Vec<String>), Vec<String>> {
+    load_from_inputs(std::iter::once((input.as_bytes(), format)))
+}
+
 pub fn load_from_str(input: &str, format: Format) -> Result<Config, Vec<String>> {
-    let (builder, load_warnings) = load_from_inputs(std::iter::once((input.as_bytes(), format)))?;
+    let (builder, load_warnings) = load_builder_from_str(input, format)?;
     let (config, build_warnings) = builder.build_with_warnings()?;
 
     for warning in load_warnings.into_iter().chain(build_warnings) {
@@ -43,11 +43,14 @@ pub use diff::ConfigDiff;
 pub use format::{Format, FormatHint};
 pub use id::{ComponentKey, OutputId};
 pub use loading::{
-    load, load_builder_from_paths, load_from_paths, load_from_paths_with_provider_and_secrets,
-    load_from_str, load_source_from_paths, merge_path_lists, process_paths, SecretBackend,
-    CONFIG_PATHS,
+    load, load_builder_from_paths, load_builder_from_str, load_from_paths,
+    load_from_paths_with_provider_and_secrets, load_from_str, load_source_from_paths,
+    merge_path_lists, process_paths, SecretBackend, CONFIG_PATHS,
+};
+pub use sink::{
+    SinkConfig, SinkContext, SinkDeadLetterOptions, SinkDescription, SinkHealthcheckOptions,
+    SinkOuter, DEAD_LETTER_OUTPUT,
 };
-pub use sink::{SinkConfig, SinkContext, SinkDescription, SinkHealthcheckOptions, SinkOuter};
 pub use source::{SourceConfig, SourceContext, SourceDescription, SourceOuter};
 pub use transform::{TransformDescription, TransformOuter};
 pub use unit_test::{build_unit_tests, build_unit_tests_main, UnitTestResult};
@@ -637,6 +640,45 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn bad_shared_buffer() {
+        let err = load(
+            r#"
+            [sources.in0]
+            type = "basic_source"
+
+            [sources.in1]
+            type = "basic_source"
+
+            [sinks.out0]
+            type = "basic_sink"
+            inputs = ["in0", "in1"]
+            buffer = { type = "disk", max_size = 268435488, shared = "logs" }
+
+            [sinks.out1]
+            type = "basic_sink"
+            inputs = ["in1", "in0"]
+            buffer = { type = "disk", max_size = 268435488, shared = "logs" }
+
+            [sinks.out2]
+            type = "basic_sink"
+            inputs = ["in0"]
+            buffer = { type = "disk", max_size = 536870976, shared = "logs" }
+            "#,
+            Format::Toml,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            vec![
+                "Sinks \"out0\" and \"out2\" share disk buffer \"logs\", but have different inputs.",
+                "Sinks \"out0\" and \"out2\" share disk buffer \"logs\", but configure it differently.",
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn warnings() {
         let warnings = load(
@@ -1,12 +1,17 @@
+use std::num::NonZeroUsize;
+
 use async_trait::async_trait;
 use component::ComponentDescription;
 use serde::{Deserialize, Serialize};
 use vector_buffers::{Acker, BufferConfig, BufferType};
-use vector_core::config::{AcknowledgementsConfig, GlobalOptions, Input};
+use vector_core::config::{AcknowledgementsConfig, GlobalOptions, Input, Output};
 
 use super::{component, schema, ComponentKey, ProxyConfig, Resource};
 use crate::sinks::{self, util::UriSerde};
 
+/// Name of the output that events a sink permanently failed to deliver are sent to.
+pub const DEAD_LETTER_OUTPUT: &str = "dead_letter";
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct SinkOuter<T> {
     #[serde(default = "Default::default")] // https://github.com/serde-rs/serde/issues/1541
@@ -28,6 +33,13 @@ pub struct SinkOuter<T> {
     )]
     proxy: ProxyConfig,
 
+    #[serde(
+        default,
+        deserialize_with = "crate::serde::bool_or_struct",
+        skip_serializing_if = "vector_core::serde::skip_serializing_if_default"
+    )]
+    pub dead_letter: SinkDeadLetterOptions,
+
     #[serde(flatten)]
     pub inner: Box<dyn SinkConfig>,
 }
@@ -41,6 +53,7 @@ impl<T> SinkOuter<T> {
             healthcheck_uri: None,
             inner,
             proxy: Default::default(),
+            dead_letter: SinkDeadLetterOptions::default(),
         }
     }
 
@@ -79,6 +92,18 @@ impl<T> SinkOuter<T> {
         &self.proxy
     }
 
+    /// The outputs of the sink.
+    ///
+    /// Sinks only have an output when their dead-letter output is enabled, in which case events
+    /// that the sink permanently failed to deliver are sent to it.
+    pub fn outputs(&self) -> Vec<Output> {
+        if self.dead_letter.enabled {
+            vec![Output::default(self.inner.input().data_type()).with_port(DEAD_LETTER_OUTPUT)]
+        } else {
+            Vec::new()
+        }
+    }
+
     pub(super) fn map_inputs<U>(self, f: impl Fn(&T) -> U) -> SinkOuter<U> {
         let inputs = self.inputs.iter().map(f).collect();
         self.with_inputs(inputs)
@@ -92,6 +117,7 @@ impl<T> SinkOuter<T> {
             healthcheck: self.healthcheck,
             healthcheck_uri: self.healthcheck_uri,
             proxy: self.proxy,
+            dead_letter: self.dead_letter,
         }
     }
 }
@@ -127,6 +153,33 @@ impl From<UriSerde> for SinkHealthcheckOptions {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SinkDeadLetterOptions {
+    pub enabled: bool,
+    /// Maximum number of events sent to the sink that a copy is held of, until the sink either
+    /// delivers them or sends them to the dead-letter output.
+    pub max_pending_events: NonZeroUsize,
+}
+
+impl Default for SinkDeadLetterOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pending_events: NonZeroUsize::new(10_000).unwrap(),
+        }
+    }
+}
+
+impl From<bool> for SinkDeadLetterOptions {
+    fn from(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+}
+
 #[async_trait]
 #[typetag::serde(tag = "type")]
 pub trait SinkConfig: core::fmt::Debug + Send + Sync {
@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use vector_core::internal_event::DEFAULT_OUTPUT;
 
-use super::{builder::ConfigBuilder, schema, ComponentKey, Config, OutputId, Resource};
+use super::{builder::ConfigBuilder, schema, ComponentKey, Config, OutputId, Resource, SinkOuter};
 
 /// Check that provide + topology config aren't present in the same builder, which is an error.
 pub fn check_provider(config: &ConfigBuilder) -> Result<(), Vec<String>> {
@@ -141,6 +141,68 @@ pub fn check_resources(config: &ConfigBuilder) -> Result<(), Vec<String>> {
     }
 }
 
+/// Check that the sinks sharing a disk buffer can actually share it.
+///
+/// A shared disk buffer is written once for all of the sinks sharing it, so they must all get the
+/// same events, and configure the buffer the same way.  The shared disk buffer must also be the only
+/// stage of their buffers, as the events in earlier stages aren't shared.
+pub fn check_shared_buffers(config: &ConfigBuilder) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    let mut shared_buffers = BTreeMap::<&str, Vec<(&ComponentKey, &SinkOuter<String>)>>::new();
+    for (key, sink) in config.sinks.iter() {
+        if let Some(name) = sink.buffer.shared_disk_buffer() {
+            if sink.buffer.stages().len() > 1 {
+                errors.push(format!(
+                    "Sink \"{}\" shares disk buffer \"{}\", which must be its only buffer stage.",
+                    key, name
+                ));
+            }
+            shared_buffers.entry(name).or_default().push((key, sink));
+        }
+    }
+
+    for (name, sinks) in shared_buffers {
+        let (first_key, first_sink) = sinks[0];
+        let mut first_inputs = first_sink.inputs.clone();
+        first_inputs.sort();
+        for (key, sink) in sinks.into_iter().skip(1) {
+            let mut inputs = sink.inputs.clone();
+            inputs.sort();
+            if inputs != first_inputs {
+                errors.push(format!(
+                    "Sinks \"{}\" and \"{}\" share disk buffer \"{}\", but have different inputs.",
+                    first_key, key, name
+                ));
+            }
+            if sink.buffer != first_sink.buffer {
+                errors.push(format!(
+                    "Sinks \"{}\" and \"{}\" share disk buffer \"{}\", but configure it differently.",
+                    first_key, key, name
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Check that the API only serves the mutations, which can change the running configuration, to
+/// requests that are authenticated.
+#[cfg(feature = "api")]
+pub fn check_api(config: &ConfigBuilder) -> Result<(), Vec<String>> {
+    if config.api.mutations.enabled && config.api.auth.is_none() {
+        Err(vec![
+            "The `api.mutations` can only be enabled if `api.auth` is configured.".to_owned(),
+        ])
+    } else {
+        Ok(())
+    }
+}
+
 /// To avoid collisions between `output` metric tags, check that a component
 /// does not have a named output with the name [`DEFAULT_OUTPUT`]
 pub fn check_outputs(config: &ConfigBuilder) -> Result<(), Vec<String>> {
@@ -214,7 +276,20 @@ pub fn warnings(config: &Config) -> Vec<String> {
             .collect::<Vec<_>>()
     });
 
-    for (input_type, id) in transform_ids.chain(source_ids) {
+    let sink_ids = config.sinks.iter().flat_map(|(key, sink)| {
+        sink.outputs()
+            .iter()
+            .map(|output| {
+                if let Some(port) = &output.port {
+                    ("sink", OutputId::from((key, port.clone())))
+                } else {
+                    ("sink", OutputId::from(key))
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for (input_type, id) in transform_ids.chain(source_ids).chain(sink_ids) {
         if !config
             .transforms
             .iter()
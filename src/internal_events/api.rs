@@ -6,12 +6,18 @@ use vector_core::internal_event::InternalEvent;
 #[derive(Debug)]
 pub struct ApiStarted {
     pub addr: SocketAddr,
+    pub protocol: &'static str,
     pub playground: bool,
 }
 
 impl InternalEvent for ApiStarted {
     fn emit(self) {
-        let playground = &*format!("http://{}:{}/playground", self.addr.ip(), self.addr.port());
+        let playground = &*format!(
+            "{}://{}:{}/playground",
+            self.protocol,
+            self.addr.ip(),
+            self.addr.port()
+        );
         info!(
             message="API server running.",
             address = ?self.addr,
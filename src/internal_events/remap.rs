@@ -1,5 +1,6 @@
-use metrics::counter;
+use metrics::{counter, histogram};
 use vector_core::internal_event::InternalEvent;
+use vrl::profile::Profile;
 
 use super::prelude::{error_stage, error_type};
 
@@ -69,3 +70,29 @@ impl InternalEvent for RemapMappingAbort {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct RemapProfile {
+    /// The timings recorded while mapping a single event.
+    pub profile: Profile,
+}
+
+impl InternalEvent for RemapProfile {
+    fn emit(self) {
+        for entry in self.profile.entries() {
+            let span = format!("{}..{}", entry.span.start(), entry.span.end());
+            let kind = entry.kind.to_string();
+
+            counter!(
+                "vrl_expression_invocations_total", entry.invocations,
+                "span" => span.clone(),
+                "kind" => kind.clone(),
+            );
+            histogram!(
+                "vrl_expression_duration_seconds", entry.duration,
+                "span" => span,
+                "kind" => kind,
+            );
+        }
+    }
+}
@@ -44,6 +44,7 @@ pub mod internal_events;
 pub mod api;
 pub mod app;
 pub mod async_read;
+pub(crate) mod buffer;
 #[cfg(feature = "aws-config")]
 pub mod aws;
 #[allow(unreachable_pub)]
@@ -288,10 +288,7 @@ impl Aggregator {
     /// This implementation uses https://github.com/DataDog/datadog-agent/blob/cfa750c7412faa98e87a015f8ee670e5828bbe7f/pkg/trace/stats/concentrator.go#L148-L184
     /// as a basis. It takes a trace, iterates over its constituting spans and upon matching conditions it updates statistics (mostly using the top level span).
     fn handle_trace(&mut self, partition_key: &PartitionKey, trace: &TraceEvent) {
-        let spans = match trace.get("spans") {
-            Some(Value::Array(v)) => v.iter().filter_map(|s| s.as_object()).collect(),
-            _ => vec![],
-        };
+        let spans = trace.spans().collect::<Vec<_>>();
 
         let weight = extract_weight_from_root_span(&spans);
         let payload_aggkey = PayloadAggregationKey {
@@ -20,6 +20,7 @@ pub struct ElasticsearchRequestBuilder {
 
 pub struct Metadata {
     finalizers: EventFinalizers,
+    item_finalizer_counts: Vec<usize>,
     batch_size: usize,
     events_byte_size: usize,
 }
@@ -47,8 +48,22 @@ impl RequestBuilder<Vec<ProcessedEvent>> for ElasticsearchRequestBuilder {
             .reduce(|a, b| a + b)
             .unwrap_or(0);
 
+        // Each event is an item of the bulk request, so keep track of which finalizers belong to
+        // which item, to finalize each one according to its own outcome.
+        let mut finalizers = EventFinalizers::default();
+        let item_finalizer_counts = events
+            .iter_mut()
+            .map(|event| {
+                let event_finalizers = event.take_finalizers();
+                let count = event_finalizers.len();
+                finalizers.merge(event_finalizers);
+                count
+            })
+            .collect();
+
         let metadata = Metadata {
-            finalizers: events.take_finalizers(),
+            finalizers,
+            item_finalizer_counts,
             batch_size: events.len(),
             events_byte_size,
         };
@@ -63,6 +78,7 @@ impl RequestBuilder<Vec<ProcessedEvent>> for ElasticsearchRequestBuilder {
         ElasticsearchRequest {
             payload: payload.into_payload(),
             finalizers: metadata.finalizers,
+            item_finalizer_counts: metadata.item_finalizer_counts,
             batch_size: metadata.batch_size,
             events_byte_size: metadata.events_byte_size,
         }
@@ -34,6 +34,7 @@ impl EsResultItem {
 
 #[derive(Deserialize, Debug)]
 struct EsIndexResult {
+    status: Option<u16>,
     error: Option<EsErrorDetails>,
 }
 
@@ -89,7 +90,30 @@ impl RetryLogic for ElasticsearchRetryLogic {
     }
 }
 
-fn get_error_reason(body: &str) -> String {
+/// Gets the error of each item of a bulk response, in the order of the request, along with the
+/// status the item failed with.  Items that succeeded have no error.
+///
+/// Returns `None` if the response can't be parsed.
+pub(super) fn get_item_errors(body: &str) -> Option<Vec<Option<(String, Option<u16>)>>> {
+    let response = serde_json::from_str::<EsResultResponse>(body).ok()?;
+    let errors = response
+        .items
+        .into_iter()
+        .map(|item| {
+            let result = item.result();
+            let status = result.status;
+            result.error.map(|error| {
+                (
+                    format!("error type: {}, reason: {}", error.err_type, error.reason),
+                    status,
+                )
+            })
+        })
+        .collect();
+    Some(errors)
+}
+
+pub(super) fn get_error_reason(body: &str) -> String {
     match serde_json::from_str::<EsResultResponse>(body) {
         Err(json_error) => format!(
             "some messages failed, could not parse response, error: {}",
@@ -109,7 +133,8 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::event::EventStatus;
+    use crate::event::{BatchNotifier, BatchStatus, EventFinalizer, EventFinalizers, EventStatus};
+    use vector_core::stream::DriverResponse;
 
     #[test]
     fn handles_error_response() {
@@ -123,6 +148,7 @@ mod tests {
             logic.should_retry_response(&ElasticsearchResponse {
                 http_response: response,
                 event_status: EventStatus::Rejected,
+                item_finalizer_counts: vec![1],
                 batch_size: 1,
                 events_byte_size: 1,
             }),
@@ -143,4 +169,34 @@ mod tests {
         let reason = get_error_reason(json);
         assert_eq!(reason, "error type: mapper_parsing_exception, reason: object mapping for [host] tried to parse field [host] as object, but found a concrete value");
     }
+
+    #[tokio::test]
+    async fn partial_error_response_only_rejects_failed_items() {
+        let json = "{\"took\":3,\"errors\":true,\"items\":[{\"index\":{\"_index\":\"test\",\"_id\":\"1\",\"status\":201}},{\"index\":{\"_index\":\"test\",\"_id\":\"2\",\"status\":400,\"error\":{\"type\":\"mapper_parsing_exception\",\"reason\":\"failed to parse field [host]\"}}}]}";
+        let response = ElasticsearchResponse {
+            http_response: Response::builder()
+                .status(StatusCode::OK)
+                .body(Bytes::from(json))
+                .unwrap(),
+            event_status: EventStatus::Rejected,
+            item_finalizer_counts: vec![1, 1],
+            batch_size: 2,
+            events_byte_size: 2,
+        };
+
+        let (indexed, indexed_receiver) = BatchNotifier::new_with_receiver();
+        let (failed, mut failed_receiver) = BatchNotifier::new_with_receiver();
+        let mut finalizers = EventFinalizers::new(EventFinalizer::new(indexed));
+        finalizers.add(EventFinalizer::new(failed));
+        response.finalize(finalizers);
+
+        assert_eq!(indexed_receiver.await, BatchStatus::Delivered);
+        assert_eq!((&mut failed_receiver).await, BatchStatus::Rejected);
+        let rejection = failed_receiver.rejection().unwrap();
+        assert_eq!(
+            rejection.message(),
+            "error type: mapper_parsing_exception, reason: failed to parse field [host]"
+        );
+        assert_eq!(rejection.status_code(), Some(400));
+    }
 }
@@ -15,9 +15,10 @@ use vector_core::{
     buffers::Ackable, internal_event::EventsSent, stream::DriverResponse, ByteSizeOf,
 };
 
+use super::retry::{get_error_reason, get_item_errors};
 use crate::sinks::elasticsearch::sign_request;
 use crate::{
-    event::{EventFinalizers, EventStatus, Finalizable},
+    event::{EventFinalizers, EventStatus, Finalizable, Rejection},
     http::{Auth, HttpClient},
     internal_events::ElasticsearchResponseError,
     sinks::util::{
@@ -30,6 +31,8 @@ use crate::{
 pub struct ElasticsearchRequest {
     pub payload: Bytes,
     pub finalizers: EventFinalizers,
+    /// Number of finalizers of each item of the bulk request, in order.
+    pub item_finalizer_counts: Vec<usize>,
     pub batch_size: usize,
     pub events_byte_size: usize,
 }
@@ -128,6 +131,8 @@ impl HttpRequestBuilder {
 pub struct ElasticsearchResponse {
     pub http_response: Response<Bytes>,
     pub event_status: EventStatus,
+    /// Number of finalizers of each item of the bulk request, in order.
+    pub item_finalizer_counts: Vec<usize>,
     pub batch_size: usize,
     pub events_byte_size: usize,
 }
@@ -144,6 +149,54 @@ impl DriverResponse for ElasticsearchResponse {
             output: None,
         }
     }
+
+    fn rejection(&self) -> Option<Rejection> {
+        let status = self.http_response.status();
+        let body = String::from_utf8_lossy(self.http_response.body());
+        let message = if status.is_success() {
+            get_error_reason(&body)
+        } else {
+            format!("response status: {}: {}", status, body)
+        };
+        Some(Rejection::new(message).with_status_code(status.as_u16()))
+    }
+
+    fn finalize(&self, mut finalizers: EventFinalizers) {
+        let status = self.http_response.status();
+        let status_code = status.as_u16();
+        let item_errors = if self.event_status == EventStatus::Rejected && status.is_success() {
+            get_item_errors(&String::from_utf8_lossy(self.http_response.body()))
+                .filter(|item_errors| item_errors.len() == self.item_finalizer_counts.len())
+        } else {
+            None
+        };
+
+        // A bulk request that succeeded with errors only rejects the items that failed, so as not
+        // to send the ones that were indexed to the dead-letter output.  If the items don't line up
+        // with the request, all of them are rejected instead.
+        match item_errors {
+            Some(item_errors) => {
+                for (error, count) in item_errors.into_iter().zip(&self.item_finalizer_counts) {
+                    let rest = finalizers.split_off(*count);
+                    let item_finalizers = std::mem::replace(&mut finalizers, rest);
+                    match error {
+                        Some((message, item_status)) => item_finalizers.reject(
+                            Rejection::new(message)
+                                .with_status_code(item_status.unwrap_or(status_code)),
+                        ),
+                        None => item_finalizers.update_status(EventStatus::Delivered),
+                    }
+                }
+            }
+            None => match self.event_status {
+                EventStatus::Rejected => finalizers.reject(
+                    self.rejection()
+                        .unwrap_or_else(|| Rejection::new("Request was rejected.")),
+                ),
+                status => finalizers.update_status(status),
+            },
+        }
+    }
 }
 
 impl Service<ElasticsearchRequest> for ElasticsearchService {
@@ -161,10 +214,12 @@ impl Service<ElasticsearchRequest> for ElasticsearchService {
             http_service.ready().await?;
             let batch_size = req.batch_size;
             let events_byte_size = req.events_byte_size;
+            let item_finalizer_counts = req.item_finalizer_counts.clone();
             let http_response = http_service.call(req).await?;
             let event_status = get_event_status(&http_response);
             Ok(ElasticsearchResponse {
                 event_status,
+                item_finalizer_counts,
                 http_response,
                 batch_size,
                 events_byte_size,
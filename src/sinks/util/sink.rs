@@ -60,7 +60,7 @@ use super::{
     service::{Map, ServiceBuilderExt},
     EncodedEvent,
 };
-use crate::event::EventStatus;
+use crate::event::{EventStatus, Rejection};
 
 // === BatchSink ===
 
@@ -451,8 +451,13 @@ where
             .call(items)
             .err_into()
             .map(move |result| {
-                let status = result_status(result);
-                finalizers.update_status(status);
+                let status = result_status(&result);
+                match result {
+                    Ok(response) if status == EventStatus::Rejected => {
+                        finalizers.reject(response.rejection())
+                    }
+                    _ => finalizers.update_status(status),
+                }
                 if status == EventStatus::Delivered {
                     emit!(EventsSent {
                         count,
@@ -535,11 +540,11 @@ where
     type Response = R;
 
     fn result_status(&self, result: crate::Result<Self::Response>) -> EventStatus {
-        result_status(result)
+        result_status(&result)
     }
 }
 
-fn result_status<R: Response + Send>(result: crate::Result<R>) -> EventStatus {
+fn result_status<R: Response + Send>(result: &crate::Result<R>) -> EventStatus {
     match result {
         Ok(response) => {
             if response.is_successful() {
@@ -570,6 +575,12 @@ pub trait Response: fmt::Debug {
     fn is_transient(&self) -> bool {
         true
     }
+
+    /// Gets the reason the response rejected the request, for a response that is neither
+    /// successful nor transient.
+    fn rejection(&self) -> Rejection {
+        Rejection::new(format!("{:?}", self))
+    }
 }
 
 impl Response for () {}
@@ -9,7 +9,7 @@ use vector_api_client::{
         output_events_by_component_id_patterns_subscription::OutputEventsByComponentIdPatternsSubscriptionOutputEventsByComponentIdPatterns,
         TapEncodingFormat, TapSubscriptionExt,
     },
-    Client,
+    CaCertificate, Client,
 };
 
 use crate::{
@@ -32,9 +32,23 @@ pub(crate) async fn cmd(opts: &super::Opts, mut signal_rx: SignalRx) -> exitcode
             .expect("Couldn't parse default API URL. Please report this.")
     });
 
+    let ca_certificate = match opts.api.ca_certificate() {
+        Ok(ca_certificate) => ca_certificate,
+        Err(error) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("[tap] {}", error);
+            }
+            return exitcode::CONFIG;
+        }
+    };
+
     // Return early with instructions for enabling the API if the endpoint isn't reachable
     // via a healthcheck.
-    if Client::new_with_healthcheck(url.clone()).await.is_none() {
+    if Client::new_with_healthcheck(url.clone(), opts.api.auth(), ca_certificate.clone())
+        .await
+        .is_none()
+    {
         return exitcode::UNAVAILABLE;
     }
 
@@ -65,7 +79,7 @@ pub(crate) async fn cmd(opts: &super::Opts, mut signal_rx: SignalRx) -> exitcode
         tokio::select! {
             biased;
             Ok(SignalTo::Shutdown | SignalTo::Quit) = signal_rx.recv() => break,
-            status = run(url.clone(), opts, outputs_patterns.clone(), formatter.clone()) => {
+            status = run(url.clone(), opts, ca_certificate.as_ref(), outputs_patterns.clone(), formatter.clone()) => {
                 if status == exitcode::UNAVAILABLE || status == exitcode::TEMPFAIL && !opts.no_reconnect {
                     eprintln!("[tap] Connection failed. Reconnecting in {:?} seconds.", RECONNECT_DELAY / 1000);
                     tokio::time::sleep(Duration::from_millis(RECONNECT_DELAY)).await;
@@ -82,19 +96,21 @@ pub(crate) async fn cmd(opts: &super::Opts, mut signal_rx: SignalRx) -> exitcode
 async fn run(
     url: Url,
     opts: &super::Opts,
+    ca_certificate: Option<&CaCertificate>,
     outputs_patterns: Vec<String>,
     formatter: EventFormatter,
 ) -> exitcode::ExitCode {
-    let subscription_client = match connect_subscription_client(url).await {
-        Ok(c) => c,
-        Err(e) => {
-            #[allow(clippy::print_stderr)]
-            {
-                eprintln!("[tap] Couldn't connect to Vector API via WebSockets: {}", e);
+    let subscription_client =
+        match connect_subscription_client(url, opts.api.auth().as_ref(), ca_certificate).await {
+            Ok(c) => c,
+            Err(e) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("[tap] Couldn't connect to Vector API via WebSockets: {}", e);
+                }
+                return exitcode::UNAVAILABLE;
             }
-            return exitcode::UNAVAILABLE;
-        }
-    };
+        };
 
     tokio::pin! {
         let stream = subscription_client.output_events_by_component_id_patterns_subscription(
@@ -5,6 +5,8 @@ pub(crate) use cmd::cmd;
 use url::Url;
 use vector_api_client::gql::TapEncodingFormat;
 
+use crate::cli::ApiClientOpts;
+
 #[derive(Parser, Debug, Clone)]
 #[clap(rename_all = "kebab-case")]
 pub struct Opts {
@@ -16,6 +18,9 @@ pub struct Opts {
     #[clap(short, long)]
     url: Option<Url>,
 
+    #[clap(flatten)]
+    api: ApiClientOpts,
+
     /// Maximum number of events to sample each interval
     #[clap(default_value = "100", short = 'l', long)]
     limit: u32,
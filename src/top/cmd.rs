@@ -36,12 +36,26 @@ pub async fn cmd(opts: &super::Opts) -> exitcode::ExitCode {
             .expect("Couldn't parse default API URL. Please report this.")
     });
 
-    // Create a new API client for connecting to the local/remote Vector instance.
-    let client = match Client::new_with_healthcheck(url.clone()).await {
-        Some(client) => client,
-        None => return exitcode::UNAVAILABLE,
+    let ca_certificate = match opts.api.ca_certificate() {
+        Ok(ca_certificate) => ca_certificate,
+        Err(error) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", error);
+            }
+            return exitcode::CONFIG;
+        }
     };
 
+    // Create a new API client for connecting to the local/remote Vector instance.
+    let client =
+        match Client::new_with_healthcheck(url.clone(), opts.api.auth(), ca_certificate.clone())
+            .await
+        {
+            Some(client) => client,
+            None => return exitcode::UNAVAILABLE,
+        };
+
     // Create a channel for updating state via event messages
     let (tx, rx) = tokio::sync::mpsc::channel(20);
     let state_rx = state::updater(rx).await;
@@ -56,6 +70,7 @@ pub async fn cmd(opts: &super::Opts) -> exitcode::ExitCode {
         .expect("Couldn't build WebSocket URL. Please report.");
 
     let opts_clone = opts.clone();
+    let auth = opts.api.auth();
     let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
     // This task handles reconnecting the subscription client and all
     // subscriptions in the case of a web socket disconnect
@@ -73,7 +88,13 @@ pub async fn cmd(opts: &super::Opts) -> exitcode::ExitCode {
             };
             let _ = tx.send(EventType::InitializeState(state)).await;
 
-            let subscription_client = match connect_subscription_client(ws_url.clone()).await {
+            let subscription_client = match connect_subscription_client(
+                ws_url.clone(),
+                auth.as_ref(),
+                ca_certificate.as_ref(),
+            )
+            .await
+            {
                 Ok(c) => c,
                 Err(_) => {
                     tokio::time::sleep(Duration::from_millis(RECONNECT_DELAY)).await;
@@ -8,6 +8,8 @@ use clap::Parser;
 pub use cmd::cmd;
 use url::Url;
 
+use crate::cli::ApiClientOpts;
+
 #[derive(Parser, Debug, Clone)]
 #[clap(rename_all = "kebab-case")]
 pub struct Opts {
@@ -19,6 +21,9 @@ pub struct Opts {
     #[clap(short, long)]
     url: Option<Url>,
 
+    #[clap(flatten)]
+    api: ApiClientOpts,
+
     /// Humanize metrics, using numeric suffixes - e.g. 1,100 = 1.10 k, 1,000,000 = 1.00 M
     #[clap(short = 'H', long)]
     human_metrics: bool,
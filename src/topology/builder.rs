@@ -28,6 +28,7 @@ use vector_core::{
 };
 
 use super::{
+    dead_letter::DeadLetterTracker,
     fanout::{self, Fanout},
     schema,
     task::{Task, TaskOutput},
@@ -36,7 +37,7 @@ use super::{
 use crate::{
     config::{
         ComponentKey, DataType, Input, Output, OutputId, ProxyConfig, SinkContext, SourceContext,
-        TransformContext, TransformOuter,
+        TransformContext, TransformOuter, DEAD_LETTER_OUTPUT,
     },
     event::{EventArray, EventContainer},
     internal_events::EventsReceived,
@@ -333,6 +334,8 @@ pub async fn build_pieces(
 
         let typetag = sink.inner.sink_type();
         let input_type = sink.inner.input().data_type();
+        let dead_letter_enabled = sink.dead_letter.enabled;
+        let dead_letter_max_pending_events = sink.dead_letter.max_pending_events;
 
         if config.schema.enabled {
             // At this point, we've validated that all transforms are valid, including any
@@ -358,9 +361,26 @@ pub async fn build_pieces(
                 component_name = %key.id(),
                 buffer_type = buffer_type,
             );
+            // Every sink sharing a disk buffer is a consumer of it.
+            let shared_consumers = sink
+                .buffer
+                .shared_disk_buffer()
+                .map(|name| {
+                    config
+                        .sinks()
+                        .filter(|(_, other)| other.buffer.shared_disk_buffer() == Some(name))
+                        .map(|(other_key, _)| other_key.to_string())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
             let buffer = sink
                 .buffer
-                .build(config.global.data_dir.clone(), key.to_string(), buffer_span)
+                .build(
+                    config.global.data_dir.clone(),
+                    key.to_string(),
+                    &shared_consumers,
+                    buffer_span,
+                )
                 .await;
             match buffer {
                 Err(error) => {
@@ -387,6 +407,26 @@ pub async fn build_pieces(
             Ok(built) => built,
         };
 
+        // Events that the sink permanently fails to deliver are sent to its dead-letter output, if
+        // it's enabled, by a pump that runs alongside the sink.
+        let (dead_letter, dead_letter_pump) = if dead_letter_enabled {
+            let (fanout, control) = Fanout::new();
+            outputs.insert(
+                OutputId {
+                    component: key.clone(),
+                    port: Some(DEAD_LETTER_OUTPUT.to_owned()),
+                },
+                control,
+            );
+
+            let (tracker, pump) =
+                DeadLetterTracker::new(key.clone(), fanout, dead_letter_max_pending_events);
+            let task_name = format!(">> {} ({}, dead letter) >>", typetag, key.id());
+            (Some(tracker), Some((pump, task_name)))
+        } else {
+            (None, None)
+        };
+
         let (trigger, tripwire) = Tripwire::new();
 
         let sink = async move {
@@ -404,19 +444,39 @@ pub async fn build_pieces(
 
             let mut rx = wrap(rx);
 
-            sink.run(
-                rx.by_ref()
-                    .filter(|events: &EventArray| ready(filter_events_type(events, input_type)))
-                    .inspect(|events| {
-                        emit!(EventsReceived {
-                            count: events.len(),
-                            byte_size: events.size_of(),
+            let dead_letter_pump = dead_letter_pump
+                .map(|(pump, task_name)| spawn_named(pump.in_current_span(), task_name.as_ref()));
+
+            let result = sink
+                .run(
+                    rx.by_ref()
+                        .filter(|events: &EventArray| ready(filter_events_type(events, input_type)))
+                        .then(move |events| {
+                            let dead_letter = dead_letter.clone();
+                            async move {
+                                match dead_letter {
+                                    Some(tracker) => tracker.track(events).await,
+                                    None => events,
+                                }
+                            }
                         })
-                    })
-                    .take_until_if(tripwire),
-            )
-            .await
-            .map(|_| {
+                        .inspect(|events| {
+                            emit!(EventsReceived {
+                                count: events.len(),
+                                byte_size: events.size_of(),
+                            })
+                        })
+                        .take_until_if(tripwire),
+                )
+                .await;
+
+            // Once the sink is done, wait for the events it was still holding on to to be
+            // finalized, so that any of them it rejected make it to the dead-letter output.
+            if let Some(handle) = dead_letter_pump {
+                handle.await.expect("join error");
+            }
+
+            result.map(|_| {
                 debug!("Finished.");
                 TaskOutput::Sink(rx, acker)
             })
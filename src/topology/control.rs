@@ -0,0 +1,72 @@
+use std::time::SystemTime;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::config::{ComponentKey, ConfigBuilder};
+
+pub type ControlTx = mpsc::Sender<ControlCommand>;
+pub type ControlRx = mpsc::Receiver<ControlCommand>;
+
+/// Requests to change the running topology, made from outside of the main loop of Vector, such as
+/// from the API.
+#[derive(Debug)]
+pub enum ControlRequest {
+    /// Reload the config from the filesystem.
+    ReloadFromDisk,
+    /// Reload the config from the given config builder.
+    ReloadFromConfigBuilder(ConfigBuilder),
+    /// Stop a source from sending events downstream, until it's resumed.
+    PauseSource(ComponentKey),
+    /// Let a paused source send events downstream again.
+    ResumeSource(ComponentKey),
+    /// Stop sending events to a sink, so that it only delivers the events it already has.
+    DrainSink(ComponentKey),
+    /// Send the records in the retained data files of a sink's disk buffer, written to since the
+    /// given time, to the sink again.
+    ReplaySink(ComponentKey, SystemTime),
+}
+
+/// A control request, along with where to send its outcome.
+#[derive(Debug)]
+pub struct ControlCommand {
+    pub request: ControlRequest,
+    pub respond_to: oneshot::Sender<ControlResponse>,
+}
+
+/// The outcome of a control request.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ControlResponse {
+    /// Whether the request was carried out.
+    pub success: bool,
+    /// Errors that prevented the request from being carried out.
+    pub errors: Vec<String>,
+    /// Warnings about a request that was carried out.
+    pub warnings: Vec<String>,
+}
+
+impl ControlResponse {
+    pub const fn success(warnings: Vec<String>) -> Self {
+        Self {
+            success: true,
+            errors: Vec::new(),
+            warnings,
+        }
+    }
+
+    pub const fn failure(errors: Vec<String>) -> Self {
+        Self {
+            success: false,
+            errors,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl From<Result<(), String>> for ControlResponse {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self::success(Vec::new()),
+            Err(error) => Self::failure(vec![error]),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::{collections::BTreeMap, num::NonZeroUsize};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use tokio::{select, sync::mpsc};
+
+use super::fanout::Fanout;
+use crate::{
+    config::ComponentKey,
+    event::{
+        BatchNotifier, BatchStatus, BatchStatusReceiver, Event, EventArray, EventFinalizer,
+        EventMutRef, Finalizable, Rejection, Value,
+    },
+};
+
+/// Name of the field, or prefix of the tags for metrics, that rejected events are annotated with.
+const DEAD_LETTER_FIELD: &str = "dead_letter";
+
+/// An event that was sent to a sink, along with the receiver for its finalization status.
+struct PendingEvent {
+    event: Event,
+    receiver: BatchStatusReceiver,
+}
+
+impl PendingEvent {
+    /// Waits for the sink to finalize the event, returning it if it was rejected.
+    async fn rejected(mut self) -> Option<(Event, Option<Rejection>)> {
+        match (&mut self.receiver).await {
+            BatchStatus::Rejected => Some((self.event, self.receiver.rejection().cloned())),
+            BatchStatus::Delivered | BatchStatus::Errored => None,
+        }
+    }
+}
+
+/// Tracks the events sent to a sink, so that the events it permanently fails to deliver can be
+/// sent to its dead-letter output.
+///
+/// Each event gets its own batch notifier, so that a sink rejecting only some of the events of an
+/// array doesn't send the rest of them to the dead-letter output.  A copy of each event, without
+/// the finalizers of its source, is held until the sink finalizes it.
+///
+/// Only a bounded number of copies are held at once: once the limit is reached, tracking more
+/// events waits for the sink to finalize some, and for the rejected ones to be sent on to the
+/// dead-letter output, which applies backpressure to the sink's input.
+#[derive(Clone)]
+pub(super) struct DeadLetterTracker {
+    pending: mpsc::Sender<PendingEvent>,
+}
+
+impl DeadLetterTracker {
+    /// Creates a new tracker for the sink with the given key, along with the task that sends the
+    /// events rejected by the sink to `fanout`.
+    ///
+    /// The tracker holds a copy of at most `max_pending_events` events waiting to be finalized by
+    /// the sink, in addition to as many events again waiting to be picked up by the task.  The task
+    /// completes once the tracker has been dropped and every tracked event has been finalized.
+    pub(super) fn new(
+        key: ComponentKey,
+        mut fanout: Fanout,
+        max_pending_events: NonZeroUsize,
+    ) -> (Self, impl std::future::Future<Output = ()>) {
+        let capacity = max_pending_events.get();
+        let (pending_tx, mut pending_rx) = mpsc::channel::<PendingEvent>(capacity);
+
+        let pump = async move {
+            let mut pending = FuturesUnordered::new();
+            loop {
+                select! {
+                    Some(event) = pending_rx.recv(), if pending.len() < capacity => {
+                        pending.push(event.rejected());
+                    },
+                    Some(result) = pending.next() => {
+                        if let Some((event, rejection)) = result {
+                            let event = annotate(event, rejection.as_ref(), &key);
+                            fanout.send(event.into()).await;
+                        }
+                    },
+                    else => break,
+                }
+            }
+        };
+
+        (
+            Self {
+                pending: pending_tx,
+            },
+            pump,
+        )
+    }
+
+    /// Starts tracking the given events, which are about to be sent to the sink.
+    ///
+    /// Waits for room if the tracker already holds as many events as it can.
+    pub(super) async fn track(&self, mut events: EventArray) -> EventArray {
+        let mut pending = Vec::with_capacity(events.len());
+        events.for_each_event(|event| {
+            let (batch, receiver) = BatchNotifier::new_with_receiver();
+            let finalizer = EventFinalizer::new(batch);
+            let mut copy = match event {
+                EventMutRef::Log(log) => {
+                    let copy = log.clone();
+                    log.add_finalizer(finalizer);
+                    Event::from(copy)
+                }
+                EventMutRef::Metric(metric) => {
+                    let copy = metric.clone();
+                    metric.add_finalizer(finalizer);
+                    Event::from(copy)
+                }
+                EventMutRef::Trace(trace) => {
+                    let copy = trace.clone();
+                    trace.add_finalizer(finalizer);
+                    Event::from(copy)
+                }
+            };
+            // The copy must not hold up the acknowledgement of the event to its source.
+            drop(copy.take_finalizers());
+
+            pending.push(PendingEvent {
+                event: copy,
+                receiver,
+            });
+        });
+
+        for event in pending {
+            // The pump only goes away once the tracker does, so this can't fail.
+            let _ = self.pending.send(event).await;
+        }
+        events
+    }
+}
+
+/// Annotates a rejected event with the reason it was rejected, and the sink that rejected it.
+///
+/// Logs and traces get a `dead_letter` object field, while metrics get `dead_letter_*` tags.
+fn annotate(mut event: Event, rejection: Option<&Rejection>, key: &ComponentKey) -> Event {
+    let message = rejection.map(|rejection| rejection.message().to_string());
+    let status_code = rejection.and_then(Rejection::status_code);
+
+    match &mut event {
+        Event::Log(log) => {
+            log.insert(
+                DEAD_LETTER_FIELD,
+                dead_letter_value(message, status_code, key),
+            );
+        }
+        Event::Trace(trace) => {
+            trace.insert(
+                DEAD_LETTER_FIELD,
+                dead_letter_value(message, status_code, key),
+            );
+        }
+        Event::Metric(metric) => {
+            metric.insert_tag(
+                format!("{}_component_id", DEAD_LETTER_FIELD),
+                key.id().to_string(),
+            );
+            if let Some(message) = message {
+                metric.insert_tag(format!("{}_error", DEAD_LETTER_FIELD), message);
+            }
+            if let Some(status_code) = status_code {
+                metric.insert_tag(
+                    format!("{}_status_code", DEAD_LETTER_FIELD),
+                    status_code.to_string(),
+                );
+            }
+        }
+    }
+
+    event
+}
+
+fn dead_letter_value(
+    message: Option<String>,
+    status_code: Option<u16>,
+    key: &ComponentKey,
+) -> Value {
+    let mut fields = BTreeMap::new();
+    fields.insert("component_id".to_string(), Value::from(key.id()));
+    if let Some(message) = message {
+        fields.insert("error".to_string(), Value::from(message));
+    }
+    if let Some(status_code) = status_code {
+        fields.insert(
+            "status_code".to_string(),
+            Value::from(i64::from(status_code)),
+        );
+    }
+    Value::Object(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use vector_core::buffers::{topology::builder::TopologyBuilder, WhenFull};
+
+    use super::*;
+    use crate::{
+        event::{EventContainer, EventStatus, LogEvent},
+        topology::builder::TOPOLOGY_BUFFER_SIZE,
+    };
+
+    #[tokio::test]
+    async fn sends_rejected_events_to_dead_letter_output() {
+        let (tx, rx) =
+            TopologyBuilder::standalone_memory(TOPOLOGY_BUFFER_SIZE, WhenFull::Block).await;
+        let (mut fanout, _control) = Fanout::new();
+        fanout.add(ComponentKey::from("fallback"), tx);
+
+        let (tracker, pump) = DeadLetterTracker::new(
+            ComponentKey::from("primary"),
+            fanout,
+            NonZeroUsize::new(10).unwrap(),
+        );
+        let pump = tokio::spawn(pump);
+
+        let events = EventArray::from(vec![
+            LogEvent::from("delivered"),
+            LogEvent::from("rejected"),
+        ]);
+        let mut events = tracker.track(events).await.into_events();
+        drop(tracker);
+
+        let mut delivered = events.next().unwrap();
+        let mut rejected = events.next().unwrap();
+        delivered
+            .take_finalizers()
+            .update_status(EventStatus::Delivered);
+        rejected
+            .take_finalizers()
+            .reject(Rejection::new("mapper_parsing_exception").with_status_code(400));
+
+        pump.await.unwrap();
+
+        let dead_letters = rx
+            .into_stream()
+            .flat_map(|events| futures::stream::iter(events.into_events()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(dead_letters.len(), 1);
+
+        let log = dead_letters[0].as_log();
+        assert_eq!(log["message"], "rejected".into());
+        assert_eq!(log["dead_letter.component_id"], "primary".into());
+        assert_eq!(log["dead_letter.error"], "mapper_parsing_exception".into());
+        assert_eq!(log["dead_letter.status_code"], 400.into());
+    }
+
+    #[tokio::test]
+    async fn slow_dead_letter_consumer_applies_backpressure() {
+        let (tx, rx) = TopologyBuilder::standalone_memory(1, WhenFull::Block).await;
+        let (mut fanout, _control) = Fanout::new();
+        fanout.add(ComponentKey::from("fallback"), tx);
+
+        let (tracker, pump) = DeadLetterTracker::new(
+            ComponentKey::from("primary"),
+            fanout,
+            NonZeroUsize::new(2).unwrap(),
+        );
+        let pump = tokio::spawn(pump);
+
+        // The sink rejects every event as soon as it gets it.
+        let mut sink = tokio::spawn(async move {
+            for i in 0..20 {
+                let events = EventArray::from(vec![LogEvent::from(format!("event {}", i))]);
+                for mut event in tracker.track(events).await.into_events() {
+                    event
+                        .take_finalizers()
+                        .reject(Rejection::new("mapper_parsing_exception"));
+                }
+            }
+        });
+
+        // Nothing is reading from the dead-letter output yet, so the sink gets held up once the
+        // tracker is full, rather than the tracker holding on to every event.
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), &mut sink)
+                .await
+                .is_err()
+        );
+
+        let dead_letters = rx
+            .into_stream()
+            .flat_map(|events| futures::stream::iter(events.into_events()))
+            .collect::<Vec<_>>();
+        let (dead_letters, sink) = tokio::join!(dead_letters, sink);
+        sink.unwrap();
+        pump.await.unwrap();
+
+        assert_eq!(dead_letters.len(), 20);
+    }
+}
@@ -9,6 +9,8 @@
 pub(super) use vector_core::fanout;
 
 pub mod builder;
+pub mod control;
+mod dead_letter;
 mod ready_arrays;
 mod running;
 mod schema;
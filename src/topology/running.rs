@@ -1,24 +1,26 @@
 use std::{
     collections::{HashMap, HashSet},
+    io,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
+    time::SystemTime,
 };
 
-use futures::{future, Future, FutureExt};
+use futures::{future, future::BoxFuture, Future, FutureExt};
 use tokio::{
     sync::{mpsc, watch},
     time::{interval, sleep_until, Duration, Instant},
 };
 use tracing::Instrument;
-use vector_buffers::topology::channel::BufferSender;
+use vector_buffers::{topology::channel::BufferSender, InspectError, ReplaySummary};
 use vector_common::trigger::DisabledTrigger;
 
 use super::{TapOutput, TapResource};
 use crate::{
     config::{ComponentKey, Config, ConfigDiff, HealthcheckOptions, OutputId, Resource},
-    event::EventArray,
+    event::{EventArray, EventContainer},
     shutdown::SourceShutdownCoordinator,
     spawn_named,
     topology::{
@@ -43,6 +45,8 @@ pub struct RunningTopology {
     abort_tx: mpsc::UnboundedSender<()>,
     watch: (WatchTx, WatchRx),
     pub(crate) running: Arc<AtomicBool>,
+    paused_sources: HashSet<ComponentKey>,
+    drained_sinks: HashSet<ComponentKey>,
 }
 
 impl RunningTopology {
@@ -58,6 +62,8 @@ impl RunningTopology {
             abort_tx,
             watch: watch::channel(TapResource::default()),
             running: Arc::new(AtomicBool::new(true)),
+            paused_sources: HashSet::new(),
+            drained_sinks: HashSet::new(),
         }
     }
 
@@ -97,9 +103,11 @@ impl RunningTopology {
     /// poll for when the tasks have completed. Once the returned future is
     /// dropped then everything from this RunningTopology instance is fully
     /// dropped.
-    pub fn stop(self) -> impl Future<Output = ()> {
+    pub fn stop(mut self) -> impl Future<Output = ()> {
         // Update the API's health endpoint to signal shutdown
         self.running.store(false, Ordering::Relaxed);
+        // Paused sources can't finish sending the events they have until they're resumed.
+        self.resume_paused_sources();
         // Create handy handles collections of all tasks for the subsequent
         // operations.
         let mut wait_handles = Vec::new();
@@ -204,16 +212,39 @@ impl RunningTopology {
     /// If all changes from the new configuration cannot be made, and the current configuration
     /// cannot be fully restored, then `Err(())` is returned.
     pub async fn reload_config_and_respawn(&mut self, new_config: Config) -> Result<bool, ()> {
+        self.reload_config_and_respawn_with_errors(new_config)
+            .await
+            .map(|errors| errors.is_empty())
+    }
+
+    /// Attempts to load a new configuration and update this running topology, the same way as
+    /// [`RunningTopology::reload_config_and_respawn`] does.
+    ///
+    /// If the new configuration was loaded, an empty list is returned. Otherwise, the topology is
+    /// brought back to its previous state, and the errors that prevented the new configuration from
+    /// being loaded are returned.
+    ///
+    /// # Errors
+    ///
+    /// If all changes from the new configuration cannot be made, and the current configuration
+    /// cannot be fully restored, then `Err(())` is returned.
+    pub async fn reload_config_and_respawn_with_errors(
+        &mut self,
+        new_config: Config,
+    ) -> Result<Vec<String>, ()> {
         info!("Reloading running topology with new configuration.");
 
         if self.config.global != new_config.global {
-            error!(
-                message =
-                "Global options can't be changed while reloading config file; reload aborted. Please restart Vector to reload the configuration file."
-            );
-            return Ok(false);
+            let error = "Global options can't be changed while reloading config file; reload aborted. Please restart Vector to reload the configuration file.";
+            error!(message = error);
+            return Ok(vec![error.to_string()]);
         }
 
+        // Reloading rewires components to each other, so any paused source is resumed, and any
+        // drained sink is attached to its inputs again, beforehand.
+        self.resume_paused_sources();
+        self.reattach_drained_sinks();
+
         // Calculate the change between the current configuration and the new configuration, and
         // shutdown any components that are changing so that we can reclaim their buffers before
         // spawning the new version of the component.
@@ -233,24 +264,34 @@ impl RunningTopology {
         // Try to build all of the new components coming from the new configuration.  If we can
         // successfully build them, we'll attempt to connect them up to the topology and spawn their
         // respective component tasks.
-        if let Some(mut new_pieces) = build_or_log_errors(&new_config, &diff, buffers.clone()).await
-        {
-            // If healthchecks are configured for any of the changing/new components, try running
-            // them before moving forward with connecting and spawning.  In some cases, healthchecks
-            // failing may be configured as a non-blocking issue and so we'll still continue on.
-            if self
-                .run_healthchecks(&diff, &mut new_pieces, new_config.healthchecks)
-                .await
-            {
-                self.connect_diff(&diff, &mut new_pieces).await;
-                self.spawn_diff(&diff, new_pieces);
-                self.config = new_config;
-
-                info!("New configuration loaded successfully.");
+        let errors = match builder::build_pieces(&new_config, &diff, buffers.clone()).await {
+            Ok(mut new_pieces) => {
+                // If healthchecks are configured for any of the changing/new components, try
+                // running them before moving forward with connecting and spawning.  In some cases,
+                // healthchecks failing may be configured as a non-blocking issue and so we'll still
+                // continue on.
+                if self
+                    .run_healthchecks(&diff, &mut new_pieces, new_config.healthchecks)
+                    .await
+                {
+                    self.connect_diff(&diff, &mut new_pieces).await;
+                    self.spawn_diff(&diff, new_pieces);
+                    self.config = new_config;
+
+                    info!("New configuration loaded successfully.");
+
+                    return Ok(Vec::new());
+                }
 
-                return Ok(true);
+                vec!["Sinks unhealthy.".to_string()]
             }
-        }
+            Err(errors) => {
+                for error in &errors {
+                    error!(message = "Configuration error.", %error);
+                }
+                errors
+            }
+        };
 
         // We failed to build, connect, and spawn all of the changed/new components, so we flip
         // around the configuration differential to generate all the components that we need to
@@ -268,7 +309,7 @@ impl RunningTopology {
 
                 info!("Old configuration restored successfully.");
 
-                return Ok(false);
+                return Ok(errors);
             }
         }
 
@@ -277,6 +318,202 @@ impl RunningTopology {
         Err(())
     }
 
+    /// Pauses a source, by pausing every component input it sends events to.
+    ///
+    /// The source stops sending events downstream, and eventually stops accepting events, until
+    /// it's resumed, or until the topology is reloaded.
+    ///
+    /// # Errors
+    ///
+    /// If the source doesn't exist, or is already paused, an error describing why is returned.
+    pub fn pause_source(&mut self, key: &ComponentKey) -> Result<(), String> {
+        if self.config.source(key).is_none() {
+            return Err(format!("Source \"{}\" doesn't exist.", key));
+        }
+        if !self.paused_sources.insert(key.clone()) {
+            return Err(format!("Source \"{}\" is already paused.", key));
+        }
+
+        for (output_id, consumer) in self.source_consumers(key) {
+            if let Some(output) = self.outputs.get(&output_id) {
+                debug!(component = %consumer, fanout_id = %output_id, "Pausing component input in fanout.");
+
+                let _ = output.send(ControlMessage::Replace(consumer, None));
+            }
+        }
+
+        info!(component = %key, "Paused source.");
+        Ok(())
+    }
+
+    /// Resumes a source paused by [`RunningTopology::pause_source`].
+    ///
+    /// # Errors
+    ///
+    /// If the source isn't paused, an error describing why is returned.
+    pub fn resume_source(&mut self, key: &ComponentKey) -> Result<(), String> {
+        if !self.paused_sources.remove(key) {
+            return Err(format!("Source \"{}\" isn't paused.", key));
+        }
+
+        self.replace_source_consumers(key);
+
+        info!(component = %key, "Resumed source.");
+        Ok(())
+    }
+
+    /// Drains a sink, by removing it from the outputs of every component it receives events from.
+    ///
+    /// The sink keeps delivering the events already sent to it, such as the ones in its buffer, but
+    /// isn't sent any new ones, until the topology is reloaded.
+    ///
+    /// A sink using a shared disk buffer can't be drained, as it may be the one writing the events
+    /// sent to every sink sharing the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If the sink doesn't exist, uses a shared disk buffer, or is already drained, an error
+    /// describing why is returned.
+    pub fn drain_sink(&mut self, key: &ComponentKey) -> Result<(), String> {
+        let sink = match self.config.sink(key) {
+            Some(sink) => sink,
+            None => return Err(format!("Sink \"{}\" doesn't exist.", key)),
+        };
+        if let Some(name) = sink.buffer.shared_disk_buffer() {
+            return Err(format!(
+                "Sink \"{}\" can't be drained, as it uses the shared disk buffer \"{}\".",
+                key, name
+            ));
+        }
+        if !self.drained_sinks.insert(key.clone()) {
+            return Err(format!("Sink \"{}\" is already drained.", key));
+        }
+
+        for input in self.config.inputs_for_node(key).into_iter().flatten() {
+            if let Some(output) = self.outputs.get(input) {
+                debug!(component = %key, fanout_id = %input, "Removing component input from fanout.");
+
+                let _ = output.send(ControlMessage::Remove(key.clone()));
+            }
+        }
+
+        info!(component = %key, "Draining sink.");
+        Ok(())
+    }
+
+    /// Replays the records in the retained data files of a sink's disk buffer written to since the
+    /// given time, by sending them to the sink again, after the events it already has.
+    ///
+    /// Unlike `vector buffer --replay-since`, this works while the sink is running, as the records
+    /// are sent through the sink's buffer rather than written to its data files directly. As there,
+    /// retained data files are replayed whole, and each one is deleted once its records have been
+    /// sent, as they're retained again once they've been acknowledged.
+    ///
+    /// Only the disk buffer is opened here: the returned future does the replaying, so that it
+    /// can be waited on without holding up the topology.
+    ///
+    /// # Errors
+    ///
+    /// If the sink doesn't exist, doesn't have a disk buffer of its own, or its disk buffer can't
+    /// be opened, an error describing why is returned.  The returned future fails if a retained
+    /// data file can't be read, or if the sink stops before every record has been sent to it.
+    pub async fn replay_sink(
+        &self,
+        key: &ComponentKey,
+        since: SystemTime,
+    ) -> Result<BoxFuture<'static, Result<ReplaySummary, String>>, String> {
+        let sink = match self.config.sink(key) {
+            Some(sink) => sink,
+            None => return Err(format!("Sink \"{}\" doesn't exist.", key)),
+        };
+        let inspector = match sink
+            .buffer
+            .open_disk_buffer(self.config.global.data_dir.as_deref(), &key.to_string())
+            .await
+        {
+            Ok(Some(inspector)) => inspector,
+            Ok(None) => {
+                return Err(format!(
+                    "Sink \"{}\" doesn't have a disk buffer of its own to replay.",
+                    key
+                ))
+            }
+            Err(error) => {
+                return Err(format!(
+                    "Failed to open the disk buffer of sink \"{}\": {}",
+                    key, error
+                ))
+            }
+        };
+        let mut input = match self.inputs.get(key) {
+            Some(input) => input.clone(),
+            None => return Err(format!("Sink \"{}\" isn't running.", key)),
+        };
+
+        let key = key.clone();
+        let replay = async move {
+            let retained = inspector
+                .retained_data_files()
+                .await
+                .map_err(|error| format!("Failed to list retained data files: {}", error))?;
+
+            let mut summary = ReplaySummary::default();
+            for file in retained.into_iter().filter(|file| file.modified >= since) {
+                let records = match inspector.read_retained_data_file::<EventArray>(&file).await {
+                    Ok(records) => records,
+                    // The retained data file may have been pruned in the meantime.
+                    Err(InspectError::Io { source })
+                        if source.kind() == io::ErrorKind::NotFound =>
+                    {
+                        continue
+                    }
+                    Err(error) => {
+                        return Err(format!(
+                            "Failed to read retained data file '{}': {}",
+                            file.path.display(),
+                            error
+                        ))
+                    }
+                };
+
+                for record in records {
+                    let events = record.len() as u64;
+                    input.send(record).await.map_err(|()| {
+                        format!("Sink \"{}\" stopped while records were replayed.", key)
+                    })?;
+                    summary.records += 1;
+                    summary.events += events;
+                }
+                input.flush().await.map_err(|()| {
+                    format!("Sink \"{}\" stopped while records were replayed.", key)
+                })?;
+
+                inspector
+                    .remove_retained_data_file(&file)
+                    .await
+                    .map_err(|error| {
+                        format!(
+                            "Failed to delete replayed data file '{}': {}",
+                            file.path.display(),
+                            error
+                        )
+                    })?;
+                summary.data_files += 1;
+            }
+
+            info!(
+                component = %key,
+                data_files = summary.data_files,
+                records = summary.records,
+                events = summary.events,
+                "Replayed retained data files."
+            );
+            Ok(summary)
+        };
+
+        Ok(replay.boxed())
+    }
+
     pub(crate) async fn run_healthchecks(
         &mut self,
         diff: &ConfigDiff,
@@ -466,6 +703,13 @@ impl RunningTopology {
             self.remove_inputs(key, diff).await;
         }
 
+        // Only once every changed/removed sink has been disconnected from its inputs can we remove
+        // the dead-letter outputs of changed/removed sinks, as a sink may be consuming the
+        // dead-letter output of another sink.
+        for key in diff.sinks.removed_and_changed() {
+            self.remove_outputs(key);
+        }
+
         // Now that we've disconnected or temporarily detached the inputs to all changed/removed
         // sinks, we can actually wait for them to shutdown before collecting any buffers that are
         // marked for reuse.
@@ -547,6 +791,18 @@ impl RunningTopology {
             self.setup_outputs(key, new_pieces).await;
         }
 
+        // Sinks can have a dead-letter output too, which can feed transforms and other sinks, so we
+        // configure those next.
+        for key in diff.sinks.changed_and_added() {
+            if new_pieces.outputs.contains_key(key) {
+                debug!(component = %key, "Configuring outputs for sink.");
+                if let Some(task) = new_pieces.tasks.get(key) {
+                    tap_metadata.insert(key, ("sink", task.typetag().to_string()));
+                }
+                self.setup_outputs(key, new_pieces).await;
+            }
+        }
+
         // Now that all possible outputs are configured, we can start wiring up inputs, starting
         // with transforms.
         for key in diff.transforms.changed_and_added() {
@@ -554,7 +810,8 @@ impl RunningTopology {
             self.setup_inputs(key, diff, new_pieces).await;
         }
 
-        // Now that all sources and transforms are fully configured, we can wire up sinks.
+        // Now that all sources, transforms, and sink outputs are fully configured, we can wire up
+        // sinks.
         for key in diff.sinks.changed_and_added() {
             debug!(component = %key, "Connecting inputs for sink.");
             self.setup_inputs(key, diff, new_pieces).await;
@@ -595,6 +852,7 @@ impl RunningTopology {
                 .collect::<HashMap<_, _>>();
             let mut removals = diff.sources.to_remove.clone();
             removals.extend(diff.transforms.to_remove.iter().cloned());
+            removals.extend(diff.sinks.to_remove.iter().cloned());
             self.watch
                 .0
                 .send(TapResource {
@@ -610,8 +868,6 @@ impl RunningTopology {
                         .changed_and_added()
                         .map(|key| key.to_string())
                         .collect(),
-                    // Note, only sources and transforms are relevant. Sinks do
-                    // not have outputs to tap.
                     removals,
                 })
                 .expect("Couldn't broadcast config changes.");
@@ -711,6 +967,72 @@ impl RunningTopology {
         }
     }
 
+    /// Gets the components that are sent events by the given source, along with the output of the
+    /// source they're sent events from.
+    ///
+    /// Drained sinks aren't sent events, so they're skipped.
+    fn source_consumers(&self, key: &ComponentKey) -> Vec<(OutputId, ComponentKey)> {
+        let transforms = self
+            .config
+            .transforms()
+            .map(|(consumer, transform)| (consumer, &transform.inputs));
+        let sinks = self
+            .config
+            .sinks()
+            .filter(|(consumer, _)| !self.drained_sinks.contains(consumer))
+            .map(|(consumer, sink)| (consumer, &sink.inputs));
+
+        transforms
+            .chain(sinks)
+            .flat_map(|(consumer, inputs)| {
+                inputs
+                    .iter()
+                    .filter(|input| &input.component == key)
+                    .map(move |input| (input.clone(), consumer.clone()))
+            })
+            .collect()
+    }
+
+    /// Replaces the paused inputs of every component that the given source sends events to.
+    fn replace_source_consumers(&mut self, key: &ComponentKey) {
+        for (output_id, consumer) in self.source_consumers(key) {
+            if let (Some(output), Some(input)) =
+                (self.outputs.get(&output_id), self.inputs.get(&consumer))
+            {
+                debug!(component = %consumer, fanout_id = %output_id, "Replacing component input in fanout.");
+
+                let _ = output.send(ControlMessage::Replace(consumer, Some(input.clone())));
+            }
+        }
+    }
+
+    fn resume_paused_sources(&mut self) {
+        for key in std::mem::take(&mut self.paused_sources) {
+            debug!(component = %key, "Resuming paused source.");
+            self.replace_source_consumers(&key);
+        }
+    }
+
+    fn reattach_drained_sinks(&mut self) {
+        for key in std::mem::take(&mut self.drained_sinks) {
+            let input = match self.inputs.get(&key) {
+                Some(input) => input.clone(),
+                None => {
+                    // The sink was removed while drained, so there's nothing to reattach.
+                    debug!(component = %key, "Drained sink no longer has an input to reattach.");
+                    continue;
+                }
+            };
+            for output_id in self.config.inputs_for_node(&key).into_iter().flatten() {
+                if let Some(output) = self.outputs.get(output_id) {
+                    debug!(component = %key, fanout_id = %output_id, "Reattaching component input to fanout.");
+
+                    let _ = output.send(ControlMessage::Add(key.clone(), input.clone()));
+                }
+            }
+        }
+    }
+
     fn reattach_severed_inputs(&mut self, diff: &ConfigDiff) {
         let unchanged_transforms = self
             .config
@@ -861,5 +1183,14 @@ fn get_changed_outputs(diff: &ConfigDiff, output_ids: Vec<OutputId>) -> Vec<Outp
         );
     }
 
+    for sink_key in &diff.sinks.to_change {
+        changed_outputs.extend(
+            output_ids
+                .iter()
+                .filter(|id| &id.component == sink_key)
+                .cloned(),
+        );
+    }
+
     changed_outputs
 }
@@ -134,7 +134,8 @@ async fn buffer_drop_fan_out() {
     );
     sink_outer.buffer = BufferConfig {
         stages: vec![BufferType::Memory {
-            max_events: MEMORY_BUFFER_DEFAULT_MAX_EVENTS,
+            max_events: Some(MEMORY_BUFFER_DEFAULT_MAX_EVENTS),
+            max_size: None,
             when_full: WhenFull::DropNewest,
         }],
     };
@@ -8,8 +8,10 @@ use std::{
 };
 
 use crate::{
-    config::{Config, ConfigDiff, SinkOuter},
-    event::{into_event_stream, Event, EventArray, EventContainer},
+    config::{ComponentKey, Config, ConfigDiff, SinkOuter},
+    event::{
+        into_event_stream, Event, EventArray, EventContainer, EventStatus, Finalizable, Rejection,
+    },
     test_util::{
         mock::{
             basic_sink, basic_sink_failing_healthcheck, basic_sink_with_data, basic_source,
@@ -24,7 +26,9 @@ use tokio::{
     task::yield_now,
     time::{sleep, Duration},
 };
-use vector_buffers::{BufferConfig, BufferType, WhenFull};
+use vector_buffers::{
+    BufferConfig, BufferType, DiskBufferCompression, DiskBufferRetention, WhenFull,
+};
 
 #[cfg(all(feature = "sinks-socket", feature = "sources-socket"))]
 mod crash;
@@ -215,6 +219,207 @@ async fn topology_multiple_sinks() {
     assert_eq!(expected, res2);
 }
 
+#[tokio::test]
+async fn topology_sink_dead_letter_output() {
+    trace_init();
+
+    // Create source #1 as `in1`, sink #1 attached to `in1`, and sink #2 attached to the dead-letter
+    // output of sink #1.
+    let (mut in1, source1) = basic_source();
+    let (out1, sink1) = basic_sink(10);
+    let (out2, sink2) = basic_sink(10);
+
+    let mut config = Config::builder();
+    config.add_source("in1", source1);
+    config.add_sink("out1", &["in1"], sink1);
+    config.sinks[&ComponentKey::from("out1")].dead_letter = true.into();
+    config.add_sink("out2", &["out1.dead_letter"], sink2);
+
+    let (topology, _crash) = start_topology(config.build().unwrap(), false).await;
+
+    in1.send_event(Event::from("delivered")).await.unwrap();
+    in1.send_event(Event::from("rejected")).await.unwrap();
+
+    // The mock sink leaves finalizing the events it receives to us, so deliver one and reject the
+    // other.
+    let mut out1 = Box::pin(out1.flat_map(into_event_stream));
+    let mut delivered = out1.next().await.unwrap();
+    let mut rejected = out1.next().await.unwrap();
+    delivered
+        .take_finalizers()
+        .update_status(EventStatus::Delivered);
+    rejected
+        .take_finalizers()
+        .reject(Rejection::new("invalid event").with_status_code(400));
+
+    drop(in1);
+    topology.stop().await;
+
+    // Only the rejected event should make it to sink #2, annotated with the reason it was
+    // rejected.
+    let res2 = out2.flat_map(into_event_stream).collect::<Vec<_>>().await;
+    assert_eq!(res2.len(), 1);
+    let log = res2[0].as_log();
+    assert_eq!(into_message(res2[0].clone()), "rejected");
+    assert_eq!(log["dead_letter.component_id"], "out1".into());
+    assert_eq!(log["dead_letter.error"], "invalid event".into());
+    assert_eq!(log["dead_letter.status_code"], 400.into());
+}
+
+#[tokio::test]
+async fn topology_pause_and_resume_source() {
+    trace_init();
+
+    let (mut in1, source1) = basic_source();
+    let (out1, sink1) = basic_sink(10);
+
+    let mut config = Config::builder();
+    config.add_source("in1", source1);
+    config.add_sink("out1", &["in1"], sink1);
+
+    let (mut topology, _crash) = start_topology(config.build().unwrap(), false).await;
+    let mut out1 = out1.flat_map(into_event_stream);
+
+    let key = ComponentKey::from("in1");
+    topology.pause_source(&key).unwrap();
+    assert!(topology.pause_source(&key).is_err());
+
+    // While the source is paused, none of its events make it to the sink.
+    let event = Event::from("this");
+    in1.send_event(event.clone()).await.unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), out1.next())
+            .await
+            .is_err()
+    );
+
+    topology.resume_source(&key).unwrap();
+    assert!(topology.resume_source(&key).is_err());
+
+    let res = tokio::time::timeout(Duration::from_secs(1), out1.next())
+        .await
+        .expect("timeout");
+    assert_eq!(Some(event), res);
+
+    drop(in1);
+    topology.stop().await;
+}
+
+#[tokio::test]
+async fn topology_drain_sink() {
+    trace_init();
+
+    let (mut in1, source1) = basic_source();
+    let (out1, sink1) = basic_sink(10);
+    let (out2, sink2) = basic_sink(10);
+
+    let mut config = Config::builder();
+    config.add_source("in1", source1);
+    config.add_sink("out1", &["in1"], sink1);
+    config.add_sink("out2", &["in1"], sink2);
+
+    let (mut topology, _crash) = start_topology(config.build().unwrap(), false).await;
+
+    let key = ComponentKey::from("out2");
+    assert!(topology.drain_sink(&ComponentKey::from("in1")).is_err());
+    topology.drain_sink(&key).unwrap();
+    assert!(topology.drain_sink(&key).is_err());
+
+    let event = Event::from("this");
+    in1.send_event(event.clone()).await.unwrap();
+
+    drop(in1);
+    topology.stop().await;
+
+    // Only the sink that wasn't drained is sent the event.
+    let res1 = out1.flat_map(into_event_stream).collect::<Vec<_>>().await;
+    let res2 = out2.flat_map(into_event_stream).collect::<Vec<_>>().await;
+    assert_eq!(vec![event], res1);
+    assert_eq!(Vec::<Event>::new(), res2);
+}
+
+#[tokio::test]
+async fn topology_drain_sink_with_shared_buffer() {
+    trace_init();
+
+    let tmpdir = tempfile::tempdir().expect("no tmpdir");
+
+    let mut config = Config::builder();
+    config.set_data_dir(tmpdir.path());
+    config.add_source("in1", basic_source().1);
+    for key in ["out1", "out2"] {
+        let mut sink_outer = SinkOuter::new(vec![String::from("in1")], Box::new(basic_sink(10).1));
+        sink_outer.buffer = BufferConfig {
+            stages: vec![BufferType::DiskV2 {
+                max_size: std::num::NonZeroU64::new(268_435_488).unwrap(),
+                when_full: WhenFull::Block,
+                compression: DiskBufferCompression::None,
+                encryption: None,
+                retention: None,
+                shared: Some("logs".to_string()),
+            }],
+        };
+        config.add_sink_outer(key, sink_outer);
+    }
+
+    let (mut topology, _crash) = start_topology(config.build().unwrap(), false).await;
+
+    // Either sink may be the one writing to the buffer for both of them.
+    assert!(topology.drain_sink(&ComponentKey::from("out1")).is_err());
+    assert!(topology.drain_sink(&ComponentKey::from("out2")).is_err());
+
+    topology.stop().await;
+}
+
+#[tokio::test]
+async fn topology_replay_sink() {
+    trace_init();
+
+    let tmpdir = tempfile::tempdir().expect("no tmpdir");
+
+    let mut config = Config::builder();
+    config.set_data_dir(tmpdir.path());
+    config.add_source("in1", basic_source().1);
+    config.add_sink("out1", &["in1"], basic_sink(10).1);
+    let mut sink_outer = SinkOuter::new(vec![String::from("in1")], Box::new(basic_sink(10).1));
+    sink_outer.buffer = BufferConfig {
+        stages: vec![BufferType::DiskV2 {
+            max_size: std::num::NonZeroU64::new(268_435_488).unwrap(),
+            when_full: WhenFull::Block,
+            compression: DiskBufferCompression::None,
+            encryption: None,
+            retention: Some(DiskBufferRetention {
+                max_age_secs: std::num::NonZeroU64::new(3600),
+                max_size: None,
+            }),
+            shared: None,
+        }],
+    };
+    config.add_sink_outer("out2", sink_outer);
+
+    let (topology, _crash) = start_topology(config.build().unwrap(), false).await;
+
+    let since = std::time::UNIX_EPOCH;
+    assert!(topology
+        .replay_sink(&ComponentKey::from("in1"), since)
+        .await
+        .is_err());
+    // Only a sink with a disk buffer of its own has retained data files to replay.
+    assert!(topology
+        .replay_sink(&ComponentKey::from("out1"), since)
+        .await
+        .is_err());
+
+    let replay = topology
+        .replay_sink(&ComponentKey::from("out2"), since)
+        .await
+        .unwrap();
+    let summary = replay.await.unwrap();
+    assert_eq!(summary.data_files, 0);
+
+    topology.stop().await;
+}
+
 #[tokio::test]
 async fn topology_transform_chain() {
     let (mut in1, source1) = basic_source();
@@ -12,9 +12,9 @@ use vector_common::TimeZone;
 use vector_config::configurable_component;
 use vector_vrl_functions::set_semantic_meaning::MeaningList;
 use vrl::{
-    diagnostic::{Formatter, Note},
+    diagnostic::Note,
     prelude::{DiagnosticMessage, ExpressionError},
-    Program, Runtime, Terminate, VrlRuntime,
+    Program, Runtime, Terminate, Vm, VrlRuntime,
 };
 
 use crate::{
@@ -23,7 +23,7 @@ use crate::{
         TransformDescription,
     },
     event::{Event, TargetEvents, VrlTarget},
-    internal_events::{RemapMappingAbort, RemapMappingError},
+    internal_events::{RemapMappingAbort, RemapMappingError, RemapProfile},
     schema,
     transforms::{SyncTransform, Transform, TransformOutputsBuf},
     Result,
@@ -95,6 +95,13 @@ pub struct RemapConfig {
     #[configurable(derived)]
     #[serde(default)]
     pub runtime: VrlRuntime,
+
+    /// Records the time spent resolving each expression and function call of the program.
+    ///
+    /// The invocation counts and durations are reported as the `vrl_expression_invocations_total` and
+    /// `vrl_expression_duration_seconds` internal metrics, tagged with the `span` of the expression in the program,
+    /// and its `kind`. Profiling adds overhead to every event, and is only supported by the `ast` runtime.
+    pub profile: bool,
 }
 
 impl RemapConfig {
@@ -108,8 +115,8 @@ impl RemapConfig {
         Vec<Box<dyn vrl::Function>>,
         vrl::state::ExternalEnv,
     )> {
-        let source = match (&self.source, &self.file) {
-            (Some(source), None) => source.to_owned(),
+        let mut source = match (&self.source, &self.file) {
+            (Some(source), None) => vrl::Source::new(source.to_owned()),
             (None, Some(path)) => {
                 let mut buffer = String::new();
 
@@ -118,7 +125,11 @@ impl RemapConfig {
                     .read_to_string(&mut buffer)
                     .with_context(|_| FileReadFailedSnafu { path })?;
 
-                buffer
+                // Imports in a program file are resolved relative to that file.
+                match path.parent() {
+                    Some(dir) => vrl::Source::new(buffer).with_base_dir(dir),
+                    None => vrl::Source::new(buffer),
+                }
             }
             _ => return Err(Box::new(BuildError::SourceAndOrFile)),
         };
@@ -126,6 +137,7 @@ impl RemapConfig {
         let mut functions = vrl_stdlib::all();
         functions.append(&mut enrichment::vrl_functions());
         functions.append(&mut vector_vrl_functions::vrl_functions());
+        functions.append(&mut vector_core::event::vrl_functions::all());
 
         let mut state = vrl::state::ExternalEnv::new_with_kind(
             merged_schema_definition.collection().clone().into(),
@@ -133,17 +145,13 @@ impl RemapConfig {
         state.set_external_context(enrichment_tables);
         state.set_external_context(MeaningList::default());
 
-        vrl::compile_with_state(&source, &functions, &mut state)
-            .map_err(|diagnostics| {
-                Formatter::new(&source, diagnostics)
-                    .colored()
-                    .to_string()
-                    .into()
-            })
+        source
+            .compile(&functions, &mut state)
+            .map_err(|diagnostics| source.formatter(diagnostics).colored().to_string().into())
             .map(|(program, diagnostics)| {
                 (
                     program,
-                    Formatter::new(&source, diagnostics).to_string(),
+                    source.formatter(diagnostics).to_string(),
                     functions,
                     state,
                 )
@@ -166,6 +174,10 @@ impl TransformConfig for RemapConfig {
                 let (remap, warnings) = Remap::new_ast(self.clone(), context)?;
                 (Transform::synchronous(remap), warnings)
             }
+            VrlRuntime::Vm => {
+                let (remap, warnings) = Remap::new_vm(self.clone(), context)?;
+                (Transform::synchronous(remap), warnings)
+            }
         };
 
         // TODO: We could improve on this by adding support for non-fatal error
@@ -280,13 +292,23 @@ pub trait VrlRunner {
 #[derive(Debug)]
 pub struct AstRunner {
     pub runtime: Runtime,
+    pub profile: bool,
+}
+
+impl AstRunner {
+    fn new(profile: bool) -> Self {
+        let mut runtime = Runtime::default();
+        if profile {
+            runtime.enable_profiling();
+        }
+
+        Self { runtime, profile }
+    }
 }
 
 impl Clone for AstRunner {
     fn clone(&self) -> Self {
-        Self {
-            runtime: Runtime::default(),
-        }
+        Self::new(self.profile)
     }
 }
 
@@ -299,6 +321,11 @@ impl VrlRunner for AstRunner {
     ) -> std::result::Result<value::Value, Terminate> {
         let result = self.runtime.resolve(target, program, timezone);
         self.runtime.clear();
+
+        if let Some(profile) = self.runtime.take_profile() {
+            emit!(RemapProfile { profile });
+        }
+
         result
     }
 }
@@ -313,8 +340,57 @@ impl Remap<AstRunner> {
             context.merged_schema_definition.clone(),
         )?;
 
+        let runner = AstRunner::new(config.profile);
+
+        Self::new(config, context, program, runner).map(|remap| (remap, warnings))
+    }
+}
+
+#[derive(Debug)]
+pub struct VmRunner {
+    pub runtime: Runtime,
+    pub vm: Arc<Vm>,
+}
+
+impl Clone for VmRunner {
+    fn clone(&self) -> Self {
+        Self {
+            runtime: Runtime::default(),
+            vm: Arc::clone(&self.vm),
+        }
+    }
+}
+
+impl VrlRunner for VmRunner {
+    fn run(
+        &mut self,
+        target: &mut VrlTarget,
+        _: &Program,
+        timezone: &TimeZone,
+    ) -> std::result::Result<value::Value, Terminate> {
+        let result = self.runtime.run_vm(&self.vm, target, timezone);
+        self.runtime.clear();
+        result
+    }
+}
+
+impl Remap<VmRunner> {
+    pub fn new_vm(
+        config: RemapConfig,
+        context: &TransformContext,
+    ) -> crate::Result<(Self, String)> {
+        if config.profile {
+            return Err(Box::new(BuildError::ProfileRuntime));
+        }
+
+        let (program, warnings, _, _) = config.compile_vrl_program(
+            context.enrichment_tables.clone(),
+            context.merged_schema_definition.clone(),
+        )?;
+
         let runtime = Runtime::default();
-        let runner = AstRunner { runtime };
+        let vm = Arc::new(Vm::new(&program));
+        let runner = VmRunner { runtime, vm };
 
         Self::new(config, context, program, runner).map(|remap| (remap, warnings))
     }
@@ -522,6 +598,9 @@ pub enum BuildError {
     FileOpenFailed { path: PathBuf, source: io::Error },
     #[snafu(display("Could not read vrl program {:?}: {}", path, source))]
     FileReadFailed { path: PathBuf, source: io::Error },
+
+    #[snafu(display("`profile` is only supported by the `ast` runtime"))]
+    ProfileRuntime,
 }
 
 #[cfg(test)]
@@ -537,8 +616,9 @@ mod tests {
         config::{build_unit_tests, ConfigBuilder},
         event::{
             metric::{MetricKind, MetricValue},
-            LogEvent, Metric, Value,
+            LogEvent, Metric, TraceEvent, Value,
         },
+        metrics::Controller,
         schema,
         test_util::components::{init_test, COMPONENT_MULTIPLE_OUTPUTS_TESTS},
         transforms::OutputBuffer,
@@ -650,6 +730,131 @@ mod tests {
         assert!(tform.runner().runtime.is_empty());
     }
 
+    #[test]
+    fn check_remap_vm_runtime() {
+        let schema_definitions = HashMap::from([
+            (None, test_default_schema_definition()),
+            (Some(DROPPED.to_owned()), test_dropped_schema_definition()),
+        ]);
+        let conf = RemapConfig {
+            source: Some(
+                indoc! {r#"
+                if .sentinel == "bar" {
+                    .foo = upcase!(.sentinel)
+                }
+                .bar, err = 1 + .missing
+                .err = err
+            "#}
+                .to_string(),
+            ),
+            file: None,
+            runtime: VrlRuntime::Vm,
+            ..Default::default()
+        };
+        let (mut tform, _) =
+            Remap::new_vm(conf, &TransformContext::new_test(schema_definitions)).unwrap();
+
+        let event = {
+            let mut event = LogEvent::from("event");
+            event.insert("sentinel", "bar");
+            Event::from(event)
+        };
+        let result = transform_one(&mut tform, event).unwrap();
+        assert_eq!(get_field_string(&result, "foo"), "BAR");
+        assert_eq!(result.as_log().get("bar"), Some(&Value::Null));
+        assert!(get_field_string(&result, "err").starts_with("can't add type null"));
+        assert!(tform.runner().runtime.is_empty());
+    }
+
+    /// Returns the profile metrics emitted so far for expressions of the given kind.
+    fn profile_metrics(kind: &str) -> Vec<Metric> {
+        Controller::get()
+            .unwrap()
+            .capture_metrics()
+            .into_iter()
+            .filter(|metric| {
+                metric.name().starts_with("vrl_expression_")
+                    && metric.tag_value("kind").as_deref() == Some(kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_remap_profile() {
+        init_test();
+
+        let source = ".foo = upcase!(.sentinel)";
+        let conf = RemapConfig {
+            source: Some(source.to_string()),
+            profile: true,
+            ..Default::default()
+        };
+        let mut tform = remap(conf).unwrap();
+
+        let event = {
+            let mut event = LogEvent::from("event");
+            event.insert("sentinel", "bar");
+            Event::from(event)
+        };
+        let result = transform_one(&mut tform, event).unwrap();
+        assert_eq!(get_field_string(&result, "foo"), "BAR");
+        assert!(tform.runner().runtime.is_empty());
+
+        let statement = format!("0..{}", source.len());
+        let metrics = profile_metrics("expression")
+            .into_iter()
+            .filter(|metric| metric.tag_value("span").as_ref() == Some(&statement))
+            .collect::<Vec<_>>();
+        assert!(metrics.iter().any(|metric| {
+            metric.name() == "vrl_expression_invocations_total"
+                && metric.value() == &MetricValue::Counter { value: 1.0 }
+        }));
+        assert!(metrics.iter().any(|metric| {
+            metric.name() == "vrl_expression_duration_seconds"
+                && matches!(
+                    metric.value(),
+                    MetricValue::AggregatedHistogram { count: 1, .. }
+                )
+        }));
+
+        let metrics = profile_metrics("function call `upcase`");
+        for name in [
+            "vrl_expression_invocations_total",
+            "vrl_expression_duration_seconds",
+        ] {
+            assert!(metrics
+                .iter()
+                .any(|metric| metric.name() == name && metric.tag_value("span").is_some()));
+        }
+    }
+
+    #[test]
+    fn check_remap_profile_vm_runtime() {
+        init_test();
+
+        let schema_definitions = HashMap::from([
+            (None, test_default_schema_definition()),
+            (Some(DROPPED.to_owned()), test_dropped_schema_definition()),
+        ]);
+        let conf = RemapConfig {
+            source: Some(".foo = .sentinel".to_string()),
+            runtime: VrlRuntime::Vm,
+            profile: true,
+            ..Default::default()
+        };
+
+        let err = Remap::new_vm(conf, &TransformContext::new_test(schema_definitions))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(&err, "`profile` is only supported by the `ast` runtime");
+
+        // The rejected program never runs, so nothing is reported for its statement.
+        let statement = format!("0..{}", ".foo = .sentinel".len());
+        assert!(profile_metrics("expression")
+            .iter()
+            .all(|metric| metric.tag_value("span").as_ref() != Some(&statement)));
+    }
+
     #[test]
     fn check_remap_adds() {
         let event = {
@@ -912,6 +1117,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_remap_trace() {
+        let trace = Event::Trace(TraceEvent::from(btreemap! {
+            "service" => "api",
+            "spans" => vec![
+                btreemap! { "resource" => "GET /health" },
+                btreemap! { "resource" => "GET /" },
+            ],
+        }));
+
+        let conf = RemapConfig {
+            source: Some(
+                r#".service = "web"
+                   .spans = filter(array!(.spans)) -> |_index, span| { span.resource != "GET /health" }"#
+                    .to_string(),
+            ),
+            file: None,
+            timezone: TimeZone::default(),
+            drop_on_error: true,
+            drop_on_abort: false,
+            ..Default::default()
+        };
+        let mut tform = remap(conf).unwrap();
+
+        let result = transform_one(&mut tform, trace).unwrap();
+        let trace = result.as_trace();
+        assert_eq!(trace.get("service"), Some(&Value::from("web")));
+        assert_eq!(
+            trace.spans().cloned().collect::<Vec<_>>(),
+            vec![btreemap! { "resource" => "GET /" }]
+        );
+    }
+
     #[test]
     fn check_remap_branching() {
         let happy = Event::try_from(serde_json::json!({"hello": "world"})).unwrap();
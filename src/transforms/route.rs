@@ -160,11 +160,13 @@ impl TransformConfig for RouteCompatConfig {
 #[cfg(test)]
 mod test {
     use indoc::indoc;
+    use vector_common::btreemap;
     use vector_core::transform::TransformOutputsBuf;
 
     use super::*;
     use crate::{
         config::{build_unit_tests, ConfigBuilder},
+        event::TraceEvent,
         test_util::components::{init_test, COMPONENT_MULTIPLE_OUTPUTS_TESTS},
     };
 
@@ -344,6 +346,49 @@ mod test {
         }
     }
 
+    #[test]
+    fn route_traces() {
+        let output_names = vec!["traces", "health_checks", UNMATCHED_ROUTE];
+        let health_check = Event::from(TraceEvent::from(btreemap! {
+            "spans" => vec![btreemap! { "resource" => "GET /health" }],
+        }));
+        let trace = Event::from(TraceEvent::from(btreemap! {
+            "spans" => vec![btreemap! { "resource" => "GET /" }],
+        }));
+        let log = Event::from("GET /health");
+        let config = toml::from_str::<RouteConfig>(
+            r#"
+            route.traces.type = "is_trace"
+
+            route.health_checks.type = "vrl"
+            route.health_checks.source = '.spans[0].resource == "GET /health"'
+        "#,
+        )
+        .unwrap();
+
+        let mut transform = Route::new(&config, &Default::default()).unwrap();
+        let mut outputs = TransformOutputsBuf::new_with_capacity(
+            output_names
+                .iter()
+                .map(|output_name| {
+                    Output::default(DataType::all()).with_port(output_name.to_owned())
+                })
+                .collect(),
+            1,
+        );
+
+        for event in [&health_check, &trace, &log] {
+            transform.transform(event.clone(), &mut outputs);
+        }
+
+        let traces: Vec<_> = outputs.drain_named("traces").collect();
+        assert_eq!(traces, vec![health_check.clone(), trace]);
+        let health_checks: Vec<_> = outputs.drain_named("health_checks").collect();
+        assert_eq!(health_checks, vec![health_check]);
+        let unmatched: Vec<_> = outputs.drain_named(UNMATCHED_ROUTE).collect();
+        assert_eq!(unmatched, vec![log]);
+    }
+
     #[tokio::test]
     async fn route_metrics_with_output_tag() {
         init_test();
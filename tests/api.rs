@@ -168,7 +168,7 @@ mod tests {
         let url = Url::parse(&*format!("ws://{}/graphql", addr)).unwrap();
 
         retry_until(
-            || connect_subscription_client(url.clone()),
+            || connect_subscription_client(url.clone(), None, None),
             Duration::from_millis(50),
             Duration::from_secs(10),
         )
@@ -407,6 +407,14 @@ _values: {
 			description: "A [Datadog Search](\(urls.datadog_search_syntax)) query string."
 			example:     #"*stack"#
 		},
+		{
+			name:        "json_schema"
+			description: """
+				Matches events conforming to a [JSON Schema](\(urls.json_schema)), read from the file at
+				`schema_path`.
+				"""
+			example: #"schemas/user.schema.json"#
+		},
 	]
 
 	options: {
@@ -414,6 +422,10 @@ _values: {
 			description: "The text of the condition. The syntax of the condition depends on the value of `type`."
 		}
 
+		schema_path: {
+			description: "The path of the JSON Schema to validate events against, when `type` is `json_schema`."
+		}
+
 		type: {
 			description: """
 				The type of condition to supply. See **Available syntaxes** below for a list of available types for this
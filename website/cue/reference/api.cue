@@ -15,6 +15,10 @@ api: {
 		The Vector [GraphQL](\(urls.graphql)) API allows you to interact with a
 		running Vector instance, enabling introspection and management of
 		Vector in real-time.
+
+		The API options, including `auth` and `mutations`, only take effect
+		once Vector starts: changes to them are ignored when the configuration
+		is reloaded.
 		"""
 	schema_json_url: "https://github.com/vectordotdev/vector/blob/master/lib/vector-api-client/graphql/schema.json"
 	configuration: {
@@ -47,6 +51,123 @@ api: {
 				of the address set using the `bind` parameter.
 				"""
 		}
+		mutations: {
+			common:      false
+			description: "Configures the GraphQL mutations, which change the running topology."
+			required:    false
+			type: object: options: {
+				enabled: {
+					common:      false
+					description: """
+						Whether the mutations are served. As they can change the running
+						configuration, including starting any component, they can only be
+						enabled along with `auth`.
+						"""
+					required: false
+					type: bool: default: false
+				}
+			}
+		}
+		tls: {
+			common:      false
+			description: "Configures the TLS options for connections to the API."
+			required:    false
+			type: object: options: {
+				enabled: {
+					common:      false
+					description: "Require TLS for connections to the API. If this is set, an identity certificate is also required."
+					required:    false
+					type: bool: default: false
+				}
+				ca_file: {
+					common:      false
+					description: "Absolute path to an additional CA certificate file, in DER or PEM format (X.509), or an in-line CA certificate in PEM format."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["/path/to/certificate_authority.crt"]
+					}
+				}
+				crt_file: {
+					common:      false
+					description: "Absolute path to a certificate file used to identify the API, in DER or PEM format (X.509) or PKCS#12, or an in-line certificate in PEM format. If this is set, and is not a PKCS#12 archive, `key_file` must also be set. This is required if `enabled` is set to `true`."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["/path/to/host_certificate.crt"]
+					}
+				}
+				key_file: {
+					common:      false
+					description: "Absolute path to a private key file used to identify the API, in DER or PEM format (PKCS#8), or an in-line private key in PEM format."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["/path/to/host_certificate.key"]
+					}
+				}
+				key_pass: {
+					common:      false
+					description: "Pass phrase used to unlock the encrypted key file. This has no effect unless `key_file` is set."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["${KEY_PASS_ENV_VAR}", "PassWord1"]
+					}
+				}
+				verify_certificate: {
+					common:      false
+					description: "If `true`, Vector will require a TLS certificate from clients of the API and terminate the connection if the certificate is not valid."
+					required:    false
+					type: bool: default: false
+				}
+			}
+		}
+		auth: {
+			common:      false
+			description: """
+				Credentials that requests to the `/graphql` endpoint must send in
+				their `Authorization` header. Requests without them are rejected
+				with a `401 Unauthorized` response. The `/health` endpoint doesn't
+				require them. The credentials can be loaded from a secret backend,
+				such as `token = "SECRET[backend.api_token]"`, and should only be
+				used along with `tls`.
+				"""
+			required:    false
+			type: object: options: {
+				strategy: {
+					description: "The authentication strategy to use."
+					required:    true
+					type: string: {
+						enum: {
+							basic:  "The [basic authentication strategy](\(urls.basic_auth))."
+							bearer: "The bearer token authentication strategy."
+						}
+					}
+				}
+				token: {
+					description: "The token that requests must send for bearer authentication."
+					required:    true
+					type: string: {
+						examples: ["${VECTOR_API_TOKEN}", "SECRET[backend.api_token]"]
+					}
+				}
+				user: {
+					description: "The user name that requests must send for basic authentication."
+					required:    true
+					type: string: {
+						examples: ["username"]
+					}
+				}
+				password: {
+					description: "The password that requests must send for basic authentication."
+					required:    true
+					type: string: {
+						examples: ["${VECTOR_API_PASSWORD}", "password"]
+					}
+				}
+			}
+		}
 	}
 
 	endpoints: {
@@ -55,6 +176,20 @@ api: {
 				description: """
 					Main endpoint for receiving and processing
 					GraphQL queries.
+
+					Mutations make changes to the running topology:
+					`reloadConfig` reloads the configuration from disk,
+					`applyConfig` replaces it with the given one,
+					`pauseSource` and `resumeSource` pause and resume
+					a source, and `drainSink` stops sending events to a
+					sink. Sinks using a shared disk buffer can't be
+					drained. `replaySink` sends the events in the data
+					files a sink's own disk buffer retained, written to
+					since the given time, to the sink again, while it
+					runs. Each returns whether the change was made,
+					along with any errors and warnings about it. The
+					mutations are only served if `mutations.enabled` is
+					set.
 					"""
 				responses: {
 					"200": {
@@ -66,6 +201,12 @@ api: {
 							there were any errors in your query.
 							"""
 					}
+					"401": {
+						description: """
+							The request didn't send the credentials set with
+							the `auth` option.
+							"""
+					}
 				}
 			}
 		}
@@ -167,6 +167,60 @@ cli: {
 	options: _core_options
 
 	commands: {
+		"buffer": {
+			description: """
+				Inspect a disk buffer without loading it: print its ledger state, and the
+				number of records in, and size of, each of its data files. The records can
+				be dumped, or exported to a file, as newline-delimited JSON, and data files
+				with a corrupted tail can be repaired by truncating them to their last valid
+				record. For buffers configured with `retention`, the records in retained data
+				files can be replayed, so that the sink sends them again.
+				"""
+
+			example: "vector buffer /var/lib/vector/buffer/v2/my_sink --export my_sink.ndjson"
+
+			flags: _default_flags & {
+				"dump": {
+					description: "Print the records in the buffer to stdout as newline-delimited JSON, instead of the buffer summary"
+				}
+				"all": {
+					description: "Include records that have already been read and acknowledged, but not yet deleted, when dumping or exporting records"
+				}
+				"repair": {
+					description: "Truncate data files with a corrupted tail to their last valid record. Vector must not be running with the buffer while it is repaired."
+				}
+			}
+
+			options: {
+				"encryption-key": {
+					description: "Base64-encoded 256-bit key of an encrypted buffer. Required to dump or export its records."
+					type:        "string"
+					env_var:     "VECTOR_BUFFER_ENCRYPTION_KEY"
+				}
+				"encryption-key-file": {
+					description: "Path to a file containing the base64-encoded 256-bit key of an encrypted buffer"
+					type:        "string"
+				}
+				"export": {
+					description: "Write the records in the buffer to the given file as newline-delimited JSON"
+					type:        "string"
+					example:     "my_sink.ndjson"
+				}
+				"replay-since": {
+					description: "Replay the records in the retained data files written to since the given RFC 3339 timestamp, after any records the sink has yet to send. Data files are replayed whole, so records written before the timestamp may be replayed as well. Vector must not be running with the buffer while records are replayed; use the `replaySink` API mutation to replay records while it is."
+					type:        "string"
+					example:     "2022-06-01T12:00:00Z"
+				}
+			}
+
+			args: {
+				path: {
+					description: "Path to the disk buffer directory, such as `<data_dir>/buffer/v2/<sink ID>`"
+					required:    true
+					type:        "string"
+				}
+			}
+		}
 		"graph": {
 			description: """
 				Generate a visual representation of topologies. The output is in the [DOT format](\(urls.dot_format)),
@@ -296,6 +350,24 @@ cli: {
 					description: "Vector GraphQL API server endpoint"
 					type:        "string"
 				}
+				"token": {
+					description: "Bearer token to authenticate with the Vector GraphQL API server. Conflicts with `--user`."
+					type:        "string"
+					env_var:     "VECTOR_API_TOKEN"
+				}
+				"user": {
+					description: "User to authenticate with the Vector GraphQL API server, using basic authentication. Requires `--password`."
+					type:        "string"
+				}
+				"password": {
+					description: "Password to authenticate with the Vector GraphQL API server, using basic authentication. Requires `--user`."
+					type:        "string"
+					env_var:     "VECTOR_API_PASSWORD"
+				}
+				"ca-file": {
+					description: "PEM-encoded CA certificate to trust when connecting to the Vector GraphQL API server over https, in addition to the system's trusted CAs"
+					type:        "string"
+				}
 				"limit": {
 					_short:      "l"
 					description: "Maximum number of events to sample each interval"
@@ -370,6 +442,24 @@ cli: {
 					description: "The URL for the GraphQL endpoint of the running Vector instance"
 					type:        "string"
 				}
+				"token": {
+					description: "Bearer token to authenticate with the Vector GraphQL API server. Conflicts with `--user`."
+					type:        "string"
+					env_var:     "VECTOR_API_TOKEN"
+				}
+				"user": {
+					description: "User to authenticate with the Vector GraphQL API server, using basic authentication. Requires `--password`."
+					type:        "string"
+				}
+				"password": {
+					description: "Password to authenticate with the Vector GraphQL API server, using basic authentication. Requires `--user`."
+					type:        "string"
+					env_var:     "VECTOR_API_PASSWORD"
+				}
+				"ca-file": {
+					description: "PEM-encoded CA certificate to trust when connecting to the Vector GraphQL API server over https, in addition to the system's trusted CAs"
+					type:        "string"
+				}
 			}
 		}
 
@@ -437,6 +527,13 @@ cli: {
 						The same result can be achieved by using `.` as the final expression.
 						"""
 				}
+				"profile": {
+					description: """
+						Record the time spent resolving each expression and function call,
+						and print the most expensive ones once all objects are handled.
+						Only supported by the `ast` runtime.
+						"""
+				}
 			}
 
 			options: {
@@ -467,6 +564,132 @@ cli: {
 				}
 			}
 		}
+
+		"vrl test": {
+			description: """
+				Run VRL programs against input events, and compare the resulting
+				events with the expected output. Every `<name>.vrl` file runs
+				against the event in `<name>.input.json` (an empty object if the
+				file doesn't exist), and the resulting event is compared with the
+				event in `<name>.output.json`. Exits with a non-zero code if any
+				test fails.
+				"""
+
+			flags: _default_flags & {
+				"no-diff": {
+					description: "Don't print the difference between the expected and actual output of failed tests."
+				}
+			}
+
+			options: {
+				"timezone": {
+					_short:      "z"
+					description: "The timezone used to parse dates."
+					type:        "string"
+				}
+			}
+
+			args: {
+				paths: {
+					description: """
+						The VRL programs to test, or the directories to search for
+						`.vrl` files.
+						"""
+					type:     "list"
+					required: true
+				}
+			}
+		}
+
+		"vrl lsp": {
+			description: """
+				Start a VRL language server, communicating over stdio using the
+				Language Server Protocol. It provides compiler diagnostics,
+				completion of function names, parameters and variables, hover
+				documentation for functions, and go-to-definition for variables
+				and user-defined functions. Point your editor's language client
+				at `vector vrl lsp` for `.vrl` files.
+				"""
+
+			flags: _default_flags & {
+				"stdio": {
+					description: "Communicate over stdio. This is the only supported transport, the flag is accepted for editors that always pass it."
+				}
+			}
+		}
+
+		"vrl fmt": {
+			description: """
+				Format VRL programs into their canonical form, keeping comments.
+				Vector configuration files (`.toml`, `.yaml` and `.yml`) are
+				formatted too, by reformatting the VRL programs embedded in
+				their multi-line `source` strings. Without any paths, a program
+				is read from stdin and the formatted program is written to
+				stdout.
+				"""
+
+			flags: _default_flags & {
+				"check": {
+					description: "Don't write the formatted sources, but print the changes formatting would make, and exit with an error if any source isn't formatted."
+				}
+			}
+
+			args: {
+				paths: {
+					description: """
+						The VRL programs or Vector configuration files to
+						format, or the directories to search for `.vrl` files.
+						"""
+					type:     "list"
+					required: false
+				}
+			}
+		}
+
+		"vrl debug": {
+			description: """
+				Step through a VRL program run against input events. The program
+				pauses at its first statement, or at the first breakpoint if any
+				is set, after which it can be resumed one statement at a time,
+				stepping into or over blocks and user-defined functions. While
+				paused, the variables and the event can be inspected, and every
+				`if` statement reports the branch it takes. Type `help` at the
+				prompt for the list of commands.
+				"""
+
+			flags: _default_flags
+
+			options: {
+				"input": {
+					_short: "i"
+					description: """
+						File containing the event object(s) to run the program against,
+						one JSON event per line. An empty object is used if no file is given.
+						"""
+					type: "string"
+				}
+				"break": {
+					_short: "b"
+					description: """
+						A line of the program to pause at. Can be given multiple times.
+						"""
+					type: "list"
+				}
+				"timezone": {
+					_short:      "z"
+					description: "The timezone used to parse dates."
+					type:        "string"
+				}
+			}
+
+			args: {
+				program: {
+					description: "The file containing the VRL program to debug."
+					type:        "string"
+					required:    true
+				}
+			}
+		}
 	}
 
 	env_vars: {
@@ -74,9 +74,70 @@ components: sinks: [Name=string]: {
 			type: object: {
 				examples: []
 				options: {
+					compression: {
+						common: false
+						description: """
+							The compression applied to events before they are written to the disk buffer. Compression
+							reduces the disk space and I/O used by the buffer, at the cost of CPU. `max_size` applies to
+							the compressed size of the buffer.
+
+							Changing the compression of an existing buffer migrates the events already in the buffer
+							to the new compression when Vector starts.
+							"""
+						required:      false
+						relevant_when: "type = \"disk\""
+						type: string: {
+							default: "none"
+							enum: {
+								none: "Events are stored uncompressed."
+								zstd: "Events are compressed with [Zstandard](\(urls.zstd))."
+							}
+						}
+					}
+					encryption: {
+						common: false
+						description: """
+							Encrypts events at rest in the disk buffer with AES-256-GCM. Events are compressed before
+							being encrypted. Exactly one of `key` or `key_file` must be set.
+
+							Vector refuses to start if an existing encrypted buffer is opened with a different key, or
+							without any key. Enabling encryption for an existing unencrypted buffer migrates the events
+							already in the buffer when Vector starts.
+							"""
+						required:      false
+						relevant_when: "type = \"disk\""
+						type: object: {
+							examples: []
+							options: {
+								key: {
+									common: true
+									description: """
+										The encryption key, as 32 bytes encoded with base64. Use a
+										[secret](\(urls.vector_configuration)/#secret) rather than writing the key in the
+										configuration in plain text.
+										"""
+									required: false
+									type: string: {
+										examples: ["SECRET[backend.buffer_key]"]
+									}
+								}
+								key_file: {
+									common:      true
+									description: "The path of a file holding the encryption key, as 32 bytes encoded with base64."
+									required:    false
+									type: string: {
+										examples: ["/etc/vector/buffer.key"]
+									}
+								}
+							}
+						}
+					}
 					max_events: {
 						common:        true
-						description:   "The maximum number of [events](\(urls.vector_data_model)) allowed in the buffer."
+						description: """
+							The maximum number of [events](\(urls.vector_data_model)) allowed in the buffer. Defaults to 500
+							unless `max_size` is set, in which case the buffer is only limited by size.
+							"""
 						required:      false
 						relevant_when: "type = \"memory\""
 						type: uint: {
@@ -86,18 +147,78 @@ components: sinks: [Name=string]: {
 					}
 					max_size: {
 						description: """
-							The maximum size of the buffer on the disk. Must be at least 128 megabytes (134217728 bytes).
+							The maximum size of the buffer, in bytes.
+
+							For disk buffers, this is the size of the buffer on the disk, and is required. It must be at
+							least 128 megabytes (134217728 bytes). Note that during normal disk buffer operation, the
+							disk buffer can create one additional 128 megabyte block so the minimum disk space required
+							is actually 256 megabytes.
 
-							Note that during normal disk buffer operation, the disk buffer can create one additional 128
-							megabyte block so the minimum disk space required is actually 256 megabytes.
+							For memory buffers, this is the estimated size of the events held in memory. It can be set
+							instead of, or alongside, `max_events`, in which case both limits apply.
 							"""
-						required:      true
-						relevant_when: "type = \"disk\""
+						required:      false
+						relevant_when: "type = \"disk\" or type = \"memory\""
 						type: uint: {
 							examples: [104900000]
 							unit: "bytes"
 						}
 					}
+					retention: {
+						common: false
+						description: """
+							Keeps data files for a while after all of their events have been acknowledged, instead of
+							deleting them right away, so that their events can be replayed with
+							`vector buffer <path> --replay-since <timestamp>`, or with the `replaySink`
+							API mutation while Vector is running. At least one of `max_age_secs` or
+							`max_size` must be set. Retained data files don't count towards `max_size` of the buffer.
+
+							Retained data files are kept when the compression or encryption of the buffer is changed,
+							and are still replayed in the format they were written in.
+							"""
+						required:      false
+						relevant_when: "type = \"disk\""
+						type: object: {
+							examples: []
+							options: {
+								max_age_secs: {
+									common:      true
+									description: "The maximum time, since it was last written to, a data file is retained for."
+									required:    false
+									type: uint: {
+										examples: [86400]
+										unit: "seconds"
+									}
+								}
+								max_size: {
+									common:      true
+									description: "The maximum total size of the retained data files. The oldest ones are deleted first."
+									required:    false
+									type: uint: {
+										examples: [1073741824]
+										unit: "bytes"
+									}
+								}
+							}
+						}
+					}
+					shared: {
+						common: false
+						description: """
+							The name of a disk buffer shared with other sinks. Sinks configured with the same name
+							write each event to the buffer once, and then read and acknowledge it on their own, so a
+							slow sink doesn't hold up the others beyond the size of the buffer. Data files are only
+							deleted once every sink sharing the buffer has acknowledged all of their events.
+
+							Sinks sharing a buffer must have the same inputs and the same buffer configuration, and
+							the disk buffer must be their only buffer stage.
+							"""
+						required:      false
+						relevant_when: "type = \"disk\""
+						type: string: {
+							examples: ["logs"]
+						}
+					}
 					type: {
 						common:      true
 						description: "The buffer's type and storage mechanism."
@@ -270,6 +391,36 @@ components: sinks: [Name=string]: {
 			}
 		}
 
+		dead_letter: {
+			common: false
+			description: """
+				Configures the dead-letter output of the sink. When enabled, events that the sink permanently fails to deliver, such as events rejected by the destination, are sent to the `<sink_id>.dead_letter` output, which can be used as an input of transforms and other sinks. Log and trace events are annotated with a `dead_letter` object holding the `component_id` of the sink, the `error`, and the `status_code` returned by the destination, if any. Metric events get `dead_letter_component_id`, `dead_letter_error`, and `dead_letter_status_code` tags instead. When the destination reports the outcome of each event of a request, as with the bulk responses of Elasticsearch, only the events that failed are sent to the dead-letter output.
+				"""
+			required: false
+			type: object: {
+				examples: []
+				options: {
+					enabled: {
+						common:      true
+						description: "Enables the dead-letter output of the sink."
+						required:    false
+						type: bool: default: false
+					}
+					max_pending_events: {
+						common: false
+						description: """
+							The maximum number of events sent to the sink that a copy is held of, until the sink either delivers them or sends them to the dead-letter output. Once reached, the sink's input waits for the sink to finish with some of them, which applies backpressure. Raise it for sinks that send large batches or many concurrent requests, so that the limit doesn't hold up their requests.
+							"""
+						required: false
+						type: uint: {
+							default: 10000
+							unit:    "events"
+						}
+					}
+				}
+			}
+		}
+
 		if features.healthcheck != _|_ {
 			if features.healthcheck.enabled {
 				healthcheck: {
@@ -667,6 +818,9 @@ components: sinks: [Name=string]: {
 		utilization:                          components.sources.internal_metrics.output.metrics.utilization
 		buffer_byte_size:                     components.sources.internal_metrics.output.metrics.buffer_byte_size
 		buffer_events:                        components.sources.internal_metrics.output.metrics.buffer_events
+		buffer_logical_byte_size:             components.sources.internal_metrics.output.metrics.buffer_logical_byte_size
+		buffer_received_logical_bytes_total:  components.sources.internal_metrics.output.metrics.buffer_received_logical_bytes_total
+		buffer_sent_logical_bytes_total:      components.sources.internal_metrics.output.metrics.buffer_sent_logical_bytes_total
 		buffer_received_events_total:         components.sources.internal_metrics.output.metrics.buffer_received_events_total
 		buffer_received_event_bytes_total:    components.sources.internal_metrics.output.metrics.buffer_received_event_bytes_total
 		buffer_sent_events_total:             components.sources.internal_metrics.output.metrics.buffer_sent_events_total
@@ -475,6 +475,24 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		buffer_logical_byte_size: {
+			description:       "The number of bytes currently in the buffer, before compression. Only reported by disk buffers."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		buffer_received_logical_bytes_total: {
+			description:       "The number of bytes received by this buffer, before compression. Only reported by disk buffers."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		buffer_sent_logical_bytes_total: {
+			description:       "The number of bytes sent by this buffer, before compression. Only reported by disk buffers."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		buffer_discarded_events_total: {
 			description:       "The number of events dropped by this non-blocking buffer."
 			type:              "counter"
@@ -1093,6 +1111,24 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		vrl_expression_duration_seconds: {
+			description: """
+				The time spent resolving an expression of a VRL program, per event. Only
+				reported by `remap` transforms with `profile` enabled.
+				"""
+			type:              "histogram"
+			default_namespace: "vector"
+			tags:              _vrl_expression_tags
+		}
+		vrl_expression_invocations_total: {
+			description: """
+				The total number of times an expression of a VRL program was resolved. Only
+				reported by `remap` transforms with `profile` enabled.
+				"""
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _vrl_expression_tags
+		}
 
 		// Windows metrics
 		windows_service_does_not_exist_total: {
@@ -1255,6 +1291,16 @@ components: sources: internal_metrics: {
 			description: "The path that produced the error."
 			required:    true
 		}
+		_vrl_expression_tags: _component_tags & {
+			span: {
+				description: "The byte range of the expression in the program, such as `0..12`."
+				required:    true
+			}
+			kind: {
+				description: "The kind of expression, either `expression`, or `function call` followed by the name of the function."
+				required:    true
+			}
+		}
 		_reason: {
 			description: "The type of the error"
 			required:    true
@@ -104,6 +104,17 @@ components: transforms: "remap": {
 				"""
 			type: bool: default: false
 		}
+		profile: {
+			common:   false
+			required: false
+			description: """
+				Record the time spent resolving each expression and function call of the
+				program, and report it as the `vrl_expression_invocations_total` and
+				`vrl_expression_duration_seconds` internal metrics. Profiling adds overhead
+				to every event, and is only supported by the `ast` runtime.
+				"""
+			type: bool: default: false
+		}
 	}
 
 	input: {
@@ -116,7 +127,7 @@ components: transforms: "remap": {
 			set:          true
 			summary:      true
 		}
-		traces: false
+		traces: true
 	}
 
 	examples: [
@@ -166,12 +177,45 @@ components: transforms: "remap": {
 				`timestamp` | Read/write/delete | You assign only a valid [VRL timestamp](\(urls.vrl_expressions)/#timestamp) value, not a [VRL string](\(urls.vrl_expressions)/#string).
 				`namespace` | Read/write/delete |
 				`tags` | Read/write/delete | The `tags` field must be a [VRL object](\(urls.vrl_expressions)/#object) in which all keys and values are strings.
+				`counter`, `gauge` | Read/write | The `value` field must be a number.
+				`set` | Read/write | The `values` field must be an array of strings.
+				`distribution` | Read/write | The `samples` field is an array of `{value, rate}` objects, and `statistic` is either `histogram` or `summary`.
+				`aggregated_histogram` | Read/write | The `buckets` field is an array of `{upper_limit, count}` objects, next to the `count` and `sum` fields.
+				`aggregated_summary` | Read/write | The `quantiles` field is an array of `{quantile, value}` objects, next to the `count` and `sum` fields.
+
+				The value of a metric lives in the field named after its type, such as `.counter.value`. Only the
+				field matching the metric's type is present. Assigning to another value field, such as
+				`.gauge = {"value": 1.0}`, changes the type of the metric. Fields within the value can be changed or
+				deleted, but the value as a whole can't be deleted. Each change is validated right away, so an
+				assignment or deletion that leaves the value invalid, such as `del(.counter.value)`, fails at runtime.
+
+				The whole metric can be replaced by assigning an object to the root, such as with
+				`. = new_metric!("requests", "absolute", "counter", 1)`. Incremental metrics can be converted to
+				absolute ones using `to_absolute_metric`.
 
 				It's important to note that if you try to perform a disallowed action, such as deleting the `type`
 				field using `del(.type)`, Vector doesn't abort the VRL program or throw an error. Instead, it ignores
 				the disallowed action.
 				"""
 		}
+		trace_events: {
+			title: "Trace Events"
+			body: #"""
+				Trace events are handled like log events: the whole trace is available as an object, with its spans
+				held as objects in the `.spans` array. Assignments that would break that shape, such as assigning a
+				string to `.spans` or to the root of the event, fail at runtime.
+
+				Spans can be changed with the iteration functions, for example to drop health-check spans and
+				rename a service:
+
+				```coffee
+				.spans = filter(array!(.spans)) -> |_index, span| { span.resource != "GET /health" }
+				.service = "web"
+				```
+
+				To only handle traces in a `route` or `filter` transform, use the `is_trace` condition type.
+				"""#
+		}
 		lazy_event_mutation: {
 			title: "Lazy Event Mutation"
 			body:  #"""
@@ -226,6 +270,8 @@ components: transforms: "remap": {
 	]
 
 	telemetry: metrics: {
-		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+		processing_errors_total:          components.sources.internal_metrics.output.metrics.processing_errors_total
+		vrl_expression_duration_seconds:  components.sources.internal_metrics.output.metrics.vrl_expression_duration_seconds
+		vrl_expression_invocations_total: components.sources.internal_metrics.output.metrics.vrl_expression_invocations_total
 	}
 }
@@ -0,0 +1,101 @@
+package metadata
+
+remap: expressions: loop: {
+	title: "Loop"
+	description: """
+		A _loop_ expression executes a block repeatedly. A `for` loop executes the block once for each element of an
+		array, or each field of an object. A `walk` loop executes the block once for each value nested within an array
+		or object, at any depth. A `while` loop executes the block for as long as its predicate evaluates to `true`.
+
+		Within the block, `break` stops the loop, and `continue` skips to the next iteration. Both must be used as a
+		statement of the loop block, or of a block or `if` expression nested within it.
+		"""
+	return: """
+		Loops always return `null`.
+		"""
+
+	grammar: {
+		source: """
+			("for" | "walk") ~ (key ~ ",")? ~ value ~ "in" ~ expression ~ block | "while" ~ predicate ~ block
+			"""
+		definitions: {
+			key: {
+				description: """
+					The variable bound to the index of the array element, or the name of the object field. For a
+					`walk` loop, this is the path to the nested value, as an array of field names and indices. Use
+					`_` to ignore the key.
+					"""
+			}
+			value: {
+				description: """
+					The variable bound to the current value. Use `_` to ignore the value. The loop variables are
+					only visible within the block, and any variables they shadow are restored once the loop ends.
+					"""
+			}
+			expression: {
+				description: """
+					The `expression` _must_ resolve to an array or an object. If it doesn't, a compile-time error is
+					raised. The expression is evaluated once, before the first iteration.
+					"""
+			}
+			predicate: {
+				description: """
+					The `predicate` _must_ be an expression that resolves to a Boolean. To guarantee programs
+					terminate, a `while` loop raises a runtime error once it exceeds 10,000 iterations. A `while`
+					loop is therefore always fallible, and its error must be handled by assigning the loop, as in
+					`_, err = while predicate { ... }`.
+					"""
+			}
+		}
+	}
+
+	examples: [
+		{
+			title: "For loop over an array"
+			source: #"""
+				sum = 0
+				for n in [1, 2, 3] {
+					sum = sum + n
+				}
+				sum
+				"""#
+			return: 6
+		},
+		{
+			title: "For loop over an object"
+			source: #"""
+				keys = []
+				for key, _ in { "a": 1, "b": 2 } {
+					keys = push(keys, key)
+				}
+				keys
+				"""#
+			return: ["a", "b"]
+		},
+		{
+			title: "Walk loop"
+			source: #"""
+				paths = []
+				walk path, _ in { "a": { "b": true } } {
+					paths = push(paths, join!(path, "."))
+				}
+				paths
+				"""#
+			return: ["a", "a.b"]
+		},
+		{
+			title: "While loop with break"
+			source: #"""
+				n = 1
+				_, err = while true {
+					if n >= 8 {
+						break
+					}
+					n = n * 2
+				}
+				n
+				"""#
+			return: 8
+		},
+	]
+}
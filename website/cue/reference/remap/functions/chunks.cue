@@ -0,0 +1,42 @@
+package metadata
+
+remap: functions: chunks: {
+	category: "Array"
+	description: """
+		Splits the `value` array into arrays of at most `chunk_size` items.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array to split."
+			required:    true
+			type: ["array"]
+		},
+		{
+			name:        "chunk_size"
+			description: "The maximum number of items in each chunk."
+			required:    true
+			type: ["integer"]
+		},
+	]
+	internal_failure_reasons: [
+		"`chunk_size` is less than 1.",
+	]
+	return: {
+		types: ["array"]
+		rules: [
+			"The last chunk contains the remaining items, and can be shorter than `chunk_size`.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Split an array into chunks"
+			source: """
+				chunks!([1, 2, 3, 4, 5], 2)
+				"""
+			return: [[1, 2], [3, 4], [5]]
+		},
+	]
+}
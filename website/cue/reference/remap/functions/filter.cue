@@ -0,0 +1,50 @@
+package metadata
+
+remap: functions: filter: {
+	category: "Enumerate"
+	description: #"""
+		Filter the items of a collection, keeping only the items for which the
+		closure returns `true`.
+
+		The closure receives the key (for objects) or index (for arrays) and
+		the value of each item, and must return a boolean.
+
+		The same scoping rules apply to closure blocks as they do for
+		regular blocks, meaning, any variable defined in parent scopes
+		are accessible, and mutations to those variables are preserved,
+		but any new variables instantiated in the closure block are
+		unavailable outside of the block.
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object or array to filter."
+			required:    true
+			type: ["array", "object"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array", "object"]
+		rules: [
+			"Returns a collection of the same type as `value`.",
+		]
+	}
+	examples: [
+		{
+			title: "Filter array items"
+			source: #"""
+				filter([1, 2, 3, 4]) -> |_index, value| { value > 2 }
+				"""#
+			return: [3, 4]
+		},
+		{
+			title: "Remove null fields"
+			source: #"""
+				filter({ "a": 1, "b": null, "c": 3 }) -> |_key, value| { value != null }
+				"""#
+			return: {"a": 1, "c": 3}
+		},
+	]
+}
@@ -0,0 +1,46 @@
+package metadata
+
+remap: functions: flat_map: {
+	category: "Enumerate"
+	description: #"""
+		Map each item of an array to an array, and concatenate the results.
+
+		The closure receives the index and the value of each item, and must
+		return an array.
+
+		The same scoping rules apply to closure blocks as they do for
+		regular blocks, meaning, any variable defined in parent scopes
+		are accessible, and mutations to those variables are preserved,
+		but any new variables instantiated in the closure block are
+		unavailable outside of the block.
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array to iterate."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array"]
+	}
+	examples: [
+		{
+			title: "Duplicate items"
+			source: #"""
+				flat_map(["a", "b"]) -> |_index, value| { [value, value] }
+				"""#
+			return: ["a", "a", "b", "b"]
+		},
+		{
+			title: "Split strings"
+			source: #"""
+				flat_map(["a,b", "c"]) -> |_index, value| { split(value, ",") }
+				"""#
+			return: ["a", "b", "c"]
+		},
+	]
+}
@@ -19,6 +19,16 @@ remap: functions: format_timestamp: {
 			required:    true
 			type: ["string"]
 		},
+		{
+			name:        "timezone"
+			description: """
+				The [timezone](\(urls.tz_time_zones)) to format the timestamp in, such as `Europe/Paris`, or
+				`local` for the system's timezone. The timezone must be a string literal.
+				"""
+			required: false
+			default:  "UTC"
+			type: ["string"]
+		},
 	]
 	internal_failure_reasons: []
 	return: types: ["string"]
@@ -38,5 +48,12 @@ remap: functions: format_timestamp: {
 				"""#
 			return: "21-Oct-2020 16:00"
 		},
+		{
+			title: "Format a timestamp in a timezone"
+			source: #"""
+				format_timestamp!(t'2020-10-21T16:00:00Z', format: "%+", timezone: "Asia/Tokyo")
+				"""#
+			return: "2020-10-22T01:00:00+09:00"
+		},
 	]
 }
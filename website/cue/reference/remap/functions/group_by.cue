@@ -0,0 +1,46 @@
+package metadata
+
+remap: functions: group_by: {
+	category: "Enumerate"
+	description: #"""
+		Group the items of an array by the string the closure returns for
+		each item.
+
+		The closure receives the index and the value of each item. Items are
+		kept in their original order within each group.
+
+		The same scoping rules apply to closure blocks as they do for
+		regular blocks, meaning, any variable defined in parent scopes
+		are accessible, and mutations to those variables are preserved,
+		but any new variables instantiated in the closure block are
+		unavailable outside of the block.
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array to group."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["object"]
+		rules: [
+			"Returns an object mapping each group key to an array of its items.",
+		]
+	}
+	examples: [
+		{
+			title: "Group by field"
+			source: #"""
+				group_by([{ "level": "info", "id": 1 }, { "level": "error", "id": 2 }, { "level": "info", "id": 3 }]) -> |_index, value| { value.level }
+				"""#
+			return: {
+				error: [{"id": 2, "level": "error"}]
+				info: [{"id": 1, "level": "info"}, {"id": 3, "level": "info"}]
+			}
+		},
+	]
+}
@@ -0,0 +1,65 @@
+package metadata
+
+remap: functions: hmac: {
+	category:    "Cryptography"
+	description: """
+		Calculates a [HMAC](\(urls.hmac)) of the `value` using the given `key`.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to calculate the HMAC for."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "key"
+			description: "The secret key to sign the `value` with."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "algorithm"
+			description: "The hashing algorithm to use."
+			enum: {
+				"SHA-1":   "SHA-1 algorithm"
+				"SHA-256": "SHA-256 algorithm"
+				"SHA-512": "SHA-512 algorithm"
+			}
+			required: false
+			default:  "SHA-256"
+			type: ["string"]
+		},
+		{
+			name:        "encoding"
+			description: "The encoding of the returned HMAC."
+			enum: {
+				hex:    "Lowercase hexadecimal encoding"
+				base64: "Standard Base64 encoding, with padding"
+			}
+			required: false
+			default:  "hex"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Calculate HMAC"
+			source: #"""
+				hmac("foo", "bar")
+				"""#
+			return: "147933218aaabc0b8b10a2b3a5c34684c8d94341bcf10a4736dc7270f7741851"
+		},
+		{
+			title: "Calculate HMAC with a custom algorithm and encoding"
+			source: #"""
+				hmac("foo", "bar", algorithm: "SHA-1", encoding: "base64")
+				"""#
+			return: "hdFVxV7ShqMAvRzxJN4I2H6RTzo="
+		},
+	]
+}
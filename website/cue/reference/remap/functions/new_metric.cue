@@ -0,0 +1,83 @@
+package metadata
+
+remap: functions: new_metric: {
+	category: "Event"
+	description: """
+		Builds a new metric, in the same shape as metric events are represented in VRL.
+
+		The result can be assigned to the root of a metric event (`. = new_metric!(...)`) to replace the
+		event with the new metric.
+		"""
+
+	arguments: [
+		{
+			name:        "name"
+			description: "The name of the metric."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "kind"
+			description: "The kind of the metric."
+			required:    true
+			enum: {
+				absolute:    "The value is the current total."
+				incremental: "The value is a change since the previous metric."
+			}
+			type: ["string"]
+		},
+		{
+			name:        "type"
+			description: "The type of the metric."
+			required:    true
+			enum: {
+				counter:              "A counter. The value is either a number or an object with a `value` field."
+				gauge:                "A gauge. The value is either a number or an object with a `value` field."
+				set:                  "A set. The value is either an array of strings or an object with a `values` field."
+				distribution:         "A distribution. The value is an object with `samples` and `statistic` fields."
+				aggregated_histogram: "An aggregated histogram. The value is an object with `buckets`, `count` and `sum` fields."
+				aggregated_summary:   "An aggregated summary. The value is an object with `quantiles`, `count` and `sum` fields."
+			}
+			type: ["string"]
+		},
+		{
+			name:        "value"
+			description: "The value of the metric, matching the given `type`."
+			required:    true
+			type: ["any"]
+		},
+		{
+			name:        "namespace"
+			description: "The namespace of the metric."
+			required:    false
+			type: ["string", "null"]
+		},
+		{
+			name:        "tags"
+			description: "The tags of the metric. All keys and values must be strings."
+			required:    false
+			type: ["object", "null"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` doesn't match the shape required by `type`.",
+		"`tags` contains a non-string value.",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Create a counter"
+			source: #"""
+				new_metric!("requests", "incremental", "counter", 1, tags: {"host": "localhost"})
+				"""#
+			return: {
+				name: "requests"
+				kind: "incremental"
+				type: "counter"
+				tags: host: "localhost"
+				counter: value: 1.0
+			}
+		},
+	]
+}
@@ -0,0 +1,79 @@
+package metadata
+
+remap: functions: parse_cef: {
+	category:    "Parse"
+	description: """
+		Parses the `value` in the Common Event Format (CEF). Any text in front of the `CEF:` header,
+		such as a syslog prefix, is ignored.
+
+		Pipes and backslashes in the header fields are unescaped, as are the `\\=`, `\\\\`, `\\n`
+		and `\\r` escape sequences in the extension values.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to parse."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "translate_custom_fields"
+			description: """
+				Rename custom fields, such as `cs1`, to the value of their label field, such as
+				`cs1Label`. The label fields are removed.
+				"""
+			required:    false
+			default:     false
+			type: ["boolean"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` doesn't contain a `CEF:` header.",
+		"`value` doesn't contain all header fields.",
+		"The CEF version isn't an integer.",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse CEF"
+			source: #"""
+				parse_cef!("CEF:0|Security|threatmanager|1.0|100|worm successfully stopped|10|src=10.0.0.1 dst=2.1.2.2 spt=1232")
+				"""#
+			return: {
+				cef_version:           0
+				device_vendor:         "Security"
+				device_product:        "threatmanager"
+				device_version:        "1.0"
+				device_event_class_id: "100"
+				name:                  "worm successfully stopped"
+				severity:              "10"
+				extensions: {
+					src: "10.0.0.1"
+					dst: "2.1.2.2"
+					spt: "1232"
+				}
+			}
+		},
+		{
+			title: "Parse CEF with custom fields"
+			source: #"""
+				parse_cef!("CEF:0|Vendor|Product|1.0|login|User logged in|3|suser=alice cs1Label=department cs1=Research and development", translate_custom_fields: true)
+				"""#
+			return: {
+				cef_version:           0
+				device_vendor:         "Vendor"
+				device_product:        "Product"
+				device_version:        "1.0"
+				device_event_class_id: "login"
+				name:                  "User logged in"
+				severity:              "3"
+				extensions: {
+					department: "Research and development"
+					suser:      "alice"
+				}
+			}
+		},
+	]
+}
@@ -0,0 +1,67 @@
+package metadata
+
+remap: functions: parse_leef: {
+	category:    "Parse"
+	description: """
+		Parses the `value` in the Log Event Extended Format (LEEF), versions 1.0 and 2.0. Any text
+		in front of the `LEEF:` header, such as a syslog prefix, is ignored.
+
+		Attributes are separated by a tab, unless a LEEF 2.0 header specifies another delimiter,
+		either as a character or as its hex code, such as `x5E` or `0x5E`.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to parse."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` doesn't contain a `LEEF:` header.",
+		"The LEEF version isn't supported.",
+		"`value` doesn't contain all header fields.",
+		"The attribute delimiter is invalid.",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse LEEF 1.0"
+			source: #"""
+				parse_leef!("LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|src=10.50.1.1\tdst=2.10.20.20\tsev=5")
+				"""#
+			return: {
+				leef_version:    "1.0"
+				vendor:          "Microsoft"
+				product_name:    "MSExchange"
+				product_version: "4.0 SP1"
+				event_id:        "15345"
+				attributes: {
+					src: "10.50.1.1"
+					dst: "2.10.20.20"
+					sev: "5"
+				}
+			}
+		},
+		{
+			title: "Parse LEEF 2.0 with a custom delimiter"
+			source: #"""
+				parse_leef!("LEEF:2.0|Lancope|StealthWatch|1.0|41|^|src=10.0.1.8^dst=10.0.0.5^sev=5")
+				"""#
+			return: {
+				leef_version:    "2.0"
+				vendor:          "Lancope"
+				product_name:    "StealthWatch"
+				product_version: "1.0"
+				event_id:        "41"
+				attributes: {
+					src: "10.0.1.8"
+					dst: "10.0.0.5"
+					sev: "5"
+				}
+			}
+		},
+	]
+}
@@ -0,0 +1,48 @@
+package metadata
+
+remap: functions: parse_windows_event_xml: {
+	category:    "Parse"
+	description: """
+		Parses the `value` as a Windows event rendered as XML.
+
+		The fields of the `System` section are converted to `snake_case`. Fields without attributes
+		are parsed to their value, fields with attributes to an object of those attributes, and
+		`TimeCreated` to a timestamp.
+
+		Named `Data` elements of the `EventData` section are keyed by their name, and unnamed `Data`
+		elements are collected in the `data` array. Other sections, such as `UserData`, are parsed
+		the same as `parse_xml` does.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string representation of the Windows event."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't valid XML.",
+		"The root element of `value` isn't `Event`.",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse Windows event"
+			source: #"""
+				parse_windows_event_xml!(s'<Event xmlns="http://schemas.microsoft.com/win/2004/08/events/event"><System><Provider Name="Microsoft-Windows-Security-Auditing"/><EventID>4624</EventID><TimeCreated SystemTime="2022-08-02T12:34:56.789Z"/><Channel>Security</Channel></System><EventData><Data Name="TargetUserName">alice</Data></EventData></Event>')
+				"""#
+			return: {
+				system: {
+					provider: name: "Microsoft-Windows-Security-Auditing"
+					event_id:     4624
+					time_created: "2022-08-02T12:34:56.789Z"
+					channel:      "Security"
+				}
+				event_data: TargetUserName: "alice"
+			}
+		},
+	]
+}
@@ -0,0 +1,56 @@
+package metadata
+
+remap: functions: reduce: {
+	category: "Enumerate"
+	description: #"""
+		Reduce the values of a collection into a single value.
+
+		The closure receives the accumulator and the value of each item, and
+		returns the new accumulator. The accumulator starts out as `initial`,
+		and the final accumulator is returned.
+
+		The same scoping rules apply to closure blocks as they do for
+		regular blocks, meaning, any variable defined in parent scopes
+		are accessible, and mutations to those variables are preserved,
+		but any new variables instantiated in the closure block are
+		unavailable outside of the block.
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object or array to reduce."
+			required:    true
+			type: ["array", "object"]
+		},
+		{
+			name:        "initial"
+			description: "The initial value of the accumulator."
+			required:    true
+			type: ["any"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["any"]
+		rules: [
+			"Returns `initial` if `value` is empty.",
+		]
+	}
+	examples: [
+		{
+			title: "Sum values"
+			source: #"""
+				reduce([1, 2, 3], 0) -> |sum, value| { int!(sum) + value }
+				"""#
+			return: 6
+		},
+		{
+			title: "Concatenate object values"
+			source: #"""
+				reduce({ "a": "foo", "b": "bar" }, "") -> |result, value| { string!(result) + value }
+				"""#
+			return: "foobar"
+		},
+	]
+}
@@ -0,0 +1,34 @@
+package metadata
+
+remap: functions: seahash: {
+	category:    "Cryptography"
+	description: """
+		Calculates a non-cryptographic [SeaHash](\(urls.seahash)) hash of the `value`.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to calculate the hash for."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["integer"]
+		rules: [
+			"The 64-bit hash is returned as a signed integer, so it can be negative.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Calculate seahash hash"
+			source: #"""
+				seahash("foobar")
+				"""#
+			return: 5348458858952426560
+		},
+	]
+}
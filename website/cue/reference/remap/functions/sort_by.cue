@@ -0,0 +1,50 @@
+package metadata
+
+remap: functions: sort_by: {
+	category: "Enumerate"
+	description: #"""
+		Sort the items of an array by the key the closure returns for each
+		item.
+
+		The closure receives the index and the value of each item, and must
+		return a boolean, integer, float, string or timestamp. Keys of
+		different types are ordered by type: booleans first, then numbers,
+		strings and timestamps. The sort is stable, so items with equal keys
+		keep their original order.
+
+		The same scoping rules apply to closure blocks as they do for
+		regular blocks, meaning, any variable defined in parent scopes
+		are accessible, and mutations to those variables are preserved,
+		but any new variables instantiated in the closure block are
+		unavailable outside of the block.
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array to sort."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array"]
+	}
+	examples: [
+		{
+			title: "Sort by field"
+			source: #"""
+				sort_by([{ "name": "b", "age": 30 }, { "name": "a", "age": 20 }]) -> |_index, value| { value.age }
+				"""#
+			return: [{"age": 20, "name": "a"}, {"age": 30, "name": "b"}]
+		},
+		{
+			title: "Sort by length"
+			source: #"""
+				sort_by(["ccc", "a", "bb"]) -> |_index, value| { strlen(value) }
+				"""#
+			return: ["a", "bb", "ccc"]
+		},
+	]
+}
@@ -0,0 +1,51 @@
+package metadata
+
+remap: functions: timestamp_add: {
+	category:    "Timestamp"
+	description: """
+		Adds the `duration` to the `value` timestamp. Negative durations move the timestamp back in time.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The timestamp to adjust."
+			required:    true
+			type: ["timestamp"]
+		},
+		{
+			name:        "duration"
+			description: """
+				The duration added to the timestamp.
+
+				Integers and floats are a number of seconds. Strings are a sequence of numbers and units, such as
+				`1h30m`, `-250ms` or `1.5d`, using the units `ns`, `us`, `ms`, `cs`, `ds`, `s`, `m`, `h` and `d`.
+				Units aren't case-sensitive.
+				"""
+			required: true
+			type: ["string", "integer", "float"]
+		},
+	]
+	internal_failure_reasons: [
+		"`duration` is not a valid duration.",
+		"The resulting timestamp is out of range.",
+	]
+	return: types: ["timestamp"]
+
+	examples: [
+		{
+			title: "Add a duration"
+			source: #"""
+				timestamp_add!(t'2021-02-10T23:32:00Z', "1h30m")
+				"""#
+			return: "2021-02-11T01:02:00Z"
+		},
+		{
+			title: "Add seconds"
+			source: #"""
+				timestamp_add!(t'2021-02-10T23:32:00Z', 90)
+				"""#
+			return: "2021-02-10T23:33:30Z"
+		},
+	]
+}
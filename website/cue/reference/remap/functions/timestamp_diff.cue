@@ -0,0 +1,62 @@
+package metadata
+
+remap: functions: timestamp_diff: {
+	category:    "Timestamp"
+	description: """
+		Computes the duration between the `start` and `end` timestamps, expressed in the given `unit`.
+		The result is negative if `end` is before `start`.
+		"""
+
+	arguments: [
+		{
+			name:        "end"
+			description: "The timestamp at the end of the duration."
+			required:    true
+			type: ["timestamp"]
+		},
+		{
+			name:        "start"
+			description: "The timestamp at the start of the duration."
+			required:    true
+			type: ["timestamp"]
+		},
+		{
+			name:        "unit"
+			description: "The unit to express the duration in."
+			required:    false
+			enum: {
+				ns:   "Nanoseconds"
+				us:   "Microseconds"
+				"µs": "Microseconds"
+				ms:   "Milliseconds"
+				cs:   "Centiseconds"
+				ds:   "Deciseconds"
+				s:    "Seconds"
+				m:    "Minutes"
+				h:    "Hours"
+				d:    "Days"
+			}
+			default: "s"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["float"]
+
+	examples: [
+		{
+			title: "Compute a duration in seconds"
+			source: #"""
+				timestamp_diff(t'2021-02-10T23:32:00Z', t'2021-02-10T23:30:30Z')
+				"""#
+			return: 90.0
+		},
+		{
+			title: "Compute a duration in hours"
+			source: #"""
+				timestamp_diff(t'2021-02-10T23:32:00Z', t'2021-02-11T01:02:00Z', unit: "h")
+				"""#
+			return: -1.5
+		},
+	]
+}
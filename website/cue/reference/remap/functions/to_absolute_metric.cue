@@ -0,0 +1,59 @@
+package metadata
+
+remap: functions: to_absolute_metric: {
+	category: "Event"
+	description: """
+		Converts an incremental metric to an absolute one, by adding its value to the running total of
+		previous metrics in the same series. Absolute metrics are returned unchanged, and are used as the
+		new total for their series.
+
+		Totals are kept per `remap` transform, and a series is identified by the metric's name,
+		namespace and tags. If the type of the metric changes, the total for its series is reset.
+		The total of a series that isn't seen for `ttl_secs` is forgotten, and its next metric starts
+		a new total.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The metric to convert, such as the metric event itself (`.`)."
+			required:    true
+			type: ["object"]
+		},
+		{
+			name: "ttl_secs"
+			description: #"""
+				The number of seconds after which the total of a series that hasn't been seen is
+				forgotten. Must be a positive literal integer.
+				"""#
+			required: false
+			type: ["integer"]
+			default: 3600
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid metric.",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Convert an incremental counter"
+			input: metric: {
+				kind: "incremental"
+				name: "requests"
+				tags: {}
+				counter: value: 1.0
+			}
+			source: #"""
+				. = to_absolute_metric!(.)
+				"""#
+			output: metric: {
+				kind: "absolute"
+				name: "requests"
+				tags: {}
+				counter: value: 1.0
+			}
+		},
+	]
+}
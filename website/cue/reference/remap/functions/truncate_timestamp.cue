@@ -0,0 +1,64 @@
+package metadata
+
+remap: functions: truncate_timestamp: {
+	category:    "Timestamp"
+	description: """
+		Truncates the `value` timestamp to the start of its second, minute, hour or day.
+
+		The timestamp is truncated in UTC, unless a `timezone` is given, in which case it's truncated in the local
+		time of that timezone. For example, truncating to the day in `Europe/Paris` results in the timestamp of
+		midnight in Paris.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The timestamp to truncate."
+			required:    true
+			type: ["timestamp"]
+		},
+		{
+			name:        "unit"
+			description: "The unit to truncate the timestamp to."
+			required:    true
+			enum: {
+				second: "Truncate to the start of the second"
+				minute: "Truncate to the start of the minute"
+				hour:   "Truncate to the start of the hour"
+				day:    "Truncate to the start of the day"
+			}
+			type: ["string"]
+		},
+		{
+			name:        "timezone"
+			description: """
+				The [timezone](\(urls.tz_time_zones)) to truncate the timestamp in, such as `Europe/Paris`, or
+				`local` for the system's timezone. The timezone must be a string literal.
+				"""
+			required: false
+			default:  "UTC"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"The truncated local time doesn't exist in the `timezone`, as it's skipped when clocks are turned forward.",
+	]
+	return: types: ["timestamp"]
+
+	examples: [
+		{
+			title: "Truncate to the hour"
+			source: #"""
+				truncate_timestamp(t'2021-02-10T23:32:10Z', "hour")
+				"""#
+			return: "2021-02-10T23:00:00Z"
+		},
+		{
+			title: "Truncate to the day in a timezone"
+			source: #"""
+				truncate_timestamp!(t'2021-02-10T23:32:10Z', "day", timezone: "Europe/Paris")
+				"""#
+			return: "2021-02-10T23:00:00Z"
+		},
+	]
+}
@@ -0,0 +1,60 @@
+package metadata
+
+remap: functions: validate_json_schema: {
+	category:    "Type"
+	description: """
+		Validates the `value` against the [JSON Schema](\(urls.json_schema)) in the file at `schema_path`,
+		returning the validation errors. An empty array is returned if the `value` conforms to the schema.
+
+		Each error is an object with the `instance_path` of the offending part of the `value`, the
+		`schema_path` of the keyword it violates, and a human-readable `message`.
+		"""
+	notices: [
+		"""
+			The `schema_path` must be a string literal. The schema is read and compiled when the program
+			is compiled, so changes to the schema file take effect when the program is next compiled, for
+			example when Vector reloads its configuration. Programs and `json_schema` conditions using the
+			same schema file share a single compiled copy of it.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The value to validate."
+			required:    true
+			type: ["any"]
+		},
+		{
+			name:        "schema_path"
+			description: "The path of the JSON Schema file to validate the `value` against."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` can't be converted to JSON.",
+	]
+	return: types: ["array"]
+
+	examples: [
+		{
+			title: "Validate a conforming value"
+			source: """
+				validate_json_schema!({ "name": "alice", "age": 42 }, "tests/data/json_schema/user.schema.json")
+				"""
+			return: []
+		},
+		{
+			title: "Validate a non-conforming value"
+			source: """
+				validate_json_schema!({ "name": "alice", "age": "old" }, "tests/data/json_schema/user.schema.json")
+				"""
+			return: [{
+				instance_path: "/age"
+				schema_path:   "/properties/age/type"
+				message:       #""old" is not of type "integer""#
+			}]
+		},
+	]
+}
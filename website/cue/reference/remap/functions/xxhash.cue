@@ -0,0 +1,52 @@
+package metadata
+
+remap: functions: xxhash: {
+	category:    "Cryptography"
+	description: """
+		Calculates a non-cryptographic [xxHash](\(urls.xxhash)) hash of the `value`, using a seed of `0`.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to calculate the hash for."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "variant"
+			description: "The variant of the algorithm to use."
+			enum: {
+				XXH32: "32-bit xxHash algorithm"
+				XXH64: "64-bit xxHash algorithm"
+			}
+			required: false
+			default:  "XXH64"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["integer"]
+		rules: [
+			"64-bit hashes are returned as signed integers, so they can be negative.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Calculate xxhash hash"
+			source: #"""
+				xxhash("foobar")
+				"""#
+			return: -6725556575634347271
+		},
+		{
+			title: "Calculate 32-bit xxhash hash"
+			source: #"""
+				xxhash("foobar", variant: "XXH32")
+				"""#
+			return: 3986901679
+		},
+	]
+}
@@ -0,0 +1,41 @@
+package metadata
+
+remap: functions: zip: {
+	category: "Array"
+	description: """
+		Combines two arrays into an array of pairs, where the first pair
+		contains the first items of both arrays, and so on.
+		"""
+
+	arguments: [
+		{
+			name:        "array_0"
+			description: "The first array."
+			required:    true
+			type: ["array"]
+		},
+		{
+			name:        "array_1"
+			description: "The second array."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array"]
+		rules: [
+			"If the arrays differ in length, the result is as long as the shorter array.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Zip two arrays"
+			source: """
+				zip(["a", "b"], [1, 2])
+				"""
+			return: [["a", 1], ["b", 2]]
+		},
+	]
+}
@@ -228,6 +228,7 @@ urls: {
 	heroku:                                       "https://www.heroku.com"
 	heroku_http_log_drain:                        "https://devcenter.heroku.com/articles/log-drains#https-drains"
 	heroku_start:                                 "https://devcenter.heroku.com/start"
+	hmac:                                         "\(wikipedia)/wiki/HMAC"
 	homebrew:                                     "https://brew.sh/"
 	homebrew_services:                            "\(github)/Homebrew/homebrew-services"
 	honeycomb:                                    "https://honeycomb.io"
@@ -262,6 +263,7 @@ urls: {
 	journalctl:                                   "https://www.freedesktop.org/software/systemd/man/journalctl.html"
 	journald:                                     "https://www.freedesktop.org/software/systemd/man/systemd-journald.service.html"
 	json:                                         "\(wikipedia)/wiki/JSON"
+	json_schema:                                  "https://json-schema.org/"
 	json_types:                                   "\(wikipedia)/wiki/JSON#Data_types_and_syntax"
 	jsonnet:                                      "https://jsonnet.org/"
 	kafka:                                        "https://kafka.apache.org/"
@@ -427,6 +429,7 @@ urls: {
 	rustup:                                       "https://rustup.rs"
 	redis:                                        "https://redis.io"
 	redis_rs:                                     "https://github.com/mitsuhiko/redis-rs"
+	seahash:                                      "https://docs.rs/seahash"
 	sematext:                                     "https://sematext.com"
 	sematext_create_logs_app:                     "https://apps.sematext.com/ui/integrations"
 	sematext_es:                                  "https://sematext.com/docs/logs/index-events-via-elasticsearch-api/"
@@ -624,6 +627,7 @@ urls: {
 	windows_installer:                            "\(wikipedia)/wiki/Windows_Installer"
 	windows_service:                              "https://docs.microsoft.com/en-us/powershell/module/microsoft.powershell.management/new-service"
 	woothee:                                      "https://github.com/woothee/woothee"
+	xxhash:                                       "https://cyan4973.github.io/xxHash/"
 	yaml:                                         "https://yaml.org/"
 	ytt:                                          "https://carvel.dev/ytt/"
 	yum:                                          "\(wikipedia)/wiki/Yum_(software)"